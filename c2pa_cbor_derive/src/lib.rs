@@ -0,0 +1,311 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Derive macros for `c2pa_cbor`
+//!
+//! Use these through `c2pa_cbor` with its `derive` feature rather than
+//! depending on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, Span};
+use quote::{format_ident, quote};
+use syn::{Attribute, Data, DeriveInput, Error, Fields, LitInt, LitStr, parse_macro_input};
+
+/// Derive `Serialize` and `Deserialize` for a type that is always wrapped in
+/// a CBOR tag
+///
+/// The type is written as `#[derive(Serialize, Deserialize)]` would write it,
+/// inside the tag given by `#[cbor(tag = N)]`, and decoding fails unless the
+/// input carries exactly that tag. `#[serde(...)]` attributes on the type,
+/// its fields and its variants apply as usual, and newtype structs are written
+/// as their field. Also implements `c2pa_cbor::tags::CborTagged`. Generic
+/// types are not supported.
+///
+/// Fields take two more attributes:
+/// - `#[cbor(key = N)]` writes the field with the integer map key `N`
+///   (see `c2pa_cbor::keys`); struct fields only
+/// - `#[cbor(typed_array = "f32le")]` writes a `Vec` field as an RFC 8746
+///   typed array, with the element type and byte order named as `u8`, `i8`,
+///   or `u16`, `u32`, `u64`, `i16`, `i32`, `i64`, `f16`, `f32` or `f64`
+///   followed by `be` or `le`
+///
+/// # Example
+/// ```ignore
+/// use c2pa_cbor::CborTagged;
+///
+/// #[derive(Debug, PartialEq, CborTagged)]
+/// #[cbor(tag = 1001)]
+/// struct Claim {
+///     #[serde(rename = "claim_generator")]
+///     generator: String,
+///     #[cbor(key = 3, typed_array = "f32le")]
+///     levels: Vec<f32>,
+/// }
+///
+/// let claim = Claim {
+///     generator: "test/1.0".to_string(),
+///     levels: vec![0.5],
+/// };
+/// let cbor = c2pa_cbor::to_vec(&claim).unwrap();
+/// assert_eq!(cbor[..3], [0xd9, 0x03, 0xe9]);
+/// assert_eq!(c2pa_cbor::from_slice::<Claim>(&cbor).unwrap(), claim);
+/// ```
+#[proc_macro_derive(CborTagged, attributes(cbor, serde))]
+pub fn derive_cbor_tagged(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    cbor_tagged(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// The tag number from `#[cbor(tag = N)]`
+fn cbor_tag(attrs: &[Attribute]) -> syn::Result<u64> {
+    let mut tag = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("cbor")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("tag") {
+                let value: LitInt = meta.value()?.parse()?;
+                tag = Some(value.base10_parse::<u64>()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `tag = N`"))
+            }
+        })?;
+    }
+    tag.ok_or_else(|| {
+        Error::new(
+            Span::call_site(),
+            "CborTagged needs a tag, e.g. #[cbor(tag = 1001)]",
+        )
+    })
+}
+
+/// Keep only the `#[serde(...)]` attributes, for the serde mirror of the type
+fn serde_attrs(attrs: &mut Vec<Attribute>) {
+    attrs.retain(|attr| attr.path().is_ident("serde"));
+}
+
+fn is_transparent(attr: &Attribute) -> bool {
+    let mut transparent = false;
+    let _ = attr.parse_nested_meta(|meta| {
+        transparent |= meta.path.is_ident("transparent");
+        // Skip over `key = value` arguments
+        if meta.input.peek(syn::Token![=]) {
+            meta.value()?.parse::<syn::Expr>()?;
+        }
+        Ok(())
+    });
+    transparent
+}
+
+/// Replace the `#[cbor(...)]` attributes of `fields` with the serde attributes
+/// they stand for, returning whether any field has an integer key
+fn field_attrs(fields: &mut Fields, keys_allowed: bool) -> syn::Result<bool> {
+    let mut has_keys = false;
+    for field in fields.iter_mut() {
+        let mut generated: Vec<Attribute> = Vec::new();
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("cbor"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("key") {
+                    if !keys_allowed {
+                        return Err(meta.error("integer keys are only supported on struct fields"));
+                    }
+                    let input = meta.value()?;
+                    let negative = input.parse::<Option<syn::Token![-]>>()?.is_some();
+                    let value: LitInt = input.parse()?;
+                    let key = value.base10_parse::<i64>()?;
+                    let name = format!("__cbor_key_{}__", if negative { -key } else { key });
+                    generated.push(syn::parse_quote!(#[serde(rename = #name)]));
+                    has_keys = true;
+                    Ok(())
+                } else if meta.path.is_ident("typed_array") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    let (element, order) = typed_array(&value.value())
+                        .ok_or_else(|| Error::new_spanned(&value, TYPED_ARRAY_ERROR))?;
+                    // The element type, when named, checks the field's type
+                    let generics = element
+                        .map(|element| format!("::<{}, _>", element))
+                        .unwrap_or_default();
+                    let path = format!("::c2pa_cbor::typed_array::{}", order);
+                    let serialize = format!("{}::serialize{}", path, generics);
+                    let deserialize = format!("{}::deserialize{}", path, generics);
+                    generated.push(syn::parse_quote!(
+                        #[serde(serialize_with = #serialize, deserialize_with = #deserialize)]
+                    ));
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `key = N` or `typed_array = \"...\"`"))
+                }
+            })?;
+        }
+        serde_attrs(&mut field.attrs);
+        field.attrs.extend(generated);
+    }
+    Ok(has_keys)
+}
+
+const TYPED_ARRAY_ERROR: &str = "expected a typed array element such as \"u8\", \"i8\" \
+    or \"f32le\": u16, u32, u64, i16, i32, i64, f16, f32 or f64 followed by be or le";
+
+/// The element type and byte order module for a `typed_array` attribute
+///
+/// `half::f16` is left to be inferred from the field, since `c2pa_cbor` does
+/// not re-export `half`.
+fn typed_array(name: &str) -> Option<(Option<&'static str>, &'static str)> {
+    let element = |name: &str| -> Option<Option<&'static str>> {
+        Some(Some(match name {
+            "u16" => "u16",
+            "u32" => "u32",
+            "u64" => "u64",
+            "i16" => "i16",
+            "i32" => "i32",
+            "i64" => "i64",
+            "f32" => "f32",
+            "f64" => "f64",
+            "f16" => return Some(None),
+            _ => return None,
+        }))
+    };
+    match name {
+        "u8" => Some((Some("u8"), "big_endian")),
+        "i8" => Some((Some("i8"), "big_endian")),
+        _ => {
+            if let Some(name) = name.strip_suffix("be") {
+                Some((element(name)?, "big_endian"))
+            } else {
+                Some((element(name.strip_suffix("le")?)?, "little_endian"))
+            }
+        }
+    }
+}
+
+fn cbor_tagged(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let tag = Literal::u64_suffixed(cbor_tag(&input.attrs)?);
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "CborTagged does not support generic types",
+        ));
+    }
+
+    let name = &input.ident;
+    let remote = name.to_string();
+    let mirror = format_ident!("__{}CborBody", name);
+    let mut attrs = input.attrs.clone();
+    serde_attrs(&mut attrs);
+
+    // A copy of the type that serde implements as a "remote" definition, so
+    // the untagged body can be written and read without implementing the
+    // traits on the type itself
+    let mut has_keys = false;
+    let definition = match input.data {
+        Data::Struct(mut data) => {
+            // Tagged content is visited as a plain value, which a derived
+            // newtype visitor does not accept
+            if matches!(&data.fields, Fields::Unnamed(fields) if fields.unnamed.len() == 1)
+                && !attrs.iter().any(is_transparent)
+            {
+                attrs.push(syn::parse_quote!(#[serde(transparent)]));
+            }
+            let keys_allowed = matches!(data.fields, Fields::Named(_));
+            has_keys = field_attrs(&mut data.fields, keys_allowed)?;
+            let fields = &data.fields;
+            match fields {
+                Fields::Named(_) => quote!(struct #mirror #fields),
+                _ => quote!(struct #mirror #fields;),
+            }
+        }
+        Data::Enum(mut data) => {
+            for variant in data.variants.iter_mut() {
+                serde_attrs(&mut variant.attrs);
+                field_attrs(&mut variant.fields, false)?;
+            }
+            let variants = data.variants.iter();
+            quote!(enum #mirror { #(#variants),* })
+        }
+        Data::Union(data) => {
+            return Err(Error::new_spanned(
+                data.union_token,
+                "CborTagged does not support unions",
+            ));
+        }
+    };
+
+    // Integer keys are matched to their fields on the way in
+    let body = if has_keys {
+        quote!(#mirror::deserialize(::c2pa_cbor::keys::IntegerKeys(deserializer)))
+    } else {
+        quote!(#mirror::deserialize(deserializer))
+    };
+
+    Ok(quote! {
+        const _: () = {
+            #[derive(::serde::Serialize, ::serde::Deserialize)]
+            #[serde(remote = #remote)]
+            #(#attrs)*
+            #definition
+
+            #[automatically_derived]
+            impl ::serde::Serialize for #name {
+                fn serialize<__S>(&self, serializer: __S) -> ::core::result::Result<__S::Ok, __S::Error>
+                where
+                    __S: ::serde::Serializer,
+                {
+                    struct __Body<'a>(&'a #name);
+
+                    impl ::serde::Serialize for __Body<'_> {
+                        fn serialize<__S>(&self, serializer: __S) -> ::core::result::Result<__S::Ok, __S::Error>
+                        where
+                            __S: ::serde::Serializer,
+                        {
+                            #mirror::serialize(self.0, serializer)
+                        }
+                    }
+
+                    ::c2pa_cbor::tags::serialize_with_tag(#tag, &__Body(self), serializer)
+                }
+            }
+
+            #[automatically_derived]
+            impl<'de> ::serde::Deserialize<'de> for #name {
+                fn deserialize<__D>(deserializer: __D) -> ::core::result::Result<Self, __D::Error>
+                where
+                    __D: ::serde::Deserializer<'de>,
+                {
+                    struct __Body(#name);
+
+                    impl<'de> ::serde::Deserialize<'de> for __Body {
+                        fn deserialize<__D>(deserializer: __D) -> ::core::result::Result<Self, __D::Error>
+                        where
+                            __D: ::serde::Deserializer<'de>,
+                        {
+                            #body.map(__Body)
+                        }
+                    }
+
+                    ::c2pa_cbor::tags::deserialize_with_tag::<__Body, __D>(#tag, deserializer)
+                        .map(|body| body.0)
+                }
+            }
+
+            #[automatically_derived]
+            impl ::c2pa_cbor::tags::CborTagged for #name {
+                const CBOR_TAG: u64 = #tag;
+            }
+        };
+    })
+}
@@ -0,0 +1,223 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Adapters for `serde_with`
+//!
+//! Enabled with the `serde_with` feature. Each adapter implements
+//! `SerializeAs` and `DeserializeAs`, so it can be used with `#[serde_as]`
+//! or `serde_with::As`, and nested inside other adapters such as `Vec<_>`
+//! or `Option<_>`:
+//! - [`CborBytes`]: a byte string instead of an array of integers
+//! - [`TaggedUri`]: a text string with tag 32
+//! - [`EpochTime`]: an epoch-based date/time number with tag 1
+//!
+//! Untagged input is accepted when deserializing, so the same types can be
+//! read from formats without tags; input with a different tag is rejected.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::adapters::{CborBytes, TaggedUri};
+//! use serde::{Deserialize, Serialize};
+//! use serde_with::As;
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Ingredient {
+//!     #[serde(with = "As::<TaggedUri>")]
+//!     url: String,
+//!     #[serde(with = "As::<Vec<CborBytes>>")]
+//!     hashes: Vec<Vec<u8>>,
+//! }
+//!
+//! let ingredient = Ingredient {
+//!     url: "self#jumbf=/c2pa".to_string(),
+//!     hashes: vec![vec![1, 2], vec![3]],
+//! };
+//! let cbor = c2pa_cbor::to_vec(&ingredient).unwrap();
+//! assert_eq!(c2pa_cbor::from_slice::<Ingredient>(&cbor).unwrap(), ingredient);
+//! ```
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use serde_with::{DeserializeAs, SerializeAs};
+
+use crate::{
+    constants::{TAG_EPOCH_DATETIME, TAG_URI},
+    serde_helpers::{Epoch, bytes, check_tag, deserialize_epoch, epoch_seconds},
+    tags::Tagged,
+};
+
+/// Serialize bytes as a CBOR byte string
+///
+/// Works for any `AsRef<[u8]>` when serializing and any `From<Vec<u8>>` when
+/// deserializing. Like `serde_bytes`, an array of integers is also accepted.
+pub struct CborBytes;
+
+impl<T: AsRef<[u8]>> SerializeAs<T> for CborBytes {
+    fn serialize_as<S: Serializer>(source: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        bytes::serialize(source, serializer)
+    }
+}
+
+impl<'de, T: From<Vec<u8>>> DeserializeAs<'de, T> for CborBytes {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        bytes::deserialize(deserializer)
+    }
+}
+
+/// Serialize a string as a URI with tag 32
+///
+/// Works for any `AsRef<str>` when serializing and any `From<String>` when
+/// deserializing.
+pub struct TaggedUri;
+
+impl<T: AsRef<str>> SerializeAs<T> for TaggedUri {
+    fn serialize_as<S: Serializer>(source: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        Tagged::new(Some(TAG_URI), source.as_ref()).serialize(serializer)
+    }
+}
+
+impl<'de, T: From<String>> DeserializeAs<'de, T> for TaggedUri {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        let tagged = Tagged::<String>::deserialize(deserializer)?;
+        check_tag(tagged.tag, TAG_URI)?;
+        Ok(tagged.value.into())
+    }
+}
+
+/// Serialize a point in time as seconds since the Unix epoch with tag 1
+///
+/// Supports `i64` (whole seconds) and [`SystemTime`]. Times with a fractional
+/// second are written as floats, all others as integers; both are accepted
+/// when deserializing, except that `i64` rejects fractional seconds.
+pub struct EpochTime;
+
+impl SerializeAs<i64> for EpochTime {
+    fn serialize_as<S: Serializer>(source: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+        epoch_seconds::serialize(source, serializer)
+    }
+}
+
+impl<'de> DeserializeAs<'de, i64> for EpochTime {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+        epoch_seconds::deserialize(deserializer)
+    }
+}
+
+impl SerializeAs<SystemTime> for EpochTime {
+    fn serialize_as<S: Serializer>(source: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        let (negative, duration) = match source.duration_since(UNIX_EPOCH) {
+            Ok(duration) => (false, duration),
+            Err(e) => (true, e.duration()),
+        };
+        match i64::try_from(duration.as_secs()) {
+            Ok(secs) if duration.subsec_nanos() == 0 => {
+                let secs = if negative { -secs } else { secs };
+                Tagged::new(Some(TAG_EPOCH_DATETIME), secs).serialize(serializer)
+            }
+            _ => {
+                let secs = duration.as_secs_f64();
+                let secs = if negative { -secs } else { secs };
+                Tagged::new(Some(TAG_EPOCH_DATETIME), secs).serialize(serializer)
+            }
+        }
+    }
+}
+
+impl<'de> DeserializeAs<'de, SystemTime> for EpochTime {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        let (negative, duration) = match deserialize_epoch(deserializer)? {
+            Epoch::Int(secs) => (secs < 0, Some(Duration::from_secs(secs.unsigned_abs()))),
+            Epoch::Float(secs) => (secs < 0.0, Duration::try_from_secs_f64(secs.abs()).ok()),
+        };
+        duration
+            .and_then(|duration| {
+                if negative {
+                    UNIX_EPOCH.checked_sub(duration)
+                } else {
+                    UNIX_EPOCH.checked_add(duration)
+                }
+            })
+            .ok_or_else(|| de::Error::custom("Epoch time out of range"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_with::As;
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Claim {
+        #[serde(with = "As::<CborBytes>")]
+        signature: Vec<u8>,
+        #[serde(with = "As::<Option<TaggedUri>>")]
+        url: Option<String>,
+        #[serde(with = "As::<EpochTime>")]
+        created: i64,
+        #[serde(with = "As::<EpochTime>")]
+        modified: SystemTime,
+    }
+
+    #[test]
+    fn test_adapters_encoding() {
+        let claim = Claim {
+            signature: vec![0xaa],
+            url: Some("a:b".to_string()),
+            created: -1,
+            modified: UNIX_EPOCH + Duration::from_millis(1500),
+        };
+        let cbor = crate::to_vec(&claim).unwrap();
+        assert_eq!(crate::from_slice::<Claim>(&cbor).unwrap(), claim);
+
+        // The fractional time is a tagged float, whose width depends on features
+        let expected_fields: [&[u8]; 3] = [
+            &[0x41, 0xaa],
+            &[0xd8, 0x20, 0x63, b'a', b':', b'b'],
+            &[0xc1, 0x20],
+        ];
+        for field in expected_fields {
+            assert!(
+                cbor.windows(field.len()).any(|w| w == field),
+                "missing {:02x?}",
+                field
+            );
+        }
+    }
+
+    #[test]
+    fn test_adapters_accept_untagged_and_reject_other_tags() {
+        let json = r#"{"signature":[1,2],"url":null,"created":10,"modified":20}"#;
+        let claim: Claim = serde_json::from_str(json).unwrap();
+        assert_eq!(claim.signature, [1, 2]);
+        assert_eq!(claim.modified, UNIX_EPOCH + Duration::from_secs(20));
+
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Uri(#[serde(with = "As::<TaggedUri>")] String);
+        let mut cbor = Vec::new();
+        crate::encode_tagged(&mut cbor, 33, &"a:b").unwrap();
+        assert!(crate::from_slice::<Uri>(&cbor).is_err());
+
+        #[derive(Deserialize)]
+        struct Seconds(#[serde(with = "As::<EpochTime>")] i64);
+        assert!(crate::from_slice::<Seconds>(&[0xc1, 0xf9, 0x3e, 0x00]).is_err());
+        assert_eq!(
+            crate::from_slice::<Seconds>(&[0xc1, 0xf9, 0x40, 0x00])
+                .unwrap()
+                .0,
+            2
+        );
+    }
+}
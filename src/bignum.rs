@@ -0,0 +1,486 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Bignums (tags 2 and 3) and bigfloats (tag 5)
+//!
+//! `#[serde(with = "...")]` modules for integers wider than 64 bits:
+//! - `bigint` and `biguint` for `num_bigint::BigInt` and `BigUint`,
+//!   enabled with the `num-bigint` feature
+//! - [`int128`] for `i128`, always available
+//!
+//! Following RFC 8949 §3.4.3, values that fit in `i64` or `u64` are written
+//! as plain integers; all others are written as a byte string holding the
+//! big-endian magnitude without leading zeros, tagged 2 for non-negative
+//! values and 3 for negative values (whose magnitude is `-1 - n`).
+//!
+//! All modules read both plain integers and bignums. Leading zero bytes in
+//! a bignum are accepted, and reading fails if the value does not fit the
+//! target type, or if a byte string is untagged or has another tag.
+//!
+//! [`Bigfloat`] carries a tag 5 bigfloat as its exponent and mantissa, so it
+//! survives a decode and re-encode without rounding through `f64`.
+//!
+//! # Example
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Certificate {
+//!     #[serde(with = "c2pa_cbor::bignum::int128")]
+//!     serial: i128,
+//! }
+//!
+//! let cert = Certificate { serial: 1 << 80 };
+//! let cbor = c2pa_cbor::to_vec(&cert).unwrap();
+//! // Tag 2 with an 11-byte magnitude
+//! assert_eq!(&cbor[8..11], [0xc2, 0x4b, 0x01]);
+//! assert_eq!(c2pa_cbor::from_slice::<Certificate>(&cbor).unwrap(), cert);
+//! ```
+
+use std::fmt;
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, Visitor},
+};
+use serde_bytes::Bytes;
+
+use crate::{
+    constants::{TAG_BIGFLOAT, TAG_NEGATIVE_BIGNUM, TAG_POSITIVE_BIGNUM},
+    tags::Tagged,
+};
+
+/// An integer as found in the input
+enum BigInput {
+    Int(i128),
+    Bytes(Vec<u8>),
+}
+
+impl<'de> Deserialize<'de> for BigInput {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct InputVisitor;
+
+        impl Visitor<'_> for InputVisitor {
+            type Value = BigInput;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an integer or a bignum")
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<BigInput, E> {
+                Ok(BigInput::Int(v as i128))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<BigInput, E> {
+                Ok(BigInput::Int(v as i128))
+            }
+
+            fn visit_i128<E: de::Error>(self, v: i128) -> Result<BigInput, E> {
+                Ok(BigInput::Int(v))
+            }
+
+            fn visit_u128<E: de::Error>(self, v: u128) -> Result<BigInput, E> {
+                i128::try_from(v)
+                    .map(BigInput::Int)
+                    .map_err(|_| E::custom(format!("Integer {} too large for i128", v)))
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<BigInput, E> {
+                Ok(BigInput::Bytes(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<BigInput, E> {
+                Ok(BigInput::Bytes(v))
+            }
+        }
+
+        deserializer.deserialize_any(InputVisitor)
+    }
+}
+
+/// A decoded integer: either small, or a bignum sign and magnitude
+enum BigValue {
+    Int(i128),
+    Big { negative: bool, magnitude: Vec<u8> },
+}
+
+/// Read a plain integer or a tag 2/3 bignum
+fn deserialize_big<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigValue, D::Error> {
+    let tagged = Tagged::<BigInput>::deserialize(deserializer)?;
+    match (tagged.tag, tagged.value) {
+        (None, BigInput::Int(n)) => Ok(BigValue::Int(n)),
+        (Some(tag @ (TAG_POSITIVE_BIGNUM | TAG_NEGATIVE_BIGNUM)), BigInput::Bytes(mut bytes)) => {
+            let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+            bytes.drain(..zeros);
+            Ok(BigValue::Big {
+                negative: tag == TAG_NEGATIVE_BIGNUM,
+                magnitude: bytes,
+            })
+        }
+        (Some(tag), _) => Err(de::Error::custom(format!(
+            "Unexpected tag {} for an integer",
+            tag
+        ))),
+        (None, BigInput::Bytes(_)) => Err(de::Error::custom(
+            "Expected bignum tag 2 or 3 before byte string",
+        )),
+    }
+}
+
+/// Write a bignum from its sign and big-endian magnitude (`-1 - n` if negative)
+fn serialize_big<S: Serializer>(
+    negative: bool,
+    magnitude: &[u8],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let zeros = magnitude.iter().take_while(|&&b| b == 0).count();
+    let tag = if negative {
+        TAG_NEGATIVE_BIGNUM
+    } else {
+        TAG_POSITIVE_BIGNUM
+    };
+    Tagged::new(Some(tag), Bytes::new(&magnitude[zeros..])).serialize(serializer)
+}
+
+/// `i128`, falling back to a bignum outside the 64-bit integer range
+///
+/// Use with `#[serde(with = "c2pa_cbor::bignum::int128")]`.
+pub mod int128 {
+    use super::*;
+
+    /// Serialize as a plain integer if it fits in a CBOR integer head
+    /// (-2^64 to 2^64 - 1), otherwise as a bignum
+    pub fn serialize<S: Serializer>(n: &i128, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Ok(n) = i64::try_from(*n) {
+            serializer.serialize_i64(n)
+        } else if let Ok(n) = u64::try_from(*n) {
+            serializer.serialize_u64(n)
+        } else if u64::try_from(-1 - *n).is_ok() {
+            // Below i64::MIN but still a major type 1 integer
+            serializer.serialize_i128(*n)
+        } else if *n < 0 {
+            serialize_big(true, &(-1 - *n).to_be_bytes(), serializer)
+        } else {
+            serialize_big(false, &n.to_be_bytes(), serializer)
+        }
+    }
+
+    /// Deserialize from a plain integer or a bignum that fits in `i128`
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i128, D::Error> {
+        let out_of_range = || de::Error::custom("Bignum out of range for i128");
+        match deserialize_big(deserializer)? {
+            BigValue::Int(n) => Ok(n),
+            BigValue::Big { magnitude, .. } if magnitude.len() > 16 => Err(out_of_range()),
+            BigValue::Big {
+                negative,
+                magnitude,
+            } => {
+                let n = magnitude
+                    .iter()
+                    .fold(0u128, |acc, &b| (acc << 8) | b as u128);
+                let n = i128::try_from(n).map_err(|_| out_of_range())?;
+                Ok(if negative { -1 - n } else { n })
+            }
+        }
+    }
+}
+
+/// `num_bigint::BigInt` as a plain integer or bignum
+///
+/// Use with `#[serde(with = "c2pa_cbor::bignum::bigint")]`.
+#[cfg(feature = "num-bigint")]
+pub mod bigint {
+    use num_bigint::{BigInt, BigUint, Sign};
+
+    use super::*;
+
+    /// Serialize as a plain integer if it fits in a CBOR integer head,
+    /// otherwise as a bignum
+    pub fn serialize<S: Serializer>(n: &BigInt, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Ok(n) = i128::try_from(n) {
+            return super::int128::serialize(&n, serializer);
+        }
+        match n.sign() {
+            Sign::Minus => {
+                let magnitude = n.magnitude().clone() - 1u32;
+                serialize_big(true, &magnitude.to_bytes_be(), serializer)
+            }
+            _ => serialize_big(false, &n.magnitude().to_bytes_be(), serializer),
+        }
+    }
+
+    /// Deserialize from a plain integer or a bignum
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigInt, D::Error> {
+        Ok(match deserialize_big(deserializer)? {
+            BigValue::Int(n) => BigInt::from(n),
+            BigValue::Big {
+                negative: false,
+                magnitude,
+            } => BigInt::from(BigUint::from_bytes_be(&magnitude)),
+            BigValue::Big {
+                negative: true,
+                magnitude,
+            } => -BigInt::from(BigUint::from_bytes_be(&magnitude)) - 1,
+        })
+    }
+}
+
+/// `num_bigint::BigUint` as a plain integer or tag 2 bignum
+///
+/// Use with `#[serde(with = "c2pa_cbor::bignum::biguint")]`.
+#[cfg(feature = "num-bigint")]
+pub mod biguint {
+    use num_bigint::BigUint;
+
+    use super::*;
+
+    /// Serialize as a plain integer if it fits in 64 bits, otherwise as a bignum
+    pub fn serialize<S: Serializer>(n: &BigUint, serializer: S) -> Result<S::Ok, S::Error> {
+        match u64::try_from(n) {
+            Ok(n) => serializer.serialize_u64(n),
+            Err(_) => serialize_big(false, &n.to_bytes_be(), serializer),
+        }
+    }
+
+    /// Deserialize from a non-negative plain integer or a tag 2 bignum
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigUint, D::Error> {
+        match deserialize_big(deserializer)? {
+            BigValue::Int(n) => u128::try_from(n).map(BigUint::from).ok(),
+            BigValue::Big {
+                negative: false,
+                magnitude,
+            } => Some(BigUint::from_bytes_be(&magnitude)),
+            BigValue::Big { negative: true, .. } => None,
+        }
+        .ok_or_else(|| de::Error::custom("Negative integer cannot be a BigUint"))
+    }
+}
+
+/// A bigfloat (tag 5): `mantissa * 2^exponent`
+///
+/// Encoded as `5([exponent, mantissa])`, with the mantissa written as a
+/// bignum when it does not fit in 64 bits (RFC 8949 §3.4.4). Decoding
+/// requires tag 5 and fails if either part is out of range, rather than
+/// losing precision.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::bignum::Bigfloat;
+///
+/// // 1.5 = 3 * 2^-1
+/// let cbor = [0xc5, 0x82, 0x20, 0x03];
+/// let value: Bigfloat = c2pa_cbor::from_slice(&cbor).unwrap();
+/// assert_eq!(value, Bigfloat { exponent: -1, mantissa: 3 });
+/// assert_eq!(value.to_f64(), 1.5);
+/// assert_eq!(c2pa_cbor::to_vec(&value).unwrap(), cbor);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bigfloat {
+    /// Power of two
+    pub exponent: i64,
+    /// Signed mantissa
+    pub mantissa: i128,
+}
+
+impl Bigfloat {
+    /// Nearest `f64`, which may round, overflow to infinity or underflow to zero
+    pub fn to_f64(&self) -> f64 {
+        let exponent = self.exponent.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+        self.mantissa as f64 * 2f64.powi(exponent)
+    }
+}
+
+/// An `i128` element of a bigfloat
+#[derive(Serialize, Deserialize)]
+struct Wide(#[serde(with = "int128")] i128);
+
+impl Serialize for Bigfloat {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let parts = (self.exponent, Wide(self.mantissa));
+        Tagged::new(Some(TAG_BIGFLOAT), parts).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Bigfloat {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let tagged = Tagged::<Vec<Wide>>::deserialize(deserializer)?;
+        if tagged.tag != Some(TAG_BIGFLOAT) {
+            return Err(de::Error::custom("Expected tag 5 for a bigfloat"));
+        }
+        match tagged.value[..] {
+            [Wide(exponent), Wide(mantissa)] => Ok(Bigfloat {
+                exponent: i64::try_from(exponent)
+                    .map_err(|_| de::Error::custom("Bigfloat exponent out of range"))?,
+                mantissa,
+            }),
+            _ => Err(de::Error::custom(
+                "Bigfloat must be an array of exponent and mantissa",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Wide(#[serde(with = "crate::bignum::int128")] i128);
+
+    #[test]
+    fn test_int128_encoding() {
+        let cases: [(i128, &[u8]); 8] = [
+            (5, &[0x05]),
+            (-5, &[0x24]),
+            (
+                u64::MAX as i128,
+                &[0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            ),
+            (1 << 64, &[0xc2, 0x49, 0x01, 0, 0, 0, 0, 0, 0, 0, 0]),
+            (i64::MIN as i128 - 1, &[0x3b, 0x80, 0, 0, 0, 0, 0, 0, 0]),
+            (
+                -(1 << 64),
+                &[0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            ),
+            (-(1 << 64) - 1, &[0xc3, 0x49, 0x01, 0, 0, 0, 0, 0, 0, 0, 0]),
+            (
+                i128::MIN,
+                &[
+                    0xc3, 0x50, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                    0xff, 0xff, 0xff, 0xff, 0xff,
+                ],
+            ),
+        ];
+        for (n, expected) in cases {
+            let cbor = crate::to_vec(&Wide(n)).unwrap();
+            assert_eq!(cbor, expected, "{}", n);
+            assert_eq!(crate::from_slice::<Wide>(&cbor).unwrap(), Wide(n));
+        }
+    }
+
+    #[test]
+    fn test_int128_decoding_edge_cases() {
+        // Leading zeros are ignored, and small bignums decode too
+        assert_eq!(
+            crate::from_slice::<Wide>(&[0xc2, 0x43, 0x00, 0x00, 0x07]).unwrap(),
+            Wide(7)
+        );
+        assert_eq!(crate::from_slice::<Wide>(&[0xc3, 0x40]).unwrap(), Wide(-1));
+
+        // 2^127 does not fit, nor do 17 significant bytes
+        let mut too_big = vec![0xc2, 0x50, 0x80];
+        too_big.extend([0; 15]);
+        assert!(crate::from_slice::<Wide>(&too_big).is_err());
+        let mut too_long = vec![0xc2, 0x51];
+        too_long.extend([1; 17]);
+        assert!(crate::from_slice::<Wide>(&too_long).is_err());
+
+        // Untagged or wrongly tagged byte strings are not integers
+        assert!(crate::from_slice::<Wide>(&[0x41, 0x01]).is_err());
+        assert!(crate::from_slice::<Wide>(&[0xc4, 0x41, 0x01]).is_err());
+        assert!(crate::from_slice::<Wide>(&[0xc2, 0x01]).is_err());
+    }
+
+    #[test]
+    fn test_bigfloat_round_trip() {
+        // Mantissa wider than 64 bits is a bignum
+        let value = Bigfloat {
+            exponent: -200,
+            mantissa: -(1 << 70),
+        };
+        let cbor = crate::to_vec(&value).unwrap();
+        assert_eq!(&cbor[..5], [0xc5, 0x82, 0x38, 0xc7, 0xc3]);
+        assert_eq!(crate::from_slice::<Bigfloat>(&cbor).unwrap(), value);
+
+        // Inside a map, and with an indefinite-length array
+        let mut map = std::collections::BTreeMap::new();
+        map.insert("x".to_string(), value);
+        let cbor = crate::to_vec(&map).unwrap();
+        assert_eq!(
+            crate::from_slice::<std::collections::BTreeMap<String, Bigfloat>>(&cbor).unwrap(),
+            map
+        );
+        assert_eq!(
+            crate::from_slice::<Bigfloat>(&[0xc5, 0x9f, 0x01, 0x02, 0xff]).unwrap(),
+            Bigfloat {
+                exponent: 1,
+                mantissa: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_bigfloat_rejects_malformed() {
+        // Untagged, decimal fraction tag, wrong arity and non-integer parts
+        assert!(crate::from_slice::<Bigfloat>(&[0x82, 0x01, 0x02]).is_err());
+        assert!(crate::from_slice::<Bigfloat>(&[0xc4, 0x82, 0x01, 0x02]).is_err());
+        assert!(crate::from_slice::<Bigfloat>(&[0xc5, 0x81, 0x01]).is_err());
+        assert!(crate::from_slice::<Bigfloat>(&[0xc5, 0x83, 0x01, 0x02, 0x03]).is_err());
+        assert!(crate::from_slice::<Bigfloat>(&[0xc5, 0x82, 0x01, 0x61, b'x']).is_err());
+
+        // Exponent beyond i64
+        let mut cbor = vec![0xc5, 0x82, 0x1b];
+        cbor.extend([0xff; 8]);
+        cbor.push(0x01);
+        assert!(crate::from_slice::<Bigfloat>(&cbor).is_err());
+    }
+
+    #[cfg(feature = "num-bigint")]
+    #[test]
+    fn test_num_bigint_round_trip() {
+        use num_bigint::{BigInt, BigUint};
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Signed(#[serde(with = "crate::bignum::bigint")] BigInt);
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Unsigned(#[serde(with = "crate::bignum::biguint")] BigUint);
+
+        let huge = BigUint::from_bytes_be(&[0x42; 20]);
+        let cbor = crate::to_vec(&Unsigned(huge.clone())).unwrap();
+        assert_eq!(&cbor[..2], [0xc2, 0x54]);
+        assert_eq!(
+            crate::from_slice::<Unsigned>(&cbor).unwrap(),
+            Unsigned(huge.clone())
+        );
+
+        let small = crate::to_vec(&Unsigned(BigUint::from(9u8))).unwrap();
+        assert_eq!(small, [0x09]);
+        assert!(crate::from_slice::<Unsigned>(&[0x20]).is_err());
+        assert!(crate::from_slice::<Unsigned>(&[0xc3, 0x41, 0x01]).is_err());
+
+        for n in [
+            BigInt::from(0),
+            BigInt::from(i64::MIN),
+            -BigInt::from(huge.clone()),
+            BigInt::from(huge),
+            BigInt::from(-(1i128 << 64)),
+        ] {
+            let cbor = crate::to_vec(&Signed(n.clone())).unwrap();
+            assert_eq!(crate::from_slice::<Signed>(&cbor).unwrap(), Signed(n));
+        }
+
+        // -2^64 is the most negative integer head, so it is not a bignum,
+        // though the bignum form still decodes
+        let cbor = crate::to_vec(&Signed(BigInt::from(-(1i128 << 64)))).unwrap();
+        assert_eq!(cbor, [0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+        let bignum = [0xc3, 0x48, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+        assert_eq!(
+            crate::from_slice::<Signed>(&bignum).unwrap(),
+            Signed(BigInt::from(-(1i128 << 64)))
+        );
+        assert_eq!(
+            crate::from_slice::<Wide>(&bignum).unwrap(),
+            Wide(-(1i128 << 64))
+        );
+    }
+}
@@ -0,0 +1,475 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Decoding into fixed-capacity collections without allocating
+//!
+//! Enabled with the `heapless` feature, which also enables `serde` support
+//! in the `heapless` crate. [`from_slice`] reads an item from a buffer
+//! without allocating: text and byte strings are borrowed from the input,
+//! and arrays and maps are handed to the target one element at a time. A
+//! `heapless::Vec` or `heapless::String` is filled in place, and returns an
+//! error when the input holds more than its capacity. Indefinite-length
+//! strings, whose chunks would have to be joined, are an error; indefinite
+//! arrays and maps are read.
+//!
+//! Only successful decoding is allocation free: errors carry a formatted
+//! message, and the rest of the crate still needs `std`.
+//!
+//! [`bytes`] writes a `heapless::Vec<u8, N>` as a byte string rather than
+//! an array of integers, for hashes and signatures.
+//!
+//! # Example
+//! ```
+//! use heapless::{String, Vec};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Claim {
+//!     label: String<32>,
+//!     #[serde(with = "c2pa_cbor::bounded::bytes")]
+//!     hash: Vec<u8, 32>,
+//!     versions: Vec<u8, 4>,
+//! }
+//!
+//! let claim = Claim {
+//!     label: String::try_from("c2pa.hash.data").unwrap(),
+//!     hash: Vec::from_slice(&[0xab; 32]).unwrap(),
+//!     versions: Vec::from_slice(&[1, 2]).unwrap(),
+//! };
+//! let cbor = c2pa_cbor::to_vec(&claim).unwrap();
+//! assert_eq!(c2pa_cbor::bounded::from_slice::<Claim>(&cbor).unwrap(), claim);
+//!
+//! // More elements than the capacity is an error, not a reallocation
+//! let cbor = c2pa_cbor::to_vec(&[1, 2, 3, 4, 5]).unwrap();
+//! assert!(c2pa_cbor::bounded::from_slice::<Vec<u8, 4>>(&cbor).is_err());
+//! ```
+
+use serde::de::{self, Deserialize, IntoDeserializer};
+
+use crate::{
+    Error, Result,
+    constants::*,
+    raw::{SliceReader, check_depth, float_from_arg, u64_to_usize},
+};
+
+/// Deserialize an instance of `T` from `bytes` without allocating
+///
+/// The whole slice must be one item. Nesting is limited to
+/// [`DEFAULT_MAX_DEPTH`] levels. Tags are looked
+/// through, and enums use the same shapes as [`Encoder`](crate::Encoder)
+/// writes by default.
+pub fn from_slice<'a, T: Deserialize<'a>>(bytes: &'a [u8]) -> Result<T> {
+    let mut deserializer = Deserializer {
+        reader: SliceReader::new(bytes),
+        depth: 0,
+    };
+    let value = T::deserialize(&mut deserializer)?;
+    if deserializer.reader.remaining() > 0 {
+        return Err(Error::TrailingData);
+    }
+    Ok(value)
+}
+
+struct Deserializer<'a> {
+    reader: SliceReader<'a>,
+    depth: usize,
+}
+
+impl<'a> Deserializer<'a> {
+    /// Read the content of a definite-length string, borrowing it
+    fn read_string(&mut self, arg: Option<u64>) -> Result<&'a [u8]> {
+        let len = arg.ok_or_else(|| {
+            Error::Syntax("Indefinite-length strings cannot be read without allocating".to_string())
+        })?;
+        self.reader.read_slice(u64_to_usize(len)?)
+    }
+
+    /// Run `f` one level deeper, for the content of a collection or tag
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        check_depth(self.depth)?;
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Skip any tags before the next item
+    fn skip_tags(&mut self) -> Result<()> {
+        while self.reader.peek_u8()? >> 5 == MAJOR_TAG {
+            let (_, _, arg) = self.reader.read_header()?;
+            arg.ok_or_else(|| Error::Syntax("Tag cannot be indefinite".to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let (major, info, arg) = self.reader.read_header()?;
+        match major {
+            MAJOR_UNSIGNED => visitor.visit_u64(
+                arg.ok_or_else(|| Error::Syntax("Integer cannot be indefinite".to_string()))?,
+            ),
+            MAJOR_NEGATIVE => {
+                let val =
+                    arg.ok_or_else(|| Error::Syntax("Integer cannot be indefinite".to_string()))?;
+                let value = -1 - val as i128;
+                match i64::try_from(value) {
+                    Ok(value) => visitor.visit_i64(value),
+                    Err(_) => visitor.visit_i128(value),
+                }
+            }
+            MAJOR_BYTES => visitor.visit_borrowed_bytes(self.read_string(arg)?),
+            MAJOR_TEXT => visitor.visit_borrowed_str(
+                std::str::from_utf8(self.read_string(arg)?).map_err(|_| Error::InvalidUtf8)?,
+            ),
+            MAJOR_ARRAY => {
+                let remaining = arg.map(u64_to_usize).transpose()?;
+                self.nested(|de| {
+                    let mut seq = Seq { de, remaining };
+                    let value = visitor.visit_seq(&mut seq)?;
+                    seq.end()?;
+                    Ok(value)
+                })
+            }
+            MAJOR_MAP => {
+                let remaining = arg.map(u64_to_usize).transpose()?;
+                self.nested(|de| {
+                    let mut seq = Seq { de, remaining };
+                    let value = visitor.visit_map(&mut seq)?;
+                    seq.end()?;
+                    Ok(value)
+                })
+            }
+            MAJOR_TAG => {
+                arg.ok_or_else(|| Error::Syntax("Tag cannot be indefinite".to_string()))?;
+                self.nested(|de| de.deserialize_any(visitor))
+            }
+            _ => match (info, arg) {
+                (FALSE, _) => visitor.visit_bool(false),
+                (TRUE, _) => visitor.visit_bool(true),
+                (NULL | UNDEFINED, _) => visitor.visit_unit(),
+                (FLOAT16 | FLOAT32 | FLOAT64, Some(bits)) => {
+                    visitor.visit_f64(float_from_arg(info, bits))
+                }
+                (INDEFINITE, _) => Err(Error::Syntax("Unexpected break".to_string())),
+                _ => Err(Error::Syntax("Unsupported simple value".to_string())),
+            },
+        }
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.reader.peek_u8()? {
+            0xf6 | 0xf7 => {
+                self.reader.read_u8()?;
+                visitor.visit_none()
+            }
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.skip_tags()?;
+        match self.reader.peek_u8()? >> 5 {
+            // A unit variant is written as just its key
+            MAJOR_UNSIGNED => {
+                let (_, _, arg) = self.reader.read_header()?;
+                let index = arg
+                    .and_then(|arg| u32::try_from(arg).ok())
+                    .ok_or_else(|| Error::Syntax("Enum variant index out of range".to_string()))?;
+                visitor.visit_enum(index.into_deserializer())
+            }
+            MAJOR_TEXT => {
+                let (_, _, arg) = self.reader.read_header()?;
+                let name =
+                    std::str::from_utf8(self.read_string(arg)?).map_err(|_| Error::InvalidUtf8)?;
+                visitor.visit_enum(de::value::BorrowedStrDeserializer::new(name))
+            }
+            // Other variants are a single-entry map from the key to the data
+            MAJOR_MAP => {
+                let (_, _, arg) = self.reader.read_header()?;
+                if arg != Some(1) {
+                    return Err(Error::Syntax(
+                        "Enum variant with data must be a single-entry map".to_string(),
+                    ));
+                }
+                self.nested(|de| visitor.visit_enum(Enum { de }))
+            }
+            _ => Err(Error::Syntax("Expected an enum variant".to_string())),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+// Elements of an array, or entries of a map, still to read
+struct Seq<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    // None for an indefinite length, until its break
+    remaining: Option<usize>,
+}
+
+impl Seq<'_, '_> {
+    /// Whether another element follows, consuming the break that ends an
+    /// indefinite-length collection
+    fn has_next(&mut self) -> Result<bool> {
+        match &mut self.remaining {
+            Some(0) => Ok(false),
+            Some(n) => {
+                *n -= 1;
+                Ok(true)
+            }
+            None if self.de.reader.peek_u8()? == BREAK => {
+                self.de.reader.read_u8()?;
+                self.remaining = Some(0);
+                Ok(false)
+            }
+            None => Ok(true),
+        }
+    }
+
+    /// Check that the visitor read every element
+    fn end(&mut self) -> Result<()> {
+        if self.has_next()? {
+            return Err(Error::Syntax(
+                "Collection has more elements than expected".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for &mut Seq<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>> {
+        if !self.has_next()? {
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.remaining
+    }
+}
+
+impl<'de> de::MapAccess<'de> for &mut Seq<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        if !self.has_next()? {
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.remaining
+    }
+}
+
+// The entry of a single-entry map holding an enum variant with data
+struct Enum<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'a, 'de> de::EnumAccess<'de> for Enum<'a, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self)> {
+        let variant = seed.deserialize(&mut *self.de)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Enum<'_, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        de::Deserialize::deserialize(self.de)
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self.de)
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_any(self.de, visitor)
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_any(self.de, visitor)
+    }
+}
+
+/// `#[serde(with = "c2pa_cbor::bounded::bytes")]` for `heapless::Vec<u8, N>` fields
+///
+/// Writes the field as a byte string, and reads a byte string or an array
+/// of integers, failing if it is longer than `N`.
+pub mod bytes {
+    use std::fmt;
+
+    use serde::{Deserializer, Serializer, de};
+
+    /// Serialize `bytes` as a byte string
+    pub fn serialize<S: Serializer, const N: usize>(
+        bytes: &heapless::Vec<u8, N>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(bytes)
+    }
+
+    /// Deserialize a byte string of at most `N` bytes
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<heapless::Vec<u8, N>, D::Error> {
+        deserializer.deserialize_bytes(BytesVisitor)
+    }
+
+    struct BytesVisitor<const N: usize>;
+
+    impl<'de, const N: usize> de::Visitor<'de> for BytesVisitor<N> {
+        type Value = heapless::Vec<u8, N>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a byte string of at most {N} bytes")
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+            heapless::Vec::from_slice(v).map_err(|_| E::invalid_length(v.len(), &self))
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut bytes = heapless::Vec::new();
+            while let Some(byte) = seq.next_element()? {
+                bytes
+                    .push(byte)
+                    .map_err(|_| de::Error::invalid_length(N + 1, &self))?;
+            }
+            Ok(bytes)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use heapless::{String, Vec};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    enum Action {
+        Created,
+        Edited { by: String<8> },
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Ingredient<'a> {
+        title: &'a str,
+        #[serde(with = "bytes")]
+        hash: Vec<u8, 4>,
+        actions: Vec<Action, 2>,
+        parent: Option<u32>,
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let ingredient = Ingredient {
+            title: "image.jpg",
+            hash: Vec::from_slice(&[1, 2, 3]).unwrap(),
+            actions: Vec::from_slice(&[
+                Action::Created,
+                Action::Edited {
+                    by: String::try_from("me").unwrap(),
+                },
+            ])
+            .unwrap(),
+            parent: None,
+        };
+        let cbor = crate::to_vec(&ingredient).unwrap();
+        let hash = [0x64, b'h', b'a', b's', b'h', 0x43, 1, 2, 3];
+        assert!(cbor.windows(hash.len()).any(|w| w == hash));
+        let decoded: Ingredient = from_slice(&cbor).unwrap();
+        assert_eq!(decoded, ingredient);
+        // The title points into the input
+        let start = decoded.title.as_ptr() as usize - cbor.as_ptr() as usize;
+        assert_eq!(&cbor[start..start + 9], b"image.jpg");
+
+        // The ordinary decoder reads the same types
+        assert_eq!(
+            crate::from_slice::<Vec<Action, 2>>(&crate::to_vec(&ingredient.actions).unwrap())
+                .unwrap(),
+            ingredient.actions
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        #[derive(Deserialize, Debug)]
+        struct Hash(#[serde(with = "bytes")] Vec<u8, 4>);
+
+        // Over capacity
+        let cbor = crate::to_vec(&"longer than eight").unwrap();
+        assert!(from_slice::<String<8>>(&cbor).is_err());
+        let cbor = crate::to_vec(&serde_bytes::Bytes::new(&[0; 5])).unwrap();
+        assert!(from_slice::<Hash>(&cbor).is_err());
+        let cbor = crate::to_vec(&serde_bytes::Bytes::new(&[0; 4])).unwrap();
+        assert_eq!(from_slice::<Hash>(&cbor).unwrap().0, [0; 4]);
+
+        // Indefinite-length arrays are read; strings would need joining
+        let cbor = [0x9f, 0x01, 0x02, 0xff];
+        assert_eq!(from_slice::<Vec<u8, 2>>(&cbor).unwrap(), [1, 2]);
+        let cbor = [0x7f, 0x61, b'a', 0xff];
+        assert!(from_slice::<String<8>>(&cbor).is_err());
+
+        // Tags are looked through, trailing bytes are rejected
+        assert_eq!(from_slice::<u8>(&[0xd8, 0x18, 0x07]).unwrap(), 7);
+        assert!(matches!(
+            from_slice::<u8>(&[0x07, 0x00]),
+            Err(Error::TrailingData)
+        ));
+    }
+}
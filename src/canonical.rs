@@ -0,0 +1,647 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Deterministic encoding helpers (RFC 8949 §4.2)
+//!
+//! These functions work directly on encoded bytes, so they handle every
+//! well-formed CBOR item (including integers outside the `i64` range and
+//! simple values) without going through [`Value`](crate::Value).
+//!
+//! The deterministic profile used here is the core one from RFC 8949 §4.2.1:
+//! - integers, lengths and tags use the shortest argument encoding
+//! - floats use the shortest width that preserves their value, and NaN is `0xf97e00`
+//! - indefinite-length items are converted to definite length
+//! - map entries are sorted by the bytewise order of their encoded keys
+
+use crate::{
+    Argument, Error, Result,
+    constants::*,
+    raw::{
+        CANONICAL_NAN, SliceReader, check_depth, float_from_arg, preferred_float_info,
+        u64_to_usize, write_header, write_preferred_float,
+    },
+};
+
+/// Deterministic encoding rule broken by an encoded item
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanonicalRule {
+    /// An integer, length or tag argument is not in its shortest form
+    NonMinimalArgument,
+    /// A string, array or map uses indefinite-length encoding
+    IndefiniteLength,
+    /// Map keys are not in bytewise lexicographic order of their encodings
+    UnsortedKeys,
+    /// A map contains the same key more than once
+    DuplicateKey,
+    /// A float is not in the shortest width that preserves its value,
+    /// or a NaN is not the canonical `0xf97e00`
+    NonPreferredFloat,
+}
+
+impl std::fmt::Display for CanonicalRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CanonicalRule::NonMinimalArgument => write!(f, "non-minimal integer encoding"),
+            CanonicalRule::IndefiniteLength => write!(f, "indefinite-length item"),
+            CanonicalRule::UnsortedKeys => write!(f, "unsorted map keys"),
+            CanonicalRule::DuplicateKey => write!(f, "duplicate map key"),
+            CanonicalRule::NonPreferredFloat => write!(f, "non-preferred float encoding"),
+        }
+    }
+}
+
+/// First deterministic encoding violation found in an encoded item
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CanonicalViolation {
+    /// Byte offset of the header of the offending item
+    pub offset: usize,
+    /// The rule that was broken
+    pub rule: CanonicalRule,
+}
+
+impl std::fmt::Display for CanonicalViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} at offset {}", self.rule, self.offset)
+    }
+}
+
+/// Compare two CBOR encodings for semantic equality
+///
+/// Both inputs are normalized to the deterministic profile before comparing, so
+/// differences in integer widths, float widths, indefinite-length encoding and
+/// map ordering are ignored.
+///
+/// Returns an error if either input is not a single well-formed CBOR item, or
+/// contains a map with duplicate keys.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::semantic_eq;
+///
+/// // {"a": 1, "b": 2} vs. an indefinite-length {"b": 2, "a": 1} with a wide integer
+/// let a = [0xa2, 0x61, 0x61, 0x01, 0x61, 0x62, 0x02];
+/// let b = [0xbf, 0x61, 0x62, 0x02, 0x61, 0x61, 0x18, 0x01, 0xff];
+/// assert!(semantic_eq(&a, &b).unwrap());
+/// ```
+pub fn semantic_eq(a: &[u8], b: &[u8]) -> Result<bool> {
+    Ok(canonicalize(a)? == canonicalize(b)?)
+}
+
+/// Re-encode a single CBOR item in deterministic form
+///
+/// Takes any well-formed CBOR item and emits its RFC 8949 deterministic
+/// encoding: shortest integer and length arguments, preferred (shortest
+/// lossless) floats, definite lengths and sorted map keys. Tags and simple
+/// values are preserved. Use this to normalize third-party data before hashing.
+///
+/// Returns an error if the input is not exactly one well-formed CBOR item, or
+/// if a map contains duplicate keys once normalized.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::canonicalize;
+///
+/// // Indefinite-length [1, 1.5] with a wide integer and a double
+/// let input = [
+///     0x9f, 0x19, 0x00, 0x01, 0xfb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff,
+/// ];
+/// assert_eq!(canonicalize(&input).unwrap(), [0x82, 0x01, 0xf9, 0x3e, 0x00]);
+/// ```
+pub fn canonicalize(bytes: &[u8]) -> Result<Vec<u8>> {
+    normalize(bytes, Profile::Core)
+}
+
+/// Deterministic profile applied by [`normalize`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Profile {
+    /// RFC 8949 §4.2.1 core deterministic encoding
+    Core,
+    /// Core rules plus dCBOR numeric reduction and simple value restrictions
+    Dcbor,
+}
+
+/// Re-encode a single CBOR item under `profile`
+pub(crate) fn normalize(bytes: &[u8], profile: Profile) -> Result<Vec<u8>> {
+    if bytes.is_empty() {
+        return Err(Error::Syntax("empty input".to_string()));
+    }
+
+    let mut reader = SliceReader::new(bytes);
+    let mut out = Vec::with_capacity(bytes.len());
+    normalize_item(&mut reader, &mut out, 0, profile)?;
+    if reader.remaining() > 0 {
+        return Err(Error::TrailingData);
+    }
+    Ok(out)
+}
+
+/// Returns true if `bytes` is a single item already in deterministic form
+///
+/// See [`check_canonical`] to find out which rule is broken and where.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::is_canonical;
+///
+/// assert!(is_canonical(&[0x18, 0x18]).unwrap()); // 24
+/// assert!(!is_canonical(&[0x18, 0x01]).unwrap()); // 1 with a one-byte argument
+/// ```
+pub fn is_canonical(bytes: &[u8]) -> Result<bool> {
+    Ok(check_canonical(bytes)?.is_none())
+}
+
+/// Find the first deterministic encoding violation in `bytes`
+///
+/// Walks the encoding in place without re-encoding it and returns `Ok(None)` if
+/// the item conforms to the deterministic profile. Otherwise returns the byte
+/// offset and rule of the first violation; the remainder of the input is not
+/// examined. Malformed input is reported as an error.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::{CanonicalRule, check_canonical};
+///
+/// // {"b": 1, "a": 2}
+/// let cbor = [0xa2, 0x61, b'b', 0x01, 0x61, b'a', 0x02];
+/// let violation = check_canonical(&cbor).unwrap().unwrap();
+/// assert_eq!(violation.rule, CanonicalRule::UnsortedKeys);
+/// assert_eq!(violation.offset, 4);
+/// ```
+pub fn check_canonical(bytes: &[u8]) -> Result<Option<CanonicalViolation>> {
+    if bytes.is_empty() {
+        return Err(Error::Syntax("empty input".to_string()));
+    }
+
+    let mut reader = SliceReader::new(bytes);
+    if let Some(violation) = check_item(&mut reader, bytes, 0)? {
+        return Ok(Some(violation));
+    }
+    if reader.remaining() > 0 {
+        return Err(Error::TrailingData);
+    }
+    Ok(None)
+}
+
+/// Read the content of a byte or text string, joining indefinite-length chunks
+fn read_string_content(
+    reader: &mut SliceReader<'_>,
+    major: u8,
+    arg: Option<u64>,
+) -> Result<Vec<u8>> {
+    let content = match arg {
+        Some(len) => read_chunk(reader, major, len)?.to_vec(),
+        None => {
+            let mut content = Vec::new();
+            while reader.peek_u8()? != BREAK {
+                let (chunk_major, _, chunk_len) = reader.read_header()?;
+                if chunk_major != major {
+                    return Err(Error::Syntax(
+                        "Indefinite string chunks must match the string type".to_string(),
+                    ));
+                }
+                let chunk_len = chunk_len.ok_or_else(|| {
+                    Error::Syntax("Indefinite string chunks cannot be indefinite".to_string())
+                })?;
+                content.extend_from_slice(read_chunk(reader, major, chunk_len)?);
+            }
+            reader.read_u8()?;
+            content
+        }
+    };
+    Ok(content)
+}
+
+fn read_chunk<'a>(reader: &mut SliceReader<'a>, major: u8, len: u64) -> Result<&'a [u8]> {
+    let len = u64_to_usize(len)?;
+    let chunk = reader.read_slice(len)?;
+    if major == MAJOR_TEXT {
+        std::str::from_utf8(chunk).map_err(|_| Error::InvalidUtf8)?;
+    }
+    Ok(chunk)
+}
+
+fn check_item(
+    reader: &mut SliceReader<'_>,
+    bytes: &[u8],
+    depth: usize,
+) -> Result<Option<CanonicalViolation>> {
+    let offset = reader.position();
+    let violation = |rule| Ok(Some(CanonicalViolation { offset, rule }));
+
+    let (major, info, arg) = reader.read_header()?;
+    if major != MAJOR_SIMPLE {
+        match arg {
+            Some(arg) if Argument::from_info(info, arg).is_some_and(|arg| !arg.is_preferred()) => {
+                return violation(CanonicalRule::NonMinimalArgument);
+            }
+            None if matches!(major, MAJOR_BYTES | MAJOR_TEXT | MAJOR_ARRAY | MAJOR_MAP) => {
+                return violation(CanonicalRule::IndefiniteLength);
+            }
+            None => return Err(Error::Syntax("Unexpected indefinite length".to_string())),
+            _ => {}
+        }
+    }
+
+    match (major, arg) {
+        (MAJOR_BYTES | MAJOR_TEXT, Some(len)) => {
+            read_chunk(reader, major, len)?;
+        }
+        (MAJOR_ARRAY, Some(len)) => {
+            check_depth(depth)?;
+            for _ in 0..len {
+                if let Some(v) = check_item(reader, bytes, depth + 1)? {
+                    return Ok(Some(v));
+                }
+            }
+        }
+        (MAJOR_MAP, Some(len)) => {
+            check_depth(depth)?;
+            let mut previous_key: Option<&[u8]> = None;
+            for _ in 0..len {
+                let key_start = reader.position();
+                if let Some(v) = check_item(reader, bytes, depth + 1)? {
+                    return Ok(Some(v));
+                }
+                let key = &bytes[key_start..reader.position()];
+                if let Some(previous) = previous_key {
+                    let rule = match previous.cmp(key) {
+                        std::cmp::Ordering::Less => None,
+                        std::cmp::Ordering::Equal => Some(CanonicalRule::DuplicateKey),
+                        std::cmp::Ordering::Greater => Some(CanonicalRule::UnsortedKeys),
+                    };
+                    if let Some(rule) = rule {
+                        return Ok(Some(CanonicalViolation {
+                            offset: key_start,
+                            rule,
+                        }));
+                    }
+                }
+                previous_key = Some(key);
+                if let Some(v) = check_item(reader, bytes, depth + 1)? {
+                    return Ok(Some(v));
+                }
+            }
+        }
+        (MAJOR_TAG, _) => {
+            check_depth(depth)?;
+            return check_item(reader, bytes, depth + 1);
+        }
+        (MAJOR_SIMPLE, _) => match (info, arg) {
+            (0..=23, _) => {}
+            (SIMPLE_VALUE, Some(val)) if val >= 32 => {}
+            (FLOAT16 | FLOAT32 | FLOAT64, Some(bits)) => {
+                let value = float_from_arg(info, bits);
+                let is_preferred = if value.is_nan() {
+                    info == FLOAT16 && bits == CANONICAL_NAN as u64
+                } else {
+                    info == preferred_float_info(value)
+                };
+                if !is_preferred {
+                    return violation(CanonicalRule::NonPreferredFloat);
+                }
+            }
+            (INDEFINITE, _) => return Err(Error::Syntax("Unexpected break".to_string())),
+            _ => return Err(Error::Syntax("Invalid simple value".to_string())),
+        },
+        _ => {}
+    }
+    Ok(None)
+}
+
+fn normalize_item(
+    reader: &mut SliceReader<'_>,
+    out: &mut Vec<u8>,
+    depth: usize,
+    profile: Profile,
+) -> Result<()> {
+    let (major, info, arg) = reader.read_header()?;
+    match major {
+        MAJOR_UNSIGNED | MAJOR_NEGATIVE => {
+            let val =
+                arg.ok_or_else(|| Error::Syntax("Integer cannot be indefinite".to_string()))?;
+            write_header(out, major, val);
+        }
+        MAJOR_BYTES | MAJOR_TEXT => {
+            let content = read_string_content(reader, major, arg)?;
+            write_header(out, major, content.len() as u64);
+            out.extend_from_slice(&content);
+        }
+        MAJOR_ARRAY => {
+            check_depth(depth)?;
+            let mut items = Vec::new();
+            let mut count = 0u64;
+            match arg {
+                Some(len) => {
+                    for _ in 0..len {
+                        normalize_item(reader, &mut items, depth + 1, profile)?;
+                    }
+                    count = len;
+                }
+                None => {
+                    while reader.peek_u8()? != BREAK {
+                        normalize_item(reader, &mut items, depth + 1, profile)?;
+                        count += 1;
+                    }
+                    reader.read_u8()?;
+                }
+            }
+            write_header(out, MAJOR_ARRAY, count);
+            out.extend_from_slice(&items);
+        }
+        MAJOR_MAP => {
+            check_depth(depth)?;
+            let mut entries = Vec::new();
+            let mut read_entry = |reader: &mut SliceReader<'_>| -> Result<()> {
+                let mut key = Vec::new();
+                let mut value = Vec::new();
+                normalize_item(reader, &mut key, depth + 1, profile)?;
+                normalize_item(reader, &mut value, depth + 1, profile)?;
+                entries.push((key, value));
+                Ok(())
+            };
+            match arg {
+                Some(len) => {
+                    for _ in 0..len {
+                        read_entry(reader)?;
+                    }
+                }
+                None => {
+                    while reader.peek_u8()? != BREAK {
+                        read_entry(reader)?;
+                    }
+                    reader.read_u8()?;
+                }
+            }
+
+            // Deterministic order is the bytewise order of the encoded keys
+            entries.sort();
+            if entries.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+                return Err(Error::Syntax("Duplicate map key".to_string()));
+            }
+            write_header(out, MAJOR_MAP, entries.len() as u64);
+            for (key, value) in entries {
+                out.extend_from_slice(&key);
+                out.extend_from_slice(&value);
+            }
+        }
+        MAJOR_TAG => {
+            check_depth(depth)?;
+            let tag = arg.ok_or_else(|| Error::Syntax("Tag cannot be indefinite".to_string()))?;
+            write_header(out, MAJOR_TAG, tag);
+            normalize_item(reader, out, depth + 1, profile)?;
+        }
+        _ if profile == Profile::Dcbor => match (info, arg) {
+            (FALSE | TRUE | NULL, _) => out.push((MAJOR_SIMPLE << 5) | info),
+            (FLOAT16 | FLOAT32 | FLOAT64, Some(bits)) => {
+                write_reduced_float(out, float_from_arg(info, bits))
+            }
+            (INDEFINITE, _) => return Err(Error::Syntax("Unexpected break".to_string())),
+            (0..=23, _) | (SIMPLE_VALUE, Some(32..)) => {
+                let value = arg.unwrap_or(info as u64);
+                return Err(Error::Syntax(format!(
+                    "dCBOR does not allow simple value {}",
+                    value
+                )));
+            }
+            _ => return Err(Error::Syntax("Invalid simple value".to_string())),
+        },
+        _ => match (info, arg) {
+            (0..=23, _) => out.push((MAJOR_SIMPLE << 5) | info),
+            (SIMPLE_VALUE, Some(val)) if val >= 32 => {
+                out.extend_from_slice(&[(MAJOR_SIMPLE << 5) | SIMPLE_VALUE, val as u8])
+            }
+            (FLOAT16 | FLOAT32 | FLOAT64, Some(bits)) => {
+                write_preferred_float(out, float_from_arg(info, bits))
+            }
+            (INDEFINITE, _) => return Err(Error::Syntax("Unexpected break".to_string())),
+            _ => return Err(Error::Syntax("Invalid simple value".to_string())),
+        },
+    }
+    Ok(())
+}
+
+/// Write a float under dCBOR numeric reduction
+///
+/// Integral values that fit a CBOR integer (including `-0.0`) are written as
+/// integers; everything else uses the preferred float encoding.
+fn write_reduced_float(out: &mut Vec<u8>, v: f64) {
+    // 2^64, the first value beyond the range of major types 0 and 1
+    const LIMIT: f64 = 18446744073709551616.0;
+    if v.fract() != 0.0 || !(-LIMIT..LIMIT).contains(&v) {
+        // Also covers NaN and the infinities, for which fract() is NaN
+        write_preferred_float(out, v);
+    } else if v >= 0.0 {
+        write_header(out, MAJOR_UNSIGNED, v as u64);
+    } else if v == -LIMIT {
+        write_header(out, MAJOR_NEGATIVE, u64::MAX);
+    } else {
+        // -v is exact here, unlike -1 - v for large magnitudes
+        write_header(out, MAJOR_NEGATIVE, (-v) as u64 - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::to_vec;
+
+    #[test]
+    fn test_semantic_eq_integer_widths() {
+        // 1 encoded in 1, 2, 3, 5 and 9 bytes
+        let forms: [&[u8]; 5] = [
+            &[0x01],
+            &[0x18, 0x01],
+            &[0x19, 0x00, 0x01],
+            &[0x1a, 0x00, 0x00, 0x00, 0x01],
+            &[0x1b, 0, 0, 0, 0, 0, 0, 0, 0x01],
+        ];
+        for form in forms {
+            assert!(semantic_eq(&[0x01], form).unwrap());
+        }
+        assert!(!semantic_eq(&[0x01], &[0x02]).unwrap());
+        // 0 and -1 share an argument but not a major type
+        assert!(!semantic_eq(&[0x00], &[0x20]).unwrap());
+    }
+
+    #[test]
+    fn test_semantic_eq_indefinite_lengths() {
+        // "hello" as one chunk vs. indefinite "hel" + "lo"
+        let definite = to_vec(&"hello").unwrap();
+        let chunked = [0x7f, 0x63, b'h', b'e', b'l', 0x62, b'l', b'o', 0xff];
+        assert!(semantic_eq(&definite, &chunked).unwrap());
+
+        let definite = to_vec(&vec![1, 2, 3]).unwrap();
+        let indefinite = [0x9f, 0x01, 0x02, 0x03, 0xff];
+        assert!(semantic_eq(&definite, &indefinite).unwrap());
+    }
+
+    #[test]
+    fn test_semantic_eq_map_order() {
+        let mut map = BTreeMap::new();
+        map.insert("a".to_string(), 1);
+        map.insert("bb".to_string(), 2);
+        let encoded = to_vec(&map).unwrap();
+
+        // Same entries in the opposite order
+        let reordered = [0xa2, 0x62, b'b', b'b', 0x02, 0x61, b'a', 0x01];
+        assert!(semantic_eq(&encoded, &reordered).unwrap());
+    }
+
+    #[test]
+    fn test_semantic_eq_float_widths() {
+        let f64_form = to_vec(&1.5f64).unwrap();
+        let f32_form = to_vec(&1.5f32).unwrap();
+        let f16_form = [0xf9, 0x3e, 0x00];
+        assert!(semantic_eq(&f64_form, &f32_form).unwrap());
+        assert!(semantic_eq(&f64_form, &f16_form).unwrap());
+
+        // All NaNs compare equal after normalization
+        let nan64 = to_vec(&f64::NAN).unwrap();
+        assert!(semantic_eq(&nan64, &[0xf9, 0x7e, 0x00]).unwrap());
+
+        // Floats and integers stay distinct
+        assert!(!semantic_eq(&f16_form, &[0x01]).unwrap());
+    }
+
+    #[test]
+    fn test_canonicalize_sorts_keys_bytewise() {
+        // Bytewise order puts shorter encoded keys first: 10 < -1 < "z" < "aa"
+        let mut input = vec![0xa4];
+        input.extend_from_slice(&[0x62, b'a', b'a', 0x01]);
+        input.extend_from_slice(&[0x61, b'z', 0x02]);
+        input.extend_from_slice(&[0x20, 0x03]);
+        input.extend_from_slice(&[0x0a, 0x04]);
+
+        let expected = [
+            0xa4, 0x0a, 0x04, 0x20, 0x03, 0x61, b'z', 0x02, 0x62, b'a', b'a', 0x01,
+        ];
+        assert_eq!(canonicalize(&input).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_canonicalize_preserves_tags_and_simple_values() {
+        // Tag 1 with a wide argument around an f64 that fits in f32
+        let input = [
+            0xd9, 0x00, 0x01, 0xfb, 0x41, 0xd6, 0x5f, 0xfc, 0x60, 0x00, 0x00, 0x00,
+        ];
+        let output = canonicalize(&input).unwrap();
+        assert_eq!(output[0], 0xc1);
+        assert_eq!(output[1], 0xfa);
+        assert_eq!(output.len(), 6);
+
+        // undefined and simple(255) pass through unchanged
+        assert_eq!(canonicalize(&[0xf7]).unwrap(), [0xf7]);
+        assert_eq!(canonicalize(&[0xf8, 0xff]).unwrap(), [0xf8, 0xff]);
+        // simple values below 32 must use the one-byte form
+        assert!(canonicalize(&[0xf8, 0x10]).is_err());
+    }
+
+    #[test]
+    fn test_canonicalize_large_integers() {
+        // u64::MAX and -2^64 are outside the range of Value but still canonicalize
+        let max = [0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+        assert_eq!(canonicalize(&max).unwrap(), max);
+        let min = [0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+        assert_eq!(canonicalize(&min).unwrap(), min);
+    }
+
+    #[test]
+    fn test_canonicalize_is_idempotent() {
+        let input = [
+            0xbf, 0x61, b'b', 0x9f, 0x18, 0x05, 0xff, 0x61, b'a', 0xf6, 0xff,
+        ];
+        let once = canonicalize(&input).unwrap();
+        assert_eq!(canonicalize(&once).unwrap(), once);
+        assert_eq!(once, [0xa2, 0x61, b'a', 0xf6, 0x61, b'b', 0x81, 0x05]);
+    }
+
+    #[test]
+    fn test_check_canonical_reports_rule_and_offset() {
+        let check = |bytes: &[u8]| check_canonical(bytes).unwrap();
+
+        // [1, 24 in two bytes]
+        assert_eq!(
+            check(&[0x82, 0x01, 0x19, 0x00, 0x18]),
+            Some(CanonicalViolation {
+                offset: 2,
+                rule: CanonicalRule::NonMinimalArgument
+            })
+        );
+        // Indefinite-length array
+        assert_eq!(
+            check(&[0x9f, 0xff]).map(|v| v.rule),
+            Some(CanonicalRule::IndefiniteLength)
+        );
+        // 1.5 as f32
+        assert_eq!(
+            check(&to_vec(&1.5f32).unwrap()).map(|v| v.rule),
+            Some(CanonicalRule::NonPreferredFloat)
+        );
+        // Signalling NaN in half precision
+        assert_eq!(
+            check(&[0xf9, 0x7c, 0x01]).map(|v| v.rule),
+            Some(CanonicalRule::NonPreferredFloat)
+        );
+        // {1: 0, 1: 0}
+        assert_eq!(
+            check(&[0xa2, 0x01, 0x00, 0x01, 0x00]),
+            Some(CanonicalViolation {
+                offset: 3,
+                rule: CanonicalRule::DuplicateKey
+            })
+        );
+        // Non-minimal tag number
+        assert_eq!(
+            check(&[0xd8, 0x01, 0x00]).map(|v| v.rule),
+            Some(CanonicalRule::NonMinimalArgument)
+        );
+    }
+
+    #[test]
+    fn test_is_canonical_accepts_canonical_output() {
+        let inputs: [&[u8]; 3] = [
+            &[
+                0xbf, 0x61, b'b', 0x9f, 0x18, 0x05, 0xff, 0x61, b'a', 0xf6, 0xff,
+            ],
+            &[0xfb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0],
+            &[0xd9, 0x00, 0x20, 0x7f, 0x61, b'x', 0xff],
+        ];
+        for input in inputs {
+            assert!(!is_canonical(input).unwrap());
+            assert!(is_canonical(&canonicalize(input).unwrap()).unwrap());
+        }
+        assert!(is_canonical(&[0xf7]).unwrap());
+        assert!(is_canonical(&[0xf9, 0x7e, 0x00]).unwrap());
+    }
+
+    #[test]
+    fn test_check_canonical_malformed_input() {
+        assert!(check_canonical(&[]).is_err());
+        assert!(check_canonical(&[0x82, 0x01]).is_err());
+        assert!(check_canonical(&[0x01, 0x01]).is_err());
+        assert!(check_canonical(&[0x61, 0xff]).is_err());
+        assert!(check_canonical(&[0xff]).is_err());
+    }
+
+    #[test]
+    fn test_semantic_eq_errors() {
+        assert!(semantic_eq(&[], &[0x01]).is_err());
+        assert!(semantic_eq(&[0x01, 0x02], &[0x01]).is_err());
+        assert!(semantic_eq(&[0x62, b'a'], &[0x01]).is_err());
+        // Duplicate key once normalized
+        let dup = [0xa2, 0x01, 0x00, 0x18, 0x01, 0x00];
+        assert!(semantic_eq(&dup, &[0xa0]).is_err());
+    }
+}
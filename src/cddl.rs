@@ -0,0 +1,1993 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! CDDL schema validation (RFC 8610)
+//!
+//! Enabled with the `cddl` feature. [`validate_cddl`] checks an encoded item
+//! against the first rule of a CDDL schema and reports every [`Violation`]
+//! with its location. [`Schema`] parses a schema once for repeated use, and
+//! can validate against any of its rules.
+//!
+//! The supported subset covers what the C2PA specification uses:
+//! - maps with bareword, literal and type keys, and arrays, including
+//!   occurrence indicators (`?`, `*`, `+`, `n*m`) and inline groups
+//! - type choices (`/`), group choices (`//`) and sockets extended with
+//!   `/=` and `//=`
+//! - integer and float ranges (`..` and `...`), including named bounds
+//! - the `.size`, `.lt`, `.le`, `.gt`, `.ge`, `.eq`, `.ne`, `.cbor`, `.and`,
+//!   `.within` and `.default` controls
+//! - tags (`#6.n(type)`), major types (`#m.n`), `~` unwrapping and `&`
+//!   value enumerations
+//! - the standard prelude (`tstr`, `uint`, `tdate`, `float32`, ...)
+//!
+//! Generic rules and the `.regexp`, `.bits` and `.cborseq` controls are not
+//! supported and are reported when the schema is parsed. The item is read
+//! with its tags, full 64-bit integers, float widths and map entries as
+//! encoded, so `float16` only matches half precision floats and duplicate
+//! keys are reported.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::cddl::validate_cddl;
+//!
+//! let schema = r#"
+//!     hashed-uri-map = {
+//!         "url": tstr,
+//!         ? "alg": tstr .size (1..32),
+//!         "hash": bstr,
+//!     }
+//! "#;
+//!
+//! // {"url": "self#jumbf=c2pa", "hash": h'00'}
+//! let mut bytes = vec![0xa2, 0x63, b'u', b'r', b'l', 0x6f];
+//! bytes.extend_from_slice(b"self#jumbf=c2pa");
+//! bytes.extend_from_slice(&[0x64, b'h', b'a', b's', b'h', 0x41, 0x00]);
+//! assert!(validate_cddl(schema, &bytes).is_ok());
+//!
+//! // {"url": 1}
+//! let violations = validate_cddl(schema, &[0xa1, 0x63, b'u', b'r', b'l', 0x01]).unwrap_err();
+//! assert_eq!(violations.len(), 2);
+//! assert_eq!(violations[0].to_string(), "/url: expected tstr, found integer 1");
+//! assert_eq!(violations[1].to_string(), "/: missing key \"hash\"");
+//! ```
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+};
+
+use crate::{
+    Error, PathSegment, Result,
+    constants::*,
+    raw::{SliceReader, check_depth, float_from_arg, u64_to_usize},
+};
+
+/// Rules every schema can refer to, from RFC 8610 Appendix D
+///
+/// `any`, `uint`, `nint`, `bstr`, `tstr`, `float16`, `float32`, `float64`,
+/// `false`, `true`, `nil` and `undefined` are built in.
+const PRELUDE: &str = r#"
+bool = false / true
+int = uint / nint
+integer = int / bigint
+unsigned = uint / biguint
+number = int / float
+float16-32 = float16 / float32
+float32-64 = float32 / float64
+float = float16-32 / float64
+bytes = bstr
+text = tstr
+null = nil
+tdate = #6.0(tstr)
+time = #6.1(number)
+biguint = #6.2(bstr)
+bignint = #6.3(bstr)
+bigint = biguint / bignint
+decfrac = #6.4([e10: int, m: integer])
+bigfloat = #6.5([e2: int, m: integer])
+eb64url = #6.21(any)
+eb64legacy = #6.22(any)
+eb16 = #6.23(any)
+encoded-cbor = #6.24(bstr)
+uri = #6.32(tstr)
+b64url = #6.33(tstr)
+b64legacy = #6.34(tstr)
+regexp = #6.35(tstr)
+mime-message = #6.36(tstr)
+cbor-any = #6.55799(any)
+"#;
+
+const BUILT_IN: &[&str] = &[
+    "any",
+    "uint",
+    "nint",
+    "bstr",
+    "tstr",
+    "float16",
+    "float32",
+    "float64",
+    "false",
+    "true",
+    "nil",
+    "undefined",
+];
+
+/// Limit on rule references followed without reaching a new item, which
+/// stops schemas like `a = b  b = a` from recursing forever
+const MAX_RULE_DEPTH: usize = 256;
+
+/// A place where the data doesn't match the schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Location of the offending item, empty for the top-level item
+    pub path: Vec<PathSegment>,
+    /// What was expected and what was found
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "/")?;
+        }
+        for segment in &self.path {
+            match segment {
+                PathSegment::Key(key) => write!(f, "/{}", key)?,
+                PathSegment::IntKey(key) => write!(f, "/{}", key)?,
+                PathSegment::OtherKey => write!(f, "/?")?,
+                PathSegment::Index(index) => write!(f, "/{}", index)?,
+            }
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+fn violation(path: &[PathSegment], message: String) -> Vec<Violation> {
+    vec![Violation {
+        path: path.to_vec(),
+        message,
+    }]
+}
+
+/// Check `bytes` against the first rule of the CDDL `schema`
+///
+/// An invalid schema is reported as a single violation at the top level. Use
+/// [`Schema::parse`] to tell schema errors apart, or to validate many items.
+pub fn validate_cddl(schema: &str, bytes: &[u8]) -> std::result::Result<(), Vec<Violation>> {
+    let schema =
+        Schema::parse(schema).map_err(|e| violation(&[], format!("invalid schema: {}", e)))?;
+    schema.validate(bytes)
+}
+
+/// A parsed CDDL schema
+#[derive(Debug, Clone)]
+pub struct Schema {
+    rules: HashMap<String, Type>,
+    root: String,
+}
+
+impl Schema {
+    /// Parse a CDDL schema whose first rule is the root
+    ///
+    /// Fails with [`Error::Syntax`] for malformed or unsupported CDDL, and for
+    /// references to rules that are never defined. Sockets (`$name` and
+    /// `$$name`) may be left undefined, in which case nothing matches them.
+    pub fn parse(cddl: &str) -> Result<Schema> {
+        let mut rules = HashMap::new();
+        let mut references = Vec::new();
+        let prelude = Parser::new(PRELUDE)?.parse_rules(&mut references)?;
+        for (name, assign, ty) in prelude {
+            add_rule(&mut rules, name, assign, ty)?;
+        }
+        let user = Parser::new(cddl)?.parse_rules(&mut references)?;
+        let root = user
+            .first()
+            .map(|(name, _, _)| name.clone())
+            .ok_or_else(|| Error::Syntax("CDDL schema has no rules".to_string()))?;
+        for (name, assign, ty) in user {
+            add_rule(&mut rules, name, assign, ty)?;
+        }
+        for (name, line) in references {
+            if !rules.contains_key(&name)
+                && !BUILT_IN.contains(&name.as_str())
+                && !name.starts_with('$')
+            {
+                return Err(Error::Syntax(format!(
+                    "CDDL line {}: undefined rule {}",
+                    line, name
+                )));
+            }
+        }
+        Ok(Schema { rules, root })
+    }
+
+    /// Name of the rule [`validate`](Self::validate) checks against
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    /// Check that `bytes` holds exactly one item matching the root rule
+    pub fn validate(&self, bytes: &[u8]) -> std::result::Result<(), Vec<Violation>> {
+        self.validate_rule(&self.root, bytes)
+    }
+
+    /// Check that `bytes` holds exactly one item matching the rule `name`
+    pub fn validate_rule(
+        &self,
+        name: &str,
+        bytes: &[u8],
+    ) -> std::result::Result<(), Vec<Violation>> {
+        if !self.rules.contains_key(name) && !BUILT_IN.contains(&name) {
+            return Err(violation(&[], format!("no rule named {}", name)));
+        }
+        let mut reader = SliceReader::new(bytes);
+        let item = read_item(&mut reader, 0)
+            .map_err(|e| violation(&[], format!("malformed CBOR: {}", e)))?;
+        if reader.remaining() > 0 {
+            return Err(violation(&[], "trailing data after the item".to_string()));
+        }
+        let violations = self.check(&Type::Name(name.to_string()), &item, &mut Vec::new(), 0);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+// Schema model
+
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    Int(i128),
+    Float(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Control {
+    Size,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    Cbor,
+    And,
+    Within,
+    Default,
+}
+
+impl Control {
+    fn name(self) -> &'static str {
+        match self {
+            Control::Size => "size",
+            Control::Lt => "lt",
+            Control::Le => "le",
+            Control::Gt => "gt",
+            Control::Ge => "ge",
+            Control::Eq => "eq",
+            Control::Ne => "ne",
+            Control::Cbor => "cbor",
+            Control::And => "and",
+            Control::Within => "within",
+            Control::Default => "default",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Type {
+    Any,
+    Literal(Literal),
+    Name(String),
+    Choice(Vec<Type>),
+    /// Bounds and whether the upper bound is included
+    Range(Box<Type>, Box<Type>, bool),
+    Control(Box<Type>, Control, Box<Type>),
+    Map(Group),
+    Array(Group),
+    /// A parenthesized group, usable only inside a map or array
+    Group(Group),
+    Tag(Option<u64>, Box<Type>),
+    Major(u8, Option<u64>),
+    Unwrap(String),
+    /// Any value type from the entries of a group
+    Enum(Box<Type>),
+}
+
+#[derive(Debug, Clone)]
+struct Group {
+    choices: Vec<Vec<Entry>>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    min: usize,
+    max: usize,
+    key: Option<Type>,
+    value: Type,
+}
+
+impl Group {
+    fn single(entry: Entry) -> Group {
+        Group {
+            choices: vec![vec![entry]],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Assign {
+    /// `=`
+    Define,
+    /// `/=`
+    TypeChoice,
+    /// `//=`
+    GroupChoice,
+}
+
+fn add_rule(
+    rules: &mut HashMap<String, Type>,
+    name: String,
+    assign: Assign,
+    ty: Type,
+) -> Result<()> {
+    let ty = match (assign, rules.remove(&name)) {
+        (Assign::Define, _) | (Assign::TypeChoice, None) => ty,
+        (Assign::TypeChoice, Some(Type::Choice(mut alternatives))) => {
+            alternatives.push(ty);
+            Type::Choice(alternatives)
+        }
+        (Assign::TypeChoice, Some(existing)) => Type::Choice(vec![existing, ty]),
+        (Assign::GroupChoice, existing) => {
+            let group = match ty {
+                Type::Group(group) => group,
+                ty => Group::single(Entry {
+                    min: 1,
+                    max: 1,
+                    key: None,
+                    value: ty,
+                }),
+            };
+            match existing {
+                None => Type::Group(group),
+                Some(Type::Group(mut existing)) => {
+                    existing.choices.extend(group.choices);
+                    Type::Group(existing)
+                }
+                Some(_) => {
+                    return Err(Error::Syntax(format!(
+                        "CDDL rule {} is a type and can't be extended with //=",
+                        name
+                    )));
+                }
+            }
+        }
+    };
+    rules.insert(name, ty);
+    Ok(())
+}
+
+fn describe_literal(literal: &Literal) -> String {
+    match literal {
+        Literal::Int(n) => n.to_string(),
+        Literal::Float(f) => format!("{:?}", f),
+        Literal::Text(s) => format!("{:?}", s),
+        Literal::Bytes(b) => {
+            let hex: String = b.iter().map(|b| format!("{:02x}", b)).collect();
+            format!("h'{}'", hex)
+        }
+    }
+}
+
+fn describe(ty: &Type) -> String {
+    match ty {
+        Type::Any => "any".to_string(),
+        Type::Literal(literal) => describe_literal(literal),
+        Type::Name(name) => name.clone(),
+        Type::Choice(alternatives) => alternatives
+            .iter()
+            .map(describe)
+            .collect::<Vec<_>>()
+            .join(" / "),
+        Type::Range(min, max, inclusive) => format!(
+            "{}{}{}",
+            describe(min),
+            if *inclusive { ".." } else { "..." },
+            describe(max)
+        ),
+        Type::Control(target, control, arg) => {
+            format!("{} .{} {}", describe(target), control.name(), describe(arg))
+        }
+        Type::Map(_) => "map".to_string(),
+        Type::Array(_) => "array".to_string(),
+        Type::Group(_) => "group".to_string(),
+        Type::Tag(Some(tag), content) => format!("#6.{}({})", tag, describe(content)),
+        Type::Tag(None, content) => format!("#6({})", describe(content)),
+        Type::Major(major, Some(arg)) => format!("#{}.{}", major, arg),
+        Type::Major(major, None) => format!("#{}", major),
+        Type::Unwrap(name) => format!("~{}", name),
+        Type::Enum(group) => format!("&{}", describe(group)),
+    }
+}
+
+// Lexer
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Int(i128),
+    Float(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+    /// `#`, `#6`, `#6.32`
+    Hash(Option<u8>, Option<u64>),
+    Control(String),
+    Assign(Assign),
+    Slash,
+    DoubleSlash,
+    /// `..` (inclusive) or `...`
+    Range(bool),
+    Open(char),
+    Close(char),
+    Comma,
+    Colon,
+    Arrow,
+    Question,
+    Star,
+    Plus,
+    Caret,
+    Tilde,
+    Amp,
+}
+
+struct Lexed {
+    token: Token,
+    line: usize,
+    start: usize,
+    end: usize,
+}
+
+fn is_name_start(c: u8) -> bool {
+    c.is_ascii_alphabetic() || matches!(c, b'@' | b'_' | b'$')
+}
+
+fn is_name_char(c: u8) -> bool {
+    is_name_start(c) || c.is_ascii_digit() || matches!(c, b'-' | b'.')
+}
+
+fn lex(source: &str) -> Result<Vec<Lexed>> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    let mut line = 1;
+    let error =
+        |line: usize, message: String| Error::Syntax(format!("CDDL line {}: {}", line, message));
+
+    while pos < bytes.len() {
+        let c = bytes[pos];
+        let start = pos;
+        let next = bytes.get(pos + 1).copied();
+        let token = match c {
+            b'\n' => {
+                line += 1;
+                pos += 1;
+                continue;
+            }
+            _ if c.is_ascii_whitespace() => {
+                pos += 1;
+                continue;
+            }
+            b';' => {
+                while pos < bytes.len() && bytes[pos] != b'\n' {
+                    pos += 1;
+                }
+                continue;
+            }
+            b'"' => {
+                let mut text = String::new();
+                pos += 1;
+                loop {
+                    let Some(&c) = bytes.get(pos) else {
+                        return Err(error(line, "unterminated text string".to_string()));
+                    };
+                    pos += 1;
+                    match c {
+                        b'"' => break,
+                        b'\\' => {
+                            let escaped = bytes.get(pos).copied().unwrap_or(b'"');
+                            pos += 1;
+                            match escaped {
+                                b'n' => text.push('\n'),
+                                b'r' => text.push('\r'),
+                                b't' => text.push('\t'),
+                                b'u' => {
+                                    let code = source
+                                        .get(pos..pos + 4)
+                                        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                                        .and_then(char::from_u32)
+                                        .ok_or_else(|| {
+                                            error(line, "invalid \\u escape".to_string())
+                                        })?;
+                                    text.push(code);
+                                    pos += 4;
+                                }
+                                other => text.push(other as char),
+                            }
+                        }
+                        _ => {
+                            // Copy the whole UTF-8 sequence starting here
+                            let len = source[pos - 1..].chars().next().map_or(1, char::len_utf8);
+                            text.push_str(&source[pos - 1..pos - 1 + len]);
+                            pos += len - 1;
+                        }
+                    }
+                }
+                Token::Text(text)
+            }
+            b'h' if next == Some(b'\'') => {
+                let end = source[pos + 2..]
+                    .find('\'')
+                    .ok_or_else(|| error(line, "unterminated byte string".to_string()))?;
+                let hex: String = source[pos + 2..pos + 2 + end]
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .collect();
+                if !hex.len().is_multiple_of(2) {
+                    return Err(error(line, "odd number of hex digits".to_string()));
+                }
+                let decoded = (0..hex.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+                    .collect::<std::result::Result<Vec<u8>, _>>()
+                    .map_err(|_| error(line, "invalid hex byte string".to_string()))?;
+                pos += end + 3;
+                Token::Bytes(decoded)
+            }
+            b'\'' => {
+                let end = source[pos + 1..]
+                    .find('\'')
+                    .ok_or_else(|| error(line, "unterminated byte string".to_string()))?;
+                let content = source.as_bytes()[pos + 1..pos + 1 + end].to_vec();
+                pos += end + 2;
+                Token::Bytes(content)
+            }
+            _ if c.is_ascii_digit() || (c == b'-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                pos += 1;
+                let radix = match (c, next) {
+                    (b'0', Some(b'x')) => 16,
+                    (b'0', Some(b'b')) => 2,
+                    _ => 10,
+                };
+                if radix != 10 {
+                    pos += 1;
+                }
+                let mut is_float = false;
+                while pos < bytes.len() {
+                    let d = bytes[pos];
+                    let in_number = if radix == 16 {
+                        d.is_ascii_hexdigit()
+                    } else if d == b'.' {
+                        // A fraction, not the start of a range
+                        let fraction = bytes.get(pos + 1).is_some_and(|n| n.is_ascii_digit());
+                        is_float |= fraction;
+                        fraction && radix == 10
+                    } else if (d == b'e' || d == b'E') && radix == 10 {
+                        is_float = true;
+                        if matches!(bytes.get(pos + 1), Some(b'+' | b'-')) {
+                            pos += 1;
+                        }
+                        true
+                    } else {
+                        d.is_ascii_digit()
+                    };
+                    if !in_number {
+                        break;
+                    }
+                    pos += 1;
+                }
+                let text = &source[start..pos];
+                let invalid = || error(line, format!("invalid number {}", text));
+                if is_float {
+                    Token::Float(text.parse().map_err(|_| invalid())?)
+                } else if radix == 10 {
+                    Token::Int(text.parse().map_err(|_| invalid())?)
+                } else {
+                    let (sign, digits) = match text.strip_prefix('-') {
+                        Some(digits) => (-1, digits),
+                        None => (1, text),
+                    };
+                    Token::Int(
+                        sign * i128::from_str_radix(&digits[2..], radix).map_err(|_| invalid())?,
+                    )
+                }
+            }
+            _ if is_name_start(c) => {
+                while pos < bytes.len() && is_name_char(bytes[pos]) {
+                    pos += 1;
+                }
+                // Names can't end in '-' or '.'
+                while matches!(bytes[pos - 1], b'-' | b'.') {
+                    pos -= 1;
+                }
+                Token::Name(source[start..pos].to_string())
+            }
+            b'.' if next == Some(b'.') => {
+                let exclusive = bytes.get(pos + 2) == Some(&b'.');
+                pos += if exclusive { 3 } else { 2 };
+                Token::Range(!exclusive)
+            }
+            b'.' if next.is_some_and(|n| n.is_ascii_alphabetic()) => {
+                pos += 1;
+                while pos < bytes.len()
+                    && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'-')
+                {
+                    pos += 1;
+                }
+                Token::Control(source[start + 1..pos].to_string())
+            }
+            b'#' => {
+                pos += 1;
+                let major = bytes
+                    .get(pos)
+                    .filter(|d| d.is_ascii_digit())
+                    .map(|d| d - b'0');
+                let mut arg = None;
+                if major.is_some() {
+                    pos += 1;
+                    if bytes.get(pos) == Some(&b'.') {
+                        let digits = bytes[pos + 1..]
+                            .iter()
+                            .take_while(|d| d.is_ascii_digit())
+                            .count();
+                        arg =
+                            Some(source[pos + 1..pos + 1 + digits].parse().map_err(|_| {
+                                error(line, "invalid major type argument".to_string())
+                            })?);
+                        pos += digits + 1;
+                    }
+                }
+                Token::Hash(major, arg)
+            }
+            b'/' => {
+                let (token, len) = match (next, bytes.get(pos + 2)) {
+                    (Some(b'/'), Some(b'=')) => (Token::Assign(Assign::GroupChoice), 3),
+                    (Some(b'/'), _) => (Token::DoubleSlash, 2),
+                    (Some(b'='), _) => (Token::Assign(Assign::TypeChoice), 2),
+                    _ => (Token::Slash, 1),
+                };
+                pos += len;
+                token
+            }
+            b'=' if next == Some(b'>') => {
+                pos += 2;
+                Token::Arrow
+            }
+            _ => {
+                pos += 1;
+                match c {
+                    b'=' => Token::Assign(Assign::Define),
+                    b'(' | b'{' | b'[' => Token::Open(c as char),
+                    b')' | b'}' | b']' => Token::Close(c as char),
+                    b',' => Token::Comma,
+                    b':' => Token::Colon,
+                    b'?' => Token::Question,
+                    b'*' => Token::Star,
+                    b'+' => Token::Plus,
+                    b'^' => Token::Caret,
+                    b'~' => Token::Tilde,
+                    b'&' => Token::Amp,
+                    b'<' => return Err(error(line, "generic rules are not supported".to_string())),
+                    _ => {
+                        let c = source[start..].chars().next().unwrap_or('?');
+                        return Err(error(line, format!("unexpected character {:?}", c)));
+                    }
+                }
+            }
+        };
+        tokens.push(Lexed {
+            token,
+            line,
+            start,
+            end: pos,
+        });
+    }
+    Ok(tokens)
+}
+
+// Parser
+
+struct Parser {
+    tokens: Vec<Lexed>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(source: &str) -> Result<Parser> {
+        Ok(Parser {
+            tokens: lex(source)?,
+            pos: 0,
+        })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|t| &t.token)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset).map(|t| &t.token)
+    }
+
+    /// Whether the next two tokens are written without space between them
+    fn adjacent(&self) -> bool {
+        match (self.tokens.get(self.pos), self.tokens.get(self.pos + 1)) {
+            (Some(a), Some(b)) => a.end == b.start,
+            _ => false,
+        }
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map_or(1, |t| t.line)
+    }
+
+    fn error(&self, message: impl fmt::Display) -> Error {
+        Error::Syntax(format!("CDDL line {}: {}", self.line(), message))
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .map(|t| t.token.clone())
+            .ok_or_else(|| self.error("unexpected end of schema"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<()> {
+        if self.eat(&token) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {:?}, found {:?}", token, self.peek())))
+        }
+    }
+
+    fn parse_rules(
+        &mut self,
+        references: &mut Vec<(String, usize)>,
+    ) -> Result<Vec<(String, Assign, Type)>> {
+        let mut rules = Vec::new();
+        while self.peek().is_some() {
+            let name = match self.next()? {
+                Token::Name(name) => name,
+                other => return Err(self.error(format!("expected a rule name, found {:?}", other))),
+            };
+            let assign = match self.next()? {
+                Token::Assign(assign) => assign,
+                other => {
+                    return Err(self.error(format!("expected = after {}, found {:?}", name, other)));
+                }
+            };
+            let entry = self.parse_entry(references)?;
+            let ty = if entry.key.is_none() && (entry.min, entry.max) == (1, 1) {
+                entry.value
+            } else {
+                Type::Group(Group::single(entry))
+            };
+            rules.push((name, assign, ty));
+        }
+        Ok(rules)
+    }
+
+    fn parse_occurrence(&mut self) -> (usize, usize) {
+        let bound = |n: i128| usize::try_from(n).unwrap_or(usize::MAX);
+        match self.peek() {
+            Some(Token::Question) => {
+                self.pos += 1;
+                (0, 1)
+            }
+            Some(Token::Plus) => {
+                self.pos += 1;
+                (1, usize::MAX)
+            }
+            Some(Token::Star) => {
+                let adjacent = self.adjacent();
+                self.pos += 1;
+                match self.peek() {
+                    Some(&Token::Int(max)) if adjacent => {
+                        self.pos += 1;
+                        (0, bound(max))
+                    }
+                    _ => (0, usize::MAX),
+                }
+            }
+            Some(&Token::Int(min)) if self.adjacent() && self.peek_at(1) == Some(&Token::Star) => {
+                self.pos += 1;
+                let adjacent = self.adjacent();
+                self.pos += 1;
+                match self.peek() {
+                    Some(&Token::Int(max)) if adjacent => {
+                        self.pos += 1;
+                        (bound(min), bound(max))
+                    }
+                    _ => (bound(min), usize::MAX),
+                }
+            }
+            _ => (1, 1),
+        }
+    }
+
+    fn parse_entry(&mut self, references: &mut Vec<(String, usize)>) -> Result<Entry> {
+        let (min, max) = self.parse_occurrence();
+        if let Some(Token::Name(name)) = self.peek()
+            && self.peek_at(1) == Some(&Token::Colon)
+        {
+            let key = Type::Literal(Literal::Text(name.clone()));
+            self.pos += 2;
+            let value = self.parse_type(references)?;
+            return Ok(Entry {
+                min,
+                max,
+                key: Some(key),
+                value,
+            });
+        }
+        let first = self.parse_type(references)?;
+        let cut = self.eat(&Token::Caret);
+        if self.eat(&Token::Arrow) || (!cut && self.eat(&Token::Colon)) {
+            let value = self.parse_type(references)?;
+            return Ok(Entry {
+                min,
+                max,
+                key: Some(first),
+                value,
+            });
+        }
+        if cut {
+            return Err(self.error("expected => after ^"));
+        }
+        Ok(Entry {
+            min,
+            max,
+            key: None,
+            value: first,
+        })
+    }
+
+    fn parse_group(&mut self, close: char, references: &mut Vec<(String, usize)>) -> Result<Group> {
+        let mut choices = Vec::new();
+        let mut entries = Vec::new();
+        loop {
+            match self.peek() {
+                Some(Token::Close(c)) if *c == close => {
+                    self.pos += 1;
+                    choices.push(entries);
+                    return Ok(Group { choices });
+                }
+                Some(Token::DoubleSlash) => {
+                    self.pos += 1;
+                    choices.push(std::mem::take(&mut entries));
+                }
+                Some(Token::Comma) => self.pos += 1,
+                Some(_) => entries.push(self.parse_entry(references)?),
+                None => return Err(self.error(format!("missing {}", close))),
+            }
+        }
+    }
+
+    fn parse_type(&mut self, references: &mut Vec<(String, usize)>) -> Result<Type> {
+        let mut alternatives = vec![self.parse_type1(references)?];
+        while self.eat(&Token::Slash) {
+            alternatives.push(self.parse_type1(references)?);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.remove(0)
+        } else {
+            Type::Choice(alternatives)
+        })
+    }
+
+    fn parse_type1(&mut self, references: &mut Vec<(String, usize)>) -> Result<Type> {
+        let target = self.parse_type2(references)?;
+        match self.peek() {
+            Some(&Token::Range(inclusive)) => {
+                self.pos += 1;
+                let max = self.parse_type2(references)?;
+                Ok(Type::Range(Box::new(target), Box::new(max), inclusive))
+            }
+            Some(Token::Control(name)) => {
+                let control = match name.as_str() {
+                    "size" => Control::Size,
+                    "lt" => Control::Lt,
+                    "le" => Control::Le,
+                    "gt" => Control::Gt,
+                    "ge" => Control::Ge,
+                    "eq" => Control::Eq,
+                    "ne" => Control::Ne,
+                    "cbor" => Control::Cbor,
+                    "and" => Control::And,
+                    "within" => Control::Within,
+                    "default" => Control::Default,
+                    other => {
+                        return Err(self.error(format!("unsupported control operator .{}", other)));
+                    }
+                };
+                self.pos += 1;
+                let arg = self.parse_type2(references)?;
+                Ok(Type::Control(Box::new(target), control, Box::new(arg)))
+            }
+            _ => Ok(target),
+        }
+    }
+
+    fn parse_type2(&mut self, references: &mut Vec<(String, usize)>) -> Result<Type> {
+        let line = self.line();
+        Ok(match self.next()? {
+            Token::Int(n) => Type::Literal(Literal::Int(n)),
+            Token::Float(f) => Type::Literal(Literal::Float(f)),
+            Token::Text(s) => Type::Literal(Literal::Text(s)),
+            Token::Bytes(b) => Type::Literal(Literal::Bytes(b)),
+            Token::Name(name) => {
+                references.push((name.clone(), line));
+                Type::Name(name)
+            }
+            Token::Open('(') => {
+                let group = self.parse_group(')', references)?;
+                match &group.choices[..] {
+                    [entries] if matches!(&entries[..], [e] if e.key.is_none() && (e.min, e.max) == (1, 1)) => {
+                        entries[0].value.clone()
+                    }
+                    _ => Type::Group(group),
+                }
+            }
+            Token::Open('{') => Type::Map(self.parse_group('}', references)?),
+            Token::Open('[') => Type::Array(self.parse_group(']', references)?),
+            Token::Tilde => match self.next()? {
+                Token::Name(name) => {
+                    references.push((name.clone(), line));
+                    Type::Unwrap(name)
+                }
+                other => {
+                    return Err(
+                        self.error(format!("expected a rule name after ~, found {:?}", other))
+                    );
+                }
+            },
+            Token::Amp => match self.peek() {
+                Some(Token::Open('(')) => {
+                    self.pos += 1;
+                    Type::Enum(Box::new(Type::Group(self.parse_group(')', references)?)))
+                }
+                _ => Type::Enum(Box::new(self.parse_type2(references)?)),
+            },
+            Token::Hash(None, _) => Type::Any,
+            Token::Hash(Some(6), tag) if self.peek() == Some(&Token::Open('(')) => {
+                self.pos += 1;
+                let content = self.parse_type(references)?;
+                self.expect(Token::Close(')'))?;
+                Type::Tag(tag, Box::new(content))
+            }
+            Token::Hash(Some(major @ 0..=7), arg) => Type::Major(major, arg),
+            other => return Err(self.error(format!("unexpected {:?}", other))),
+        })
+    }
+}
+
+// Decoded item
+
+/// A data item as encoded, keeping what [`Value`](crate::Value) drops: tags,
+/// the full integer range, float widths and duplicate map keys
+enum Item {
+    Int(i128),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Item>),
+    Map(Vec<(Item, Item)>),
+    Tag(u64, Box<Item>),
+    /// Value and additional info (the encoded width)
+    Float(f64, u8),
+    Simple(u8),
+}
+
+impl Item {
+    fn describe(&self) -> String {
+        match self {
+            Item::Int(n) => format!("integer {}", n),
+            Item::Bytes(b) => format!("byte string of {} bytes", b.len()),
+            Item::Text(s) if s.len() <= 32 => format!("text string {:?}", s),
+            Item::Text(s) => format!("text string of {} bytes", s.len()),
+            Item::Array(items) => format!("array of {} items", items.len()),
+            Item::Map(entries) => format!("map of {} entries", entries.len()),
+            Item::Tag(tag, _) => format!("tag {}", tag),
+            Item::Float(f, _) => format!("float {:?}", f),
+            Item::Simple(FALSE) => "false".to_string(),
+            Item::Simple(TRUE) => "true".to_string(),
+            Item::Simple(NULL) => "null".to_string(),
+            Item::Simple(UNDEFINED) => "undefined".to_string(),
+            Item::Simple(n) => format!("simple value {}", n),
+        }
+    }
+
+    fn segment(&self) -> PathSegment {
+        match self {
+            Item::Text(s) => PathSegment::Key(s.clone()),
+            Item::Int(n) => PathSegment::IntKey(*n),
+            _ => PathSegment::OtherKey,
+        }
+    }
+}
+
+fn read_string(reader: &mut SliceReader, major: u8, len: Option<u64>) -> Result<Vec<u8>> {
+    if let Some(len) = len {
+        return Ok(reader.read_slice(u64_to_usize(len)?)?.to_vec());
+    }
+    let mut out = Vec::new();
+    while !at_break(reader)? {
+        match reader.read_header()? {
+            (m, _, Some(len)) if m == major => {
+                out.extend_from_slice(reader.read_slice(u64_to_usize(len)?)?)
+            }
+            _ => {
+                return Err(Error::Syntax(
+                    "Invalid chunk in indefinite-length string".to_string(),
+                ));
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn at_break(reader: &mut SliceReader) -> Result<bool> {
+    if reader.peek_u8()? == BREAK {
+        reader.read_u8()?;
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+fn read_item(reader: &mut SliceReader, depth: usize) -> Result<Item> {
+    check_depth(depth)?;
+    let (major, info, arg) = reader.read_header()?;
+    Ok(match (major, arg) {
+        (MAJOR_UNSIGNED, Some(n)) => Item::Int(n as i128),
+        (MAJOR_NEGATIVE, Some(n)) => Item::Int(-1 - n as i128),
+        (MAJOR_BYTES, len) => Item::Bytes(read_string(reader, MAJOR_BYTES, len)?),
+        (MAJOR_TEXT, len) => Item::Text(
+            String::from_utf8(read_string(reader, MAJOR_TEXT, len)?)
+                .map_err(|_| Error::InvalidUtf8)?,
+        ),
+        (MAJOR_ARRAY, len) => {
+            let mut items = Vec::new();
+            match len {
+                Some(len) => {
+                    for _ in 0..u64_to_usize(len)? {
+                        items.push(read_item(reader, depth + 1)?);
+                    }
+                }
+                None => {
+                    while !at_break(reader)? {
+                        items.push(read_item(reader, depth + 1)?);
+                    }
+                }
+            }
+            Item::Array(items)
+        }
+        (MAJOR_MAP, len) => {
+            let mut entries = Vec::new();
+            let mut read_entry = |reader: &mut SliceReader| -> Result<()> {
+                let key = read_item(reader, depth + 1)?;
+                entries.push((key, read_item(reader, depth + 1)?));
+                Ok(())
+            };
+            match len {
+                Some(len) => {
+                    for _ in 0..u64_to_usize(len)? {
+                        read_entry(reader)?;
+                    }
+                }
+                None => {
+                    while !at_break(reader)? {
+                        read_entry(reader)?;
+                    }
+                }
+            }
+            Item::Map(entries)
+        }
+        (MAJOR_TAG, Some(tag)) => Item::Tag(tag, Box::new(read_item(reader, depth + 1)?)),
+        (MAJOR_SIMPLE, Some(arg)) => match info {
+            FLOAT16 | FLOAT32 | FLOAT64 => Item::Float(float_from_arg(info, arg), info),
+            SIMPLE_VALUE if arg < 32 => {
+                return Err(Error::Syntax(format!(
+                    "Two-byte simple value {} below 32",
+                    arg
+                )));
+            }
+            _ => Item::Simple(arg as u8),
+        },
+        (MAJOR_SIMPLE, None) => return Err(Error::Syntax("Unexpected break".to_string())),
+        _ => {
+            return Err(Error::Syntax(format!(
+                "Major type {} cannot be indefinite",
+                major
+            )));
+        }
+    })
+}
+
+// Validation
+
+fn built_in_matches(name: &str, item: &Item) -> Option<bool> {
+    Some(match name {
+        "any" => true,
+        "uint" => matches!(item, Item::Int(n) if *n >= 0),
+        "nint" => matches!(item, Item::Int(n) if *n < 0),
+        "bstr" => matches!(item, Item::Bytes(_)),
+        "tstr" => matches!(item, Item::Text(_)),
+        "float16" => matches!(item, Item::Float(_, FLOAT16)),
+        "float32" => matches!(item, Item::Float(_, FLOAT32)),
+        "float64" => matches!(item, Item::Float(_, FLOAT64)),
+        "false" => matches!(item, Item::Simple(FALSE)),
+        "true" => matches!(item, Item::Simple(TRUE)),
+        "nil" => matches!(item, Item::Simple(NULL)),
+        "undefined" => matches!(item, Item::Simple(UNDEFINED)),
+        _ => return None,
+    })
+}
+
+fn literal_matches(literal: &Literal, item: &Item) -> bool {
+    match (literal, item) {
+        (Literal::Int(a), Item::Int(b)) => a == b,
+        (Literal::Float(a), Item::Float(b, _)) => a == b,
+        (Literal::Text(a), Item::Text(b)) => a == b,
+        (Literal::Bytes(a), Item::Bytes(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn compare(item: &Item, literal: &Literal) -> Option<std::cmp::Ordering> {
+    match (item, literal) {
+        (Item::Int(a), Literal::Int(b)) => Some(a.cmp(b)),
+        (Item::Int(a), Literal::Float(b)) => (*a as f64).partial_cmp(b),
+        (Item::Float(a, _), Literal::Int(b)) => a.partial_cmp(&(*b as f64)),
+        (Item::Float(a, _), Literal::Float(b)) => a.partial_cmp(b),
+        _ => None,
+    }
+}
+
+impl Schema {
+    /// The literal a type stands for, following rule references
+    fn literal<'a>(&'a self, ty: &'a Type) -> Option<&'a Literal> {
+        let mut ty = ty;
+        for _ in 0..MAX_RULE_DEPTH {
+            match ty {
+                Type::Literal(literal) => return Some(literal),
+                Type::Name(name) => ty = self.rules.get(name)?,
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    /// The group a keyless map or array entry stands for, if it is one
+    fn group<'a>(&'a self, ty: &'a Type) -> Option<&'a Group> {
+        let mut ty = ty;
+        for _ in 0..MAX_RULE_DEPTH {
+            match ty {
+                Type::Group(group) => return Some(group),
+                Type::Name(name) => ty = self.rules.get(name)?,
+                Type::Unwrap(name) => match self.rules.get(name)? {
+                    Type::Map(group) | Type::Array(group) => return Some(group),
+                    _ => return None,
+                },
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    fn check(
+        &self,
+        ty: &Type,
+        item: &Item,
+        path: &mut Vec<PathSegment>,
+        depth: usize,
+    ) -> Vec<Violation> {
+        if depth > MAX_RULE_DEPTH {
+            return violation(path, "schema nesting too deep".to_string());
+        }
+        let mismatch = |path: &[PathSegment]| {
+            violation(
+                path,
+                format!("expected {}, found {}", describe(ty), item.describe()),
+            )
+        };
+        match ty {
+            Type::Any => Vec::new(),
+            Type::Literal(literal) if literal_matches(literal, item) => Vec::new(),
+            Type::Literal(_) => mismatch(path),
+            Type::Name(name) => match self.rules.get(name) {
+                Some(rule) => {
+                    let violations = self.check(rule, item, path, depth + 1);
+                    // Name simple types rather than spelling out their choices
+                    let simple = matches!(
+                        rule,
+                        Type::Choice(_) | Type::Literal(_) | Type::Name(_) | Type::Range(..)
+                    );
+                    if simple && violations.iter().all(|v| v.path.len() == path.len()) {
+                        if violations.is_empty() {
+                            violations
+                        } else {
+                            mismatch(path)
+                        }
+                    } else {
+                        violations
+                    }
+                }
+                None if built_in_matches(name, item) == Some(true) => Vec::new(),
+                // A built-in that doesn't match, or a socket nothing was added to
+                None => mismatch(path),
+            },
+            Type::Choice(alternatives) => {
+                let mut best: Option<Vec<Violation>> = None;
+                for alternative in alternatives {
+                    let violations = self.check(alternative, item, path, depth + 1);
+                    if violations.is_empty() {
+                        return violations;
+                    }
+                    // Prefer the alternative that got furthest into the item
+                    let reach = violations.iter().map(|v| v.path.len()).max();
+                    if best
+                        .as_ref()
+                        .is_none_or(|b| reach > b.iter().map(|v| v.path.len()).max())
+                    {
+                        best = Some(violations);
+                    }
+                }
+                match best {
+                    Some(violations) if violations.iter().any(|v| v.path.len() > path.len()) => {
+                        violations
+                    }
+                    _ => mismatch(path),
+                }
+            }
+            Type::Range(min, max, inclusive) => {
+                let (Some(min), Some(max)) = (self.literal(min), self.literal(max)) else {
+                    return violation(
+                        path,
+                        format!("range {} has non-literal bounds", describe(ty)),
+                    );
+                };
+                let kinds_match = matches!(
+                    (item, min, max),
+                    (Item::Int(_), Literal::Int(_), Literal::Int(_))
+                        | (Item::Float(..), Literal::Float(_), Literal::Float(_))
+                );
+                let above_min = compare(item, min).is_some_and(|o| o.is_ge());
+                let below_max =
+                    compare(item, max).is_some_and(|o| o.is_lt() || (*inclusive && o.is_eq()));
+                if kinds_match && above_min && below_max {
+                    Vec::new()
+                } else {
+                    mismatch(path)
+                }
+            }
+            Type::Control(target, control, arg) => {
+                let violations = self.check(target, item, path, depth + 1);
+                if !violations.is_empty() {
+                    return violations;
+                }
+                self.check_control(ty, *control, arg, item, path, depth)
+            }
+            Type::Map(group) => match item {
+                Item::Map(entries) => self.check_map(group, entries, path, depth),
+                _ => mismatch(path),
+            },
+            Type::Array(group) => match item {
+                Item::Array(items) => self.check_array(group, items, path, depth),
+                _ => mismatch(path),
+            },
+            Type::Group(_) => violation(
+                path,
+                "a group can only be used inside a map or array".to_string(),
+            ),
+            Type::Tag(tag, content_type) => match item {
+                Item::Tag(actual, content) if tag.is_none_or(|tag| tag == *actual) => {
+                    self.check(content_type, content, path, depth + 1)
+                }
+                _ => mismatch(path),
+            },
+            Type::Major(major, arg) => {
+                let matches = match (*major, item) {
+                    (MAJOR_UNSIGNED, Item::Int(n)) if *n >= 0 => {
+                        arg.is_none_or(|a| a as i128 == *n)
+                    }
+                    (MAJOR_NEGATIVE, Item::Int(n)) if *n < 0 => {
+                        arg.is_none_or(|a| -1 - a as i128 == *n)
+                    }
+                    (MAJOR_BYTES, Item::Bytes(_)) | (MAJOR_TEXT, Item::Text(_)) => arg.is_none(),
+                    (MAJOR_ARRAY, Item::Array(_)) | (MAJOR_MAP, Item::Map(_)) => arg.is_none(),
+                    (MAJOR_TAG, Item::Tag(tag, _)) => arg.is_none_or(|a| a == *tag),
+                    (MAJOR_SIMPLE, Item::Simple(n)) => arg.is_none_or(|a| a == *n as u64),
+                    (MAJOR_SIMPLE, Item::Float(_, info)) => arg.is_none_or(|a| a == *info as u64),
+                    _ => false,
+                };
+                if matches { Vec::new() } else { mismatch(path) }
+            }
+            Type::Unwrap(name) => match self.rules.get(name) {
+                Some(Type::Tag(_, content_type)) => match item {
+                    Item::Tag(_, content) => self.check(content_type, content, path, depth + 1),
+                    _ => self.check(content_type, item, path, depth + 1),
+                },
+                _ => violation(
+                    path,
+                    format!("~{} can only be used inside a map or array", name),
+                ),
+            },
+            Type::Enum(source) => {
+                let Some(group) = self.group(source).or(match source.as_ref() {
+                    Type::Map(group) | Type::Array(group) => Some(group),
+                    _ => None,
+                }) else {
+                    return violation(path, format!("{} is not a group", describe(source)));
+                };
+                let found = group
+                    .choices
+                    .iter()
+                    .flatten()
+                    .any(|entry| self.check(&entry.value, item, path, depth + 1).is_empty());
+                if found { Vec::new() } else { mismatch(path) }
+            }
+        }
+    }
+
+    fn check_control(
+        &self,
+        ty: &Type,
+        control: Control,
+        arg: &Type,
+        item: &Item,
+        path: &mut Vec<PathSegment>,
+        depth: usize,
+    ) -> Vec<Violation> {
+        let failed = |path: &[PathSegment], what: String| {
+            violation(path, format!("{} doesn't satisfy {}", what, describe(ty)))
+        };
+        match control {
+            Control::Default => Vec::new(),
+            Control::And | Control::Within => self.check(arg, item, path, depth + 1),
+            Control::Size => {
+                let len = match item {
+                    Item::Bytes(b) => Some(b.len()),
+                    Item::Text(s) => Some(s.len()),
+                    _ => None,
+                };
+                // On unsigned integers the size is a number of bytes
+                let fits = match (len, item, self.literal(arg)) {
+                    (Some(len), _, _) => self
+                        .check(arg, &Item::Int(len as i128), &mut Vec::new(), depth + 1)
+                        .is_empty(),
+                    (None, Item::Int(n), Some(Literal::Int(bytes))) => {
+                        *n >= 0 && (*bytes >= 16 || (*bytes >= 0 && *n < 1 << (8 * bytes)))
+                    }
+                    _ => false,
+                };
+                match (fits, len) {
+                    (true, _) => Vec::new(),
+                    (false, Some(len)) => failed(path, format!("size {}", len)),
+                    (false, None) => failed(path, item.describe()),
+                }
+            }
+            Control::Eq | Control::Ne => {
+                let Some(literal) = self.literal(arg) else {
+                    return violation(path, format!("{} needs a literal argument", describe(ty)));
+                };
+                let equal = match compare(item, literal) {
+                    Some(order) => order.is_eq(),
+                    None => literal_matches(literal, item),
+                };
+                if equal == (control == Control::Eq) {
+                    Vec::new()
+                } else {
+                    failed(path, item.describe())
+                }
+            }
+            Control::Lt | Control::Le | Control::Gt | Control::Ge => {
+                let Some(literal) = self.literal(arg) else {
+                    return violation(path, format!("{} needs a literal argument", describe(ty)));
+                };
+                let satisfied = compare(item, literal).is_some_and(|order| match control {
+                    Control::Lt => order.is_lt(),
+                    Control::Le => order.is_le(),
+                    Control::Gt => order.is_gt(),
+                    _ => order.is_ge(),
+                });
+                if satisfied {
+                    Vec::new()
+                } else {
+                    failed(path, item.describe())
+                }
+            }
+            Control::Cbor => {
+                let Item::Bytes(bytes) = item else {
+                    return failed(path, item.describe());
+                };
+                let mut reader = SliceReader::new(bytes);
+                match read_item(&mut reader, 0) {
+                    Ok(_) if reader.remaining() > 0 => {
+                        violation(path, "embedded CBOR has trailing data".to_string())
+                    }
+                    Ok(embedded) => self.check(arg, &embedded, path, depth + 1),
+                    Err(e) => violation(path, format!("embedded CBOR is malformed: {}", e)),
+                }
+            }
+        }
+    }
+
+    fn check_map(
+        &self,
+        group: &Group,
+        entries: &[(Item, Item)],
+        path: &mut Vec<PathSegment>,
+        depth: usize,
+    ) -> Vec<Violation> {
+        let mut used = vec![false; entries.len()];
+        let mut violations = self.match_map_group(group, entries, &mut used, path, depth);
+        for ((key, _), used) in entries.iter().zip(used) {
+            if !used {
+                path.push(key.segment());
+                violations.extend(violation(
+                    path,
+                    format!("unexpected key {}", key.describe()),
+                ));
+                path.pop();
+            }
+        }
+        violations
+    }
+
+    /// Match the first choice of `group` that fits, marking the entries it uses
+    fn match_map_group(
+        &self,
+        group: &Group,
+        entries: &[(Item, Item)],
+        used: &mut Vec<bool>,
+        path: &mut Vec<PathSegment>,
+        depth: usize,
+    ) -> Vec<Violation> {
+        let mut best: Option<(Vec<bool>, Vec<Violation>)> = None;
+        for choice in &group.choices {
+            let mut trial = used.clone();
+            let violations = self.match_map_entries(choice, entries, &mut trial, path, depth);
+            if violations.is_empty() {
+                *used = trial;
+                return violations;
+            }
+            if best
+                .as_ref()
+                .is_none_or(|(_, b)| violations.len() < b.len())
+            {
+                best = Some((trial, violations));
+            }
+        }
+        let (trial, violations) = best.unwrap_or_default();
+        *used = trial;
+        violations
+    }
+
+    fn match_map_entries(
+        &self,
+        schema: &[Entry],
+        entries: &[(Item, Item)],
+        used: &mut Vec<bool>,
+        path: &mut Vec<PathSegment>,
+        depth: usize,
+    ) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for entry in schema {
+            let Some(key) = &entry.key else {
+                let Some(group) = self.group(&entry.value) else {
+                    violations.extend(violation(
+                        path,
+                        format!("map entry {} has no key", describe(&entry.value)),
+                    ));
+                    continue;
+                };
+                if entry.min == 0 {
+                    // An optional group is used only if it matches completely
+                    let mut trial = used.clone();
+                    if self
+                        .match_map_group(group, entries, &mut trial, path, depth + 1)
+                        .is_empty()
+                    {
+                        *used = trial;
+                    }
+                } else {
+                    violations.extend(self.match_map_group(group, entries, used, path, depth + 1));
+                }
+                continue;
+            };
+
+            // A literal key claims its entry even when the value is wrong; a key
+            // type only claims entries whose value matches too
+            let literal = self.literal(key).is_some();
+            let mut count = 0;
+            for (index, (k, v)) in entries.iter().enumerate() {
+                if used[index] || !self.check(key, k, &mut Vec::new(), depth + 1).is_empty() {
+                    continue;
+                }
+                path.push(k.segment());
+                let value_violations = self.check(&entry.value, v, path, depth + 1);
+                path.pop();
+                if literal {
+                    used[index] = true;
+                    count += 1;
+                    violations.extend(value_violations);
+                } else if value_violations.is_empty() && count < entry.max {
+                    used[index] = true;
+                    count += 1;
+                }
+            }
+            if count < entry.min {
+                let message = if literal {
+                    format!("missing key {}", describe(key))
+                } else {
+                    format!(
+                        "expected at least {} entries {} => {}",
+                        entry.min,
+                        describe(key),
+                        describe(&entry.value)
+                    )
+                };
+                violations.extend(violation(path, message));
+            } else if count > entry.max {
+                violations.extend(violation(
+                    path,
+                    format!("key {} appears {} times", describe(key), count),
+                ));
+            }
+        }
+        violations
+    }
+
+    fn check_array(
+        &self,
+        group: &Group,
+        items: &[Item],
+        path: &mut Vec<PathSegment>,
+        depth: usize,
+    ) -> Vec<Violation> {
+        if self
+            .group_ends(group, items, 0, depth)
+            .contains(&items.len())
+        {
+            return Vec::new();
+        }
+
+        // Point at the offending items where the expected shape is simple:
+        // a homogeneous array, or a fixed sequence of single items
+        let check_items = |types: &mut dyn Iterator<Item = &Type>, path: &mut Vec<PathSegment>| {
+            let mut violations = Vec::new();
+            for (index, (ty, item)) in types.zip(items).enumerate() {
+                path.push(PathSegment::Index(index));
+                violations.extend(self.check(ty, item, path, depth + 1));
+                path.pop();
+            }
+            violations
+        };
+        if let [entries] = &group.choices[..] {
+            let single = |entry: &Entry| self.group(&entry.value).is_none();
+            if let [entry] = &entries[..]
+                && single(entry)
+            {
+                if items.len() < entry.min || items.len() > entry.max {
+                    return violation(
+                        path,
+                        format!(
+                            "expected {} items, found {}",
+                            occurrence(entry),
+                            items.len()
+                        ),
+                    );
+                }
+                return check_items(&mut std::iter::repeat(&entry.value), path);
+            }
+            if entries.len() == items.len()
+                && entries
+                    .iter()
+                    .all(|e| (e.min, e.max) == (1, 1) && single(e))
+            {
+                let violations = check_items(&mut entries.iter().map(|e| &e.value), path);
+                if !violations.is_empty() {
+                    return violations;
+                }
+            }
+        }
+        violation(
+            path,
+            format!(
+                "array of {} items doesn't match the expected sequence",
+                items.len()
+            ),
+        )
+    }
+
+    /// Every position a group can end at when matched from `start`
+    fn group_ends(
+        &self,
+        group: &Group,
+        items: &[Item],
+        start: usize,
+        depth: usize,
+    ) -> BTreeSet<usize> {
+        let mut ends = BTreeSet::new();
+        if depth > MAX_RULE_DEPTH {
+            return ends;
+        }
+        for entries in &group.choices {
+            let mut positions = BTreeSet::from([start]);
+            for entry in entries {
+                let mut next = BTreeSet::new();
+                for &position in &positions {
+                    next.extend(self.repeat_ends(entry, items, position, depth));
+                }
+                positions = next;
+                if positions.is_empty() {
+                    break;
+                }
+            }
+            ends.extend(positions);
+        }
+        ends
+    }
+
+    /// Every position an entry can end at, repeated as its occurrence allows
+    fn repeat_ends(
+        &self,
+        entry: &Entry,
+        items: &[Item],
+        start: usize,
+        depth: usize,
+    ) -> BTreeSet<usize> {
+        let mut reached = BTreeSet::new();
+        if entry.min == 0 {
+            reached.insert(start);
+        }
+        let group = self.group(&entry.value);
+        let mut frontier = BTreeSet::from([start]);
+        let mut count = 0;
+        // Groups can match nothing, so bound the repetitions by the items left
+        while count < entry.max && count <= items.len() - start && !frontier.is_empty() {
+            count += 1;
+            let mut next = BTreeSet::new();
+            for &position in &frontier {
+                match group {
+                    Some(group) => next.extend(self.group_ends(group, items, position, depth + 1)),
+                    None => {
+                        if position < items.len()
+                            && self
+                                .check(&entry.value, &items[position], &mut Vec::new(), depth + 1)
+                                .is_empty()
+                        {
+                            next.insert(position + 1);
+                        }
+                    }
+                }
+            }
+            if count >= entry.min {
+                reached.extend(next.iter().copied());
+            }
+            frontier = next;
+        }
+        reached
+    }
+}
+
+fn occurrence(entry: &Entry) -> String {
+    match (entry.min, entry.max) {
+        (min, max) if min == max => min.to_string(),
+        (min, usize::MAX) => format!("at least {}", min),
+        (min, max) => format!("{} to {}", min, max),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::Value;
+
+    fn bytes<T: serde::Serialize>(value: &T) -> Vec<u8> {
+        crate::to_vec(value).unwrap()
+    }
+
+    fn messages(result: std::result::Result<(), Vec<Violation>>) -> Vec<String> {
+        result
+            .unwrap_err()
+            .iter()
+            .map(Violation::to_string)
+            .collect()
+    }
+
+    const ACTIONS: &str = r#"
+        ; Simplified from the C2PA actions assertion
+        actions-map = {
+            "actions": [1* action-items-map],
+            ? "metadata": { * tstr => any },
+        }
+
+        action-items-map = {
+            "action": $action-choice,
+            ? "when": tdate,
+            ? "softwareAgent": tstr .size (1..max-tstr-length),
+            ? "digitalSourceType": tstr,
+            ? "parameters": parameters-map,
+        }
+
+        $action-choice /= "c2pa.created"
+        $action-choice /= "c2pa.opened"
+        $action-choice /= "c2pa.edited"
+
+        parameters-map = {
+            ? "ingredient": hashed-uri-map,
+            ? "description": tstr,
+        }
+
+        hashed-uri-map = {
+            "url": tstr,
+            ? "alg": tstr,
+            "hash": bstr,
+        }
+
+        max-tstr-length = 64
+    "#;
+
+    #[test]
+    fn test_cddl_c2pa_actions() {
+        let schema = Schema::parse(ACTIONS).unwrap();
+        assert_eq!(schema.root(), "actions-map");
+
+        let mut action = BTreeMap::new();
+        action.insert("action", Value::Text("c2pa.opened".to_string()));
+        action.insert(
+            "parameters",
+            Value::Map(BTreeMap::from([(
+                Value::Text("ingredient".to_string()),
+                Value::Map(BTreeMap::from([
+                    (
+                        Value::Text("url".to_string()),
+                        Value::Text("self#jumbf=c2pa/ingredient".to_string()),
+                    ),
+                    (Value::Text("hash".to_string()), Value::Bytes(vec![0; 32])),
+                ])),
+            )])),
+        );
+        let valid = BTreeMap::from([("actions", vec![action.clone()])]);
+        assert_eq!(schema.validate(&bytes(&valid)), Ok(()));
+
+        // Unknown action, over-long agent, missing hash and an extra key
+        action.insert("action", Value::Text("c2pa.deleted".to_string()));
+        action.insert("softwareAgent", Value::Text("x".repeat(65)));
+        action.insert(
+            "parameters",
+            Value::Map(BTreeMap::from([(
+                Value::Text("ingredient".to_string()),
+                Value::Map(BTreeMap::from([(
+                    Value::Text("url".to_string()),
+                    Value::Text("self#jumbf=c2pa".to_string()),
+                )])),
+            )])),
+        );
+        action.insert("extra", Value::Bool(true));
+        let invalid = BTreeMap::from([("actions", vec![action])]);
+        assert_eq!(
+            messages(schema.validate(&bytes(&invalid))),
+            [
+                "/actions/0/action: expected $action-choice, found text string \"c2pa.deleted\"",
+                "/actions/0/softwareAgent: size 65 doesn't satisfy tstr .size 1..max-tstr-length",
+                "/actions/0/parameters/ingredient: missing key \"hash\"",
+                "/actions/0/extra: unexpected key text string \"extra\"",
+            ]
+        );
+
+        // At least one action
+        let empty = BTreeMap::from([("actions", Vec::<u8>::new())]);
+        assert_eq!(
+            messages(schema.validate(&bytes(&empty))),
+            ["/actions: expected at least 1 items, found 0"]
+        );
+    }
+
+    #[test]
+    fn test_cddl_types_and_ranges() {
+        let check = |schema: &str, value: &[u8]| validate_cddl(schema, value).is_ok();
+
+        assert!(check(
+            "a = uint",
+            &[0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+        ));
+        assert!(check(
+            "a = nint",
+            &[0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+        ));
+        assert!(!check("a = uint", &[0x20]));
+        assert!(check("a = 0..10", &[0x0a]));
+        assert!(!check("a = 0...10", &[0x0a]));
+        assert!(check("a = -5..-1", &[0x24]));
+        assert!(check("a = 0.0..1.0", &[0xf9, 0x38, 0x00]));
+        assert!(!check("a = 0..10", &[0xf9, 0x38, 0x00]));
+        assert!(check("a = uint .lt 10 / tstr", &[0x61, b'x']));
+        assert!(!check("a = uint .lt 10", &[0x0a]));
+        assert!(check("a = uint .size 1", &[0x18, 0xff]));
+        assert!(!check("a = uint .size 1", &[0x19, 0x01, 0x00]));
+        assert!(check("a = bstr .size 2", &[0x42, 0x01, 0x02]));
+        assert!(check("a = float16", &[0xf9, 0x3c, 0x00]));
+        assert!(!check("a = float16", &[0xfb, 0x3f, 0xf0, 0, 0, 0, 0, 0, 0]));
+        assert!(check("a = number", &[0xfa, 0x3f, 0x80, 0x00, 0x00]));
+        assert!(check("a = bool / nil", &[0xf6]));
+        assert!(check("a = undefined", &[0xf7]));
+        assert!(check("a = h'0102' / 'ab'", &[0x42, b'a', b'b']));
+        assert!(check("a = \"x\" / 1 / 1.5", &[0xf9, 0x3e, 0x00]));
+        assert!(check("a = &colors  colors = (red: 1, green: 2)", &[0x02]));
+        assert!(!check("a = &colors  colors = (red: 1, green: 2)", &[0x03]));
+        assert!(check("a = #", &[0x80]));
+        assert!(check("a = #7.22", &[0xf6]));
+        assert!(check("a = #3", &[0x60]));
+        assert!(!check("a = #3", &[0x40]));
+    }
+
+    #[test]
+    fn test_cddl_tags() {
+        let check = |schema: &str, value: &[u8]| validate_cddl(schema, value).is_ok();
+
+        // 32("https://a")
+        let uri = [
+            0xd8, 0x20, 0x69, b'h', b't', b't', b'p', b's', b':', b'/', b'/', b'a',
+        ];
+        assert!(check("a = uri", &uri));
+        assert!(check("a = #6.32(tstr)", &uri));
+        assert!(!check("a = #6.33(tstr)", &uri));
+        assert!(!check("a = tstr", &uri));
+        assert!(check("a = ~b  b = #6.32(tstr)", &uri));
+        assert!(check("a = #6.6(any)", &[0xc6, 0x00]));
+        assert!(check("a = tdate", &[0xc0, 0x61, b'x']));
+        assert!(check("a = bigint", &[0xc3, 0x41, 0x01]));
+        // 24(<<[1]>>)
+        assert!(check(
+            "a = #6.24(bstr .cbor [uint])",
+            &[0xd8, 0x18, 0x42, 0x81, 0x01]
+        ));
+        assert!(!check(
+            "a = #6.24(bstr .cbor [tstr])",
+            &[0xd8, 0x18, 0x42, 0x81, 0x01]
+        ));
+    }
+
+    #[test]
+    fn test_cddl_groups_and_occurrences() {
+        let check = |schema: &str, value: &[u8]| validate_cddl(schema, value).is_ok();
+
+        // Inline and optional groups in maps
+        let schema = "m = { common, ? (\"x\": int, \"y\": int) }  common = (\"id\": uint)";
+        assert!(check(schema, &[0xa1, 0x62, b'i', b'd', 0x01]));
+        assert!(check(
+            schema,
+            &[
+                0xa3, 0x62, b'i', b'd', 0x01, 0x61, b'x', 0x01, 0x61, b'y', 0x02
+            ]
+        ));
+        assert!(!check(
+            schema,
+            &[0xa2, 0x62, b'i', b'd', 0x01, 0x61, b'x', 0x01]
+        ));
+
+        // Group choices and group sockets
+        let schema = "m = { \"a\": int // \"b\": tstr }";
+        assert!(check(schema, &[0xa1, 0x61, b'a', 0x01]));
+        assert!(check(schema, &[0xa1, 0x61, b'b', 0x60]));
+        assert!(!check(schema, &[0xa1, 0x61, b'b', 0x01]));
+        let schema = "m = { $$ext }  $$ext //= (\"a\": int)  $$ext //= (\"b\": int)";
+        assert!(check(schema, &[0xa1, 0x61, b'b', 0x01]));
+
+        // Integer keys, key types, and duplicate keys
+        assert!(check(
+            "m = { 1 => tstr, * int => bool }",
+            &[0xa2, 0x01, 0x60, 0x02, 0xf5]
+        ));
+        assert!(!check(
+            "m = { 1 => tstr, * int => bool }",
+            &[0xa2, 0x01, 0x60, 0x02, 0x01]
+        ));
+        assert!(!check(
+            "m = { \"a\": int }",
+            &[0xa2, 0x61, b'a', 0x01, 0x61, b'a', 0x02]
+        ));
+
+        // Array occurrences, nested groups and backtracking
+        assert!(check("a = [2*3 int]", &[0x82, 0x01, 0x02]));
+        assert!(!check("a = [2*3 int]", &[0x84, 0x01, 0x02, 0x03, 0x04]));
+        assert!(check("a = [* int, tstr]", &[0x83, 0x01, 0x02, 0x60]));
+        assert!(check(
+            "a = [* (int, tstr)]",
+            &[0x84, 0x01, 0x60, 0x02, 0x60]
+        ));
+        assert!(!check("a = [* (int, tstr)]", &[0x83, 0x01, 0x60, 0x02]));
+        assert!(check("a = [* any, 1]", &[0x83, 0x01, 0x01, 0x01]));
+        assert!(check("a = [x: int, y: int]", &[0x82, 0x01, 0x02]));
+        assert!(check("a = [? int, * tstr]", &[0x9f, 0x60, 0x60, 0xff]));
+
+        // Recursive rules
+        let schema = "tree = [* tree / int]";
+        assert!(check(schema, &[0x82, 0x81, 0x01, 0x80]));
+        assert!(!check(schema, &[0x82, 0x81, 0x60, 0x80]));
+    }
+
+    #[test]
+    fn test_cddl_violation_paths() {
+        let schema = "a = [x: int, y: { 1 => tstr }]";
+        assert_eq!(
+            validate_cddl(schema, &[0x82, 0x60, 0xa1, 0x01, 0x01]).unwrap_err(),
+            [
+                Violation {
+                    path: vec![PathSegment::Index(0)],
+                    message: "expected int, found text string \"\"".to_string(),
+                },
+                Violation {
+                    path: vec![PathSegment::Index(1), PathSegment::IntKey(1)],
+                    message: "expected tstr, found integer 1".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cddl_schema_errors() {
+        assert!(Schema::parse("").is_err());
+        assert!(Schema::parse("a = b").is_err());
+        assert!(Schema::parse("a = tstr .regexp \"x\"").is_err());
+        assert!(Schema::parse("a = { \"x\": int").is_err());
+        assert!(Schema::parse("a = \"unterminated").is_err());
+        // Sockets may be left empty, and then match nothing
+        let schema = Schema::parse("a = $b").unwrap();
+        assert!(schema.validate(&[0x00]).is_err());
+
+        let violations = validate_cddl("a = b", &[0x00]).unwrap_err();
+        assert!(violations[0].message.starts_with("invalid schema"));
+        let violations = validate_cddl("a = int", &[0x00, 0x00]).unwrap_err();
+        assert_eq!(violations[0].message, "trailing data after the item");
+        let violations = validate_cddl("a = int", &[0x18]).unwrap_err();
+        assert!(violations[0].message.starts_with("malformed CBOR"));
+        assert!(
+            Schema::parse("a = int  b = tstr")
+                .unwrap()
+                .validate_rule("b", &[0x60])
+                .is_ok()
+        );
+    }
+}
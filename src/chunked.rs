@@ -0,0 +1,350 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Lossless round trip of indefinite-length strings
+//!
+//! By default, indefinite-length byte and text strings are joined into a single
+//! buffer on decode and always re-encoded with a definite length. That is fine for
+//! most data, but hashing externally produced CBOR requires reproducing the exact
+//! original bytes. [`ChunkedBytes`] and [`ChunkedText`] record the length of each
+//! chunk on decode and write the same chunks back out on encode.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::{ChunkedText, from_slice, to_vec};
+//!
+//! // Indefinite-length "hello" split as "hel" + "lo"
+//! let cbor = [0x7f, 0x63, b'h', b'e', b'l', 0x62, b'l', b'o', 0xff];
+//! let text: ChunkedText = from_slice(&cbor).unwrap();
+//! assert_eq!(text.as_str(), "hello");
+//! assert_eq!(text.chunk_lengths(), Some(&[3, 2][..]));
+//! assert_eq!(to_vec(&text).unwrap(), cbor);
+//! ```
+
+use std::fmt;
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, Visitor},
+    ser::SerializeSeq,
+};
+use serde_bytes::{ByteBuf, Bytes};
+
+use crate::constants::{CHUNKED_BYTES_MARKER, CHUNKED_TEXT_MARKER};
+
+/// Split `len` bytes into consecutive ranges of the given lengths
+fn chunk_ranges(lengths: &[usize]) -> impl Iterator<Item = std::ops::Range<usize>> + '_ {
+    let mut offset = 0;
+    lengths.iter().map(move |&len| {
+        let start = offset;
+        offset += len;
+        start..offset
+    })
+}
+
+/// A byte string that remembers its indefinite-length chunk boundaries
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ChunkedBytes {
+    data: Vec<u8>,
+    chunk_lengths: Option<Vec<usize>>,
+}
+
+impl ChunkedBytes {
+    /// Create a definite-length byte string
+    pub fn new(data: Vec<u8>) -> Self {
+        ChunkedBytes {
+            data,
+            chunk_lengths: None,
+        }
+    }
+
+    /// Create an indefinite-length byte string from its chunks
+    pub fn from_chunks<I: IntoIterator<Item = Vec<u8>>>(chunks: I) -> Self {
+        let mut data = Vec::new();
+        let mut lengths = Vec::new();
+        for chunk in chunks {
+            lengths.push(chunk.len());
+            data.extend_from_slice(&chunk);
+        }
+        ChunkedBytes {
+            data,
+            chunk_lengths: Some(lengths),
+        }
+    }
+
+    /// The joined content of all chunks
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Chunk lengths, or `None` if the string has a definite length
+    pub fn chunk_lengths(&self) -> Option<&[usize]> {
+        self.chunk_lengths.as_deref()
+    }
+
+    /// Returns true if the string is encoded with an indefinite length
+    pub fn is_indefinite(&self) -> bool {
+        self.chunk_lengths.is_some()
+    }
+
+    /// The individual chunks (a single chunk for definite-length strings)
+    pub fn chunks(&self) -> Vec<&[u8]> {
+        match &self.chunk_lengths {
+            Some(lengths) => chunk_ranges(lengths).map(|r| &self.data[r]).collect(),
+            None => vec![&self.data[..]],
+        }
+    }
+
+    /// Consume the wrapper and return the joined content
+    pub fn into_inner(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+/// A text string that remembers its indefinite-length chunk boundaries
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ChunkedText {
+    text: String,
+    chunk_lengths: Option<Vec<usize>>,
+}
+
+impl ChunkedText {
+    /// Create a definite-length text string
+    pub fn new(text: String) -> Self {
+        ChunkedText {
+            text,
+            chunk_lengths: None,
+        }
+    }
+
+    /// Create an indefinite-length text string from its chunks
+    pub fn from_chunks<I: IntoIterator<Item = String>>(chunks: I) -> Self {
+        let mut text = String::new();
+        let mut lengths = Vec::new();
+        for chunk in chunks {
+            lengths.push(chunk.len());
+            text.push_str(&chunk);
+        }
+        ChunkedText {
+            text,
+            chunk_lengths: Some(lengths),
+        }
+    }
+
+    /// The joined content of all chunks
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Chunk lengths in bytes, or `None` if the string has a definite length
+    pub fn chunk_lengths(&self) -> Option<&[usize]> {
+        self.chunk_lengths.as_deref()
+    }
+
+    /// Returns true if the string is encoded with an indefinite length
+    pub fn is_indefinite(&self) -> bool {
+        self.chunk_lengths.is_some()
+    }
+
+    /// The individual chunks (a single chunk for definite-length strings)
+    pub fn chunks(&self) -> Vec<&str> {
+        match &self.chunk_lengths {
+            Some(lengths) => chunk_ranges(lengths).map(|r| &self.text[r]).collect(),
+            None => vec![&self.text[..]],
+        }
+    }
+
+    /// Consume the wrapper and return the joined content
+    pub fn into_inner(self) -> String {
+        self.text
+    }
+}
+
+// The encoder recognizes the marker names and writes the chunk sequence as an
+// indefinite-length string. Other serializers see a plain sequence of chunks.
+struct ByteChunks<'a>(Vec<&'a [u8]>);
+
+impl Serialize for ByteChunks<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for chunk in &self.0 {
+            seq.serialize_element(Bytes::new(chunk))?;
+        }
+        seq.end()
+    }
+}
+
+impl Serialize for ChunkedBytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.chunk_lengths {
+            Some(_) => serializer
+                .serialize_newtype_struct(CHUNKED_BYTES_MARKER, &ByteChunks(self.chunks())),
+            None => serializer.serialize_bytes(&self.data),
+        }
+    }
+}
+
+impl Serialize for ChunkedText {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.chunk_lengths {
+            Some(_) => serializer.serialize_newtype_struct(CHUNKED_TEXT_MARKER, &self.chunks()),
+            None => serializer.serialize_str(&self.text),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ChunkedBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ChunkedBytesVisitor;
+
+        impl<'de> Visitor<'de> for ChunkedBytesVisitor {
+            type Value = ChunkedBytes;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a byte string")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<ChunkedBytes, E> {
+                Ok(ChunkedBytes::new(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<ChunkedBytes, E> {
+                Ok(ChunkedBytes::new(v))
+            }
+
+            // The decoder presents indefinite-length strings as a sequence of chunks
+            fn visit_seq<A: de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<ChunkedBytes, A::Error> {
+                let mut chunks = Vec::new();
+                while let Some(chunk) = seq.next_element::<ByteBuf>()? {
+                    chunks.push(chunk.into_vec());
+                }
+                Ok(ChunkedBytes::from_chunks(chunks))
+            }
+
+            fn visit_newtype_struct<D: Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<ChunkedBytes, D::Error> {
+                deserializer.deserialize_any(self)
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(CHUNKED_BYTES_MARKER, ChunkedBytesVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for ChunkedText {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ChunkedTextVisitor;
+
+        impl<'de> Visitor<'de> for ChunkedTextVisitor {
+            type Value = ChunkedText;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a text string")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<ChunkedText, E> {
+                Ok(ChunkedText::new(v.to_string()))
+            }
+
+            fn visit_string<E: de::Error>(self, v: String) -> Result<ChunkedText, E> {
+                Ok(ChunkedText::new(v))
+            }
+
+            // The decoder presents indefinite-length strings as a sequence of chunks
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<ChunkedText, A::Error> {
+                let mut chunks = Vec::new();
+                while let Some(chunk) = seq.next_element::<String>()? {
+                    chunks.push(chunk);
+                }
+                Ok(ChunkedText::from_chunks(chunks))
+            }
+
+            fn visit_newtype_struct<D: Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<ChunkedText, D::Error> {
+                deserializer.deserialize_any(self)
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(CHUNKED_TEXT_MARKER, ChunkedTextVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::{Value, from_slice, to_value, to_vec};
+
+    #[test]
+    fn test_chunked_bytes_roundtrip_preserves_chunks() {
+        let cbor = [0x5f, 0x42, 0x01, 0x02, 0x41, 0x03, 0x40, 0xff];
+        let bytes: ChunkedBytes = from_slice(&cbor).unwrap();
+        assert_eq!(bytes.as_bytes(), &[1, 2, 3]);
+        assert_eq!(bytes.chunk_lengths(), Some(&[2, 1, 0][..]));
+        assert_eq!(bytes.chunks(), vec![&[1u8, 2][..], &[3][..], &[][..]]);
+        assert_eq!(to_vec(&bytes).unwrap(), cbor);
+    }
+
+    #[test]
+    fn test_chunked_definite_strings_stay_definite() {
+        let cbor = to_vec(&"hello").unwrap();
+        let text: ChunkedText = from_slice(&cbor).unwrap();
+        assert!(!text.is_indefinite());
+        assert_eq!(to_vec(&text).unwrap(), cbor);
+
+        let cbor = to_vec(&serde_bytes::Bytes::new(b"abc")).unwrap();
+        let bytes: ChunkedBytes = from_slice(&cbor).unwrap();
+        assert!(!bytes.is_indefinite());
+        assert_eq!(to_vec(&bytes).unwrap(), cbor);
+    }
+
+    #[test]
+    fn test_chunked_text_in_struct() {
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Claim {
+            title: ChunkedText,
+        }
+
+        // {"title": (_ "ab", "c")}
+        let cbor = [
+            0xa1, 0x65, b't', b'i', b't', b'l', b'e', 0x7f, 0x62, b'a', b'b', 0x61, b'c', 0xff,
+        ];
+        let claim: Claim = from_slice(&cbor).unwrap();
+        assert_eq!(claim.title.as_str(), "abc");
+        assert_eq!(claim.title.chunks(), vec!["ab", "c"]);
+        assert_eq!(to_vec(&claim).unwrap(), cbor);
+    }
+
+    #[test]
+    fn test_chunked_text_rejects_invalid_utf8_chunk() {
+        let cbor = [0x7f, 0x61, 0xff, 0xff];
+        assert!(from_slice::<ChunkedText>(&cbor).is_err());
+    }
+
+    #[test]
+    fn test_chunked_to_value_joins_chunks() {
+        let text = ChunkedText::from_chunks(vec!["ab".to_string(), "c".to_string()]);
+        assert_eq!(to_value(&text).unwrap(), Value::Text("abc".to_string()));
+
+        let bytes = ChunkedBytes::from_chunks(vec![vec![1], vec![2, 3]]);
+        assert_eq!(to_value(&bytes).unwrap(), Value::Bytes(vec![1, 2, 3]));
+    }
+}
@@ -0,0 +1,360 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Compatibility checks against serde_cbor and ciborium
+//!
+//! Enabled with the `compat_check` feature, which also enables
+//! `differential`. Where [`differential`](crate::differential) compares
+//! decoders on raw input, this module starts from Rust values, to find the
+//! serde representations (newtypes, enums, tags, floats) that this crate
+//! writes or reads differently from the crate a codebase is migrating from.
+//!
+//! [`check_encoding`] serializes a value with this crate and with each
+//! [`Reference`] and reports an [`EncodingDiff`] where the bytes differ.
+//! [`check_value_encoding`] does the same for a [`Value`], converting it to
+//! each reference's own value type first so tags are written natively.
+//! [`check_cross_decoding`] decodes the bytes written by each side with the
+//! other and reports a [`CrossDecodeFailure`] where decoding fails or gives
+//! back a different value.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::compat_check::{check_cross_decoding, check_encoding};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Ingredient {
+//!     title: String,
+//!     relationship: String,
+//! }
+//!
+//! let ingredient = Ingredient {
+//!     title: "image.jpg".to_string(),
+//!     relationship: "parentOf".to_string(),
+//! };
+//! assert!(check_encoding(&ingredient).is_empty());
+//! assert!(check_cross_decoding(&ingredient).is_empty());
+//! ```
+
+use std::fmt;
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::Value;
+pub use crate::differential::Reference;
+
+impl Reference {
+    /// Serialize `value` with this reference's default settings
+    pub fn encode<T: Serialize>(self, value: &T) -> std::result::Result<Vec<u8>, String> {
+        match self {
+            Reference::Ciborium => {
+                let mut out = Vec::new();
+                ciborium::ser::into_writer(value, &mut out).map_err(|e| e.to_string())?;
+                Ok(out)
+            }
+            Reference::SerdeCbor => serde_cbor::to_vec(value).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Deserialize a `T` from `input` with this reference, rejecting trailing data
+    pub fn decode_as<T: DeserializeOwned>(self, input: &[u8]) -> std::result::Result<T, String> {
+        match self {
+            Reference::Ciborium => {
+                let mut reader = input;
+                let value = ciborium::de::from_reader(&mut reader).map_err(|e| e.to_string())?;
+                if !reader.is_empty() {
+                    return Err("trailing data".to_string());
+                }
+                Ok(value)
+            }
+            Reference::SerdeCbor => serde_cbor::from_slice(input).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Render encoded bytes for a report, as diagnostic notation when they parse
+fn describe(bytes: &std::result::Result<Vec<u8>, String>) -> String {
+    match bytes {
+        Ok(bytes) => crate::to_diagnostic(bytes).unwrap_or_else(|_| {
+            let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+            format!("h'{}'", hex)
+        }),
+        Err(e) => format!("error: {}", e),
+    }
+}
+
+/// Different output from this crate and a reference for the same value
+#[derive(Debug, Clone)]
+pub struct EncodingDiff {
+    /// The reference encoder that disagreed
+    pub reference: Reference,
+    /// Bytes written by this crate
+    pub ours: std::result::Result<Vec<u8>, String>,
+    /// Bytes written by the reference
+    pub theirs: std::result::Result<Vec<u8>, String>,
+}
+
+impl EncodingDiff {
+    /// Offset of the first differing byte, if both sides encoded the value
+    pub fn first_difference(&self) -> Option<usize> {
+        let (Ok(ours), Ok(theirs)) = (&self.ours, &self.theirs) else {
+            return None;
+        };
+        ours.iter()
+            .zip(theirs)
+            .position(|(a, b)| a != b)
+            .or(Some(ours.len().min(theirs.len())))
+    }
+}
+
+impl fmt::Display for EncodingDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} encodes differently: ours {}, theirs {}",
+            self.reference,
+            describe(&self.ours),
+            describe(&self.theirs)
+        )
+    }
+}
+
+fn diff_encodings(
+    ours: std::result::Result<Vec<u8>, String>,
+    theirs: impl Fn(Reference) -> std::result::Result<Vec<u8>, String>,
+) -> Vec<EncodingDiff> {
+    Reference::ALL
+        .iter()
+        .filter_map(|&reference| {
+            let theirs = theirs(reference);
+            let agree = match (&ours, &theirs) {
+                (Ok(a), Ok(b)) => a == b,
+                (Err(_), Err(_)) => true,
+                _ => false,
+            };
+            (!agree).then(|| EncodingDiff {
+                reference,
+                ours: ours.clone(),
+                theirs,
+            })
+        })
+        .collect()
+}
+
+/// Serialize `value` with this crate and every reference, returning each
+/// reference whose bytes differ
+///
+/// This crate uses [`to_vec`](crate::to_vec) and the references their own
+/// defaults, so differences from [`EncoderOptions`](crate::EncoderOptions) or
+/// the `compact_floats` feature show up here too.
+pub fn check_encoding<T: Serialize>(value: &T) -> Vec<EncodingDiff> {
+    diff_encodings(
+        crate::to_vec(value).map_err(|e| e.to_string()),
+        |reference| reference.encode(value),
+    )
+}
+
+/// Encode `value` with this crate and every reference, returning each
+/// reference whose bytes differ
+///
+/// `value` is converted to `ciborium::value::Value` or `serde_cbor::Value`
+/// before encoding, so this compares how each crate writes its own value
+/// type. Undefined and simple values have no equivalent there and become
+/// null, so they always differ.
+pub fn check_value_encoding(value: &Value) -> Vec<EncodingDiff> {
+    diff_encodings(
+        crate::to_vec(value).map_err(|e| e.to_string()),
+        |reference| match reference {
+            Reference::Ciborium => reference.encode(&ciborium::value::Value::from(value.clone())),
+            Reference::SerdeCbor => reference.encode(&serde_cbor::Value::from(value.clone())),
+        },
+    )
+}
+
+/// Which side wrote the bytes in a cross-decoding check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Encoded by this crate, decoded by the reference
+    ToReference,
+    /// Encoded by the reference, decoded by this crate
+    FromReference,
+}
+
+/// Bytes written by one side that the other could not read back as the same value
+#[derive(Debug, Clone)]
+pub struct CrossDecodeFailure {
+    /// The reference on the other side
+    pub reference: Reference,
+    /// Which side wrote the bytes
+    pub direction: Direction,
+    /// The bytes that were decoded
+    pub bytes: Vec<u8>,
+    /// The decode error, or the different value that was decoded
+    pub message: String,
+}
+
+impl fmt::Display for CrossDecodeFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (writer, reader) = match self.direction {
+            Direction::ToReference => ("c2pa_cbor".to_string(), format!("{:?}", self.reference)),
+            Direction::FromReference => (format!("{:?}", self.reference), "c2pa_cbor".to_string()),
+        };
+        write!(
+            f,
+            "{} can't read {} output {}: {}",
+            reader,
+            writer,
+            describe(&Ok(self.bytes.clone())),
+            self.message
+        )
+    }
+}
+
+/// Encode `value` with each side and decode it with the other, returning every
+/// combination that fails or decodes to a different value
+///
+/// A side that can't encode `value` at all is skipped here; use
+/// [`check_encoding`] to see encoding errors.
+pub fn check_cross_decoding<T>(value: &T) -> Vec<CrossDecodeFailure>
+where
+    T: Serialize + DeserializeOwned + PartialEq + fmt::Debug,
+{
+    let check = |reference, direction, bytes: Vec<u8>, decoded: std::result::Result<T, String>| {
+        let message = match decoded {
+            Ok(decoded) if decoded == *value => return None,
+            Ok(decoded) => format!("decoded as {:?}", decoded),
+            Err(e) => e,
+        };
+        Some(CrossDecodeFailure {
+            reference,
+            direction,
+            bytes,
+            message,
+        })
+    };
+
+    let ours = crate::to_vec(value).ok();
+    let mut failures = Vec::new();
+    for reference in Reference::ALL {
+        if let Some(bytes) = &ours {
+            let decoded = reference.decode_as(bytes);
+            failures.extend(check(
+                reference,
+                Direction::ToReference,
+                bytes.clone(),
+                decoded,
+            ));
+        }
+        if let Ok(bytes) = reference.encode(value) {
+            let decoded = crate::from_slice(&bytes).map_err(|e| e.to_string());
+            failures.extend(check(reference, Direction::FromReference, bytes, decoded));
+        }
+    }
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Assertion {
+        label: String,
+        version: Option<u32>,
+        hashes: Vec<u64>,
+        kind: Kind,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Kind {
+        Created,
+        Opened(String),
+    }
+
+    #[test]
+    fn test_compat_structs_agree() {
+        for kind in [Kind::Created, Kind::Opened("c2pa.opened".to_string())] {
+            let assertion = Assertion {
+                label: "c2pa.actions".to_string(),
+                version: Some(2),
+                hashes: vec![0, 24, 65536, u64::MAX],
+                kind,
+            };
+            let diffs = check_encoding(&assertion);
+            assert!(diffs.is_empty(), "{}", diffs[0]);
+            let failures = check_cross_decoding(&assertion);
+            assert!(failures.is_empty(), "{}", failures[0]);
+        }
+    }
+
+    #[cfg(not(feature = "compact_floats"))]
+    #[test]
+    fn test_compat_reports_float_width() {
+        // Both references shorten 1.5 to half precision; this crate writes
+        // f64 unless compact_floats is enabled
+        let diffs = check_encoding(&1.5f64);
+        assert_eq!(diffs.len(), 2);
+        for diff in &diffs {
+            assert_eq!(diff.ours.as_deref().unwrap()[0], 0xfb);
+            assert_eq!(diff.theirs.as_deref().unwrap(), &[0xf9, 0x3e, 0x00]);
+            assert_eq!(diff.first_difference(), Some(0));
+            assert!(diff.to_string().contains("encodes differently"));
+        }
+        // The values still read back everywhere
+        assert!(check_cross_decoding(&1.5f64).is_empty());
+    }
+
+    #[test]
+    fn test_compat_value_encoding() {
+        // Keys in an order both crates' maps agree on
+        let value = Value::Map(BTreeMap::from([
+            (Value::Integer(1), Value::Text("a".to_string())),
+            (Value::Text("b".to_string()), Value::Bytes(vec![1, 2])),
+            (
+                Value::Text("c".to_string()),
+                Value::Array(vec![Value::Bool(true), Value::Null]),
+            ),
+        ]));
+        let diffs = check_value_encoding(&value);
+        assert!(diffs.is_empty(), "{}", diffs[0]);
+
+        let tagged = Value::Tag(32, Box::new(Value::Text("https://a.b".to_string())));
+        assert!(
+            check_value_encoding(&tagged)
+                .iter()
+                .all(|d| d.reference != Reference::Ciborium)
+        );
+
+        let diffs = check_value_encoding(&Value::Undefined);
+        assert_eq!(diffs.len(), 2);
+        assert!(diffs.iter().all(|d| d.theirs.as_deref() == Ok(&[0xf6][..])));
+    }
+
+    #[test]
+    fn test_compat_reports_cross_decoding_failure() {
+        let failure = CrossDecodeFailure {
+            reference: Reference::SerdeCbor,
+            direction: Direction::FromReference,
+            bytes: vec![0x01],
+            message: "invalid type".to_string(),
+        };
+        assert_eq!(
+            failure.to_string(),
+            "c2pa_cbor can't read SerdeCbor output 1: invalid type"
+        );
+    }
+}
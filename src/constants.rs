@@ -27,68 +27,78 @@ pub(crate) const MAJOR_SIMPLE: u8 = 7;
 // Standard CBOR tags (RFC 8949)
 pub(crate) const TAG_DATETIME_STRING: u64 = 0; // Standard date/time string (RFC 3339)
 pub(crate) const TAG_EPOCH_DATETIME: u64 = 1; // Epoch-based date/time
-#[allow(dead_code)]
 pub(crate) const TAG_POSITIVE_BIGNUM: u64 = 2; // Positive bignum
-#[allow(dead_code)]
 pub(crate) const TAG_NEGATIVE_BIGNUM: u64 = 3; // Negative bignum
-#[allow(dead_code)]
 pub(crate) const TAG_DECIMAL_FRACTION: u64 = 4; // Decimal fraction
-#[allow(dead_code)]
 pub(crate) const TAG_BIGFLOAT: u64 = 5; // Bigfloat
+pub(crate) const TAG_EXPECT_BASE64URL: u64 = 21; // Expected conversion to base64url
+pub(crate) const TAG_EXPECT_BASE64: u64 = 22; // Expected conversion to base64
+pub(crate) const TAG_EXPECT_BASE16: u64 = 23; // Expected conversion to base16
+pub(crate) const TAG_ENCODED_CBOR: u64 = 24; // Encoded CBOR data item
+pub(crate) const TAG_STRINGREF: u64 = 25; // Reference to a string in the current string table
+pub(crate) const TAG_SHAREABLE: u64 = 28; // Value that may be referenced later
+pub(crate) const TAG_SHAREDREF: u64 = 29; // Reference to a shareable value
 pub(crate) const TAG_URI: u64 = 32; // URI (RFC 3986)
 pub(crate) const TAG_BASE64URL: u64 = 33; // Base64url-encoded text
 pub(crate) const TAG_BASE64: u64 = 34; // Base64-encoded text
-#[allow(dead_code)]
 pub(crate) const TAG_MIME: u64 = 36; // MIME message
+pub(crate) const TAG_LANG_STRING: u64 = 38; // Language-tagged string (RFC 9290)
+pub(crate) const TAG_MULTI_DIM_ARRAY: u64 = 40; // Multi-dimensional array, row-major (RFC 8746)
+pub(crate) const TAG_MULTI_DIM_ARRAY_COLUMN_MAJOR: u64 = 1040; // Multi-dimensional array, column-major (RFC 8746)
+pub(crate) const TAG_SELF_DESCRIBE: u64 = 55799; // Self-described CBOR
 
 // RFC 8746 - Typed arrays encoded as byte strings
 pub(crate) const TAG_UINT8_ARRAY: u64 = 64; // uint8 array
 pub(crate) const TAG_UINT16BE_ARRAY: u64 = 65; // uint16 big-endian array
 pub(crate) const TAG_UINT32BE_ARRAY: u64 = 66; // uint32 big-endian array
 pub(crate) const TAG_UINT64BE_ARRAY: u64 = 67; // uint64 big-endian array
-#[allow(dead_code)]
 pub(crate) const TAG_UINT8_CLAMPED_ARRAY: u64 = 68; // uint8 clamped array
 pub(crate) const TAG_UINT16LE_ARRAY: u64 = 69; // uint16 little-endian array
 pub(crate) const TAG_UINT32LE_ARRAY: u64 = 70; // uint32 little-endian array
 pub(crate) const TAG_UINT64LE_ARRAY: u64 = 71; // uint64 little-endian array
-#[allow(dead_code)]
 pub(crate) const TAG_SINT8_ARRAY: u64 = 72; // sint8 array
-#[allow(dead_code)]
 pub(crate) const TAG_SINT16BE_ARRAY: u64 = 73; // sint16 big-endian array
-#[allow(dead_code)]
 pub(crate) const TAG_SINT32BE_ARRAY: u64 = 74; // sint32 big-endian array
-#[allow(dead_code)]
 pub(crate) const TAG_SINT64BE_ARRAY: u64 = 75; // sint64 big-endian array
-#[allow(dead_code)]
 pub(crate) const TAG_SINT16LE_ARRAY: u64 = 77; // sint16 little-endian array
-#[allow(dead_code)]
 pub(crate) const TAG_SINT32LE_ARRAY: u64 = 78; // sint32 little-endian array
-#[allow(dead_code)]
 pub(crate) const TAG_SINT64LE_ARRAY: u64 = 79; // sint64 little-endian array
 pub(crate) const TAG_FLOAT16BE_ARRAY: u64 = 80; // float16 big-endian array
 pub(crate) const TAG_FLOAT32BE_ARRAY: u64 = 81; // float32 big-endian array
 pub(crate) const TAG_FLOAT64BE_ARRAY: u64 = 82; // float64 big-endian array
-#[allow(dead_code)]
 pub(crate) const TAG_FLOAT128BE_ARRAY: u64 = 83; // float128 big-endian array
 pub(crate) const TAG_FLOAT16LE_ARRAY: u64 = 84; // float16 little-endian array
 pub(crate) const TAG_FLOAT32LE_ARRAY: u64 = 85; // float32 little-endian array
 pub(crate) const TAG_FLOAT64LE_ARRAY: u64 = 86; // float64 little-endian array
-#[allow(dead_code)]
 pub(crate) const TAG_FLOAT128LE_ARRAY: u64 = 87; // float128 little-endian array
+pub(crate) const TAG_STRINGREF_NAMESPACE: u64 = 256; // Opens a new string table
 
 // Additional info values
 pub(crate) const FALSE: u8 = 20;
 pub(crate) const TRUE: u8 = 21;
 pub(crate) const NULL: u8 = 22;
 pub(crate) const UNDEFINED: u8 = 23;
-#[allow(dead_code)] // These are unassigned in the IANA registry
-pub(crate) const SIMPLE_VALUE: u8 = 24;
+pub(crate) const SIMPLE_VALUE: u8 = 24; // a 1-byte simple value follows
 pub(crate) const FLOAT16: u8 = 25;
 pub(crate) const FLOAT32: u8 = 26;
 pub(crate) const FLOAT64: u8 = 27;
 pub(crate) const INDEFINITE: u8 = 31;
 pub(crate) const BREAK: u8 = 0xff;
 
+// Newtype struct names used to pass chunked strings through serde
+pub(crate) const CHUNKED_BYTES_MARKER: &str = "__cbor_chunked_bytes__";
+pub(crate) const CHUNKED_TEXT_MARKER: &str = "__cbor_chunked_text__";
+// Newtype struct name for a tag number passed through `tags::current_cbor_tag`
+pub(crate) const CBOR_TAG_MARKER: &str = "__cbor_tag__";
+// Newtype struct name for an `Option` written by the `nullable` module
+pub(crate) const NULLABLE_MARKER: &str = "__cbor_nullable__";
+// Newtype struct name for `Value::Undefined`, written as the undefined simple value
+pub(crate) const UNDEFINED_MARKER: &str = "__cbor_undefined__";
+// Newtype struct name for `Value::Simple`, written as a simple value
+pub(crate) const SIMPLE_MARKER: &str = "__cbor_simple__";
+// Struct field name prefix for an integer map key, see `keys`
+pub(crate) const INTEGER_KEY_PREFIX: &str = "__cbor_key_";
+
 // DOS protection limits
 /// Default maximum allocation size (100MB) to prevent OOM attacks from malicious CBOR.
 ///
@@ -0,0 +1,274 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Conversions between [`Value`] and the value types of other CBOR crates
+//!
+//! Enabled with the `ciborium` and `serde_cbor` features. Integers outside
+//! the `i64` range of [`Value::Integer`] become bignums (tags 2 and 3), and
+//! bignums that fit the other crate's integer type become plain integers
+//! again, so integers round-trip exactly. Other tags are carried over as-is.
+//! Converting into [`Value`] fails on duplicate map keys, which a
+//! [`BTreeMap`] cannot hold.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    Error, Result, Value,
+    constants::{TAG_NEGATIVE_BIGNUM, TAG_POSITIVE_BIGNUM},
+};
+
+/// Build an integer, using a bignum when it does not fit in `i64`
+fn int_to_value(n: i128) -> Value {
+    if let Ok(i) = i64::try_from(n) {
+        return Value::Integer(i);
+    }
+    // Negative bignums encode -1 - n
+    let (tag, magnitude) = if n < 0 {
+        (TAG_NEGATIVE_BIGNUM, (-1 - n) as u128)
+    } else {
+        (TAG_POSITIVE_BIGNUM, n as u128)
+    };
+    let bytes = magnitude.to_be_bytes();
+    let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+    Value::Tag(tag, Box::new(Value::Bytes(bytes[start..].to_vec())))
+}
+
+/// The value of a bignum, if it fits in `i128`
+fn bignum_to_int(value: &Value) -> Option<i128> {
+    let Value::Tag(tag @ (TAG_POSITIVE_BIGNUM | TAG_NEGATIVE_BIGNUM), content) = value else {
+        return None;
+    };
+    let Value::Bytes(bytes) = content.as_ref() else {
+        return None;
+    };
+    let digits: Vec<u8> = bytes.iter().copied().skip_while(|&b| b == 0).collect();
+    if digits.len() > 16 {
+        return None;
+    }
+    let n = i128::try_from(digits.iter().fold(0u128, |acc, &b| (acc << 8) | b as u128)).ok()?;
+    Some(if *tag == TAG_NEGATIVE_BIGNUM {
+        -1 - n
+    } else {
+        n
+    })
+}
+
+/// Collect map entries, rejecting duplicate keys
+fn collect_map(entries: impl IntoIterator<Item = Result<(Value, Value)>>) -> Result<Value> {
+    let mut map = BTreeMap::new();
+    for entry in entries {
+        let (key, value) = entry?;
+        if map.insert(key, value).is_some() {
+            return Err(Error::Syntax("Duplicate map key".to_string()));
+        }
+    }
+    Ok(Value::Map(map))
+}
+
+#[cfg(feature = "ciborium")]
+impl From<Value> for ciborium::value::Value {
+    fn from(value: Value) -> Self {
+        use ciborium::value::{Integer, Value as Cv};
+
+        if let Some(n) = bignum_to_int(&value)
+            && let Ok(n) = Integer::try_from(n)
+        {
+            return Cv::Integer(n);
+        }
+        match value {
+            // ciborium has no undefined or other simple values
+            Value::Null | Value::Undefined | Value::Simple(_) => Cv::Null,
+            Value::Bool(b) => Cv::Bool(b),
+            Value::Integer(i) => Cv::Integer(i.into()),
+            Value::Float(f) => Cv::Float(f),
+            Value::Bytes(b) => Cv::Bytes(b),
+            Value::Text(s) => Cv::Text(s),
+            Value::Array(a) => Cv::Array(a.into_iter().map(Cv::from).collect()),
+            Value::Map(m) => Cv::Map(m.into_iter().map(|(k, v)| (k.into(), v.into())).collect()),
+            Value::OrderedMap(m) => {
+                Cv::Map(m.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+            }
+            Value::Tag(tag, content) => Cv::Tag(tag, Box::new((*content).into())),
+        }
+    }
+}
+
+#[cfg(feature = "ciborium")]
+impl TryFrom<ciborium::value::Value> for Value {
+    type Error = Error;
+
+    fn try_from(value: ciborium::value::Value) -> Result<Self> {
+        use ciborium::value::Value as Cv;
+
+        Ok(match value {
+            Cv::Null => Value::Null,
+            Cv::Bool(b) => Value::Bool(b),
+            Cv::Integer(i) => int_to_value(i.into()),
+            Cv::Float(f) => Value::Float(f),
+            Cv::Bytes(b) => Value::Bytes(b),
+            Cv::Text(s) => Value::Text(s),
+            Cv::Array(a) => {
+                Value::Array(a.into_iter().map(Value::try_from).collect::<Result<_>>()?)
+            }
+            Cv::Map(m) => collect_map(
+                m.into_iter()
+                    .map(|(k, v)| Ok((Value::try_from(k)?, Value::try_from(v)?))),
+            )?,
+            Cv::Tag(tag, content) => Value::Tag(tag, Box::new(Value::try_from(*content)?)),
+            _ => return Err(Error::Message("Unsupported ciborium value".to_string())),
+        })
+    }
+}
+
+#[cfg(feature = "serde_cbor")]
+impl From<Value> for serde_cbor::Value {
+    fn from(value: Value) -> Self {
+        use serde_cbor::Value as Sv;
+
+        if let Some(n) = bignum_to_int(&value) {
+            return Sv::Integer(n);
+        }
+        match value {
+            // serde_cbor has no undefined or other simple values
+            Value::Null | Value::Undefined | Value::Simple(_) => Sv::Null,
+            Value::Bool(b) => Sv::Bool(b),
+            Value::Integer(i) => Sv::Integer(i.into()),
+            Value::Float(f) => Sv::Float(f),
+            Value::Bytes(b) => Sv::Bytes(b),
+            Value::Text(s) => Sv::Text(s),
+            Value::Array(a) => Sv::Array(a.into_iter().map(Sv::from).collect()),
+            Value::Map(m) => Sv::Map(m.into_iter().map(|(k, v)| (k.into(), v.into())).collect()),
+            Value::OrderedMap(m) => {
+                Sv::Map(m.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+            }
+            Value::Tag(tag, content) => Sv::Tag(tag, Box::new((*content).into())),
+        }
+    }
+}
+
+#[cfg(feature = "serde_cbor")]
+impl TryFrom<serde_cbor::Value> for Value {
+    type Error = Error;
+
+    fn try_from(value: serde_cbor::Value) -> Result<Self> {
+        use serde_cbor::Value as Sv;
+
+        Ok(match value {
+            Sv::Null => Value::Null,
+            Sv::Bool(b) => Value::Bool(b),
+            Sv::Integer(i) => int_to_value(i),
+            Sv::Float(f) => Value::Float(f),
+            Sv::Bytes(b) => Value::Bytes(b),
+            Sv::Text(s) => Value::Text(s),
+            Sv::Array(a) => {
+                Value::Array(a.into_iter().map(Value::try_from).collect::<Result<_>>()?)
+            }
+            Sv::Map(m) => collect_map(
+                m.into_iter()
+                    .map(|(k, v)| Ok((Value::try_from(k)?, Value::try_from(v)?))),
+            )?,
+            Sv::Tag(tag, content) => Value::Tag(tag, Box::new(Value::try_from(*content)?)),
+            _ => return Err(Error::Message("Unsupported serde_cbor value".to_string())),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bignum_helpers() {
+        assert_eq!(int_to_value(-5), Value::Integer(-5));
+        let big = int_to_value(u64::MAX as i128);
+        assert_eq!(big, Value::Tag(2, Box::new(Value::Bytes(vec![0xff; 8]))));
+        assert_eq!(bignum_to_int(&big), Some(u64::MAX as i128));
+
+        let min = int_to_value(i128::MIN);
+        assert_eq!(bignum_to_int(&min), Some(i128::MIN));
+        let mut magnitude = vec![0x7f];
+        magnitude.extend([0xff; 15]);
+        assert_eq!(min, Value::Tag(3, Box::new(Value::Bytes(magnitude))));
+
+        // Leading zeros are allowed; more than 128 bits is not an i128
+        let padded = Value::Tag(2, Box::new(Value::Bytes(vec![0, 0, 1])));
+        assert_eq!(bignum_to_int(&padded), Some(1));
+        let huge = Value::Tag(2, Box::new(Value::Bytes(vec![1; 17])));
+        assert_eq!(bignum_to_int(&huge), None);
+        assert_eq!(
+            bignum_to_int(&Value::Tag(4, Box::new(Value::Bytes(vec![1])))),
+            None
+        );
+    }
+
+    #[cfg(feature = "ciborium")]
+    #[test]
+    fn test_ciborium_round_trip() {
+        use ciborium::value::{Integer, Value as Cv};
+
+        let original = Cv::Map(vec![
+            (Cv::Text("max".into()), Cv::Integer(Integer::from(u64::MAX))),
+            (
+                Cv::Integer(Integer::from(-3i64)),
+                Cv::Tag(32, Box::new(Cv::Text("self#jumbf=/c2pa".into()))),
+            ),
+            (
+                Cv::Text("list".into()),
+                Cv::Array(vec![Cv::Null, Cv::Float(1.5), Cv::Bytes(vec![1])]),
+            ),
+        ]);
+        let value = Value::try_from(original.clone()).unwrap();
+        let map = value.as_map().unwrap();
+        assert_eq!(
+            map[&Value::Text("max".into())],
+            Value::Tag(2, Box::new(Value::Bytes(vec![0xff; 8])))
+        );
+
+        // BTreeMap ordering differs from the original entry order
+        let (Cv::Map(entries), Cv::Map(expected)) = (Cv::from(value), original) else {
+            panic!("expected maps");
+        };
+        assert_eq!(entries.len(), expected.len());
+        for entry in &expected {
+            assert!(entries.contains(entry), "missing {:?}", entry);
+        }
+
+        let duplicate = Cv::Map(vec![
+            (Cv::Integer(Integer::from(1u8)), Cv::Null),
+            (Cv::Integer(Integer::from(1u8)), Cv::Bool(true)),
+        ]);
+        assert!(Value::try_from(duplicate).is_err());
+    }
+
+    #[cfg(feature = "serde_cbor")]
+    #[test]
+    fn test_serde_cbor_round_trip() {
+        use serde_cbor::Value as Sv;
+
+        let mut map = BTreeMap::new();
+        map.insert(Sv::Integer(-(1i128 << 64)), Sv::Text("min".into()));
+        map.insert(Sv::Integer(7), Sv::Tag(24, Box::new(Sv::Bytes(vec![0xa0]))));
+        map.insert(
+            Sv::Text("f".into()),
+            Sv::Array(vec![Sv::Float(0.5), Sv::Bool(false)]),
+        );
+        let original = Sv::Map(map);
+
+        let value = Value::try_from(original.clone()).unwrap();
+        assert_eq!(Sv::from(value), original);
+
+        // Bignums too large for i128 stay tagged
+        let huge = Value::Tag(2, Box::new(Value::Bytes(vec![1; 17])));
+        assert_eq!(Sv::from(huge), Sv::Tag(2, Box::new(Sv::Bytes(vec![1; 17]))));
+    }
+}
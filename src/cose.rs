@@ -0,0 +1,1676 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! COSE header maps, algorithm identifiers, signature structures and keys (RFC 9052)
+//!
+//! [`Algorithm`] names the registered algorithm identifiers, which are
+//! written as integers. [`sig_structure_sign1`] and its siblings build the
+//! bytes that are actually signed, and [`CoseSign1`] builds and parses
+//! single-signer messages with embedded or detached payloads. [`CoseKey`]
+//! holds a `COSE_Key` and converts EC2 and OKP keys to and from their raw
+//! public key bytes. [`HeaderMap`] holds the protected or unprotected
+//! header parameters of a COSE structure. Labels registered with IANA are named by [`HeaderLabel`]
+//! and written as their integer labels; any other label, such as the text
+//! labels C2PA adds (`"sigTst"`, `"rVals"`), is kept as a [`Value`]. Entries
+//! are written in the deterministic order of RFC 8949 §4.2.1, the bytewise
+//! order of their encoded labels, so the same parameters always encode to
+//! the same bytes.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::{
+//!     Value,
+//!     cose::{Algorithm, HeaderLabel, HeaderMap},
+//! };
+//!
+//! let mut header = HeaderMap::new();
+//! header.insert("sigTst", Value::Array(Vec::new()));
+//! header.insert(HeaderLabel::Kid, Value::Bytes(b"signer".to_vec()));
+//! header.insert(HeaderLabel::Alg, Algorithm::Es256.into());
+//!
+//! let cbor = c2pa_cbor::to_vec(&header).unwrap();
+//! assert_eq!(
+//!     c2pa_cbor::to_diagnostic(&cbor).unwrap(),
+//!     "{\n  1: -7,\n  4: h'7369676e6572',\n  \"sigTst\": []\n}"
+//! );
+//!
+//! let decoded: HeaderMap = c2pa_cbor::from_slice(&cbor).unwrap();
+//! assert_eq!(decoded.alg(), Some(Algorithm::Es256));
+//! assert_eq!(decoded, header);
+//! ```
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, btree_map},
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, MapAccess, Visitor},
+    ser::{SerializeMap, SerializeTuple},
+};
+use serde_bytes::{ByteBuf, Bytes};
+
+use crate::{
+    DEFAULT_MAX_ALLOCATION, Decoder, Error, Value,
+    constants::{MAJOR_ARRAY, MAJOR_BYTES, MAJOR_NEGATIVE, MAJOR_TEXT, MAJOR_UNSIGNED},
+    raw::write_header,
+    tags::Tagged,
+};
+
+/// Tag of a `COSE_Sign1` message
+pub const COSE_SIGN1_TAG: u64 = 18;
+
+macro_rules! define_header_labels {
+    ($($variant:ident = $label:literal, $description:literal;)*) => {
+        /// A COSE header parameter label
+        ///
+        /// Labels compare by their deterministic encoding, so
+        /// `HeaderLabel::Other(Value::Integer(1))` is the same label as
+        /// [`HeaderLabel::Alg`].
+        #[derive(Debug, Clone)]
+        pub enum HeaderLabel {
+            $(
+                #[doc = $description]
+                $variant,
+            )*
+            /// Any label not listed above
+            Other(Value),
+        }
+
+        impl HeaderLabel {
+            /// The registered label with this integer, if any
+            pub fn from_i64(label: i64) -> Option<Self> {
+                match label {
+                    $($label => Some(HeaderLabel::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// The integer of a registered label
+            pub fn registered(&self) -> Option<i64> {
+                match self {
+                    $(HeaderLabel::$variant => Some($label),)*
+                    HeaderLabel::Other(_) => None,
+                }
+            }
+        }
+    };
+}
+
+// From the IANA COSE Header Parameters registry
+define_header_labels! {
+    Alg = 1, "Cryptographic algorithm";
+    Crit = 2, "Critical headers to be understood";
+    ContentType = 3, "Content type of the payload";
+    Kid = 4, "Key identifier";
+    Iv = 5, "Full initialization vector";
+    PartialIv = 6, "Partial initialization vector";
+    CounterSignature = 7, "CBOR-encoded signature structure (deprecated)";
+    CounterSignature0 = 9, "Counter signature with implied signer and headers (deprecated)";
+    KidContext = 10, "Identifies the context for the key identifier";
+    CounterSignatureV2 = 11, "V2 countersignature attribute";
+    CounterSignature0V2 = 12, "V2 abbreviated countersignature";
+    Kcwt = 13, "A CBOR Web Token containing a COSE_Key in a cnf claim";
+    Kccs = 14, "A CWT Claims Set containing a COSE_Key in a cnf claim";
+    CwtClaims = 15, "CWT claims set";
+    Typ = 16, "Content type of the complete COSE object";
+    X5Bag = 32, "An unordered bag of X.509 certificates";
+    X5Chain = 33, "An ordered chain of X.509 certificates";
+    X5T = 34, "Hash of an X.509 certificate";
+    X5U = 35, "URI pointing to an X.509 certificate";
+}
+
+impl HeaderLabel {
+    /// The label as a CBOR value
+    pub fn to_value(&self) -> Value {
+        match (self, self.registered()) {
+            (HeaderLabel::Other(value), _) => value.clone(),
+            (_, label) => Value::Integer(label.expect("named labels are registered")),
+        }
+    }
+
+    /// Major type, argument and content of an integer or text label, which
+    /// order the same way as its deterministic encoding
+    fn sort_key(&self) -> Option<(u8, u64, &[u8])> {
+        match self {
+            HeaderLabel::Other(Value::Integer(n)) if *n >= 0 => {
+                Some((MAJOR_UNSIGNED, *n as u64, &[]))
+            }
+            // -1 - n, the argument of a negative integer
+            HeaderLabel::Other(Value::Integer(n)) => Some((MAJOR_NEGATIVE, !*n as u64, &[])),
+            HeaderLabel::Other(Value::Text(s)) => Some((MAJOR_TEXT, s.len() as u64, s.as_bytes())),
+            HeaderLabel::Other(_) => None,
+            registered => Some((MAJOR_UNSIGNED, registered.registered()? as u64, &[])),
+        }
+    }
+}
+
+impl From<i64> for HeaderLabel {
+    fn from(label: i64) -> Self {
+        HeaderLabel::from_i64(label).unwrap_or(HeaderLabel::Other(Value::Integer(label)))
+    }
+}
+
+impl From<&str> for HeaderLabel {
+    fn from(label: &str) -> Self {
+        HeaderLabel::Other(Value::Text(label.to_string()))
+    }
+}
+
+impl From<String> for HeaderLabel {
+    fn from(label: String) -> Self {
+        HeaderLabel::Other(Value::Text(label))
+    }
+}
+
+impl From<Value> for HeaderLabel {
+    fn from(label: Value) -> Self {
+        match label {
+            Value::Integer(label) => label.into(),
+            label => HeaderLabel::Other(label),
+        }
+    }
+}
+
+impl Ord for HeaderLabel {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.sort_key(), other.sort_key()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            // COSE labels are integers or text; anything else still compares
+            // by its encoding
+            _ => self
+                .to_value()
+                .to_canonical_vec()
+                .cmp(&other.to_value().to_canonical_vec()),
+        }
+    }
+}
+
+impl PartialOrd for HeaderLabel {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for HeaderLabel {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for HeaderLabel {}
+
+impl Serialize for HeaderLabel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (self, self.registered()) {
+            (HeaderLabel::Other(value), _) => value.serialize(serializer),
+            (_, label) => serializer.serialize_i64(label.expect("named labels are registered")),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for HeaderLabel {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Value::deserialize(deserializer).map(HeaderLabel::from)
+    }
+}
+
+macro_rules! define_algorithms {
+    ($($variant:ident = $id:literal, $name:literal, $description:literal;)*) => {
+        /// A COSE algorithm identifier
+        ///
+        /// Serializes as its integer identifier. Identifiers compare by
+        /// value, so `Algorithm::Other(-7)` is the same algorithm as
+        /// [`Algorithm::Es256`].
+        ///
+        /// # Example
+        /// ```
+        /// use c2pa_cbor::cose::Algorithm;
+        ///
+        /// assert_eq!(Algorithm::from(-37), Algorithm::Ps256);
+        /// assert_eq!(Algorithm::Ps256.name(), Some("PS256"));
+        /// assert_eq!(c2pa_cbor::to_vec(&Algorithm::Ps256).unwrap(), [0x38, 0x24]);
+        /// assert_eq!(i64::from(Algorithm::from(-1000)), -1000);
+        /// ```
+        #[derive(Debug, Clone, Copy)]
+        #[non_exhaustive]
+        pub enum Algorithm {
+            $(
+                #[doc = $description]
+                $variant,
+            )*
+            /// Any identifier not listed above
+            Other(i64),
+        }
+
+        impl Algorithm {
+            /// The integer identifier
+            pub fn to_i64(self) -> i64 {
+                match self {
+                    $(Algorithm::$variant => $id,)*
+                    Algorithm::Other(id) => id,
+                }
+            }
+
+            /// The name in the IANA registry, if the identifier is listed
+            pub fn name(self) -> Option<&'static str> {
+                match Algorithm::from(self.to_i64()) {
+                    $(Algorithm::$variant => Some($name),)*
+                    Algorithm::Other(_) => None,
+                }
+            }
+        }
+
+        impl From<i64> for Algorithm {
+            fn from(id: i64) -> Self {
+                match id {
+                    $($id => Algorithm::$variant,)*
+                    _ => Algorithm::Other(id),
+                }
+            }
+        }
+    };
+}
+
+// From the IANA COSE Algorithms registry
+define_algorithms! {
+    Rs512 = -259, "RS512", "RSASSA-PKCS1-v1_5 using SHA-512";
+    Rs384 = -258, "RS384", "RSASSA-PKCS1-v1_5 using SHA-384";
+    Rs256 = -257, "RS256", "RSASSA-PKCS1-v1_5 using SHA-256";
+    Ed448 = -53, "Ed448", "EdDSA using the Ed448 parameter set";
+    Esp512 = -52, "ESP512", "ECDSA using the P-521 curve and SHA-512";
+    Esp384 = -51, "ESP384", "ECDSA using the P-384 curve and SHA-384";
+    Es256K = -47, "ES256K", "ECDSA using secp256k1 and SHA-256";
+    Shake256 = -45, "SHAKE256", "SHAKE-256 512-bit hash";
+    Sha512 = -44, "SHA-512", "SHA-2 512-bit hash";
+    Sha384 = -43, "SHA-384", "SHA-2 384-bit hash";
+    Ps512 = -39, "PS512", "RSASSA-PSS using SHA-512";
+    Ps384 = -38, "PS384", "RSASSA-PSS using SHA-384";
+    Ps256 = -37, "PS256", "RSASSA-PSS using SHA-256";
+    Es512 = -36, "ES512", "ECDSA using SHA-512";
+    Es384 = -35, "ES384", "ECDSA using SHA-384";
+    Ed25519 = -19, "Ed25519", "EdDSA using the Ed25519 parameter set";
+    Shake128 = -18, "SHAKE128", "SHAKE-128 256-bit hash";
+    Sha256 = -16, "SHA-256", "SHA-2 256-bit hash";
+    Sha256_64 = -15, "SHA-256/64", "SHA-2 256-bit hash truncated to 64 bits";
+    Esp256 = -9, "ESP256", "ECDSA using the P-256 curve and SHA-256";
+    EdDsa = -8, "EdDSA", "EdDSA";
+    Es256 = -7, "ES256", "ECDSA using SHA-256";
+    Direct = -6, "direct", "Direct use of a content encryption key";
+    A128Gcm = 1, "A128GCM", "AES-GCM with a 128-bit key";
+    A192Gcm = 2, "A192GCM", "AES-GCM with a 192-bit key";
+    A256Gcm = 3, "A256GCM", "AES-GCM with a 256-bit key";
+    Hmac256_64 = 4, "HMAC 256/64", "HMAC with SHA-256 truncated to 64 bits";
+    Hmac256 = 5, "HMAC 256/256", "HMAC with SHA-256";
+    Hmac384 = 6, "HMAC 384/384", "HMAC with SHA-384";
+    Hmac512 = 7, "HMAC 512/512", "HMAC with SHA-512";
+    AesCcm16_64_128 = 10, "AES-CCM-16-64-128", "AES-CCM with a 128-bit key, 64-bit tag and 13-byte nonce";
+    AesCcm16_64_256 = 11, "AES-CCM-16-64-256", "AES-CCM with a 256-bit key, 64-bit tag and 13-byte nonce";
+    AesCcm64_64_128 = 12, "AES-CCM-64-64-128", "AES-CCM with a 128-bit key, 64-bit tag and 7-byte nonce";
+    AesCcm64_64_256 = 13, "AES-CCM-64-64-256", "AES-CCM with a 256-bit key, 64-bit tag and 7-byte nonce";
+    AesMac128_64 = 14, "AES-MAC 128/64", "AES-CBC-MAC with a 128-bit key and 64-bit tag";
+    AesMac256_64 = 15, "AES-MAC 256/64", "AES-CBC-MAC with a 256-bit key and 64-bit tag";
+    ChaCha20Poly1305 = 24, "ChaCha20/Poly1305", "ChaCha20/Poly1305 with a 256-bit key and 128-bit tag";
+    AesMac128_128 = 25, "AES-MAC 128/128", "AES-CBC-MAC with a 128-bit key and 128-bit tag";
+    AesMac256_128 = 26, "AES-MAC 256/128", "AES-CBC-MAC with a 256-bit key and 128-bit tag";
+    AesCcm16_128_128 = 30, "AES-CCM-16-128-128", "AES-CCM with a 128-bit key, 128-bit tag and 13-byte nonce";
+    AesCcm16_128_256 = 31, "AES-CCM-16-128-256", "AES-CCM with a 256-bit key, 128-bit tag and 13-byte nonce";
+    AesCcm64_128_128 = 32, "AES-CCM-64-128-128", "AES-CCM with a 128-bit key, 128-bit tag and 7-byte nonce";
+    AesCcm64_128_256 = 33, "AES-CCM-64-128-256", "AES-CCM with a 256-bit key, 128-bit tag and 7-byte nonce";
+}
+
+impl From<Algorithm> for i64 {
+    fn from(alg: Algorithm) -> i64 {
+        alg.to_i64()
+    }
+}
+
+impl From<Algorithm> for Value {
+    fn from(alg: Algorithm) -> Value {
+        Value::Integer(alg.to_i64())
+    }
+}
+
+impl TryFrom<Value> for Algorithm {
+    type Error = Error;
+
+    /// Fails unless the value is an integer
+    fn try_from(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Integer(id) => Ok(id.into()),
+            _ => Err(Error::Message(
+                "COSE algorithm identifier must be an integer".to_string(),
+            )),
+        }
+    }
+}
+
+impl PartialEq for Algorithm {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_i64() == other.to_i64()
+    }
+}
+
+impl Eq for Algorithm {}
+
+impl Hash for Algorithm {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_i64().hash(state);
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{}", self.to_i64()),
+        }
+    }
+}
+
+impl Serialize for Algorithm {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(self.to_i64())
+    }
+}
+
+impl<'de> Deserialize<'de> for Algorithm {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        i64::deserialize(deserializer).map(Algorithm::from)
+    }
+}
+
+/// COSE header parameters, in deterministic order
+///
+/// Serializes as a map with integer keys for registered labels. Decoding
+/// fails if the same label appears twice, as RFC 9052 §3 requires.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeaderMap {
+    entries: BTreeMap<HeaderLabel, Value>,
+}
+
+impl HeaderMap {
+    /// An empty header map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a parameter, returning its previous value
+    pub fn insert(&mut self, label: impl Into<HeaderLabel>, value: Value) -> Option<Value> {
+        self.entries.insert(label.into(), value)
+    }
+
+    /// The value of a parameter
+    pub fn get(&self, label: impl Into<HeaderLabel>) -> Option<&Value> {
+        self.entries.get(&label.into())
+    }
+
+    /// Remove a parameter, returning its value
+    pub fn remove(&mut self, label: impl Into<HeaderLabel>) -> Option<Value> {
+        self.entries.remove(&label.into())
+    }
+
+    /// Returns true if the parameter is set
+    pub fn contains(&self, label: impl Into<HeaderLabel>) -> bool {
+        self.entries.contains_key(&label.into())
+    }
+
+    /// Number of parameters
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no parameters are set
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The parameters in deterministic order
+    pub fn iter(&self) -> btree_map::Iter<'_, HeaderLabel, Value> {
+        self.entries.iter()
+    }
+
+    /// The algorithm (label 1), if it is an integer
+    pub fn alg(&self) -> Option<Algorithm> {
+        self.get(HeaderLabel::Alg)?.clone().try_into().ok()
+    }
+
+    /// The key identifier (label 4), if it is a byte string
+    pub fn kid(&self) -> Option<&[u8]> {
+        match self.get(HeaderLabel::Kid)? {
+            Value::Bytes(kid) => Some(kid),
+            _ => None,
+        }
+    }
+
+    /// The encoding of a protected header, to be wrapped in a byte string
+    ///
+    /// An empty map is encoded as zero bytes rather than as `0xa0`, as RFC
+    /// 9052 §3 requires. Pass the result as-is to [`sig_structure_sign1`]
+    /// and friends, which add the byte string header themselves.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::cose::{Algorithm, HeaderLabel, HeaderMap};
+    ///
+    /// let mut header = HeaderMap::new();
+    /// assert!(header.to_protected_bytes().unwrap().is_empty());
+    /// header.insert(HeaderLabel::Alg, Algorithm::Es256.into());
+    /// assert_eq!(header.to_protected_bytes().unwrap(), [0xa1, 0x01, 0x26]);
+    /// ```
+    pub fn to_protected_bytes(&self) -> crate::Result<Vec<u8>> {
+        if self.is_empty() {
+            return Ok(Vec::new());
+        }
+        crate::to_vec(self)
+    }
+}
+
+impl<'a> IntoIterator for &'a HeaderMap {
+    type Item = (&'a HeaderLabel, &'a Value);
+    type IntoIter = btree_map::Iter<'a, HeaderLabel, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<(HeaderLabel, Value)> for HeaderMap {
+    fn from_iter<I: IntoIterator<Item = (HeaderLabel, Value)>>(iter: I) -> Self {
+        HeaderMap {
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Serialize for HeaderMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.entries.len()))?;
+        for (label, value) in &self.entries {
+            map.serialize_entry(label, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for HeaderMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct HeaderMapVisitor;
+
+        impl<'de> Visitor<'de> for HeaderMapVisitor {
+            type Value = HeaderMap;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a COSE header map")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<HeaderMap, A::Error> {
+                let mut entries = BTreeMap::new();
+                while let Some(label) = map.next_key::<HeaderLabel>()? {
+                    match entries.entry(label) {
+                        btree_map::Entry::Vacant(entry) => {
+                            entry.insert(map.next_value()?);
+                        }
+                        btree_map::Entry::Occupied(entry) => {
+                            return Err(de::Error::custom(format!(
+                                "duplicate header label {:?}",
+                                entry.key().to_value()
+                            )));
+                        }
+                    }
+                }
+                Ok(HeaderMap { entries })
+            }
+        }
+
+        deserializer.deserialize_map(HeaderMapVisitor)
+    }
+}
+
+/// Bytes to sign for a `COSE_Sign1` (RFC 9052 §4.4)
+///
+/// Builds `["Signature1", protected, external_aad, payload]` in
+/// deterministic encoding. `protected` is the encoded protected header map
+/// exactly as it appears in the message, or empty when there is none (see
+/// [`HeaderMap::to_protected_bytes`]); it is wrapped in a byte string here,
+/// so it must not be wrapped already.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::cose::sig_structure_sign1;
+///
+/// // Protected header {1: -7}
+/// let tbs = sig_structure_sign1(&[0xa1, 0x01, 0x26], b"", b"payload");
+/// assert_eq!(
+///     c2pa_cbor::to_diagnostic(&tbs).unwrap(),
+///     "[\n  \"Signature1\",\n  h'a10126',\n  h'',\n  h'7061796c6f6164'\n]"
+/// );
+/// ```
+pub fn sig_structure_sign1(protected: &[u8], external_aad: &[u8], payload: &[u8]) -> Vec<u8> {
+    signature_structure("Signature1", &[protected, external_aad, payload], &[])
+}
+
+/// Bytes to sign for one signer of a `COSE_Sign` (RFC 9052 §4.4)
+///
+/// Builds `["Signature", body_protected, sign_protected, external_aad, payload]`,
+/// where `body_protected` is the protected header of the message and
+/// `sign_protected` that of the `COSE_Signature`. Both are passed as in
+/// [`sig_structure_sign1`].
+pub fn sig_structure_sign(
+    body_protected: &[u8],
+    sign_protected: &[u8],
+    external_aad: &[u8],
+    payload: &[u8],
+) -> Vec<u8> {
+    signature_structure(
+        "Signature",
+        &[body_protected, sign_protected, external_aad, payload],
+        &[],
+    )
+}
+
+/// Bytes to sign for a countersignature (RFC 9338 §3.3)
+///
+/// Builds `["CounterSignature", body_protected, sign_protected, external_aad,
+/// payload, other_fields]`, where `body_protected` and `payload` are those
+/// of the structure being countersigned and `sign_protected` is the
+/// protected header of the countersignature. `other_fields` holds the
+/// signature or tag of the countersigned structure, such as the signature of
+/// a `COSE_Sign1`; it is left out when empty.
+pub fn sig_structure_counter_signature(
+    body_protected: &[u8],
+    sign_protected: &[u8],
+    external_aad: &[u8],
+    payload: &[u8],
+    other_fields: &[&[u8]],
+) -> Vec<u8> {
+    signature_structure(
+        "CounterSignature",
+        &[body_protected, sign_protected, external_aad, payload],
+        other_fields,
+    )
+}
+
+/// Encode `[context, fields..., [other_fields...]]` with every field as a byte string
+fn signature_structure(context: &str, fields: &[&[u8]], other_fields: &[&[u8]]) -> Vec<u8> {
+    let capacity: usize = fields.iter().chain(other_fields).map(|f| f.len() + 9).sum();
+    let mut out = Vec::with_capacity(capacity + context.len() + 18);
+    let items = 1 + fields.len() + usize::from(!other_fields.is_empty());
+    write_header(&mut out, MAJOR_ARRAY, items as u64);
+    write_header(&mut out, MAJOR_TEXT, context.len() as u64);
+    out.extend_from_slice(context.as_bytes());
+    for field in fields {
+        write_header(&mut out, MAJOR_BYTES, field.len() as u64);
+        out.extend_from_slice(field);
+    }
+    if !other_fields.is_empty() {
+        write_header(&mut out, MAJOR_ARRAY, other_fields.len() as u64);
+        for field in other_fields {
+            write_header(&mut out, MAJOR_BYTES, field.len() as u64);
+            out.extend_from_slice(field);
+        }
+    }
+    out
+}
+
+/// A `COSE_Sign1` message (RFC 9052 §4.2)
+///
+/// The protected header is kept as the exact bytes that were signed, so a
+/// parsed message can be verified without re-encoding it. A message is
+/// created only by signing, through [`sign`](Self::sign) or
+/// [`sign_detached`](Self::sign_detached), which compute the bytes to sign
+/// from the same payload that goes into the message. When verifying,
+/// [`to_be_signed`](Self::to_be_signed) only accepts a message that carries
+/// its payload and [`to_be_signed_detached`](Self::to_be_signed_detached)
+/// only one whose payload is nil, so the payload signed is always the one
+/// the message refers to.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::cose::{Algorithm, CoseSign1, HeaderLabel, HeaderMap};
+///
+/// let mut protected = HeaderMap::new();
+/// protected.insert(HeaderLabel::Alg, Algorithm::Es256.into());
+/// // Stand-in for a real signature algorithm
+/// let sign = |tbs: &[u8]| Ok::<_, c2pa_cbor::Error>(tbs.len().to_be_bytes().to_vec());
+///
+/// let manifest = b"detached manifest";
+/// let message = CoseSign1::sign_detached(&protected, HeaderMap::new(), manifest, b"", sign)
+///     .unwrap();
+/// let cbor = message.to_vec().unwrap();
+///
+/// let parsed = CoseSign1::from_slice(&cbor).unwrap();
+/// assert!(parsed.is_detached());
+/// assert_eq!(parsed.protected_header().unwrap().alg(), Some(Algorithm::Es256));
+/// let tbs = parsed.to_be_signed_detached(b"", manifest).unwrap();
+/// assert_eq!(parsed.signature(), sign(&tbs).unwrap());
+/// // The embedded-payload path refuses a detached message
+/// assert!(parsed.to_be_signed(b"").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoseSign1 {
+    protected: Vec<u8>,
+    unprotected: HeaderMap,
+    payload: Option<Vec<u8>>,
+    signature: Vec<u8>,
+}
+
+impl CoseSign1 {
+    /// Sign `payload` and carry it in the message
+    ///
+    /// `signer` receives the bytes to sign (see [`sig_structure_sign1`]) and
+    /// returns the signature.
+    pub fn sign<E: From<Error>>(
+        protected: &HeaderMap,
+        unprotected: HeaderMap,
+        payload: Vec<u8>,
+        external_aad: &[u8],
+        signer: impl FnOnce(&[u8]) -> Result<Vec<u8>, E>,
+    ) -> Result<Self, E> {
+        let protected = protected.to_protected_bytes()?;
+        let signature = signer(&sig_structure_sign1(&protected, external_aad, &payload))?;
+        Ok(CoseSign1 {
+            protected,
+            unprotected,
+            payload: Some(payload),
+            signature,
+        })
+    }
+
+    /// Sign `payload` and leave it out of the message, which carries nil instead
+    ///
+    /// The payload has to be supplied again to verify the signature, see
+    /// [`to_be_signed_detached`](Self::to_be_signed_detached).
+    pub fn sign_detached<E: From<Error>>(
+        protected: &HeaderMap,
+        unprotected: HeaderMap,
+        payload: &[u8],
+        external_aad: &[u8],
+        signer: impl FnOnce(&[u8]) -> Result<Vec<u8>, E>,
+    ) -> Result<Self, E> {
+        let protected = protected.to_protected_bytes()?;
+        let signature = signer(&sig_structure_sign1(&protected, external_aad, payload))?;
+        Ok(CoseSign1 {
+            protected,
+            unprotected,
+            payload: None,
+            signature,
+        })
+    }
+
+    /// Parse a message, with or without its tag
+    pub fn from_slice(bytes: &[u8]) -> crate::Result<Self> {
+        let mut decoder = Decoder::from_slice(bytes).with_max_allocation(DEFAULT_MAX_ALLOCATION);
+        match decoder.peek_tag()? {
+            None | Some(COSE_SIGN1_TAG) => {}
+            Some(tag) => {
+                return Err(Error::Syntax(format!(
+                    "Expected a COSE_Sign1 (tag 18), found tag {}",
+                    tag
+                )));
+            }
+        }
+        let message = decoder.decode()?;
+        decoder.end()?;
+        Ok(message)
+    }
+
+    /// Encode the message with its tag
+    ///
+    /// The untagged form is what `Serialize` writes, for embedding the
+    /// message in another structure.
+    pub fn to_vec(&self) -> crate::Result<Vec<u8>> {
+        crate::to_vec(&Tagged::new(Some(COSE_SIGN1_TAG), self))
+    }
+
+    /// The bytes to verify the signature against, for a message that carries its payload
+    ///
+    /// Returns an error if the payload is detached.
+    pub fn to_be_signed(&self, external_aad: &[u8]) -> crate::Result<Vec<u8>> {
+        match &self.payload {
+            Some(payload) => Ok(sig_structure_sign1(&self.protected, external_aad, payload)),
+            None => Err(Error::Message(
+                "COSE_Sign1 payload is detached and must be supplied".to_string(),
+            )),
+        }
+    }
+
+    /// The bytes to verify the signature against, for a message whose payload is detached
+    ///
+    /// Returns an error if the message carries its own payload, which is
+    /// what would be signed instead.
+    pub fn to_be_signed_detached(
+        &self,
+        external_aad: &[u8],
+        payload: &[u8],
+    ) -> crate::Result<Vec<u8>> {
+        match self.payload {
+            Some(_) => Err(Error::Message(
+                "COSE_Sign1 carries its own payload".to_string(),
+            )),
+            None => Ok(sig_structure_sign1(&self.protected, external_aad, payload)),
+        }
+    }
+
+    /// The protected header exactly as it was signed
+    pub fn protected_bytes(&self) -> &[u8] {
+        &self.protected
+    }
+
+    /// The protected header, decoded
+    pub fn protected_header(&self) -> crate::Result<HeaderMap> {
+        if self.protected.is_empty() {
+            return Ok(HeaderMap::new());
+        }
+        crate::from_slice(&self.protected)
+    }
+
+    /// The unprotected header
+    pub fn unprotected(&self) -> &HeaderMap {
+        &self.unprotected
+    }
+
+    /// The unprotected header, which can be changed without invalidating the signature
+    pub fn unprotected_mut(&mut self) -> &mut HeaderMap {
+        &mut self.unprotected
+    }
+
+    /// The embedded payload, or `None` if it is detached
+    pub fn payload(&self) -> Option<&[u8]> {
+        self.payload.as_deref()
+    }
+
+    /// Returns true if the payload is not carried in the message
+    pub fn is_detached(&self) -> bool {
+        self.payload.is_none()
+    }
+
+    /// Take the payload out of the message, leaving nil in its place
+    ///
+    /// The signature stays valid for the returned payload.
+    pub fn detach(&mut self) -> Option<Vec<u8>> {
+        self.payload.take()
+    }
+
+    /// The signature
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+}
+
+impl Serialize for CoseSign1 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut message = serializer.serialize_tuple(4)?;
+        message.serialize_element(Bytes::new(&self.protected))?;
+        message.serialize_element(&self.unprotected)?;
+        message.serialize_element(&self.payload.as_deref().map(Bytes::new))?;
+        message.serialize_element(Bytes::new(&self.signature))?;
+        message.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for CoseSign1 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (protected, unprotected, payload, signature) =
+            <(ByteBuf, HeaderMap, Option<ByteBuf>, ByteBuf)>::deserialize(deserializer)?;
+        Ok(CoseSign1 {
+            protected: protected.into_vec(),
+            unprotected,
+            payload: payload.map(ByteBuf::into_vec),
+            signature: signature.into_vec(),
+        })
+    }
+}
+
+macro_rules! define_key_registry {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident = $id:literal, $description:literal;)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone)]
+        #[non_exhaustive]
+        pub enum $name {
+            $(
+                #[doc = $description]
+                $variant,
+            )*
+            /// Any integer value not listed above
+            Other(i64),
+            /// A text value
+            Text(String),
+        }
+
+        impl $name {
+            /// The integer value, or `None` for a text value
+            pub fn to_i64(&self) -> Option<i64> {
+                match self {
+                    $($name::$variant => Some($id),)*
+                    $name::Other(id) => Some(*id),
+                    $name::Text(_) => None,
+                }
+            }
+        }
+
+        impl From<i64> for $name {
+            fn from(id: i64) -> Self {
+                match id {
+                    $($id => $name::$variant,)*
+                    _ => $name::Other(id),
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(text: &str) -> Self {
+                $name::Text(text.to_string())
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(text: String) -> Self {
+                $name::Text(text)
+            }
+        }
+
+        impl From<$name> for Value {
+            fn from(value: $name) -> Self {
+                match value {
+                    $name::Text(text) => Value::Text(text),
+                    other => Value::Integer(other.to_i64().unwrap_or_default()),
+                }
+            }
+        }
+
+        impl TryFrom<Value> for $name {
+            type Error = Error;
+
+            fn try_from(value: Value) -> crate::Result<Self> {
+                match value {
+                    Value::Integer(id) => Ok($name::from(id)),
+                    Value::Text(text) => Ok($name::Text(text)),
+                    _ => Err(Error::Message(format!(
+                        "{} must be an integer or text",
+                        stringify!($name)
+                    ))),
+                }
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                match (self, other) {
+                    ($name::Text(a), $name::Text(b)) => a == b,
+                    _ => self.to_i64().is_some() && self.to_i64() == other.to_i64(),
+                }
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                match self {
+                    $name::Text(text) => text.hash(state),
+                    other => other.to_i64().hash(state),
+                }
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    $name::Text(text) => serializer.serialize_str(text),
+                    other => serializer.serialize_i64(other.to_i64().unwrap_or_default()),
+                }
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                $name::try_from(Value::deserialize(deserializer)?).map_err(de::Error::custom)
+            }
+        }
+    };
+}
+
+// From the IANA COSE Key Types, Elliptic Curves and key_ops registries
+define_key_registry! {
+    /// A COSE key type (`kty`)
+    KeyType {
+        Okp = 1, "Octet key pair, for EdDSA and ECDH with X25519 and X448";
+        Ec2 = 2, "Elliptic curve key with x and y coordinates";
+        Rsa = 3, "RSA key";
+        Symmetric = 4, "Symmetric key";
+        HssLms = 5, "Public key for HSS/LMS hash-based digital signatures";
+        WalnutDsa = 6, "WalnutDSA public key";
+    }
+}
+
+define_key_registry! {
+    /// A COSE elliptic curve (`crv`)
+    Curve {
+        P256 = 1, "NIST P-256, also known as secp256r1";
+        P384 = 2, "NIST P-384, also known as secp384r1";
+        P521 = 3, "NIST P-521, also known as secp521r1";
+        X25519 = 4, "X25519 for use with ECDH only";
+        X448 = 5, "X448 for use with ECDH only";
+        Ed25519 = 6, "Ed25519 for use with EdDSA only";
+        Ed448 = 7, "Ed448 for use with EdDSA only";
+        Secp256k1 = 8, "SECG secp256k1 curve";
+    }
+}
+
+define_key_registry! {
+    /// A permitted use of a key (`key_ops`)
+    KeyOperation {
+        Sign = 1, "Compute a digital signature or MAC";
+        Verify = 2, "Verify a digital signature or MAC";
+        Encrypt = 3, "Encrypt content";
+        Decrypt = 4, "Decrypt content and validate decryption, if applicable";
+        WrapKey = 5, "Encrypt a key";
+        UnwrapKey = 6, "Decrypt a key and validate decryption, if applicable";
+        DeriveKey = 7, "Derive a key";
+        DeriveBits = 8, "Derive bits not to be used as a key";
+        MacCreate = 9, "Create a MAC value";
+        MacVerify = 10, "Validate a MAC value";
+    }
+}
+
+impl Curve {
+    /// Length in bytes of a coordinate or OKP public key on this curve, if known
+    pub fn coordinate_len(&self) -> Option<usize> {
+        match self {
+            Curve::P256 | Curve::Secp256k1 | Curve::X25519 | Curve::Ed25519 => Some(32),
+            Curve::P384 => Some(48),
+            Curve::P521 => Some(66),
+            Curve::X448 => Some(56),
+            Curve::Ed448 => Some(57),
+            Curve::Other(_) | Curve::Text(_) => None,
+        }
+    }
+}
+
+/// The y coordinate of an EC2 key: the full value, or only its sign bit for point compression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum YCoordinate {
+    /// The coordinate, big-endian
+    Bytes(Vec<u8>),
+    /// The least significant bit of the coordinate
+    Sign(bool),
+}
+
+// Common parameter labels (RFC 9052 §7.1)
+const KTY: i64 = 1;
+const KID: i64 = 2;
+const ALG: i64 = 3;
+const KEY_OPS: i64 = 4;
+const BASE_IV: i64 = 5;
+// EC2 and OKP parameter labels (RFC 9053 §7.1 and §7.2)
+const CRV: i64 = -1;
+const X: i64 = -2;
+const Y: i64 = -3;
+const D: i64 = -4;
+
+/// A `COSE_Key` (RFC 9052 §7)
+///
+/// The common parameters and the parameters of EC2 and OKP keys have their
+/// own fields; everything else, including the parameters of other key types
+/// such as RSA, is kept in [`params`](Self::params). Serializes with integer
+/// labels in deterministic order. The key type, curve and key operations may
+/// also be text, as RFC 9052 allows.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::cose::{Algorithm, CoseKey, Curve, KeyType};
+///
+/// // An uncompressed P-256 public key: 0x04, x, y
+/// let mut point = vec![0x04];
+/// point.extend_from_slice(&[0x11; 32]);
+/// point.extend_from_slice(&[0x22; 32]);
+///
+/// let mut key = CoseKey::from_ec_point(Curve::P256, &point).unwrap();
+/// key.alg = Some(Algorithm::Es256);
+/// let cbor = key.to_vec().unwrap();
+///
+/// let decoded = CoseKey::from_slice(&cbor).unwrap();
+/// assert_eq!(decoded.kty, KeyType::Ec2);
+/// assert_eq!(decoded.ec_point().unwrap(), point);
+/// assert_eq!(decoded, key);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoseKey {
+    /// Key type (label 1)
+    pub kty: KeyType,
+    /// Key identifier (label 2)
+    pub kid: Option<Vec<u8>>,
+    /// Algorithm the key is restricted to (label 3)
+    ///
+    /// An algorithm given as text is kept in [`params`](Self::params).
+    pub alg: Option<Algorithm>,
+    /// Permitted operations (label 4), all if empty
+    pub key_ops: Vec<KeyOperation>,
+    /// Base IV (label 5)
+    pub base_iv: Option<Vec<u8>>,
+    /// Curve of an EC2 or OKP key (label -1)
+    pub crv: Option<Curve>,
+    /// x coordinate of an EC2 key, or public key of an OKP key (label -2)
+    pub x: Option<Vec<u8>>,
+    /// y coordinate of an EC2 key (label -3)
+    pub y: Option<YCoordinate>,
+    /// Private key of an EC2 or OKP key (label -4)
+    pub d: Option<Vec<u8>>,
+    /// Every other parameter, keyed by label
+    pub params: BTreeMap<Value, Value>,
+}
+
+impl CoseKey {
+    /// A key of type `kty` with no parameters set
+    pub fn new(kty: KeyType) -> Self {
+        CoseKey {
+            kty,
+            kid: None,
+            alg: None,
+            key_ops: Vec::new(),
+            base_iv: None,
+            crv: None,
+            x: None,
+            y: None,
+            d: None,
+            params: BTreeMap::new(),
+        }
+    }
+
+    /// An EC2 public key from a SEC1 encoded point, uncompressed (`0x04`) or compressed (`0x02`, `0x03`)
+    ///
+    /// Returns an error if the encoding is not recognized or its length
+    /// does not match the curve.
+    pub fn from_ec_point(crv: Curve, point: &[u8]) -> crate::Result<Self> {
+        let invalid = || Error::Message("invalid SEC1 elliptic curve point".to_string());
+        let (&format, coordinates) = point.split_first().ok_or_else(invalid)?;
+        let len = match format {
+            0x04 => coordinates.len() / 2,
+            0x02 | 0x03 => coordinates.len(),
+            _ => return Err(invalid()),
+        };
+        let expected = crv.coordinate_len().unwrap_or(len);
+        if len == 0 || len != expected || (format == 0x04 && coordinates.len() != 2 * len) {
+            return Err(invalid());
+        }
+
+        let mut key = CoseKey::new(KeyType::Ec2);
+        key.crv = Some(crv);
+        key.x = Some(coordinates[..len].to_vec());
+        key.y = Some(match format {
+            0x04 => YCoordinate::Bytes(coordinates[len..].to_vec()),
+            _ => YCoordinate::Sign(format == 0x03),
+        });
+        Ok(key)
+    }
+
+    /// An OKP public key, such as an Ed25519 or X25519 key
+    pub fn from_okp(crv: Curve, x: &[u8]) -> Self {
+        let mut key = CoseKey::new(KeyType::Okp);
+        key.crv = Some(crv);
+        key.x = Some(x.to_vec());
+        key
+    }
+
+    /// Add the private key `d` (builder pattern)
+    pub fn with_private_key(mut self, d: &[u8]) -> Self {
+        self.d = Some(d.to_vec());
+        self
+    }
+
+    /// The SEC1 encoding of an EC2 public key, compressed if the key only has the sign of y
+    pub fn ec_point(&self) -> crate::Result<Vec<u8>> {
+        if self.kty != KeyType::Ec2 {
+            return Err(Error::Message("not an EC2 key".to_string()));
+        }
+        let (Some(x), Some(y)) = (&self.x, &self.y) else {
+            return Err(Error::Message("EC2 key is missing x or y".to_string()));
+        };
+        let mut point = Vec::with_capacity(1 + 2 * x.len());
+        match y {
+            YCoordinate::Bytes(y) => {
+                point.push(0x04);
+                point.extend_from_slice(x);
+                point.extend_from_slice(y);
+            }
+            YCoordinate::Sign(odd) => {
+                point.push(if *odd { 0x03 } else { 0x02 });
+                point.extend_from_slice(x);
+            }
+        }
+        Ok(point)
+    }
+
+    /// The public key of an OKP key
+    pub fn okp_public_key(&self) -> crate::Result<&[u8]> {
+        match (&self.kty, &self.x) {
+            (KeyType::Okp, Some(x)) => Ok(x),
+            (KeyType::Okp, None) => Err(Error::Message("OKP key is missing x".to_string())),
+            _ => Err(Error::Message("not an OKP key".to_string())),
+        }
+    }
+
+    /// The private key, if present
+    pub fn private_key(&self) -> Option<&[u8]> {
+        self.d.as_deref()
+    }
+
+    /// Encode the key
+    pub fn to_vec(&self) -> crate::Result<Vec<u8>> {
+        crate::to_vec(self)
+    }
+
+    /// Decode a key
+    pub fn from_slice(bytes: &[u8]) -> crate::Result<Self> {
+        crate::from_slice(bytes)
+    }
+
+    /// Returns true if `crv`, `x`, `y` and `d` are this key type's parameters
+    fn has_curve_params(&self) -> bool {
+        matches!(self.kty, KeyType::Okp | KeyType::Ec2)
+    }
+}
+
+impl Serialize for CoseKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::Error as _;
+
+        let curve_params = [
+            (CRV, self.crv.clone().map(Value::from)),
+            (X, self.x.clone().map(Value::Bytes)),
+            (
+                Y,
+                self.y.clone().map(|y| match y {
+                    YCoordinate::Bytes(y) => Value::Bytes(y),
+                    YCoordinate::Sign(odd) => Value::Bool(odd),
+                }),
+            ),
+            (D, self.d.clone().map(Value::Bytes)),
+        ];
+        if !self.has_curve_params() && curve_params.iter().any(|(_, value)| value.is_some()) {
+            return Err(S::Error::custom(
+                "crv, x, y and d only apply to OKP and EC2 keys",
+            ));
+        }
+        let key_ops = (!self.key_ops.is_empty())
+            .then(|| Value::Array(self.key_ops.iter().cloned().map(Value::from).collect()));
+        let common = [
+            (KTY, Some(self.kty.clone().into())),
+            (KID, self.kid.clone().map(Value::Bytes)),
+            (ALG, self.alg.map(Value::from)),
+            (KEY_OPS, key_ops),
+            (BASE_IV, self.base_iv.clone().map(Value::Bytes)),
+        ];
+
+        let mut entries = Vec::new();
+        for (label, value) in common.into_iter().chain(curve_params) {
+            if let Some(value) = value {
+                entries.push((Value::Integer(label), value));
+            }
+        }
+        for (label, value) in &self.params {
+            // A text alg has no Algorithm and is kept in params
+            let text_alg = *label == Value::Integer(ALG) && self.alg.is_none();
+            if (matches!(label, Value::Integer(KTY..=BASE_IV)) && !text_alg)
+                || (self.has_curve_params() && matches!(label, Value::Integer(D..=CRV)))
+            {
+                return Err(S::Error::custom(format!(
+                    "COSE_Key parameter {:?} must be set through its field",
+                    label
+                )));
+            }
+            entries.push((label.clone(), value.clone()));
+        }
+
+        // Deterministic order is the bytewise order of the encoded labels
+        let mut entries = entries
+            .into_iter()
+            .map(|(label, value)| Ok((crate::to_vec(&label)?, label, value)))
+            .collect::<crate::Result<Vec<_>>>()
+            .map_err(S::Error::custom)?;
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut map = serializer.serialize_map(Some(entries.len()))?;
+        for (_, label, value) in &entries {
+            map.serialize_entry(label, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for CoseKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct CoseKeyVisitor;
+
+        impl<'de> Visitor<'de> for CoseKeyVisitor {
+            type Value = CoseKey;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a COSE_Key")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<CoseKey, A::Error> {
+                let mut params = BTreeMap::new();
+                while let Some(label) = map.next_key::<Value>()? {
+                    match params.entry(label) {
+                        btree_map::Entry::Vacant(entry) => {
+                            entry.insert(map.next_value()?);
+                        }
+                        btree_map::Entry::Occupied(entry) => {
+                            return Err(de::Error::custom(format!(
+                                "duplicate COSE_Key parameter {:?}",
+                                entry.key()
+                            )));
+                        }
+                    }
+                }
+                key_from_params(params).map_err(de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_map(CoseKeyVisitor)
+    }
+}
+
+/// Move the parameters with their own fields out of `params`
+fn key_from_params(mut params: BTreeMap<Value, Value>) -> crate::Result<CoseKey> {
+    let invalid = |label: i64| Error::Message(format!("invalid COSE_Key parameter {}", label));
+    let bytes = |label: i64, value: Option<Value>| match value {
+        None => Ok(None),
+        Some(Value::Bytes(bytes)) => Ok(Some(bytes)),
+        Some(_) => Err(invalid(label)),
+    };
+    let take =
+        |params: &mut BTreeMap<Value, Value>, label: i64| params.remove(&Value::Integer(label));
+
+    let kty = take(&mut params, KTY)
+        .ok_or_else(|| Error::Message("COSE_Key is missing kty".to_string()))?;
+    let mut key = CoseKey::new(KeyType::try_from(kty).map_err(|_| invalid(KTY))?);
+    key.kid = bytes(KID, take(&mut params, KID))?;
+    if let Some(Value::Integer(alg)) = params.get(&Value::Integer(ALG)) {
+        key.alg = Some(Algorithm::from(*alg));
+        take(&mut params, ALG);
+    } else if !matches!(
+        params.get(&Value::Integer(ALG)),
+        None | Some(Value::Text(_))
+    ) {
+        return Err(invalid(ALG));
+    }
+    key.key_ops = match take(&mut params, KEY_OPS) {
+        None => Vec::new(),
+        Some(Value::Array(ops)) => ops
+            .into_iter()
+            .map(|op| KeyOperation::try_from(op).map_err(|_| invalid(KEY_OPS)))
+            .collect::<crate::Result<_>>()?,
+        Some(_) => return Err(invalid(KEY_OPS)),
+    };
+    key.base_iv = bytes(BASE_IV, take(&mut params, BASE_IV))?;
+    if key.has_curve_params() {
+        key.crv = take(&mut params, CRV)
+            .map(|crv| Curve::try_from(crv).map_err(|_| invalid(CRV)))
+            .transpose()?;
+        key.x = bytes(X, take(&mut params, X))?;
+        key.y = match take(&mut params, Y) {
+            None => None,
+            Some(Value::Bytes(y)) => Some(YCoordinate::Bytes(y)),
+            Some(Value::Bool(odd)) => Some(YCoordinate::Sign(odd)),
+            Some(_) => return Err(invalid(Y)),
+        };
+        key.d = bytes(D, take(&mut params, D))?;
+    }
+    key.params = params;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic_order() {
+        let mut header = HeaderMap::new();
+        header.insert("sigTst", Value::Array(Vec::new()));
+        header.insert(-1, Value::Integer(1));
+        header.insert(HeaderLabel::X5Chain, Value::Bytes(vec![0x30]));
+        header.insert(HeaderLabel::Kid, Value::Bytes(b"a".to_vec()));
+        header.insert(HeaderLabel::Alg, Value::Integer(-35));
+        header.insert(100, Value::Null);
+
+        // 1, 4, 33 (0x1821), 100 (0x1864), -1 (0x20), then the text label
+        let cbor = crate::to_vec(&header).unwrap();
+        assert_eq!(
+            cbor,
+            [
+                0xa6, 0x01, 0x38, 0x22, 0x04, 0x41, b'a', 0x18, 0x21, 0x41, 0x30, 0x18, 0x64, 0xf6,
+                0x20, 0x01, 0x66, b's', b'i', b'g', b'T', b's', b't', 0x80
+            ]
+        );
+        assert!(crate::is_canonical(&cbor).unwrap());
+
+        let decoded: HeaderMap = crate::from_slice(&cbor).unwrap();
+        assert_eq!(decoded, header);
+        assert_eq!(decoded.alg(), Some(Algorithm::Es384));
+        assert_eq!(decoded.kid(), Some(&b"a"[..]));
+        assert!(decoded.contains(HeaderLabel::X5Chain));
+        assert_eq!(
+            decoded.iter().next().map(|(label, _)| label.clone()),
+            Some(HeaderLabel::Alg)
+        );
+    }
+
+    #[test]
+    fn test_labels() {
+        assert_eq!(HeaderLabel::from(1), HeaderLabel::Alg);
+        assert!(matches!(
+            HeaderLabel::from(Value::Integer(33)),
+            HeaderLabel::X5Chain
+        ));
+        assert_eq!(HeaderLabel::Other(Value::Integer(4)), HeaderLabel::Kid);
+        assert_eq!(HeaderLabel::X5U.registered(), Some(35));
+        assert_eq!(HeaderLabel::from("rVals").registered(), None);
+        assert_eq!(HeaderLabel::from_i64(8), None);
+
+        // An unnormalized integer label still replaces the registered one
+        let mut header = HeaderMap::new();
+        header.insert(HeaderLabel::Alg, Value::Integer(-7));
+        let previous = header.insert(HeaderLabel::Other(Value::Integer(1)), Value::Integer(-8));
+        assert_eq!(previous, Some(Value::Integer(-7)));
+        assert_eq!(header.len(), 1);
+        assert_eq!(header.alg(), Some(Algorithm::EdDsa));
+
+        // Labels order as their encodings do, and distinct labels stay distinct
+        let mut labels: Vec<HeaderLabel> = vec![
+            "a".into(),
+            HeaderLabel::X5Chain,
+            (-25).into(),
+            Value::Bytes(vec![1]).into(),
+            "".into(),
+            (-24).into(),
+            24.into(),
+            HeaderLabel::Alg,
+            Value::Simple(25).into(),
+            Value::Simple(24).into(),
+            "ab".into(),
+            (-1).into(),
+        ];
+        let mut by_encoding = labels.clone();
+        by_encoding.sort_by_key(|label| label.to_value().to_canonical_vec());
+        labels.sort();
+        assert_eq!(labels, by_encoding);
+        let unique: std::collections::BTreeSet<_> = labels.iter().collect();
+        assert_eq!(unique.len(), labels.len());
+    }
+
+    #[test]
+    fn test_algorithms() {
+        assert_eq!(Algorithm::from(-7), Algorithm::Es256);
+        assert_eq!(Algorithm::Other(-8), Algorithm::EdDsa);
+        assert_eq!(Algorithm::Other(-8).name(), Some("EdDSA"));
+        assert_eq!(Algorithm::from(-257).to_string(), "RS256");
+        assert_eq!(Algorithm::from(-1000).to_string(), "-1000");
+        assert_eq!(i64::from(Algorithm::Hmac256), 5);
+
+        for alg in [
+            Algorithm::Es256,
+            Algorithm::Rs512,
+            Algorithm::A256Gcm,
+            Algorithm::Other(-99),
+        ] {
+            let cbor = crate::to_vec(&alg).unwrap();
+            assert_eq!(crate::from_slice::<Algorithm>(&cbor).unwrap(), alg);
+            assert_eq!(Algorithm::try_from(Value::from(alg)).unwrap(), alg);
+        }
+        assert_eq!(crate::to_vec(&Algorithm::Es256).unwrap(), [0x26]);
+        assert_eq!(
+            crate::to_vec(&Algorithm::Rs256).unwrap(),
+            [0x39, 0x01, 0x00]
+        );
+        assert!(Algorithm::try_from(Value::Text("ES256".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_sig_structures() {
+        let mut header = HeaderMap::new();
+        header.insert(HeaderLabel::Alg, Algorithm::Es256.into());
+        let protected = header.to_protected_bytes().unwrap();
+
+        // Matches the structure serde would produce
+        let tbs = sig_structure_sign1(&protected, b"aad", b"payload");
+        let expected = crate::to_vec(&(
+            "Signature1",
+            Bytes::new(&protected),
+            Bytes::new(b"aad"),
+            Bytes::new(b"payload"),
+        ))
+        .unwrap();
+        assert_eq!(tbs, expected);
+        assert!(crate::is_canonical(&tbs).unwrap());
+
+        let tbs = sig_structure_sign(&protected, &[], b"", b"payload");
+        assert_eq!(
+            crate::to_diagnostic(&tbs).unwrap(),
+            "[\n  \"Signature\",\n  h'a10126',\n  h'',\n  h'',\n  h'7061796c6f6164'\n]"
+        );
+
+        let long_payload = vec![0xab; 300];
+        let tbs =
+            sig_structure_counter_signature(&protected, &protected, b"", &long_payload, &[b"sig"]);
+        let (context, _, _, _, payload, other): (
+            String,
+            ByteBuf,
+            ByteBuf,
+            ByteBuf,
+            ByteBuf,
+            Vec<ByteBuf>,
+        ) = crate::from_slice(&tbs).unwrap();
+        assert_eq!(context, "CounterSignature");
+        assert_eq!(payload.into_vec(), long_payload);
+        assert_eq!(other, [ByteBuf::from(b"sig".to_vec())]);
+
+        let tbs = sig_structure_counter_signature(&protected, &protected, b"", &long_payload, &[]);
+        assert!(crate::is_canonical(&tbs).unwrap());
+        assert_eq!(tbs[0], 0x85);
+    }
+
+    // Depends on every byte signed, like a real signature
+    fn fake_sign(tbs: &[u8]) -> crate::Result<Vec<u8>> {
+        Ok(tbs.iter().rev().copied().collect())
+    }
+
+    #[test]
+    fn test_cose_sign1() {
+        let mut protected = HeaderMap::new();
+        protected.insert(HeaderLabel::Alg, Algorithm::Es256.into());
+        let mut unprotected = HeaderMap::new();
+        unprotected.insert(HeaderLabel::Kid, Value::Bytes(b"key".to_vec()));
+
+        let message = CoseSign1::sign(
+            &protected,
+            unprotected.clone(),
+            b"payload".to_vec(),
+            b"",
+            fake_sign,
+        )
+        .unwrap();
+        let cbor = message.to_vec().unwrap();
+        assert_eq!(&cbor[..2], [0xd2, 0x84]);
+        let parsed = CoseSign1::from_slice(&cbor).unwrap();
+        assert_eq!(parsed, message);
+        assert_eq!(parsed.payload(), Some(&b"payload"[..]));
+        assert_eq!(parsed.unprotected().kid(), Some(&b"key"[..]));
+        let tbs = parsed.to_be_signed(b"").unwrap();
+        assert_eq!(parsed.signature(), fake_sign(&tbs).unwrap());
+        assert!(parsed.to_be_signed_detached(b"", b"payload").is_err());
+
+        // Detaching keeps the signature valid for the same payload
+        let mut detached = parsed.clone();
+        let payload = detached.detach().unwrap();
+        let tbs_detached = detached.to_be_signed_detached(b"", &payload).unwrap();
+        assert_eq!(tbs_detached, tbs);
+
+        // A nil payload in the untagged form
+        let message = CoseSign1::sign_detached(
+            &HeaderMap::new(),
+            unprotected,
+            b"payload",
+            b"aad",
+            fake_sign,
+        )
+        .unwrap();
+        let cbor = crate::to_vec(&message).unwrap();
+        assert_eq!(cbor[0], 0x84);
+        assert_eq!(cbor[1], 0x40); // empty protected header
+        let parsed = CoseSign1::from_slice(&cbor).unwrap();
+        assert!(parsed.is_detached());
+        assert!(parsed.protected_header().unwrap().is_empty());
+        assert!(parsed.to_be_signed(b"aad").is_err());
+        let tbs = parsed.to_be_signed_detached(b"aad", b"payload").unwrap();
+        assert_eq!(parsed.signature(), fake_sign(&tbs).unwrap());
+
+        // Other tags are rejected
+        let mut wrong_tag = cbor.clone();
+        wrong_tag.insert(0, 0xd1);
+        assert!(CoseSign1::from_slice(&wrong_tag).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_label() {
+        // {1: -7, 1: -8}
+        let cbor = [0xa2, 0x01, 0x26, 0x01, 0x27];
+        assert!(crate::from_slice::<HeaderMap>(&cbor).is_err());
+    }
+
+    #[test]
+    fn test_cose_key_ec2_point() {
+        let mut point = vec![0x04];
+        point.extend(1..=64u8);
+        let key = CoseKey::from_ec_point(Curve::P256, &point).unwrap();
+        assert_eq!(key.x.as_deref(), Some(&point[1..33]));
+        assert_eq!(key.y, Some(YCoordinate::Bytes(point[33..].to_vec())));
+        assert_eq!(key.ec_point().unwrap(), point);
+
+        let mut compressed = vec![0x03];
+        compressed.extend_from_slice(&[0xaa; 48]);
+        let key = CoseKey::from_ec_point(Curve::P384, &compressed).unwrap();
+        assert_eq!(key.y, Some(YCoordinate::Sign(true)));
+        let decoded = CoseKey::from_slice(&key.to_vec().unwrap()).unwrap();
+        assert_eq!(decoded.ec_point().unwrap(), compressed);
+
+        // Wrong length for the curve, odd uncompressed length, unknown format
+        assert!(CoseKey::from_ec_point(Curve::P384, &point).is_err());
+        assert!(CoseKey::from_ec_point(Curve::Other(-100), &point[..64]).is_err());
+        assert!(CoseKey::from_ec_point(Curve::P256, &[0x05; 65]).is_err());
+        assert!(CoseKey::from_ec_point(Curve::P256, &[]).is_err());
+        assert!(
+            CoseKey::from_okp(Curve::Ed25519, &[1; 32])
+                .ec_point()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_cose_key_okp() {
+        let mut key = CoseKey::from_okp(Curve::Ed25519, &[0x11; 4]).with_private_key(&[0x22; 4]);
+        key.kid = Some(b"k".to_vec());
+        key.key_ops = vec![KeyOperation::Sign, KeyOperation::Other(42)];
+        let cbor = key.to_vec().unwrap();
+        assert_eq!(
+            crate::to_diagnostic(&cbor).unwrap(),
+            "{\n  1: 1,\n  2: h'6b',\n  4: [\n    1,\n    42\n  ],\n  -1: 6,\n  -2: h'11111111',\n  -4: h'22222222'\n}"
+        );
+
+        let decoded = CoseKey::from_slice(&cbor).unwrap();
+        assert_eq!(decoded, key);
+        assert_eq!(decoded.okp_public_key().unwrap(), &[0x11; 4]);
+        assert_eq!(decoded.private_key(), Some(&[0x22; 4][..]));
+        assert!(CoseKey::new(KeyType::Okp).okp_public_key().is_err());
+    }
+
+    #[test]
+    fn test_cose_key_other_params() {
+        // RSA keys keep their negative labels (n, e) in params
+        let mut key = CoseKey::new(KeyType::Rsa);
+        key.params
+            .insert(Value::Integer(-1), Value::Bytes(vec![0xc5; 8]));
+        key.params
+            .insert(Value::Integer(-2), Value::Bytes(vec![1, 0, 1]));
+        key.params
+            .insert(Value::Text("note".into()), Value::Bool(true));
+        let decoded = CoseKey::from_slice(&key.to_vec().unwrap()).unwrap();
+        assert_eq!(decoded, key);
+        assert_eq!(decoded.crv, None);
+
+        // Parameters with their own fields can't be set through params
+        key.params
+            .insert(Value::Integer(2), Value::Bytes(Vec::new()));
+        assert!(key.to_vec().is_err());
+        let mut key = CoseKey::from_okp(Curve::X25519, &[0; 32]);
+        key.params
+            .insert(Value::Integer(-4), Value::Bytes(Vec::new()));
+        assert!(key.to_vec().is_err());
+
+        // EC parameters only apply to OKP and EC2 keys
+        let mut key = CoseKey::new(KeyType::Symmetric);
+        key.crv = Some(Curve::P256);
+        assert!(key.to_vec().is_err());
+    }
+
+    #[test]
+    fn test_cose_key_invalid() {
+        // {2: h''}: no kty
+        assert!(CoseKey::from_slice(&[0xa1, 0x02, 0x40]).is_err());
+        // {1: 2, -2: 0}: x is not a byte string
+        assert!(CoseKey::from_slice(&[0xa2, 0x01, 0x02, 0x21, 0x00]).is_err());
+        // {1: 1, 1: 1}: duplicate label
+        let err = CoseKey::from_slice(&[0xa2, 0x01, 0x01, 0x01, 0x01]).unwrap_err();
+        assert!(err.to_string().contains("duplicate"));
+        // {1: 2, -3: true}
+        let key = CoseKey::from_slice(&[0xa2, 0x01, 0x02, 0x22, 0xf5]).unwrap();
+        assert_eq!(key.y, Some(YCoordinate::Sign(true)));
+        assert_eq!(KeyType::from(2), KeyType::Ec2);
+        assert_eq!(Curve::Other(1), Curve::P256);
+        // {1: 2, 3: h''}: alg is neither an integer nor text
+        assert!(CoseKey::from_slice(&[0xa2, 0x01, 0x02, 0x03, 0x40]).is_err());
+    }
+
+    #[test]
+    fn test_cose_key_text_values() {
+        // {1: "EC2", 3: "ES256", 4: [1, "sign"], -1: "P-256"}
+        let cbor = [
+            0xa4, 0x01, 0x63, b'E', b'C', b'2', 0x03, 0x65, b'E', b'S', b'2', b'5', b'6', 0x04,
+            0x82, 0x01, 0x64, b's', b'i', b'g', b'n', 0x20, 0x65, b'P', b'-', b'2', b'5', b'6',
+        ];
+        let key = CoseKey::from_slice(&cbor).unwrap();
+        assert_eq!(key.kty, KeyType::from("EC2"));
+        assert_eq!(key.alg, None);
+        assert_eq!(key.params[&Value::Integer(3)], Value::Text("ES256".into()));
+        assert_eq!(
+            key.key_ops,
+            vec![KeyOperation::Sign, KeyOperation::Text("sign".into())]
+        );
+        // A text kty is not EC2, so -1 stays in params
+        assert_eq!(key.crv, None);
+        assert_eq!(key.params[&Value::Integer(-1)], Value::Text("P-256".into()));
+        assert_eq!(key.to_vec().unwrap(), cbor);
+
+        // {1: 2, -1: "P-256"}
+        let cbor = [0xa2, 0x01, 0x02, 0x20, 0x65, b'P', b'-', b'2', b'5', b'6'];
+        let key = CoseKey::from_slice(&cbor).unwrap();
+        assert_eq!(key.crv, Some(Curve::Text("P-256".into())));
+        assert_eq!(key.crv.as_ref().unwrap().coordinate_len(), None);
+        assert_eq!(key.to_vec().unwrap(), cbor);
+    }
+}
@@ -0,0 +1,453 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! CBOR Web Token claims (RFC 8392)
+//!
+//! [`ClaimsSet`] holds the registered claims of a CWT as typed fields and
+//! any other claims as [`Value`]s. It serializes as a map with integer keys
+//! for the registered claims, in deterministic order. A CWT is a COSE
+//! message (such as a [`CoseSign1`](crate::cose::CoseSign1)) whose payload
+//! is the encoded claims set, optionally wrapped in tag 61 with [`wrap`].
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::{
+//!     Value,
+//!     cwt::{self, ClaimsSet},
+//! };
+//!
+//! let mut claims = ClaimsSet {
+//!     iss: Some("coap://as.example.com".to_string()),
+//!     exp: Some(1444064944.into()),
+//!     ..Default::default()
+//! };
+//! claims.extra.insert(Value::Text("c2pa".to_string()), Value::Bool(true));
+//!
+//! let cbor = claims.to_vec().unwrap();
+//! assert_eq!(
+//!     c2pa_cbor::to_diagnostic(&cbor).unwrap(),
+//!     "{\n  1: \"coap://as.example.com\",\n  4: 1444064944,\n  \"c2pa\": true\n}"
+//! );
+//! assert_eq!(ClaimsSet::from_slice(&cbor).unwrap(), claims);
+//!
+//! // Tag 61 marks a COSE message as a CWT
+//! let token = cwt::wrap(&[0x84, 0x40, 0xa0, 0xf6, 0x40]);
+//! assert_eq!(token[..2], [0xd8, 0x3d]);
+//! assert_eq!(cwt::unwrap(&token).unwrap(), [0x84, 0x40, 0xa0, 0xf6, 0x40]);
+//! ```
+
+use std::{cmp::Ordering, collections::BTreeMap, fmt};
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, MapAccess, Visitor},
+    ser::{self, SerializeMap},
+};
+use serde_bytes::ByteBuf;
+
+use crate::{
+    Result, Value,
+    constants::MAJOR_TAG,
+    raw::{SliceReader, write_header},
+};
+
+/// Tag that marks a COSE message as a CWT
+pub const CWT_TAG: u64 = 61;
+
+/// Issuer claim key
+pub const ISS: i64 = 1;
+/// Subject claim key
+pub const SUB: i64 = 2;
+/// Audience claim key
+pub const AUD: i64 = 3;
+/// Expiration time claim key
+pub const EXP: i64 = 4;
+/// Not-before claim key
+pub const NBF: i64 = 5;
+/// Issued-at claim key
+pub const IAT: i64 = 6;
+/// CWT ID claim key
+pub const CTI: i64 = 7;
+
+/// A time in a claim: seconds since the Unix epoch (RFC 8392 §2)
+///
+/// Written as an integer or a float; decoding rejects non-finite floats.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumericDate {
+    /// Whole seconds
+    Integer(i64),
+    /// Seconds with a fractional part
+    Float(f64),
+}
+
+impl NumericDate {
+    /// The time as a float
+    pub fn as_f64(self) -> f64 {
+        match self {
+            NumericDate::Integer(seconds) => seconds as f64,
+            NumericDate::Float(seconds) => seconds,
+        }
+    }
+
+    // Compare with whole seconds, exactly for integers
+    fn cmp_seconds(self, seconds: i64) -> Option<Ordering> {
+        match self {
+            NumericDate::Integer(date) => Some(date.cmp(&seconds)),
+            NumericDate::Float(date) => date.partial_cmp(&(seconds as f64)),
+        }
+    }
+}
+
+impl From<i64> for NumericDate {
+    fn from(seconds: i64) -> Self {
+        NumericDate::Integer(seconds)
+    }
+}
+
+impl From<f64> for NumericDate {
+    fn from(seconds: f64) -> Self {
+        NumericDate::Float(seconds)
+    }
+}
+
+impl From<NumericDate> for Value {
+    fn from(date: NumericDate) -> Self {
+        match date {
+            NumericDate::Integer(seconds) => Value::Integer(seconds),
+            NumericDate::Float(seconds) => Value::Float(seconds),
+        }
+    }
+}
+
+impl Serialize for NumericDate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match *self {
+            NumericDate::Integer(seconds) => serializer.serialize_i64(seconds),
+            NumericDate::Float(seconds) => serializer.serialize_f64(seconds),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for NumericDate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct NumericDateVisitor;
+
+        impl<'de> Visitor<'de> for NumericDateVisitor {
+            type Value = NumericDate;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a NumericDate")
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> std::result::Result<NumericDate, E> {
+                Ok(NumericDate::Integer(v))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> std::result::Result<NumericDate, E> {
+                i64::try_from(v)
+                    .map(NumericDate::Integer)
+                    .map_err(|_| E::custom(format!("NumericDate {} out of range", v)))
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> std::result::Result<NumericDate, E> {
+                if !v.is_finite() {
+                    return Err(E::custom(format!("NumericDate {} is not finite", v)));
+                }
+                Ok(NumericDate::Float(v))
+            }
+        }
+
+        deserializer.deserialize_any(NumericDateVisitor)
+    }
+}
+
+/// The claims of a CBOR Web Token
+///
+/// Decoding fails if a registered claim has the wrong type or any claim appears
+/// twice, and encoding fails if [`extra`](Self::extra) holds the key of a
+/// registered claim.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClaimsSet {
+    /// Issuer (key 1)
+    pub iss: Option<String>,
+    /// Subject (key 2)
+    pub sub: Option<String>,
+    /// Audience (key 3)
+    pub aud: Option<String>,
+    /// Expiration time (key 4)
+    pub exp: Option<NumericDate>,
+    /// Not before (key 5)
+    pub nbf: Option<NumericDate>,
+    /// Issued at (key 6)
+    pub iat: Option<NumericDate>,
+    /// CWT ID (key 7)
+    pub cti: Option<Vec<u8>>,
+    /// Every other claim, keyed by integer or text
+    pub extra: BTreeMap<Value, Value>,
+}
+
+impl ClaimsSet {
+    /// Encode the claims set
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        crate::to_vec(self)
+    }
+
+    /// Decode a claims set
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        crate::from_slice(bytes)
+    }
+
+    /// Returns true if `now`, in seconds since the Unix epoch, is at or after
+    /// [`nbf`](Self::nbf) and before [`exp`](Self::exp)
+    ///
+    /// Missing times do not restrict validity.
+    pub fn is_valid_at(&self, now: i64) -> bool {
+        self.nbf.is_none_or(|nbf| {
+            matches!(nbf.cmp_seconds(now), Some(Ordering::Less | Ordering::Equal))
+        }) && self
+            .exp
+            .is_none_or(|exp| exp.cmp_seconds(now) == Some(Ordering::Greater))
+    }
+}
+
+impl Serialize for ClaimsSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let registered = [
+            (ISS, self.iss.clone().map(Value::Text)),
+            (SUB, self.sub.clone().map(Value::Text)),
+            (AUD, self.aud.clone().map(Value::Text)),
+            (EXP, self.exp.map(Value::from)),
+            (NBF, self.nbf.map(Value::from)),
+            (IAT, self.iat.map(Value::from)),
+            (CTI, self.cti.clone().map(Value::Bytes)),
+        ];
+        let mut claims = Vec::with_capacity(registered.len() + self.extra.len());
+        for (key, value) in registered {
+            if let Some(value) = value {
+                claims.push((
+                    crate::to_vec(&key).map_err(ser::Error::custom)?,
+                    Value::Integer(key),
+                    value,
+                ));
+            }
+        }
+        for (key, value) in &self.extra {
+            if let Value::Integer(ISS..=CTI) = key {
+                return Err(ser::Error::custom(format!(
+                    "claim {:?} must be set through its field",
+                    key
+                )));
+            }
+            let encoded = crate::to_vec(key).map_err(ser::Error::custom)?;
+            claims.push((encoded, key.clone(), value.clone()));
+        }
+
+        // Deterministic order is the bytewise order of the encoded keys
+        claims.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut map = serializer.serialize_map(Some(claims.len()))?;
+        for (_, key, value) in &claims {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for ClaimsSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct ClaimsSetVisitor;
+
+        impl<'de> Visitor<'de> for ClaimsSetVisitor {
+            type Value = ClaimsSet;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a CWT claims set")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> std::result::Result<ClaimsSet, A::Error> {
+                fn set<T, E: de::Error>(
+                    field: &mut Option<T>,
+                    value: T,
+                    key: i64,
+                ) -> std::result::Result<(), E> {
+                    match field.replace(value) {
+                        Some(_) => Err(E::custom(format!("duplicate claim {}", key))),
+                        None => Ok(()),
+                    }
+                }
+
+                let mut claims = ClaimsSet::default();
+                while let Some(key) = map.next_key::<Value>()? {
+                    match key {
+                        Value::Integer(ISS) => set(&mut claims.iss, map.next_value()?, ISS)?,
+                        Value::Integer(SUB) => set(&mut claims.sub, map.next_value()?, SUB)?,
+                        Value::Integer(AUD) => set(&mut claims.aud, map.next_value()?, AUD)?,
+                        Value::Integer(EXP) => set(&mut claims.exp, map.next_value()?, EXP)?,
+                        Value::Integer(NBF) => set(&mut claims.nbf, map.next_value()?, NBF)?,
+                        Value::Integer(IAT) => set(&mut claims.iat, map.next_value()?, IAT)?,
+                        Value::Integer(CTI) => {
+                            let cti: ByteBuf = map.next_value()?;
+                            set(&mut claims.cti, cti.into_vec(), CTI)?
+                        }
+                        key => {
+                            let value = map.next_value()?;
+                            if claims.extra.contains_key(&key) {
+                                return Err(de::Error::custom(format!(
+                                    "duplicate claim {:?}",
+                                    key
+                                )));
+                            }
+                            claims.extra.insert(key, value);
+                        }
+                    }
+                }
+                Ok(claims)
+            }
+        }
+
+        deserializer.deserialize_map(ClaimsSetVisitor)
+    }
+}
+
+/// Mark an encoded COSE message as a CWT by wrapping it in tag 61
+pub fn wrap(cose: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(cose.len() + 2);
+    write_header(&mut out, MAJOR_TAG, CWT_TAG);
+    out.extend_from_slice(cose);
+    out
+}
+
+/// The COSE message inside a CWT, with tag 61 removed if present
+///
+/// The COSE message itself is not checked; parse it with, for example,
+/// [`CoseSign1::from_slice`](crate::cose::CoseSign1::from_slice).
+pub fn unwrap(token: &[u8]) -> Result<&[u8]> {
+    let mut reader = SliceReader::new(token);
+    match reader.read_header()? {
+        (MAJOR_TAG, _, Some(CWT_TAG)) => Ok(&token[reader.position()..]),
+        _ => Ok(token),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cose::{Algorithm, CoseSign1, HeaderLabel, HeaderMap};
+
+    #[test]
+    fn test_rfc8392_example() {
+        // Claims set from RFC 8392 Appendix A.1
+        let hex = "a70175636f61703a2f2f61732e6578616d706c652e636f6d02656572696b77037818636f61703a2f2f6c696768742e6578616d706c652e636f6d041a5612aeb0051a5610d9f0061a5610d9f007420b71";
+        let cbor: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect();
+        let claims = ClaimsSet::from_slice(&cbor).unwrap();
+        assert_eq!(claims.iss.as_deref(), Some("coap://as.example.com"));
+        assert_eq!(claims.sub.as_deref(), Some("erikw"));
+        assert_eq!(claims.aud.as_deref(), Some("coap://light.example.com"));
+        assert_eq!(claims.exp, Some(NumericDate::Integer(1444064944)));
+        assert_eq!(claims.nbf, Some(NumericDate::Integer(1443944944)));
+        assert_eq!(claims.iat, Some(NumericDate::Integer(1443944944)));
+        assert_eq!(claims.cti, Some(vec![0x0b, 0x71]));
+        assert!(claims.extra.is_empty());
+        assert_eq!(claims.to_vec().unwrap(), cbor);
+
+        assert!(claims.is_valid_at(1443944944));
+        assert!(!claims.is_valid_at(1443944943));
+        assert!(!claims.is_valid_at(1444064944));
+    }
+
+    #[test]
+    fn test_extension_claims() {
+        let mut claims = ClaimsSet {
+            sub: Some("device".to_string()),
+            ..Default::default()
+        };
+        claims.extra.insert(
+            Value::Text("scope".to_string()),
+            Value::Text("read".to_string()),
+        );
+        claims
+            .extra
+            .insert(Value::Integer(-70000), Value::Integer(1));
+        claims
+            .extra
+            .insert(Value::Integer(8), Value::Map(BTreeMap::new()));
+
+        let cbor = claims.to_vec().unwrap();
+        assert!(crate::is_canonical(&cbor).unwrap());
+        assert_eq!(ClaimsSet::from_slice(&cbor).unwrap(), claims);
+
+        claims.extra.insert(Value::Integer(EXP), Value::Integer(0));
+        assert!(claims.to_vec().is_err());
+    }
+
+    #[test]
+    fn test_invalid_claims() {
+        // {4: "soon"}
+        assert!(ClaimsSet::from_slice(&[0xa1, 0x04, 0x64, b's', b'o', b'o', b'n']).is_err());
+        // {1: "a", 1: "b"}
+        assert!(ClaimsSet::from_slice(&[0xa2, 0x01, 0x61, b'a', 0x01, 0x61, b'b']).is_err());
+        // {4: NaN}
+        assert!(ClaimsSet::from_slice(&[0xa1, 0x04, 0xf9, 0x7e, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_float_numeric_dates() {
+        // {4: 1444064944.5, 5: 1443944944.0}
+        let mut cbor = vec![0xa2, 0x04, 0xfb];
+        cbor.extend_from_slice(&1444064944.5f64.to_be_bytes());
+        cbor.extend_from_slice(&[0x05, 0xfb]);
+        cbor.extend_from_slice(&1443944944.0f64.to_be_bytes());
+        let claims = ClaimsSet::from_slice(&cbor).unwrap();
+        assert_eq!(claims.exp, Some(NumericDate::Float(1444064944.5)));
+        assert_eq!(claims.nbf.map(NumericDate::as_f64), Some(1443944944.0));
+        assert_eq!(
+            ClaimsSet::from_slice(&claims.to_vec().unwrap()).unwrap(),
+            claims
+        );
+
+        assert!(claims.is_valid_at(1443944944));
+        assert!(!claims.is_valid_at(1443944943));
+        assert!(claims.is_valid_at(1444064944));
+        assert!(!claims.is_valid_at(1444064945));
+    }
+
+    #[test]
+    fn test_signed_token() {
+        let claims = ClaimsSet {
+            iss: Some("issuer".to_string()),
+            iat: Some(1700000000.into()),
+            ..Default::default()
+        };
+        let mut protected = HeaderMap::new();
+        protected.insert(HeaderLabel::Alg, Algorithm::EdDsa.into());
+        let message = CoseSign1::sign(
+            &protected,
+            HeaderMap::new(),
+            claims.to_vec().unwrap(),
+            b"",
+            |tbs| Ok::<_, crate::Error>(tbs[..4].to_vec()),
+        )
+        .unwrap();
+        let token = wrap(&message.to_vec().unwrap());
+        assert_eq!(token[..3], [0xd8, 0x3d, 0xd2]);
+
+        let parsed = CoseSign1::from_slice(unwrap(&token).unwrap()).unwrap();
+        let decoded = ClaimsSet::from_slice(parsed.payload().unwrap()).unwrap();
+        assert_eq!(decoded, claims);
+    }
+}
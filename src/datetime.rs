@@ -0,0 +1,388 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Date/time support for tags 0 and 1
+//!
+//! Enabled with the `chrono` and `time` features. The `chrono`
+//! module handles `chrono::DateTime<Utc>` and the `time` module
+//! handles `time::OffsetDateTime`, both as `#[serde(with = "...")]` modules:
+//! - the module itself writes an RFC 3339 string with tag 0
+//! - its `epoch` submodule writes seconds since the epoch with tag 1, as an
+//!   integer for whole seconds and a float otherwise
+//!
+//! Either module reads both representations, with or without the tag, so
+//! data written by other encoders is accepted. Strings must be valid RFC 3339
+//! date/times; a tag other than 0 or 1, or a tag that does not match the
+//! content, is an error.
+//!
+//! # Example
+//! ```
+//! # #[cfg(feature = "chrono")]
+//! # {
+//! use chrono::{DateTime, TimeZone, Utc};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Action {
+//!     #[serde(with = "c2pa_cbor::datetime::chrono")]
+//!     when: DateTime<Utc>,
+//! }
+//!
+//! let action = Action {
+//!     when: Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap(),
+//! };
+//! let cbor = c2pa_cbor::to_vec(&action).unwrap();
+//! assert_eq!(&cbor[6..9], [0xc0, 0x74, b'2']); // tag 0, 20-byte string
+//! assert_eq!(c2pa_cbor::from_slice::<Action>(&cbor).unwrap(), action);
+//! # }
+//! ```
+
+use std::fmt;
+
+use serde::{
+    Deserialize, Deserializer,
+    de::{self, Visitor},
+};
+
+use crate::{
+    constants::{TAG_DATETIME_STRING, TAG_EPOCH_DATETIME},
+    tags::Tagged,
+};
+
+/// A date/time as found in the input, before conversion
+enum DateTimeInput {
+    Text(String),
+    Epoch { secs: i64, nanos: u32 },
+}
+
+impl<'de> Deserialize<'de> for DateTimeInput {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct InputVisitor;
+
+        impl Visitor<'_> for InputVisitor {
+            type Value = DateTimeInput;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an RFC 3339 string or seconds since the epoch")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<DateTimeInput, E> {
+                Ok(DateTimeInput::Text(v.to_string()))
+            }
+
+            fn visit_string<E: de::Error>(self, v: String) -> Result<DateTimeInput, E> {
+                Ok(DateTimeInput::Text(v))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<DateTimeInput, E> {
+                Ok(DateTimeInput::Epoch { secs: v, nanos: 0 })
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<DateTimeInput, E> {
+                let secs = i64::try_from(v).map_err(|_| E::custom("Epoch time out of range"))?;
+                Ok(DateTimeInput::Epoch { secs, nanos: 0 })
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<DateTimeInput, E> {
+                let floor = v.floor();
+                if !v.is_finite() || floor < i64::MIN as f64 || floor >= i64::MAX as f64 {
+                    return Err(E::custom("Epoch time out of range"));
+                }
+                let nanos = ((v - floor) * 1e9).round() as u32;
+                // Rounding can carry into the next second
+                Ok(match nanos {
+                    1_000_000_000 => DateTimeInput::Epoch {
+                        secs: floor as i64 + 1,
+                        nanos: 0,
+                    },
+                    nanos => DateTimeInput::Epoch {
+                        secs: floor as i64,
+                        nanos,
+                    },
+                })
+            }
+        }
+
+        deserializer.deserialize_any(InputVisitor)
+    }
+}
+
+/// Read a date/time in either representation, checking its tag
+fn deserialize_input<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<DateTimeInput, D::Error> {
+    let tagged = Tagged::<DateTimeInput>::deserialize(deserializer)?;
+    match (tagged.tag, &tagged.value) {
+        (None, _)
+        | (Some(TAG_DATETIME_STRING), DateTimeInput::Text(_))
+        | (Some(TAG_EPOCH_DATETIME), DateTimeInput::Epoch { .. }) => Ok(tagged.value),
+        (Some(tag), _) => Err(de::Error::custom(format!(
+            "Tag {} does not match the date/time content",
+            tag
+        ))),
+    }
+}
+
+/// Seconds since the epoch as a float, for times with a fractional second
+fn epoch_float(secs: i64, nanos: u32) -> f64 {
+    secs as f64 + nanos as f64 / 1e9
+}
+
+/// `DateTime<Utc>` as a tag 0 RFC 3339 string
+///
+/// Use with `#[serde(with = "c2pa_cbor::datetime::chrono")]`.
+#[cfg(feature = "chrono")]
+pub mod chrono {
+    use ::chrono::{DateTime, SecondsFormat, Utc};
+    use serde::{Deserializer, Serialize, Serializer, de};
+
+    use super::{DateTimeInput, deserialize_input, epoch_float};
+    use crate::{
+        constants::{TAG_DATETIME_STRING, TAG_EPOCH_DATETIME},
+        tags::Tagged,
+    };
+
+    fn from_input<E: de::Error>(input: DateTimeInput) -> Result<DateTime<Utc>, E> {
+        match input {
+            DateTimeInput::Text(s) => DateTime::parse_from_rfc3339(&s)
+                .map(|dt| dt.with_timezone(&Utc))
+                .map_err(|e| E::custom(format!("Invalid RFC 3339 date/time {:?}: {}", s, e))),
+            DateTimeInput::Epoch { secs, nanos } => DateTime::from_timestamp(secs, nanos)
+                .ok_or_else(|| E::custom("Epoch time out of range")),
+        }
+    }
+
+    /// Serialize as an RFC 3339 string with tag 0
+    pub fn serialize<S: Serializer>(dt: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+        let text = dt.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+        Tagged::new(Some(TAG_DATETIME_STRING), text).serialize(serializer)
+    }
+
+    /// Deserialize from a tag 0 string or a tag 1 number
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        from_input(deserialize_input(deserializer)?)
+    }
+
+    /// `DateTime<Utc>` as tag 1 seconds since the epoch
+    ///
+    /// Use with `#[serde(with = "c2pa_cbor::datetime::chrono::epoch")]`.
+    pub mod epoch {
+        use super::*;
+
+        /// Serialize as seconds since the epoch with tag 1
+        pub fn serialize<S: Serializer>(
+            dt: &DateTime<Utc>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            let (secs, nanos) = (dt.timestamp(), dt.timestamp_subsec_nanos());
+            if nanos == 0 {
+                Tagged::new(Some(TAG_EPOCH_DATETIME), secs).serialize(serializer)
+            } else {
+                Tagged::new(Some(TAG_EPOCH_DATETIME), epoch_float(secs, nanos))
+                    .serialize(serializer)
+            }
+        }
+
+        /// Deserialize from a tag 0 string or a tag 1 number
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<DateTime<Utc>, D::Error> {
+            super::deserialize(deserializer)
+        }
+    }
+}
+
+/// `OffsetDateTime` as a tag 0 RFC 3339 string
+///
+/// Use with `#[serde(with = "c2pa_cbor::datetime::time")]`.
+#[cfg(feature = "time")]
+pub mod time {
+    use ::time::{OffsetDateTime, format_description::well_known::Rfc3339};
+    use serde::{Deserializer, Serialize, Serializer, de, ser};
+
+    use super::{DateTimeInput, deserialize_input, epoch_float};
+    use crate::{
+        constants::{TAG_DATETIME_STRING, TAG_EPOCH_DATETIME},
+        tags::Tagged,
+    };
+
+    fn from_input<E: de::Error>(input: DateTimeInput) -> Result<OffsetDateTime, E> {
+        match input {
+            DateTimeInput::Text(s) => OffsetDateTime::parse(&s, &Rfc3339)
+                .map_err(|e| E::custom(format!("Invalid RFC 3339 date/time {:?}: {}", s, e))),
+            DateTimeInput::Epoch { secs, nanos } => OffsetDateTime::from_unix_timestamp_nanos(
+                secs as i128 * 1_000_000_000 + nanos as i128,
+            )
+            .map_err(|_| E::custom("Epoch time out of range")),
+        }
+    }
+
+    /// Serialize as an RFC 3339 string with tag 0, keeping the UTC offset
+    pub fn serialize<S: Serializer>(dt: &OffsetDateTime, serializer: S) -> Result<S::Ok, S::Error> {
+        let text = dt.format(&Rfc3339).map_err(ser::Error::custom)?;
+        Tagged::new(Some(TAG_DATETIME_STRING), text).serialize(serializer)
+    }
+
+    /// Deserialize from a tag 0 string or a tag 1 number
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<OffsetDateTime, D::Error> {
+        from_input(deserialize_input(deserializer)?)
+    }
+
+    /// `OffsetDateTime` as tag 1 seconds since the epoch
+    ///
+    /// Use with `#[serde(with = "c2pa_cbor::datetime::time::epoch")]`. The
+    /// UTC offset is not stored; decoded values are in UTC.
+    pub mod epoch {
+        use super::*;
+
+        /// Serialize as seconds since the epoch with tag 1
+        pub fn serialize<S: Serializer>(
+            dt: &OffsetDateTime,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            let (secs, nanos) = (dt.unix_timestamp(), dt.nanosecond());
+            if nanos == 0 {
+                Tagged::new(Some(TAG_EPOCH_DATETIME), secs).serialize(serializer)
+            } else {
+                Tagged::new(Some(TAG_EPOCH_DATETIME), epoch_float(secs, nanos))
+                    .serialize(serializer)
+            }
+        }
+
+        /// Deserialize from a tag 0 string or a tag 1 number
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<OffsetDateTime, D::Error> {
+            super::deserialize(deserializer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tagged_text(tag: u64, text: &str) -> Vec<u8> {
+        let mut cbor = Vec::new();
+        crate::encode_tagged(&mut cbor, tag, &text).unwrap();
+        cbor
+    }
+
+    #[test]
+    fn test_datetime_input_tags() {
+        let read = |cbor: &[u8]| deserialize_input(&mut crate::Decoder::from_slice(cbor));
+
+        assert!(matches!(
+            read(&tagged_text(0, "2024-01-15T10:30:00Z")),
+            Ok(DateTimeInput::Text(_))
+        ));
+        // 1(-1.5) is half a second into the second before -1
+        assert!(matches!(
+            read(&[0xc1, 0xf9, 0xbe, 0x00]),
+            Ok(DateTimeInput::Epoch {
+                secs: -2,
+                nanos: 500_000_000
+            })
+        ));
+        assert!(matches!(
+            read(&[0x1a, 0x65, 0xa5, 0x08, 0x38]),
+            Ok(DateTimeInput::Epoch {
+                secs: 1705314360,
+                nanos: 0
+            })
+        ));
+
+        // Mismatched or unrelated tags
+        assert!(read(&tagged_text(1, "2024-01-15T10:30:00Z")).is_err());
+        assert!(read(&[0xc0, 0x01]).is_err());
+        assert!(read(&tagged_text(32, "2024-01-15T10:30:00Z")).is_err());
+        assert!(read(&[0xc1, 0xf9, 0x7c, 0x00]).is_err());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono_datetime() {
+        use ::chrono::{DateTime, TimeZone, Utc};
+        use serde::Serialize;
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Times {
+            #[serde(with = "crate::datetime::chrono")]
+            text: DateTime<Utc>,
+            #[serde(with = "crate::datetime::chrono::epoch")]
+            epoch: DateTime<Utc>,
+        }
+
+        let when = Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap();
+        let times = Times {
+            text: when,
+            epoch: when + ::chrono::Duration::milliseconds(250),
+        };
+        let cbor = crate::to_vec(&times).unwrap();
+        assert_eq!(crate::from_slice::<Times>(&cbor).unwrap(), times);
+
+        // Each field reads the other representation too
+        #[derive(Serialize)]
+        struct Swapped {
+            text: i64,
+            epoch: &'static str,
+        }
+        let swapped: Times = crate::from_slice(
+            &crate::to_vec(&Swapped {
+                text: when.timestamp(),
+                epoch: "2024-01-15T12:30:00+02:00",
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!((swapped.text, swapped.epoch), (when, when));
+
+        let invalid = tagged_text(0, "2024-01-15 10:30:00");
+        assert!(self::chrono::deserialize(&mut crate::Decoder::from_slice(&invalid)).is_err());
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time_datetime() {
+        use ::time::{OffsetDateTime, UtcOffset};
+        use serde::Serialize;
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Times {
+            #[serde(with = "crate::datetime::time")]
+            text: OffsetDateTime,
+            #[serde(with = "crate::datetime::time::epoch")]
+            epoch: OffsetDateTime,
+        }
+
+        let when = OffsetDateTime::from_unix_timestamp(1705314600).unwrap();
+        let times = Times {
+            text: when.to_offset(UtcOffset::from_hms(2, 0, 0).unwrap()),
+            epoch: when + ::time::Duration::milliseconds(250),
+        };
+        let cbor = crate::to_vec(&times).unwrap();
+        let decoded: Times = crate::from_slice(&cbor).unwrap();
+        assert_eq!(decoded, times);
+        assert_eq!(decoded.text.offset(), times.text.offset());
+
+        let expected = tagged_text(0, "2024-01-15T12:30:00+02:00");
+        assert!(cbor.windows(expected.len()).any(|w| w == expected));
+
+        let invalid = tagged_text(0, "15 Jan 2024");
+        assert!(self::time::deserialize(&mut crate::Decoder::from_slice(&invalid)).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! dCBOR (Gordian deterministic CBOR) profile
+//!
+//! dCBOR builds on the core deterministic encoding used by
+//! [`canonicalize`](crate::canonicalize) and adds:
+//! - numeric reduction: floats with integral values that fit a CBOR integer
+//!   are encoded as integers (so `2.0` becomes `2` and `-0.0` becomes `0`)
+//! - NaN normalization: every NaN is encoded as `0xf97e00`
+//! - only `false`, `true` and `null` are allowed as simple values
+//! - duplicate map keys are rejected, including keys that only become equal
+//!   after numeric reduction
+//!
+//! Unicode NFC normalization of text strings is not checked, since this crate
+//! does not carry Unicode normalization tables.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::dcbor;
+//!
+//! let bytes = dcbor::to_vec(&vec![1.0, 1.5]).unwrap();
+//! assert_eq!(bytes, [0x82, 0x01, 0xf9, 0x3e, 0x00]);
+//!
+//! let values: Vec<f64> = dcbor::from_slice(&bytes).unwrap();
+//! assert_eq!(values, vec![1.0, 1.5]);
+//!
+//! // A double-precision 1.0 is not reduced, so it is rejected
+//! assert!(dcbor::validate(&[0xfb, 0x3f, 0xf0, 0, 0, 0, 0, 0, 0]).is_err());
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Error, Result,
+    canonical::{Profile, check_canonical, normalize},
+};
+
+/// Re-encode a single CBOR item as dCBOR
+///
+/// Returns an error if the input is malformed, contains simple values other
+/// than `false`, `true` and `null`, or has duplicate map keys once reduced.
+pub fn canonicalize(bytes: &[u8]) -> Result<Vec<u8>> {
+    normalize(bytes, Profile::Dcbor)
+}
+
+/// Check that `bytes` is a single item already encoded as dCBOR
+pub fn validate(bytes: &[u8]) -> Result<()> {
+    if let Some(violation) = check_canonical(bytes)? {
+        return Err(Error::Syntax(format!("Not dCBOR: {}", violation)));
+    }
+    // Core rules hold, so any difference comes from numeric reduction
+    if canonicalize(bytes)? != bytes {
+        return Err(Error::Syntax(
+            "Not dCBOR: integral floats must be encoded as integers".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Serialize a value to dCBOR bytes
+pub fn to_vec<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    canonicalize(&crate::to_vec(value)?)
+}
+
+/// Deserialize a value from bytes, rejecting input that is not dCBOR
+pub fn from_slice<'de, T: Deserialize<'de>>(bytes: &[u8]) -> Result<T> {
+    validate(bytes)?;
+    crate::from_slice(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[test]
+    fn test_dcbor_numeric_reduction() {
+        assert_eq!(to_vec(&2.0f64).unwrap(), [0x02]);
+        assert_eq!(to_vec(&-0.0f64).unwrap(), [0x00]);
+        assert_eq!(to_vec(&-1.0f32).unwrap(), [0x20]);
+        assert_eq!(to_vec(&65536.0f64).unwrap(), [0x1a, 0x00, 0x01, 0x00, 0x00]);
+        // 2^63 and -2^64 are integral but outside i64
+        assert_eq!(
+            to_vec(&9223372036854775808.0f64).unwrap(),
+            [0x1b, 0x80, 0, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            to_vec(&-18446744073709551616.0f64).unwrap(),
+            [0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+        );
+        // 2^64 does not fit and stays a float
+        assert_eq!(
+            to_vec(&18446744073709551616.0f64).unwrap(),
+            [0xfa, 0x5f, 0x80, 0x00, 0x00]
+        );
+        // Non-integral values and infinities keep the preferred float encoding
+        assert_eq!(to_vec(&1.5f64).unwrap(), [0xf9, 0x3e, 0x00]);
+        assert_eq!(to_vec(&f64::INFINITY).unwrap(), [0xf9, 0x7c, 0x00]);
+    }
+
+    #[test]
+    fn test_dcbor_nan_normalization() {
+        let nan = f64::from_bits(0x7ff8_0000_0000_0001);
+        assert_eq!(to_vec(&nan).unwrap(), [0xf9, 0x7e, 0x00]);
+        assert_eq!(to_vec(&f32::NAN).unwrap(), [0xf9, 0x7e, 0x00]);
+        assert!(validate(&[0xfa, 0x7f, 0xc0, 0x00, 0x00]).is_err());
+        assert!(validate(&[0xf9, 0x7e, 0x00]).is_ok());
+    }
+
+    #[test]
+    fn test_dcbor_simple_values() {
+        assert!(canonicalize(&[0xf4]).is_ok());
+        assert!(canonicalize(&[0xf5]).is_ok());
+        assert!(canonicalize(&[0xf6]).is_ok());
+        assert!(canonicalize(&[0xf7]).is_err()); // undefined
+        assert!(canonicalize(&[0xe0]).is_err()); // simple(0)
+        assert!(canonicalize(&[0xf8, 0x20]).is_err()); // simple(32)
+        assert!(validate(&[0xf7]).is_err());
+    }
+
+    #[test]
+    fn test_dcbor_duplicate_keys_after_reduction() {
+        // {1: 0, 1.0: 0}
+        let input = [0xa2, 0x01, 0x00, 0xf9, 0x3c, 0x00, 0x00];
+        assert!(crate::canonicalize(&input).is_ok());
+        assert!(canonicalize(&input).is_err());
+    }
+
+    #[test]
+    fn test_dcbor_from_slice_rejects_non_conforming() {
+        let mut map = BTreeMap::new();
+        map.insert("b".to_string(), 2.0f64);
+        map.insert("a".to_string(), 0.5f64);
+        let bytes = to_vec(&map).unwrap();
+        assert_eq!(from_slice::<BTreeMap<String, f64>>(&bytes).unwrap(), map);
+
+        // Same data with a float that was not reduced
+        let plain = crate::canonicalize(&crate::to_vec(&map).unwrap()).unwrap();
+        assert_ne!(plain, bytes);
+        assert!(from_slice::<BTreeMap<String, f64>>(&plain).is_err());
+
+        // Unsorted keys are reported with their location
+        let unsorted = [0xa2, 0x61, b'b', 0x01, 0x61, b'a', 0x02];
+        match validate(&unsorted) {
+            Err(Error::Syntax(msg)) => assert_eq!(msg, "Not dCBOR: unsorted map keys at offset 4"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -15,9 +15,175 @@
 
 use std::io::{BufReader, Cursor, Read};
 
-use serde::{Deserialize, de::IntoDeserializer};
+use serde::{
+    Deserialize,
+    de::{DeserializeSeed, IntoDeserializer},
+};
+
+use crate::{
+    Argument, Error, Header, MajorType, Result, UriPolicy, VariantKeys,
+    constants::*,
+    header::argument_len,
+    raw::u64_to_usize,
+    tags::{TaggedSerialize, check_declared_tag, set_tag},
+};
+
+/// Kind of string reported to a [`StringPolicy`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringKind {
+    /// Byte string (major type 2)
+    Bytes,
+    /// Text string (major type 3)
+    Text,
+}
+
+/// One step of the location of a string within the decoded document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    /// Value of a map entry with a text key
+    Key(String),
+    /// Value of a map entry with an integer key
+    IntKey(i128),
+    /// Value of a map entry whose key is neither text nor an integer
+    OtherKey,
+    /// Element of an array
+    Index(usize),
+}
+
+/// How numbers are read into a numeric type of the other kind
+///
+/// Set with [`Decoder::with_number_coercion`]. Non-finite floats and values
+/// outside the range of the target type are always an error.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumberCoercion {
+    /// Integers are read into float types, as serde allows; floats are
+    /// never read into integer types
+    #[default]
+    None,
+    /// Only convert when the value is unchanged: whole floats into integer
+    /// types, and integers the float type represents exactly
+    Strict,
+    /// Floats into integer types are truncated toward zero, and integers
+    /// into float types are rounded to the nearest float
+    Lossy,
+}
+
+// The numeric type a visitor asked for
+#[derive(Clone, Copy, PartialEq)]
+enum NumberTarget {
+    Integer,
+    F32,
+    F64,
+}
+
+/// Options that change which CBOR a [`Decoder`] accepts and how it is read
+///
+/// The defaults accept any well-formed CBOR that fits the target type.
+/// Options are set with [`Decoder::with_options`] or the matching builder
+/// methods.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::{Decoder, DecoderOptions};
+///
+/// let options = DecoderOptions {
+///     strict_nulls: true,
+///     allow_indefinite: false,
+///     ..Default::default()
+/// };
+/// let mut decoder = Decoder::new(&[0x9f, 0xff][..]).with_options(options);
+/// assert!(decoder.decode::<Vec<u8>>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecoderOptions {
+    /// Accept newtype structs wrapped in a 1-element array, see
+    /// [`Decoder::with_legacy_newtypes`]
+    pub legacy_newtypes: bool,
+    /// How integer enum variant keys are read, see [`Decoder::with_variant_keys`]
+    pub variant_keys: VariantKeys,
+    /// Reject null where an `Option` is expected, see [`Decoder::with_strict_nulls`]
+    pub strict_nulls: bool,
+    /// Reject `undefined`, see [`Decoder::with_strict_undefined`]
+    pub strict_undefined: bool,
+    /// Reject 2-byte simple values below 32, see [`Decoder::with_strict_simple_values`]
+    pub strict_simple_values: bool,
+    /// Reject NaN and infinite floats, see [`Decoder::with_finite_floats`]
+    pub finite_floats: bool,
+    /// Convert between integers and floats, see [`Decoder::with_number_coercion`]
+    pub number_coercion: NumberCoercion,
+    /// Replace invalid UTF-8 in text strings, see [`Decoder::with_lossy_utf8`]
+    pub lossy_utf8: bool,
+    /// Accept indefinite-length strings, arrays and maps, see
+    /// [`Decoder::with_allow_indefinite`]
+    pub allow_indefinite: bool,
+    /// Decode maps into [`Value`](crate::Value) in wire order, see
+    /// [`Decoder::with_ordered_maps`]
+    pub ordered_maps: bool,
+}
+
+impl Default for DecoderOptions {
+    fn default() -> Self {
+        DecoderOptions {
+            legacy_newtypes: false,
+            variant_keys: VariantKeys::Name,
+            strict_nulls: false,
+            strict_undefined: false,
+            strict_simple_values: false,
+            finite_floats: false,
+            number_coercion: NumberCoercion::None,
+            lossy_utf8: false,
+            allow_indefinite: true,
+            ordered_maps: false,
+        }
+    }
+}
+
+/// Policy callbacks invoked for every text and byte string while decoding
+///
+/// This lets callers reject disallowed content (oversized thumbnails,
+/// forbidden URI schemes, ...) during parsing instead of after the whole
+/// document has been materialized. Returning an error aborts decoding with
+/// that error.
+///
+/// `path` lists the map keys and array indices leading to the string; it is
+/// empty for a top-level string. Map keys are reported with the path of the
+/// map they belong to.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::{Decoder, Error, PathSegment, Result, StringKind, StringPolicy};
+///
+/// struct MaxThumbnail(u64);
+///
+/// impl StringPolicy for MaxThumbnail {
+///     fn check_len(&mut self, path: &[PathSegment], kind: StringKind, len: u64) -> Result<()> {
+///         let is_thumbnail = matches!(path.last(), Some(PathSegment::Key(k)) if k == "thumbnail");
+///         if kind == StringKind::Bytes && is_thumbnail && len > self.0 {
+///             return Err(Error::Message(format!("thumbnail of {len} bytes is too large")));
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// // {"thumbnail": h'00000000'}
+/// let data = [0xa1, 0x69, b't', b'h', b'u', b'm', b'b', b'n', b'a', b'i', b'l', 0x44, 0, 0, 0, 0];
+/// let mut decoder = Decoder::new(&data[..]).with_string_policy(MaxThumbnail(2));
+/// assert!(decoder.decode::<c2pa_cbor::Value>().is_err());
+/// ```
+pub trait StringPolicy {
+    /// Called with the declared length before a string's content is read
+    ///
+    /// For indefinite-length strings this is called before each chunk with
+    /// the total length the string will have once that chunk is read.
+    fn check_len(&mut self, _path: &[PathSegment], _kind: StringKind, _len: u64) -> Result<()> {
+        Ok(())
+    }
 
-use crate::{Error, Result, constants::*};
+    /// Called with the complete content of each string once it has been read
+    fn check_data(&mut self, _path: &[PathSegment], _kind: StringKind, _data: &[u8]) -> Result<()> {
+        Ok(())
+    }
+}
 
 pub struct Decoder<R: Read> {
     reader: R,
@@ -25,18 +191,24 @@ pub struct Decoder<R: Read> {
     max_allocation: Option<usize>,
     recursion_depth: usize,
     max_recursion_depth: usize,
-    current_tag: Option<u64>,
-}
-
-/// Safely convert u64 to usize, checking for overflow on 32-bit platforms
-#[inline]
-fn u64_to_usize(val: u64) -> Result<usize> {
-    usize::try_from(val).map_err(|_| {
-        Error::Syntax(format!(
-            "Length {} exceeds maximum supported size on this platform",
-            val
-        ))
-    })
+    policy: Option<Box<dyn StringPolicy + Send>>,
+    // Location bookkeeping, only maintained while a policy is installed
+    path: Vec<PathSegment>,
+    capture_key: bool,
+    captured_key: Option<PathSegment>,
+    pending_key: Option<PathSegment>,
+    uri_policy: Option<UriPolicy>,
+    // Set after reading tag 32 while a URI policy is installed
+    expect_uri: bool,
+    max_items: Option<u64>,
+    items: u64,
+    memory_budget: Option<usize>,
+    memory_used: usize,
+    options: DecoderOptions,
+    // Raw bytes of text strings that were not valid UTF-8
+    invalid_utf8: Vec<Vec<u8>>,
+    // Where the chunks of indefinite-length strings are joined
+    scratch: Vec<u8>,
 }
 
 impl<R: Read> Decoder<R> {
@@ -63,7 +235,20 @@ impl<R: Read> Decoder<R> {
             max_allocation: None,
             recursion_depth: 0,
             max_recursion_depth: DEFAULT_MAX_DEPTH,
-            current_tag: None,
+            policy: None,
+            path: Vec::new(),
+            capture_key: false,
+            captured_key: None,
+            pending_key: None,
+            uri_policy: None,
+            expect_uri: false,
+            max_items: None,
+            items: 0,
+            memory_budget: None,
+            memory_used: 0,
+            options: DecoderOptions::default(),
+            invalid_utf8: Vec::new(),
+            scratch: Vec::new(),
         }
     }
 
@@ -93,6 +278,8 @@ impl<R: Read> Decoder<R> {
     ///
     /// This prevents stack overflow from deeply nested CBOR structures.
     /// Default is 128 levels, which is sufficient for most use cases.
+    /// [`decode_value`](Self::decode_value) and [`skip`](Self::skip) do not
+    /// recurse, so a much higher limit is safe when only they are used.
     ///
     /// # Examples
     ///
@@ -109,6 +296,510 @@ impl<R: Read> Decoder<R> {
         self
     }
 
+    /// Install a policy that is consulted for every text and byte string (builder pattern)
+    ///
+    /// See [`StringPolicy`] for when each callback runs. Tracking the path of
+    /// each string has a small cost, so it is only done while a policy is set.
+    pub fn with_string_policy<P: StringPolicy + Send + 'static>(mut self, policy: P) -> Self {
+        self.policy = Some(Box::new(policy));
+        self
+    }
+
+    /// Check every tag 32 (URI) value against `policy` (builder pattern)
+    ///
+    /// Decoding fails if a tag 32 value is not a text string, is not a
+    /// syntactically valid URI, or uses a scheme that is not allowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::{Decoder, UriPolicy, Value, encode_uri};
+    ///
+    /// let mut data = Vec::new();
+    /// encode_uri(&mut data, "ftp://example.com").unwrap();
+    /// let mut decoder = Decoder::new(&data[..]).with_uri_policy(UriPolicy::c2pa());
+    /// assert!(decoder.decode::<Value>().is_err());
+    /// ```
+    pub fn with_uri_policy(mut self, policy: UriPolicy) -> Self {
+        self.uri_policy = Some(policy);
+        self
+    }
+
+    /// Set the maximum number of data items to decode (builder pattern)
+    ///
+    /// Every header counts as one item: integers, strings and each chunk of an
+    /// indefinite-length string, arrays, maps, tags, and simple values. Because
+    /// the work done per item is bounded, this bounds decode time for inputs
+    /// that are small but made of many tiny items, such as deeply repeated
+    /// empty arrays. See [`from_slice_bounded`] for a limit relative to the
+    /// input length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::{Decoder, Value};
+    ///
+    /// let data = [0x83, 0x80, 0x80, 0x80]; // [[], [], []]
+    /// let mut decoder = Decoder::new(&data[..]).with_max_items(3);
+    /// assert!(decoder.decode::<Value>().is_err());
+    /// ```
+    pub fn with_max_items(mut self, max_items: u64) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    /// Set a total memory budget for decoded data, in bytes (builder pattern)
+    ///
+    /// Unlike [`with_max_allocation`](Self::with_max_allocation), which
+    /// limits each string, the budget is cumulative: every item is charged
+    /// the size of a [`Value`](crate::Value), and every text and byte string
+    /// its length as well, which bounds the memory of a decoded `Value` tree.
+    /// Strings are charged before they are allocated. Once the budget is
+    /// exceeded decoding stops with [`Error::MemoryBudgetExceeded`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::{Decoder, Error, Value};
+    ///
+    /// let data = c2pa_cbor::to_vec(&vec![vec![0u8; 600]; 3]).unwrap();
+    /// let mut decoder = Decoder::new(&data[..]).with_memory_budget(1024);
+    /// assert!(matches!(
+    ///     decoder.decode::<Value>(),
+    ///     Err(Error::MemoryBudgetExceeded(1024))
+    /// ));
+    /// ```
+    pub fn with_memory_budget(mut self, bytes: usize) -> Self {
+        self.memory_budget = Some(bytes);
+        self
+    }
+
+    /// Replace all decoding options at once (builder pattern)
+    pub fn with_options(mut self, options: DecoderOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// The decoding options in effect
+    pub fn options(&self) -> DecoderOptions {
+        self.options
+    }
+
+    /// Also read newtype structs wrapped in a 1-element array (builder pattern)
+    ///
+    /// Newtype structs are read from their inner value by default, which is
+    /// how this crate and other CBOR libraries write them. Data written by
+    /// earlier releases of this crate, or with
+    /// [`Encoder::with_legacy_newtypes`](crate::Encoder::with_legacy_newtypes),
+    /// wraps the inner value in a 1-element array instead. In this
+    /// compatibility mode a 1-element array in newtype position is always
+    /// taken to be such a wrapper, and anything else is read transparently.
+    /// A transparently written newtype whose inner value is itself a
+    /// 1-element array is therefore misread, so only enable this for data
+    /// that may contain the legacy form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::Decoder;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// struct Label(String);
+    ///
+    /// let legacy = [0x81, 0x61, b'a'];
+    /// assert!(Decoder::new(&legacy[..]).decode::<Label>().is_err());
+    /// let mut decoder = Decoder::new(&legacy[..]).with_legacy_newtypes();
+    /// assert_eq!(decoder.decode::<Label>().unwrap(), Label("a".into()));
+    /// ```
+    pub fn with_legacy_newtypes(mut self) -> Self {
+        self.options.legacy_newtypes = true;
+        self
+    }
+
+    /// Choose how integer enum variant keys are read (builder pattern)
+    ///
+    /// Text keys always name the variant. Integer keys are variant indexes
+    /// by default, as written with [`VariantKeys::Index`]. With
+    /// [`VariantKeys::NumericName`] they are matched against variant names
+    /// in decimal instead, such as `#[serde(rename = "100")]`. See
+    /// [`Encoder::with_variant_keys`](crate::Encoder::with_variant_keys) for an example.
+    pub fn with_variant_keys(mut self, variant_keys: VariantKeys) -> Self {
+        self.options.variant_keys = variant_keys;
+        self
+    }
+
+    /// Reject null where an `Option` is expected (builder pattern)
+    ///
+    /// By default a null decodes as `None`, just like an absent struct
+    /// field does. For schemas where only an absent field means `None` and
+    /// an explicit null is invalid, this makes null an error for every
+    /// `Option`. Fields that may legitimately hold null can opt out with the
+    /// [`nullable`](crate::nullable) module, which also tells the two apart.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::Decoder;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Debug, PartialEq)]
+    /// struct Claim {
+    ///     title: Option<String>,
+    /// }
+    ///
+    /// let absent = [0xa0]; // {}
+    /// let null = [0xa1, 0x65, b't', b'i', b't', b'l', b'e', 0xf6]; // {"title": null}
+    /// let mut decoder = Decoder::new(&absent[..]).with_strict_nulls();
+    /// assert_eq!(decoder.decode::<Claim>().unwrap(), Claim { title: None });
+    /// let mut decoder = Decoder::new(&null[..]).with_strict_nulls();
+    /// assert!(decoder.decode::<Claim>().is_err());
+    /// ```
+    pub fn with_strict_nulls(mut self) -> Self {
+        self.options.strict_nulls = true;
+        self
+    }
+
+    /// Reject `undefined` (builder pattern)
+    ///
+    /// By default `undefined` decodes like `null`: as unit, as `None` for an
+    /// `Option`, and as [`Value::Undefined`](crate::Value::Undefined). With
+    /// this option it is a syntax error wherever it appears.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::Decoder;
+    ///
+    /// let cbor = [0x82, 0x01, 0xf7]; // [1, undefined]
+    /// let mut decoder = Decoder::new(&cbor[..]);
+    /// assert_eq!(decoder.decode::<Vec<Option<u8>>>().unwrap(), [Some(1), None]);
+    /// let mut decoder = Decoder::new(&cbor[..]).with_strict_undefined();
+    /// assert!(decoder.decode::<Vec<Option<u8>>>().is_err());
+    /// ```
+    pub fn with_strict_undefined(mut self) -> Self {
+        self.options.strict_undefined = true;
+        self
+    }
+
+    /// Reject simple values below 32 in the 2-byte form (builder pattern)
+    ///
+    /// RFC 8949 only allows the 2-byte form (`0xf8 n`) for values 32–255;
+    /// 24–31 are reserved and 0–23 have a 1-byte form. By default such
+    /// items decode as [`Value::Simple`](crate::Value::Simple); with this
+    /// option they are a syntax error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::{Decoder, Value};
+    ///
+    /// let mut decoder = Decoder::new(&[0xf8, 0xff][..]).with_strict_simple_values();
+    /// assert_eq!(decoder.decode::<Value>().unwrap(), Value::Simple(255));
+    /// let mut decoder = Decoder::new(&[0xf8, 0x18][..]).with_strict_simple_values();
+    /// assert!(decoder.decode::<Value>().is_err());
+    /// ```
+    pub fn with_strict_simple_values(mut self) -> Self {
+        self.options.strict_simple_values = true;
+        self
+    }
+
+    /// Reject NaN and infinite floats (builder pattern)
+    ///
+    /// For schemas that only allow finite numbers. Floats of every width
+    /// are checked, whatever type they are decoded into.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::Decoder;
+    ///
+    /// let nan = [0xf9, 0x7e, 0x00];
+    /// assert!(Decoder::new(&nan[..]).decode::<f64>().unwrap().is_nan());
+    /// assert!(Decoder::new(&nan[..]).with_finite_floats().decode::<f64>().is_err());
+    /// ```
+    pub fn with_finite_floats(mut self) -> Self {
+        self.options.finite_floats = true;
+        self
+    }
+
+    /// Convert between integers and floats to fit the target type (builder pattern)
+    ///
+    /// Producers that start from JSON don't keep integers and floats apart,
+    /// so `3.0` may arrive for a `u32` field or `3` for an `f64` field. See
+    /// [`NumberCoercion`] for the conversions of each mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::{Decoder, NumberCoercion};
+    ///
+    /// let whole = [0xf9, 0x42, 0x00]; // 3.0
+    /// assert!(c2pa_cbor::from_slice::<u32>(&whole).is_err());
+    /// let mut decoder = Decoder::new(&whole[..]).with_number_coercion(NumberCoercion::Strict);
+    /// assert_eq!(decoder.decode::<u32>().unwrap(), 3);
+    ///
+    /// let fraction = [0xf9, 0x3e, 0x00]; // 1.5
+    /// let mut decoder = Decoder::new(&fraction[..]).with_number_coercion(NumberCoercion::Strict);
+    /// assert!(decoder.decode::<u32>().is_err());
+    /// let mut decoder = Decoder::new(&fraction[..]).with_number_coercion(NumberCoercion::Lossy);
+    /// assert_eq!(decoder.decode::<u32>().unwrap(), 1);
+    /// ```
+    pub fn with_number_coercion(mut self, number_coercion: NumberCoercion) -> Self {
+        self.options.number_coercion = number_coercion;
+        self
+    }
+
+    /// Replace invalid UTF-8 in text strings with U+FFFD (builder pattern)
+    ///
+    /// By default a text string that is not valid UTF-8 is an
+    /// [`Error::InvalidUtf8`]. With this option it decodes with each invalid
+    /// sequence replaced, which helps when inspecting malformed documents.
+    /// Either way the raw bytes of the string are kept, see
+    /// [`invalid_utf8_strings`](Self::invalid_utf8_strings).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::Decoder;
+    ///
+    /// let cbor = [0x63, b'a', 0xff, b'b']; // "a\xffb"
+    /// assert!(Decoder::new(&cbor[..]).decode::<String>().is_err());
+    ///
+    /// let mut decoder = Decoder::new(&cbor[..]).with_lossy_utf8();
+    /// assert_eq!(decoder.decode::<String>().unwrap(), "a\u{fffd}b");
+    /// assert_eq!(decoder.invalid_utf8_strings(), [vec![b'a', 0xff, b'b']]);
+    /// ```
+    pub fn with_lossy_utf8(mut self) -> Self {
+        self.options.lossy_utf8 = true;
+        self
+    }
+
+    /// Accept or reject indefinite-length items (builder pattern)
+    ///
+    /// Indefinite-length strings, arrays and maps are accepted by default.
+    /// Canonical C2PA data only uses definite lengths, so decoders that
+    /// enforce that pass `false`; debugging tools can keep the default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::Decoder;
+    ///
+    /// let cbor = [0x9f, 0x01, 0xff]; // [_ 1]
+    /// assert_eq!(Decoder::new(&cbor[..]).decode::<Vec<u8>>().unwrap(), [1]);
+    /// let mut decoder = Decoder::new(&cbor[..]).with_allow_indefinite(false);
+    /// assert!(decoder.decode::<Vec<u8>>().is_err());
+    /// ```
+    pub fn with_allow_indefinite(mut self, allow: bool) -> Self {
+        self.options.allow_indefinite = allow;
+        self
+    }
+
+    /// Decode maps into [`Value::OrderedMap`](crate::Value::OrderedMap) (builder pattern)
+    ///
+    /// By default maps decode into [`Value::Map`](crate::Value::Map), which
+    /// sorts the entries and keeps only the last of repeated keys. With this
+    /// option a `Value` shows each map as it was sent, for forensics and
+    /// debugging, and [`Value::duplicate_keys`](crate::Value::duplicate_keys)
+    /// finds every repeated key. Other types are decoded as usual.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::{Decoder, Value};
+    ///
+    /// let cbor = [0xa2, 0x61, b'b', 0x01, 0x61, b'a', 0x02]; // {"b": 1, "a": 2}
+    /// let mut decoder = Decoder::new(&cbor[..]).with_ordered_maps();
+    /// let value: Value = decoder.decode().unwrap();
+    /// let keys: Vec<_> = value.as_ordered_map().unwrap().iter().map(|(k, _)| k).collect();
+    /// assert_eq!(keys, [&Value::Text("b".into()), &Value::Text("a".into())]);
+    /// assert_eq!(c2pa_cbor::to_vec(&value).unwrap(), cbor);
+    /// ```
+    pub fn with_ordered_maps(mut self) -> Self {
+        self.options.ordered_maps = true;
+        self
+    }
+
+    /// The raw bytes of each text string that was not valid UTF-8, in the order read
+    ///
+    /// Without [`with_lossy_utf8`](Self::with_lossy_utf8) decoding stops at
+    /// the first such string, which is then the last entry. Indefinite-length
+    /// strings are checked, and reported, one chunk at a time.
+    pub fn invalid_utf8_strings(&self) -> &[Vec<u8>] {
+        &self.invalid_utf8
+    }
+
+    /// Join the chunks of indefinite-length strings in `scratch` (builder pattern)
+    ///
+    /// The decoder joins chunks in a buffer it keeps between items, so once
+    /// the buffer has grown to fit the longest string, decoding chunked
+    /// strings no longer allocates for it. Supplying a buffer lets it start at
+    /// a known capacity, or be shared between decoders over time; a `String`
+    /// can be passed with [`String::into_bytes`]. Strings are then handed to
+    /// `Deserialize` impls by reference, so types such as `serde_bytes::ByteBuf`
+    /// make one exact-size copy, and identifiers none. The contents of
+    /// `scratch` are ignored.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::Decoder;
+    ///
+    /// // (_ "ab", "c")
+    /// let cbor = [0x7f, 0x62, b'a', b'b', 0x61, b'c', 0xff];
+    /// let mut decoder = Decoder::new(&cbor[..]).with_scratch(Vec::with_capacity(1024));
+    /// assert_eq!(decoder.decode::<String>().unwrap(), "abc");
+    /// let scratch = decoder.take_scratch();
+    /// assert!(scratch.capacity() >= 1024);
+    /// ```
+    pub fn with_scratch(mut self, scratch: Vec<u8>) -> Self {
+        self.scratch = scratch;
+        self
+    }
+
+    /// Take back the scratch buffer, see [`with_scratch`](Self::with_scratch)
+    ///
+    /// The decoder continues with an empty buffer.
+    pub fn take_scratch(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.scratch)
+    }
+
+    /// Start reading from `reader`, returning the previous reader
+    ///
+    /// Options, limits and policies are kept. Everything tied to the previous
+    /// input is cleared: a peeked byte, the item count and memory used
+    /// against [`with_max_items`](Self::with_max_items) and
+    /// [`with_memory_budget`](Self::with_memory_budget), and the
+    /// [`invalid_utf8_strings`](Self::invalid_utf8_strings). Internal buffers,
+    /// including the [scratch buffer](Self::with_scratch), keep their
+    /// capacity, so a decoder reused across messages stops allocating for them.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::Decoder;
+    ///
+    /// let mut decoder = Decoder::new(&[0x01, 0x02][..]).with_max_items(1);
+    /// assert_eq!(decoder.decode::<u8>().unwrap(), 1);
+    /// // A second item is over the limit, but a new message starts from zero
+    /// assert!(decoder.decode::<u8>().is_err());
+    /// decoder.reset(&[0x03][..]);
+    /// assert_eq!(decoder.decode::<u8>().unwrap(), 3);
+    /// ```
+    pub fn reset(&mut self, reader: R) -> R {
+        self.peeked = None;
+        self.recursion_depth = 0;
+        self.path.clear();
+        self.capture_key = false;
+        self.captured_key = None;
+        self.pending_key = None;
+        self.expect_uri = false;
+        self.items = 0;
+        self.memory_used = 0;
+        self.invalid_utf8.clear();
+        std::mem::replace(&mut self.reader, reader)
+    }
+
+    fn check_float(&self, value: f64) -> Result<()> {
+        if self.options.finite_floats && !value.is_finite() {
+            return Err(Error::Syntax(format!(
+                "Non-finite float {value} not allowed"
+            )));
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn count_item(&mut self) -> Result<()> {
+        if let Some(max) = self.max_items {
+            self.items += 1;
+            if self.items > max {
+                return Err(Error::Syntax(format!(
+                    "CBOR item count exceeds maximum {}",
+                    max
+                )));
+            }
+        }
+        self.charge_memory(std::mem::size_of::<crate::Value>())
+    }
+
+    /// Charge `bytes` against the memory budget, if one is set
+    #[inline]
+    fn charge_memory(&mut self, bytes: usize) -> Result<()> {
+        if let Some(budget) = self.memory_budget {
+            self.memory_used = self.memory_used.saturating_add(bytes);
+            if self.memory_used > budget {
+                return Err(Error::MemoryBudgetExceeded(budget));
+            }
+        }
+        Ok(())
+    }
+
+    /// Record a tag that has just been read, arming the URI check for tag 32
+    ///
+    /// The tag is published through [`current_cbor_tag`](crate::tags::current_cbor_tag)
+    /// while its content is read. Returns the enclosing tag, which the caller
+    /// restores with `set_tag` afterwards.
+    #[must_use]
+    fn enter_tag(&mut self, tag: u64) -> Option<u64> {
+        self.expect_uri = tag == TAG_URI && self.uri_policy.is_some();
+        set_tag(Some(tag))
+    }
+
+    fn policy_check_len(&mut self, kind: StringKind, len: u64) -> Result<()> {
+        if let Some(policy) = self.policy.as_mut() {
+            policy.check_len(&self.path, kind, len)?;
+        }
+        Ok(())
+    }
+
+    fn policy_check_data(&mut self, kind: StringKind, data: &[u8]) -> Result<()> {
+        if let Some(policy) = self.policy.as_mut() {
+            policy.check_data(&self.path, kind, data)?;
+        }
+        Ok(())
+    }
+
+    /// Deserialize a map key, remembering it as the path segment of the following value
+    fn deserialize_key<'de, K: serde::de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<K::Value> {
+        // Keys and values are items of their own, outside the enclosing tag
+        let outer_tag = set_tag(None);
+        if self.policy.is_none() {
+            let key = seed.deserialize(&mut *self);
+            set_tag(outer_tag);
+            return key;
+        }
+        self.capture_key = true;
+        self.captured_key = None;
+        let key = seed.deserialize(&mut *self);
+        self.capture_key = false;
+        self.pending_key = Some(self.captured_key.take().unwrap_or(PathSegment::OtherKey));
+        set_tag(outer_tag);
+        key
+    }
+
+    /// Deserialize a nested value with `segment` appended to the current path
+    fn deserialize_at<'de, T: serde::de::DeserializeSeed<'de>>(
+        &mut self,
+        segment: Option<PathSegment>,
+        seed: T,
+    ) -> Result<T::Value> {
+        let outer_tag = set_tag(None);
+        let value = match segment {
+            Some(segment) if self.policy.is_some() => {
+                self.path.push(segment);
+                let value = seed.deserialize(&mut *self);
+                self.path.pop();
+                value
+            }
+            _ => seed.deserialize(&mut *self),
+        };
+        set_tag(outer_tag);
+        value
+    }
+
     fn check_recursion_depth(&self) -> Result<()> {
         if self.recursion_depth >= self.max_recursion_depth {
             return Err(Error::Syntax(format!(
@@ -152,19 +843,19 @@ impl<R: Read> Decoder<R> {
         Ok(buf[0])
     }
 
-    fn read_u16(&mut self) -> Result<u16> {
+    fn read_be_u16(&mut self) -> Result<u16> {
         let mut buf = [0u8; 2];
         self.reader.read_exact(&mut buf)?;
         Ok(u16::from_be_bytes(buf))
     }
 
-    fn read_u32(&mut self) -> Result<u32> {
+    fn read_be_u32(&mut self) -> Result<u32> {
         let mut buf = [0u8; 4];
         self.reader.read_exact(&mut buf)?;
         Ok(u32::from_be_bytes(buf))
     }
 
-    fn read_u64(&mut self) -> Result<u64> {
+    fn read_be_u64(&mut self) -> Result<u64> {
         let mut buf = [0u8; 8];
         self.reader.read_exact(&mut buf)?;
         Ok(u64::from_be_bytes(buf))
@@ -174,14 +865,26 @@ impl<R: Read> Decoder<R> {
         Ok(match info {
             0..=23 => Some(info as u64),
             24 => Some(self.read_u8()? as u64),
-            25 => Some(self.read_u16()? as u64),
-            26 => Some(self.read_u32()? as u64),
-            27 => Some(self.read_u64()?),
-            INDEFINITE => None, // Indefinite length
+            25 => Some(self.read_be_u16()? as u64),
+            26 => Some(self.read_be_u32()? as u64),
+            27 => Some(self.read_be_u64()?),
+            INDEFINITE => {
+                self.check_indefinite_allowed()?;
+                None
+            }
             _ => return Err(Error::Syntax("Invalid CBOR value".to_string())),
         })
     }
 
+    fn check_indefinite_allowed(&self) -> Result<()> {
+        if !self.options.allow_indefinite {
+            return Err(Error::Syntax(
+                "Indefinite-length items are not allowed".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
     pub(crate) fn peek_u8(&mut self) -> Result<u8> {
         if let Some(byte) = self.peeked {
             return Ok(byte);
@@ -207,7 +910,8 @@ impl<R: Read> Decoder<R> {
 
     /// Read a definite-length byte buffer
     #[inline]
-    fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+    fn read_exact_bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+        self.charge_memory(len)?;
         let mut buf = self.try_allocate(len)?;
         self.reader.read_exact(&mut buf)?;
         Ok(buf)
@@ -215,98 +919,163 @@ impl<R: Read> Decoder<R> {
 
     /// Read a definite-length text string
     #[inline]
-    fn read_text(&mut self, len: usize) -> Result<String> {
-        let buf = self.read_bytes(len)?;
-        String::from_utf8(buf).map_err(|_| Error::InvalidUtf8)
+    fn read_exact_text(&mut self, len: usize) -> Result<String> {
+        let buf = self.read_exact_bytes(len)?;
+        self.text_from_utf8(buf)
+    }
+
+    /// Convert the content of a text string, recording it if it is not valid UTF-8
+    fn text_from_utf8(&mut self, buf: Vec<u8>) -> Result<String> {
+        let bytes = match String::from_utf8(buf) {
+            Ok(text) => return Ok(text),
+            Err(e) => e.into_bytes(),
+        };
+        let text = String::from_utf8_lossy(&bytes).into_owned();
+        self.invalid_utf8.push(bytes);
+        if !self.options.lossy_utf8 {
+            return Err(Error::InvalidUtf8);
+        }
+        Ok(text)
     }
 
-    /// Read indefinite-length byte string by concatenating chunks
+    /// Check a text string against the URI policy, and capture it as a map key
+    fn check_text(&mut self, s: &str, expect_uri: bool, capture_key: bool) -> Result<()> {
+        if expect_uri && let Some(policy) = &self.uri_policy {
+            policy.check(s)?;
+        }
+        if capture_key {
+            self.captured_key = Some(PathSegment::Key(s.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Read a definite-length byte string, consulting the string policy
     #[inline]
+    fn read_byte_string(&mut self, len: u64) -> Result<Vec<u8>> {
+        self.policy_check_len(StringKind::Bytes, len)?;
+        let buf = self.read_exact_bytes(u64_to_usize(len)?)?;
+        self.policy_check_data(StringKind::Bytes, &buf)?;
+        Ok(buf)
+    }
+
+    /// Read a definite-length text string, consulting the string policy
+    #[inline]
+    fn read_text_string(&mut self, len: u64) -> Result<String> {
+        self.policy_check_len(StringKind::Text, len)?;
+        let s = self.read_exact_text(u64_to_usize(len)?)?;
+        self.policy_check_data(StringKind::Text, s.as_bytes())?;
+        Ok(s)
+    }
+
+    /// Read indefinite-length byte string by concatenating chunks
     fn read_indefinite_bytes(&mut self) -> Result<Vec<u8>> {
-        let mut result = Vec::new();
-        loop {
-            if self.is_break()? {
-                self.read_break()?;
-                break;
-            }
-            let initial = self.read_u8()?;
-            let major = initial >> 5;
-            let info = initial & 0x1f;
-            if major != MAJOR_BYTES {
-                return Err(Error::Syntax(
-                    "Indefinite byte string chunks must be byte strings".to_string(),
-                ));
-            }
-            let len = self.read_length(info)?.ok_or_else(|| {
-                Error::Syntax("Indefinite byte string chunks cannot be indefinite".to_string())
-            })?;
-            let chunk = self.read_bytes(u64_to_usize(len)?)?;
-
-            // Check cumulative size against max_allocation limit
-            let new_size = result.len().saturating_add(chunk.len());
-            if let Some(max) = self.max_allocation
-                && new_size > max
-            {
-                return Err(Error::Syntax(format!(
-                    "Indefinite byte string total size {} exceeds maximum {} bytes",
-                    new_size, max
-                )));
-            }
-
-            result.extend_from_slice(&chunk);
-        }
-        Ok(result)
+        let buf = self.read_indefinite_scratch(MAJOR_BYTES)?;
+        let bytes = buf.clone();
+        self.scratch = buf;
+        Ok(bytes)
     }
 
     /// Read indefinite-length text string by concatenating chunks
-    #[inline]
     fn read_indefinite_text(&mut self) -> Result<String> {
-        let mut result = String::new();
+        let buf = self.read_indefinite_scratch(MAJOR_TEXT)?;
+        let text = String::from_utf8(buf.clone()).map_err(|_| Error::InvalidUtf8);
+        self.scratch = buf;
+        text
+    }
+
+    /// Concatenate the chunks of an indefinite-length string in the scratch buffer
+    ///
+    /// The buffer is taken out of the decoder, and the caller puts it back
+    /// when done with the content. Text chunks are each checked for UTF-8.
+    fn read_indefinite_scratch(&mut self, major: u8) -> Result<Vec<u8>> {
+        let (kind, name) = if major == MAJOR_TEXT {
+            (StringKind::Text, "text")
+        } else {
+            (StringKind::Bytes, "byte")
+        };
+        let mut buf = std::mem::take(&mut self.scratch);
+        buf.clear();
         loop {
             if self.is_break()? {
                 self.read_break()?;
                 break;
             }
             let initial = self.read_u8()?;
-            let major = initial >> 5;
-            let info = initial & 0x1f;
-            if major != MAJOR_TEXT {
-                return Err(Error::Syntax(
-                    "Indefinite text string chunks must be text strings".to_string(),
-                ));
+            self.count_item()?;
+            if initial >> 5 != major {
+                return Err(Error::Syntax(format!(
+                    "Indefinite {name} string chunks must be {name} strings"
+                )));
             }
-            let len = self.read_length(info)?.ok_or_else(|| {
-                Error::Syntax("Indefinite text string chunks cannot be indefinite".to_string())
+            let len = self.read_length(initial & 0x1f)?.ok_or_else(|| {
+                Error::Syntax(format!(
+                    "Indefinite {name} string chunks cannot be indefinite"
+                ))
             })?;
-            let chunk = self.read_text(u64_to_usize(len)?)?;
+            self.policy_check_len(kind, (buf.len() as u64).saturating_add(len))?;
+            let len = u64_to_usize(len)?;
 
             // Check cumulative size against max_allocation limit
-            let new_size = result.len().saturating_add(chunk.len());
+            let new_size = buf.len().saturating_add(len);
             if let Some(max) = self.max_allocation
                 && new_size > max
             {
                 return Err(Error::Syntax(format!(
-                    "Indefinite text string total size {} exceeds maximum {} bytes",
-                    new_size, max
+                    "Indefinite {name} string total size {new_size} exceeds maximum {max} bytes"
                 )));
             }
-
-            result.push_str(&chunk);
+            self.charge_memory(len)?;
+            buf.try_reserve(len).map_err(|_| {
+                Error::Syntax(format!("Cannot allocate {} bytes (out of memory)", len))
+            })?;
+            let start = buf.len();
+            buf.resize(new_size, 0);
+            self.reader.read_exact(&mut buf[start..])?;
+
+            if kind == StringKind::Text && std::str::from_utf8(&buf[start..]).is_err() {
+                let chunk = buf.split_off(start);
+                let text = self.text_from_utf8(chunk)?;
+                buf.extend_from_slice(text.as_bytes());
+            }
         }
-        Ok(result)
+        self.policy_check_data(kind, &buf)?;
+        Ok(buf)
     }
 
+    /// Read a tag number, the header of a tagged item
+    ///
+    /// Returns an error if the next item is not tagged. The item is left
+    /// unread in that case, so it can still be decoded.
     pub fn read_tag(&mut self) -> Result<u64> {
-        let initial = self.read_u8()?;
-        let major = initial >> 5;
-        let info = initial & 0x1f;
+        self.peek_tag()?
+            .ok_or_else(|| Error::Syntax("Expected a tag".to_string()))
+    }
 
-        if major != MAJOR_TAG {
-            return Err(Error::Syntax("Invalid CBOR value".to_string()));
+    /// Read a tag number if the next item is tagged
+    ///
+    /// Returns `Some(tag)` after consuming the tag header, leaving the tag
+    /// content next, or `None` without consuming anything. This only needs
+    /// one byte of lookahead, so it works over any reader.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::Decoder;
+    ///
+    /// // 32("a:b") followed by "c:d"
+    /// let data = [0xd8, 0x20, 0x63, b'a', b':', b'b', 0x63, b'c', b':', b'd'];
+    /// let mut decoder = Decoder::new(&data[..]);
+    /// assert_eq!(decoder.peek_tag().unwrap(), Some(32));
+    /// assert_eq!(decoder.decode::<String>().unwrap(), "a:b");
+    /// assert_eq!(decoder.peek_tag().unwrap(), None);
+    /// assert_eq!(decoder.decode::<String>().unwrap(), "c:d");
+    /// ```
+    pub fn peek_tag(&mut self) -> Result<Option<u64>> {
+        if self.peek_u8()? >> 5 != MAJOR_TAG {
+            return Ok(None);
         }
-
-        match self.read_length(info)? {
-            Some(tag) => Ok(tag),
+        match self.read_header_of(MAJOR_TAG, "a tag")?.1 {
+            Some(tag) => Ok(Some(tag)),
             None => Err(Error::Syntax("Tag cannot be indefinite".to_string())),
         }
     }
@@ -315,6 +1084,310 @@ impl<R: Read> Decoder<R> {
         T::deserialize(&mut *self)
     }
 
+    /// Decode the next item into an existing value, reusing its allocations where possible
+    ///
+    /// Uses [`Deserialize::deserialize_in_place`]. Vectors keep their
+    /// capacity and are decoded element by element into the existing
+    /// elements. Types without their own `deserialize_in_place` are simply
+    /// replaced; this includes derived structs unless serde's
+    /// `deserialize_in_place` feature is enabled. On error, `place` may be
+    /// left partly updated.
+    pub fn decode_in_place<'de, T: Deserialize<'de>>(&mut self, place: &mut T) -> Result<()> {
+        T::deserialize_in_place(&mut *self, place)
+    }
+
+    /// Decode the next item with a [`DeserializeSeed`]
+    ///
+    /// The seed carries state into deserialization, such as an interning
+    /// table or a schema chosen at run time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::Decoder;
+    /// use serde::de::{Deserialize, DeserializeSeed, Deserializer};
+    ///
+    /// // Decodes a string as its index in a shared table, adding it if new
+    /// struct Intern<'a>(&'a mut Vec<String>);
+    ///
+    /// impl<'de> DeserializeSeed<'de> for Intern<'_> {
+    ///     type Value = usize;
+    ///
+    ///     fn deserialize<D: Deserializer<'de>>(self, d: D) -> Result<usize, D::Error> {
+    ///         let s = String::deserialize(d)?;
+    ///         Ok(match self.0.iter().position(|t| *t == s) {
+    ///             Some(i) => i,
+    ///             None => {
+    ///                 self.0.push(s);
+    ///                 self.0.len() - 1
+    ///             }
+    ///         })
+    ///     }
+    /// }
+    ///
+    /// let data = [0x61, b'a', 0x61, b'b', 0x61, b'a'];
+    /// let mut decoder = Decoder::new(&data[..]);
+    /// let mut table = Vec::new();
+    /// let ids: Vec<usize> = (0..3)
+    ///     .map(|_| decoder.decode_seed(Intern(&mut table)).unwrap())
+    ///     .collect();
+    /// assert_eq!(ids, [0, 1, 0]);
+    /// assert_eq!(table, ["a", "b"]);
+    /// ```
+    pub fn decode_seed<'de, S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<S::Value> {
+        seed.deserialize(&mut *self)
+    }
+
+    /// Decode a value that must carry the tag it declares
+    ///
+    /// Reads an optional tag, decodes the content as `T` and returns an error
+    /// if the tag read differs from [`TaggedSerialize::cbor_tag`] of the result.
+    pub fn decode_tagged<'de, T: TaggedSerialize + Deserialize<'de>>(&mut self) -> Result<T> {
+        let tag = self.peek_tag()?;
+        let value: T = self.decode()?;
+        check_declared_tag(&value, tag).map_err(Error::Syntax)?;
+        Ok(value)
+    }
+
+    /// The major type of the next item, without consuming it
+    ///
+    /// Together with the `read_*` primitives below, this allows structures
+    /// defined outside serde (COSE, CWT) to be parsed item by item. Each
+    /// primitive checks the major type before consuming anything, so a
+    /// mismatch leaves the decoder where it was.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::{Decoder, MajorType};
+    ///
+    /// // [h'a10126', {4: "kid"}]
+    /// let data = [0x82, 0x43, 0xa1, 0x01, 0x26, 0xa1, 0x04, 0x63, b'k', b'i', b'd'];
+    /// let mut decoder = Decoder::new(&data[..]);
+    /// assert_eq!(decoder.read_array_header().unwrap(), Some(2));
+    /// assert_eq!(decoder.read_bytes().unwrap(), [0xa1, 0x01, 0x26]);
+    /// assert_eq!(decoder.peek_major_type().unwrap(), MajorType::Map);
+    /// assert_eq!(decoder.read_map_header().unwrap(), Some(1));
+    /// assert_eq!(decoder.read_u64().unwrap(), 4);
+    /// assert!(decoder.read_bytes().is_err());
+    /// assert_eq!(decoder.read_text().unwrap(), "kid");
+    /// decoder.end().unwrap();
+    /// ```
+    pub fn peek_major_type(&mut self) -> Result<MajorType> {
+        Ok(MajorType::from_initial_byte(self.peek_u8()?))
+    }
+
+    /// Read the header of the next item, whatever its major type
+    ///
+    /// Only the initial byte and the argument bytes are consumed; string
+    /// content, elements and tag content follow and are read separately. A
+    /// break reads as an indefinite [`MajorType::Simple`] header. See
+    /// [`Header`] for an example.
+    pub fn read_header(&mut self) -> Result<Header> {
+        let initial = self.read_u8()?;
+        let mut following = [0u8; 8];
+        let len = argument_len(initial);
+        for byte in &mut following[..len] {
+            *byte = self.read_u8()?;
+        }
+        let header = Header::from_parts(initial, &following[..len])?;
+        if header.value == Argument::Indefinite {
+            if initial == BREAK {
+                return Ok(header);
+            }
+            self.check_indefinite_allowed()?;
+        }
+        self.count_item()?;
+        Ok(header)
+    }
+
+    /// Consume the header of the next item if it has major type `major`
+    ///
+    /// Returns the additional info and the argument, `None` if indefinite.
+    fn read_header_of(&mut self, major: u8, expected: &str) -> Result<(u8, Option<u64>)> {
+        let initial = self.peek_u8()?;
+        if initial >> 5 != major {
+            return Err(Error::Syntax(format!(
+                "Expected {}, found major type {}",
+                expected,
+                initial >> 5
+            )));
+        }
+        self.read_u8()?;
+        self.count_item()?;
+        let info = initial & 0x1f;
+        Ok((info, self.read_length(info)?))
+    }
+
+    /// Read the header of an array, returning its length or `None` if indefinite
+    ///
+    /// The elements follow and are read separately; an indefinite array ends
+    /// with a break, which [`peek_major_type`](Self::peek_major_type) reports
+    /// as [`MajorType::Simple`].
+    pub fn read_array_header(&mut self) -> Result<Option<u64>> {
+        Ok(self.read_header_of(MAJOR_ARRAY, "an array")?.1)
+    }
+
+    /// Read the header of a map, returning its number of entries or `None` if indefinite
+    pub fn read_map_header(&mut self) -> Result<Option<u64>> {
+        Ok(self.read_header_of(MAJOR_MAP, "a map")?.1)
+    }
+
+    /// Read a byte string, joining the chunks of an indefinite-length one
+    pub fn read_bytes(&mut self) -> Result<Vec<u8>> {
+        match self.read_header_of(MAJOR_BYTES, "a byte string")?.1 {
+            Some(len) => self.read_byte_string(len),
+            None => self.read_indefinite_bytes(),
+        }
+    }
+
+    /// Read a byte string through [`std::io::Read`] instead of into memory
+    ///
+    /// Works for definite and indefinite-length (chunked) byte strings; the
+    /// chunks are joined seamlessly. The reader reaches end of file at the
+    /// end of the byte string, after which the decoder is positioned at the
+    /// next item. If the reader is dropped early, the rest of the string is
+    /// left unread and the decoder cannot continue.
+    ///
+    /// A [`StringPolicy`] sees the running length of the string before each
+    /// chunk, but never the complete content.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use c2pa_cbor::Decoder;
+    ///
+    /// // (_ h'0102', h'03') followed by 7
+    /// let data = [0x5f, 0x42, 0x01, 0x02, 0x41, 0x03, 0xff, 0x07];
+    /// let mut decoder = Decoder::new(&data[..]);
+    /// let mut content = Vec::new();
+    /// decoder.bytes_reader().unwrap().read_to_end(&mut content).unwrap();
+    /// assert_eq!(content, [1, 2, 3]);
+    /// assert_eq!(decoder.decode::<u8>().unwrap(), 7);
+    /// ```
+    pub fn bytes_reader(&mut self) -> Result<ByteStringReader<'_, R>> {
+        let len = self.read_header_of(MAJOR_BYTES, "a byte string")?.1;
+        if let Some(len) = len {
+            self.policy_check_len(StringKind::Bytes, len)?;
+        }
+        Ok(ByteStringReader {
+            decoder: self,
+            remaining: len.unwrap_or(0),
+            total: len.unwrap_or(0),
+            indefinite: len.is_none(),
+            done: false,
+        })
+    }
+
+    /// Read a text string, joining the chunks of an indefinite-length one
+    pub fn read_text(&mut self) -> Result<String> {
+        match self.read_header_of(MAJOR_TEXT, "a text string")?.1 {
+            Some(len) => self.read_text_string(len),
+            None => self.read_indefinite_text(),
+        }
+    }
+
+    /// Read an unsigned integer
+    pub fn read_u64(&mut self) -> Result<u64> {
+        self.read_header_of(MAJOR_UNSIGNED, "an unsigned integer")?
+            .1
+            .ok_or_else(|| Error::Syntax("Unexpected indefinite length".to_string()))
+    }
+
+    /// Read an integer of either sign that fits in an `i64`
+    pub fn read_i64(&mut self) -> Result<i64> {
+        let major = match self.peek_u8()? >> 5 {
+            MAJOR_NEGATIVE => MAJOR_NEGATIVE,
+            _ => MAJOR_UNSIGNED,
+        };
+        let n = self
+            .read_header_of(major, "an integer")?
+            .1
+            .ok_or_else(|| Error::Syntax("Unexpected indefinite length".to_string()))?;
+        let value = if major == MAJOR_NEGATIVE {
+            -1 - n as i128
+        } else {
+            n as i128
+        };
+        i64::try_from(value).map_err(|_| Error::IntegerOverflow(value))
+    }
+
+    /// Read a half, single or double precision float as an `f64`
+    ///
+    /// Integers are not converted; they are an error like any other type.
+    pub fn read_f64(&mut self) -> Result<f64> {
+        let initial = self.peek_u8()?;
+        let info = initial & 0x1f;
+        if initial >> 5 != MAJOR_SIMPLE || !matches!(info, FLOAT16 | FLOAT32 | FLOAT64) {
+            return Err(Error::Syntax("Expected a float".to_string()));
+        }
+        self.read_u8()?;
+        self.count_item()?;
+        let value = match info {
+            FLOAT16 => half::f16::from_bits(self.read_be_u16()?).to_f64(),
+            FLOAT32 => f32::from_bits(self.read_be_u32()?) as f64,
+            _ => f64::from_bits(self.read_be_u64()?),
+        };
+        self.check_float(value)?;
+        Ok(value)
+    }
+
+    /// Read `true` or `false`
+    pub fn read_bool(&mut self) -> Result<bool> {
+        let value = match self.peek_u8()? {
+            initial if initial == (MAJOR_SIMPLE << 5) | FALSE => false,
+            initial if initial == (MAJOR_SIMPLE << 5) | TRUE => true,
+            _ => return Err(Error::Syntax("Expected a boolean".to_string())),
+        };
+        self.read_u8()?;
+        self.count_item()?;
+        Ok(value)
+    }
+
+    /// Read `null`
+    pub fn read_null(&mut self) -> Result<()> {
+        if self.peek_u8()? != (MAJOR_SIMPLE << 5) | NULL {
+            return Err(Error::Syntax("Expected null".to_string()));
+        }
+        self.read_u8()?;
+        self.count_item()?;
+        Ok(())
+    }
+
+    /// Read the byte following a 2-byte simple value header
+    fn read_simple_value(&mut self) -> Result<u8> {
+        let value = self.read_u8()?;
+        if value < 32 && self.options.strict_simple_values {
+            return Err(Error::Syntax(format!(
+                "Simple value {value} not allowed in the 2-byte form"
+            )));
+        }
+        Ok(value)
+    }
+
+    /// Check that the reader has no input left after the decoded item
+    ///
+    /// Returns [`Error::TrailingData`] if another byte can be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::Decoder;
+    ///
+    /// let mut decoder = Decoder::new(&[0x01, 0x02][..]);
+    /// assert_eq!(decoder.decode::<u8>().unwrap(), 1);
+    /// assert!(decoder.end().is_err());
+    /// ```
+    pub fn end(&mut self) -> Result<()> {
+        match self.peek_u8() {
+            Ok(_) => Err(Error::TrailingData),
+            Err(Error::Io(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Shared core deserialization logic used by both by-value and by-reference implementations
     #[inline]
     fn deserialize_any_impl<'de, V: serde::de::Visitor<'de>>(
@@ -322,35 +1395,70 @@ impl<R: Read> Decoder<R> {
         visitor: V,
     ) -> Result<V::Value> {
         let initial = self.read_u8()?;
+        self.count_item()?;
         let major = initial >> 5;
         let info = initial & 0x1f;
+        // Only the outermost item of a map key names the path segment
+        let capture_key = std::mem::take(&mut self.capture_key);
+        let expect_uri = std::mem::take(&mut self.expect_uri);
+        if expect_uri && major != MAJOR_TEXT {
+            return Err(Error::Syntax(
+                "Tag 32 (URI) must enclose a text string".to_string(),
+            ));
+        }
 
         match major {
             MAJOR_UNSIGNED => {
                 let val = self.read_length(info)?.ok_or_else(|| {
                     Error::Syntax("Unsigned integer cannot be indefinite".to_string())
                 })?;
+                if capture_key {
+                    self.captured_key = Some(PathSegment::IntKey(val as i128));
+                }
                 visitor.visit_u64(val)
             }
             MAJOR_NEGATIVE => {
                 let val = self.read_length(info)?.ok_or_else(|| {
                     Error::Syntax("Negative integer cannot be indefinite".to_string())
                 })?;
-                visitor.visit_i64(-1 - val as i64)
+                let value = -1 - val as i128;
+                if capture_key {
+                    self.captured_key = Some(PathSegment::IntKey(value));
+                }
+                // Magnitudes of 2^63 and up are legal CBOR but only fit in an i128
+                match i64::try_from(value) {
+                    Ok(value) => visitor.visit_i64(value),
+                    Err(_) => visitor.visit_i128(value),
+                }
             }
             MAJOR_BYTES => match self.read_length(info)? {
                 Some(len) => {
-                    let buf = self.read_bytes(u64_to_usize(len)?)?;
+                    let buf = self.read_byte_string(len)?;
                     visitor.visit_byte_buf(buf)
                 }
-                None => visitor.visit_byte_buf(self.read_indefinite_bytes()?),
+                None => {
+                    let buf = self.read_indefinite_scratch(MAJOR_BYTES)?;
+                    let value = visitor.visit_bytes(&buf);
+                    self.scratch = buf;
+                    value
+                }
             },
             MAJOR_TEXT => match self.read_length(info)? {
                 Some(len) => {
-                    let s = self.read_text(u64_to_usize(len)?)?;
+                    let s = self.read_text_string(len)?;
+                    self.check_text(&s, expect_uri, capture_key)?;
                     visitor.visit_string(s)
                 }
-                None => visitor.visit_string(self.read_indefinite_text()?),
+                None => {
+                    let buf = self.read_indefinite_scratch(MAJOR_TEXT)?;
+                    // Every chunk was checked or replaced, so this does not fail
+                    let s = std::str::from_utf8(&buf).map_err(|_| Error::InvalidUtf8)?;
+                    let value = self
+                        .check_text(s, expect_uri, capture_key)
+                        .and_then(|()| visitor.visit_str(s));
+                    self.scratch = buf;
+                    value
+                }
             },
             MAJOR_ARRAY => {
                 self.check_recursion_depth()?;
@@ -359,10 +1467,12 @@ impl<R: Read> Decoder<R> {
                     Some(len) => visitor.visit_seq(SeqAccess {
                         de: self,
                         remaining: Some(u64_to_usize(len)?),
+                        index: 0,
                     }),
                     None => visitor.visit_seq(SeqAccess {
                         de: self,
                         remaining: None,
+                        index: 0,
                     }),
                 }
                 // Note: recursion_depth is decremented in SeqAccess::drop
@@ -370,16 +1480,13 @@ impl<R: Read> Decoder<R> {
             MAJOR_MAP => {
                 self.check_recursion_depth()?;
                 self.recursion_depth += 1;
-                match self.read_length(info)? {
-                    Some(len) => visitor.visit_map(MapAccess {
+                let remaining = self.read_length(info)?.map(u64_to_usize).transpose()?;
+                crate::value::visit_map_ordered(self.options.ordered_maps, || {
+                    visitor.visit_map(MapAccess {
                         de: self,
-                        remaining: Some(u64_to_usize(len)?),
-                    }),
-                    None => visitor.visit_map(MapAccess {
-                        de: self,
-                        remaining: None,
-                    }),
-                }
+                        remaining,
+                    })
+                })
                 // Note: recursion_depth is decremented in MapAccess::drop
             }
             MAJOR_TAG => {
@@ -388,7 +1495,7 @@ impl<R: Read> Decoder<R> {
                     .read_length(info)?
                     .ok_or_else(|| Error::Syntax("Tag cannot be indefinite".to_string()))?;
                 // Store the tag
-                self.current_tag = Some(tag);
+                let outer_tag = self.enter_tag(tag);
 
                 // For maximum compatibility: try visit_map first (for Tagged<T>),
                 // and if that fails, fall back to transparent pass-through (for String, i64, etc.)
@@ -398,31 +1505,44 @@ impl<R: Read> Decoder<R> {
                     visitor,
                 );
 
-                // Clear the tag after deserialization
-                self.current_tag = None;
+                // Restore the enclosing tag after deserialization
+                set_tag(outer_tag);
                 result
             }
             MAJOR_SIMPLE => match info {
                 FALSE => visitor.visit_bool(false),
                 TRUE => visitor.visit_bool(true),
                 NULL => visitor.visit_none(),
-                UNDEFINED => visitor.visit_unit(),
+                UNDEFINED if self.options.strict_undefined => {
+                    Err(Error::Syntax("Unexpected undefined".to_string()))
+                }
+                UNDEFINED => crate::value::visit_undefined(visitor),
+                0..FALSE => crate::value::visit_simple(visitor, info),
+                SIMPLE_VALUE => {
+                    let value = self.read_simple_value()?;
+                    crate::value::visit_simple(visitor, value)
+                }
                 FLOAT16 => {
                     let mut buf = [0u8; 2];
                     self.reader.read_exact(&mut buf)?;
                     // Requires the `half` crate or wait for f16 to be stabilized
                     let f16_value = half::f16::from_be_bytes(buf);
+                    self.check_float(f16_value.to_f64())?;
                     visitor.visit_f32(f16_value.to_f32())
                 }
                 FLOAT32 => {
                     let mut buf = [0u8; 4];
                     self.reader.read_exact(&mut buf)?;
-                    visitor.visit_f32(f32::from_be_bytes(buf))
+                    let value = f32::from_be_bytes(buf);
+                    self.check_float(value as f64)?;
+                    visitor.visit_f32(value)
                 }
                 FLOAT64 => {
                     let mut buf = [0u8; 8];
                     self.reader.read_exact(&mut buf)?;
-                    visitor.visit_f64(f64::from_be_bytes(buf))
+                    let value = f64::from_be_bytes(buf);
+                    self.check_float(value)?;
+                    visitor.visit_f64(value)
                 }
                 _ => Err(Error::Syntax("Invalid CBOR value".to_string())),
             },
@@ -430,6 +1550,140 @@ impl<R: Read> Decoder<R> {
         }
     }
 
+    /// Deserialize into a numeric type, applying the number coercion mode
+    fn deserialize_number_impl<'de, V: serde::de::Visitor<'de>>(
+        &mut self,
+        visitor: V,
+        target: NumberTarget,
+    ) -> Result<V::Value> {
+        if self.options.number_coercion == NumberCoercion::None || self.expect_uri {
+            return self.deserialize_any_impl(visitor);
+        }
+        let initial = self.peek_u8()?;
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        let is_float = major == MAJOR_SIMPLE && matches!(info, FLOAT16 | FLOAT32 | FLOAT64);
+        let is_integer = major == MAJOR_UNSIGNED || major == MAJOR_NEGATIVE;
+        let strict = self.options.number_coercion == NumberCoercion::Strict;
+
+        if target == NumberTarget::Integer && is_float {
+            self.capture_key = false;
+            let value = self.read_f64()?;
+            if !value.is_finite() || (strict && value.fract() != 0.0) {
+                return Err(Error::Syntax(format!(
+                    "Float {value} can't be read as an integer"
+                )));
+            }
+            let value = value.trunc();
+            // 2^64 and -2^63 are exact in f64; `as` saturates past them
+            return if (0.0..18446744073709551616.0).contains(&value) {
+                visitor.visit_u64(value as u64)
+            } else if (-9223372036854775808.0..0.0).contains(&value) {
+                visitor.visit_i64(value as i64)
+            } else {
+                Err(Error::Syntax(format!("Float {value} out of integer range")))
+            };
+        }
+
+        if target != NumberTarget::Integer && is_integer {
+            self.read_u8()?;
+            self.count_item()?;
+            let n = self
+                .read_length(info)?
+                .ok_or_else(|| Error::Syntax("Integer cannot be indefinite".to_string()))?
+                as i128;
+            let value = if major == MAJOR_NEGATIVE { -1 - n } else { n };
+            if std::mem::take(&mut self.capture_key) {
+                self.captured_key = Some(PathSegment::IntKey(value));
+            }
+            return if target == NumberTarget::F32 {
+                let float = value as f32;
+                if strict && float as i128 != value {
+                    return Err(Error::Syntax(format!(
+                        "Integer {value} is not exact as f32"
+                    )));
+                }
+                visitor.visit_f32(float)
+            } else {
+                let float = value as f64;
+                if strict && float as i128 != value {
+                    return Err(Error::Syntax(format!(
+                        "Integer {value} is not exact as f64"
+                    )));
+                }
+                visitor.visit_f64(float)
+            };
+        }
+
+        self.deserialize_any_impl(visitor)
+    }
+
+    /// Present an indefinite-length string as a sequence of its chunks
+    ///
+    /// Anything else (including definite-length strings) is deserialized normally.
+    fn deserialize_chunked_impl<'de, V: serde::de::Visitor<'de>>(
+        &mut self,
+        visitor: V,
+    ) -> Result<V::Value> {
+        let initial = self.peek_u8()?;
+        let major = initial >> 5;
+        if self.expect_uri
+            || (major != MAJOR_BYTES && major != MAJOR_TEXT)
+            || initial & 0x1f != INDEFINITE
+        {
+            return self.deserialize_any_impl(visitor);
+        }
+        self.check_indefinite_allowed()?;
+        self.read_u8()?;
+        self.count_item()?;
+
+        let kind = if major == MAJOR_TEXT {
+            StringKind::Text
+        } else {
+            StringKind::Bytes
+        };
+        let mut chunks = Vec::new();
+        let mut total = 0usize;
+        while !self.is_break()? {
+            let initial = self.read_u8()?;
+            self.count_item()?;
+            if initial >> 5 != major {
+                return Err(Error::Syntax(
+                    "Indefinite string chunks must match the string type".to_string(),
+                ));
+            }
+            let len = self.read_length(initial & 0x1f)?.ok_or_else(|| {
+                Error::Syntax("Indefinite string chunks cannot be indefinite".to_string())
+            })?;
+            self.policy_check_len(kind, (total as u64).saturating_add(len))?;
+            let mut chunk = self.read_exact_bytes(u64_to_usize(len)?)?;
+            if kind == StringKind::Text {
+                // Each chunk of a text string must be valid UTF-8 on its own
+                chunk = self.text_from_utf8(chunk)?.into_bytes();
+            }
+
+            // Check cumulative size against max_allocation limit
+            total = total.saturating_add(chunk.len());
+            if let Some(max) = self.max_allocation
+                && total > max
+            {
+                return Err(Error::Syntax(format!(
+                    "Indefinite string total size {} exceeds maximum {} bytes",
+                    total, max
+                )));
+            }
+            chunks.push(chunk);
+        }
+        self.read_break()?;
+        if self.policy.is_some() {
+            self.policy_check_data(kind, &chunks.concat())?;
+        }
+
+        visitor.visit_seq(ChunkAccess {
+            chunks: chunks.into_iter(),
+            text: major == MAJOR_TEXT,
+        })
+    }
+
     /// Shared enum deserialization logic used by both by-value and by-reference implementations
     #[inline]
     fn deserialize_enum_impl<'de, V: serde::de::Visitor<'de>>(
@@ -437,6 +1691,7 @@ impl<R: Read> Decoder<R> {
         visitor: V,
     ) -> Result<V::Value> {
         let initial = self.read_u8()?;
+        self.count_item()?;
         let major = initial >> 5;
         let info = initial & 0x1f;
 
@@ -446,9 +1701,23 @@ impl<R: Read> Decoder<R> {
                 let len = self.read_length(info)?.ok_or_else(|| {
                     Error::Syntax("Enum variant cannot be indefinite length".to_string())
                 })?;
-                let s = self.read_text(u64_to_usize(len)?)?;
+                let s = self.read_text_string(len)?;
                 visitor.visit_enum(UnitVariantAccess { variant: s })
             }
+            MAJOR_UNSIGNED => {
+                // Unit variant encoded as an integer key
+                let n = self
+                    .read_length(info)?
+                    .ok_or_else(|| Error::Syntax("Invalid CBOR value".to_string()))?;
+                match self.options.variant_keys {
+                    VariantKeys::NumericName => visitor.visit_enum(UnitVariantAccess {
+                        variant: n.to_string(),
+                    }),
+                    VariantKeys::Name | VariantKeys::Index => {
+                        visitor.visit_enum(UnitVariantAccess { variant: n })
+                    }
+                }
+            }
             MAJOR_MAP => {
                 // Variant with data encoded as {"variant": data}
                 let len = self.read_length(info)?;
@@ -462,6 +1731,406 @@ impl<R: Read> Decoder<R> {
             _ => Err(Error::Syntax("Invalid CBOR type for enum".to_string())),
         }
     }
+
+    /// Skip a value that the caller ignores, such as an unknown struct field
+    fn deserialize_ignored_impl<'de, V: serde::de::Visitor<'de>>(
+        &mut self,
+        visitor: V,
+    ) -> Result<V::Value> {
+        // Policies see every string, so ignored values still take the full path
+        if self.policy.is_some() || self.uri_policy.is_some() {
+            return self.deserialize_any_impl(visitor);
+        }
+        self.skip()?;
+        visitor.visit_unit()
+    }
+
+    /// Depth check for the iterative paths, which may run inside a recursive decode
+    fn check_value_depth(&self, depth: usize) -> Result<()> {
+        let depth = self.recursion_depth + depth;
+        if depth >= self.max_recursion_depth {
+            return Err(Error::Syntax(format!(
+                "CBOR nesting depth {} exceeds maximum {}",
+                depth, self.max_recursion_depth
+            )));
+        }
+        Ok(())
+    }
+
+    /// Decode a [`Value`](crate::Value) without recursion
+    ///
+    /// Produces the same result as `decode::<Value>()`, but nesting is tracked
+    /// on the heap, so the depth set with [`with_max_depth`](Self::with_max_depth)
+    /// can be raised far beyond what the thread stack would allow. All other
+    /// limits apply as usual. While a [`StringPolicy`] is installed this falls
+    /// back to the recursive path, which tracks string locations.
+    ///
+    /// Dropping a `Value` still recurses, but uses far less stack per level
+    /// than decoding does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::Decoder;
+    ///
+    /// let mut data = vec![0x81; 10_000];
+    /// data.push(0x00);
+    /// let mut decoder = Decoder::new(&data[..]).with_max_depth(20_000);
+    /// assert!(decoder.decode_value().unwrap().is_array());
+    /// ```
+    pub fn decode_value(&mut self) -> Result<crate::Value> {
+        use crate::Value;
+
+        if self.policy.is_some() {
+            return self.decode();
+        }
+
+        let mut stack: Vec<ValueFrame> = Vec::new();
+        let mut depth = 0;
+        loop {
+            // Close a finished container, or account for the item about to be read
+            let closed = match stack.last_mut() {
+                Some(ValueFrame::Array { remaining, .. })
+                | Some(ValueFrame::Map {
+                    remaining,
+                    key: None,
+                    ..
+                }) => match remaining {
+                    Some(0) => true,
+                    Some(n) => {
+                        *n -= 1;
+                        false
+                    }
+                    None if self.is_break()? => {
+                        self.read_break()?;
+                        true
+                    }
+                    None => false,
+                },
+                Some(ValueFrame::Map {
+                    remaining: None, ..
+                }) if self.is_break()? => {
+                    return Err(Error::Syntax("Map key without a value".to_string()));
+                }
+                _ => false,
+            };
+
+            let value = if closed {
+                depth -= 1;
+                match stack.pop() {
+                    Some(ValueFrame::Array { items, .. }) => Value::Array(items),
+                    Some(ValueFrame::Map { entries, .. }) if self.options.ordered_maps => {
+                        Value::OrderedMap(entries)
+                    }
+                    Some(ValueFrame::Map { entries, .. }) => {
+                        Value::Map(entries.into_iter().collect())
+                    }
+                    _ => unreachable!("only containers are closed"),
+                }
+            } else {
+                let expect_uri = matches!(stack.last(), Some(ValueFrame::Tag { uri: true }));
+                let initial = self.read_u8()?;
+                self.count_item()?;
+                let major = initial >> 5;
+                let info = initial & 0x1f;
+                if expect_uri && major != MAJOR_TEXT {
+                    return Err(Error::Syntax(
+                        "Tag 32 (URI) must enclose a text string".to_string(),
+                    ));
+                }
+                match major {
+                    MAJOR_UNSIGNED => {
+                        let val = self.read_length(info)?.ok_or_else(|| {
+                            Error::Syntax("Unsigned integer cannot be indefinite".to_string())
+                        })?;
+                        let val =
+                            i64::try_from(val).map_err(|_| Error::IntegerOverflow(val as i128))?;
+                        Value::Integer(val)
+                    }
+                    MAJOR_NEGATIVE => {
+                        let val = self.read_length(info)?.ok_or_else(|| {
+                            Error::Syntax("Negative integer cannot be indefinite".to_string())
+                        })?;
+                        let val = -1 - val as i128;
+                        Value::Integer(i64::try_from(val).map_err(|_| Error::IntegerOverflow(val))?)
+                    }
+                    MAJOR_BYTES => Value::Bytes(match self.read_length(info)? {
+                        Some(len) => self.read_byte_string(len)?,
+                        None => self.read_indefinite_bytes()?,
+                    }),
+                    MAJOR_TEXT => Value::Text(match self.read_length(info)? {
+                        Some(len) => self.read_text_string(len)?,
+                        None => self.read_indefinite_text()?,
+                    }),
+                    MAJOR_ARRAY | MAJOR_MAP => {
+                        self.check_value_depth(depth)?;
+                        depth += 1;
+                        let remaining = self.read_length(info)?.map(u64_to_usize).transpose()?;
+                        stack.push(if major == MAJOR_ARRAY {
+                            ValueFrame::Array {
+                                items: Vec::with_capacity(remaining.unwrap_or(0).min(4096)),
+                                remaining,
+                            }
+                        } else {
+                            ValueFrame::Map {
+                                entries: Vec::with_capacity(remaining.unwrap_or(0).min(4096)),
+                                key: None,
+                                remaining,
+                            }
+                        });
+                        continue;
+                    }
+                    MAJOR_TAG => {
+                        let tag = self
+                            .read_length(info)?
+                            .ok_or_else(|| Error::Syntax("Tag cannot be indefinite".to_string()))?;
+                        stack.push(ValueFrame::Tag {
+                            uri: tag == TAG_URI && self.uri_policy.is_some(),
+                        });
+                        continue;
+                    }
+                    _ => match info {
+                        FALSE => Value::Bool(false),
+                        TRUE => Value::Bool(true),
+                        NULL => Value::Null,
+                        UNDEFINED if self.options.strict_undefined => {
+                            return Err(Error::Syntax("Unexpected undefined".to_string()));
+                        }
+                        UNDEFINED => Value::Undefined,
+                        0..FALSE => Value::Simple(info),
+                        SIMPLE_VALUE => Value::Simple(self.read_simple_value()?),
+                        FLOAT16 | FLOAT32 | FLOAT64 => {
+                            let value = match info {
+                                FLOAT16 => half::f16::from_bits(self.read_be_u16()?).to_f64(),
+                                FLOAT32 => f32::from_bits(self.read_be_u32()?) as f64,
+                                _ => f64::from_bits(self.read_be_u64()?),
+                            };
+                            self.check_float(value)?;
+                            Value::Float(value)
+                        }
+                        _ => return Err(Error::Syntax("Invalid CBOR value".to_string())),
+                    },
+                }
+            };
+
+            // Hand the finished value to its parent; tags are transparent, as in
+            // the Deserialize impl for Value
+            loop {
+                match stack.last_mut() {
+                    None => return Ok(value),
+                    Some(ValueFrame::Tag { uri }) => {
+                        if *uri && let (Some(policy), Value::Text(s)) = (&self.uri_policy, &value) {
+                            policy.check(s)?;
+                        }
+                        stack.pop();
+                    }
+                    Some(ValueFrame::Array { items, .. }) => {
+                        items.push(value);
+                        break;
+                    }
+                    Some(ValueFrame::Map { entries, key, .. }) => {
+                        match key.take() {
+                            Some(k) => entries.push((k, value)),
+                            None => *key = Some(value),
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Skip over one data item without building it, and without recursion
+    ///
+    /// String contents are discarded unread, so text is not checked for valid
+    /// UTF-8. The depth and item limits apply as they do when decoding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::Decoder;
+    ///
+    /// // [[1, "ab"], {}] followed by 7
+    /// let data = [0x82, 0x82, 0x01, 0x62, b'a', b'b', 0xa0, 0x07];
+    /// let mut decoder = Decoder::new(&data[..]);
+    /// decoder.skip().unwrap();
+    /// assert_eq!(decoder.decode::<u8>().unwrap(), 7);
+    /// ```
+    pub fn skip(&mut self) -> Result<()> {
+        // Items left in each open container; None until a break
+        let mut stack: Vec<Option<u64>> = Vec::new();
+        // Set after a tag, whose content stands in for it in the parent
+        let mut tagged = false;
+        loop {
+            if !tagged {
+                match stack.last_mut() {
+                    Some(Some(0)) => {
+                        stack.pop();
+                        if stack.is_empty() {
+                            return Ok(());
+                        }
+                        continue;
+                    }
+                    Some(Some(n)) => *n -= 1,
+                    Some(None) if self.is_break()? => {
+                        self.read_break()?;
+                        stack.pop();
+                        if stack.is_empty() {
+                            return Ok(());
+                        }
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+
+            let initial = self.read_u8()?;
+            self.count_item()?;
+            tagged = false;
+            let major = initial >> 5;
+            let info = initial & 0x1f;
+            if major == MAJOR_SIMPLE {
+                // Float bytes are consumed as the header argument
+                match info {
+                    SIMPLE_VALUE => Some(self.read_simple_value()?.into()),
+                    0..=UNDEFINED | FLOAT16 | FLOAT32 | FLOAT64 => self.read_length(info)?,
+                    _ => return Err(Error::Syntax("Invalid CBOR value".to_string())),
+                };
+            } else {
+                match (major, self.read_length(info)?) {
+                    (MAJOR_BYTES | MAJOR_TEXT, Some(len)) => self.skip_bytes(len)?,
+                    (MAJOR_BYTES | MAJOR_TEXT, None) => {
+                        while !self.is_break()? {
+                            let initial = self.read_u8()?;
+                            self.count_item()?;
+                            if initial >> 5 != major {
+                                return Err(Error::Syntax(
+                                    "Indefinite string chunks must match the string type"
+                                        .to_string(),
+                                ));
+                            }
+                            let len = self.read_length(initial & 0x1f)?.ok_or_else(|| {
+                                Error::Syntax(
+                                    "Indefinite string chunks cannot be indefinite".to_string(),
+                                )
+                            })?;
+                            self.skip_bytes(len)?;
+                        }
+                        self.read_break()?;
+                    }
+                    (MAJOR_ARRAY, len) => {
+                        self.check_value_depth(stack.len())?;
+                        stack.push(len);
+                        continue;
+                    }
+                    (MAJOR_MAP, len) => {
+                        self.check_value_depth(stack.len())?;
+                        stack.push(len.map(|n| n.saturating_mul(2)));
+                        continue;
+                    }
+                    (MAJOR_TAG, Some(_)) => {
+                        tagged = true;
+                        continue;
+                    }
+                    (_, None) => {
+                        return Err(Error::Syntax("Unexpected indefinite length".to_string()));
+                    }
+                    _ => {}
+                }
+            }
+            if stack.is_empty() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_bytes(&mut self, len: u64) -> Result<()> {
+        let skipped = std::io::copy(&mut (&mut self.reader).take(len), &mut std::io::sink())?;
+        if skipped < len {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(())
+    }
+}
+
+/// Streaming reader over a byte string, returned by [`Decoder::bytes_reader`]
+pub struct ByteStringReader<'a, R: Read> {
+    decoder: &'a mut Decoder<R>,
+    // Bytes left in the current chunk (or the whole definite string)
+    remaining: u64,
+    // Length of the string read or announced so far
+    total: u64,
+    indefinite: bool,
+    done: bool,
+}
+
+impl<R: Read> ByteStringReader<'_, R> {
+    /// Move to the next chunk of an indefinite string, or finish at the break
+    fn next_chunk(&mut self) -> Result<()> {
+        if !self.indefinite || self.decoder.is_break()? {
+            if self.indefinite {
+                self.decoder.read_break()?;
+            }
+            self.done = true;
+            return Ok(());
+        }
+        let initial = self.decoder.read_u8()?;
+        self.decoder.count_item()?;
+        if initial >> 5 != MAJOR_BYTES {
+            return Err(Error::Syntax(
+                "Indefinite byte string chunks must be byte strings".to_string(),
+            ));
+        }
+        let len = self.decoder.read_length(initial & 0x1f)?.ok_or_else(|| {
+            Error::Syntax("Indefinite byte string chunks cannot be indefinite".to_string())
+        })?;
+        self.total = self.total.saturating_add(len);
+        self.decoder
+            .policy_check_len(StringKind::Bytes, self.total)?;
+        self.remaining = len;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for ByteStringReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.remaining == 0 {
+            if self.done || buf.is_empty() {
+                return Ok(0);
+            }
+            self.next_chunk().map_err(|e| match e {
+                Error::Io(e) => e,
+                e => std::io::Error::new(std::io::ErrorKind::InvalidData, e),
+            })?;
+        }
+        let want = buf
+            .len()
+            .min(usize::try_from(self.remaining).unwrap_or(usize::MAX));
+        let n = self.decoder.reader.read(&mut buf[..want])?;
+        if n == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+// Open item while decoding a Value iteratively
+enum ValueFrame {
+    Array {
+        items: Vec<crate::Value>,
+        remaining: Option<usize>,
+    },
+    // Entries in wire order, sorted into a map when it closes
+    Map {
+        entries: Vec<(crate::Value, crate::Value)>,
+        key: Option<crate::Value>,
+        remaining: Option<usize>,
+    },
+    // Tags are read transparently; `uri` arms the tag 32 check
+    Tag {
+        uri: bool,
+    },
 }
 
 impl<'de> Decoder<&'de [u8]> {
@@ -469,96 +2138,149 @@ impl<'de> Decoder<&'de [u8]> {
     pub fn from_slice(input: &'de [u8]) -> Self {
         Decoder::new(input)
     }
+
+    /// Read a typed array, borrowing its elements from the input when possible
+    ///
+    /// The elements are borrowed when the array is a definite-length byte
+    /// string in the target's byte order whose content is suitably aligned
+    /// for `T`. Otherwise they are converted into an owned `Vec`, with the
+    /// same checks as [`decode_typed_array`](crate::decode_typed_array).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::{Decoder, TypedArray};
+    ///
+    /// let cbor = c2pa_cbor::to_vec(&TypedArray::new(vec![1.5f32, 2.5])).unwrap();
+    /// let mut decoder = Decoder::from_slice(&cbor);
+    /// let floats = decoder.as_typed_slice::<f32>().unwrap();
+    /// assert_eq!(&floats[..], [1.5, 2.5]);
+    /// ```
+    #[cfg(feature = "zero_copy")]
+    pub fn as_typed_slice<T>(&mut self) -> Result<crate::typed_array::TypedArrayRef<'de, T>>
+    where
+        T: crate::TypedArrayElement + bytemuck::Pod,
+    {
+        use crate::typed_array::{PackedBytes, TypedArrayRef, elements_from_bytes};
+
+        let tag = self.read_tag()?;
+        let little_endian = T::byte_order(tag).ok_or_else(|| {
+            Error::Syntax(format!(
+                "Tag {} is not a typed array of {}",
+                tag,
+                std::any::type_name::<T>()
+            ))
+        })?;
+
+        let initial = self.peek_u8()?;
+        if initial >> 5 != MAJOR_BYTES || initial & 0x1f == INDEFINITE {
+            // Chunked or written as an array of byte values
+            let packed: PackedBytes = self.decode()?;
+            return Ok(TypedArrayRef::Owned(elements_from_bytes(
+                &packed.0,
+                little_endian,
+            )?));
+        }
+
+        self.read_u8()?;
+        self.count_item()?;
+        let len = match self.read_length(initial & 0x1f)? {
+            Some(len) => u64_to_usize(len)?,
+            None => unreachable!(),
+        };
+        let input: &'de [u8] = self.reader;
+        if len > input.len() {
+            return Err(Error::Eof);
+        }
+        let (content, rest) = input.split_at(len);
+        self.reader = rest;
+
+        let native = little_endian == cfg!(target_endian = "little") || T::SIZE == 1;
+        if native
+            && len % T::SIZE == 0
+            && let Ok(elements) = bytemuck::try_cast_slice(content)
+        {
+            return Ok(TypedArrayRef::Borrowed(elements));
+        }
+        Ok(TypedArrayRef::Owned(elements_from_bytes(
+            content,
+            little_endian,
+        )?))
+    }
 }
 
+// Numeric types go through the number coercion mode
+macro_rules! deserialize_numbers {
+    ($($($method:ident)* => $target:expr);*) => {
+        $($(
+            fn $method<V: serde::de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                self.deserialize_number_impl(visitor, $target)
+            }
+        )*)*
+    };
+}
+
+macro_rules! delegate_numbers {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: serde::de::Visitor<'de>>(mut self, visitor: V) -> Result<V::Value> {
+                serde::Deserializer::$method(&mut self, visitor)
+            }
+        )*
+    };
+}
+
+// The owned decoder shares every decoding path with `&mut Decoder`, so a
+// value decodes the same whether it is top level, in an Option or a newtype
 impl<'de, R: Read> serde::Deserializer<'de> for Decoder<R> {
     type Error = crate::Error;
 
     serde::forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string
-        bytes byte_buf unit unit_struct newtype_struct seq tuple
-        tuple_struct struct identifier ignored_any
+        bool i128 u128 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct struct identifier
+    }
+
+    delegate_numbers! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_f32 deserialize_f64
     }
 
     fn deserialize_option<V: serde::de::Visitor<'de>>(mut self, visitor: V) -> Result<V::Value> {
-        // Peek at next byte to check for null
-        let initial = self.read_u8()?;
-        if initial == 0xf6 {
-            // CBOR null
-            visitor.visit_none()
-        } else {
-            // Not null - process as Some(...)
-            let major = initial >> 5;
-            let info = initial & 0x1f;
+        serde::Deserializer::deserialize_option(&mut self, visitor)
+    }
 
-            // Handle the value based on major type
-            match major {
-                MAJOR_MAP => match self.read_length(info)? {
-                    Some(len) => visitor.visit_some(MapDeserializer {
-                        de: &mut self,
-                        remaining: Some(u64_to_usize(len)?),
-                    }),
-                    None => visitor.visit_some(MapDeserializer {
-                        de: &mut self,
-                        remaining: None,
-                    }),
-                },
-                MAJOR_ARRAY => match self.read_length(info)? {
-                    Some(len) => visitor.visit_some(ArrayDeserializer {
-                        de: &mut self,
-                        remaining: Some(u64_to_usize(len)?),
-                    }),
-                    None => visitor.visit_some(ArrayDeserializer {
-                        de: &mut self,
-                        remaining: None,
-                    }),
-                },
-                _ => {
-                    // For simple types, deserialize directly
-                    visitor.visit_some(PrefetchedDeserializer {
-                        de: &mut self,
-                        major,
-                        info,
-                    })
-                }
-            }
-        }
+    fn deserialize_any<V: serde::de::Visitor<'de>>(mut self, visitor: V) -> Result<V::Value> {
+        serde::Deserializer::deserialize_any(&mut self, visitor)
+    }
+
+    fn deserialize_ignored_any<V: serde::de::Visitor<'de>>(
+        mut self,
+        visitor: V,
+    ) -> Result<V::Value> {
+        serde::Deserializer::deserialize_ignored_any(&mut self, visitor)
     }
 
-    fn deserialize_any<V: serde::de::Visitor<'de>>(mut self, visitor: V) -> Result<V::Value> {
-        self.deserialize_any_impl(visitor)
+    fn deserialize_enum<V: serde::de::Visitor<'de>>(
+        mut self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        serde::Deserializer::deserialize_enum(&mut self, name, variants, visitor)
     }
 
-    fn deserialize_enum<V: serde::de::Visitor<'de>>(
+    fn deserialize_newtype_struct<V: serde::de::Visitor<'de>>(
         mut self,
-        _name: &'static str,
-        _variants: &'static [&'static str],
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value> {
-        self.deserialize_enum_impl(visitor)
+        serde::Deserializer::deserialize_newtype_struct(&mut self, name, visitor)
     }
 
     fn deserialize_map<V: serde::de::Visitor<'de>>(mut self, visitor: V) -> Result<V::Value> {
-        // Check for CBOR tag - if present, use TaggedValueDeserializer.deserialize_map
-        let peek = self.peek_u8()?;
-        let major = peek >> 5;
-
-        if major == MAJOR_TAG {
-            // Read the tag
-            let initial = self.read_u8()?;
-            let info = initial & 0x1f;
-            let tag = self
-                .read_length(info)?
-                .ok_or_else(|| Error::Syntax("Tag cannot be indefinite".to_string()))?;
-
-            self.current_tag = Some(tag);
-            let result = TaggedValueDeserializer { de: &mut self, tag }.deserialize_map(visitor);
-            self.current_tag = None;
-            result
-        } else {
-            // No tag, process as normal map
-            self.deserialize_any_impl(visitor)
-        }
+        serde::Deserializer::deserialize_map(&mut self, visitor)
     }
 }
 
@@ -566,61 +2288,57 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Decoder<R> {
     type Error = crate::Error;
 
     serde::forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string
+        bool i128 u128 char str string
         bytes byte_buf unit unit_struct seq tuple
-        tuple_struct struct identifier ignored_any
+        tuple_struct struct identifier
+    }
+
+    deserialize_numbers! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 => NumberTarget::Integer;
+        deserialize_f32 => NumberTarget::F32;
+        deserialize_f64 => NumberTarget::F64
     }
 
     fn deserialize_option<V: serde::de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        // Peek at next byte - check for CBOR null (0xf6)
-        let initial = self.read_u8()?;
-        if initial == 0xf6 {
-            return visitor.visit_none();
+        // Content of tag 32 must go through the URI check in deserialize_any_impl
+        if self.expect_uri {
+            return visitor.visit_some(self);
         }
 
-        // Not null - process as Some(...)
-        // We've already read the initial byte, so handle it inline
-        let major = initial >> 5;
-        let info = initial & 0x1f;
-
-        // Handle the value based on major type
-        match major {
-            MAJOR_MAP => match self.read_length(info)? {
-                Some(len) => visitor.visit_some(MapDeserializer {
-                    de: self,
-                    remaining: Some(u64_to_usize(len)?),
-                }),
-                None => visitor.visit_some(MapDeserializer {
-                    de: self,
-                    remaining: None,
-                }),
-            },
-            MAJOR_ARRAY => match self.read_length(info)? {
-                Some(len) => visitor.visit_some(ArrayDeserializer {
-                    de: self,
-                    remaining: Some(u64_to_usize(len)?),
-                }),
-                None => visitor.visit_some(ArrayDeserializer {
-                    de: self,
-                    remaining: None,
-                }),
-            },
-            _ => {
-                // For simple types, deserialize directly
-                // We need to recreate the deserialization with the byte we already read
-                visitor.visit_some(PrefetchedDeserializer {
-                    de: self,
-                    major,
-                    info,
-                })
+        // Only null and undefined are None; anything else is left unread for
+        // the inner type, which then sees the item exactly as it would outside
+        // an Option
+        let next = self.peek_u8()?;
+        if next == (MAJOR_SIMPLE << 5) | UNDEFINED {
+            if self.options.strict_undefined {
+                return Err(Error::Syntax("Unexpected undefined".to_string()));
+            }
+            self.read_u8()?;
+            self.count_item()?;
+            return visitor.visit_none();
+        }
+        if next == (MAJOR_SIMPLE << 5) | NULL {
+            if self.options.strict_nulls {
+                return Err(Error::Syntax(
+                    "Unexpected null; an absent field is required for None".to_string(),
+                ));
             }
+            self.read_u8()?;
+            self.count_item()?;
+            return visitor.visit_none();
         }
+        visitor.visit_some(self)
     }
 
     fn deserialize_any<V: serde::de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
         self.deserialize_any_impl(visitor)
     }
 
+    fn deserialize_ignored_any<V: serde::de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_ignored_impl(visitor)
+    }
+
     fn deserialize_enum<V: serde::de::Visitor<'de>>(
         self,
         _name: &'static str,
@@ -632,9 +2350,35 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Decoder<R> {
 
     fn deserialize_newtype_struct<V: serde::de::Visitor<'de>>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value> {
+        // ChunkedBytes/ChunkedText ask for the chunks of indefinite-length strings
+        if name == CHUNKED_BYTES_MARKER || name == CHUNKED_TEXT_MARKER {
+            return self.deserialize_chunked_impl(visitor);
+        }
+
+        // Fields using the nullable module accept null even with strict nulls
+        if name == NULLABLE_MARKER {
+            if self.peek_u8()? == (MAJOR_SIMPLE << 5) | NULL {
+                self.read_u8()?;
+                self.count_item()?;
+                return visitor.visit_none();
+            }
+            return visitor.visit_some(self);
+        }
+
+        // Legacy data wraps the inner value in a 1-element array
+        if self.options.legacy_newtypes && self.peek_u8()? == (MAJOR_ARRAY << 5) | 1 {
+            self.read_u8()?;
+            self.count_item()?;
+            self.check_recursion_depth()?;
+            self.recursion_depth += 1;
+            let result = visitor.visit_newtype_struct(&mut *self);
+            self.recursion_depth -= 1;
+            return result;
+        }
+
         // Newtype structs are serialized transparently (just the inner value)
         // This is serde's standard behavior - the newtype wrapper is not encoded in CBOR
         visitor.visit_newtype_struct(self)
@@ -648,14 +2392,15 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Decoder<R> {
         if major == MAJOR_TAG {
             // Read the tag
             let initial = self.read_u8()?;
+            self.count_item()?;
             let info = initial & 0x1f;
             let tag = self
                 .read_length(info)?
                 .ok_or_else(|| Error::Syntax("Tag cannot be indefinite".to_string()))?;
 
-            self.current_tag = Some(tag);
+            let outer_tag = self.enter_tag(tag);
             let result = TaggedValueDeserializer { de: self, tag }.deserialize_map(visitor);
-            self.current_tag = None;
+            set_tag(outer_tag);
             result
         } else {
             // No tag, process as normal map
@@ -664,159 +2409,13 @@ impl<'de, R: Read> serde::Deserializer<'de> for &mut Decoder<R> {
     }
 }
 
-// Helper deserializers for Option handling
-struct MapDeserializer<'a, R: Read> {
-    de: &'a mut Decoder<R>,
-    remaining: Option<usize>,
-}
-
-impl<'de, 'a, R: Read> serde::Deserializer<'de> for MapDeserializer<'a, R> {
-    type Error = crate::Error;
-
-    serde::forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string
-        bytes byte_buf option unit unit_struct newtype_struct seq tuple
-        tuple_struct map struct enum identifier ignored_any
-    }
-
-    fn deserialize_any<V: serde::de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        visitor.visit_map(MapAccess {
-            de: self.de,
-            remaining: self.remaining,
-        })
-    }
-}
-
-struct ArrayDeserializer<'a, R: Read> {
-    de: &'a mut Decoder<R>,
-    remaining: Option<usize>,
-}
-
-impl<'de, 'a, R: Read> serde::Deserializer<'de> for ArrayDeserializer<'a, R> {
-    type Error = crate::Error;
-
-    serde::forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string
-        bytes byte_buf option unit unit_struct newtype_struct seq tuple
-        tuple_struct map struct enum identifier ignored_any
-    }
-
-    fn deserialize_any<V: serde::de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        visitor.visit_seq(SeqAccess {
-            de: self.de,
-            remaining: self.remaining,
-        })
-    }
-}
-
-struct PrefetchedDeserializer<'a, R: Read> {
-    de: &'a mut Decoder<R>,
-    major: u8,
-    info: u8,
-}
-
-impl<'de, 'a, R: Read> serde::Deserializer<'de> for PrefetchedDeserializer<'a, R> {
-    type Error = crate::Error;
-
-    serde::forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string
-        bytes byte_buf option unit unit_struct newtype_struct seq tuple
-        tuple_struct map struct enum identifier ignored_any
-    }
-
-    fn deserialize_any<V: serde::de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        match self.major {
-            MAJOR_UNSIGNED => {
-                let val = self.de.read_length(self.info)?.ok_or_else(|| {
-                    Error::Syntax("Unsigned integer cannot be indefinite".to_string())
-                })?;
-                visitor.visit_u64(val)
-            }
-            MAJOR_NEGATIVE => {
-                let val = self.de.read_length(self.info)?.ok_or_else(|| {
-                    Error::Syntax("Negative integer cannot be indefinite".to_string())
-                })?;
-                visitor.visit_i64(-1 - val as i64)
-            }
-            MAJOR_TEXT => {
-                let len = self.de.read_length(self.info)?.ok_or_else(|| {
-                    Error::Syntax("Text in option must be definite length".to_string())
-                })?;
-                let s = self.de.read_text(u64_to_usize(len)?)?;
-                visitor.visit_string(s)
-            }
-            MAJOR_BYTES => {
-                let len = self.de.read_length(self.info)?.ok_or_else(|| {
-                    Error::Syntax("Bytes in option must be definite length".to_string())
-                })?;
-                let buf = self.de.read_bytes(u64_to_usize(len)?)?;
-                visitor.visit_byte_buf(buf)
-            }
-            MAJOR_ARRAY => {
-                self.de.check_recursion_depth()?;
-                self.de.recursion_depth += 1;
-                match self.de.read_length(self.info)? {
-                    Some(len) => visitor.visit_seq(SeqAccess {
-                        de: self.de,
-                        remaining: Some(u64_to_usize(len)?),
-                    }),
-                    None => visitor.visit_seq(SeqAccess {
-                        de: self.de,
-                        remaining: None,
-                    }),
-                }
-                // Note: recursion_depth is decremented in SeqAccess::drop
-            }
-            MAJOR_MAP => {
-                self.de.check_recursion_depth()?;
-                self.de.recursion_depth += 1;
-                match self.de.read_length(self.info)? {
-                    Some(len) => visitor.visit_map(MapAccess {
-                        de: self.de,
-                        remaining: Some(u64_to_usize(len)?),
-                    }),
-                    None => visitor.visit_map(MapAccess {
-                        de: self.de,
-                        remaining: None,
-                    }),
-                }
-                // Note: recursion_depth is decremented in MapAccess::drop
-            }
-            MAJOR_TAG => {
-                // Read the tag number
-                let tag = self
-                    .de
-                    .read_length(self.info)?
-                    .ok_or_else(|| Error::Syntax("Tag cannot be indefinite".to_string()))?;
-                // Store the tag
-                self.de.current_tag = Some(tag);
-
-                // Deserialize the tagged content using TaggedValueDeserializer
-                let result = serde::Deserializer::deserialize_any(
-                    TaggedValueDeserializer { de: self.de, tag },
-                    visitor,
-                );
-
-                // Clear the tag after deserialization
-                self.de.current_tag = None;
-                result
-            }
-            MAJOR_SIMPLE => match self.info {
-                FALSE => visitor.visit_bool(false),
-                TRUE => visitor.visit_bool(true),
-                _ => Err(Error::Syntax("Invalid simple type in option".to_string())),
-            },
-            _ => Err(Error::Syntax("Unsupported type in option".to_string())),
-        }
-    }
-}
-
-// Enum access for unit variants (encoded as strings)
-struct UnitVariantAccess {
-    variant: String,
+// Enum access for unit variants (encoded as a name or an integer key)
+struct UnitVariantAccess<K> {
+    // The variant name, or its index
+    variant: K,
 }
 
-impl<'de> serde::de::EnumAccess<'de> for UnitVariantAccess {
+impl<'de, K: IntoDeserializer<'de, Error>> serde::de::EnumAccess<'de> for UnitVariantAccess<K> {
     type Error = crate::Error;
     type Variant = UnitOnly;
 
@@ -824,10 +2423,7 @@ impl<'de> serde::de::EnumAccess<'de> for UnitVariantAccess {
         self,
         seed: V,
     ) -> Result<(V::Value, Self::Variant)> {
-        // Deserialize the variant name as a string
-        let bytes = crate::to_vec(&self.variant)?;
-        let mut decoder = Decoder::new(&bytes[..]);
-        let value = seed.deserialize(&mut decoder)?;
+        let value = seed.deserialize(self.variant.into_deserializer())?;
         Ok((value, UnitOnly))
     }
 }
@@ -878,7 +2474,17 @@ impl<'de, 'a, R: Read> serde::de::EnumAccess<'de> for VariantAccess<'a, R> {
         self,
         seed: V,
     ) -> Result<(V::Value, Self::Variant)> {
-        // Read the key (variant name)
+        // Integer keys name the variant in decimal when asked to
+        if self.de.options.variant_keys == VariantKeys::NumericName
+            && self.de.peek_u8()? >> 5 == MAJOR_UNSIGNED
+        {
+            let name = self.de.read_u64()?.to_string();
+            let value =
+                seed.deserialize(IntoDeserializer::<'de, Error>::into_deserializer(name))?;
+            return Ok((value, self));
+        }
+
+        // Read the key (variant name, or index)
         let value = seed.deserialize(&mut *self.de)?;
         Ok((value, self))
     }
@@ -912,9 +2518,41 @@ impl<'de, 'a, R: Read> serde::de::VariantAccess<'de> for VariantAccess<'a, R> {
     }
 }
 
+// Sequence of string chunks for ChunkedBytes/ChunkedText
+struct ChunkAccess {
+    chunks: std::vec::IntoIter<Vec<u8>>,
+    text: bool,
+}
+
+impl<'de> serde::de::SeqAccess<'de> for ChunkAccess {
+    type Error = crate::Error;
+
+    fn next_element_seed<T: serde::de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>> {
+        let Some(chunk) = self.chunks.next() else {
+            return Ok(None);
+        };
+        if self.text {
+            // Chunks were checked as they were read
+            let s = String::from_utf8(chunk).map_err(|_| Error::InvalidUtf8)?;
+            seed.deserialize(s.into_deserializer()).map(Some)
+        } else {
+            seed.deserialize(serde::de::value::BytesDeserializer::new(&chunk))
+                .map(Some)
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.chunks.len())
+    }
+}
+
 struct SeqAccess<'a, R: Read> {
     de: &'a mut Decoder<R>,
     remaining: Option<usize>, // None for indefinite-length
+    index: usize,
 }
 
 impl<'a, R: Read> Drop for SeqAccess<'a, R> {
@@ -931,21 +2569,25 @@ impl<'de, 'a, R: Read> serde::de::SeqAccess<'de> for SeqAccess<'a, R> {
         seed: T,
     ) -> Result<Option<T::Value>> {
         match self.remaining {
-            Some(0) => Ok(None),
-            Some(ref mut n) => {
-                *n -= 1;
-                seed.deserialize(&mut *self.de).map(Some)
-            }
+            Some(0) => return Ok(None),
+            Some(ref mut n) => *n -= 1,
             None => {
                 // Indefinite-length: check for break marker
                 if self.de.is_break()? {
                     self.de.read_break()?;
-                    Ok(None)
-                } else {
-                    seed.deserialize(&mut *self.de).map(Some)
+                    return Ok(None);
                 }
             }
         }
+        let index = self.index;
+        self.index += 1;
+        self.de
+            .deserialize_at(Some(PathSegment::Index(index)), seed)
+            .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.remaining
     }
 }
 
@@ -971,7 +2613,7 @@ impl<'de, 'a, R: Read> serde::de::MapAccess<'de> for MapAccess<'a, R> {
             Some(0) => Ok(None),
             Some(ref mut n) => {
                 *n -= 1;
-                seed.deserialize(&mut *self.de).map(Some)
+                self.de.deserialize_key(seed).map(Some)
             }
             None => {
                 // Indefinite-length: check for break marker
@@ -979,14 +2621,19 @@ impl<'de, 'a, R: Read> serde::de::MapAccess<'de> for MapAccess<'a, R> {
                     self.de.read_break()?;
                     Ok(None)
                 } else {
-                    seed.deserialize(&mut *self.de).map(Some)
+                    self.de.deserialize_key(seed).map(Some)
                 }
             }
         }
     }
 
     fn next_value_seed<V: serde::de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
-        seed.deserialize(&mut *self.de)
+        let segment = self.de.pending_key.take();
+        self.de.deserialize_at(segment, seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.remaining
     }
 }
 
@@ -1189,6 +2836,14 @@ impl<'de, 'a, R: Read> serde::de::MapAccess<'de> for TaggedMapAccess<'a, R> {
             )),
         }
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(match self.state {
+            TaggedMapState::BeforeTag => 2,
+            TaggedMapState::AfterTag | TaggedMapState::BeforeValue => 1,
+            TaggedMapState::Done => 0,
+        })
+    }
 }
 
 /// Deserializes a value from CBOR bytes
@@ -1216,6 +2871,67 @@ pub fn from_slice<'de, T: Deserialize<'de>>(slice: &[u8]) -> Result<T> {
     Ok(value)
 }
 
+/// Deserializes CBOR bytes into an existing value, reusing its allocations where possible
+///
+/// The in-place counterpart of [`from_slice`], for decoding many items of
+/// the same shape into one long-lived value. See
+/// [`Decoder::decode_in_place`] for which types reuse their allocations.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::{from_slice_in_place, to_vec};
+///
+/// let mut samples: Vec<u32> = Vec::with_capacity(64);
+/// let buffer = samples.as_ptr();
+/// for frame in [vec![1u32, 2, 3], vec![4, 5]] {
+///     from_slice_in_place(&mut samples, &to_vec(&frame).unwrap()).unwrap();
+///     assert_eq!(samples, frame);
+/// }
+/// assert_eq!(samples.as_ptr(), buffer);
+/// ```
+pub fn from_slice_in_place<'de, T: Deserialize<'de>>(place: &mut T, slice: &[u8]) -> Result<()> {
+    if slice.is_empty() {
+        return Err(Error::Syntax("empty input".to_string()));
+    }
+
+    let mut decoder = Decoder::new(Cursor::new(slice)).with_max_allocation(DEFAULT_MAX_ALLOCATION);
+    decoder.decode_in_place(place)?;
+
+    let remaining = slice.len() as u64 - decoder.reader.position();
+    if remaining > 0 {
+        return Err(Error::Syntax(format!(
+            "unexpected trailing data: {} bytes remaining",
+            remaining
+        )));
+    }
+
+    Ok(())
+}
+
+/// Deserializes a value from CBOR bytes with a [`DeserializeSeed`]
+///
+/// The seeded counterpart of [`from_slice`], with the same limits and the
+/// same check for trailing data. See [`Decoder::decode_seed`] for an example
+/// seed.
+pub fn from_slice_seed<'de, S: DeserializeSeed<'de>>(seed: S, slice: &[u8]) -> Result<S::Value> {
+    if slice.is_empty() {
+        return Err(Error::Syntax("empty input".to_string()));
+    }
+
+    let mut decoder = Decoder::new(Cursor::new(slice)).with_max_allocation(DEFAULT_MAX_ALLOCATION);
+    let value = decoder.decode_seed(seed)?;
+
+    let remaining = slice.len() as u64 - decoder.reader.position();
+    if remaining > 0 {
+        return Err(Error::Syntax(format!(
+            "unexpected trailing data: {} bytes remaining",
+            remaining
+        )));
+    }
+
+    Ok(value)
+}
+
 /// Deserializes a value from a CBOR reader
 ///
 /// Wraps the reader in a BufReader for optimal performance with small reads.
@@ -1228,6 +2944,74 @@ pub fn from_reader<R: Read, T: for<'de> Deserialize<'de>>(reader: R) -> Result<T
     decoder.decode()
 }
 
+/// Deserializes one value from the start of a CBOR reader, returning it and the bytes it used
+///
+/// Reads exactly the bytes of the item and no more, so the reader is left at
+/// the start of whatever follows: pass `&mut reader` to decode a stream of
+/// concatenated items one at a time. Since nothing can be read ahead, the
+/// reader is not buffered; wrap it in a `BufReader` first if reads are
+/// expensive and the rest of the stream is read through the same buffer.
+/// The default allocation limit applies.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::from_reader_partial;
+///
+/// // "a", [1, 2] and 3 back to back
+/// let stream = [0x61, b'a', 0x82, 0x01, 0x02, 0x03];
+/// let mut reader = &stream[..];
+/// let (first, used): (String, u64) = from_reader_partial(&mut reader).unwrap();
+/// assert_eq!((first.as_str(), used), ("a", 2));
+/// let (second, used): (Vec<u8>, u64) = from_reader_partial(&mut reader).unwrap();
+/// assert_eq!((second, used), (vec![1, 2], 3));
+/// assert_eq!(reader, [0x03]);
+/// ```
+pub fn from_reader_partial<R: Read, T: for<'de> Deserialize<'de>>(reader: R) -> Result<(T, u64)> {
+    let mut decoder = Decoder::new(CountingReader {
+        inner: reader,
+        count: 0,
+    })
+    .with_max_allocation(DEFAULT_MAX_ALLOCATION);
+    let value = decoder.decode()?;
+    // A byte peeked past the end of the item was read but not used
+    let used = decoder.reader.count - decoder.peeked.is_some() as u64;
+    Ok((value, used))
+}
+
+/// Deserializes a value from a CBOR reader, failing if the reader has more data after it
+///
+/// Like [`from_reader`], but returns [`Error::TrailingData`] unless the item
+/// is followed by end of file.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::from_reader_exact;
+///
+/// assert_eq!(from_reader_exact::<_, u8>(&[0x07][..]).unwrap(), 7);
+/// assert!(from_reader_exact::<_, u8>(&[0x07, 0x08][..]).is_err());
+/// ```
+pub fn from_reader_exact<R: Read, T: for<'de> Deserialize<'de>>(reader: R) -> Result<T> {
+    let mut decoder =
+        Decoder::new(BufReader::new(reader)).with_max_allocation(DEFAULT_MAX_ALLOCATION);
+    let value = decoder.decode()?;
+    decoder.end()?;
+    Ok(value)
+}
+
+/// Reader that counts the bytes read through it, for [`from_reader_partial`]
+struct CountingReader<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+}
+
 /// Deserializes a value from a CBOR reader with a maximum allocation limit
 ///
 /// This is useful for untrusted input to prevent DoS attacks via extremely
@@ -1241,6 +3025,49 @@ pub fn from_reader_with_limit<R: Read, T: for<'de> Deserialize<'de>>(
     decoder.decode()
 }
 
+/// Deserializes a value from CBOR bytes, bounding the number of items by the input length
+///
+/// Decoding fails once more than `max_items_per_byte * slice.len()` data items
+/// (rounded up) have been read. Every item takes at least one byte, so a ratio
+/// of `1.0` or more never triggers; lower ratios reject inputs that pack many
+/// zero-length items into few bytes and are cheap to send but slow to decode.
+/// The default allocation limit also applies.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::{Value, from_slice_bounded};
+///
+/// // 9 items in 9 bytes
+/// let dense = [0x88, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80];
+/// assert!(from_slice_bounded::<Value>(&dense, 1.0).is_ok());
+/// assert!(from_slice_bounded::<Value>(&dense, 0.5).is_err());
+/// ```
+pub fn from_slice_bounded<'de, T: Deserialize<'de>>(
+    slice: &[u8],
+    max_items_per_byte: f64,
+) -> Result<T> {
+    if slice.is_empty() {
+        return Err(Error::Syntax("empty input".to_string()));
+    }
+
+    let max_items = (slice.len() as f64 * max_items_per_byte).ceil() as u64;
+    let mut decoder = Decoder::new(Cursor::new(slice))
+        .with_max_allocation(DEFAULT_MAX_ALLOCATION)
+        .with_max_items(max_items);
+    let value = decoder.decode()?;
+
+    // Check if all bytes were consumed
+    let remaining = slice.len() as u64 - decoder.reader.position();
+    if remaining > 0 {
+        return Err(Error::Syntax(format!(
+            "unexpected trailing data: {} bytes remaining",
+            remaining
+        )));
+    }
+
+    Ok(value)
+}
+
 /// Deserializes a value from CBOR bytes with a maximum allocation limit
 ///
 /// This is useful for untrusted input to prevent DoS attacks via extremely
@@ -0,0 +1,1263 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Diagnostic notation (RFC 8949 §8)
+//!
+//! [`to_diagnostic`] renders an encoded item as text, one array element or
+//! map entry per line, so that changes in encoded bytes show up as readable
+//! line diffs. The output also records how the item was encoded, using the
+//! encoding indicators of RFC 8610 Appendix G:
+//! - `_` marks indefinite-length strings, arrays and maps
+//! - `_0` to `_3` mark integers, lengths and tag numbers that use a 1, 2, 4
+//!   or 8 byte argument where a shorter one would do
+//! - floats always carry `_1`, `_2` or `_3` for half, single or double
+//!   precision
+//!
+//! [`annotate`] instead lists the encoded bytes themselves, one item per
+//! line, with a description of each, and [`diff_encodings`] lists where two
+//! encodings differ, telling different values from the same value encoded
+//! differently.
+//!
+//! [`Value`] displays in the same notation, without encoding indicators
+//! since a `Value` does not record how it was encoded: `{}` writes it on
+//! one line, and `{:#}` one array element or map entry per line.
+
+use std::{
+    fmt::{self, Write},
+    ops::Range,
+};
+
+use crate::{
+    Argument, Error, PathSegment, Result, Value,
+    constants::*,
+    raw::{SliceReader, check_depth, float_from_arg, u64_to_usize},
+};
+
+/// Render a single encoded CBOR item in diagnostic notation
+///
+/// Returns an error for malformed input or trailing data.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::diag::to_diagnostic;
+///
+/// // {"a": [1, h'ff'], "b": 32("x")}
+/// let cbor = [0xa2, 0x61, b'a', 0x82, 0x01, 0x41, 0xff, 0x61, b'b', 0xd8, 0x20, 0x61, b'x'];
+/// assert_eq!(
+///     to_diagnostic(&cbor).unwrap(),
+///     "{\n  \"a\": [\n    1,\n    h'ff'\n  ],\n  \"b\": 32(\"x\")\n}"
+/// );
+/// assert_eq!(to_diagnostic(&[0x18, 0x01]).unwrap(), "1_0");
+/// ```
+pub fn to_diagnostic(bytes: &[u8]) -> Result<String> {
+    if bytes.is_empty() {
+        return Err(Error::Syntax("empty input".to_string()));
+    }
+    let mut reader = SliceReader::new(bytes);
+    let mut out = String::new();
+    write_item(&mut reader, &mut out, 0)?;
+    if reader.remaining() > 0 {
+        return Err(Error::TrailingData);
+    }
+    Ok(out)
+}
+
+/// Encoding indicator for an argument that is longer than necessary
+fn width_indicator(info: u8, arg: u64) -> &'static str {
+    let minimal = match arg {
+        0..=23 => 0..=23,
+        24..=0xff => 24..=24,
+        0x100..=0xffff => 25..=25,
+        0x1_0000..=0xffff_ffff => 26..=26,
+        _ => 27..=27,
+    };
+    match info {
+        _ if minimal.contains(&info) => "",
+        24 => "_0",
+        25 => "_1",
+        26 => "_2",
+        _ => "_3",
+    }
+}
+
+fn indent(out: &mut String, depth: usize) {
+    for _ in 0..depth {
+        out.push_str("  ");
+    }
+}
+
+fn write_text(out: &mut String, bytes: &[u8]) -> Result<()> {
+    let text = std::str::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)?;
+    write_escaped(out, text);
+    Ok(())
+}
+
+fn write_escaped(out: &mut String, text: &str) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_hex(out: &mut String, bytes: &[u8]) {
+    out.push_str("h'");
+    for b in bytes {
+        let _ = write!(out, "{:02x}", b);
+    }
+    out.push('\'');
+}
+
+fn write_string(out: &mut String, major: u8, bytes: &[u8]) -> Result<()> {
+    if major == MAJOR_TEXT {
+        return write_text(out, bytes);
+    }
+    write_hex(out, bytes);
+    Ok(())
+}
+
+fn write_float_value(out: &mut String, value: f64) {
+    if value.is_nan() {
+        out.push_str("NaN");
+    } else if value.is_infinite() {
+        out.push_str(if value > 0.0 { "Infinity" } else { "-Infinity" });
+    } else {
+        let _ = write!(out, "{:?}", value);
+    }
+}
+
+fn write_float(out: &mut String, info: u8, bits: u64) {
+    write_float_value(out, float_from_arg(info, bits));
+    out.push_str(match info {
+        FLOAT16 => "_1",
+        FLOAT32 => "_2",
+        _ => "_3",
+    });
+}
+
+/// Whether the next byte is a break, consuming it if so
+fn at_break(reader: &mut SliceReader<'_>) -> Result<bool> {
+    if reader.peek_u8()? == BREAK {
+        reader.read_u8()?;
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+fn write_item(reader: &mut SliceReader<'_>, out: &mut String, depth: usize) -> Result<()> {
+    let (major, info, arg) = reader.read_header()?;
+    match (major, arg) {
+        (MAJOR_UNSIGNED, Some(n)) => {
+            let _ = write!(out, "{}{}", n, width_indicator(info, n));
+        }
+        (MAJOR_NEGATIVE, Some(n)) => {
+            let _ = write!(out, "{}{}", -1 - n as i128, width_indicator(info, n));
+        }
+        (MAJOR_BYTES | MAJOR_TEXT, Some(len)) => {
+            let len = u64_to_usize(len)?;
+            let content = reader.read_slice(len)?;
+            write_string(out, major, content)?;
+            out.push_str(width_indicator(info, len as u64));
+        }
+        (MAJOR_BYTES | MAJOR_TEXT, None) => {
+            out.push_str("(_ ");
+            let mut first = true;
+            while !at_break(reader)? {
+                let (chunk_major, chunk_info, chunk_len) = reader.read_header()?;
+                let Some(len) = chunk_len.filter(|_| chunk_major == major) else {
+                    return Err(Error::Syntax(
+                        "Indefinite string chunks must be definite strings of the same type"
+                            .to_string(),
+                    ));
+                };
+                if !first {
+                    out.push_str(", ");
+                }
+                first = false;
+                let len = u64_to_usize(len)?;
+                write_string(out, major, reader.read_slice(len)?)?;
+                out.push_str(width_indicator(chunk_info, len as u64));
+            }
+            out.push(')');
+        }
+        (MAJOR_ARRAY | MAJOR_MAP, len) => {
+            check_depth(depth)?;
+            let (open, close) = if major == MAJOR_ARRAY {
+                ('[', ']')
+            } else {
+                ('{', '}')
+            };
+            out.push(open);
+            match len {
+                None => out.push('_'),
+                Some(n) => out.push_str(width_indicator(info, n)),
+            }
+            let mut count = 0u64;
+            loop {
+                let done = match len {
+                    Some(n) => count == n,
+                    None => at_break(reader)?,
+                };
+                if done {
+                    break;
+                }
+                out.push_str(if count == 0 { "\n" } else { ",\n" });
+                indent(out, depth + 1);
+                write_item(reader, out, depth + 1)?;
+                if major == MAJOR_MAP {
+                    if len.is_none() && reader.peek_u8()? == BREAK {
+                        return Err(Error::Syntax("Map key without a value".to_string()));
+                    }
+                    out.push_str(": ");
+                    write_item(reader, out, depth + 1)?;
+                }
+                count += 1;
+            }
+            if count > 0 {
+                out.push('\n');
+                indent(out, depth);
+            } else if len.is_none() {
+                out.push(' ');
+            }
+            out.push(close);
+        }
+        (MAJOR_TAG, Some(tag)) => {
+            check_depth(depth)?;
+            let _ = write!(out, "{}{}(", tag, width_indicator(info, tag));
+            write_item(reader, out, depth)?;
+            out.push(')');
+        }
+        (MAJOR_SIMPLE, _) => match (info, arg) {
+            (20, _) => out.push_str("false"),
+            (21, _) => out.push_str("true"),
+            (22, _) => out.push_str("null"),
+            (23, _) => out.push_str("undefined"),
+            (0..=19, Some(n)) => {
+                let _ = write!(out, "simple({})", n);
+            }
+            (SIMPLE_VALUE, Some(n)) if n >= 32 => {
+                let _ = write!(out, "simple({})", n);
+            }
+            (FLOAT16 | FLOAT32 | FLOAT64, Some(bits)) => write_float(out, info, bits),
+            (INDEFINITE, _) => return Err(Error::Syntax("Unexpected break".to_string())),
+            _ => return Err(Error::Syntax("Invalid simple value".to_string())),
+        },
+        _ => return Err(Error::Syntax("Unexpected indefinite length".to_string())),
+    }
+    Ok(())
+}
+
+// One line of `annotate` output: the bytes of an item header or string
+// content, at its nesting depth
+struct Row {
+    offset: usize,
+    depth: usize,
+    hex: String,
+    comment: String,
+}
+
+/// Annotate a single encoded CBOR item as a hex dump
+///
+/// Each line holds the offset of some bytes, the bytes in hex, indented by
+/// nesting depth, and a comment describing them: the major type and
+/// argument of each item header, the name of well-known tags, and the
+/// content of strings, split into lines of 16 bytes. Arguments encoded in
+/// more bytes than necessary are marked, which helps when two encodings of
+/// the same value hash differently. Returns an error for malformed input or
+/// trailing data.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::diag::annotate;
+///
+/// // {"a": 32("x"), "n": 1_1}
+/// let cbor = [
+///     0xa2, 0x61, b'a', 0xd8, 0x20, 0x61, b'x', 0x61, b'n', 0x19, 0x00, 0x01,
+/// ];
+/// assert_eq!(
+///     annotate(&cbor).unwrap(),
+///     [
+///         "0000  a2           # map(2)",
+///         "0001     61        #   text(1)",
+///         "0002        61     #     \"a\"",
+///         "0003     d8 20     #   tag(32) URI",
+///         "0005        61     #     text(1)",
+///         "0006           78  #       \"x\"",
+///         "0007     61        #   text(1)",
+///         "0008        6e     #     \"n\"",
+///         "0009     19 00 01  #   unsigned(1), not preferred",
+///     ]
+///     .join("\n")
+/// );
+/// ```
+pub fn annotate(bytes: &[u8]) -> Result<String> {
+    if bytes.is_empty() {
+        return Err(Error::Syntax("empty input".to_string()));
+    }
+    let mut reader = SliceReader::new(bytes);
+    let mut rows = Vec::new();
+    annotate_item(&mut reader, bytes, &mut rows, 0)?;
+    if reader.remaining() > 0 {
+        return Err(Error::TrailingData);
+    }
+
+    let width = rows
+        .iter()
+        .map(|row| row.depth * 3 + row.hex.len())
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for row in rows {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        let hex_indent = row.depth * 3;
+        let _ = write!(out, "{:04x}  {:hex_indent$}{}", row.offset, "", row.hex);
+        if !row.comment.is_empty() {
+            let _ = write!(
+                out,
+                "{:pad$}  # {:comment_indent$}{}",
+                "",
+                "",
+                row.comment,
+                pad = width - hex_indent - row.hex.len(),
+                comment_indent = row.depth * 2,
+            );
+        }
+    }
+    Ok(out)
+}
+
+/// Name of a well-known tag, for `annotate`
+fn tag_name(tag: u64) -> Option<&'static str> {
+    Some(match tag {
+        TAG_DATETIME_STRING => "date/time string",
+        TAG_EPOCH_DATETIME => "epoch date/time",
+        TAG_POSITIVE_BIGNUM => "positive bignum",
+        TAG_NEGATIVE_BIGNUM => "negative bignum",
+        TAG_DECIMAL_FRACTION => "decimal fraction",
+        TAG_BIGFLOAT => "bigfloat",
+        16 => "COSE_Encrypt0",
+        17 => "COSE_Mac0",
+        18 => "COSE_Sign1",
+        TAG_EXPECT_BASE64URL => "expected base64url",
+        TAG_EXPECT_BASE64 => "expected base64",
+        TAG_EXPECT_BASE16 => "expected base16",
+        TAG_ENCODED_CBOR => "encoded CBOR item",
+        TAG_STRINGREF => "string reference",
+        TAG_SHAREABLE => "shareable value",
+        TAG_SHAREDREF => "shared value reference",
+        TAG_URI => "URI",
+        TAG_BASE64URL => "base64url text",
+        TAG_BASE64 => "base64 text",
+        TAG_MIME => "MIME message",
+        TAG_LANG_STRING => "language-tagged string",
+        TAG_MULTI_DIM_ARRAY => "multi-dimensional array",
+        TAG_MULTI_DIM_ARRAY_COLUMN_MAJOR => "multi-dimensional array, column-major",
+        TAG_UINT8_ARRAY..=TAG_FLOAT128LE_ARRAY => "typed array",
+        96 => "COSE_Encrypt",
+        97 => "COSE_Mac",
+        98 => "COSE_Sign",
+        TAG_STRINGREF_NAMESPACE => "string reference namespace",
+        TAG_SELF_DESCRIBE => "self-described CBOR",
+        _ => return None,
+    })
+}
+
+fn annotate_item(
+    reader: &mut SliceReader<'_>,
+    bytes: &[u8],
+    rows: &mut Vec<Row>,
+    depth: usize,
+) -> Result<()> {
+    let push = |rows: &mut Vec<Row>, offset: usize, end: usize, depth: usize, comment: String| {
+        let hex = bytes[offset..end]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<_>>()
+            .join(" ");
+        rows.push(Row {
+            offset,
+            depth,
+            hex,
+            comment,
+        });
+    };
+
+    let start = reader.position();
+    let (major, info, arg) = reader.read_header()?;
+    let end = reader.position();
+    let note = match arg {
+        Some(value) if major != MAJOR_SIMPLE => match Argument::from_info(info, value) {
+            Some(argument) if !argument.is_preferred() => ", not preferred",
+            _ => "",
+        },
+        _ => "",
+    };
+    match (major, arg) {
+        (MAJOR_UNSIGNED, Some(n)) => {
+            push(rows, start, end, depth, format!("unsigned({}){}", n, note))
+        }
+        (MAJOR_NEGATIVE, Some(n)) => push(
+            rows,
+            start,
+            end,
+            depth,
+            format!("negative({}){}", -1 - n as i128, note),
+        ),
+        (MAJOR_BYTES | MAJOR_TEXT, Some(len)) => {
+            let kind = if major == MAJOR_TEXT { "text" } else { "bytes" };
+            push(
+                rows,
+                start,
+                end,
+                depth,
+                format!("{}({}){}", kind, len, note),
+            );
+            let content = reader.read_slice(u64_to_usize(len)?)?;
+            let mut comment = String::new();
+            if major == MAJOR_TEXT {
+                let text = std::str::from_utf8(content).map_err(|_| Error::InvalidUtf8)?;
+                write_escaped(&mut comment, text);
+            }
+            for (i, chunk) in content.chunks(16).enumerate() {
+                let offset = end + i * 16;
+                push(
+                    rows,
+                    offset,
+                    offset + chunk.len(),
+                    depth + 1,
+                    std::mem::take(&mut comment),
+                );
+            }
+        }
+        (MAJOR_BYTES | MAJOR_TEXT | MAJOR_ARRAY | MAJOR_MAP, None) => {
+            check_depth(depth)?;
+            let kind = match major {
+                MAJOR_BYTES => "bytes",
+                MAJOR_TEXT => "text",
+                MAJOR_ARRAY => "array",
+                _ => "map",
+            };
+            push(rows, start, end, depth, format!("{}(indefinite)", kind));
+            let mut count = 0u64;
+            while reader.peek_u8()? != BREAK {
+                if matches!(major, MAJOR_BYTES | MAJOR_TEXT) {
+                    let initial = reader.peek_u8()?;
+                    if initial >> 5 != major || initial & 0x1f == INDEFINITE {
+                        return Err(Error::Syntax(
+                            "Indefinite string chunks must be definite strings of the same type"
+                                .to_string(),
+                        ));
+                    }
+                }
+                annotate_item(reader, bytes, rows, depth + 1)?;
+                count += 1;
+            }
+            if major == MAJOR_MAP && count % 2 == 1 {
+                return Err(Error::Syntax("Map key without a value".to_string()));
+            }
+            let offset = reader.position();
+            reader.read_u8()?;
+            push(rows, offset, offset + 1, depth + 1, "break".to_string());
+        }
+        (MAJOR_ARRAY | MAJOR_MAP, Some(len)) => {
+            check_depth(depth)?;
+            let kind = if major == MAJOR_ARRAY { "array" } else { "map" };
+            push(
+                rows,
+                start,
+                end,
+                depth,
+                format!("{}({}){}", kind, len, note),
+            );
+            let items = if major == MAJOR_MAP {
+                len.checked_mul(2).ok_or(Error::LengthOverflow(len))?
+            } else {
+                len
+            };
+            for _ in 0..items {
+                annotate_item(reader, bytes, rows, depth + 1)?;
+            }
+        }
+        (MAJOR_TAG, Some(tag)) => {
+            check_depth(depth)?;
+            let name = tag_name(tag)
+                .map(|name| format!(" {}", name))
+                .unwrap_or_default();
+            push(
+                rows,
+                start,
+                end,
+                depth,
+                format!("tag({}){}{}", tag, name, note),
+            );
+            annotate_item(reader, bytes, rows, depth + 1)?;
+        }
+        (MAJOR_SIMPLE, _) => {
+            let comment = match (info, arg) {
+                (20, _) => "false".to_string(),
+                (21, _) => "true".to_string(),
+                (22, _) => "null".to_string(),
+                (23, _) => "undefined".to_string(),
+                (0..=19, Some(n)) => format!("simple({})", n),
+                (SIMPLE_VALUE, Some(n)) if n >= 32 => format!("simple({})", n),
+                (FLOAT16 | FLOAT32 | FLOAT64, Some(bits)) => {
+                    let mut comment = match info {
+                        FLOAT16 => "float16(",
+                        FLOAT32 => "float32(",
+                        _ => "float64(",
+                    }
+                    .to_string();
+                    write_float_value(&mut comment, float_from_arg(info, bits));
+                    comment.push(')');
+                    comment
+                }
+                (INDEFINITE, _) => return Err(Error::Syntax("Unexpected break".to_string())),
+                _ => return Err(Error::Syntax("Invalid simple value".to_string())),
+            };
+            push(rows, start, end, depth, comment);
+        }
+        _ => return Err(Error::Syntax("Unexpected indefinite length".to_string())),
+    }
+    Ok(())
+}
+
+/// How two encodings differ at one item, see [`DiffEntry`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    /// The same value encoded differently: an integer, length, tag number
+    /// or float of a different width, or a definite length where the other
+    /// side has an indefinite one
+    Encoding,
+    /// The same map entries in a different order
+    KeyOrder,
+    /// Different values
+    Value,
+    /// A map entry or array element only in the first encoding
+    OnlyInA,
+    /// A map entry or array element only in the second encoding
+    OnlyInB,
+}
+
+/// One place where two encodings differ, found by [`diff_encodings`]
+///
+/// An encoding difference in the header of an array, map or tag covers just
+/// the header, and is described as in [`annotate`]; every other entry covers
+/// the whole item, or the whole map entry, described in diagnostic notation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffEntry {
+    /// Location of the item in both documents
+    pub path: Vec<PathSegment>,
+    /// How the items differ
+    pub kind: DiffKind,
+    /// Byte range in the first encoding, absent for [`DiffKind::OnlyInB`]
+    pub a: Option<Range<usize>>,
+    /// Byte range in the second encoding, absent for [`DiffKind::OnlyInA`]
+    pub b: Option<Range<usize>>,
+    /// The bytes at `a`, described
+    pub a_item: Option<String>,
+    /// The bytes at `b`, described
+    pub b_item: Option<String>,
+}
+
+/// Find where two encoded CBOR items differ, structurally
+///
+/// Both items are walked together: array elements by index, and map
+/// entries by key, whatever their order or key encoding. Each item whose
+/// bytes differ is reported where the difference starts, with the byte
+/// ranges in both inputs, so an entry for an array does not also list its
+/// elements. Values that are equal but encoded differently are reported as
+/// [`DiffKind::Encoding`], which is what usually makes two supposedly
+/// identical documents hash differently. Returns an empty list for
+/// identical input, and an error if either input is malformed or has
+/// trailing data.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::{DiffKind, PathSegment, diag::diff_encodings};
+///
+/// // {"alg": "ps256", "pad": 1} vs {"pad": 1_0, "alg": "es256"}
+/// let a = [
+///     0xa2, 0x63, b'a', b'l', b'g', 0x65, b'p', b's', b'2', b'5', b'6', 0x63, b'p', b'a', b'd',
+///     0x01,
+/// ];
+/// let b = [
+///     0xa2, 0x63, b'p', b'a', b'd', 0x18, 0x01, 0x63, b'a', b'l', b'g', 0x65, b'e', b's', b'2',
+///     b'5', b'6',
+/// ];
+/// let diffs = diff_encodings(&a, &b).unwrap();
+/// assert_eq!(diffs.len(), 3);
+/// assert_eq!(diffs[0].kind, DiffKind::KeyOrder);
+///
+/// assert_eq!(diffs[1].path, [PathSegment::Key("alg".into())]);
+/// assert_eq!(diffs[1].kind, DiffKind::Value);
+/// assert_eq!(diffs[1].a, Some(5..11));
+/// assert_eq!(diffs[1].b, Some(11..17));
+///
+/// assert_eq!(diffs[2].path, [PathSegment::Key("pad".into())]);
+/// assert_eq!(diffs[2].kind, DiffKind::Encoding);
+/// assert_eq!(diffs[2].a_item.as_deref(), Some("1"));
+/// assert_eq!(diffs[2].b_item.as_deref(), Some("1_0"));
+/// ```
+pub fn diff_encodings(a: &[u8], b: &[u8]) -> Result<Vec<DiffEntry>> {
+    let a_node = parse_node(a)?;
+    let b_node = parse_node(b)?;
+    let mut differ = Differ {
+        a,
+        b,
+        path: Vec::new(),
+        entries: Vec::new(),
+    };
+    differ.compare(&a_node, &b_node);
+    Ok(differ.entries)
+}
+
+// An item parsed for `diff_encodings`, with the bytes it covers
+struct Node {
+    range: Range<usize>,
+    header_end: usize,
+    major: u8,
+    info: u8,
+    arg: Option<u64>,
+    content: Content,
+}
+
+enum Content {
+    Scalar,
+    // The content of a string, with the chunks of an indefinite one joined
+    String(Vec<u8>),
+    // Array elements, or map keys and values in turn
+    Items(Vec<Node>),
+    Tag(Box<Node>),
+}
+
+fn parse_node(bytes: &[u8]) -> Result<Node> {
+    if bytes.is_empty() {
+        return Err(Error::Syntax("empty input".to_string()));
+    }
+    let mut reader = SliceReader::new(bytes);
+    let node = read_node(&mut reader, 0)?;
+    if reader.remaining() > 0 {
+        return Err(Error::TrailingData);
+    }
+    Ok(node)
+}
+
+fn read_node(reader: &mut SliceReader<'_>, depth: usize) -> Result<Node> {
+    let start = reader.position();
+    let (major, info, arg) = reader.read_header()?;
+    let header_end = reader.position();
+    let content = match (major, arg) {
+        (MAJOR_UNSIGNED | MAJOR_NEGATIVE, Some(_)) => Content::Scalar,
+        (MAJOR_BYTES | MAJOR_TEXT, Some(len)) => {
+            let content = reader.read_slice(u64_to_usize(len)?)?;
+            if major == MAJOR_TEXT {
+                std::str::from_utf8(content).map_err(|_| Error::InvalidUtf8)?;
+            }
+            Content::String(content.to_vec())
+        }
+        (MAJOR_BYTES | MAJOR_TEXT, None) => {
+            let mut content = Vec::new();
+            while !at_break(reader)? {
+                let (chunk_major, _, chunk_len) = reader.read_header()?;
+                let Some(len) = chunk_len.filter(|_| chunk_major == major) else {
+                    return Err(Error::Syntax(
+                        "Indefinite string chunks must be definite strings of the same type"
+                            .to_string(),
+                    ));
+                };
+                content.extend_from_slice(reader.read_slice(u64_to_usize(len)?)?);
+            }
+            if major == MAJOR_TEXT {
+                std::str::from_utf8(&content).map_err(|_| Error::InvalidUtf8)?;
+            }
+            Content::String(content)
+        }
+        (MAJOR_ARRAY | MAJOR_MAP, len) => {
+            check_depth(depth)?;
+            let mut items = Vec::new();
+            match len {
+                Some(len) => {
+                    let count = if major == MAJOR_MAP {
+                        len.checked_mul(2).ok_or(Error::LengthOverflow(len))?
+                    } else {
+                        len
+                    };
+                    for _ in 0..count {
+                        items.push(read_node(reader, depth + 1)?);
+                    }
+                }
+                None => {
+                    while !at_break(reader)? {
+                        items.push(read_node(reader, depth + 1)?);
+                    }
+                    if major == MAJOR_MAP && items.len() % 2 == 1 {
+                        return Err(Error::Syntax("Map key without a value".to_string()));
+                    }
+                }
+            }
+            Content::Items(items)
+        }
+        (MAJOR_TAG, Some(_)) => {
+            check_depth(depth)?;
+            Content::Tag(Box::new(read_node(reader, depth + 1)?))
+        }
+        (MAJOR_SIMPLE, _) => match info {
+            INDEFINITE => return Err(Error::Syntax("Unexpected break".to_string())),
+            SIMPLE_VALUE if arg.is_some_and(|n| n < 32) => {
+                return Err(Error::Syntax("Invalid simple value".to_string()));
+            }
+            28..=30 => return Err(Error::Syntax("Invalid simple value".to_string())),
+            _ => Content::Scalar,
+        },
+        _ => return Err(Error::Syntax("Unexpected indefinite length".to_string())),
+    };
+    Ok(Node {
+        range: start..reader.position(),
+        header_end,
+        major,
+        info,
+        arg,
+        content,
+    })
+}
+
+impl Node {
+    fn header(&self) -> Range<usize> {
+        self.range.start..self.header_end
+    }
+
+    // Whether this is a float, rather than another simple value
+    fn is_float(&self) -> bool {
+        self.major == MAJOR_SIMPLE && matches!(self.info, FLOAT16 | FLOAT32 | FLOAT64)
+    }
+
+    // Location step for the value of the map entry with this key
+    fn key_segment(&self) -> PathSegment {
+        match (self.major, self.arg, &self.content) {
+            (MAJOR_TEXT, _, Content::String(text)) => {
+                PathSegment::Key(String::from_utf8_lossy(text).into_owned())
+            }
+            (MAJOR_UNSIGNED, Some(n), _) => PathSegment::IntKey(n as i128),
+            (MAJOR_NEGATIVE, Some(n), _) => PathSegment::IntKey(-1 - n as i128),
+            _ => PathSegment::OtherKey,
+        }
+    }
+
+    // Whether two scalars hold the same value, whatever their width
+    fn same_scalar(&self, other: &Node) -> bool {
+        match (self.is_float(), other.is_float()) {
+            (true, true) => {
+                let x = float_from_arg(self.info, self.arg.unwrap_or(0));
+                let y = float_from_arg(other.info, other.arg.unwrap_or(0));
+                x.to_bits() == y.to_bits() || (x.is_nan() && y.is_nan())
+            }
+            (false, false) if self.major == MAJOR_SIMPLE => {
+                self.arg.unwrap_or(self.info as u64) == other.arg.unwrap_or(other.info as u64)
+            }
+            (false, false) => self.arg == other.arg,
+            _ => false,
+        }
+    }
+}
+
+// Header of an array, map or tag, as described by `annotate`
+fn describe_header(node: &Node) -> String {
+    let kind = match node.major {
+        MAJOR_ARRAY => "array",
+        MAJOR_MAP => "map",
+        _ => "tag",
+    };
+    let Some(arg) = node.arg else {
+        return format!("{}(indefinite)", kind);
+    };
+    let note = match Argument::from_info(node.info, arg) {
+        Some(argument) if !argument.is_preferred() => ", not preferred",
+        _ => "",
+    };
+    format!("{}({}){}", kind, arg, note)
+}
+
+struct Differ<'a> {
+    a: &'a [u8],
+    b: &'a [u8],
+    path: Vec<PathSegment>,
+    entries: Vec<DiffEntry>,
+}
+
+impl Differ<'_> {
+    fn push(&mut self, kind: DiffKind, a: Option<Range<usize>>, b: Option<Range<usize>>) {
+        // Both inputs parsed, so every whole item renders
+        let describe = |bytes: &[u8], range: &Range<usize>| {
+            to_diagnostic(&bytes[range.clone()]).unwrap_or_default()
+        };
+        self.entries.push(DiffEntry {
+            path: self.path.clone(),
+            kind,
+            a_item: a.as_ref().map(|range| describe(self.a, range)),
+            b_item: b.as_ref().map(|range| describe(self.b, range)),
+            a,
+            b,
+        });
+    }
+
+    fn push_header(&mut self, x: &Node, y: &Node) {
+        self.entries.push(DiffEntry {
+            path: self.path.clone(),
+            kind: DiffKind::Encoding,
+            a: Some(x.header()),
+            b: Some(y.header()),
+            a_item: Some(describe_header(x)),
+            b_item: Some(describe_header(y)),
+        });
+    }
+
+    fn compare(&mut self, x: &Node, y: &Node) {
+        if self.a[x.range.clone()] == self.b[y.range.clone()] {
+            return;
+        }
+        let (a, b) = (Some(x.range.clone()), Some(y.range.clone()));
+        if x.major != y.major {
+            return self.push(DiffKind::Value, a, b);
+        }
+        match (&x.content, &y.content) {
+            (Content::Scalar, Content::Scalar) => {
+                let kind = if x.same_scalar(y) {
+                    DiffKind::Encoding
+                } else {
+                    DiffKind::Value
+                };
+                self.push(kind, a, b);
+            }
+            (Content::String(s), Content::String(t)) => {
+                let kind = if s == t {
+                    DiffKind::Encoding
+                } else {
+                    DiffKind::Value
+                };
+                self.push(kind, a, b);
+            }
+            (Content::Tag(s), Content::Tag(t)) if x.arg == y.arg => {
+                if self.a[x.header()] != self.b[y.header()] {
+                    self.push_header(x, y);
+                }
+                self.compare(s, t);
+            }
+            (Content::Items(xs), Content::Items(ys)) if x.major == MAJOR_ARRAY => {
+                if xs.len() == ys.len() && self.a[x.header()] != self.b[y.header()] {
+                    self.push_header(x, y);
+                }
+                for index in 0..xs.len().max(ys.len()) {
+                    self.path.push(PathSegment::Index(index));
+                    match (xs.get(index), ys.get(index)) {
+                        (Some(s), Some(t)) => self.compare(s, t),
+                        (Some(s), None) => {
+                            self.push(DiffKind::OnlyInA, Some(s.range.clone()), None)
+                        }
+                        (None, Some(t)) => {
+                            self.push(DiffKind::OnlyInB, None, Some(t.range.clone()))
+                        }
+                        (None, None) => unreachable!(),
+                    }
+                    self.path.pop();
+                }
+            }
+            (Content::Items(xs), Content::Items(ys)) => self.compare_maps(x, xs, y, ys),
+            _ => self.push(DiffKind::Value, a, b),
+        }
+    }
+
+    fn compare_maps(&mut self, x: &Node, xs: &[Node], y: &Node, ys: &[Node]) {
+        // Keys match when they encode the same value
+        let canonical = |bytes: &[u8], key: &Node| {
+            let key = &bytes[key.range.clone()];
+            crate::canonicalize(key).unwrap_or_else(|_| key.to_vec())
+        };
+        let mut b_keys: Vec<Option<Vec<u8>>> = ys
+            .chunks(2)
+            .map(|entry| Some(canonical(self.b, &entry[0])))
+            .collect();
+        let mut matched = Vec::new();
+        let mut only_in_a = Vec::new();
+        for (index, entry) in xs.chunks(2).enumerate() {
+            let key = canonical(self.a, &entry[0]);
+            match b_keys.iter().position(|k| k.as_ref() == Some(&key)) {
+                Some(found) => {
+                    b_keys[found] = None;
+                    matched.push((index, found));
+                }
+                None => only_in_a.push(index),
+            }
+        }
+
+        if xs.len() == ys.len() && self.a[x.header()] != self.b[y.header()] {
+            self.push_header(x, y);
+        }
+        if matched.windows(2).any(|pair| pair[0].1 > pair[1].1) {
+            self.push(
+                DiffKind::KeyOrder,
+                Some(x.range.clone()),
+                Some(y.range.clone()),
+            );
+        }
+        let entry_range = |entry: &[Node]| entry[0].range.start..entry[1].range.end;
+        for (i, j) in matched {
+            let (s, t) = (&xs[2 * i..2 * i + 2], &ys[2 * j..2 * j + 2]);
+            self.path.push(s[0].key_segment());
+            self.compare(&s[0], &t[0]);
+            self.compare(&s[1], &t[1]);
+            self.path.pop();
+        }
+        for i in only_in_a {
+            let s = &xs[2 * i..2 * i + 2];
+            self.path.push(s[0].key_segment());
+            self.push(DiffKind::OnlyInA, Some(entry_range(s)), None);
+            self.path.pop();
+        }
+        for (j, key) in b_keys.iter().enumerate() {
+            if key.is_some() {
+                let t = &ys[2 * j..2 * j + 2];
+                self.path.push(t[0].key_segment());
+                self.push(DiffKind::OnlyInB, None, Some(entry_range(t)));
+                self.path.pop();
+            }
+        }
+    }
+}
+
+/// Diagnostic notation for a [`Value`]
+///
+/// The alternate form `{:#}` puts each array element and map entry on its
+/// own line, indented by two spaces per level.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::Value;
+///
+/// let value = Value::Map(
+///     [
+///         (Value::Text("a".into()), Value::Array(vec![Value::Integer(1), Value::Bytes(vec![0xff])])),
+///         (Value::Text("b".into()), Value::Tag(32, Box::new(Value::Text("x".into())))),
+///     ]
+///     .into(),
+/// );
+/// assert_eq!(value.to_string(), r#"{"a": [1, h'ff'], "b": 32("x")}"#);
+/// assert_eq!(
+///     format!("{:#}", value),
+///     "{\n  \"a\": [\n    1,\n    h'ff'\n  ],\n  \"b\": 32(\"x\")\n}"
+/// );
+/// ```
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        write_value(&mut out, self, f.alternate(), 0);
+        f.write_str(&out)
+    }
+}
+
+fn write_value(out: &mut String, value: &Value, pretty: bool, depth: usize) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Undefined => out.push_str("undefined"),
+        Value::Bool(b) => {
+            let _ = write!(out, "{}", b);
+        }
+        Value::Integer(i) => {
+            let _ = write!(out, "{}", i);
+        }
+        Value::Float(f) => write_float_value(out, *f),
+        Value::Bytes(b) => write_hex(out, b),
+        Value::Text(s) => write_escaped(out, s),
+        Value::Array(items) => {
+            write_container(out, ('[', ']'), items.iter(), pretty, depth, |out, item| {
+                write_value(out, item, pretty, depth + 1)
+            })
+        }
+        Value::Map(map) => {
+            write_container(out, ('{', '}'), map.iter(), pretty, depth, |out, entry| {
+                write_entry(out, entry, pretty, depth + 1)
+            })
+        }
+        Value::OrderedMap(entries) => write_container(
+            out,
+            ('{', '}'),
+            entries.iter().map(|(k, v)| (k, v)),
+            pretty,
+            depth,
+            |out, entry| write_entry(out, entry, pretty, depth + 1),
+        ),
+        Value::Tag(tag, content) => {
+            let _ = write!(out, "{}(", tag);
+            write_value(out, content, pretty, depth);
+            out.push(')');
+        }
+        Value::Simple(n) => {
+            let _ = write!(out, "simple({})", n);
+        }
+    }
+}
+
+fn write_entry(out: &mut String, (key, value): (&Value, &Value), pretty: bool, depth: usize) {
+    write_value(out, key, pretty, depth);
+    out.push_str(": ");
+    write_value(out, value, pretty, depth);
+}
+
+fn write_container<T>(
+    out: &mut String,
+    (open, close): (char, char),
+    items: impl ExactSizeIterator<Item = T>,
+    pretty: bool,
+    depth: usize,
+    mut write: impl FnMut(&mut String, T),
+) {
+    out.push(open);
+    let len = items.len();
+    for (i, item) in items.enumerate() {
+        if pretty {
+            out.push_str(if i == 0 { "\n" } else { ",\n" });
+            indent(out, depth + 1);
+        } else if i > 0 {
+            out.push_str(", ");
+        }
+        write(out, item);
+    }
+    if pretty && len > 0 {
+        out.push('\n');
+        indent(out, depth);
+    }
+    out.push(close);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostic_scalars() {
+        let cases: [(&[u8], &str); 12] = [
+            (&[0x17], "23"),
+            (&[0x19, 0x00, 0x01], "1_1"),
+            (&[0x38, 0x63], "-100"),
+            (
+                &[0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+                "-18446744073709551616",
+            ),
+            (&[0x62, b'"', b'\n'], "\"\\\"\\n\""),
+            (&[0x42, 0x00, 0xab], "h'00ab'"),
+            (&[0xf9, 0x3e, 0x00], "1.5_1"),
+            (&[0xfa, 0x7f, 0x80, 0x00, 0x00], "Infinity_2"),
+            (&[0xfb, 0x7f, 0xf8, 0, 0, 0, 0, 0, 0], "NaN_3"),
+            (&[0xf7], "undefined"),
+            (&[0xf8, 0xff], "simple(255)"),
+            (&[0xd9, 0x01, 0x00, 0x40], "256(h'')"),
+        ];
+        for (bytes, expected) in cases {
+            assert_eq!(to_diagnostic(bytes).unwrap(), expected, "{:02x?}", bytes);
+        }
+    }
+
+    #[test]
+    fn test_diagnostic_indefinite_and_empty() {
+        // [_ (_ "a", "b"), {}, []]
+        let cbor = [0x9f, 0x7f, 0x61, b'a', 0x61, b'b', 0xff, 0xa0, 0x80, 0xff];
+        assert_eq!(
+            to_diagnostic(&cbor).unwrap(),
+            "[_\n  (_ \"a\", \"b\"),\n  {},\n  []\n]"
+        );
+        assert_eq!(to_diagnostic(&[0xbf, 0xff]).unwrap(), "{_ }");
+        assert_eq!(to_diagnostic(&[0x98, 0x00]).unwrap(), "[_0]");
+    }
+
+    #[test]
+    fn test_diagnostic_rejects_malformed() {
+        assert!(to_diagnostic(&[]).is_err());
+        assert!(to_diagnostic(&[0x01, 0x01]).is_err());
+        assert!(to_diagnostic(&[0xbf, 0x01, 0xff]).is_err());
+        assert!(to_diagnostic(&[0x5f, 0x61, b'a', 0xff]).is_err());
+        assert!(to_diagnostic(&[0x61, 0xff]).is_err());
+        assert!(to_diagnostic(&[0xf8, 0x10]).is_err());
+    }
+
+    #[test]
+    fn test_display_value() {
+        let value = Value::Array(vec![
+            Value::Null,
+            Value::Undefined,
+            Value::Bool(false),
+            Value::Integer(-7),
+            Value::Float(1.0),
+            Value::Float(f64::NEG_INFINITY),
+            Value::Text("q\"\n".to_string()),
+            Value::Simple(99),
+            Value::Map(Default::default()),
+            Value::OrderedMap(vec![
+                (Value::Integer(2), Value::Array(vec![])),
+                (Value::Integer(1), Value::Bytes(vec![1, 2])),
+            ]),
+        ]);
+        assert_eq!(
+            value.to_string(),
+            r#"[null, undefined, false, -7, 1.0, -Infinity, "q\"\n", simple(99), {}, {2: [], 1: h'0102'}]"#
+        );
+        assert_eq!(
+            format!("{:#}", value.as_array().unwrap()[9]),
+            "{\n  2: [],\n  1: h'0102'\n}"
+        );
+
+        // The alternate form lays out preferred encodings as to_diagnostic does
+        let value = Value::Map(
+            [(
+                Value::Text("list".into()),
+                Value::Tag(24, Box::new(Value::Array(vec![Value::Integer(1)]))),
+            )]
+            .into(),
+        );
+        assert_eq!(
+            format!("{:#}", value),
+            to_diagnostic(&value.to_canonical_vec()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_annotate() {
+        // [_ (_ h'01'), -1, 1.5, simple(99), 24(h'<17 bytes>')]
+        let mut cbor = vec![
+            0x9f, 0x5f, 0x41, 0x01, 0xff, 0x20, 0xf9, 0x3e, 0x00, 0xf8, 0x63, 0xd8, 0x18, 0x51,
+        ];
+        cbor.extend(0..17);
+        cbor.push(0xff);
+        assert_eq!(
+            annotate(&cbor).unwrap(),
+            [
+                "0000  9f                                                        # array(indefinite)",
+                "0001     5f                                                     #   bytes(indefinite)",
+                "0002        41                                                  #     bytes(1)",
+                "0003           01",
+                "0004        ff                                                  #     break",
+                "0005     20                                                     #   negative(-1)",
+                "0006     f9 3e 00                                               #   float16(1.5)",
+                "0009     f8 63                                                  #   simple(99)",
+                "000b     d8 18                                                  #   tag(24) encoded CBOR item",
+                "000d        51                                                  #     bytes(17)",
+                "000e           00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f",
+                "001e           10",
+                "001f     ff                                                     #   break",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_annotate_rejects_malformed() {
+        assert!(annotate(&[]).is_err());
+        assert!(annotate(&[0x01, 0x01]).is_err());
+        assert!(annotate(&[0xbf, 0x01, 0xff]).is_err());
+        assert!(annotate(&[0x5f, 0x61, b'a', 0xff]).is_err());
+        assert!(annotate(&[0x62, 0xff, 0xfe]).is_err());
+        assert!(annotate(&[0xff]).is_err());
+    }
+
+    #[test]
+    fn test_diff_encodings() {
+        assert!(
+            diff_encodings(&[0x82, 0x01, 0x02], &[0x82, 0x01, 0x02])
+                .unwrap()
+                .is_empty()
+        );
+
+        // [1, 1.5, 32("a")] vs [_ 1, 1.5_3, 32_0("a"), 4]
+        let a = [0x83, 0x01, 0xf9, 0x3e, 0x00, 0xd8, 0x20, 0x61, b'a'];
+        let b = [
+            0x9f, 0x01, 0xfb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0, 0xd9, 0x00, 0x20, 0x61, b'a', 0x04,
+            0xff,
+        ];
+        let diffs = diff_encodings(&a, &b).unwrap();
+        let summary: Vec<_> = diffs
+            .iter()
+            .map(|d| (d.path.clone(), d.kind, d.a.clone(), d.b.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    vec![PathSegment::Index(1)],
+                    DiffKind::Encoding,
+                    Some(2..5),
+                    Some(2..11)
+                ),
+                (
+                    vec![PathSegment::Index(2)],
+                    DiffKind::Encoding,
+                    Some(5..7),
+                    Some(11..14)
+                ),
+                (
+                    vec![PathSegment::Index(3)],
+                    DiffKind::OnlyInB,
+                    None,
+                    Some(16..17)
+                ),
+            ]
+        );
+        assert_eq!(diffs[0].b_item.as_deref(), Some("1.5_3"));
+        assert_eq!(diffs[1].b_item.as_deref(), Some("tag(32), not preferred"));
+
+        // The same length in a different width is an encoding difference
+        let diffs = diff_encodings(&[0x81, 0x00], &[0x98, 0x01, 0x00]).unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].kind, DiffKind::Encoding);
+        assert_eq!(diffs[0].a_item.as_deref(), Some("array(1)"));
+        assert_eq!(diffs[0].b_item.as_deref(), Some("array(1), not preferred"));
+
+        // {1: h'00', -1: 0} vs {_ 1: (_ h'00'), 2: 0}
+        let a = [0xa2, 0x01, 0x41, 0x00, 0x20, 0x00];
+        let b = [0xbf, 0x01, 0x5f, 0x41, 0x00, 0xff, 0x02, 0x00, 0xff];
+        let diffs = diff_encodings(&a, &b).unwrap();
+        let summary: Vec<_> = diffs.iter().map(|d| (d.path.clone(), d.kind)).collect();
+        assert_eq!(
+            summary,
+            [
+                (vec![], DiffKind::Encoding),
+                (vec![PathSegment::IntKey(1)], DiffKind::Encoding),
+                (vec![PathSegment::IntKey(-1)], DiffKind::OnlyInA),
+                (vec![PathSegment::IntKey(2)], DiffKind::OnlyInB),
+            ]
+        );
+        assert_eq!(diffs[0].b_item.as_deref(), Some("map(indefinite)"));
+        assert_eq!(diffs[2].a, Some(4..6));
+
+        // Different types, tags or simple values are different values
+        let diffs = diff_encodings(&[0xf4], &[0xf6]).unwrap();
+        assert_eq!(diffs[0].kind, DiffKind::Value);
+        let diffs = diff_encodings(&[0xc1, 0x00], &[0xc0, 0x00]).unwrap();
+        assert_eq!(diffs[0].kind, DiffKind::Value);
+        let diffs = diff_encodings(&[0x01], &[0x61, b'1']).unwrap();
+        assert_eq!(diffs[0].kind, DiffKind::Value);
+
+        assert!(diff_encodings(&[], &[0x00]).is_err());
+        assert!(diff_encodings(&[0x00], &[0x00, 0x00]).is_err());
+        assert!(diff_encodings(&[0xbf, 0x01, 0xff], &[0xa0]).is_err());
+    }
+}
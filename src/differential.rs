@@ -0,0 +1,196 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Differential testing against reference decoders
+//!
+//! Enabled with the `differential` feature, which also enables the
+//! `ciborium` and `serde_cbor` conversions. [`compare`] decodes the same
+//! input with this crate and with a reference decoder and reports a
+//! [`Divergence`] when one accepts the input and the other rejects it, or
+//! when both accept it but produce different values. It is meant to be
+//! driven by a fuzzer or a corpus of interesting inputs.
+//!
+//! Decoding into [`Value`] passes through tags, so tags are removed from the
+//! reference result before comparing. Values are compared with
+//! [`Value`]'s total order, so NaNs compare equal. Error messages are not
+//! compared; two rejections always agree.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::differential::{Reference, compare};
+//!
+//! // [1, "a", {2: h'ff'}]
+//! let input = [0x83, 0x01, 0x61, b'a', 0xa1, 0x02, 0x41, 0xff];
+//! assert!(compare(&input, Reference::Ciborium).is_none());
+//! assert!(compare(&input, Reference::SerdeCbor).is_none());
+//! ```
+
+use std::fmt;
+
+use crate::{Result, Value};
+
+/// Reference decoder to compare against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reference {
+    /// `ciborium::from_reader` into `ciborium::value::Value`
+    Ciborium,
+    /// `serde_cbor::from_slice` into `serde_cbor::Value`
+    SerdeCbor,
+}
+
+impl Reference {
+    /// All supported reference decoders
+    pub const ALL: [Reference; 2] = [Reference::Ciborium, Reference::SerdeCbor];
+
+    /// Decode `input` with this reference and convert the result to a [`Value`]
+    pub fn decode(self, input: &[u8]) -> std::result::Result<Value, String> {
+        match self {
+            Reference::Ciborium => {
+                let mut reader = input;
+                let value: ciborium::value::Value =
+                    ciborium::de::from_reader(&mut reader).map_err(|e| e.to_string())?;
+                if !reader.is_empty() {
+                    return Err("trailing data".to_string());
+                }
+                Value::try_from(value).map_err(|e| e.to_string())
+            }
+            Reference::SerdeCbor => {
+                let value: serde_cbor::Value =
+                    serde_cbor::from_slice(input).map_err(|e| e.to_string())?;
+                Value::try_from(value).map_err(|e| e.to_string())
+            }
+        }
+    }
+}
+
+/// Outcome of decoding the same input with this crate and a reference
+#[derive(Debug, Clone)]
+pub struct Divergence {
+    /// The reference decoder that disagreed
+    pub reference: Reference,
+    /// Result from this crate
+    pub ours: std::result::Result<Value, String>,
+    /// Result from the reference, with tags removed
+    pub theirs: std::result::Result<Value, String>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} disagrees: ours {:?}, theirs {:?}",
+            self.reference, self.ours, self.theirs
+        )
+    }
+}
+
+fn decode_ours(input: &[u8]) -> Result<Value> {
+    crate::from_slice(input)
+}
+
+/// Decode `input` with this crate and `reference`, returning any disagreement
+pub fn compare(input: &[u8], reference: Reference) -> Option<Divergence> {
+    let ours = decode_ours(input).map_err(|e| e.to_string());
+    let theirs = reference.decode(input).map(Value::into_untagged);
+    let agree = match (&ours, &theirs) {
+        (Ok(a), Ok(b)) => a.cmp(b) == std::cmp::Ordering::Equal,
+        (Err(_), Err(_)) => true,
+        _ => false,
+    };
+    (!agree).then_some(Divergence {
+        reference,
+        ours,
+        theirs,
+    })
+}
+
+/// Compare `input` against every reference decoder
+pub fn compare_all(input: &[u8]) -> Vec<Divergence> {
+    Reference::ALL
+        .iter()
+        .filter_map(|&reference| compare(input, reference))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_differential_corpus_agrees() {
+        let corpus: &[&[u8]] = &[
+            // Integers, floats, strings and simple values
+            &[0x00],
+            &[0x3b, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            &[0xf9, 0x3c, 0x00],
+            &[0xfb, 0x40, 0x09, 0x21, 0xfb, 0x54, 0x44, 0x2d, 0x18],
+            &[0x63, b'a', b'b', b'c'],
+            &[0x43, 0x01, 0x02, 0x03],
+            &[0xf4],
+            &[0xf6],
+            // Indefinite-length strings and containers
+            &[0x5f, 0x41, 0x01, 0x41, 0x02, 0xff],
+            &[0x7f, 0x61, b'a', 0x61, b'b', 0xff],
+            &[0x9f, 0x01, 0x82, 0x02, 0x03, 0xff],
+            &[0xbf, 0x61, b'a', 0x01, 0xff],
+            // Tags are compared by content
+            &[0xd8, 0x20, 0x61, b'x'],
+            &[0xc1, 0x1a, 0x65, 0xa5, 0x08, 0x38],
+            // Malformed input is rejected by everyone
+            &[],
+            &[0x18],
+            &[0x62, b'a'],
+            &[0x1c],
+            &[0x82, 0x01],
+            &[0x01, 0x02],
+            &[0xff],
+        ];
+        for input in corpus {
+            let divergences = compare_all(input);
+            assert!(divergences.is_empty(), "{:02x?}: {}", input, divergences[0]);
+        }
+    }
+
+    #[test]
+    fn test_differential_reports_known_divergence() {
+        // Value holds i64 integers, so u64::MAX is rejected here but accepted
+        // by both references
+        let input = [0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+        let divergences = compare_all(&input);
+        assert_eq!(divergences.len(), 2);
+        assert!(
+            divergences
+                .iter()
+                .all(|d| d.ours.is_err() && d.theirs.is_ok())
+        );
+    }
+
+    #[test]
+    fn test_differential_mutations_do_not_panic() {
+        // Flip bytes of a valid seed with a fixed xorshift sequence
+        let seed = crate::to_vec(&(1u8, "text", vec![1.5f64], Some(b'x'))).unwrap();
+        let mut state: u32 = 0x2545_f491;
+        for _ in 0..2000 {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let mut input = seed.clone();
+            let index = state as usize % input.len();
+            input[index] ^= (state >> 8) as u8;
+            input.truncate(input.len() - (state >> 24) as usize % 3);
+            for divergence in compare_all(&input) {
+                assert!(divergence.to_string().contains("disagrees"));
+            }
+        }
+    }
+}
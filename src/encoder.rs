@@ -13,41 +13,518 @@
 
 // Portions derived from serde_cbor (https://github.com/pyfisch/cbor)
 
-use std::io::Write;
+use std::io::{IoSlice, Write};
 
 use serde::Serialize;
 
-use crate::{Error, Result, constants::*};
+use crate::{
+    Error, Header, Result, Value,
+    constants::*,
+    keys::FieldKey,
+    raw::{SliceReader, u64_to_usize},
+    tags::{Tagged, TaggedSerialize},
+};
+
+/// Middleware hook for top-level items written by [`Encoder::encode`]
+///
+/// Each hook sees the complete encoding of an item before it reaches the
+/// underlying writer. Hooks can observe the bytes (e.g. to feed a hasher, a
+/// logger or a size accountant) or wrap them by returning a replacement.
+///
+/// Hooks run in registration order: each hook's [`wrap`](EncodeHook::wrap) receives
+/// the output of the previous hook, and its [`observe`](EncodeHook::observe) sees the
+/// bytes after its own `wrap` has run. Raw header writes such as
+/// [`Encoder::write_tag`] are not items and pass through unobserved.
+///
+/// Closures of the form `FnMut(&[u8]) -> Result<()>` implement this trait as
+/// observers.
+///
+/// # Example
+/// ```
+/// use std::sync::{
+///     Arc,
+///     atomic::{AtomicUsize, Ordering},
+/// };
+///
+/// use c2pa_cbor::{EncodeHook, Encoder, Result};
+///
+/// struct SizeCounter(Arc<AtomicUsize>);
+///
+/// impl EncodeHook for SizeCounter {
+///     fn observe(&mut self, item: &[u8]) -> Result<()> {
+///         self.0.fetch_add(item.len(), Ordering::Relaxed);
+///         Ok(())
+///     }
+/// }
+///
+/// let total = Arc::new(AtomicUsize::new(0));
+/// let mut encoder = Encoder::new(Vec::new()).with_hook(SizeCounter(total.clone()));
+/// encoder.encode(&"hello").unwrap();
+/// encoder.encode(&42u8).unwrap();
+/// assert_eq!(total.load(Ordering::Relaxed), encoder.into_inner().len());
+/// ```
+pub trait EncodeHook {
+    /// Called with the (possibly wrapped) bytes of each top-level item
+    fn observe(&mut self, _item: &[u8]) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called with the bytes of each top-level item; the returned bytes are emitted instead
+    fn wrap(&mut self, item: Vec<u8>) -> Result<Vec<u8>> {
+        Ok(item)
+    }
+}
+
+impl<F: FnMut(&[u8]) -> Result<()>> EncodeHook for F {
+    fn observe(&mut self, item: &[u8]) -> Result<()> {
+        self(item)
+    }
+}
+
+/// Options that change the shape of the CBOR written by an [`Encoder`]
+///
+/// The defaults produce standard CBOR that other libraries read the same
+/// way. Options are set with [`Encoder::with_options`] or the matching
+/// builder methods.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::{Encoder, EncoderOptions};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Label(String);
+///
+/// let options = EncoderOptions {
+///     legacy_newtypes: true,
+///     ..Default::default()
+/// };
+/// let mut encoder = Encoder::new(Vec::new()).with_options(options);
+/// encoder.encode(&Label("a".into())).unwrap();
+/// assert_eq!(encoder.into_inner(), [0x81, 0x61, b'a']);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EncoderOptions {
+    /// Stream sequences and maps of unknown length instead of buffering them,
+    /// see [`Encoder::with_indefinite_lengths`]
+    pub indefinite_lengths: bool,
+    /// Wrap newtype structs in a 1-element array, see [`Encoder::with_legacy_newtypes`]
+    pub legacy_newtypes: bool,
+    /// How enum variants are identified, see [`Encoder::with_variant_keys`]
+    pub variant_keys: VariantKeys,
+    /// Leave out struct fields whose value is `None`, see [`Encoder::with_omit_none_fields`]
+    pub omit_none_fields: bool,
+    /// How NaN floats are written, see [`Encoder::with_nan_policy`]
+    pub nan_policy: NanPolicy,
+    /// Most bytes to write, see [`Encoder::with_max_output_len`]
+    pub max_output_len: Option<u64>,
+    /// Write repeated strings as references, see [`Encoder::with_stringrefs`]
+    pub stringrefs: bool,
+}
+
+/// How NaN floats are written
+///
+/// NaN has many encodings: any payload bits, in any float width. Deterministic
+/// profiles such as RFC 8949 §4.2.2 and dCBOR require the single half-precision
+/// quiet NaN `0xf97e00`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NanPolicy {
+    /// Write NaN like any other float, keeping its width and payload bits
+    #[default]
+    Preserve,
+    /// Write every NaN as `0xf97e00`
+    Canonical,
+    /// Return an error for NaN
+    Error,
+}
+
+/// How enum variants are identified on the wire
+///
+/// A unit variant is written as just its key; a variant with data is
+/// written as a single-entry map from its key to the data. Integer keys are
+/// read back according to [`Decoder::with_variant_keys`](crate::Decoder::with_variant_keys);
+/// text keys are always matched against the variant names.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VariantKeys {
+    /// The variant name as a text string, e.g. `{"Circle": 1.5}`
+    #[default]
+    Name,
+    /// The variant index (its position in the enum, from 0) as an unsigned integer
+    Index,
+    /// Variant names that are decimal integers, such as those renamed with
+    /// `#[serde(rename = "100")]`, as unsigned integers; other names as text
+    NumericName,
+}
 
 // Encoder
 pub struct Encoder<W: Write> {
-    writer: W,
+    writer: CountingWriter<W>,
+    hooks: Vec<Box<dyn EncodeHook + Send>>,
+    options: EncoderOptions,
+    // Set by serialize_none, so an omitted None field can be told from a unit value
+    none_written: bool,
 }
 
 impl<W: Write> Encoder<W> {
     pub fn new(writer: W) -> Self {
-        Encoder { writer }
+        Encoder {
+            writer: CountingWriter::new(writer),
+            hooks: Vec::new(),
+            options: EncoderOptions::default(),
+            none_written: false,
+        }
+    }
+
+    /// Replace all encoding options at once (builder pattern)
+    pub fn with_options(mut self, options: EncoderOptions) -> Self {
+        self.options = options;
+        self.writer.limit = options.max_output_len;
+        self
+    }
+
+    /// The encoding options in effect
+    pub fn options(&self) -> EncoderOptions {
+        self.options
+    }
+
+    /// Write sequences and maps of unknown length as indefinite-length items (builder pattern)
+    ///
+    /// By default, when serde does not know the length of a sequence or map
+    /// up front, the elements are buffered in memory so that a
+    /// definite-length header can be written once they are all known. With
+    /// this option the encoder instead writes an indefinite-length header,
+    /// streams each element straight to the writer and ends with a break,
+    /// so memory use no longer grows with the number of elements.
+    ///
+    /// Indefinite lengths are not allowed in deterministic encoding (RFC 8949
+    /// §4.2.1), so leave this off for anything that will be signed.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::Encoder;
+    /// use serde::Serializer;
+    ///
+    /// let mut encoder = Encoder::new(Vec::new()).with_indefinite_lengths();
+    /// (&mut encoder).collect_seq((1..=3).filter(|n| n % 2 == 1)).unwrap();
+    /// assert_eq!(encoder.into_inner(), [0x9f, 0x01, 0x03, 0xff]);
+    /// ```
+    pub fn with_indefinite_lengths(mut self) -> Self {
+        self.options.indefinite_lengths = true;
+        self
+    }
+
+    /// Wrap every newtype struct in a 1-element array (builder pattern)
+    ///
+    /// Newtype structs such as `struct Label(String)` are written as their
+    /// inner value, like serde_cbor, ciborium and serde's other formats do.
+    /// Earlier releases of this crate wrapped them in a 1-element array
+    /// instead; this option reproduces that output for readers that still
+    /// expect it. [`Decoder::with_legacy_newtypes`](crate::Decoder::with_legacy_newtypes)
+    /// reads it back.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::Encoder;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Label(String);
+    ///
+    /// let mut encoder = Encoder::new(Vec::new()).with_legacy_newtypes();
+    /// encoder.encode(&Label("a".into())).unwrap();
+    /// assert_eq!(encoder.into_inner(), [0x81, 0x61, b'a']);
+    /// ```
+    pub fn with_legacy_newtypes(mut self) -> Self {
+        self.options.legacy_newtypes = true;
+        self
+    }
+
+    /// Choose how enum variants are identified (builder pattern)
+    ///
+    /// Wire formats that key variants by number, such as `{1: {...}}`, are
+    /// written with [`VariantKeys::Index`] when the numbers are the variant
+    /// positions, or with [`VariantKeys::NumericName`] and
+    /// `#[serde(rename = "1")]` on each variant for arbitrary numbers.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::{Decoder, Encoder, VariantKeys};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    /// enum Action {
+    ///     #[serde(rename = "1")]
+    ///     Created { by: String },
+    ///     #[serde(rename = "7")]
+    ///     Deleted,
+    /// }
+    ///
+    /// let mut encoder = Encoder::new(Vec::new()).with_variant_keys(VariantKeys::NumericName);
+    /// encoder.encode(&Action::Deleted).unwrap();
+    /// let cbor = encoder.into_inner();
+    /// assert_eq!(cbor, [0x07]);
+    ///
+    /// let mut decoder = Decoder::new(&cbor[..]).with_variant_keys(VariantKeys::NumericName);
+    /// assert_eq!(decoder.decode::<Action>().unwrap(), Action::Deleted);
+    /// ```
+    pub fn with_variant_keys(mut self, variant_keys: VariantKeys) -> Self {
+        self.options.variant_keys = variant_keys;
+        self
+    }
+
+    /// Leave out struct fields whose value is `None` (builder pattern)
+    ///
+    /// This has the effect of `#[serde(skip_serializing_if = "Option::is_none")]`
+    /// on every `Option` field of every struct and struct variant, so an
+    /// absent field means `None`. Fields of unit type, which are also written
+    /// as null, are kept. Use the [`nullable`](crate::nullable) module for
+    /// fields that must still be able to hold an explicit null.
+    ///
+    /// Because the number of fields is only known once they are all
+    /// encoded, structs are buffered like maps of unknown length.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::Encoder;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Claim {
+    ///     title: Option<String>,
+    ///     format: Option<String>,
+    /// }
+    ///
+    /// let claim = Claim {
+    ///     title: None,
+    ///     format: Some("jpg".into()),
+    /// };
+    /// let mut encoder = Encoder::new(Vec::new()).with_omit_none_fields();
+    /// encoder.encode(&claim).unwrap();
+    /// let cbor = encoder.into_inner();
+    /// assert_eq!(
+    ///     c2pa_cbor::to_diagnostic(&cbor).unwrap(),
+    ///     "{\n  \"format\": \"jpg\"\n}"
+    /// );
+    /// ```
+    pub fn with_omit_none_fields(mut self) -> Self {
+        self.options.omit_none_fields = true;
+        self
+    }
+
+    /// Choose how NaN floats are written (builder pattern)
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::{Encoder, NanPolicy};
+    ///
+    /// let mut encoder = Encoder::new(Vec::new()).with_nan_policy(NanPolicy::Canonical);
+    /// encoder.encode(&f64::from_bits(0x7ff8_0000_0000_0001)).unwrap();
+    /// assert_eq!(encoder.into_inner(), [0xf9, 0x7e, 0x00]);
+    ///
+    /// let mut encoder = Encoder::new(Vec::new()).with_nan_policy(NanPolicy::Error);
+    /// assert!(encoder.encode(&f32::NAN).is_err());
+    /// ```
+    pub fn with_nan_policy(mut self, nan_policy: NanPolicy) -> Self {
+        self.options.nan_policy = nan_policy;
+        self
+    }
+
+    /// Fail with [`Error::OutputLimitExceeded`] rather than write more than
+    /// `max_len` bytes (builder pattern)
+    ///
+    /// The limit covers everything written to the inner writer, as counted by
+    /// [`bytes_written`](Self::bytes_written), and is checked before each
+    /// write, so the writer never receives bytes past it. What was written
+    /// before the failing call stays written. After [`reset`](Self::reset)
+    /// the limit applies to the new writer.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::{Encoder, Error};
+    ///
+    /// let mut encoder = Encoder::new(Vec::new()).with_max_output_len(8);
+    /// encoder.encode(&"fits").unwrap();
+    /// assert!(matches!(
+    ///     encoder.encode(&"too long"),
+    ///     Err(Error::OutputLimitExceeded(8))
+    /// ));
+    /// assert_eq!(encoder.into_inner(), b"\x64fits");
+    /// ```
+    pub fn with_max_output_len(mut self, max_len: u64) -> Self {
+        self.options.max_output_len = Some(max_len);
+        self.writer.limit = Some(max_len);
+        self
+    }
+
+    /// Write repeated strings as references to their first occurrence (builder pattern)
+    ///
+    /// Each item written with [`encode`](Self::encode) or another top-level
+    /// method is encoded into a temporary buffer and then
+    /// [packed](crate::stringref::pack): it is wrapped in a string namespace
+    /// (tag 256) and each repeat of a long enough string is replaced with a
+    /// reference (tag 25) to its first occurrence. Decode the result with
+    /// [`stringref::from_slice`](crate::stringref::from_slice). Hooks see the
+    /// packed item.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::{Encoder, stringref};
+    ///
+    /// let mut encoder = Encoder::new(Vec::new()).with_stringrefs();
+    /// encoder.encode(&["c2pa.actions", "c2pa.actions"]).unwrap();
+    /// let cbor = encoder.into_inner();
+    /// assert_eq!(c2pa_cbor::to_diagnostic(&cbor).unwrap(), "256([\n  \"c2pa.actions\",\n  25(0)\n])");
+    /// assert_eq!(
+    ///     stringref::from_slice::<Vec<String>>(&cbor).unwrap(),
+    ///     ["c2pa.actions", "c2pa.actions"]
+    /// );
+    /// ```
+    pub fn with_stringrefs(mut self) -> Self {
+        self.options.stringrefs = true;
+        self
+    }
+
+    /// Apply the NaN policy, returning true if the NaN has been written
+    fn write_nan(&mut self) -> Result<bool> {
+        match self.options.nan_policy {
+            NanPolicy::Preserve => Ok(false),
+            NanPolicy::Canonical => {
+                self.writer
+                    .write_all(&[(MAJOR_SIMPLE << 5) | FLOAT16, 0x7e, 0x00])?;
+                Ok(true)
+            }
+            NanPolicy::Error => Err(Error::Message(
+                "NaN not allowed by the NaN policy".to_string(),
+            )),
+        }
+    }
+
+    /// A hookless encoder into `writer` with the same encoding options as this one
+    ///
+    /// String references are left to the outer encoder, which packs whole items.
+    fn nested<V: Write>(&self, writer: V) -> Encoder<V> {
+        Encoder {
+            writer: CountingWriter::new(writer),
+            hooks: Vec::new(),
+            options: EncoderOptions {
+                stringrefs: false,
+                ..self.options
+            },
+            none_written: false,
+        }
+    }
+
+    /// Register a hook that sees every top-level item (builder pattern)
+    ///
+    /// See [`EncodeHook`] for ordering rules. While any hook is registered, each
+    /// item is encoded into a temporary buffer before being written.
+    pub fn with_hook<H: EncodeHook + Send + 'static>(mut self, hook: H) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
     }
 
     /// Consume the encoder and return the inner writer
     pub fn into_inner(self) -> W {
-        self.writer
+        self.writer.into_inner()
+    }
+
+    /// Start writing to `writer`, returning the previous writer
+    ///
+    /// Options and hooks are kept, and [`bytes_written`](Self::bytes_written)
+    /// starts again from zero, so one encoder can serve many messages.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::Encoder;
+    ///
+    /// let mut encoder = Encoder::new(Vec::new()).with_indefinite_lengths();
+    /// encoder.encode(&1u8).unwrap();
+    /// let first = encoder.reset(Vec::new());
+    /// encoder.encode(&2u8).unwrap();
+    /// assert_eq!(first, [0x01]);
+    /// assert_eq!(encoder.into_inner(), [0x02]);
+    /// ```
+    pub fn reset(&mut self, writer: W) -> W {
+        self.none_written = false;
+        let mut writer = CountingWriter::new(writer);
+        writer.limit = self.options.max_output_len;
+        std::mem::replace(&mut self.writer, writer).into_inner()
+    }
+
+    /// Number of bytes written to the inner writer so far
+    ///
+    /// Counts everything that reached the writer, including raw headers and
+    /// the output of hooks, so after encoding a value it is the length of
+    /// its encoding even when the writer cannot report it.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::Encoder;
+    ///
+    /// let mut encoder = Encoder::new(std::io::sink());
+    /// encoder.encode(&"hello").unwrap();
+    /// assert_eq!(encoder.bytes_written(), 6);
+    /// encoder.write_tag(24).unwrap();
+    /// assert_eq!(encoder.bytes_written(), 8);
+    /// ```
+    pub fn bytes_written(&self) -> u64 {
+        self.writer.count()
+    }
+
+    /// Write the key identifying an enum variant
+    fn write_variant_key(&mut self, variant_index: u32, variant: &'static str) -> Result<()> {
+        match self.options.variant_keys {
+            VariantKeys::Name => self.write_text(variant),
+            VariantKeys::Index => self.write_type_value(MAJOR_UNSIGNED, variant_index.into()),
+            VariantKeys::NumericName => match variant.parse::<u64>() {
+                // Only canonical spellings, so "007" and "+7" stay text and round trip
+                Ok(n) if n.to_string() == variant => self.write_type_value(MAJOR_UNSIGNED, n),
+                _ => self.write_text(variant),
+            },
+        }
+    }
+
+    /// Encode a struct field's value, or `None` if it is a `None` to leave out
+    fn encode_field<T: ?Sized + Serialize>(&self, value: &T) -> Result<Option<Vec<u8>>> {
+        let mut bytes = Vec::new();
+        let none_written = {
+            let mut nested = self.nested(&mut bytes);
+            value.serialize(&mut nested)?;
+            nested.none_written
+        };
+        let omit = none_written && bytes == [(MAJOR_SIMPLE << 5) | NULL];
+        Ok((!omit).then_some(bytes))
     }
 
     fn write_type_value(&mut self, major: u8, value: u64) -> Result<()> {
-        if value < 24 {
-            self.writer.write_all(&[(major << 5) | value as u8])?;
-        } else if value < 256 {
-            self.writer.write_all(&[(major << 5) | 24, value as u8])?;
-        } else if value < 65536 {
-            self.writer.write_all(&[(major << 5) | 25])?;
-            self.writer.write_all(&(value as u16).to_be_bytes())?;
-        } else if value < 4294967296 {
-            self.writer.write_all(&[(major << 5) | 26])?;
-            self.writer.write_all(&(value as u32).to_be_bytes())?;
-        } else {
-            self.writer.write_all(&[(major << 5) | 27])?;
-            self.writer.write_all(&value.to_be_bytes())?;
+        let (header, len) = type_value_header(major, value);
+        self.writer.write_all(&header[..len])?;
+        Ok(())
+    }
+
+    /// Write a byte or text string header followed by its content
+    ///
+    /// Short strings are copied after the header and written at once; longer
+    /// ones are written with one vectored write where the writer supports it,
+    /// so each string costs one call into the writer rather than two or more.
+    fn write_string(&mut self, major: u8, body: &[u8]) -> Result<()> {
+        let (header, len) = type_value_header(major, body.len() as u64);
+        if body.len() <= COALESCE_LIMIT {
+            let mut buf = [0u8; 9 + COALESCE_LIMIT];
+            buf[..len].copy_from_slice(&header[..len]);
+            buf[len..len + body.len()].copy_from_slice(body);
+            self.writer.write_all(&buf[..len + body.len()])?;
+            return Ok(());
+        }
+        let mut slices = [IoSlice::new(&header[..len]), IoSlice::new(body)];
+        let mut slices = &mut slices[..];
+        while !slices.is_empty() {
+            match self.writer.write_vectored(slices) {
+                Ok(0) => return Err(std::io::Error::from(std::io::ErrorKind::WriteZero).into()),
+                Ok(n) => IoSlice::advance_slices(&mut slices, n),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
         }
         Ok(())
     }
@@ -56,6 +533,18 @@ impl<W: Write> Encoder<W> {
         self.write_type_value(MAJOR_TAG, tag)
     }
 
+    /// Write an item header as given, keeping the width of its argument
+    ///
+    /// Like [`write_tag`](Self::write_tag), this writes only a header, so
+    /// hooks do not see it. Malformed headers, such as an indefinite-length
+    /// integer, return an error without writing anything. See [`Header`] for
+    /// an example.
+    pub fn write_header(&mut self, header: Header) -> Result<()> {
+        header.check()?;
+        self.writer.write_all(&header.to_bytes())?;
+        Ok(())
+    }
+
     /// Start an indefinite-length array
     pub fn write_array_indefinite(&mut self) -> Result<()> {
         self.writer.write_all(&[(MAJOR_ARRAY << 5) | INDEFINITE])?;
@@ -74,11 +563,553 @@ impl<W: Write> Encoder<W> {
         Ok(())
     }
 
+    /// Start a definite-length array; the next `len` items are its elements
+    ///
+    /// Like [`write_tag`](Self::write_tag), this writes only a header, so
+    /// hooks do not see it. Together with the other `write_*` primitives it
+    /// lets a structure such as a COSE header be written item by item.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::Encoder;
+    ///
+    /// // [1, {"alg": -7}, h'00', null]
+    /// let mut encoder = Encoder::new(Vec::new());
+    /// encoder.write_array_header(4).unwrap();
+    /// encoder.write_u64(1).unwrap();
+    /// encoder.write_map_header(1).unwrap();
+    /// encoder.write_text("alg").unwrap();
+    /// encoder.write_i64(-7).unwrap();
+    /// encoder.write_bytes(&[0x00]).unwrap();
+    /// encoder.write_null().unwrap();
+    /// assert_eq!(
+    ///     encoder.into_inner(),
+    ///     [0x84, 0x01, 0xa1, 0x63, b'a', b'l', b'g', 0x26, 0x41, 0x00, 0xf6]
+    /// );
+    /// ```
+    pub fn write_array_header(&mut self, len: u64) -> Result<()> {
+        self.write_type_value(MAJOR_ARRAY, len)
+    }
+
+    /// Start a definite-length map; the next `len` pairs of items are its entries
+    pub fn write_map_header(&mut self, len: u64) -> Result<()> {
+        self.write_type_value(MAJOR_MAP, len)
+    }
+
+    /// Write a byte string
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        serde::Serializer::serialize_bytes(self, bytes)
+    }
+
+    /// Write a text string
+    pub fn write_text(&mut self, text: &str) -> Result<()> {
+        serde::Serializer::serialize_str(self, text)
+    }
+
+    /// Write an unsigned integer
+    pub fn write_u64(&mut self, value: u64) -> Result<()> {
+        self.write_type_value(MAJOR_UNSIGNED, value)
+    }
+
+    /// Write a signed integer, as major type 1 if negative
+    pub fn write_i64(&mut self, value: i64) -> Result<()> {
+        serde::Serializer::serialize_i64(self, value)
+    }
+
+    /// Write a float the same way [`encode`](Self::encode) writes an `f64`
+    pub fn write_f64(&mut self, value: f64) -> Result<()> {
+        serde::Serializer::serialize_f64(self, value)
+    }
+
+    /// Write `true` or `false`
+    pub fn write_bool(&mut self, value: bool) -> Result<()> {
+        serde::Serializer::serialize_bool(self, value)
+    }
+
+    /// Write `null`
+    pub fn write_null(&mut self) -> Result<()> {
+        serde::Serializer::serialize_none(self)
+    }
+
+    /// Write `undefined`
+    ///
+    /// Serde has no undefined value; [`Value::Undefined`] is written with this.
+    pub fn write_undefined(&mut self) -> Result<()> {
+        self.writer.write_all(&[(MAJOR_SIMPLE << 5) | UNDEFINED])?;
+        Ok(())
+    }
+
+    /// Write a simple value
+    ///
+    /// Values 24–31 are reserved by RFC 8949 and return an error.
+    pub fn write_simple(&mut self, value: u8) -> Result<()> {
+        match value {
+            0..=23 => self.writer.write_all(&[(MAJOR_SIMPLE << 5) | value])?,
+            24..=31 => {
+                return Err(Error::Message(format!("Simple value {value} is reserved")));
+            }
+            _ => self
+                .writer
+                .write_all(&[(MAJOR_SIMPLE << 5) | SIMPLE_VALUE, value])?,
+        }
+        Ok(())
+    }
+
+    /// Write a sequence of string chunks as an indefinite-length string
+    fn write_chunked<T: ?Sized + Serialize>(&mut self, major: u8, chunks: &T) -> Result<()> {
+        // The chunks arrive as an array of strings; re-frame them without the array header
+        let mut buf = Vec::new();
+        chunks.serialize(&mut Encoder::new(&mut buf))?;
+        let mut reader = SliceReader::new(&buf);
+        let count = match reader.read_header()? {
+            (MAJOR_ARRAY, _, Some(count)) => count,
+            _ => {
+                return Err(Error::Message(
+                    "chunked string must serialize as a sequence of chunks".to_string(),
+                ));
+            }
+        };
+        let start = reader.position();
+        for _ in 0..count {
+            let len = match reader.read_header()? {
+                (chunk_major, _, Some(len)) if chunk_major == major => len,
+                _ => {
+                    return Err(Error::Message(
+                        "chunks must be definite-length strings of the same type".to_string(),
+                    ));
+                }
+            };
+            reader.read_slice(u64_to_usize(len)?)?;
+        }
+
+        self.writer.write_all(&[(major << 5) | INDEFINITE])?;
+        self.writer.write_all(&buf[start..reader.position()])?;
+        self.write_break()
+    }
+
     pub fn encode<T: Serialize>(&mut self, value: &T) -> Result<()> {
-        value.serialize(&mut *self)
+        if self.hooks.is_empty() && !self.options.stringrefs {
+            return value.serialize(&mut *self);
+        }
+
+        // Hooks and string references need the whole item, so encode it to a buffer first
+        let mut item = Vec::new();
+        value.serialize(&mut self.nested(&mut item))?;
+        self.write_hooked(item)
+    }
+
+    /// Encode a value wrapped in the tag it declares
+    ///
+    /// Writes [`TaggedSerialize::cbor_tag`] (if any) followed by the value.
+    /// The item goes through the hooks like any other top-level item.
+    ///
+    /// [`TaggedSerialize::cbor_tag`]: crate::tags::TaggedSerialize::cbor_tag
+    pub fn encode_tagged<T: TaggedSerialize>(&mut self, value: &T) -> Result<()> {
+        self.encode(&Tagged::new(value.cbor_tag(), value))
+    }
+
+    /// Encode the items of `iter` as an array, streaming them as they are produced
+    ///
+    /// When the iterator's size hint is exact (as for any `ExactSizeIterator`),
+    /// a definite-length array is written; otherwise the array is
+    /// indefinite-length and ends with a break. Either way the items are
+    /// written one at a time without being collected first. Returns an error
+    /// if an exact size hint turns out to be wrong.
+    ///
+    /// The array is a single top-level item, so when hooks are registered it
+    /// is buffered in full before they see it.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::Encoder;
+    ///
+    /// let mut encoder = Encoder::new(Vec::new());
+    /// encoder.encode_iter(1..=3u8).unwrap();
+    /// encoder.encode_iter((1..=3u8).filter(|n| n % 2 == 1)).unwrap();
+    /// assert_eq!(
+    ///     encoder.into_inner(),
+    ///     [0x83, 0x01, 0x02, 0x03, 0x9f, 0x01, 0x03, 0xff]
+    /// );
+    /// ```
+    pub fn encode_iter<I>(&mut self, iter: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Serialize,
+    {
+        if !self.hooks.is_empty() || self.options.stringrefs {
+            let mut item = Vec::new();
+            self.nested(&mut item).encode_iter(iter)?;
+            return self.write_hooked(item);
+        }
+
+        let iter = iter.into_iter();
+        match iter.size_hint() {
+            (len, Some(upper)) if len == upper => {
+                self.write_type_value(MAJOR_ARRAY, len as u64)?;
+                let mut count = 0;
+                for item in iter {
+                    count += 1;
+                    if count > len {
+                        break;
+                    }
+                    item.serialize(&mut *self)?;
+                }
+                if count > len {
+                    return Err(Error::Message(format!(
+                        "iterator produced more than the {} items it reported",
+                        len
+                    )));
+                }
+                if count < len {
+                    return Err(Error::Message(format!(
+                        "iterator reported {} items but produced {}",
+                        len, count
+                    )));
+                }
+                Ok(())
+            }
+            _ => {
+                self.write_array_indefinite()?;
+                for item in iter {
+                    item.serialize(&mut *self)?;
+                }
+                self.write_break()
+            }
+        }
+    }
+
+    /// Start an indefinite-length byte string written through [`std::io::Write`]
+    ///
+    /// Data written to the returned writer is emitted as definite-length
+    /// chunks of exactly `chunk_size` bytes (the last one may be shorter), so
+    /// at most one chunk is held in memory. Call
+    /// [`finish`](ByteStringWriter::finish) to write the final chunk and the
+    /// break; dropping the writer does the same but ignores errors.
+    ///
+    /// Like [`write_tag`](Self::write_tag), this writes straight to the
+    /// underlying writer, bypassing hooks. Returns an error if `chunk_size`
+    /// is zero.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    ///
+    /// use c2pa_cbor::Encoder;
+    ///
+    /// let mut encoder = Encoder::new(Vec::new());
+    /// let mut bytes = encoder.bytes_writer(4).unwrap();
+    /// bytes.write_all(b"hello").unwrap();
+    /// bytes.finish().unwrap();
+    /// assert_eq!(
+    ///     encoder.into_inner(),
+    ///     [0x5f, 0x44, b'h', b'e', b'l', b'l', 0x41, b'o', 0xff]
+    /// );
+    /// ```
+    pub fn bytes_writer(&mut self, chunk_size: usize) -> Result<ByteStringWriter<'_, W>> {
+        if chunk_size == 0 {
+            return Err(Error::Message(
+                "byte string chunk size must be at least 1".to_string(),
+            ));
+        }
+        self.writer.write_all(&[(MAJOR_BYTES << 5) | INDEFINITE])?;
+        Ok(ByteStringWriter {
+            encoder: self,
+            chunk_size,
+            buffer: Vec::new(),
+            finished: false,
+        })
+    }
+
+    /// Pass a fully encoded top-level item through the hooks and write it
+    fn write_hooked(&mut self, mut item: Vec<u8>) -> Result<()> {
+        if self.options.stringrefs {
+            item = crate::stringref::pack(&item)?;
+        }
+        for hook in &mut self.hooks {
+            item = hook.wrap(item)?;
+            hook.observe(&item)?;
+        }
+        self.writer.write_all(&item)?;
+        Ok(())
+    }
+
+    /// Write a [`Value`] in document order without recursion
+    ///
+    /// Arrays and maps are walked with an explicit stack and written straight
+    /// to the writer, so the depth of the value is limited only by memory.
+    /// Scalars are encoded exactly as [`encode`](Self::encode) would encode
+    /// them. Unlike the `Serialize` impl for `Value`, tags are written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use c2pa_cbor::{Encoder, Value};
+    ///
+    /// let value = Value::Tag(24, Box::new(Value::Array(vec![Value::Integer(1)])));
+    /// let mut encoder = Encoder::new(Vec::new());
+    /// encoder.write_value_streaming(&value).unwrap();
+    /// assert_eq!(encoder.into_inner(), [0xd8, 0x18, 0x81, 0x01]);
+    /// ```
+    pub fn write_value_streaming(&mut self, value: &Value) -> Result<()> {
+        if !self.hooks.is_empty() || self.options.stringrefs {
+            let mut item = Vec::new();
+            self.nested(&mut item).write_value_streaming(value)?;
+            return self.write_hooked(item);
+        }
+
+        let mut stack: Vec<PendingItems> = Vec::new();
+        let mut next = value;
+        loop {
+            match next {
+                Value::Null => serde::Serializer::serialize_none(&mut *self)?,
+                Value::Undefined => self.write_undefined()?,
+                Value::Simple(n) => self.write_simple(*n)?,
+                Value::Bool(b) => serde::Serializer::serialize_bool(&mut *self, *b)?,
+                Value::Integer(i) => serde::Serializer::serialize_i64(&mut *self, *i)?,
+                Value::Float(f) => serde::Serializer::serialize_f64(&mut *self, *f)?,
+                Value::Bytes(b) => serde::Serializer::serialize_bytes(&mut *self, b)?,
+                Value::Text(s) => serde::Serializer::serialize_str(&mut *self, s)?,
+                Value::Array(items) => {
+                    self.write_type_value(MAJOR_ARRAY, items.len() as u64)?;
+                    stack.push(PendingItems::Array(items.iter()));
+                }
+                Value::Map(entries) => {
+                    self.write_type_value(MAJOR_MAP, entries.len() as u64)?;
+                    stack.push(PendingItems::Map(entries.iter(), None));
+                }
+                Value::OrderedMap(entries) => {
+                    self.write_type_value(MAJOR_MAP, entries.len() as u64)?;
+                    stack.push(PendingItems::OrderedMap(entries.iter(), None));
+                }
+                Value::Tag(tag, content) => {
+                    self.write_tag(*tag)?;
+                    next = content.as_ref();
+                    continue;
+                }
+            }
+
+            // Move on to the next unwritten item, closing finished containers
+            next = loop {
+                match stack.last_mut() {
+                    None => return Ok(()),
+                    Some(PendingItems::Array(items)) => {
+                        if let Some(item) = items.next() {
+                            break item;
+                        }
+                    }
+                    Some(PendingItems::Map(entries, pending_value)) => {
+                        if let Some(value) = pending_value.take() {
+                            break value;
+                        }
+                        if let Some((key, value)) = entries.next() {
+                            *pending_value = Some(value);
+                            break key;
+                        }
+                    }
+                    Some(PendingItems::OrderedMap(entries, pending_value)) => {
+                        if let Some(value) = pending_value.take() {
+                            break value;
+                        }
+                        if let Some((key, value)) = entries.next() {
+                            *pending_value = Some(value);
+                            break key;
+                        }
+                    }
+                }
+                stack.pop();
+            };
+        }
+    }
+}
+
+/// Writer that counts the bytes written through it
+///
+/// Use it to learn the length of output going to a sink that cannot be
+/// seeked or measured, such as a socket or a hasher. [`Encoder`] counts its
+/// own output the same way, see [`Encoder::bytes_written`].
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::CountingWriter;
+///
+/// let mut writer = CountingWriter::new(std::io::sink());
+/// c2pa_cbor::to_writer(&mut writer, &[1, 2, 3]).unwrap();
+/// assert_eq!(writer.count(), 4);
+/// ```
+pub struct CountingWriter<W> {
+    inner: W,
+    count: u64,
+    // The encoder's output limit; writes that would pass it fail whole
+    limit: Option<u64>,
+}
+
+impl<W: Write> CountingWriter<W> {
+    /// Wrap `inner` with a count of zero
+    pub fn new(inner: W) -> Self {
+        CountingWriter {
+            inner,
+            count: 0,
+            limit: None,
+        }
+    }
+
+    fn check_limit(&self, len: usize) -> std::io::Result<()> {
+        if let Some(limit) = self.limit
+            && self.count.saturating_add(len as u64) > limit
+        {
+            return Err(std::io::Error::other(Error::OutputLimitExceeded(limit)));
+        }
+        Ok(())
+    }
+
+    /// Number of bytes the wrapped writer has accepted
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The wrapped writer
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Return the wrapped writer
+    pub fn into_inner(self) -> W {
+        self.inner
     }
 }
 
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.check_limit(buf.len())?;
+        // Only count what the inner writer accepted
+        let n = self.inner.write(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> std::io::Result<usize> {
+        self.check_limit(bufs.iter().map(|buf| buf.len()).sum())?;
+        let n = self.inner.write_vectored(bufs)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writer for an indefinite-length byte string, returned by [`Encoder::bytes_writer`]
+pub struct ByteStringWriter<'a, W: Write> {
+    encoder: &'a mut Encoder<W>,
+    chunk_size: usize,
+    // Start of the next chunk, always shorter than `chunk_size`
+    buffer: Vec<u8>,
+    finished: bool,
+}
+
+impl<W: Write> ByteStringWriter<'_, W> {
+    fn write_chunk(&mut self, chunk: &[u8]) -> Result<()> {
+        self.encoder.write_string(MAJOR_BYTES, chunk)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        self.finished = true;
+        if !self.buffer.is_empty() {
+            let rest = std::mem::take(&mut self.buffer);
+            self.write_chunk(&rest)?;
+        }
+        self.encoder.write_break()
+    }
+
+    /// Write any buffered data as a final chunk, then the break
+    pub fn finish(mut self) -> Result<()> {
+        self.close()
+    }
+}
+
+impl<W: Write> Write for ByteStringWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.buffer.is_empty() && buf.len() >= self.chunk_size {
+            // Whole chunk available, skip the copy
+            let chunk = &buf[..self.chunk_size];
+            self.write_chunk(chunk).map_err(into_io_error)?;
+            return Ok(self.chunk_size);
+        }
+        let take = buf.len().min(self.chunk_size - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..take]);
+        if self.buffer.len() == self.chunk_size {
+            let chunk = std::mem::take(&mut self.buffer);
+            self.write_chunk(&chunk).map_err(into_io_error)?;
+            self.buffer = chunk;
+            self.buffer.clear();
+        }
+        Ok(take)
+    }
+
+    /// Flush the underlying writer; a partial chunk stays buffered
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.encoder.writer.flush()
+    }
+}
+
+impl<W: Write> Drop for ByteStringWriter<'_, W> {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+// Strings up to this length are written together with their header in one call
+const COALESCE_LIMIT: usize = 64;
+
+/// The header of an item with `value` as its argument, in preferred serialization,
+/// and its length
+fn type_value_header(major: u8, value: u64) -> ([u8; 9], usize) {
+    let mut header = [0u8; 9];
+    let len = if value < 24 {
+        header[0] = (major << 5) | value as u8;
+        1
+    } else if value < 256 {
+        header[0] = (major << 5) | 24;
+        header[1] = value as u8;
+        2
+    } else if value < 65536 {
+        header[0] = (major << 5) | 25;
+        header[1..3].copy_from_slice(&(value as u16).to_be_bytes());
+        3
+    } else if value < 4294967296 {
+        header[0] = (major << 5) | 26;
+        header[1..5].copy_from_slice(&(value as u32).to_be_bytes());
+        5
+    } else {
+        header[0] = (major << 5) | 27;
+        header[1..9].copy_from_slice(&value.to_be_bytes());
+        9
+    };
+    (header, len)
+}
+
+fn into_io_error(e: Error) -> std::io::Error {
+    match e {
+        Error::Io(e) => e,
+        e => std::io::Error::other(e),
+    }
+}
+
+// Container being written by `write_value_streaming`, with the items still to write
+enum PendingItems<'v> {
+    Array(std::slice::Iter<'v, Value>),
+    // Map entries, and the value whose key has just been written
+    Map(
+        std::collections::btree_map::Iter<'v, Value, Value>,
+        Option<&'v Value>,
+    ),
+    OrderedMap(std::slice::Iter<'v, (Value, Value)>, Option<&'v Value>),
+}
+
 /// Wrapper for serializing sequences/maps with optional buffering
 ///
 /// When serde knows the collection length (the common case), this writes directly
@@ -92,6 +1123,8 @@ impl<W: Write> Encoder<W> {
 pub enum SerializeVec<'a, W: Write> {
     /// Direct mode: length known, writes immediately (zero overhead)
     Direct { encoder: &'a mut Encoder<W> },
+    /// Indefinite mode: length unknown, writes immediately and ends with a break
+    Indefinite { encoder: &'a mut Encoder<W> },
     /// Array buffering mode: length unknown, collects elements
     Array {
         encoder: &'a mut Encoder<W>,
@@ -111,7 +1144,7 @@ impl<'a, W: Write> serde::Serializer for &'a mut Encoder<W> {
     type SerializeMap = SerializeVec<'a, W>;
     type SerializeSeq = SerializeVec<'a, W>;
     type SerializeStruct = SerializeVec<'a, W>;
-    type SerializeStructVariant = &'a mut Encoder<W>;
+    type SerializeStructVariant = SerializeVec<'a, W>;
     type SerializeTuple = SerializeVec<'a, W>;
     type SerializeTupleStruct = SerializeVec<'a, W>;
     type SerializeTupleVariant = &'a mut Encoder<W>;
@@ -142,6 +1175,18 @@ impl<'a, W: Write> serde::Serializer for &'a mut Encoder<W> {
         }
     }
 
+    fn serialize_i128(self, v: i128) -> Result<()> {
+        // A CBOR integer head holds magnitudes up to 2^64 - 1 in either sign;
+        // wider values need a bignum (see `bignum::int128`)
+        let (major, n) = if v >= 0 {
+            (MAJOR_UNSIGNED, u64::try_from(v))
+        } else {
+            (MAJOR_NEGATIVE, u64::try_from(-1 - v))
+        };
+        let n = n.map_err(|_| Error::Message(format!("Integer {} too large for CBOR", v)))?;
+        self.write_type_value(major, n)
+    }
+
     fn serialize_u8(self, v: u8) -> Result<()> {
         self.serialize_u64(v as u64)
     }
@@ -158,7 +1203,16 @@ impl<'a, W: Write> serde::Serializer for &'a mut Encoder<W> {
         self.write_type_value(MAJOR_UNSIGNED, v)
     }
 
+    fn serialize_u128(self, v: u128) -> Result<()> {
+        let n = u64::try_from(v)
+            .map_err(|_| Error::Message(format!("Integer {} too large for CBOR", v)))?;
+        self.write_type_value(MAJOR_UNSIGNED, n)
+    }
+
     fn serialize_f32(self, v: f32) -> Result<()> {
+        if v.is_nan() && self.write_nan()? {
+            return Ok(());
+        }
         // Encode as CBOR float32 (major type 7, additional info 26)
         self.writer.write_all(&[(MAJOR_SIMPLE << 5) | FLOAT32])?;
         self.writer.write_all(&v.to_be_bytes())?;
@@ -166,6 +1220,9 @@ impl<'a, W: Write> serde::Serializer for &'a mut Encoder<W> {
     }
 
     fn serialize_f64(self, v: f64) -> Result<()> {
+        if v.is_nan() && self.write_nan()? {
+            return Ok(());
+        }
         #[cfg(feature = "compact_floats")]
         {
             // Try to encode compactly as f16 first, then f32, fallback to f64
@@ -201,18 +1258,15 @@ impl<'a, W: Write> serde::Serializer for &'a mut Encoder<W> {
     }
 
     fn serialize_str(self, v: &str) -> Result<()> {
-        self.write_type_value(MAJOR_TEXT, v.len() as u64)?;
-        self.writer.write_all(v.as_bytes())?;
-        Ok(())
+        self.write_string(MAJOR_TEXT, v.as_bytes())
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<()> {
-        self.write_type_value(MAJOR_BYTES, v.len() as u64)?;
-        self.writer.write_all(v)?;
-        Ok(())
+        self.write_string(MAJOR_BYTES, v)
     }
 
     fn serialize_none(self) -> Result<()> {
+        self.none_written = true;
         self.writer.write_all(&[(MAJOR_SIMPLE << 5) | NULL])?;
         Ok(())
     }
@@ -222,7 +1276,8 @@ impl<'a, W: Write> serde::Serializer for &'a mut Encoder<W> {
     }
 
     fn serialize_unit(self) -> Result<()> {
-        self.serialize_none()
+        self.writer.write_all(&[(MAJOR_SIMPLE << 5) | NULL])?;
+        Ok(())
     }
 
     fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
@@ -232,10 +1287,10 @@ impl<'a, W: Write> serde::Serializer for &'a mut Encoder<W> {
     fn serialize_unit_variant(
         self,
         _name: &'static str,
-        _variant_index: u32,
+        variant_index: u32,
         variant: &'static str,
     ) -> Result<()> {
-        self.serialize_str(variant)
+        self.write_variant_key(variant_index, variant)
     }
 
     fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<()>
@@ -252,21 +1307,52 @@ impl<'a, W: Write> serde::Serializer for &'a mut Encoder<W> {
             return value.serialize(self);
         }
 
-        // Serialize transparently (just the inner value, not wrapped in an array)
-        // This is serde's default behavior for newtype structs
-        // Users can still use #[serde(transparent)] for clarity, but it's not required
+        // Chunked strings from ChunkedBytes/ChunkedText keep their chunk boundaries
+        match name {
+            CHUNKED_BYTES_MARKER => return self.write_chunked(MAJOR_BYTES, value),
+            CHUNKED_TEXT_MARKER => return self.write_chunked(MAJOR_TEXT, value),
+            CBOR_TAG_MARKER => {
+                // Tagged<T> with a tag number that has no static marker
+                if let Some(tag) = crate::tags::current_cbor_tag() {
+                    self.write_tag(tag)?;
+                }
+                return value.serialize(self);
+            }
+            UNDEFINED_MARKER => return self.write_undefined(),
+            SIMPLE_MARKER => {
+                return match crate::value::to_value(value)? {
+                    Value::Integer(n @ 0..=255) => self.write_simple(n as u8),
+                    _ => Err(Error::Message("Simple value must be a u8".to_string())),
+                };
+            }
+            NULLABLE_MARKER => {
+                // An explicit null from the nullable module is never left out
+                let none_written = self.none_written;
+                value.serialize(&mut *self)?;
+                self.none_written = none_written;
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        // Serialize transparently (just the inner value), as serde's other formats do.
+        // Users can still use #[serde(transparent)] for clarity, but it's not required.
+        // Only the legacy option wraps the value in a 1-element array.
+        if self.options.legacy_newtypes {
+            self.write_type_value(MAJOR_ARRAY, 1)?;
+        }
         value.serialize(self)
     }
 
     fn serialize_newtype_variant<T: ?Sized + Serialize>(
         self,
         _name: &'static str,
-        _variant_index: u32,
+        variant_index: u32,
         variant: &'static str,
         value: &T,
     ) -> Result<()> {
         self.write_type_value(MAJOR_MAP, 1)?;
-        variant.serialize(&mut *self)?;
+        self.write_variant_key(variant_index, variant)?;
         value.serialize(self)?;
         Ok(())
     }
@@ -278,6 +1364,10 @@ impl<'a, W: Write> serde::Serializer for &'a mut Encoder<W> {
                 self.write_type_value(MAJOR_ARRAY, len as u64)?;
                 Ok(SerializeVec::Direct { encoder: self })
             }
+            None if self.options.indefinite_lengths => {
+                self.write_array_indefinite()?;
+                Ok(SerializeVec::Indefinite { encoder: self })
+            }
             None => {
                 // Slow path: length unknown (rare), buffer elements until end()
                 // Only happens with custom iterators that don't implement ExactSizeIterator
@@ -304,12 +1394,12 @@ impl<'a, W: Write> serde::Serializer for &'a mut Encoder<W> {
     fn serialize_tuple_variant(
         self,
         _name: &'static str,
-        _variant_index: u32,
+        variant_index: u32,
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
         self.write_type_value(MAJOR_MAP, 1)?;
-        variant.serialize(&mut *self)?;
+        self.write_variant_key(variant_index, variant)?;
         self.write_type_value(MAJOR_ARRAY, len as u64)?;
         Ok(self)
     }
@@ -321,6 +1411,10 @@ impl<'a, W: Write> serde::Serializer for &'a mut Encoder<W> {
                 self.write_type_value(MAJOR_MAP, len as u64)?;
                 Ok(SerializeVec::Direct { encoder: self })
             }
+            None if self.options.indefinite_lengths => {
+                self.write_map_indefinite()?;
+                Ok(SerializeVec::Indefinite { encoder: self })
+            }
             None => {
                 // Slow path: length unknown, buffer key-value pairs until end()
                 // Happens with #[serde(flatten)] or custom map-like types in serde_transcode
@@ -339,20 +1433,23 @@ impl<'a, W: Write> serde::Serializer for &'a mut Encoder<W> {
         // and rely on the Serialize impl to not use skip_serializing_if, or to use #[serde(transparent)]
         // The proper fix is for users to not mix skip_serializing_if with CBOR serialization,
         // or to use indefinite-length encoding via manual encoding
+        if self.options.omit_none_fields {
+            // The field count is only known after dropping None fields
+            return self.serialize_map(None);
+        }
         self.serialize_map(Some(len))
     }
 
     fn serialize_struct_variant(
         self,
-        _name: &'static str,
-        _variant_index: u32,
+        name: &'static str,
+        variant_index: u32,
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStructVariant> {
         self.write_type_value(MAJOR_MAP, 1)?;
-        variant.serialize(&mut *self)?;
-        self.write_type_value(MAJOR_MAP, len as u64)?;
-        Ok(self)
+        self.write_variant_key(variant_index, variant)?;
+        self.serialize_struct(name, len)
     }
 }
 
@@ -434,7 +1531,7 @@ impl<W: Write> serde::ser::SerializeStruct for &mut Encoder<W> {
         key: &'static str,
         value: &T,
     ) -> Result<()> {
-        key.serialize(&mut **self)?;
+        FieldKey(key).serialize(&mut **self)?;
         value.serialize(&mut **self)
     }
 
@@ -452,7 +1549,7 @@ impl<W: Write> serde::ser::SerializeStructVariant for &mut Encoder<W> {
         key: &'static str,
         value: &T,
     ) -> Result<()> {
-        key.serialize(&mut **self)?;
+        FieldKey(key).serialize(&mut **self)?;
         value.serialize(&mut **self)
     }
 
@@ -464,14 +1561,13 @@ impl<W: Write> serde::ser::SerializeStructVariant for &mut Encoder<W> {
 // Implementations for SerializeVec (handles buffering for unknown-length collections)
 
 impl<'a, W: Write> SerializeVec<'a, W> {
-    /// Serialize a value to a buffer for later writing
-    fn serialize_to_buffer<T>(value: &T) -> Result<Vec<u8>>
+    /// Serialize a value to a buffer for later writing, with the encoder's options
+    fn serialize_to_buffer<T>(encoder: &Encoder<W>, value: &T) -> Result<Vec<u8>>
     where
         T: ?Sized + Serialize,
     {
         let mut buf = Vec::new();
-        let mut encoder = Encoder::new(&mut buf);
-        value.serialize(&mut encoder)?;
+        value.serialize(&mut encoder.nested(&mut buf))?;
         Ok(buf)
     }
 
@@ -491,9 +1587,11 @@ impl<'a, W: Write> serde::ser::SerializeSeq for SerializeVec<'a, W> {
         T: ?Sized + Serialize,
     {
         match self {
-            SerializeVec::Direct { encoder } => value.serialize(&mut **encoder),
-            SerializeVec::Array { buffer, .. } => {
-                buffer.push(Self::serialize_to_buffer(value)?);
+            SerializeVec::Direct { encoder } | SerializeVec::Indefinite { encoder } => {
+                value.serialize(&mut **encoder)
+            }
+            SerializeVec::Array { encoder, buffer } => {
+                buffer.push(Self::serialize_to_buffer(encoder, value)?);
                 Ok(())
             }
             SerializeVec::Map { .. } => Err(Error::Message(
@@ -505,6 +1603,7 @@ impl<'a, W: Write> serde::ser::SerializeSeq for SerializeVec<'a, W> {
     fn end(self) -> Result<()> {
         match self {
             SerializeVec::Direct { .. } => Ok(()),
+            SerializeVec::Indefinite { encoder } => encoder.write_break(),
             SerializeVec::Array { encoder, buffer } => {
                 // Write definite-length array header now that we know the count
                 encoder.write_type_value(MAJOR_ARRAY, buffer.len() as u64)?;
@@ -556,9 +1655,15 @@ impl<'a, W: Write> serde::ser::SerializeMap for SerializeVec<'a, W> {
         T: ?Sized + Serialize,
     {
         match self {
-            SerializeVec::Direct { encoder } => key.serialize(&mut **encoder),
-            SerializeVec::Map { pending_key, .. } => {
-                *pending_key = Some(Self::serialize_to_buffer(key)?);
+            SerializeVec::Direct { encoder } | SerializeVec::Indefinite { encoder } => {
+                key.serialize(&mut **encoder)
+            }
+            SerializeVec::Map {
+                encoder,
+                pending_key,
+                ..
+            } => {
+                *pending_key = Some(Self::serialize_to_buffer(encoder, key)?);
                 Ok(())
             }
             SerializeVec::Array { .. } => Err(Error::Message(
@@ -572,13 +1677,15 @@ impl<'a, W: Write> serde::ser::SerializeMap for SerializeVec<'a, W> {
         T: ?Sized + Serialize,
     {
         match self {
-            SerializeVec::Direct { encoder } => value.serialize(&mut **encoder),
+            SerializeVec::Direct { encoder } | SerializeVec::Indefinite { encoder } => {
+                value.serialize(&mut **encoder)
+            }
             SerializeVec::Map {
+                encoder,
                 buffer,
                 pending_key,
-                ..
             } => {
-                let value_bytes = Self::serialize_to_buffer(value)?;
+                let value_bytes = Self::serialize_to_buffer(encoder, value)?;
                 if let Some(key_bytes) = pending_key.take() {
                     buffer.push((key_bytes, value_bytes));
                     Ok(())
@@ -597,6 +1704,7 @@ impl<'a, W: Write> serde::ser::SerializeMap for SerializeVec<'a, W> {
     fn end(self) -> Result<()> {
         match self {
             SerializeVec::Direct { .. } => Ok(()),
+            SerializeVec::Indefinite { encoder } => encoder.write_break(),
             SerializeVec::Map {
                 encoder,
                 buffer,
@@ -632,7 +1740,26 @@ impl<'a, W: Write> serde::ser::SerializeStruct for SerializeVec<'a, W> {
         key: &'static str,
         value: &T,
     ) -> Result<()> {
-        serde::ser::SerializeMap::serialize_entry(self, key, value)
+        // Only unknown-length structs can drop fields, see Encoder::with_omit_none_fields
+        match self {
+            SerializeVec::Indefinite { encoder } if encoder.options.omit_none_fields => {
+                if let Some(bytes) = encoder.encode_field(value)? {
+                    FieldKey(key).serialize(&mut **encoder)?;
+                    Self::write_buffered(encoder, &bytes)?;
+                }
+                return Ok(());
+            }
+            SerializeVec::Map {
+                encoder, buffer, ..
+            } if encoder.options.omit_none_fields => {
+                if let Some(bytes) = encoder.encode_field(value)? {
+                    buffer.push((Self::serialize_to_buffer(encoder, &FieldKey(key))?, bytes));
+                }
+                return Ok(());
+            }
+            _ => {}
+        }
+        serde::ser::SerializeMap::serialize_entry(self, &FieldKey(key), value)
     }
 
     fn end(self) -> Result<()> {
@@ -640,25 +1767,33 @@ impl<'a, W: Write> serde::ser::SerializeStruct for SerializeVec<'a, W> {
     }
 }
 
+impl<'a, W: Write> serde::ser::SerializeStructVariant for SerializeVec<'a, W> {
+    type Error = crate::Error;
+    type Ok = ();
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        serde::ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        serde::ser::SerializeStruct::end(self)
+    }
+}
+
 // Convenience functions
 /// Serializes a value to a CBOR byte vector
+///
+/// Maps and sequences of unknown length, such as structs with
+/// `#[serde(flatten)]` fields, are buffered and written with a definite length.
 pub fn to_vec<T: Serialize>(value: &T) -> Result<Vec<u8>> {
-    // Try direct serialization first
     let mut buf = Vec::new();
     let mut encoder = Encoder::new(&mut buf);
-    match encoder.encode(value) {
-        Ok(()) => Ok(buf),
-        Err(Error::Message(ref msg)) if msg.contains("indefinite-length") => {
-            // Fall back to value-based serialization for types that need indefinite length
-            // This handles #[serde(flatten)] and other cases where size is unknown
-            let value = crate::value::to_value(value)?;
-            buf.clear();
-            let mut encoder = Encoder::new(&mut buf);
-            encoder.encode(&value)?;
-            Ok(buf)
-        }
-        Err(e) => Err(e),
-    }
+    encoder.encode(value)?;
+    Ok(buf)
 }
 
 /// Serializes a value to a CBOR writer
@@ -17,6 +17,7 @@ use std::io;
 
 // CBOR error type
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// IO error
     Io(io::Error),
@@ -30,6 +31,14 @@ pub enum Error {
     TrailingData,
     /// General message (serde compatibility)
     Message(String),
+    /// A length does not fit in `usize` on this platform
+    LengthOverflow(u64),
+    /// The memory budget set with `Decoder::with_memory_budget` (in bytes) was exceeded
+    MemoryBudgetExceeded(usize),
+    /// An integer on the wire does not fit in the requested type
+    IntegerOverflow(i128),
+    /// The output limit set with `Encoder::with_max_output_len` (in bytes) would be exceeded
+    OutputLimitExceeded(u64),
 }
 
 impl std::fmt::Display for Error {
@@ -41,6 +50,20 @@ impl std::fmt::Display for Error {
             Error::Syntax(s) => write!(f, "Syntax error: {}", s),
             Error::TrailingData => write!(f, "Trailing data"),
             Error::Message(s) => write!(f, "{}", s),
+            Error::LengthOverflow(len) => write!(
+                f,
+                "Length {} exceeds maximum supported size on this platform",
+                len
+            ),
+            Error::MemoryBudgetExceeded(budget) => {
+                write!(f, "Memory budget of {} bytes exceeded", budget)
+            }
+            Error::IntegerOverflow(n) => {
+                write!(f, "Integer {} out of range for the target type", n)
+            }
+            Error::OutputLimitExceeded(limit) => {
+                write!(f, "Output limit of {} bytes exceeded", limit)
+            }
         }
     }
 }
@@ -49,6 +72,11 @@ impl std::error::Error for Error {}
 
 impl From<io::Error> for Error {
     fn from(e: io::Error) -> Self {
+        // Errors of this crate that passed through a writer, such as the output limit
+        if e.get_ref().is_some_and(|inner| inner.is::<Error>()) {
+            let inner = e.into_inner().expect("checked above");
+            return *inner.downcast::<Error>().expect("checked above");
+        }
         Error::Io(e)
     }
 }
@@ -61,7 +89,17 @@ impl serde::ser::Error for Error {
 
 impl serde::de::Error for Error {
     fn custom<T: std::fmt::Display>(msg: T) -> Self {
-        Error::Message(msg.to_string())
+        let msg = msg.to_string();
+        // An IntegerOverflow that passed through a visitor generic over its
+        // error type, such as Value's, keeps its variant
+        if let Some(n) = msg
+            .strip_prefix("Integer ")
+            .and_then(|rest| rest.strip_suffix(" out of range for the target type"))
+            .and_then(|n| n.parse().ok())
+        {
+            return Error::IntegerOverflow(n);
+        }
+        Error::Message(msg)
     }
 }
 
@@ -0,0 +1,226 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Golden fixture files for encoded output
+//!
+//! Enabled with the `fixtures` feature. Each fixture is stored as a pair of
+//! files in [`fixture_dir`]: `<name>.cbor` holds the exact bytes and
+//! `<name>.diag` holds the same item in diagnostic notation (see
+//! [`crate::diag`]). The `.cbor` file is what [`assert_matches`] compares
+//! against; the `.diag` file is what a reviewer reads, so a change to the
+//! wire format shows up as a line diff instead of a binary change.
+//!
+//! Set `UPDATE_FIXTURES=1` to have [`assert_matches`] rewrite fixtures
+//! instead of comparing against them.
+//!
+//! # Example
+//! ```no_run
+//! use c2pa_cbor::fixtures;
+//!
+//! let bytes = c2pa_cbor::to_vec(&("alg", 256)).unwrap();
+//! fixtures::assert_matches("alg_pair", &bytes);
+//! ```
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{Error, Result, diag::to_diagnostic};
+
+/// Environment variable overriding the fixture directory
+pub const FIXTURE_DIR_ENV: &str = "C2PA_CBOR_FIXTURES";
+
+/// Environment variable that makes [`assert_matches`] rewrite fixtures
+pub const UPDATE_FIXTURES_ENV: &str = "UPDATE_FIXTURES";
+
+/// Directory fixtures are read from and written to
+///
+/// `$C2PA_CBOR_FIXTURES` if set, otherwise `tests/fixtures` under
+/// `$CARGO_MANIFEST_DIR` (the crate whose tests are running), otherwise
+/// `tests/fixtures` under the current directory.
+pub fn fixture_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os(FIXTURE_DIR_ENV) {
+        return PathBuf::from(dir);
+    }
+    std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join("tests")
+        .join("fixtures")
+}
+
+fn fixture_paths(dir: &Path, name: &str) -> Result<(PathBuf, PathBuf)> {
+    if name.is_empty() || Path::new(name).components().any(|c| c.as_os_str() == "..") {
+        return Err(Error::Message(format!("invalid fixture name {:?}", name)));
+    }
+    Ok((
+        dir.join(format!("{}.cbor", name)),
+        dir.join(format!("{}.diag", name)),
+    ))
+}
+
+/// Diagnostic text as stored in a `.diag` file
+fn diag_text(bytes: &[u8]) -> Result<String> {
+    Ok(to_diagnostic(bytes)? + "\n")
+}
+
+fn save_in(dir: &Path, name: &str, bytes: &[u8]) -> Result<()> {
+    let (cbor_path, diag_path) = fixture_paths(dir, name)?;
+    let diag = diag_text(bytes)?;
+    if let Some(parent) = cbor_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(cbor_path, bytes)?;
+    fs::write(diag_path, diag)?;
+    Ok(())
+}
+
+/// Compare `bytes` with the stored fixture, describing any mismatch
+fn check_in(dir: &Path, name: &str, bytes: &[u8]) -> Result<Option<String>> {
+    let (cbor_path, diag_path) = fixture_paths(dir, name)?;
+    let actual = diag_text(bytes)?;
+    let expected_bytes = match fs::read(&cbor_path) {
+        Ok(expected) => expected,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(Some(format!(
+                "fixture {} does not exist; run with {}=1 to create it\nactual:\n{}",
+                cbor_path.display(),
+                UPDATE_FIXTURES_ENV,
+                actual
+            )));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if expected_bytes != bytes {
+        let expected = diag_text(&expected_bytes)
+            .unwrap_or_else(|e| format!("<stored bytes are not valid CBOR: {}>\n", e));
+        let first_difference = expected
+            .lines()
+            .zip(actual.lines())
+            .position(|(a, b)| a != b)
+            .unwrap_or_else(|| expected.lines().count().min(actual.lines().count()));
+        return Ok(Some(format!(
+            "fixture {} differs from the encoded bytes, first at diagnostic line {}\n\
+             expected:\n{}actual:\n{}",
+            cbor_path.display(),
+            first_difference + 1,
+            expected,
+            actual
+        )));
+    }
+    let stored_diag = fs::read_to_string(&diag_path).unwrap_or_default();
+    if stored_diag != actual {
+        return Ok(Some(format!(
+            "fixture {} is out of date with {}; run with {}=1 to regenerate it",
+            diag_path.display(),
+            cbor_path.display(),
+            UPDATE_FIXTURES_ENV
+        )));
+    }
+    Ok(None)
+}
+
+/// Write `bytes` and their diagnostic notation as fixture `name`
+///
+/// `name` may contain `/` to group fixtures in subdirectories.
+pub fn save(name: &str, bytes: &[u8]) -> Result<()> {
+    save_in(&fixture_dir(), name, bytes)
+}
+
+/// Assert that `bytes` match fixture `name` exactly
+///
+/// On mismatch, panics with the expected and actual diagnostic notation.
+/// Also panics if the `.diag` companion is missing or stale. When
+/// `UPDATE_FIXTURES` is set, the fixture is written instead.
+pub fn assert_matches(name: &str, bytes: &[u8]) {
+    let dir = fixture_dir();
+    if std::env::var_os(UPDATE_FIXTURES_ENV).is_some() {
+        if let Err(e) = save_in(&dir, name, bytes) {
+            panic!("failed to update fixture {}: {}", name, e);
+        }
+        return;
+    }
+    match check_in(&dir, name, bytes) {
+        Ok(None) => {}
+        Ok(Some(mismatch)) => panic!("{}", mismatch),
+        Err(e) => panic!("failed to check fixture {}: {}", name, e),
+    }
+}
+
+// The tests use the file system, which wasm32-unknown-unknown lacks
+#[cfg(all(test, not(all(target_arch = "wasm32", target_os = "unknown"))))]
+mod tests {
+    use super::*;
+
+    fn temp_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "c2pa_cbor_fixtures_{}_{}",
+            test,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_fixture_save_and_check() {
+        let dir = temp_dir("save");
+        let bytes = crate::to_vec(&vec![1u8, 2]).unwrap();
+        save_in(&dir, "nested/pair", &bytes).unwrap();
+
+        assert_eq!(fs::read(dir.join("nested/pair.cbor")).unwrap(), bytes);
+        assert_eq!(
+            fs::read_to_string(dir.join("nested/pair.diag")).unwrap(),
+            "[\n  1,\n  2\n]\n"
+        );
+        assert_eq!(check_in(&dir, "nested/pair", &bytes).unwrap(), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_fixture_mismatch_reports_diagnostic_diff() {
+        let dir = temp_dir("mismatch");
+        save_in(&dir, "pair", &crate::to_vec(&vec![1u8, 2]).unwrap()).unwrap();
+
+        let changed = crate::to_vec(&vec![1u8, 3]).unwrap();
+        let report = check_in(&dir, "pair", &changed).unwrap().unwrap();
+        assert!(report.contains("first at diagnostic line 3"), "{}", report);
+        assert!(report.contains("expected:\n[\n  1,\n  2\n]"), "{}", report);
+        assert!(report.contains("actual:\n[\n  1,\n  3\n]"), "{}", report);
+
+        let missing = check_in(&dir, "absent", &changed).unwrap().unwrap();
+        assert!(missing.contains("does not exist"), "{}", missing);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_fixture_stale_diag_is_reported() {
+        let dir = temp_dir("stale");
+        let bytes = crate::to_vec(&"x").unwrap();
+        save_in(&dir, "text", &bytes).unwrap();
+        fs::write(dir.join("text.diag"), "\"y\"\n").unwrap();
+
+        let report = check_in(&dir, "text", &bytes).unwrap().unwrap();
+        assert!(report.contains("out of date"), "{}", report);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_fixture_rejects_bad_names() {
+        let dir = temp_dir("names");
+        assert!(save_in(&dir, "", &[0x00]).is_err());
+        assert!(save_in(&dir, "../escape", &[0x00]).is_err());
+        assert!(save_in(&dir, "bad", &[0x18]).is_err());
+    }
+}
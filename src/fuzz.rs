@@ -0,0 +1,492 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Random values and documents for fuzzing and property tests
+//!
+//! Enabled with the `arbitrary` feature, which implements
+//! `arbitrary::Arbitrary` for [`Value`] so it can be used directly as a
+//! `cargo fuzz` input, and adds [`arbitrary_document`] to encode a value the
+//! way other encoders might. The `proptest` feature adds [`value_strategy`]
+//! and [`document_strategy`] for the same generators under proptest.
+//!
+//! Generation is biased toward edge cases rather than spread evenly:
+//! integers and floats at header and precision boundaries, tag numbers of
+//! every header width including well-known tags, occasional chains nested
+//! close to [`DEFAULT_MAX_DEPTH`], and in
+//! documents, arguments wider than needed up to 64-bit lengths and
+//! indefinite-length strings and containers. A generated document always
+//! decodes to the value it was built from, less its tags (see
+//! [`Value::into_untagged`]), so the pair can be round-tripped here and fed
+//! to other CBOR crates for differential testing.
+//!
+//! # Example
+//! ```
+//! use arbitrary::{Arbitrary, Unstructured};
+//! use c2pa_cbor::{Value, fuzz::arbitrary_document};
+//!
+//! let data = [7u8; 64];
+//! let mut u = Unstructured::new(&data);
+//! let value = Value::arbitrary(&mut u)?;
+//! let bytes = arbitrary_document(&mut u, &value)?;
+//! let decoded: Value = c2pa_cbor::from_slice(&bytes).unwrap();
+//! assert_eq!(decoded.cmp(&value.into_untagged()), std::cmp::Ordering::Equal);
+//! # Ok::<(), arbitrary::Error>(())
+//! ```
+
+use std::collections::BTreeMap;
+
+use arbitrary::{Arbitrary, Unstructured};
+
+use crate::Value;
+use crate::constants::*;
+use crate::raw::preferred_float_info;
+
+/// Depth below which ordinary generation may still nest
+const MAX_NESTING: usize = 6;
+
+/// Longest chain built when generating deep nesting, leaving headroom for the
+/// decoder's default depth limit
+const DEEP_NESTING: usize = DEFAULT_MAX_DEPTH - 8;
+
+/// Integers at the boundaries of each header width and of `i64`
+const EDGE_INTEGERS: &[i64] = &[
+    0,
+    1,
+    -1,
+    23,
+    24,
+    -24,
+    -25,
+    255,
+    256,
+    -256,
+    -257,
+    65535,
+    65536,
+    4294967295,
+    4294967296,
+    -4294967296,
+    -4294967297,
+    i64::MAX,
+    i64::MIN,
+];
+
+/// Floats at the boundaries of each precision, plus infinities and NaN
+const EDGE_FLOATS: &[f64] = &[
+    0.0,
+    -0.0,
+    1.0,
+    1.5,
+    65504.0,               // largest f16
+    5.960464477539063e-8,  // smallest f16 subnormal
+    f32::MAX as f64,       // largest f32
+    1.401298464324817e-45, // smallest f32 subnormal
+    f64::MAX,
+    f64::MIN_POSITIVE,
+    f64::EPSILON,
+    f64::INFINITY,
+    f64::NEG_INFINITY,
+    f64::NAN,
+];
+
+/// Tags with meaning in this crate or in C2PA
+const WELL_KNOWN_TAGS: &[u64] = &[
+    TAG_DATETIME_STRING,
+    TAG_EPOCH_DATETIME,
+    TAG_POSITIVE_BIGNUM,
+    TAG_NEGATIVE_BIGNUM,
+    TAG_DECIMAL_FRACTION,
+    TAG_BIGFLOAT,
+    TAG_ENCODED_CBOR,
+    TAG_URI,
+    TAG_MULTI_DIM_ARRAY,
+    TAG_UINT8_ARRAY,
+    TAG_SELF_DESCRIBE,
+];
+
+/// Map a number in `0..=243` onto the unassigned simple values
+///
+/// 20–23 decode as `false`, `true`, `null` and `undefined`, and 24–31 are
+/// reserved, so neither range can appear as [`Value::Simple`].
+fn simple_value(n: u8) -> Value {
+    Value::Simple(if n < 20 { n } else { n + 12 })
+}
+
+/// True one time in `n`, and false once `u` runs out of data
+///
+/// `Unstructured::ratio` is true on exhausted input; this keeps exhausted
+/// input on the plain path so generation and encoding stay small.
+fn one_in(u: &mut Unstructured, n: u8) -> arbitrary::Result<bool> {
+    Ok(u.int_in_range(1..=n)? == n)
+}
+
+/// Wrap `value` in `depth` single-entry containers, alternating arrays and maps
+fn nest(mut value: Value, depth: usize) -> Value {
+    for level in 0..depth {
+        value = if level % 2 == 0 {
+            Value::Array(vec![value])
+        } else {
+            Value::Map(BTreeMap::from([(Value::Integer(level as i64), value)]))
+        };
+    }
+    value
+}
+
+impl<'a> Arbitrary<'a> for Value {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        if one_in(u, 16)? {
+            let depth = u.int_in_range(1..=DEEP_NESTING)?;
+            Ok(nest(arbitrary_value(u, MAX_NESTING)?, depth))
+        } else {
+            arbitrary_value(u, 0)
+        }
+    }
+}
+
+/// Generate a value at `depth`, which is a scalar once `depth` reaches
+/// [`MAX_NESTING`]
+fn arbitrary_value(u: &mut Unstructured, depth: usize) -> arbitrary::Result<Value> {
+    let kinds = if depth < MAX_NESTING { 10 } else { 7 };
+    Ok(match u.int_in_range(0..=kinds)? {
+        0 => Value::Null,
+        1 => Value::Undefined,
+        2 => Value::Bool(u.arbitrary()?),
+        3 => Value::Integer(if u.arbitrary()? {
+            *u.choose(EDGE_INTEGERS)?
+        } else {
+            u.arbitrary()?
+        }),
+        4 => Value::Float(if u.arbitrary()? {
+            *u.choose(EDGE_FLOATS)?
+        } else {
+            u.arbitrary()?
+        }),
+        5 => Value::Bytes(u.arbitrary()?),
+        6 => Value::Text(u.arbitrary()?),
+        7 => simple_value(u.int_in_range(0..=243)?),
+        8 => {
+            let len = u.int_in_range(0..=4)?;
+            let mut items = Vec::with_capacity(len);
+            for _ in 0..len {
+                items.push(arbitrary_value(u, depth + 1)?);
+            }
+            Value::Array(items)
+        }
+        9 => {
+            let mut map = BTreeMap::new();
+            for _ in 0..u.int_in_range(0..=4)? {
+                map.insert(
+                    arbitrary_value(u, depth + 1)?,
+                    arbitrary_value(u, depth + 1)?,
+                );
+            }
+            Value::Map(map)
+        }
+        _ => Value::Tag(arbitrary_tag(u)?, Box::new(arbitrary_value(u, depth + 1)?)),
+    })
+}
+
+/// Pick a tag number of each header width with equal weight, or a well-known tag
+fn arbitrary_tag(u: &mut Unstructured) -> arbitrary::Result<u64> {
+    Ok(match u.int_in_range(0..=5)? {
+        0 => u.int_in_range(0..=23)?,
+        1 => u.int_in_range(24..=0xff)?,
+        2 => u.int_in_range(0x100..=0xffff)?,
+        3 => u.int_in_range(0x1_0000..=0xffff_ffff)?,
+        4 => u.int_in_range(0x1_0000_0000..=u64::MAX)?,
+        _ => *u.choose(WELL_KNOWN_TAGS)?,
+    })
+}
+
+/// Encode `value` with encoding choices drawn from `u`
+///
+/// Unlike [`to_vec`](crate::to_vec), arguments may be wider than needed (up to
+/// 64-bit lengths and tag numbers), floats may be wider than their preferred
+/// width, and strings, arrays and maps may use indefinite lengths, with
+/// strings split into random chunks. The result always decodes back to
+/// `value` with its tags removed.
+pub fn arbitrary_document(u: &mut Unstructured, value: &Value) -> arbitrary::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    write_value(u, value, &mut out)?;
+    Ok(out)
+}
+
+/// Write a header whose argument uses the shortest width or any wider one
+fn write_header(
+    u: &mut Unstructured,
+    out: &mut Vec<u8>,
+    major: u8,
+    value: u64,
+) -> arbitrary::Result<()> {
+    let shortest = match value {
+        0..24 => 0,
+        24..0x100 => 1,
+        0x100..0x1_0000 => 2,
+        0x1_0000..0x1_0000_0000 => 3,
+        _ => 4,
+    };
+    // Favor 64-bit arguments, the width decoders see least
+    let width = if one_in(u, 4)? {
+        4
+    } else {
+        u.int_in_range(shortest..=4)?
+    };
+    match width {
+        0 => out.push((major << 5) | value as u8),
+        1 => out.extend_from_slice(&[(major << 5) | 24, value as u8]),
+        2 => {
+            out.push((major << 5) | 25);
+            out.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+        3 => {
+            out.push((major << 5) | 26);
+            out.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        _ => {
+            out.push((major << 5) | 27);
+            out.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+    Ok(())
+}
+
+/// Split `len` bytes into random chunk lengths, with empty chunks mixed in,
+/// keeping each split point acceptable to `is_boundary`
+fn chunk_lengths(
+    u: &mut Unstructured,
+    len: usize,
+    is_boundary: impl Fn(usize) -> bool,
+) -> arbitrary::Result<Vec<usize>> {
+    let mut lengths = Vec::new();
+    let mut start = 0;
+    while start < len {
+        if u.arbitrary()? {
+            lengths.push(0);
+        }
+        let mut end = u.int_in_range(start + 1..=len)?;
+        while !is_boundary(end) {
+            end += 1;
+        }
+        lengths.push(end - start);
+        start = end;
+    }
+    Ok(lengths)
+}
+
+fn write_value(u: &mut Unstructured, value: &Value, out: &mut Vec<u8>) -> arbitrary::Result<()> {
+    match value {
+        Value::Null => out.push(0xf6),
+        Value::Undefined => out.push(0xf7),
+        Value::Bool(b) => out.push(if *b { 0xf5 } else { 0xf4 }),
+        Value::Integer(i) if *i < 0 => write_header(u, out, MAJOR_NEGATIVE, (-1 - *i) as u64)?,
+        Value::Integer(i) => write_header(u, out, MAJOR_UNSIGNED, *i as u64)?,
+        Value::Float(f) => {
+            let info = u.int_in_range(preferred_float_info(*f)..=FLOAT64)?;
+            out.push((MAJOR_SIMPLE << 5) | info);
+            match info {
+                FLOAT16 => out.extend_from_slice(&half::f16::from_f64(*f).to_be_bytes()),
+                FLOAT32 => out.extend_from_slice(&(*f as f32).to_be_bytes()),
+                _ => out.extend_from_slice(&f.to_be_bytes()),
+            }
+        }
+        Value::Bytes(bytes) if one_in(u, 4)? => {
+            out.push((MAJOR_BYTES << 5) | INDEFINITE);
+            let mut rest = &bytes[..];
+            for len in chunk_lengths(u, bytes.len(), |_| true)? {
+                let (chunk, tail) = rest.split_at(len);
+                write_header(u, out, MAJOR_BYTES, len as u64)?;
+                out.extend_from_slice(chunk);
+                rest = tail;
+            }
+            out.push(BREAK);
+        }
+        Value::Bytes(bytes) => {
+            write_header(u, out, MAJOR_BYTES, bytes.len() as u64)?;
+            out.extend_from_slice(bytes);
+        }
+        Value::Text(text) if one_in(u, 4)? => {
+            out.push((MAJOR_TEXT << 5) | INDEFINITE);
+            let mut rest = &text[..];
+            for len in chunk_lengths(u, text.len(), |i| text.is_char_boundary(i))? {
+                let (chunk, tail) = rest.split_at(len);
+                write_header(u, out, MAJOR_TEXT, len as u64)?;
+                out.extend_from_slice(chunk.as_bytes());
+                rest = tail;
+            }
+            out.push(BREAK);
+        }
+        Value::Text(text) => {
+            write_header(u, out, MAJOR_TEXT, text.len() as u64)?;
+            out.extend_from_slice(text.as_bytes());
+        }
+        Value::Array(items) => {
+            let indefinite = one_in(u, 4)?;
+            if indefinite {
+                out.push((MAJOR_ARRAY << 5) | INDEFINITE);
+            } else {
+                write_header(u, out, MAJOR_ARRAY, items.len() as u64)?;
+            }
+            for item in items {
+                write_value(u, item, out)?;
+            }
+            if indefinite {
+                out.push(BREAK);
+            }
+        }
+        Value::Map(map) => write_map(u, map.iter(), out)?,
+        Value::OrderedMap(entries) => write_map(u, entries.iter().map(|(k, v)| (k, v)), out)?,
+        Value::Tag(tag, content) => {
+            write_header(u, out, MAJOR_TAG, *tag)?;
+            write_value(u, content, out)?;
+        }
+        Value::Simple(n) if *n < 24 => out.push((MAJOR_SIMPLE << 5) | n),
+        Value::Simple(n) => out.extend_from_slice(&[(MAJOR_SIMPLE << 5) | SIMPLE_VALUE, *n]),
+    }
+    Ok(())
+}
+
+fn write_map<'a>(
+    u: &mut Unstructured,
+    entries: impl ExactSizeIterator<Item = (&'a Value, &'a Value)>,
+    out: &mut Vec<u8>,
+) -> arbitrary::Result<()> {
+    let indefinite = one_in(u, 4)?;
+    if indefinite {
+        out.push((MAJOR_MAP << 5) | INDEFINITE);
+    } else {
+        write_header(u, out, MAJOR_MAP, entries.len() as u64)?;
+    }
+    for (k, v) in entries {
+        write_value(u, k, out)?;
+        write_value(u, v, out)?;
+    }
+    if indefinite {
+        out.push(BREAK);
+    }
+    Ok(())
+}
+
+/// Proptest strategy for [`Value`] with the same edge-case bias as its
+/// `Arbitrary` implementation
+#[cfg(feature = "proptest")]
+pub fn value_strategy() -> impl proptest::strategy::Strategy<Value = Value> {
+    use proptest::{collection, prelude::*, sample};
+
+    let scalar = prop_oneof![
+        Just(Value::Null),
+        Just(Value::Undefined),
+        any::<bool>().prop_map(Value::Bool),
+        prop_oneof![sample::select(EDGE_INTEGERS), any::<i64>()].prop_map(Value::Integer),
+        prop_oneof![sample::select(EDGE_FLOATS), any::<f64>()].prop_map(Value::Float),
+        collection::vec(any::<u8>(), 0..32).prop_map(Value::Bytes),
+        any::<String>().prop_map(Value::Text),
+        (0u8..=243).prop_map(simple_value),
+    ];
+    let tag = prop_oneof![
+        0u64..24,
+        24u64..0x100,
+        0x100u64..0x1_0000,
+        0x1_0000u64..0x1_0000_0000,
+        0x1_0000_0000u64..=u64::MAX,
+        sample::select(WELL_KNOWN_TAGS),
+    ];
+    let nested = scalar
+        .clone()
+        .prop_recursive(MAX_NESTING as u32, 64, 4, move |inner| {
+            prop_oneof![
+                collection::vec(inner.clone(), 0..4).prop_map(Value::Array),
+                collection::btree_map(inner.clone(), inner.clone(), 0..4).prop_map(Value::Map),
+                (tag.clone(), inner).prop_map(|(tag, v)| Value::Tag(tag, Box::new(v))),
+            ]
+        });
+    let deep = (scalar, 1..=DEEP_NESTING).prop_map(|(value, depth)| nest(value, depth));
+    prop_oneof![15 => nested, 1 => deep]
+}
+
+/// Proptest strategy for a [`Value`] and a document from
+/// [`arbitrary_document`] that decodes to it, less its tags
+#[cfg(feature = "proptest")]
+pub fn document_strategy() -> impl proptest::strategy::Strategy<Value = (Value, Vec<u8>)> {
+    use proptest::{collection, prelude::*};
+
+    (value_strategy(), collection::vec(any::<u8>(), 0..256)).prop_map(|(value, choices)| {
+        // Exhausted choices fall back to the preferred encoding, so this never
+        // fails
+        let bytes = arbitrary_document(&mut Unstructured::new(&choices), &value)
+            .expect("arbitrary_document only fails on invalid ranges");
+        (value, bytes)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use super::*;
+
+    /// Fixed pseudo-random input from an xorshift sequence
+    fn seed_data(mut state: u32, len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_arbitrary_values_round_trip() {
+        let mut reencoded = 0;
+        for seed in 1..500 {
+            let data = seed_data(seed, 512);
+            let mut u = Unstructured::new(&data);
+            let value = Value::arbitrary(&mut u).unwrap();
+            let untagged = value.clone().into_untagged();
+
+            let encoded = crate::to_vec(&value).unwrap();
+            let decoded: Value = crate::from_slice(&encoded).unwrap();
+            assert_eq!(decoded.cmp(&untagged), Ordering::Equal, "seed {seed}");
+
+            let document = arbitrary_document(&mut u, &value).unwrap();
+            reencoded += usize::from(document != encoded);
+            let decoded: Value = crate::from_slice(&document).unwrap();
+            assert_eq!(decoded.cmp(&untagged), Ordering::Equal, "seed {seed}");
+            let decoded = crate::Decoder::new(&document[..]).decode_value().unwrap();
+            assert_eq!(decoded.cmp(&untagged), Ordering::Equal, "seed {seed}");
+        }
+        assert!(reencoded > 0);
+    }
+
+    #[test]
+    fn test_arbitrary_document_edge_cases() {
+        // Without data every choice is the preferred encoding
+        let value = Value::Array(vec![Value::Text("héllo".to_string()), Value::Integer(1)]);
+        let document = arbitrary_document(&mut Unstructured::new(&[]), &value).unwrap();
+        assert_eq!(document, crate::to_vec(&value).unwrap());
+
+        let deep = nest(Value::Null, DEEP_NESTING);
+        let document = arbitrary_document(&mut Unstructured::new(&[]), &deep).unwrap();
+        assert_eq!(crate::from_slice::<Value>(&document).unwrap(), deep);
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn test_documents_decode_to_their_value((value, document) in document_strategy()) {
+            let decoded: Value = crate::from_slice(&document).unwrap();
+            proptest::prop_assert_eq!(decoded.cmp(&value.into_untagged()), Ordering::Equal);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Hash encoded bytes as they are written or read
+//!
+//! [`DigestWriter`] and [`DigestReader`] wrap a writer or reader and pass
+//! every byte that goes through them to a callback, so a digest can be
+//! computed in the same pass as encoding or decoding. The callback is any
+//! `FnMut(&[u8])`; with the `digest` crate traits (as implemented by `sha2`)
+//! that is `|bytes| hasher.update(bytes)`.
+//!
+//! [`Decoder`](crate::Decoder) reads no further than the end of the item it
+//! decodes, so a `DigestReader` passed to `Decoder::new` hashes exactly the
+//! bytes of that item. [`from_reader`](crate::from_reader) buffers its input
+//! and may read ahead, so use a `Decoder` directly for this.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::{Decoder, DigestReader, DigestWriter};
+//!
+//! // A toy checksum; any hasher's update method works the same way
+//! let mut written = 0u32;
+//! let mut out = Vec::new();
+//! let writer = DigestWriter::new(&mut out, |b: &[u8]| written += b.len() as u32);
+//! c2pa_cbor::to_writer(writer, &[1, 2, 3]).unwrap();
+//! assert_eq!(written, 4);
+//!
+//! // Two items back to back; only the first is hashed
+//! out.extend([0x05]);
+//! let mut read = Vec::new();
+//! let reader = DigestReader::new(&out[..], |b: &[u8]| read.extend_from_slice(b));
+//! let mut decoder = Decoder::new(reader);
+//! assert_eq!(decoder.decode::<Vec<u8>>().unwrap(), [1, 2, 3]);
+//! drop(decoder);
+//! assert_eq!(read, [0x83, 0x01, 0x02, 0x03]);
+//! ```
+
+use std::io::{self, Read, Write};
+
+/// Writer that passes every byte written through it to `update`
+pub struct DigestWriter<W, F> {
+    inner: W,
+    update: F,
+}
+
+impl<W: Write, F: FnMut(&[u8])> DigestWriter<W, F> {
+    /// Wrap `inner`, calling `update` with each run of bytes written to it
+    pub fn new(inner: W, update: F) -> Self {
+        DigestWriter { inner, update }
+    }
+
+    /// The wrapped writer
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Return the wrapped writer and the callback
+    pub fn into_parts(self) -> (W, F) {
+        (self.inner, self.update)
+    }
+}
+
+impl<W: Write, F: FnMut(&[u8])> Write for DigestWriter<W, F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Only hash what the inner writer accepted
+        let n = self.inner.write(buf)?;
+        (self.update)(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Reader that passes every byte read through it to `update`
+pub struct DigestReader<R, F> {
+    inner: R,
+    update: F,
+}
+
+impl<R: Read, F: FnMut(&[u8])> DigestReader<R, F> {
+    /// Wrap `inner`, calling `update` with each run of bytes read from it
+    pub fn new(inner: R, update: F) -> Self {
+        DigestReader { inner, update }
+    }
+
+    /// The wrapped reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Return the wrapped reader and the callback
+    pub fn into_parts(self) -> (R, F) {
+        (self.inner, self.update)
+    }
+}
+
+impl<R: Read, F: FnMut(&[u8])> Read for DigestReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        (self.update)(&buf[..n]);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Decoder;
+
+    /// Writer that accepts at most 3 bytes per call
+    struct Trickle(Vec<u8>);
+
+    impl Write for Trickle {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let n = buf.len().min(3);
+            self.0.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_digest_writer_sees_written_bytes() {
+        let mut seen = Vec::new();
+        let mut writer =
+            DigestWriter::new(Trickle(Vec::new()), |b: &[u8]| seen.extend_from_slice(b));
+        crate::to_writer(&mut writer, &"a longer text string").unwrap();
+        let (inner, _) = writer.into_parts();
+        assert_eq!(seen, inner.0);
+        assert_eq!(seen, crate::to_vec(&"a longer text string").unwrap());
+    }
+
+    #[test]
+    fn test_digest_reader_covers_one_item() {
+        // {"a": (_ h'01', h'02')} then 0
+        let data = [0xa1, 0x61, b'a', 0x5f, 0x41, 0x01, 0x41, 0x02, 0xff, 0x00];
+        let mut seen = Vec::new();
+        let mut decoder = Decoder::new(DigestReader::new(&data[..], |b: &[u8]| {
+            seen.extend_from_slice(b)
+        }));
+        decoder.decode::<crate::Value>().unwrap();
+        drop(decoder);
+        assert_eq!(seen, data[..9]);
+    }
+
+    #[cfg(feature = "signing")]
+    #[test]
+    fn test_digest_writer_with_sha256() {
+        use sha2::{Digest, Sha256};
+
+        let claim = std::collections::BTreeMap::from([("alg", "ps256")]);
+        let mut hasher = Sha256::new();
+        let mut out = Vec::new();
+        crate::to_writer(
+            DigestWriter::new(&mut out, |b: &[u8]| hasher.update(b)),
+            &claim,
+        )
+        .unwrap();
+        assert_eq!(
+            hasher.finalize().to_vec(),
+            crate::signing::HashAlgorithm::Sha256.digest(&out)
+        );
+    }
+}
@@ -0,0 +1,426 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! The header of a CBOR item: major type, additional info and argument
+//!
+//! [`Decoder::read_header`](crate::Decoder::read_header) and
+//! [`Encoder::write_header`](crate::Encoder::write_header) read and write one
+//! header at a time, leaving the content (string bytes, elements, tag content)
+//! to the caller. The [`Argument`] keeps the width it was encoded with, so
+//! copying headers through reproduces the input byte for byte, including
+//! non-preferred encodings and float widths.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::{Argument, Decoder, Encoder, Header, MajorType};
+//!
+//! // Reverse the elements of [1, 2, 500]
+//! let data = [0x83, 0x01, 0x02, 0x19, 0x01, 0xf4];
+//! let mut decoder = Decoder::from_slice(&data);
+//! let array = decoder.read_header().unwrap();
+//! assert_eq!(array, Header::new(MajorType::Array, 3));
+//! let mut elements = Vec::new();
+//! for _ in 0..3 {
+//!     elements.push(decoder.read_header().unwrap());
+//! }
+//! assert_eq!(elements[2].value, Argument::U16(500));
+//!
+//! let mut encoder = Encoder::new(Vec::new());
+//! encoder.write_header(array).unwrap();
+//! for header in elements.into_iter().rev() {
+//!     encoder.write_header(header).unwrap();
+//! }
+//! assert_eq!(encoder.into_inner(), [0x83, 0x19, 0x01, 0xf4, 0x02, 0x01]);
+//! ```
+
+use crate::{Error, Result, constants::*};
+
+/// Major type of a CBOR item (RFC 8949 §3.1)
+///
+/// Reported by [`Decoder::peek_major_type`](crate::Decoder::peek_major_type)
+/// and held in a [`Header`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MajorType {
+    /// Unsigned integer (major type 0)
+    Unsigned,
+    /// Negative integer (major type 1)
+    Negative,
+    /// Byte string (major type 2)
+    Bytes,
+    /// Text string (major type 3)
+    Text,
+    /// Array (major type 4)
+    Array,
+    /// Map (major type 5)
+    Map,
+    /// Tag (major type 6)
+    Tag,
+    /// Simple value, float or break (major type 7)
+    Simple,
+}
+
+impl MajorType {
+    /// The major type encoded in the top three bits of an initial byte
+    pub fn from_initial_byte(initial: u8) -> Self {
+        match initial >> 5 {
+            MAJOR_UNSIGNED => MajorType::Unsigned,
+            MAJOR_NEGATIVE => MajorType::Negative,
+            MAJOR_BYTES => MajorType::Bytes,
+            MAJOR_TEXT => MajorType::Text,
+            MAJOR_ARRAY => MajorType::Array,
+            MAJOR_MAP => MajorType::Map,
+            MAJOR_TAG => MajorType::Tag,
+            _ => MajorType::Simple,
+        }
+    }
+
+    /// The major type numbered `number` (0–7)
+    pub fn from_number(number: u8) -> Option<Self> {
+        (number < 8).then(|| MajorType::from_initial_byte(number << 5))
+    }
+
+    /// The major type number (0–7), the top three bits of an initial byte
+    pub fn number(self) -> u8 {
+        match self {
+            MajorType::Unsigned => MAJOR_UNSIGNED,
+            MajorType::Negative => MAJOR_NEGATIVE,
+            MajorType::Bytes => MAJOR_BYTES,
+            MajorType::Text => MAJOR_TEXT,
+            MajorType::Array => MAJOR_ARRAY,
+            MajorType::Map => MAJOR_MAP,
+            MajorType::Tag => MAJOR_TAG,
+            MajorType::Simple => MAJOR_SIMPLE,
+        }
+    }
+}
+
+/// The argument of an item header, with the width it is encoded in
+///
+/// For major type 7 the argument is a simple value or the bits of a float:
+/// `Immediate(20)` is `false`, `U8(n)` is simple value `n`, and `U16`, `U32`
+/// and `U64` are half, single and double precision floats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Argument {
+    /// A value below 24, held in the initial byte
+    Immediate(u8),
+    /// One byte following the initial byte (additional info 24)
+    U8(u8),
+    /// Two bytes following the initial byte (additional info 25)
+    U16(u16),
+    /// Four bytes following the initial byte (additional info 26)
+    U32(u32),
+    /// Eight bytes following the initial byte (additional info 27)
+    U64(u64),
+    /// Indefinite length (additional info 31), or a break for major type 7
+    Indefinite,
+}
+
+impl Argument {
+    /// The preferred (shortest) encoding of `value`
+    pub fn new(value: u64) -> Self {
+        if value < 24 {
+            Argument::Immediate(value as u8)
+        } else if let Ok(value) = u8::try_from(value) {
+            Argument::U8(value)
+        } else if let Ok(value) = u16::try_from(value) {
+            Argument::U16(value)
+        } else if let Ok(value) = u32::try_from(value) {
+            Argument::U32(value)
+        } else {
+            Argument::U64(value)
+        }
+    }
+
+    /// The argument encoded with additional info `info` (0–27 or 31)
+    ///
+    /// Returns `None` if `info` is reserved, or `value` does not fit the
+    /// width `info` stands for.
+    pub fn from_info(info: u8, value: u64) -> Option<Self> {
+        Some(match info {
+            0..=23 => (value == u64::from(info)).then_some(Argument::Immediate(info))?,
+            24 => Argument::U8(value.try_into().ok()?),
+            25 => Argument::U16(value.try_into().ok()?),
+            26 => Argument::U32(value.try_into().ok()?),
+            27 => Argument::U64(value),
+            INDEFINITE => Argument::Indefinite,
+            _ => return None,
+        })
+    }
+
+    /// The value of the argument, or `None` if indefinite
+    pub fn value(self) -> Option<u64> {
+        match self {
+            Argument::Immediate(value) | Argument::U8(value) => Some(value.into()),
+            Argument::U16(value) => Some(value.into()),
+            Argument::U32(value) => Some(value.into()),
+            Argument::U64(value) => Some(value),
+            Argument::Indefinite => None,
+        }
+    }
+
+    /// The additional info bits of the initial byte
+    pub fn additional_info(self) -> u8 {
+        match self {
+            Argument::Immediate(value) => value,
+            Argument::U8(_) => 24,
+            Argument::U16(_) => 25,
+            Argument::U32(_) => 26,
+            Argument::U64(_) => 27,
+            Argument::Indefinite => INDEFINITE,
+        }
+    }
+
+    /// The number of argument bytes after the initial byte: 0, 1, 2, 4 or 8
+    pub fn width(self) -> usize {
+        match self {
+            Argument::Immediate(_) | Argument::Indefinite => 0,
+            Argument::U8(_) => 1,
+            Argument::U16(_) => 2,
+            Argument::U32(_) => 4,
+            Argument::U64(_) => 8,
+        }
+    }
+
+    /// Whether this is the shortest encoding of its value
+    pub fn is_preferred(self) -> bool {
+        match self.value() {
+            Some(value) => Argument::new(value) == self,
+            None => true,
+        }
+    }
+}
+
+/// The number of argument bytes following `initial`
+pub(crate) fn argument_len(initial: u8) -> usize {
+    match initial & 0x1f {
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        _ => 0,
+    }
+}
+
+/// The header of a CBOR item (RFC 8949 §3)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Header {
+    /// The major type
+    pub major: MajorType,
+    /// The argument: a length, an integer, a tag number, a simple value or float bits
+    pub value: Argument,
+}
+
+impl Header {
+    /// A header with the preferred encoding of `value`
+    pub fn new(major: MajorType, value: u64) -> Self {
+        Header {
+            major,
+            value: Argument::new(value),
+        }
+    }
+
+    /// The header of an indefinite-length string or collection, or a break
+    pub fn indefinite(major: MajorType) -> Self {
+        Header {
+            major,
+            value: Argument::Indefinite,
+        }
+    }
+
+    /// Decode a header from an initial byte and the bytes that follow it
+    ///
+    /// Returns the header and the number of bytes it takes.
+    pub fn from_slice(bytes: &[u8]) -> Result<(Self, usize)> {
+        let &initial = bytes.first().ok_or(Error::Eof)?;
+        let len = 1 + argument_len(initial);
+        let following = bytes.get(1..len).ok_or(Error::Eof)?;
+        let header = Header::from_parts(initial, following)?;
+        Ok((header, len))
+    }
+
+    /// Build a header from its initial byte and the argument bytes after it,
+    /// of which there must be [`argument_len`]
+    pub(crate) fn from_parts(initial: u8, following: &[u8]) -> Result<Self> {
+        let value = match initial & 0x1f {
+            info @ 0..=23 => Argument::Immediate(info),
+            24 => Argument::U8(following[0]),
+            25 => Argument::U16(u16::from_be_bytes([following[0], following[1]])),
+            26 => Argument::U32(u32::from_be_bytes(following[..4].try_into().unwrap())),
+            27 => Argument::U64(u64::from_be_bytes(following[..8].try_into().unwrap())),
+            INDEFINITE => Argument::Indefinite,
+            info => {
+                return Err(Error::Syntax(format!(
+                    "Reserved additional info {} in header",
+                    info
+                )));
+            }
+        };
+        let header = Header {
+            major: MajorType::from_initial_byte(initial),
+            value,
+        };
+        header.check()?;
+        Ok(header)
+    }
+
+    /// Check that the header is well-formed
+    pub fn check(&self) -> Result<()> {
+        match (self.major, self.value) {
+            (_, Argument::Immediate(value)) if value >= 24 => Err(Error::Syntax(format!(
+                "Immediate argument {} does not fit in the initial byte",
+                value
+            ))),
+            (MajorType::Unsigned | MajorType::Negative | MajorType::Tag, Argument::Indefinite) => {
+                Err(Error::Syntax(format!(
+                    "Major type {} cannot be indefinite",
+                    self.major.number()
+                )))
+            }
+            // Simple values below 32 must use the one-byte form
+            (MajorType::Simple, Argument::U8(value)) if value < 32 => Err(Error::Syntax(format!(
+                "Simple value {} must be encoded in the initial byte",
+                value
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// The encoded header: the initial byte and the argument bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.encoded_len());
+        out.push((self.major.number() << 5) | self.value.additional_info());
+        match self.value {
+            Argument::U8(value) => out.push(value),
+            Argument::U16(value) => out.extend_from_slice(&value.to_be_bytes()),
+            Argument::U32(value) => out.extend_from_slice(&value.to_be_bytes()),
+            Argument::U64(value) => out.extend_from_slice(&value.to_be_bytes()),
+            Argument::Immediate(_) | Argument::Indefinite => {}
+        }
+        out
+    }
+
+    /// The number of bytes the encoded header takes
+    pub fn encoded_len(&self) -> usize {
+        1 + self.value.width()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Decoder, Encoder};
+
+    #[test]
+    fn test_major_type_numbers() {
+        for number in 0..8 {
+            let major = MajorType::from_number(number).unwrap();
+            assert_eq!(major.number(), number);
+            assert_eq!(MajorType::from_initial_byte(number << 5 | 0x1f), major);
+        }
+        assert_eq!(MajorType::from_number(8), None);
+    }
+
+    #[test]
+    fn test_argument_widths() {
+        for (value, argument, len) in [
+            (0, Argument::Immediate(0), 1),
+            (23, Argument::Immediate(23), 1),
+            (24, Argument::U8(24), 2),
+            (256, Argument::U16(256), 3),
+            (65536, Argument::U32(65536), 5),
+            (u64::MAX, Argument::U64(u64::MAX), 9),
+        ] {
+            assert_eq!(Argument::new(value), argument);
+            assert_eq!(argument.value(), Some(value));
+            let header = Header::new(MajorType::Bytes, value);
+            assert_eq!(header.encoded_len(), len);
+            assert_eq!(
+                Header::from_slice(&header.to_bytes()).unwrap(),
+                (header, len)
+            );
+        }
+        assert_eq!(Argument::from_info(25, 10), Some(Argument::U16(10)));
+        assert_eq!(Argument::from_info(24, 256), None);
+        assert_eq!(Argument::from_info(5, 6), None);
+        assert_eq!(Argument::from_info(28, 0), None);
+        assert_eq!(Argument::U32(1).width(), 4);
+        assert!(Argument::new(10).is_preferred());
+        assert!(!Argument::U16(10).is_preferred());
+        assert_eq!(Argument::Indefinite.value(), None);
+    }
+
+    #[test]
+    fn test_read_and_write_headers_round_trip() {
+        // 0(h'') with a non-preferred length, a half float, simple(255),
+        // an empty indefinite text string and its break
+        let data = [0xc0, 0x58, 0x00, 0xf9, 0x3c, 0x00, 0xf8, 0xff, 0x7f, 0xff];
+        let mut decoder = Decoder::from_slice(&data);
+        let mut encoder = Encoder::new(Vec::new());
+        let mut headers = Vec::new();
+        for _ in 0..5 {
+            let header = decoder.read_header().unwrap();
+            encoder.write_header(header).unwrap();
+            headers.push(header);
+        }
+        // The break is read the same way
+        let end = decoder.read_header().unwrap();
+        encoder.write_header(end).unwrap();
+        decoder.end().unwrap();
+
+        assert_eq!(
+            headers,
+            [
+                Header::new(MajorType::Tag, 0),
+                Header {
+                    major: MajorType::Bytes,
+                    value: Argument::U8(0),
+                },
+                Header {
+                    major: MajorType::Simple,
+                    value: Argument::U16(0x3c00),
+                },
+                Header::new(MajorType::Simple, 255),
+                Header::indefinite(MajorType::Text),
+            ]
+        );
+        assert_eq!(end, Header::indefinite(MajorType::Simple));
+        assert_eq!(encoder.into_inner(), data);
+    }
+
+    #[test]
+    fn test_malformed_headers_are_rejected() {
+        // Reserved additional info, indefinite integer, two-byte simple value below 32
+        for data in [[0x1c_u8, 0x00], [0x1f, 0x00], [0xf8, 0x14]] {
+            assert!(Header::from_slice(&data).is_err(), "{:02x?}", data);
+            assert!(
+                Decoder::from_slice(&data).read_header().is_err(),
+                "{:02x?}",
+                data
+            );
+        }
+        assert!(matches!(Header::from_slice(&[0x19, 0x01]), Err(Error::Eof)));
+
+        let mut encoder = Encoder::new(Vec::new());
+        let too_big = Header {
+            major: MajorType::Unsigned,
+            value: Argument::Immediate(24),
+        };
+        assert!(encoder.write_header(too_big).is_err());
+        assert!(
+            encoder
+                .write_header(Header::indefinite(MajorType::Tag))
+                .is_err()
+        );
+        assert!(encoder.into_inner().is_empty());
+    }
+}
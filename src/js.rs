@@ -0,0 +1,81 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! JavaScript entry points for WebAssembly builds
+//!
+//! Enabled with the `js` feature, which also enables `json`. The functions
+//! here are exported with `wasm-bindgen` under camelCase names, taking and
+//! returning `Uint8Array`s for CBOR and strings for JSON and diagnostic
+//! notation. Errors are thrown as JavaScript `Error`s carrying the message
+//! of the [`Error`](crate::Error).
+//!
+//! ```js
+//! import { jsonToCbor, cborToJson, toDiagnostic } from "./c2pa_cbor.js";
+//!
+//! const cbor = jsonToCbor('{"label": "c2pa.actions"}'); // Uint8Array
+//! toDiagnostic(cbor); // '{"label": "c2pa.actions"}'
+//! cborToJson(cbor); // '{"label":"c2pa.actions"}'
+//! ```
+//!
+//! On other targets they are ordinary Rust functions, but an error can only
+//! be created inside a JavaScript host.
+
+use wasm_bindgen::prelude::*;
+
+/// Transcode a JSON document to CBOR
+#[wasm_bindgen(js_name = jsonToCbor)]
+pub fn json_to_cbor(json: &str) -> Result<Vec<u8>, JsError> {
+    let mut cbor = Vec::new();
+    crate::transcode_json_to_cbor(json.as_bytes(), &mut cbor)?;
+    Ok(cbor)
+}
+
+/// Transcode a CBOR item to JSON, as [`transcode_cbor_to_json`](crate::transcode_cbor_to_json) does
+#[wasm_bindgen(js_name = cborToJson)]
+pub fn cbor_to_json(cbor: &[u8]) -> Result<String, JsError> {
+    crate::validate(cbor)?;
+    let mut json = Vec::new();
+    crate::transcode_cbor_to_json(cbor, &mut json)?;
+    Ok(String::from_utf8(json)?)
+}
+
+/// Render a CBOR item in diagnostic notation, see [`to_diagnostic`](crate::to_diagnostic)
+#[wasm_bindgen(js_name = toDiagnostic)]
+pub fn to_diagnostic(cbor: &[u8]) -> Result<String, JsError> {
+    Ok(crate::to_diagnostic(cbor)?)
+}
+
+/// Check that the input is exactly one well-formed CBOR item, see [`validate`](fn@crate::validate)
+#[wasm_bindgen]
+pub fn validate(cbor: &[u8]) -> Result<(), JsError> {
+    Ok(crate::validate(cbor)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let cbor = json_to_cbor(r#"{"label": "c2pa.actions", "count": [1, 2.5]}"#).unwrap();
+        validate(&cbor).unwrap();
+        assert_eq!(
+            to_diagnostic(&cbor).unwrap(),
+            crate::to_diagnostic(&cbor).unwrap()
+        );
+        assert_eq!(
+            cbor_to_json(&cbor).unwrap(),
+            r#"{"label":"c2pa.actions","count":[1,2.5]}"#
+        );
+    }
+}
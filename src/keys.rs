@@ -0,0 +1,458 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Integer map keys for struct fields
+//!
+//! A field renamed to `__cbor_key_N__` is written with the integer key `N`
+//! by [`Encoder`](crate::Encoder) and [`to_value`](crate::to_value), as in
+//! COSE headers. Serde matches integer keys to fields by position, so reading
+//! them back goes through [`IntegerKeys`], which hands each integer key to the
+//! struct as the name of the field declared with it. `#[cbor(key = N)]` with
+//! `#[derive(CborTagged)]` (the `derive` feature) does both.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::{Decoder, keys::IntegerKeys};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Header {
+//!     #[serde(rename = "__cbor_key_1__")]
+//!     alg: i64,
+//!     #[serde(rename = "__cbor_key_-1__")]
+//!     crv: i64,
+//!     kid: String,
+//! }
+//!
+//! let header = Header { alg: -7, crv: 1, kid: "a".to_string() };
+//! let cbor = c2pa_cbor::to_vec(&header).unwrap();
+//! // {1: -7, -1: 1, "kid": "a"}
+//! assert_eq!(cbor, [0xa3, 0x01, 0x26, 0x20, 0x01, 0x63, b'k', b'i', b'd', 0x61, b'a']);
+//!
+//! let mut decoder = Decoder::from_slice(&cbor);
+//! assert_eq!(Header::deserialize(IntegerKeys(&mut decoder)).unwrap(), header);
+//! ```
+
+use std::fmt;
+
+use serde::{
+    Deserializer, Serialize, Serializer,
+    de::{self, DeserializeSeed, Visitor},
+};
+
+use crate::constants::INTEGER_KEY_PREFIX;
+
+/// The integer key of a field named `__cbor_key_N__`
+pub(crate) fn integer_key(name: &str) -> Option<i64> {
+    name.strip_prefix(INTEGER_KEY_PREFIX)?
+        .strip_suffix("__")?
+        .parse()
+        .ok()
+}
+
+/// A struct field name, written as an integer when it names an integer key
+pub(crate) struct FieldKey(pub &'static str);
+
+impl Serialize for FieldKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match integer_key(self.0) {
+            Some(key) => serializer.serialize_i64(key),
+            None => serializer.serialize_str(self.0),
+        }
+    }
+}
+
+/// A deserializer that reads integer map keys as `__cbor_key_N__` field names
+///
+/// Only structs are affected, and only for integers that one of their fields
+/// declares; other integer keys still select fields by position. Nested
+/// values are read from the wrapped deserializer unchanged.
+pub struct IntegerKeys<D>(pub D);
+
+macro_rules! forward_deserialize {
+    ($inner:tt; $($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+                self.$inner.$method(visitor)
+            }
+        )*
+    };
+}
+
+macro_rules! forward_visit {
+    ($($method:ident: $ty:ty)*) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> Result<Self::Value, E> {
+                self.visitor.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for IntegerKeys<D> {
+    type Error = D::Error;
+
+    forward_deserialize! {
+        0;
+        deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32
+        deserialize_u64 deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char
+        deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
+        deserialize_option deserialize_unit deserialize_seq deserialize_map
+        deserialize_identifier deserialize_ignored_any
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0.deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0.deserialize_tuple_struct(name, len, visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0
+            .deserialize_struct(name, fields, StructVisitor { visitor, fields })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.0.is_human_readable()
+    }
+}
+
+/// Passes the struct's map on with its keys translated
+struct StructVisitor<V> {
+    visitor: V,
+    fields: &'static [&'static str],
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for StructVisitor<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.visitor.expecting(formatter)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        self.visitor.visit_seq(seq)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        self.visitor.visit_map(KeyedMap {
+            map,
+            fields: self.fields,
+        })
+    }
+}
+
+struct KeyedMap<A> {
+    map: A,
+    fields: &'static [&'static str],
+}
+
+impl<'de, A: de::MapAccess<'de>> de::MapAccess<'de> for KeyedMap<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        self.map.next_key_seed(KeySeed {
+            seed,
+            fields: self.fields,
+        })
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, A::Error> {
+        self.map.next_value_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.map.size_hint()
+    }
+}
+
+struct KeySeed<K> {
+    seed: K,
+    fields: &'static [&'static str],
+}
+
+impl<'de, K: DeserializeSeed<'de>> DeserializeSeed<'de> for KeySeed<K> {
+    type Value = K::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<K::Value, D::Error> {
+        self.seed.deserialize(KeyDeserializer {
+            de: deserializer,
+            fields: self.fields,
+        })
+    }
+}
+
+/// A map key, whose field identifier is looked up among the declared integer keys
+struct KeyDeserializer<D> {
+    de: D,
+    fields: &'static [&'static str],
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for KeyDeserializer<D> {
+    type Error = D::Error;
+
+    forward_deserialize! {
+        de;
+        deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32
+        deserialize_u64 deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char
+        deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
+        deserialize_option deserialize_unit deserialize_seq deserialize_map
+        deserialize_ignored_any
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        self.de.deserialize_identifier(KeyVisitor {
+            visitor,
+            fields: self.fields,
+        })
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.de.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.de.deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.de.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.de.deserialize_tuple_struct(name, len, visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.de.deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.de.deserialize_enum(name, variants, visitor)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.de.is_human_readable()
+    }
+}
+
+struct KeyVisitor<V> {
+    visitor: V,
+    fields: &'static [&'static str],
+}
+
+impl<V> KeyVisitor<V> {
+    /// The field declared with integer key `key`
+    fn field(&self, key: i64) -> Option<&'static str> {
+        self.fields
+            .iter()
+            .copied()
+            .find(|field| integer_key(field) == Some(key))
+    }
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for KeyVisitor<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.visitor.expecting(formatter)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        match self.field(v) {
+            Some(field) => self.visitor.visit_str(field),
+            None => self.visitor.visit_i64(v),
+        }
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        match i64::try_from(v).ok().and_then(|key| self.field(key)) {
+            Some(field) => self.visitor.visit_str(field),
+            None => self.visitor.visit_u64(v),
+        }
+    }
+
+    forward_visit! {
+        visit_bool: bool
+        visit_i128: i128
+        visit_u128: u128
+        visit_f64: f64
+        visit_str: &str
+        visit_borrowed_str: &'de str
+        visit_string: String
+        visit_bytes: &[u8]
+        visit_borrowed_bytes: &'de [u8]
+        visit_byte_buf: Vec<u8>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::{Decoder, Value, from_slice, to_value};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Header {
+        #[serde(rename = "__cbor_key_1__")]
+        alg: i64,
+        #[serde(rename = "__cbor_key_4__")]
+        kid: Option<String>,
+        label: String,
+    }
+
+    fn header() -> Header {
+        Header {
+            alg: -7,
+            kid: Some("k".to_string()),
+            label: "x".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_integer_key_names() {
+        assert_eq!(integer_key("__cbor_key_3__"), Some(3));
+        assert_eq!(integer_key("__cbor_key_-8__"), Some(-8));
+        assert_eq!(integer_key("__cbor_key_x__"), None);
+        assert_eq!(integer_key("key_3"), None);
+    }
+
+    #[test]
+    fn test_round_trip_through_decoder() {
+        let cbor = crate::to_vec(&header()).unwrap();
+        assert_eq!(&cbor[..3], [0xa3, 0x01, 0x26]);
+        assert_eq!(cbor[3], 0x04);
+
+        let mut decoder = Decoder::from_slice(&cbor);
+        assert_eq!(
+            Header::deserialize(IntegerKeys(&mut decoder)).unwrap(),
+            header()
+        );
+    }
+
+    #[test]
+    fn test_to_value_writes_integer_keys() {
+        let value = to_value(header()).unwrap();
+        let Value::Map(map) = &value else {
+            panic!("expected a map, got {:?}", value);
+        };
+        assert!(map.contains_key(&Value::Integer(1)));
+        assert!(map.contains_key(&Value::Integer(4)));
+        assert!(map.contains_key(&Value::Text("label".to_string())));
+
+        let cbor = crate::to_vec(&value).unwrap();
+        let mut decoder = Decoder::from_slice(&cbor);
+        assert_eq!(
+            Header::deserialize(IntegerKeys(&mut decoder)).unwrap(),
+            header()
+        );
+        // Without the adapter, integer keys select fields by position
+        assert!(from_slice::<Header>(&cbor).is_err());
+    }
+
+    #[test]
+    fn test_undeclared_integer_keys_select_by_position() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Pair {
+            #[serde(rename = "__cbor_key_5__")]
+            first: u8,
+            second: u8,
+        }
+
+        // {5: 1, 1: 2}: key 1 is not declared, so it is the second field
+        let cbor = [0xa2, 0x05, 0x01, 0x01, 0x02];
+        let mut decoder = Decoder::from_slice(&cbor);
+        let pair = Pair::deserialize(IntegerKeys(&mut decoder)).unwrap();
+        assert_eq!(
+            pair,
+            Pair {
+                first: 1,
+                second: 2
+            }
+        );
+    }
+}
@@ -0,0 +1,414 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Lazy access to encoded CBOR, decoding only what is asked for
+//!
+//! Enabled with the `lazy` feature. A [`LazyValue`] is a handle on an
+//! encoded item. Looking up a map key or an array element scans the headers
+//! of the entries before it, skipping over their content, and returns
+//! another handle; scalars are read when asked for. Nothing after the
+//! requested item is read, and no sibling is decoded, so reading a few
+//! fields of a large document costs little more than finding them.
+//!
+//! Unlike `view::CborView`, any well-formed encoding is accepted,
+//! indefinite lengths included, and nothing is checked up front: malformed
+//! input is reported by whichever call reaches it. Strings are borrowed
+//! unless they have indefinite length.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::lazy::LazyValue;
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Claim {
+//!     title: String,
+//!     alg: String,
+//!     assertions: Vec<String>,
+//! }
+//!
+//! let claim = Claim {
+//!     title: "photo.jpg".to_string(),
+//!     alg: "sha256".to_string(),
+//!     assertions: vec!["c2pa.actions".to_string(), "c2pa.hash.data".to_string()],
+//! };
+//! let cbor = c2pa_cbor::to_vec(&claim).unwrap();
+//!
+//! let root = LazyValue::new(&cbor);
+//! assert_eq!(root.get("alg").unwrap().unwrap().as_text().unwrap(), "sha256");
+//! let second = root.get("assertions").unwrap().unwrap().index(1).unwrap().unwrap();
+//! assert_eq!(second.as_text().unwrap(), "c2pa.hash.data");
+//! ```
+
+use std::borrow::Cow;
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+    Error, MajorType, Result, ValueRef,
+    constants::*,
+    raw::{SliceReader, float_from_arg, item_end},
+    value_ref::read_string,
+};
+
+/// A handle on an encoded item, read on demand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LazyValue<'a> {
+    // Starts with the item; anything after it is ignored
+    bytes: &'a [u8],
+}
+
+impl<'a> LazyValue<'a> {
+    /// A handle on the first item in `bytes`
+    ///
+    /// Nothing is read yet, and anything after the first item is ignored.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        LazyValue { bytes }
+    }
+
+    fn header(&self) -> Result<(u8, u8, Option<u64>, SliceReader<'a>)> {
+        let mut reader = SliceReader::new(self.bytes);
+        let (major, info, arg) = reader.read_header()?;
+        Ok((major, info, arg, reader))
+    }
+
+    fn at(&self, pos: usize) -> LazyValue<'a> {
+        LazyValue {
+            bytes: &self.bytes[pos..],
+        }
+    }
+
+    /// The encoded bytes of this item, found by skipping over it
+    pub fn as_raw(&self) -> Result<&'a [u8]> {
+        Ok(&self.bytes[..item_end(self.bytes, 0)?])
+    }
+
+    /// Major type of this item
+    pub fn major(&self) -> Result<MajorType> {
+        let initial = *self.bytes.first().ok_or(Error::Eof)?;
+        Ok(MajorType::from_initial_byte(initial))
+    }
+
+    /// Tag number of this item, if it is tagged
+    pub fn tag(&self) -> Result<Option<u64>> {
+        Ok(match self.header()? {
+            (MAJOR_TAG, _, arg, _) => arg,
+            _ => None,
+        })
+    }
+
+    /// The item inside any tags
+    pub fn untagged(&self) -> Result<LazyValue<'a>> {
+        let mut value = *self;
+        while let (MAJOR_TAG, _, _, reader) = value.header()? {
+            value = value.at(reader.position());
+        }
+        Ok(value)
+    }
+
+    /// Read an integer that fits in `i64`
+    pub fn as_i64(&self) -> Result<i64> {
+        match self.header()? {
+            (MAJOR_UNSIGNED, _, Some(n), _) => i64::try_from(n).ok(),
+            (MAJOR_NEGATIVE, _, Some(n), _) => i64::try_from(n).ok().map(|n| -1 - n),
+            _ => return Err(Error::Syntax("Expected integer".to_string())),
+        }
+        .ok_or_else(|| Error::Syntax("Integer out of range".to_string()))
+    }
+
+    /// Read an unsigned integer
+    pub fn as_u64(&self) -> Result<u64> {
+        match self.header()? {
+            (MAJOR_UNSIGNED, _, Some(n), _) => Ok(n),
+            _ => Err(Error::Syntax("Expected unsigned integer".to_string())),
+        }
+    }
+
+    /// Read a float
+    pub fn as_f64(&self) -> Result<f64> {
+        match self.header()? {
+            (MAJOR_SIMPLE, info @ (FLOAT16 | FLOAT32 | FLOAT64), Some(bits), _) => {
+                Ok(float_from_arg(info, bits))
+            }
+            _ => Err(Error::Syntax("Expected float".to_string())),
+        }
+    }
+
+    /// Read a boolean
+    pub fn as_bool(&self) -> Result<bool> {
+        match self.bytes.first() {
+            Some(0xf4) => Ok(false),
+            Some(0xf5) => Ok(true),
+            _ => Err(Error::Syntax("Expected boolean".to_string())),
+        }
+    }
+
+    /// Whether this item is `null`
+    pub fn is_null(&self) -> bool {
+        self.bytes.first() == Some(&0xf6)
+    }
+
+    /// The content of a byte string, borrowed unless it has indefinite length
+    pub fn as_bytes(&self) -> Result<Cow<'a, [u8]>> {
+        match self.header()? {
+            (MAJOR_BYTES, _, arg, mut reader) => read_string(&mut reader, MAJOR_BYTES, arg),
+            _ => Err(Error::Syntax("Expected byte string".to_string())),
+        }
+    }
+
+    /// The content of a text string, borrowed unless it has indefinite length
+    pub fn as_text(&self) -> Result<Cow<'a, str>> {
+        let (MAJOR_TEXT, _, arg, mut reader) = self.header()? else {
+            return Err(Error::Syntax("Expected text string".to_string()));
+        };
+        Ok(match read_string(&mut reader, MAJOR_TEXT, arg)? {
+            Cow::Borrowed(bytes) => {
+                Cow::Borrowed(std::str::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)?)
+            }
+            Cow::Owned(bytes) => {
+                Cow::Owned(String::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)?)
+            }
+        })
+    }
+
+    /// Number of elements or entries of an array or map, or `None` if it
+    /// has indefinite length
+    pub fn len(&self) -> Result<Option<u64>> {
+        match self.header()? {
+            (MAJOR_ARRAY | MAJOR_MAP, _, len, _) => Ok(len),
+            _ => Err(Error::Syntax("Expected array or map".to_string())),
+        }
+    }
+
+    /// The elements of an array, in order
+    pub fn items(&self) -> Result<Items<'a>> {
+        match self.header()? {
+            (MAJOR_ARRAY, _, remaining, reader) => Ok(Items {
+                bytes: self.bytes,
+                pos: reader.position(),
+                remaining,
+            }),
+            _ => Err(Error::Syntax("Expected array".to_string())),
+        }
+    }
+
+    /// The entries of a map, in encoded order
+    pub fn entries(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<(LazyValue<'a>, LazyValue<'a>)>> + use<'a>> {
+        let (MAJOR_MAP, _, len, reader) = self.header()? else {
+            return Err(Error::Syntax("Expected map".to_string()));
+        };
+        let mut items = Items {
+            bytes: self.bytes,
+            pos: reader.position(),
+            remaining: len
+                .map(|len| len.checked_mul(2).ok_or(Error::LengthOverflow(len)))
+                .transpose()?,
+        };
+        Ok(std::iter::from_fn(move || {
+            let key = items.next()?;
+            let value = items.next().unwrap_or(Err(Error::Eof));
+            Some(key.and_then(|key| value.map(|value| (key, value))))
+        }))
+    }
+
+    /// The element at `index` of an array, skipping the ones before it
+    pub fn index(&self, index: usize) -> Result<Option<LazyValue<'a>>> {
+        self.items()?.nth(index).transpose()
+    }
+
+    /// The value for the first entry whose key matches
+    fn find(
+        &self,
+        matches: impl Fn(&LazyValue<'a>) -> Result<bool>,
+    ) -> Result<Option<LazyValue<'a>>> {
+        for entry in self.entries()? {
+            let (key, value) = entry?;
+            if matches(&key)? {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The value for a text key of a map
+    pub fn get(&self, key: &str) -> Result<Option<LazyValue<'a>>> {
+        self.find(|k| Ok(k.major()? == MajorType::Text && k.as_text()? == key))
+    }
+
+    /// The value for an integer key of a map, as used by COSE headers
+    pub fn get_int(&self, key: i64) -> Result<Option<LazyValue<'a>>> {
+        self.find(|k| {
+            Ok(
+                matches!(k.major()?, MajorType::Unsigned | MajorType::Negative)
+                    && k.as_i64().ok() == Some(key),
+            )
+        })
+    }
+
+    /// The value for a text key, which must be present
+    pub fn require(&self, key: &str) -> Result<LazyValue<'a>> {
+        self.get(key)?
+            .ok_or_else(|| Error::Syntax(format!("Missing map key {:?}", key)))
+    }
+
+    /// Decode this item
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<T> {
+        crate::from_slice(self.as_raw()?)
+    }
+
+    /// Read this item into a [`ValueRef`], borrowing its strings
+    pub fn to_value_ref(&self) -> Result<ValueRef<'a>> {
+        crate::from_slice_ref(self.as_raw()?)
+    }
+}
+
+/// Iterator over the elements of an array, returned by [`LazyValue::items`]
+#[derive(Debug, Clone)]
+pub struct Items<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    // None until a break
+    remaining: Option<u64>,
+}
+
+impl<'a> Iterator for Items<'a> {
+    type Item = Result<LazyValue<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.remaining {
+            Some(0) => return None,
+            Some(n) => *n -= 1,
+            None => match self.bytes.get(self.pos) {
+                Some(&BREAK) => return None,
+                Some(_) => {}
+                None => {
+                    self.remaining = Some(0);
+                    return Some(Err(Error::Eof));
+                }
+            },
+        }
+        match item_end(self.bytes, self.pos) {
+            Ok(end) => {
+                let value = LazyValue {
+                    bytes: &self.bytes[self.pos..],
+                };
+                self.pos = end;
+                Some(Ok(value))
+            }
+            Err(e) => {
+                self.remaining = Some(0);
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Value;
+
+    // {-1: 6, "list": [_ 1, (_ h'00', h'01'), {"a": true}], 1: "ES256", "alg": 32("sha256")}
+    fn sample() -> Vec<u8> {
+        let mut cbor = vec![0xa4, 0x20, 0x06, 0x64];
+        cbor.extend_from_slice(b"list");
+        cbor.extend_from_slice(&[
+            0x9f, 0x01, 0x5f, 0x41, 0x00, 0x41, 0x01, 0xff, 0xa1, 0x61, b'a', 0xf5, 0xff,
+        ]);
+        cbor.extend_from_slice(&[0x01, 0x65]);
+        cbor.extend_from_slice(b"ES256");
+        cbor.extend_from_slice(&[0x63]);
+        cbor.extend_from_slice(b"alg");
+        cbor.extend_from_slice(&[0xd8, 0x20, 0x66]);
+        cbor.extend_from_slice(b"sha256");
+        cbor
+    }
+
+    #[test]
+    fn test_lazy_getters() {
+        let cbor = sample();
+        let root = LazyValue::new(&cbor);
+        assert_eq!(root.major().unwrap(), MajorType::Map);
+        assert_eq!(root.len().unwrap(), Some(4));
+        assert_eq!(root.as_raw().unwrap(), cbor);
+
+        assert_eq!(root.get_int(-1).unwrap().unwrap().as_i64().unwrap(), 6);
+        assert_eq!(
+            root.get_int(1).unwrap().unwrap().as_text().unwrap(),
+            "ES256"
+        );
+        assert!(root.get_int(2).unwrap().is_none());
+        assert!(root.get("missing").unwrap().is_none());
+        assert!(root.require("missing").is_err());
+
+        let alg = root.require("alg").unwrap();
+        assert_eq!(alg.tag().unwrap(), Some(32));
+        assert!(alg.as_text().is_err());
+        let alg = alg.untagged().unwrap().as_text().unwrap();
+        assert!(matches!(alg, Cow::Borrowed("sha256")));
+
+        let list = root.require("list").unwrap();
+        assert_eq!(list.len().unwrap(), None);
+        assert_eq!(list.items().unwrap().count(), 3);
+        assert_eq!(list.index(0).unwrap().unwrap().as_u64().unwrap(), 1);
+        assert_eq!(
+            list.index(1).unwrap().unwrap().as_bytes().unwrap(),
+            Cow::<[u8]>::Owned(vec![0, 1])
+        );
+        let inner = list.index(2).unwrap().unwrap();
+        assert!(inner.require("a").unwrap().as_bool().unwrap());
+        assert!(list.index(3).unwrap().is_none());
+
+        // Handles decode the item they point at, and nothing after it
+        assert_eq!(inner.as_raw().unwrap(), [0xa1, 0x61, b'a', 0xf5]);
+        assert_eq!(
+            inner.to_value_ref().unwrap().into_owned(),
+            inner.decode::<Value>().unwrap()
+        );
+        let entries: Vec<_> = root.entries().unwrap().collect::<Result<_>>().unwrap();
+        assert_eq!(entries.len(), 4);
+    }
+
+    #[test]
+    fn test_lazy_skips_untouched_content() {
+        // [h'...', 7] where the byte string claims more bytes than the input
+        // holds: reading the first element fails, but only when it is reached
+        let cbor = [0x82, 0x07, 0x5a, 0xff, 0xff, 0xff, 0xff, 0x00];
+        let root = LazyValue::new(&cbor);
+        assert_eq!(root.index(0).unwrap().unwrap().as_u64().unwrap(), 7);
+        assert!(root.index(1).is_err());
+        assert!(root.as_raw().is_err());
+    }
+
+    #[test]
+    fn test_lazy_malformed() {
+        assert!(LazyValue::new(&[]).major().is_err());
+        assert!(LazyValue::new(&[0xff]).as_raw().is_err());
+        assert!(LazyValue::new(&[0x3f]).as_raw().is_err());
+        // Chunks of an indefinite string must be definite strings of its type
+        assert!(LazyValue::new(&[0x5f, 0x01, 0xff]).as_raw().is_err());
+        assert!(LazyValue::new(&[0x7f, 0x41, 0x00, 0xff]).as_raw().is_err());
+        assert!(LazyValue::new(&[0x5f, 0x80, 0xff]).as_raw().is_err());
+        assert_eq!(
+            LazyValue::new(&[0x5f, 0x41, 0x00, 0xff, 0x00])
+                .as_raw()
+                .unwrap(),
+            [0x5f, 0x41, 0x00, 0xff]
+        );
+        assert!(LazyValue::new(&[0x9f, 0x01]).index(1).is_err());
+        assert!(LazyValue::new(&[0xa1, 0x01]).get_int(1).is_err());
+        assert!(LazyValue::new(&[0x01]).get("a").is_err());
+    }
+}
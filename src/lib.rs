@@ -80,26 +80,143 @@
 
 // Internal constants module (not part of public API)
 mod constants;
+// Internal raw byte walking helpers (not part of public API)
+mod raw;
 
 pub mod error;
 pub use error::{Error, Result};
 
 pub mod encoder;
-pub use encoder::{Encoder, to_vec, to_writer};
+pub use encoder::{
+    ByteStringWriter, CountingWriter, EncodeHook, Encoder, EncoderOptions, NanPolicy, VariantKeys,
+    to_vec, to_writer,
+};
 
 pub mod decoder;
 // Re-export DOS protection constants for user configuration
 pub use constants::{DEFAULT_MAX_ALLOCATION, DEFAULT_MAX_DEPTH};
 pub use decoder::{
-    Decoder, from_reader, from_reader_with_limit, from_slice, from_slice_with_limit,
+    ByteStringReader, Decoder, DecoderOptions, NumberCoercion, PathSegment, StringKind,
+    StringPolicy, from_reader, from_reader_exact, from_reader_partial, from_reader_with_limit,
+    from_slice, from_slice_bounded, from_slice_in_place, from_slice_seed, from_slice_with_limit,
 };
 
+pub mod header;
+pub use header::{Argument, Header, MajorType};
+
 pub mod value;
-pub use value::{Value, from_value, to_value};
+pub use value::{DuplicateKey, Value, from_value, to_value};
+
+pub mod patch;
+pub use patch::{Patch, diff};
+
+pub mod value_ref;
+#[cfg(feature = "json")]
+pub use value::{from_json, to_json};
+pub use value_ref::{ValueRef, from_slice_ref};
+
+#[cfg(any(feature = "ciborium", feature = "serde_cbor"))]
+mod convert;
+
+#[cfg(feature = "differential")]
+pub mod differential;
+
+#[cfg(feature = "compat_check")]
+pub mod compat_check;
+
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
+
+#[cfg(feature = "cddl")]
+pub mod cddl;
+#[cfg(feature = "cddl")]
+pub mod reflect;
+
+pub mod serde_helpers;
+
+#[cfg(feature = "serde_with")]
+pub mod adapters;
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub mod datetime;
 
 pub mod tags;
 pub use tags::*;
 
+#[cfg(feature = "derive")]
+pub use c2pa_cbor_derive::CborTagged;
+
+pub mod bignum;
+pub use bignum::Bigfloat;
+
+pub mod typed_array;
+pub use typed_array::{MultiDimArray, TypedArray, TypedArrayElement, decode_typed_array};
+
+pub mod chunked;
+pub use chunked::{ChunkedBytes, ChunkedText};
+
+pub mod uri;
+pub use uri::{JumbfUri, SELF_JUMBF, UriPolicy, validate_uri};
+
+pub mod validate;
+pub use validate::{validate, validate_reader};
+
+pub mod scan;
+pub use scan::{ScannedItem, Scanner, scan};
+
+pub mod diag;
+pub use diag::{DiffEntry, DiffKind, annotate, diff_encodings, to_diagnostic};
+
+pub mod hashing;
+pub use hashing::{DigestReader, DigestWriter};
+
+pub mod single_element;
+
+pub mod nullable;
+
+pub mod keys;
+
+pub mod canonical;
+pub use canonical::{
+    CanonicalRule, CanonicalViolation, canonicalize, check_canonical, is_canonical, semantic_eq,
+};
+
+pub mod dcbor;
+
+pub mod stringref;
+
+pub mod sharing;
+
+pub mod cose;
+
+pub mod cwt;
+
+#[cfg(feature = "view")]
+pub mod view;
+
+#[cfg(feature = "lazy")]
+pub mod lazy;
+
+#[cfg(feature = "query")]
+pub mod query;
+
+#[cfg(feature = "heapless")]
+pub mod bounded;
+
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
+
+#[cfg(feature = "signing")]
+pub mod signing;
+
+#[cfg(feature = "json")]
+pub mod transcode;
+#[cfg(feature = "json")]
+pub use transcode::{transcode_cbor_to_json, transcode_json_to_cbor};
+
+#[cfg(feature = "js")]
+pub mod js;
+
 /// Serialization module for compatibility with serde_cbor
 pub mod ser;
 
@@ -484,6 +601,10 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(
+        all(target_arch = "wasm32", target_os = "unknown"),
+        ignore = "std::time is unavailable"
+    )]
     fn test_performance_summary() {
         use std::time::Instant;
 
@@ -563,6 +684,10 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(
+        all(target_arch = "wasm32", target_os = "unknown"),
+        ignore = "std::time is unavailable"
+    )]
     fn test_encoding_speed_vs_size() {
         use std::time::Instant;
 
@@ -596,6 +721,10 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(
+        all(target_arch = "wasm32", target_os = "unknown"),
+        ignore = "std::time is unavailable"
+    )]
     fn test_decoding_speed_vs_size() {
         use std::time::Instant;
 
@@ -679,6 +808,412 @@ mod tests {
         assert_eq!(decoded.get("b"), Some(&2));
     }
 
+    #[test]
+    fn test_indefinite_lengths_for_unknown_sizes() {
+        use serde::Serializer;
+
+        #[derive(Serialize)]
+        struct Flattened {
+            id: u8,
+            #[serde(flatten)]
+            extra: HashMap<String, u8>,
+        }
+
+        let mut enc = Encoder::new(Vec::new()).with_indefinite_lengths();
+        (&mut enc)
+            .collect_seq((0u8..100).filter(|n| n % 40 == 0))
+            .unwrap();
+        assert_eq!(enc.into_inner(), [0x9f, 0x00, 0x18, 0x28, 0x18, 0x50, 0xff]);
+
+        // Flatten serializes a map of unknown length; known lengths stay definite
+        let value = Flattened {
+            id: 1,
+            extra: HashMap::from([("a".to_string(), 2)]),
+        };
+        let mut enc = Encoder::new(Vec::new()).with_indefinite_lengths();
+        enc.encode(&vec![value]).unwrap();
+        let buf = enc.into_inner();
+        assert_eq!(&buf[..2], [0x81, 0xbf]);
+        assert_eq!(buf[buf.len() - 1], BREAK);
+        let decoded: Vec<HashMap<String, u8>> = from_slice(&buf).unwrap();
+        assert_eq!(decoded[0]["id"], 1);
+        assert_eq!(decoded[0]["a"], 2);
+
+        // The option survives the buffering done for hooks
+        let mut enc = Encoder::new(Vec::new())
+            .with_indefinite_lengths()
+            .with_hook(|_: &[u8]| Ok::<(), Error>(()));
+        enc.encode(&Flattened {
+            id: 3,
+            extra: HashMap::new(),
+        })
+        .unwrap();
+        assert_eq!(enc.into_inner(), [0xbf, 0x62, b'i', b'd', 0x03, 0xff]);
+    }
+
+    #[test]
+    fn test_encode_iter() {
+        // Exact size: definite-length, and readable as a Vec
+        let mut enc = Encoder::new(Vec::new());
+        enc.encode_iter((0..30u32).map(|n| n * n)).unwrap();
+        let buf = enc.into_inner();
+        assert_eq!(&buf[..2], [0x98, 0x1e]);
+        let squares: Vec<u32> = from_slice(&buf).unwrap();
+        assert_eq!(squares[29], 841);
+
+        // Unknown size: indefinite-length
+        let mut enc = Encoder::new(Vec::new());
+        enc.encode_iter(["a", "", "b"].iter().filter(|s| !s.is_empty()))
+            .unwrap();
+        let buf = enc.into_inner();
+        assert_eq!(buf, [0x9f, 0x61, b'a', 0x61, b'b', 0xff]);
+        assert_eq!(from_slice::<Vec<String>>(&buf).unwrap(), ["a", "b"]);
+
+        // Hooks see the array as one item
+        let mut items = Vec::new();
+        let mut enc = Encoder::new(Vec::new()).with_hook(move |item: &[u8]| {
+            items.push(item.len());
+            assert_eq!(items, [4]);
+            Ok::<(), Error>(())
+        });
+        enc.encode_iter(vec![true, false, true]).unwrap();
+        assert_eq!(enc.into_inner(), [0x83, 0xf5, 0xf4, 0xf5]);
+    }
+
+    #[test]
+    fn test_encode_iter_rejects_wrong_size_hint() {
+        struct Lying(u8, usize);
+
+        impl Iterator for Lying {
+            type Item = u8;
+
+            fn next(&mut self) -> Option<u8> {
+                self.0 = self.0.checked_sub(1)?;
+                Some(self.0)
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (self.1, Some(self.1))
+            }
+        }
+
+        assert!(Encoder::new(Vec::new()).encode_iter(Lying(2, 3)).is_err());
+        assert!(Encoder::new(Vec::new()).encode_iter(Lying(3, 2)).is_err());
+        let mut enc = Encoder::new(Vec::new());
+        enc.encode_iter(Lying(2, 2)).unwrap();
+        assert_eq!(enc.into_inner(), [0x82, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn test_bytes_writer_chunks() {
+        use std::io::Write;
+
+        let payload: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        let mut enc = Encoder::new(Vec::new());
+        enc.write_array_header(2).unwrap();
+        {
+            let mut writer = enc.bytes_writer(300).unwrap();
+            // Odd-sized writes that straddle chunk boundaries
+            for piece in payload.chunks(7) {
+                writer.write_all(piece).unwrap();
+            }
+            writer.finish().unwrap();
+        }
+        enc.write_text("done").unwrap();
+        let buf = enc.into_inner();
+
+        assert_eq!(&buf[..4], [0x82, 0x5f, 0x59, 0x01]);
+        let (bytes, done): (serde_bytes::ByteBuf, String) = from_slice(&buf).unwrap();
+        assert_eq!(bytes.into_vec(), payload);
+        assert_eq!(done, "done");
+
+        // Chunks are 300, 300, 300 and 100 bytes
+        let chunked: ChunkedBytes = from_slice(&buf[1..buf.len() - 5]).unwrap();
+        assert_eq!(chunked.chunk_lengths(), Some(&[300, 300, 300, 100][..]));
+    }
+
+    #[test]
+    fn test_bytes_writer_drop_and_empty() {
+        use std::io::Write;
+
+        let mut enc = Encoder::new(Vec::new());
+        enc.bytes_writer(2).unwrap().write_all(b"abc").unwrap();
+        assert_eq!(enc.into_inner(), [0x5f, 0x42, b'a', b'b', 0x41, b'c', 0xff]);
+
+        let mut enc = Encoder::new(Vec::new());
+        enc.bytes_writer(16).unwrap().finish().unwrap();
+        assert_eq!(enc.into_inner(), [0x5f, 0xff]);
+
+        assert!(Encoder::new(Vec::new()).bytes_writer(0).is_err());
+    }
+
+    #[test]
+    fn test_bytes_written() {
+        use std::io::Write;
+
+        // Streaming to a sink that can't report its size, with a hook in the way
+        let mut enc = Encoder::new(CountingWriter::new(std::io::sink()))
+            .with_hook(|_: &[u8]| Ok::<(), Error>(()));
+        enc.write_map_header(1).unwrap();
+        assert_eq!(enc.bytes_written(), 1);
+        enc.encode(&"data").unwrap();
+        assert_eq!(enc.bytes_written(), 6);
+        {
+            let mut writer = enc.bytes_writer(3).unwrap();
+            writer.write_all(b"abcdefg").unwrap();
+            writer.finish().unwrap();
+        }
+        // 0x5f, three chunks of 4, 4 and 2 bytes, 0xff
+        assert_eq!(enc.bytes_written(), 18);
+        assert_eq!(enc.into_inner().count(), 18);
+    }
+
+    #[test]
+    fn test_max_output_len() {
+        use std::io::Write;
+
+        // A long byte string is refused whole
+        let mut enc = Encoder::new(Vec::new()).with_max_output_len(100);
+        enc.write_array_header(2).unwrap();
+        assert!(matches!(
+            enc.write_bytes(&[0; 100]),
+            Err(Error::OutputLimitExceeded(100))
+        ));
+        enc.write_bytes(&[0; 96]).unwrap();
+        assert_eq!(enc.bytes_written(), 99);
+        assert_eq!(enc.reset(Vec::new()).len(), 99);
+
+        // The limit survives a reset, and is set by options too
+        assert!(enc.encode(&vec![0u64; 100]).is_err());
+        let options = EncoderOptions {
+            max_output_len: Some(3),
+            ..Default::default()
+        };
+        let mut enc = Encoder::new(Vec::new()).with_options(options);
+        assert!(matches!(
+            enc.encode(&100_000u32),
+            Err(Error::OutputLimitExceeded(3))
+        ));
+        assert!(enc.into_inner().is_empty());
+
+        // Errors from a byte string writer come back as the same error
+        let mut enc = Encoder::new(Vec::new()).with_max_output_len(8);
+        let mut writer = enc.bytes_writer(4).unwrap();
+        writer.write_all(b"abcd").unwrap();
+        let err = writer.write_all(b"efgh").unwrap_err();
+        assert!(matches!(Error::from(err), Error::OutputLimitExceeded(8)));
+    }
+
+    #[test]
+    fn test_strings_written_in_one_call() {
+        use std::io::{IoSlice, Write};
+
+        // Records the bytes of each call it receives
+        #[derive(Default)]
+        struct Calls(Vec<Vec<u8>>);
+
+        impl Write for Calls {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.push(buf.to_vec());
+                Ok(buf.len())
+            }
+
+            fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> std::io::Result<usize> {
+                self.0
+                    .push(bufs.iter().flat_map(|buf| buf.iter().copied()).collect());
+                Ok(self.0.last().unwrap().len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let payload = vec![7u8; 70000];
+        let mut enc = Encoder::new(Calls::default());
+        enc.write_bytes(&payload).unwrap();
+        enc.write_text("short").unwrap();
+        enc.encode(&TypedArray::new(vec![1.5f32; 100])).unwrap();
+        let calls = enc.into_inner().0;
+
+        assert_eq!(calls.len(), 4);
+        assert_eq!(calls[0][..5], [0x5a, 0x00, 0x01, 0x11, 0x70]);
+        assert_eq!(calls[0].len(), 5 + payload.len());
+        assert_eq!(calls[1], [0x65, b's', b'h', b'o', b'r', b't']);
+        // The tag is a separate header; the typed array body comes with its length
+        assert_eq!(calls[2], [0xd8, 0x55]);
+        assert_eq!(calls[3][..3], [0x59, 0x01, 0x90]);
+        assert_eq!(calls[3].len(), 403);
+    }
+
+    #[test]
+    fn test_bytes_reader_round_trip() {
+        use std::io::{Read, Write};
+
+        let payload: Vec<u8> = (0..5000u32).map(|n| (n % 251) as u8).collect();
+        let mut enc = Encoder::new(Vec::new());
+        let mut writer = enc.bytes_writer(1024).unwrap();
+        writer.write_all(&payload).unwrap();
+        writer.finish().unwrap();
+        enc.encode(&payload.len()).unwrap();
+        let buf = enc.into_inner();
+
+        // Read in small pieces through a reader with no slice access
+        let mut dec = Decoder::new(std::io::Cursor::new(&buf));
+        let mut reader = dec.bytes_reader().unwrap();
+        let mut read = Vec::new();
+        let mut piece = [0u8; 33];
+        loop {
+            let n = reader.read(&mut piece).unwrap();
+            if n == 0 {
+                break;
+            }
+            read.extend_from_slice(&piece[..n]);
+        }
+        assert_eq!(read, payload);
+        assert_eq!(dec.decode::<usize>().unwrap(), payload.len());
+
+        // Definite and empty strings
+        let mut dec = Decoder::from_slice(&[0x43, 1, 2, 3, 0x40, 0x5f, 0xff]);
+        let mut read = Vec::new();
+        dec.bytes_reader().unwrap().read_to_end(&mut read).unwrap();
+        assert_eq!(read, [1, 2, 3]);
+        for _ in 0..2 {
+            read.clear();
+            dec.bytes_reader().unwrap().read_to_end(&mut read).unwrap();
+            assert!(read.is_empty());
+        }
+        dec.end().unwrap();
+    }
+
+    #[test]
+    fn test_bytes_reader_rejects_malformed() {
+        use std::io::Read;
+
+        let mut sink = Vec::new();
+        // Text, a text chunk, a nested indefinite chunk and a truncated string
+        assert!(Decoder::from_slice(&[0x61, b'a']).bytes_reader().is_err());
+        for data in [
+            &[0x5f, 0x61, b'a', 0xff][..],
+            &[0x5f, 0x5f, 0xff, 0xff],
+            &[0x43, 1],
+        ] {
+            let mut dec = Decoder::from_slice(data);
+            assert!(dec.bytes_reader().unwrap().read_to_end(&mut sink).is_err());
+        }
+    }
+
+    #[test]
+    fn test_encoder_primitives() {
+        // A COSE_Sign1-shaped structure: [h'a10126', {}, null, h'']
+        let mut enc = Encoder::new(Vec::new());
+        enc.write_tag(18).unwrap();
+        enc.write_array_header(4).unwrap();
+        enc.write_bytes(&[0xa1, 0x01, 0x26]).unwrap();
+        enc.write_map_header(0).unwrap();
+        enc.write_null().unwrap();
+        enc.write_bytes(&[]).unwrap();
+        assert_eq!(
+            enc.into_inner(),
+            [0xd2, 0x84, 0x43, 0xa1, 0x01, 0x26, 0xa0, 0xf6, 0x40]
+        );
+
+        let mut enc = Encoder::new(Vec::new());
+        enc.write_array_header(6).unwrap();
+        enc.write_u64(500).unwrap();
+        enc.write_i64(-500).unwrap();
+        enc.write_text("é").unwrap();
+        enc.write_bool(true).unwrap();
+        enc.write_bool(false).unwrap();
+        enc.write_f64(1.5).unwrap();
+        let buf = enc.into_inner();
+        assert_eq!(&buf[..8], [0x86, 0x19, 0x01, 0xf4, 0x39, 0x01, 0xf3, 0x62]);
+        let decoded: (u64, i64, String, bool, bool, f64) = from_slice(&buf).unwrap();
+        assert_eq!(decoded, (500, -500, "é".to_string(), true, false, 1.5));
+
+        // Headers for lengths past the one-byte forms
+        let mut enc = Encoder::new(Vec::new());
+        enc.write_map_header(1 << 16).unwrap();
+        enc.write_array_header(24).unwrap();
+        assert_eq!(enc.into_inner(), [0xba, 0x00, 0x01, 0x00, 0x00, 0x98, 0x18]);
+    }
+
+    #[test]
+    fn test_decoder_primitives() {
+        let mut enc = Encoder::new(Vec::new());
+        enc.write_array_header(7).unwrap();
+        enc.write_u64(u64::MAX).unwrap();
+        enc.write_i64(i64::MIN).unwrap();
+        enc.write_text("kid").unwrap();
+        enc.write_bytes(&[1, 2]).unwrap();
+        enc.write_f64(-2.5).unwrap();
+        enc.write_bool(true).unwrap();
+        enc.write_null().unwrap();
+        let buf = enc.into_inner();
+
+        let mut dec = Decoder::from_slice(&buf);
+        assert_eq!(dec.peek_major_type().unwrap(), MajorType::Array);
+        assert_eq!(dec.read_array_header().unwrap(), Some(7));
+        assert_eq!(dec.read_u64().unwrap(), u64::MAX);
+        assert_eq!(dec.read_i64().unwrap(), i64::MIN);
+        assert_eq!(dec.read_text().unwrap(), "kid");
+        assert_eq!(dec.read_bytes().unwrap(), [1, 2]);
+        assert_eq!(dec.read_f64().unwrap(), -2.5);
+        assert!(dec.read_bool().unwrap());
+        dec.read_null().unwrap();
+        dec.end().unwrap();
+
+        // Indefinite headers, chunked strings and half floats
+        let buf = [
+            0xbf, 0x7f, 0x61, b'a', 0x61, b'b', 0xff, 0xf9, 0x3c, 0x00, 0xff,
+        ];
+        let mut dec = Decoder::from_slice(&buf);
+        assert_eq!(dec.read_map_header().unwrap(), None);
+        assert_eq!(dec.read_text().unwrap(), "ab");
+        assert_eq!(dec.read_f64().unwrap(), 1.0);
+        assert_eq!(dec.peek_major_type().unwrap(), MajorType::Simple);
+    }
+
+    #[test]
+    fn test_decoder_primitives_reject_wrong_types() {
+        // A mismatch leaves the item in place for another attempt
+        let mut dec = Decoder::from_slice(&[0x38, 0x63]);
+        assert!(dec.read_u64().is_err());
+        assert!(dec.read_f64().is_err());
+        assert!(dec.read_text().is_err());
+        assert_eq!(dec.read_i64().unwrap(), -100);
+
+        // Too large for i64, an integer as a float, and undefined as null
+        assert!(
+            Decoder::from_slice(&[0x1b, 0x80, 0, 0, 0, 0, 0, 0, 0])
+                .read_i64()
+                .is_err()
+        );
+        assert!(Decoder::from_slice(&[0x01]).read_f64().is_err());
+        assert!(Decoder::from_slice(&[0xf7]).read_null().is_err());
+        assert!(Decoder::from_slice(&[0xf6]).read_bool().is_err());
+        assert!(Decoder::from_slice(&[0x1f]).read_u64().is_err());
+    }
+
+    #[test]
+    fn test_peek_tag_over_reader() {
+        use std::io::Read;
+
+        // 1(0) then an untagged 7, through a reader that cannot seek back
+        let data = [0xc1, 0x00, 0x07];
+        let mut dec = Decoder::new(std::io::Cursor::new(&data).chain(&[][..]));
+        assert_eq!(dec.peek_tag().unwrap(), Some(1));
+        assert_eq!(dec.decode::<u8>().unwrap(), 0);
+        assert_eq!(dec.peek_tag().unwrap(), None);
+        assert!(dec.read_tag().is_err());
+        assert_eq!(dec.decode::<u8>().unwrap(), 7);
+        // End of input is an error, not a missing tag
+        assert!(dec.peek_tag().is_err());
+
+        // Indefinite tag headers are malformed
+        assert!(Decoder::from_slice(&[0xdf, 0x00]).peek_tag().is_err());
+    }
+
     #[test]
     fn test_indefinite_byte_string() {
         use serde_bytes::ByteBuf;
@@ -725,6 +1260,44 @@ mod tests {
         assert_eq!(decoded, "Hello World");
     }
 
+    #[test]
+    fn test_reject_indefinite_lengths() {
+        use crate::{ChunkedBytes, Decoder, DecoderOptions};
+
+        fn definite(bytes: &[u8]) -> Decoder<&[u8]> {
+            Decoder::new(bytes).with_allow_indefinite(false)
+        }
+        let indefinite: [&[u8]; 4] = [
+            &[0x9f, 0x01, 0xff],
+            &[0xbf, 0x01, 0x02, 0xff],
+            &[0x5f, 0x41, 0x01, 0xff],
+            &[0x7f, 0x61, b'a', 0xff],
+        ];
+        for bytes in indefinite {
+            assert!(from_slice::<Value>(bytes).is_ok());
+            assert!(definite(bytes).decode::<Value>().is_err());
+            assert!(definite(bytes).decode_value().is_err());
+            assert!(definite(bytes).skip().is_err());
+        }
+        assert!(
+            definite(&[0x5f, 0x41, 0x01, 0xff])
+                .decode::<ChunkedBytes>()
+                .is_err()
+        );
+        assert!(definite(&[0x9f, 0x01, 0xff]).read_array_header().is_err());
+        // Nested inside definite items too
+        assert!(
+            definite(&[0x81, 0x9f, 0xff])
+                .decode::<Vec<Vec<u8>>>()
+                .is_err()
+        );
+
+        let mut decoder = definite(&[0x82, 0x41, 0x01, 0xa0]);
+        assert!(!decoder.options().allow_indefinite);
+        decoder.decode::<Value>().unwrap();
+        assert!(DecoderOptions::default().allow_indefinite);
+    }
+
     #[test]
     fn test_ser_module_serializer() {
         use crate::ser::Serializer;
@@ -1343,6 +1916,45 @@ mod tests {
         assert!(matches!(result, Err(Error::InvalidUtf8)));
     }
 
+    #[test]
+    fn test_decoder_lossy_utf8() {
+        use crate::{ChunkedText, Decoder};
+
+        // ["ok", "a\xffb", (_ "c", "\xfe"), {"k": "\xc3"}]
+        let cbor = [
+            0x84, 0x62, b'o', b'k', 0x63, b'a', 0xff, b'b', 0x7f, 0x61, b'c', 0x61, 0xfe, 0xff,
+            0xa1, 0x61, b'k', 0x61, 0xc3,
+        ];
+        let replaced = [vec![b'a', 0xff, b'b'], vec![0xfe], vec![0xc3]];
+
+        // Strict by default, with the offending string kept
+        let mut decoder = Decoder::new(&cbor[..]);
+        assert!(matches!(decoder.decode::<Value>(), Err(Error::InvalidUtf8)));
+        assert_eq!(decoder.invalid_utf8_strings(), &replaced[..1]);
+
+        let mut decoder = Decoder::new(&cbor[..]).with_lossy_utf8();
+        let value = decoder.decode::<Value>().unwrap();
+        assert_eq!(
+            value.as_array().unwrap()[1],
+            Value::Text("a\u{fffd}b".to_string())
+        );
+        assert_eq!(
+            value.as_array().unwrap()[2],
+            Value::Text("c\u{fffd}".to_string())
+        );
+        assert_eq!(decoder.invalid_utf8_strings(), replaced);
+
+        let mut decoder = Decoder::new(&cbor[..]).with_lossy_utf8();
+        decoder.decode_value().unwrap();
+        assert_eq!(decoder.invalid_utf8_strings(), replaced);
+
+        // Chunk boundaries are kept for ChunkedText
+        let chunked = [0x7f, 0x61, b'c', 0x61, 0xfe, 0xff];
+        let mut decoder = Decoder::new(&chunked[..]).with_lossy_utf8();
+        let text: ChunkedText = decoder.decode().unwrap();
+        assert_eq!(text.chunks(), ["c", "\u{fffd}"]);
+    }
+
     #[test]
     fn test_decoder_indefinite_text_wrong_chunk_type() {
         // Indefinite text string with byte string chunk (invalid)
@@ -1620,6 +2232,137 @@ mod tests {
         assert_eq!(decoded, 42);
     }
 
+    #[test]
+    fn test_encoder_hook_observes_items() {
+        use std::sync::{Arc, Mutex};
+
+        let items = Arc::new(Mutex::new(Vec::new()));
+        let seen = items.clone();
+        let mut enc = Encoder::new(Vec::new()).with_hook(move |item: &[u8]| -> Result<()> {
+            seen.lock().unwrap().push(item.to_vec());
+            Ok(())
+        });
+        enc.encode(&"hi").unwrap();
+        enc.encode(&vec![1u8, 2]).unwrap();
+        let out = enc.into_inner();
+
+        let items = items.lock().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0], to_vec(&"hi").unwrap());
+        assert_eq!(items[1], to_vec(&vec![1u8, 2]).unwrap());
+        assert_eq!(out, items.concat());
+    }
+
+    #[test]
+    fn test_encoder_hook_wraps_items() {
+        // Wrap every item in tag 24 (encoded CBOR data item)
+        struct EmbedCbor;
+        impl EncodeHook for EmbedCbor {
+            fn wrap(&mut self, item: Vec<u8>) -> Result<Vec<u8>> {
+                let mut out = Vec::new();
+                encode_tagged(&mut out, 24, &serde_bytes::Bytes::new(&item))?;
+                Ok(out)
+            }
+        }
+
+        let mut enc = Encoder::new(Vec::new()).with_hook(EmbedCbor);
+        enc.encode(&42u8).unwrap();
+        let out = enc.into_inner();
+        assert_eq!(out, vec![0xd8, 0x18, 0x42, 0x18, 0x2a]);
+    }
+
+    #[test]
+    fn test_string_policy_reports_paths() {
+        use std::sync::{Arc, Mutex};
+
+        type Seen = Arc<Mutex<Vec<(Vec<PathSegment>, StringKind, Vec<u8>)>>>;
+        struct Recorder(Seen);
+        impl StringPolicy for Recorder {
+            fn check_data(
+                &mut self,
+                path: &[PathSegment],
+                kind: StringKind,
+                data: &[u8],
+            ) -> Result<()> {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((path.to_vec(), kind, data.to_vec()));
+                Ok(())
+            }
+        }
+
+        // {"a": ["x", {1: h'0102'}]}
+        let data = [
+            0xa1, 0x61, b'a', 0x82, 0x61, b'x', 0xa1, 0x01, 0x42, 0x01, 0x02,
+        ];
+        let seen: Seen = Arc::default();
+        let mut decoder = Decoder::new(&data[..]).with_string_policy(Recorder(seen.clone()));
+        let _: Value = decoder.decode().unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(
+            *seen,
+            vec![
+                (vec![], StringKind::Text, b"a".to_vec()),
+                (
+                    vec![PathSegment::Key("a".into()), PathSegment::Index(0)],
+                    StringKind::Text,
+                    b"x".to_vec()
+                ),
+                (
+                    vec![
+                        PathSegment::Key("a".into()),
+                        PathSegment::Index(1),
+                        PathSegment::IntKey(1)
+                    ],
+                    StringKind::Bytes,
+                    vec![1, 2]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_string_policy_rejects_before_reading() {
+        struct NoLargeBytes;
+        impl StringPolicy for NoLargeBytes {
+            fn check_len(
+                &mut self,
+                _path: &[PathSegment],
+                kind: StringKind,
+                len: u64,
+            ) -> Result<()> {
+                if kind == StringKind::Bytes && len > 4 {
+                    return Err(Error::Message("byte string too large".to_string()));
+                }
+                Ok(())
+            }
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Doc {
+            #[allow(dead_code)]
+            #[serde(with = "serde_bytes")]
+            thumbnail: Vec<u8>,
+        }
+
+        // The declared length is rejected even though the content is missing
+        let data = [
+            0xa1, 0x69, b't', b'h', b'u', b'm', b'b', b'n', b'a', b'i', b'l', 0x58, 0xff,
+        ];
+        let mut decoder = Decoder::new(&data[..]).with_string_policy(NoLargeBytes);
+        match decoder.decode::<Doc>() {
+            Err(Error::Message(msg)) => assert_eq!(msg, "byte string too large"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Indefinite-length strings are checked as chunks accumulate
+        let data = [0x5f, 0x43, 1, 2, 3, 0x42, 4, 5, 0xff];
+        let mut decoder = Decoder::new(&data[..]).with_string_policy(NoLargeBytes);
+        assert!(decoder.decode::<serde_bytes::ByteBuf>().is_err());
+    }
+
     // ============================================================================
     // Comprehensive Deserialization Coverage Tests
     // ============================================================================
@@ -1839,6 +2582,245 @@ mod tests {
         assert_eq!(decoded, TestEnum::Value(42));
     }
 
+    #[test]
+    fn test_integer_variant_keys() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        enum Shape {
+            Point,
+            Circle(u8),
+            Pair(u8, u8),
+            Rect { w: u8, h: u8 },
+        }
+
+        fn encode<T: Serialize>(value: &T, keys: VariantKeys) -> Vec<u8> {
+            let mut enc = Encoder::new(Vec::new()).with_variant_keys(keys);
+            enc.encode(value).unwrap();
+            enc.into_inner()
+        }
+
+        let cases = [
+            (Shape::Point, vec![0x00]),
+            (Shape::Circle(5), vec![0xa1, 0x01, 0x05]),
+            (Shape::Pair(1, 2), vec![0xa1, 0x02, 0x82, 0x01, 0x02]),
+            (
+                Shape::Rect { w: 3, h: 4 },
+                vec![0xa1, 0x03, 0xa2, 0x61, b'w', 0x03, 0x61, b'h', 0x04],
+            ),
+        ];
+        for (shape, expected) in cases {
+            let cbor = encode(&shape, VariantKeys::Index);
+            assert_eq!(cbor, expected);
+            // Integer keys are read as indexes by default
+            assert_eq!(from_slice::<Shape>(&cbor).unwrap(), shape);
+            // Names stay the default on the encoder
+            assert_eq!(to_vec(&shape).unwrap(), encode(&shape, VariantKeys::Name));
+        }
+        assert!(from_slice::<Shape>(&[0x04]).is_err());
+        assert!(from_slice::<Shape>(&[0xa1, 0x09, 0x00]).is_err());
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        enum Action {
+            #[serde(rename = "1")]
+            Created {
+                by: String,
+            },
+            #[serde(rename = "100")]
+            Deleted,
+            #[serde(rename = "007")]
+            Padded,
+            Other(u8),
+        }
+
+        let cases = [
+            (
+                Action::Created { by: "a".into() },
+                vec![0xa1, 0x01, 0xa1, 0x62, b'b', b'y', 0x61, b'a'],
+            ),
+            (Action::Deleted, vec![0x18, 0x64]),
+            (Action::Padded, vec![0x63, b'0', b'0', b'7']),
+            (
+                Action::Other(2),
+                vec![0xa1, 0x65, b'O', b't', b'h', b'e', b'r', 0x02],
+            ),
+        ];
+        for (action, expected) in cases {
+            let cbor = encode(&action, VariantKeys::NumericName);
+            assert_eq!(cbor, expected);
+            let mut dec = Decoder::new(&cbor[..]).with_variant_keys(VariantKeys::NumericName);
+            assert_eq!(dec.decode::<Action>().unwrap(), action);
+        }
+        // Read as an index, 100 is out of range
+        assert!(from_slice::<Action>(&[0x18, 0x64]).is_err());
+    }
+
+    #[test]
+    fn test_enum_representations() {
+        use serde_bytes::ByteBuf;
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Inner {
+            id: u8,
+        }
+
+        macro_rules! round_trip_enum {
+            ($name:ident, [$($attr:tt)*]) => {{
+                #[derive(Debug, PartialEq, Serialize, Deserialize)]
+                #[serde($($attr)*)]
+                enum $name {
+                    Unit,
+                    Bytes { data: ByteBuf },
+                    Wrapped(Inner),
+                    Map(HashMap<String, ByteBuf>),
+                }
+
+                let values = [
+                    $name::Unit,
+                    $name::Bytes {
+                        data: ByteBuf::from(vec![1, 2]),
+                    },
+                    $name::Wrapped(Inner { id: 7 }),
+                    $name::Map(HashMap::from([("k".to_string(), ByteBuf::from(vec![3]))])),
+                ];
+                for value in values {
+                    let cbor = to_vec(&value).unwrap();
+                    // Byte strings stay byte strings, and every map has a definite length
+                    assert!(!cbor.contains(&0xbf));
+                    assert_eq!(from_slice::<$name>(&cbor).unwrap(), value);
+                    let mut dec = Decoder::new(&cbor[..]);
+                    assert_eq!(dec.decode::<$name>().unwrap(), value);
+                }
+                to_vec(&$name::Bytes {
+                    data: ByteBuf::from(vec![1, 2]),
+                })
+                .unwrap()
+            }};
+        }
+
+        let external = round_trip_enum!(External, [rename_all = "lowercase"]);
+        assert_eq!(
+            external,
+            [
+                0xa1, 0x65, b'b', b'y', b't', b'e', b's', 0xa1, 0x64, b'd', b'a', b't', b'a', 0x42,
+                1, 2
+            ]
+        );
+        let internal = round_trip_enum!(Internal, [tag = "t", rename_all = "lowercase"]);
+        assert_eq!(
+            internal,
+            [
+                0xa2, 0x61, b't', 0x65, b'b', b'y', b't', b'e', b's', 0x64, b'd', b'a', b't', b'a',
+                0x42, 1, 2
+            ]
+        );
+        let adjacent = round_trip_enum!(
+            Adjacent,
+            [tag = "t", content = "c", rename_all = "lowercase"]
+        );
+        assert_eq!(
+            adjacent,
+            [
+                0xa2, 0x61, b't', 0x65, b'b', b'y', b't', b'e', b's', 0x61, b'c', 0xa1, 0x64, b'd',
+                b'a', b't', b'a', 0x42, 1, 2
+            ]
+        );
+        let untagged = round_trip_enum!(Untagged, [untagged]);
+        assert_eq!(untagged, [0xa1, 0x64, b'd', b'a', b't', b'a', 0x42, 1, 2]);
+
+        // Buffered maps keep the encoder's options, here for a flattened field
+        #[derive(Serialize)]
+        struct Flat {
+            #[serde(flatten)]
+            rest: HashMap<String, Tagless>,
+        }
+        #[derive(Serialize)]
+        enum Tagless {
+            Only,
+        }
+        let flat = Flat {
+            rest: HashMap::from([("a".to_string(), Tagless::Only)]),
+        };
+        let mut enc = Encoder::new(Vec::new()).with_variant_keys(VariantKeys::Index);
+        enc.encode(&flat).unwrap();
+        assert_eq!(enc.into_inner(), [0xa1, 0x61, b'a', 0x00]);
+    }
+
+    #[test]
+    fn test_omit_none_fields() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Record {
+            a: Option<u8>,
+            list: Vec<Option<u8>>,
+            b: Option<u8>,
+        }
+
+        #[derive(Serialize)]
+        struct WithUnit {
+            unit: (),
+            none: Option<u8>,
+        }
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        enum Event {
+            Edit { by: Option<String>, at: u8 },
+        }
+
+        let record = Record {
+            a: None,
+            list: vec![None],
+            b: Some(1),
+        };
+        let mut enc = Encoder::new(Vec::new()).with_omit_none_fields();
+        enc.encode(&record).unwrap();
+        let cbor = enc.into_inner();
+        // None inside collections is kept
+        assert_eq!(
+            to_diagnostic(&cbor).unwrap(),
+            "{\n  \"list\": [\n    null\n  ],\n  \"b\": 1\n}"
+        );
+        assert_eq!(from_slice::<Record>(&cbor).unwrap(), record);
+
+        // Unit fields are also null, but are not None
+        let mut enc = Encoder::new(Vec::new()).with_omit_none_fields();
+        enc.encode(&WithUnit {
+            unit: (),
+            none: None,
+        })
+        .unwrap();
+        assert_eq!(
+            to_diagnostic(&enc.into_inner()).unwrap(),
+            "{\n  \"unit\": null\n}"
+        );
+
+        // Struct variants too, and without buffering in indefinite mode
+        let event = Event::Edit { by: None, at: 2 };
+        let mut enc = Encoder::new(Vec::new())
+            .with_omit_none_fields()
+            .with_indefinite_lengths();
+        enc.encode(&event).unwrap();
+        let cbor = enc.into_inner();
+        assert_eq!(
+            cbor,
+            [
+                0xa1, 0x64, b'E', b'd', b'i', b't', 0xbf, 0x62, b'a', b't', 0x02, 0xff
+            ]
+        );
+        assert_eq!(from_slice::<Event>(&cbor).unwrap(), event);
+
+        // The default still writes every field
+        assert_eq!(
+            to_vec(&event).unwrap(),
+            [
+                0xa1, 0x64, b'E', b'd', b'i', b't', 0xa2, 0x62, b'b', b'y', 0xf6, 0x62, b'a', b't',
+                0x02
+            ]
+        );
+
+        // Strict nulls: only absence means None
+        let cbor = to_vec(&record).unwrap();
+        let mut dec = Decoder::new(&cbor[..]).with_strict_nulls();
+        assert!(dec.decode::<Record>().is_err());
+    }
+
     #[test]
     fn test_decode_enum_tuple_variant() {
         #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -1870,7 +2852,7 @@ mod tests {
 
     #[test]
     fn test_decode_newtype_struct_array_format() {
-        // Test NEW format: newtype struct as 1-element array
+        // Newtype structs round trip as their inner value
         #[derive(Debug, Serialize, Deserialize, PartialEq)]
         struct Wrapped(String);
 
@@ -1882,7 +2864,7 @@ mod tests {
 
     #[test]
     fn test_decode_newtype_struct_transparent_format() {
-        // Test OLD format compatibility: direct value (not wrapped in array)
+        // Data written by other libraries: direct value (not wrapped in array)
         #[derive(Debug, Deserialize, PartialEq)]
         struct Wrapped(String);
 
@@ -1892,6 +2874,66 @@ mod tests {
         assert_eq!(decoded, Wrapped("test".to_string()));
     }
 
+    #[test]
+    fn test_legacy_newtypes() {
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Wrapper(HashMap<String, i32>);
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Outer {
+            id: Wrapper,
+            tags: Vec<Wrapper>,
+        }
+
+        let wrapper = Wrapper(HashMap::from([("a".to_string(), 1)]));
+        let transparent = to_vec(&wrapper).unwrap();
+        assert_eq!(transparent, to_vec(&wrapper.0).unwrap());
+
+        let mut enc = Encoder::new(Vec::new()).with_legacy_newtypes();
+        assert!(enc.options().legacy_newtypes);
+        enc.encode(&wrapper).unwrap();
+        let legacy = enc.into_inner();
+        assert_eq!(legacy[0], 0x81);
+        assert_eq!(&legacy[1..], &transparent[..]);
+
+        // The standard decoder only reads the transparent form
+        assert_eq!(from_slice::<Wrapper>(&transparent).unwrap(), wrapper);
+        assert!(from_slice::<Wrapper>(&legacy).is_err());
+
+        // The compatibility mode reads both, including nested newtypes
+        for cbor in [&transparent, &legacy] {
+            let mut dec = Decoder::new(&cbor[..]).with_legacy_newtypes();
+            assert_eq!(dec.decode::<Wrapper>().unwrap(), wrapper);
+        }
+        let outer = Outer {
+            id: Wrapper(HashMap::new()),
+            tags: vec![Wrapper(HashMap::from([("b".to_string(), 2)]))],
+        };
+        let options = EncoderOptions {
+            legacy_newtypes: true,
+            ..Default::default()
+        };
+        let mut enc = Encoder::new(Vec::new()).with_options(options);
+        enc.encode(&outer).unwrap();
+        let cbor = enc.into_inner();
+        assert!(from_slice::<Outer>(&cbor).is_err());
+        let mut dec = Decoder::new(&cbor[..]).with_legacy_newtypes();
+        assert_eq!(dec.decode::<Outer>().unwrap(), outer);
+
+        // Hooked encoders keep the option for the buffered item
+        let mut enc = Encoder::new(Vec::new())
+            .with_legacy_newtypes()
+            .with_hook(|_: &[u8]| Ok::<(), Error>(()));
+        enc.encode(&wrapper).unwrap();
+        assert_eq!(enc.into_inner(), legacy);
+
+        // The wrapper counts towards the nesting limit
+        let mut dec = Decoder::new(&legacy[..])
+            .with_legacy_newtypes()
+            .with_max_depth(1);
+        assert!(dec.decode::<Wrapper>().is_err());
+    }
+
     #[test]
     fn test_decode_option_some_various_types() {
         // Test Option<T> for various T types
@@ -1961,6 +3003,163 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("exceeds maximum"));
     }
 
+    #[test]
+    fn test_decode_from_reader_partial_and_exact() {
+        use std::io::Read;
+
+        use crate::decoder::{from_reader_exact, from_reader_partial};
+
+        // A framed stream: three items, then a trailer that is not CBOR
+        let mut stream = Vec::new();
+        for item in [vec![1u32, 500], vec![], vec![70_000]] {
+            stream.extend(to_vec(&item).unwrap());
+        }
+        stream.extend(b"END");
+
+        let mut reader = std::io::Cursor::new(&stream);
+        let mut sizes = Vec::new();
+        for _ in 0..3 {
+            let (_, used): (Vec<u32>, u64) = from_reader_partial(&mut reader).unwrap();
+            sizes.push(used);
+        }
+        assert_eq!(sizes, [5, 1, 6]);
+        let mut trailer = String::new();
+        reader.read_to_string(&mut trailer).unwrap();
+        assert_eq!(trailer, "END");
+
+        let one = to_vec(&vec![1u32, 500]).unwrap();
+        assert_eq!(
+            from_reader_exact::<_, Vec<u32>>(&one[..]).unwrap(),
+            [1, 500]
+        );
+        assert!(matches!(
+            from_reader_exact::<_, Vec<u32>>(&stream[..]),
+            Err(Error::TrailingData)
+        ));
+        assert!(from_reader_partial::<_, Vec<u32>>(&one[..3]).is_err());
+    }
+
+    #[test]
+    fn test_from_slice_seed() {
+        use serde::de::DeserializeSeed;
+
+        // Scales every number by a factor known only at run time
+        struct Scaled(u32);
+
+        impl<'de> DeserializeSeed<'de> for Scaled {
+            type Value = Vec<u32>;
+
+            fn deserialize<D: serde::Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> std::result::Result<Vec<u32>, D::Error> {
+                let values = Vec::<u32>::deserialize(deserializer)?;
+                Ok(values.into_iter().map(|v| v * self.0).collect())
+            }
+        }
+
+        let cbor = to_vec(&vec![1u32, 2, 3]).unwrap();
+        assert_eq!(from_slice_seed(Scaled(10), &cbor).unwrap(), [10, 20, 30]);
+        // PhantomData is the seed equivalent of from_slice
+        let plain: Vec<u32> = from_slice_seed(std::marker::PhantomData, &cbor).unwrap();
+        assert_eq!(plain, [1, 2, 3]);
+
+        let mut trailing = cbor.clone();
+        trailing.push(0x00);
+        assert!(from_slice_seed(Scaled(1), &trailing).is_err());
+        assert!(from_slice_seed(Scaled(1), &[]).is_err());
+    }
+
+    #[test]
+    fn test_from_slice_in_place_reuses_vectors() {
+        let mut rows: Vec<Vec<u32>> = (0..3).map(|_| Vec::with_capacity(16)).collect();
+        let buffers: Vec<*const u32> = rows.iter().map(|r| r.as_ptr()).collect();
+
+        for frame in [
+            vec![vec![1u32, 2], vec![3], vec![4, 5, 6]],
+            vec![vec![7], vec![8, 9], vec![]],
+        ] {
+            from_slice_in_place(&mut rows, &to_vec(&frame).unwrap()).unwrap();
+            assert_eq!(rows, frame);
+            let now: Vec<*const u32> = rows.iter().map(|r| r.as_ptr()).collect();
+            assert_eq!(now, buffers);
+        }
+
+        // Fewer rows truncates, more rows appends
+        from_slice_in_place(&mut rows, &to_vec(&vec![vec![1u32]]).unwrap()).unwrap();
+        assert_eq!(rows, [vec![1]]);
+        from_slice_in_place(&mut rows, &to_vec(&vec![vec![2u32]; 4]).unwrap()).unwrap();
+        assert_eq!(rows.len(), 4);
+
+        let mut value = 0u8;
+        assert!(from_slice_in_place(&mut value, &[0x01, 0x02]).is_err());
+        assert!(from_slice_in_place(&mut value, &[]).is_err());
+    }
+
+    #[test]
+    fn test_seq_and_map_size_hints() {
+        use serde::de::{Deserializer, MapAccess, SeqAccess, Visitor};
+
+        // Records the hint reported before and after the first element
+        struct Hints;
+        impl<'de> Visitor<'de> for Hints {
+            type Value = (Option<usize>, Option<usize>);
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("an array or map")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> std::result::Result<Self::Value, A::Error> {
+                let before = seq.size_hint();
+                seq.next_element::<serde::de::IgnoredAny>()?;
+                let after = seq.size_hint();
+                while seq.next_element::<serde::de::IgnoredAny>()?.is_some() {}
+                Ok((before, after))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> std::result::Result<Self::Value, A::Error> {
+                let before = map.size_hint();
+                map.next_entry::<serde::de::IgnoredAny, serde::de::IgnoredAny>()?;
+                let after = map.size_hint();
+                while map
+                    .next_entry::<serde::de::IgnoredAny, serde::de::IgnoredAny>()?
+                    .is_some()
+                {}
+                Ok((before, after))
+            }
+        }
+
+        let hints = |cbor: &[u8]| {
+            (&mut Decoder::from_slice(cbor))
+                .deserialize_any(Hints)
+                .unwrap()
+        };
+
+        let big = to_vec(&vec![0u8; 100_000]).unwrap();
+        assert_eq!(hints(&big), (Some(100_000), Some(99_999)));
+        assert_eq!(hints(&[0xa2, 0x01, 0x02, 0x03, 0x04]), (Some(2), Some(1)));
+        // Indefinite lengths have no hint
+        assert_eq!(hints(&[0x9f, 0x01, 0x02, 0xff]), (None, None));
+        assert_eq!(hints(&[0xbf, 0x01, 0x02, 0xff]), (None, None));
+
+        // Collections and Value arrays are allocated once from the header
+        let decoded: Vec<u8> = from_slice(&big).unwrap();
+        assert_eq!(decoded.capacity(), 100_000);
+        let value: Value = from_slice(&to_vec(&vec![1u8; 1000]).unwrap()).unwrap();
+        match value {
+            Value::Array(items) => assert_eq!(items.capacity(), 1000),
+            other => panic!("expected an array, got {other:?}"),
+        }
+        // A huge declared length doesn't pre-allocate before the data is read
+        assert!(from_slice::<Value>(&[0x9b, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff]).is_err());
+    }
+
     #[test]
     fn test_decode_error_empty_input() {
         let empty: &[u8] = &[];
@@ -2585,6 +3784,553 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("exceeds maximum"));
     }
 
+    #[test]
+    fn test_max_items_counts_every_header() {
+        use crate::Decoder;
+
+        // [1, "a", 24(h''), (_ "b", "c"), null] is 9 items
+        let cbor = [
+            0x85, 0x01, 0x61, b'a', 0xd8, 0x18, 0x40, 0x7f, 0x61, b'b', 0x61, b'c', 0xff, 0xf6,
+        ];
+        let mut decoder = Decoder::new(&cbor[..]).with_max_items(9);
+        assert!(decoder.decode::<Value>().is_ok());
+
+        let mut decoder = Decoder::new(&cbor[..]).with_max_items(8);
+        let err = decoder.decode::<Value>().unwrap_err();
+        assert!(err.to_string().contains("item count exceeds maximum 8"));
+
+        // Options and typed targets are counted the same way
+        let mut decoder = Decoder::new(&[0x82, 0xf6, 0x01][..]).with_max_items(2);
+        assert!(decoder.decode::<Vec<Option<u8>>>().is_err());
+    }
+
+    #[test]
+    fn test_memory_budget() {
+        use crate::Decoder;
+
+        let item = std::mem::size_of::<Value>();
+        // ["abc", h'0102', [1]] is 5 items and 5 bytes of strings
+        let cbor = [0x83, 0x63, b'a', b'b', b'c', 0x42, 0x01, 0x02, 0x81, 0x01];
+        let needed = 5 * item + 5;
+        for budget in [needed, needed + 100] {
+            let mut decoder = Decoder::new(&cbor[..]).with_memory_budget(budget);
+            decoder.decode::<Value>().unwrap();
+            let mut decoder = Decoder::new(&cbor[..]).with_memory_budget(budget);
+            decoder.decode_value().unwrap();
+        }
+        let mut decoder = Decoder::new(&cbor[..]).with_memory_budget(needed - 1);
+        assert!(matches!(
+            decoder.decode::<Value>(),
+            Err(Error::MemoryBudgetExceeded(b)) if b == needed - 1
+        ));
+        let mut decoder = Decoder::new(&cbor[..]).with_memory_budget(needed - 1);
+        assert!(matches!(
+            decoder.decode_value(),
+            Err(Error::MemoryBudgetExceeded(_))
+        ));
+
+        // A string is charged before its content is allocated or read
+        let huge = [0x5a, 0x7f, 0xff, 0xff, 0xff];
+        let mut decoder = Decoder::new(&huge[..]).with_memory_budget(1 << 20);
+        assert!(matches!(
+            decoder.decode::<Value>(),
+            Err(Error::MemoryBudgetExceeded(_))
+        ));
+
+        // The budget spans every item decoded with the same decoder
+        let mut decoder = Decoder::new(&[0x01, 0x02][..]).with_memory_budget(item);
+        decoder.decode::<u8>().unwrap();
+        assert!(decoder.decode::<u8>().is_err());
+    }
+
+    #[test]
+    fn test_reset() {
+        use crate::Decoder;
+
+        let item = std::mem::size_of::<Value>();
+        let mut decoder = Decoder::new(&[0x61, 0xff, 0x01][..])
+            .with_memory_budget(2 * item)
+            .with_lossy_utf8();
+        assert_eq!(decoder.decode::<String>().unwrap(), "\u{fffd}");
+        assert_eq!(decoder.invalid_utf8_strings().len(), 1);
+        // Peeks the trailing 0x01
+        assert!(decoder.end().is_err());
+        let rest = decoder.reset(&[0x02][..]);
+        assert!(rest.is_empty());
+        assert!(decoder.invalid_utf8_strings().is_empty());
+        assert_eq!(decoder.decode::<u8>().unwrap(), 2);
+        decoder.end().unwrap();
+
+        // The budget applies to each message again
+        decoder.reset(&[0x82, 0x01, 0x02][..]);
+        assert!(matches!(
+            decoder.decode::<Value>(),
+            Err(Error::MemoryBudgetExceeded(_))
+        ));
+
+        // An encoder keeps its options and counts from zero
+        #[derive(Serialize)]
+        struct Wrapper(u8);
+
+        let mut enc = Encoder::new(Vec::new()).with_legacy_newtypes();
+        enc.encode(&Wrapper(1)).unwrap();
+        assert_eq!(enc.reset(Vec::new()), [0x81, 0x01]);
+        assert_eq!(enc.bytes_written(), 0);
+        enc.encode(&Wrapper(2)).unwrap();
+        assert_eq!(enc.bytes_written(), 2);
+        assert_eq!(enc.into_inner(), [0x81, 0x02]);
+    }
+
+    #[test]
+    fn test_scratch_buffer() {
+        use crate::Decoder;
+
+        // {(_ "na", "me"): (_ "ab", "c"), "data": (_ h'01', h'0203')}
+        let mut cbor = vec![0xa2, 0x7f, 0x62, b'n', b'a', 0x62, b'm', b'e', 0xff];
+        cbor.extend([0x7f, 0x62, b'a', b'b', 0x61, b'c', 0xff]);
+        cbor.extend([0x64, b'd', b'a', b't', b'a']);
+        cbor.extend([0x5f, 0x41, 0x01, 0x42, 0x02, 0x03, 0xff]);
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Record {
+            name: String,
+            data: serde_bytes::ByteBuf,
+        }
+
+        let mut decoder = Decoder::new(&cbor[..]).with_scratch(Vec::with_capacity(64));
+        let record: Record = decoder.decode().unwrap();
+        assert_eq!(record.name, "abc");
+        assert_eq!(record.data.into_vec(), [1, 2, 3]);
+        let scratch = decoder.take_scratch();
+        assert_eq!(scratch.capacity(), 64);
+
+        // The buffer can move to a new decoder, and Value gets owned copies
+        let mut decoder = Decoder::new(&cbor[..]).with_scratch(scratch);
+        let value: Value = decoder.decode().unwrap();
+        let map = value.as_map().unwrap();
+        assert_eq!(map[&Value::Text("name".into())], Value::Text("abc".into()));
+        assert_eq!(
+            map[&Value::Text("data".into())],
+            Value::Bytes(vec![1, 2, 3])
+        );
+
+        // Chunks are still checked for UTF-8 one at a time
+        let split = [0x7f, 0x61, 0xc3, 0x61, 0xa9, 0xff];
+        assert!(matches!(
+            Decoder::new(&split[..]).decode::<String>(),
+            Err(Error::InvalidUtf8)
+        ));
+        let mut decoder = Decoder::new(&split[..]).with_lossy_utf8();
+        assert_eq!(decoder.decode::<String>().unwrap(), "\u{fffd}\u{fffd}");
+        assert_eq!(decoder.invalid_utf8_strings(), [vec![0xc3], vec![0xa9]]);
+    }
+
+    #[test]
+    fn test_lengths_beyond_32_bits() {
+        use crate::Decoder;
+
+        // A truncating cast on a 32-bit target would read these as h'0102030405'
+        // and [1]; everywhere they must fail instead
+        let bytes = [0x5b, 0, 0, 0, 0x01, 0, 0, 0, 0x05, 1, 2, 3, 4, 5];
+        let array = [0x9b, 0, 0, 0, 0x01, 0, 0, 0, 0x01, 0x01];
+        for cbor in [&bytes[..], &array[..]] {
+            // skip counts in u64 and never needs the conversion
+            assert!(Decoder::new(cbor).skip().is_err());
+            let results = [
+                from_slice::<Value>(cbor).map(drop),
+                Decoder::new(cbor)
+                    .with_max_allocation(1 << 20)
+                    .decode_value()
+                    .map(drop),
+                crate::validate(cbor),
+                crate::to_diagnostic(cbor).map(drop),
+                crate::canonicalize(cbor).map(drop),
+            ];
+            for result in results {
+                let err = result.unwrap_err();
+                #[cfg(target_pointer_width = "32")]
+                assert!(matches!(err, Error::LengthOverflow(_)), "{err:?}");
+                #[cfg(not(target_pointer_width = "32"))]
+                assert!(!matches!(err, Error::LengthOverflow(_)), "{err:?}");
+            }
+        }
+        assert_eq!(
+            Error::LengthOverflow(1 << 32).to_string(),
+            "Length 4294967296 exceeds maximum supported size on this platform"
+        );
+    }
+
+    #[test]
+    fn test_negative_integers_beyond_i64() {
+        // -1 - (2^64 - 1), the most negative CBOR integer
+        let min = [0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+        assert_eq!(from_slice::<i128>(&min).unwrap(), -18446744073709551616);
+        assert!(from_slice::<i64>(&min).is_err());
+        // Decoding into a Value fails the same way through either API
+        assert!(matches!(
+            from_slice::<Value>(&min),
+            Err(Error::IntegerOverflow(-18446744073709551616))
+        ));
+        assert!(matches!(
+            Decoder::new(&min[..]).decode_value(),
+            Err(Error::IntegerOverflow(-18446744073709551616))
+        ));
+        assert!(matches!(
+            Decoder::new(&min[..]).read_i64(),
+            Err(Error::IntegerOverflow(-18446744073709551616))
+        ));
+
+        // -2^63 is the boundary and still fits
+        let boundary = [0x3b, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+        assert_eq!(from_slice::<i64>(&boundary).unwrap(), i64::MIN);
+        assert_eq!(Decoder::new(&boundary[..]).read_i64().unwrap(), i64::MIN);
+
+        let unsigned = [0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+        assert!(matches!(
+            Decoder::new(&unsigned[..]).read_i64(),
+            Err(Error::IntegerOverflow(18446744073709551615))
+        ));
+        assert!(matches!(
+            from_slice::<Value>(&unsigned),
+            Err(Error::IntegerOverflow(18446744073709551615))
+        ));
+        assert!(matches!(
+            Decoder::new(&unsigned[..]).decode_value(),
+            Err(Error::IntegerOverflow(18446744073709551615))
+        ));
+        assert!(matches!(
+            to_value(u64::MAX),
+            Err(Error::IntegerOverflow(18446744073709551615))
+        ));
+        assert!(matches!(
+            to_value(-18446744073709551616i128),
+            Err(Error::IntegerOverflow(-18446744073709551616))
+        ));
+    }
+
+    #[test]
+    fn test_from_slice_bounded_rejects_dense_input() {
+        // 10,000 empty arrays in an array: one item per byte
+        let mut cbor = vec![0x99, 0x27, 0x10];
+        cbor.extend(std::iter::repeat_n(0x80, 10_000));
+        assert!(from_slice_bounded::<Vec<Vec<u8>>>(&cbor, 1.0).is_ok());
+        assert!(from_slice_bounded::<Vec<Vec<u8>>>(&cbor, 0.5).is_err());
+
+        // Ordinary data is well under the ratio
+        let cbor = to_vec(&vec!["hello".to_string(); 100]).unwrap();
+        assert_eq!(
+            from_slice_bounded::<Vec<String>>(&cbor, 0.5).unwrap().len(),
+            100
+        );
+        assert!(from_slice_bounded::<Value>(&[], 1.0).is_err());
+    }
+
+    #[test]
+    fn test_decode_value_matches_serde_path() {
+        use crate::Decoder;
+
+        let inputs: [&[u8]; 7] = [
+            // {"a": [1, -2, 1.5], 3: 24(h'01')}
+            &[
+                0xa2, 0x61, b'a', 0x83, 0x01, 0x21, 0xf9, 0x3e, 0x00, 0x03, 0xd8, 0x18, 0x41, 0x01,
+            ],
+            // [_ (_ "a", "b"), (_ h'01'), {_ 1: undefined}]
+            &[
+                0x9f, 0x7f, 0x61, b'a', 0x61, b'b', 0xff, 0x5f, 0x41, 0x01, 0xff, 0xbf, 0x01, 0xf7,
+                0xff, 0xff,
+            ],
+            &[0xfa, 0x3f, 0xc0, 0x00, 0x00],
+            &[0xfb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0],
+            &[0xc1, 0xc2, 0x80],
+            &[0xf4],
+            &[0x3b, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        ];
+        for bytes in inputs {
+            let expected: Value = from_slice(bytes).unwrap();
+            let mut decoder = Decoder::new(bytes);
+            assert_eq!(decoder.decode_value().unwrap(), expected);
+            decoder.end().unwrap();
+        }
+
+        let invalid: [&[u8]; 5] = [
+            &[0x1b, 0x80, 0, 0, 0, 0, 0, 0, 0],
+            &[0xbf, 0x01, 0xff],
+            &[0x81, 0xff],
+            &[0xfc],
+            &[0x82, 0x01],
+        ];
+        for bytes in invalid {
+            assert!(Decoder::new(bytes).decode_value().is_err());
+        }
+    }
+
+    #[test]
+    fn test_undefined() {
+        use crate::{Decoder, Encoder};
+
+        // [undefined, null]
+        let cbor = [0x82, 0xf7, 0xf6];
+        let value: Value = from_slice(&cbor).unwrap();
+        assert_eq!(value, Value::Array(vec![Value::Undefined, Value::Null]));
+        assert_eq!(Decoder::new(&cbor[..]).decode_value().unwrap(), value);
+        assert_eq!(to_vec(&value).unwrap(), cbor);
+        assert_eq!(value.to_canonical_vec(), cbor);
+
+        let mut enc = Encoder::new(Vec::new());
+        enc.write_value_streaming(&value).unwrap();
+        enc.write_undefined().unwrap();
+        assert_eq!(enc.into_inner(), [0x82, 0xf7, 0xf6, 0xf7]);
+
+        // Typed targets see undefined as unit or None
+        assert_eq!(from_slice::<Vec<Option<u8>>>(&cbor).unwrap(), [None, None]);
+        from_slice::<()>(&[0xf7]).unwrap();
+        // and other serializers see null
+        assert_eq!(serde_json::to_string(&value).unwrap(), "[null,null]");
+        // A visitor that never sees it doesn't leak it into the next unit
+        let after: Value = serde_json::from_str("null").unwrap();
+        assert_eq!(after, Value::Null);
+
+        // Strict mode rejects it in every path
+        let strict = || Decoder::new(&cbor[..]).with_strict_undefined();
+        assert!(strict().decode::<Value>().is_err());
+        assert!(strict().decode::<Vec<Option<u8>>>().is_err());
+        assert!(strict().decode_value().is_err());
+        let nulls = [0x81, 0xf6];
+        let mut decoder = Decoder::new(&nulls[..]).with_strict_undefined();
+        assert_eq!(
+            decoder.decode::<Value>().unwrap(),
+            Value::Array(vec![Value::Null])
+        );
+    }
+
+    #[test]
+    fn test_simple_values() {
+        use crate::{Decoder, Encoder};
+
+        // [simple(0), simple(19), simple(32), simple(255)]
+        let cbor = [0x84, 0xe0, 0xf3, 0xf8, 0x20, 0xf8, 0xff];
+        let value: Value = from_slice(&cbor).unwrap();
+        let expected = Value::Array([0, 19, 32, 255].map(Value::Simple).to_vec());
+        assert_eq!(value, expected);
+        assert_eq!(Decoder::new(&cbor[..]).decode_value().unwrap(), expected);
+        assert_eq!(to_vec(&value).unwrap(), cbor);
+        assert_eq!(value.to_canonical_vec(), cbor);
+        assert_eq!(crate::value::to_value(&value).unwrap(), value);
+        let mut enc = Encoder::new(Vec::new());
+        enc.write_value_streaming(&value).unwrap();
+        assert_eq!(enc.into_inner(), cbor);
+
+        // Typed targets see the number; other formats too
+        assert_eq!(from_slice::<Vec<u8>>(&cbor).unwrap(), [0, 19, 32, 255]);
+        assert_eq!(serde_json::to_string(&value).unwrap(), "[0,19,32,255]");
+        let plain: Value = from_slice(&[0x18, 0x20]).unwrap();
+        assert_eq!(plain, Value::Integer(32));
+
+        // 24-31 are reserved and have no encoding
+        let mut enc = Encoder::new(Vec::new());
+        assert!(enc.write_simple(24).is_err());
+        assert!(to_vec(&Value::Simple(31)).is_err());
+        enc.write_simple(20).unwrap();
+        assert_eq!(enc.into_inner(), [0xf4]);
+
+        // The 2-byte form below 32 is only accepted leniently
+        for bytes in [[0xf8, 0x18], [0xf8, 0x05]] {
+            let value: Value = from_slice(&bytes).unwrap();
+            assert_eq!(value, Value::Simple(bytes[1]));
+            let strict = || Decoder::new(&bytes[..]).with_strict_simple_values();
+            assert!(strict().decode::<Value>().is_err());
+            assert!(strict().decode_value().is_err());
+            assert!(strict().skip().is_err());
+            Decoder::new(&bytes[..]).skip().unwrap();
+        }
+        let mut decoder = Decoder::new(&cbor[..]).with_strict_simple_values();
+        assert_eq!(decoder.decode::<Value>().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_decode_value_deep_nesting() {
+        use crate::Decoder;
+
+        let mut bytes = vec![0x81; 100_000];
+        bytes.push(0x00);
+
+        let err = Decoder::new(&bytes[..]).decode_value().unwrap_err();
+        assert!(err.to_string().contains("exceeds maximum 128"));
+
+        let mut value = Decoder::new(&bytes[..])
+            .with_max_depth(100_001)
+            .decode_value()
+            .unwrap();
+        // Take the value apart iteratively, since dropping it would recurse
+        let mut depth = 0;
+        while let Value::Array(mut items) = value {
+            value = items.pop().unwrap();
+            depth += 1;
+        }
+        assert_eq!(depth, 100_000);
+        assert_eq!(value, Value::Integer(0));
+    }
+
+    #[test]
+    fn test_decode_value_checks_uris() {
+        use crate::{Decoder, UriPolicy};
+
+        let mut bytes = Vec::new();
+        encode_uri(&mut bytes, "ftp://example.com").unwrap();
+        let mut decoder = Decoder::new(&bytes[..]).with_uri_policy(UriPolicy::c2pa());
+        assert!(decoder.decode_value().is_err());
+        // Tag 32 around an integer
+        let mut decoder = Decoder::new(&[0xd8, 0x20, 0x01][..]).with_uri_policy(UriPolicy::c2pa());
+        assert!(decoder.decode_value().is_err());
+    }
+
+    #[test]
+    fn test_skip_items() {
+        use crate::Decoder;
+
+        // [_ 24(h'01'), {"a": (_ "b", "c")}, 1.5], then 7
+        let bytes = [
+            0x9f, 0xd8, 0x18, 0x41, 0x01, 0xa1, 0x61, b'a', 0x7f, 0x61, b'b', 0x61, b'c', 0xff,
+            0xf9, 0x3e, 0x00, 0xff, 0x07,
+        ];
+        let mut decoder = Decoder::new(&bytes[..]);
+        decoder.skip().unwrap();
+        assert_eq!(decoder.decode::<u8>().unwrap(), 7);
+        decoder.end().unwrap();
+
+        let mut deep = vec![0x81; 100_000];
+        deep.push(0x00);
+        assert!(Decoder::new(&deep[..]).skip().is_err());
+        Decoder::new(&deep[..])
+            .with_max_depth(100_001)
+            .skip()
+            .unwrap();
+
+        for bytes in [
+            &[0x82, 0x01][..],
+            &[0xc1][..],
+            &[0x81, 0xff][..],
+            &[0x62, b'a'][..],
+        ] {
+            assert!(Decoder::new(bytes).skip().is_err());
+        }
+    }
+
+    #[test]
+    fn test_ignored_fields_are_skipped() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Known {
+            a: u8,
+        }
+
+        // {"x": [[h'ff', 24("\xff")]], "a": 1}: the ignored field is never decoded,
+        // so its invalid UTF-8 is not an error
+        let bytes = [
+            0xa2, 0x61, b'x', 0x81, 0x82, 0x41, 0xff, 0xd8, 0x18, 0x61, 0xff, 0x61, b'a', 0x01,
+        ];
+        assert_eq!(from_slice::<Known>(&bytes).unwrap(), Known { a: 1 });
+
+        // Limits still apply to ignored values
+        let mut bytes = vec![0xa2, 0x61, b'x'];
+        bytes.extend(std::iter::repeat_n(0x81, 200));
+        bytes.extend([0x00, 0x61, b'a', 0x01]);
+        assert!(from_slice::<Known>(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_write_value_streaming_matches_encode() {
+        let mut map = std::collections::BTreeMap::new();
+        map.insert(
+            Value::Text("a".into()),
+            Value::Array(vec![Value::Null, Value::Float(1.5)]),
+        );
+        map.insert(Value::Integer(-7), Value::Bytes(vec![1, 2, 3]));
+        map.insert(Value::Text("empty".into()), Value::Map(Default::default()));
+        let value = Value::Array(vec![
+            Value::Map(map),
+            Value::Bool(true),
+            Value::Array(vec![]),
+        ]);
+
+        let mut encoder = Encoder::new(Vec::new());
+        encoder.write_value_streaming(&value).unwrap();
+        assert_eq!(encoder.into_inner(), to_vec(&value).unwrap());
+
+        // Tags are written, including nested ones
+        let tagged = Value::Tag(1, Box::new(Value::Tag(2, Box::new(Value::Bytes(vec![1])))));
+        let mut encoder = Encoder::new(Vec::new());
+        encoder.write_value_streaming(&tagged).unwrap();
+        assert_eq!(encoder.into_inner(), [0xc1, 0xc2, 0x41, 0x01]);
+    }
+
+    #[test]
+    fn test_write_value_streaming_deep_nesting() {
+        let mut value = Value::Integer(0);
+        for _ in 0..100_000 {
+            value = Value::Array(vec![value]);
+        }
+
+        let mut encoder = Encoder::new(Vec::new());
+        encoder.write_value_streaming(&value).unwrap();
+        let bytes = encoder.into_inner();
+        assert_eq!(bytes.len(), 100_001);
+        assert!(bytes[..100_000].iter().all(|&b| b == 0x81));
+
+        let decoded = Decoder::new(&bytes[..])
+            .with_max_depth(100_001)
+            .decode_value()
+            .unwrap();
+        // Compare and take both values apart iteratively, since dropping them would recurse
+        let (mut a, mut b) = (value, decoded);
+        loop {
+            match (a, b) {
+                (Value::Array(mut x), Value::Array(mut y)) => {
+                    a = x.pop().unwrap();
+                    b = y.pop().unwrap();
+                }
+                (x, y) => {
+                    assert_eq!(x, Value::Integer(0));
+                    assert_eq!(y, Value::Integer(0));
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_write_value_streaming_runs_hooks() {
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let mut encoder = Encoder::new(Vec::new()).with_hook(move |item: &[u8]| -> Result<()> {
+            sink.lock().unwrap().push(item.to_vec());
+            Ok(())
+        });
+        encoder
+            .write_value_streaming(&Value::Array(vec![Value::Integer(1)]))
+            .unwrap();
+        assert_eq!(encoder.into_inner(), [0x81, 0x01]);
+        assert_eq!(*seen.lock().unwrap(), vec![vec![0x81, 0x01]]);
+    }
+
+    #[test]
+    fn test_write_value_streaming_buffered_keeps_options() {
+        use crate::NanPolicy;
+
+        // Buffering for stringrefs or hooks must not drop the other options
+        let value = Value::Array(vec![Value::Float(f64::NAN)]);
+        let mut encoder = Encoder::new(Vec::new())
+            .with_nan_policy(NanPolicy::Error)
+            .with_stringrefs();
+        assert!(encoder.encode(&value).is_err());
+        assert!(encoder.write_value_streaming(&value).is_err());
+
+        let mut encoder = Encoder::new(Vec::new())
+            .with_nan_policy(NanPolicy::Canonical)
+            .with_hook(|_: &[u8]| -> Result<()> { Ok(()) });
+        encoder.write_value_streaming(&value).unwrap();
+        assert_eq!(encoder.into_inner(), [0x81, 0xf9, 0x7e, 0x00]);
+    }
+
     #[test]
     fn test_u64_to_usize_overflow() {
         use std::io::Cursor;
@@ -2623,15 +4369,11 @@ mod tests {
         assert_eq!(decoded.tag, Some(0));
         assert_eq!(decoded.value, "2024-01-15T10:30:00Z");
 
-        // Test that transparent mode works in Options (tag is ignored)
+        // Test that tags are kept inside Options
         let some_tagged = Some(Tagged::new(Some(32), "https://example.com".to_string()));
         let cbor = to_vec(&some_tagged).unwrap();
         let decoded: Option<Tagged<String>> = from_slice(&cbor).unwrap();
-        // With transparent mode, tag is lost but value is preserved
-        assert_eq!(
-            decoded.as_ref().map(|t| &t.value),
-            Some(&"https://example.com".to_string())
-        );
+        assert_eq!(decoded, some_tagged);
 
         // Test Option with None
         let none: Option<Tagged<String>> = None;
@@ -2698,4 +4440,241 @@ mod tests {
         let decoded: Option<Person> = from_slice(&cbor).unwrap();
         assert_eq!(decoded, None);
     }
+
+    #[test]
+    fn test_option_of_any_type() {
+        use crate::tags::Tagged;
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        enum Shape {
+            Point,
+            Circle(f64),
+            Rect { w: u8, h: u8 },
+        }
+
+        fn round_trip<T>(value: T)
+        where
+            T: Serialize + for<'de> Deserialize<'de> + PartialEq + std::fmt::Debug,
+        {
+            let cbor = to_vec(&value).unwrap();
+            assert_eq!(from_slice::<T>(&cbor).unwrap(), value);
+            assert_eq!(Decoder::new(&cbor[..]).decode::<T>().unwrap(), value);
+        }
+
+        round_trip(Some(Shape::Point));
+        round_trip(Some(Shape::Circle(1.5)));
+        round_trip(Some(Shape::Rect { w: 2, h: 3 }));
+        round_trip(Some((1u8, 2u8)));
+        round_trip(Some(Tagged::new(Some(1), 1_700_000_000u64)));
+        round_trip(Some(Some(5u8)));
+        round_trip(Some(1.5f32));
+        round_trip(Some(-2.25f64));
+        round_trip(Some(-7i8));
+        round_trip(vec![Some(1u8), None, Some(3)]);
+        round_trip(Some("text".to_string()));
+
+        // Strings inside an Option may still be chunked
+        let cbor = [0x7f, 0x61, b'a', 0x61, b'b', 0xff];
+        assert_eq!(
+            from_slice::<Option<String>>(&cbor).unwrap(),
+            Some("ab".into())
+        );
+
+        // A mismatched payload is rejected by the inner type
+        let cbor = to_vec(&Some("x")).unwrap();
+        assert!(from_slice::<Option<u8>>(&cbor).is_err());
+    }
+
+    #[test]
+    fn test_floats_in_options_and_newtypes() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Meters(f64);
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Reading {
+            value: Option<f32>,
+            offset: Option<Meters>,
+        }
+
+        // Half, single and double precision encodings of 1.5
+        for cbor in [
+            &[0xf9, 0x3e, 0x00][..],
+            &[0xfa, 0x3f, 0xc0, 0x00, 0x00],
+            &[0xfb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0],
+        ] {
+            assert_eq!(from_slice::<Option<f64>>(cbor).unwrap(), Some(1.5));
+            assert_eq!(from_slice::<Option<f32>>(cbor).unwrap(), Some(1.5));
+            assert_eq!(from_slice::<Meters>(cbor).unwrap(), Meters(1.5));
+            assert_eq!(
+                from_slice::<Option<Meters>>(cbor).unwrap(),
+                Some(Meters(1.5))
+            );
+            // The owned decoder takes the same paths as `&mut Decoder`
+            assert_eq!(
+                Meters::deserialize(Decoder::new(cbor)).unwrap(),
+                Meters(1.5)
+            );
+            assert_eq!(
+                Option::<f64>::deserialize(Decoder::new(cbor)).unwrap(),
+                Some(1.5)
+            );
+        }
+
+        // An epoch time (tag 1) with a float payload
+        let cbor = [0xc1, 0xfb, 0x41, 0xd9, 0x54, 0xfc, 0x40, 0x00, 0x00, 0x00];
+        assert_eq!(
+            from_slice::<Option<f64>>(&cbor).unwrap(),
+            Some(1_700_000_000.0)
+        );
+
+        let reading = Reading {
+            value: Some(-0.25),
+            offset: Some(Meters(f64::MAX)),
+        };
+        assert_eq!(
+            from_slice::<Reading>(&to_vec(&reading).unwrap()).unwrap(),
+            reading
+        );
+        let empty = Reading {
+            value: None,
+            offset: None,
+        };
+        assert_eq!(
+            from_slice::<Reading>(&to_vec(&empty).unwrap()).unwrap(),
+            empty
+        );
+
+        let nan = from_slice::<Option<f64>>(&[0xf9, 0x7e, 0x00]).unwrap();
+        assert!(nan.is_some_and(f64::is_nan));
+    }
+
+    #[test]
+    fn test_nan_policy() {
+        use crate::{Decoder, Encoder, NanPolicy};
+
+        fn encode<T: Serialize>(value: &T, policy: NanPolicy) -> Result<Vec<u8>> {
+            let mut enc = Encoder::new(Vec::new()).with_nan_policy(policy);
+            enc.encode(value)?;
+            Ok(enc.into_inner())
+        }
+
+        let payload = f64::from_bits(0x7ff8_0000_0000_0001);
+        // The default keeps width and payload
+        assert_eq!(
+            to_vec(&payload).unwrap(),
+            [0xfb, 0x7f, 0xf8, 0, 0, 0, 0, 0, 0x01]
+        );
+        assert_eq!(to_vec(&f32::NAN).unwrap(), [0xfa, 0x7f, 0xc0, 0x00, 0x00]);
+
+        for nan in [payload, f64::NAN, -f64::NAN] {
+            assert_eq!(
+                encode(&nan, NanPolicy::Canonical).unwrap(),
+                [0xf9, 0x7e, 0x00]
+            );
+            assert!(encode(&nan, NanPolicy::Error).is_err());
+        }
+        assert_eq!(
+            encode(&vec![f32::NAN], NanPolicy::Canonical).unwrap(),
+            [0x81, 0xf9, 0x7e, 0x00]
+        );
+        let value = Value::Array(vec![Value::Float(f64::NAN)]);
+        assert!(encode(&value, NanPolicy::Error).is_err());
+        // Other floats are untouched
+        assert_eq!(
+            encode(&f64::INFINITY, NanPolicy::Error).unwrap(),
+            to_vec(&f64::INFINITY).unwrap()
+        );
+        assert_eq!(
+            encode(&1.5f32, NanPolicy::Canonical).unwrap(),
+            to_vec(&1.5f32).unwrap()
+        );
+
+        // Decoding can require finite floats
+        let non_finite: [&[u8]; 4] = [
+            &[0xf9, 0x7e, 0x00],
+            &[0xf9, 0x7c, 0x00],
+            &[0xfa, 0xff, 0x80, 0x00, 0x00],
+            &[0xfb, 0x7f, 0xf8, 0, 0, 0, 0, 0, 0x01],
+        ];
+        for bytes in non_finite {
+            assert!(!from_slice::<f64>(bytes).unwrap().is_finite());
+            let finite = || Decoder::new(bytes).with_finite_floats();
+            assert!(finite().decode::<f64>().is_err());
+            assert!(finite().decode::<Value>().is_err());
+            assert!(finite().decode_value().is_err());
+            assert!(finite().read_f64().is_err());
+        }
+        let mut decoder = Decoder::new(&[0xf9, 0x3e, 0x00][..]).with_finite_floats();
+        assert_eq!(decoder.decode::<f32>().unwrap(), 1.5);
+    }
+
+    #[test]
+    fn test_number_coercion() {
+        use crate::{Decoder, NumberCoercion};
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Size {
+            width: u32,
+            ratio: f64,
+            offset: i8,
+        }
+
+        fn decode<T: for<'de> Deserialize<'de>>(bytes: &[u8], mode: NumberCoercion) -> Result<T> {
+            Decoder::new(bytes).with_number_coercion(mode).decode()
+        }
+
+        // {"width": 640.0, "ratio": 2, "offset": -3.0}
+        let json_like = [
+            0xa3, 0x65, b'w', b'i', b'd', b't', b'h', 0xf9, 0x61, 0x00, 0x65, b'r', b'a', b't',
+            b'i', b'o', 0x02, 0x66, b'o', b'f', b'f', b's', b'e', b't', 0xf9, 0xc2, 0x00,
+        ];
+        let expected = Size {
+            width: 640,
+            ratio: 2.0,
+            offset: -3,
+        };
+        assert!(from_slice::<Size>(&json_like).is_err());
+        assert_eq!(
+            decode::<Size>(&json_like, NumberCoercion::Strict).unwrap(),
+            expected
+        );
+        assert_eq!(
+            decode::<Size>(&json_like, NumberCoercion::Lossy).unwrap(),
+            expected
+        );
+
+        // 2.5, -0.5, 1e20 (out of range), NaN
+        let fraction = [0xf9, 0x41, 0x00];
+        let negative = [0xf9, 0xb8, 0x00];
+        let huge = [0xfb, 0x44, 0x15, 0xaf, 0x1d, 0x78, 0xb5, 0x8c, 0x40];
+        let nan = [0xf9, 0x7e, 0x00];
+        assert!(decode::<u32>(&fraction, NumberCoercion::Strict).is_err());
+        assert_eq!(decode::<u32>(&fraction, NumberCoercion::Lossy).unwrap(), 2);
+        assert_eq!(decode::<i32>(&negative, NumberCoercion::Lossy).unwrap(), 0);
+        assert!(decode::<u64>(&huge, NumberCoercion::Lossy).is_err());
+        assert!(decode::<u8>(&[0xf9, 0x5c, 0x00], NumberCoercion::Strict).is_err()); // 256.0
+        assert!(decode::<i64>(&nan, NumberCoercion::Lossy).is_err());
+
+        // 2^53 + 1 has no exact f64; 2^24 + 1 has no exact f32
+        let big = to_vec(&9007199254740993u64).unwrap();
+        assert!(decode::<f64>(&big, NumberCoercion::Strict).is_err());
+        assert_eq!(
+            decode::<f64>(&big, NumberCoercion::Lossy).unwrap(),
+            9007199254740992.0
+        );
+        let medium = to_vec(&16777217u32).unwrap();
+        assert!(decode::<f32>(&medium, NumberCoercion::Strict).is_err());
+        assert_eq!(
+            decode::<f64>(&medium, NumberCoercion::Strict).unwrap(),
+            16777217.0
+        );
+
+        // Matching types and other types are unaffected
+        assert_eq!(
+            decode::<f32>(&fraction, NumberCoercion::Strict).unwrap(),
+            2.5
+        );
+        assert_eq!(decode::<i8>(&[0x22], NumberCoercion::Strict).unwrap(), -3);
+        assert!(decode::<u8>(&[0x61, b'1'], NumberCoercion::Lossy).is_err());
+    }
 }
@@ -0,0 +1,175 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Tell an absent struct field from an explicit null
+//!
+//! Use on `Option<Option<T>>` fields together with `#[serde(default)]`:
+//!
+//! | CBOR             | Field value      |
+//! |------------------|------------------|
+//! | field absent     | `None`           |
+//! | `null`           | `Some(None)`     |
+//! | any other value  | `Some(Some(v))`  |
+//!
+//! The explicit null is accepted by
+//! [`Decoder::with_strict_nulls`](crate::Decoder::with_strict_nulls), and is
+//! kept by [`Encoder::with_omit_none_fields`](crate::Encoder::with_omit_none_fields),
+//! which leaves out only the absent case. Without that encoder option, add
+//! `skip_serializing_if = "Option::is_none"` so `None` stays absent.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::Encoder;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Patch {
+//!     #[serde(default, with = "c2pa_cbor::nullable")]
+//!     title: Option<Option<String>>,
+//! }
+//!
+//! let clear = Patch { title: Some(None) };
+//! let mut encoder = Encoder::new(Vec::new()).with_omit_none_fields();
+//! encoder.encode(&clear).unwrap();
+//! encoder.encode(&Patch { title: None }).unwrap();
+//! let cbor = encoder.into_inner();
+//! assert_eq!(cbor, [0xa1, 0x65, b't', b'i', b't', b'l', b'e', 0xf6, 0xa0]);
+//!
+//! let mut decoder = c2pa_cbor::Decoder::new(&cbor[..]).with_strict_nulls();
+//! assert_eq!(decoder.decode::<Patch>().unwrap(), clear);
+//! assert_eq!(decoder.decode::<Patch>().unwrap(), Patch { title: None });
+//! ```
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, Visitor},
+};
+
+use crate::constants::NULLABLE_MARKER;
+
+/// Serialize a field that may be absent, null or a value
+pub fn serialize<T, S>(value: &Option<Option<T>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    match value {
+        None => serializer.serialize_none(),
+        Some(inner) => serializer.serialize_newtype_struct(NULLABLE_MARKER, inner),
+    }
+}
+
+/// Deserialize a field that is present, either as null or a value
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    deserializer
+        .deserialize_newtype_struct(NULLABLE_MARKER, NullableVisitor(PhantomData))
+        .map(Some)
+}
+
+struct NullableVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for NullableVisitor<T> {
+    type Value = Option<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("null or a value")
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Option<T>, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Option<T>, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Option<T>, D::Error> {
+        T::deserialize(deserializer).map(Some)
+    }
+
+    // Formats other than CBOR see an ordinary newtype
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Option<T>, D::Error> {
+        Option::<T>::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::{Decoder, Encoder, from_slice, to_vec};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Patch {
+        #[serde(default, with = "crate::nullable")]
+        title: Option<Option<String>>,
+        count: Option<u8>,
+    }
+
+    fn strict(cbor: &[u8]) -> crate::Result<Patch> {
+        Decoder::new(cbor).with_strict_nulls().decode()
+    }
+
+    #[test]
+    fn test_nullable_distinguishes_absent_and_null() {
+        let cases = [
+            (None, vec![0xa0]),
+            (
+                Some(None),
+                vec![0xa1, 0x65, b't', b'i', b't', b'l', b'e', 0xf6],
+            ),
+            (
+                Some(Some("a".to_string())),
+                vec![0xa1, 0x65, b't', b'i', b't', b'l', b'e', 0x61, b'a'],
+            ),
+        ];
+        for (title, expected) in cases {
+            let patch = Patch { title, count: None };
+            let mut enc = Encoder::new(Vec::new()).with_omit_none_fields();
+            enc.encode(&patch).unwrap();
+            let cbor = enc.into_inner();
+            assert_eq!(cbor, expected);
+            assert_eq!(strict(&cbor).unwrap(), patch);
+            assert_eq!(from_slice::<Patch>(&cbor).unwrap(), patch);
+        }
+
+        // Without omitting, None is written as null and reads back as Some(None)
+        let cbor = to_vec(&Patch {
+            title: None,
+            count: None,
+        })
+        .unwrap();
+        assert_eq!(from_slice::<Patch>(&cbor).unwrap().title, Some(None));
+        // and strict nulls still reject the plain Option
+        assert!(strict(&cbor).is_err());
+    }
+
+    #[test]
+    fn test_nullable_with_json() {
+        let patch: Patch = serde_json::from_str(r#"{"title": null}"#).unwrap();
+        assert_eq!(patch.title, Some(None));
+        let patch: Patch = serde_json::from_str("{}").unwrap();
+        assert_eq!(patch.title, None);
+        let patch: Patch = serde_json::from_str(r#"{"title": "a"}"#).unwrap();
+        assert_eq!(patch.title, Some(Some("a".to_string())));
+    }
+}
@@ -0,0 +1,244 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Structural differences between [`Value`]s
+//!
+//! [`diff`] records what changed from one value to another as a [`Patch`],
+//! which [`Patch::apply`] replays onto the first value, or onto another
+//! value with the same shape. Maps are compared entry by entry, so a patch
+//! leaves entries it does not mention alone; any other change replaces the
+//! value at that point whole, arrays included. Unlike a merge with
+//! [`Value::merge`], a patch can set an entry to null.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::{Value, diff};
+//!
+//! let text = |s: &str| Value::Text(s.to_string());
+//! let template = Value::Map([(text("format"), text("image/jpeg"))].into());
+//! let item = Value::Map(
+//!     [(text("format"), text("image/png")), (text("title"), text("a.png"))].into(),
+//! );
+//!
+//! let patch = diff(&template, &item);
+//! let mut other = Value::Map(
+//!     [(text("format"), text("image/jpeg")), (text("size"), Value::Integer(1))].into(),
+//! );
+//! patch.apply(&mut other);
+//! assert_eq!(other.as_map().unwrap().len(), 3);
+//! assert_eq!(other.as_map().unwrap()[&text("format")], text("image/png"));
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::{Value, value::ordered_entry};
+
+/// The changes that turn one [`Value`] into another, made by [`diff`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Patch {
+    /// Leave the value as it is
+    Unchanged,
+    /// Replace the value
+    Replace(Value),
+    /// Change entries of a map, leaving other entries alone: `Some` patches
+    /// the entry, starting from null if it is missing, and `None` removes it
+    ///
+    /// A value that is not a map is first replaced with an empty map. An
+    /// [`OrderedMap`](Value::OrderedMap) is changed in place, keeping its
+    /// order and appending new entries.
+    Entries(BTreeMap<Value, Option<Patch>>),
+}
+
+impl Patch {
+    /// Whether applying this patch changes nothing
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Patch::Unchanged)
+    }
+
+    /// Make the changes recorded in this patch to `value`
+    pub fn apply(&self, value: &mut Value) {
+        match self {
+            Patch::Unchanged => {}
+            Patch::Replace(new) => *value = new.clone(),
+            Patch::Entries(entries) => {
+                if let Value::OrderedMap(target) = value {
+                    for (key, entry) in entries {
+                        match entry {
+                            Some(patch) => patch.apply(ordered_entry(target, key.clone())),
+                            None => target.retain(|(k, _)| k != key),
+                        }
+                    }
+                    return;
+                }
+                if !value.is_map() {
+                    *value = Value::Map(BTreeMap::new());
+                }
+                let Value::Map(map) = value else {
+                    unreachable!()
+                };
+                for (key, entry) in entries {
+                    match entry {
+                        Some(patch) => patch.apply(map.entry(key.clone()).or_insert(Value::Null)),
+                        None => {
+                            map.remove(key);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The changes that turn `from` into `to`
+///
+/// Applying the result to `from` gives a value equal to `to`, except that a
+/// NaN float is always replaced since it is not equal to itself.
+pub fn diff(from: &Value, to: &Value) -> Patch {
+    match (from, to) {
+        (Value::Map(from), Value::Map(to)) => {
+            let mut entries = BTreeMap::new();
+            for key in from.keys().filter(|key| !to.contains_key(key)) {
+                entries.insert(key.clone(), None);
+            }
+            for (key, value) in to {
+                let patch = match from.get(key) {
+                    Some(old) => diff(old, value),
+                    None => Patch::Replace(value.clone()),
+                };
+                if !patch.is_empty() {
+                    entries.insert(key.clone(), Some(patch));
+                }
+            }
+            if entries.is_empty() {
+                Patch::Unchanged
+            } else {
+                Patch::Entries(entries)
+            }
+        }
+        _ if from == to => Patch::Unchanged,
+        _ => Patch::Replace(to.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    fn map<const N: usize>(entries: [(&str, Value); N]) -> Value {
+        Value::Map(entries.into_iter().map(|(k, v)| (text(k), v)).collect())
+    }
+
+    #[test]
+    fn test_diff_round_trip() {
+        let from = map([
+            ("keep", Value::Integer(1)),
+            ("drop", Value::Integer(2)),
+            (
+                "nested",
+                map([("a", Value::Integer(1)), ("b", Value::Null)]),
+            ),
+            ("list", Value::Array(vec![Value::Integer(1)])),
+        ]);
+        let to = map([
+            ("keep", Value::Integer(1)),
+            ("nested", map([("a", Value::Null), ("b", Value::Null)])),
+            (
+                "list",
+                Value::Array(vec![Value::Integer(1), Value::Integer(2)]),
+            ),
+            ("new", Value::Tag(32, Box::new(text("https://a")))),
+        ]);
+
+        let patch = diff(&from, &to);
+        assert_eq!(
+            patch,
+            Patch::Entries(BTreeMap::from([
+                (text("drop"), None),
+                (
+                    text("list"),
+                    Some(Patch::Replace(Value::Array(vec![
+                        Value::Integer(1),
+                        Value::Integer(2)
+                    ])))
+                ),
+                (
+                    text("nested"),
+                    Some(Patch::Entries(BTreeMap::from([(
+                        text("a"),
+                        Some(Patch::Replace(Value::Null))
+                    )])))
+                ),
+                (
+                    text("new"),
+                    Some(Patch::Replace(Value::Tag(32, Box::new(text("https://a")))))
+                ),
+            ]))
+        );
+
+        let mut patched = from.clone();
+        patch.apply(&mut patched);
+        assert_eq!(patched, to);
+        assert!(diff(&to, &patched).is_empty());
+    }
+
+    #[test]
+    fn test_apply_to_other_shapes() {
+        let patch = Patch::Entries(BTreeMap::from([
+            (text("a"), Some(Patch::Replace(Value::Integer(1)))),
+            (
+                text("b"),
+                Some(Patch::Entries(BTreeMap::from([(text("c"), None)]))),
+            ),
+        ]));
+
+        // Missing entries start from null, and non-maps become maps
+        let mut value = Value::Array(vec![]);
+        patch.apply(&mut value);
+        assert_eq!(value, map([("a", Value::Integer(1)), ("b", map([]))]));
+
+        let mut value = Value::Integer(5);
+        Patch::Unchanged.apply(&mut value);
+        assert_eq!(value, Value::Integer(5));
+        assert_eq!(
+            diff(&Value::Integer(5), &text("5")),
+            Patch::Replace(text("5"))
+        );
+    }
+
+    #[test]
+    fn test_apply_to_ordered_map() {
+        let mut value = Value::OrderedMap(vec![
+            (text("z"), Value::Integer(0)),
+            (text("b"), Value::Integer(2)),
+            (text("y"), Value::Integer(0)),
+        ]);
+        let patch = Patch::Entries(BTreeMap::from([
+            (text("a"), Some(Patch::Replace(Value::Integer(1)))),
+            (text("b"), None),
+            (text("y"), Some(Patch::Replace(Value::Integer(3)))),
+        ]));
+        patch.apply(&mut value);
+        assert_eq!(
+            value,
+            Value::OrderedMap(vec![
+                (text("z"), Value::Integer(0)),
+                (text("y"), Value::Integer(3)),
+                (text("a"), Value::Integer(1)),
+            ])
+        );
+    }
+}
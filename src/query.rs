@@ -0,0 +1,431 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Path queries over encoded CBOR
+//!
+//! Enabled with the `query` feature, which also enables `lazy`. A
+//! [`Query`] is a JSONPath-style expression that selects items of an
+//! encoded document. It is evaluated with [`LazyValue`] handles, so only
+//! the headers on the way to a match are read, and only the matches
+//! themselves are decoded.
+//!
+//! A query starts with `$`, the whole document, followed by steps:
+//! - `.name` or `["name"]`: the value of a text key in a map; quote keys
+//!   that are not identifiers, such as `["c2pa.actions"]`
+//! - `[n]`: element `n` of an array, or the value of integer key `n` in a
+//!   map, so `[-1]` reads a COSE header label
+//! - `.*` or `[*]`: every element of an array, or every value of a map
+//! - `..` before any of these: the same, applied to the item and to
+//!   everything nested in it, at any depth
+//!
+//! Tags are looked through at every step. A step that finds nothing, for
+//! example a key on an array, drops that item from the results rather than
+//! failing.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::{Value, query::{Query, query}};
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Assertion {
+//!     label: String,
+//! }
+//!
+//! #[derive(Serialize)]
+//! struct Manifest {
+//!     assertions: Vec<Assertion>,
+//! }
+//!
+//! let manifest = Manifest {
+//!     assertions: vec![
+//!         Assertion { label: "c2pa.actions".to_string() },
+//!         Assertion { label: "c2pa.hash.data".to_string() },
+//!     ],
+//! };
+//! let cbor = c2pa_cbor::to_vec(&manifest).unwrap();
+//!
+//! let labels = query(&cbor, "$.assertions[*].label").unwrap();
+//! assert_eq!(labels, [Value::Text("c2pa.actions".into()), Value::Text("c2pa.hash.data".into())]);
+//!
+//! // Parse once to run the same query over many documents
+//! let second: Query = "$..label".parse().unwrap();
+//! let spans = second.spans(&cbor).unwrap();
+//! assert_eq!(&cbor[spans[1].clone()][1..], b"c2pa.hash.data");
+//! ```
+
+use std::{ops::Range, str::FromStr};
+
+use crate::{Error, MajorType, Result, Value, lazy::LazyValue, raw::check_depth};
+
+/// Selects items within one item, for a step of a [`Query`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Selector {
+    // The value of a text key
+    Key(String),
+    // An array element, or the value of an integer key
+    Int(i64),
+    // Every array element or map value
+    Wildcard,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    // Apply to nested items at any depth too
+    descendants: bool,
+    selector: Selector,
+}
+
+/// A parsed path query, see the [module documentation](self)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+impl Query {
+    /// Parse a query such as `$.assertions[*].label`
+    pub fn parse(path: &str) -> Result<Self> {
+        Parser { path, pos: 0 }.parse()
+    }
+
+    /// Handles on the items that match, in document order
+    ///
+    /// Returns an error if the parts of `bytes` read on the way are
+    /// malformed.
+    pub fn find<'a>(&self, bytes: &'a [u8]) -> Result<Vec<LazyValue<'a>>> {
+        let mut current = vec![LazyValue::new(bytes)];
+        for step in &self.steps {
+            let mut next = Vec::new();
+            for item in current {
+                if step.descendants {
+                    select_descendants(item, &step.selector, &mut next, 0)?;
+                } else {
+                    select(item, &step.selector, &mut next)?;
+                }
+            }
+            current = next;
+        }
+        Ok(current)
+    }
+
+    /// Byte ranges of the items that match, in document order
+    pub fn spans(&self, bytes: &[u8]) -> Result<Vec<Range<usize>>> {
+        self.find(bytes)?
+            .iter()
+            .map(|item| {
+                // Each match is a subslice of `bytes`
+                let raw = item.as_raw()?;
+                let start = raw.as_ptr() as usize - bytes.as_ptr() as usize;
+                Ok(start..start + raw.len())
+            })
+            .collect()
+    }
+
+    /// The items that match, decoded
+    pub fn values(&self, bytes: &[u8]) -> Result<Vec<Value>> {
+        self.find(bytes)?.iter().map(LazyValue::decode).collect()
+    }
+}
+
+impl FromStr for Query {
+    type Err = Error;
+
+    fn from_str(path: &str) -> Result<Self> {
+        Query::parse(path)
+    }
+}
+
+/// Decode the items of `bytes` that match the query `path`
+///
+/// Parses `path` each time; use [`Query`] to run one query many times.
+pub fn query(bytes: &[u8], path: &str) -> Result<Vec<Value>> {
+    Query::parse(path)?.values(bytes)
+}
+
+fn select<'a>(
+    item: LazyValue<'a>,
+    selector: &Selector,
+    out: &mut Vec<LazyValue<'a>>,
+) -> Result<()> {
+    let item = item.untagged()?;
+    match (item.major()?, selector) {
+        (MajorType::Map, Selector::Key(key)) => out.extend(item.get(key)?),
+        (MajorType::Map, Selector::Int(key)) => out.extend(item.get_int(*key)?),
+        (MajorType::Array, Selector::Int(index)) => {
+            if let Ok(index) = usize::try_from(*index) {
+                out.extend(item.index(index)?);
+            }
+        }
+        (MajorType::Map, Selector::Wildcard) => {
+            for entry in item.entries()? {
+                out.push(entry?.1);
+            }
+        }
+        (MajorType::Array, Selector::Wildcard) => {
+            for element in item.items()? {
+                out.push(element?);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn select_descendants<'a>(
+    item: LazyValue<'a>,
+    selector: &Selector,
+    out: &mut Vec<LazyValue<'a>>,
+    depth: usize,
+) -> Result<()> {
+    check_depth(depth)?;
+    select(item, selector, out)?;
+    let item = item.untagged()?;
+    match item.major()? {
+        MajorType::Map => {
+            for entry in item.entries()? {
+                select_descendants(entry?.1, selector, out, depth + 1)?;
+            }
+        }
+        MajorType::Array => {
+            for element in item.items()? {
+                select_descendants(element?, selector, out, depth + 1)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+struct Parser<'p> {
+    path: &'p str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> Error {
+        Error::Syntax(format!(
+            "Invalid query {:?} at offset {}: {}",
+            self.path, self.pos, message
+        ))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.path[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            return true;
+        }
+        false
+    }
+
+    fn parse(mut self) -> Result<Query> {
+        if !self.eat('$') {
+            return Err(self.error("expected '$'"));
+        }
+        let mut steps = Vec::new();
+        while self.pos < self.path.len() {
+            let descendants = if self.eat('.') {
+                let descendants = self.eat('.');
+                if !descendants || self.peek() != Some('[') {
+                    steps.push(Step {
+                        descendants,
+                        selector: self.dotted()?,
+                    });
+                    continue;
+                }
+                true
+            } else {
+                false
+            };
+            if !self.eat('[') {
+                return Err(self.error("expected '.' or '['"));
+            }
+            let selector = self.bracketed()?;
+            if !self.eat(']') {
+                return Err(self.error("expected ']'"));
+            }
+            steps.push(Step {
+                descendants,
+                selector,
+            });
+        }
+        Ok(Query { steps })
+    }
+
+    // A name or `*` after a dot
+    fn dotted(&mut self) -> Result<Selector> {
+        if self.eat('*') {
+            return Ok(Selector::Wildcard);
+        }
+        let rest = &self.path[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a key name or '*'"));
+        }
+        self.pos += len;
+        Ok(Selector::Key(rest[..len].to_string()))
+    }
+
+    // A quoted key, an integer or `*` between brackets
+    fn bracketed(&mut self) -> Result<Selector> {
+        if self.eat('*') {
+            return Ok(Selector::Wildcard);
+        }
+        for quote in ['"', '\''] {
+            if self.eat(quote) {
+                return self.quoted(quote).map(Selector::Key);
+            }
+        }
+        let rest = &self.path[self.pos..];
+        let len = rest.find(']').unwrap_or(rest.len());
+        let index = rest[..len]
+            .parse()
+            .map_err(|_| self.error("expected a quoted key, an integer or '*'"))?;
+        self.pos += len;
+        Ok(Selector::Int(index))
+    }
+
+    // The rest of a quoted key, with `\` escaping the next character
+    fn quoted(&mut self, quote: char) -> Result<String> {
+        let mut key = String::new();
+        let mut chars = self.path[self.pos..].chars();
+        while let Some(c) = chars.next() {
+            self.pos += c.len_utf8();
+            match c {
+                '\\' => {
+                    let escaped = chars.next().ok_or_else(|| self.error("unterminated key"))?;
+                    self.pos += escaped.len_utf8();
+                    key.push(escaped);
+                }
+                c if c == quote => return Ok(key),
+                c => key.push(c),
+            }
+        }
+        Err(self.error("unterminated key"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // {"assertions": [{"label": "a", "data": {"label": "b"}}, 24({"label": "c"})],
+    //  "c2pa.claim": {-1: 7}}
+    fn sample() -> Vec<u8> {
+        let label = |text: &str| {
+            let mut entry = vec![0x65];
+            entry.extend_from_slice(b"label");
+            entry.push(0x60 + text.len() as u8);
+            entry.extend_from_slice(text.as_bytes());
+            entry
+        };
+        let mut cbor = vec![0xa2, 0x6a];
+        cbor.extend_from_slice(b"assertions");
+        cbor.extend_from_slice(&[0x82, 0xa2]);
+        cbor.extend(label("a"));
+        cbor.push(0x64);
+        cbor.extend_from_slice(b"data");
+        cbor.push(0xa1);
+        cbor.extend(label("b"));
+        cbor.extend_from_slice(&[0xd8, 0x18, 0xa1]);
+        cbor.extend(label("c"));
+        cbor.push(0x6a);
+        cbor.extend_from_slice(b"c2pa.claim");
+        cbor.extend_from_slice(&[0xa1, 0x20, 0x07]);
+        cbor
+    }
+
+    fn texts(values: Vec<Value>) -> Vec<String> {
+        values
+            .into_iter()
+            .map(|value| value.as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_query_steps() {
+        let cbor = sample();
+        assert_eq!(
+            texts(query(&cbor, "$.assertions[*].label").unwrap()),
+            ["a", "c"]
+        );
+        assert_eq!(
+            texts(query(&cbor, "$['assertions'][1].label").unwrap()),
+            ["c"]
+        );
+        assert_eq!(texts(query(&cbor, "$..label").unwrap()), ["a", "b", "c"]);
+        assert_eq!(
+            texts(query(&cbor, "$.assertions..[\"label\"]").unwrap()),
+            ["a", "b", "c"]
+        );
+        assert_eq!(
+            query(&cbor, "$[\"c2pa.claim\"][-1]").unwrap(),
+            [Value::Integer(7)]
+        );
+        assert_eq!(query(&cbor, "$.*").unwrap().len(), 2);
+        assert_eq!(query(&cbor, "$").unwrap().len(), 1);
+
+        // Steps that find nothing drop the item
+        assert!(query(&cbor, "$.assertions.label").unwrap().is_empty());
+        assert!(query(&cbor, "$.assertions[5]").unwrap().is_empty());
+        assert!(query(&cbor, "$.missing[*]").unwrap().is_empty());
+
+        let spans = Query::parse("$.assertions[0].data")
+            .unwrap()
+            .spans(&cbor)
+            .unwrap();
+        assert_eq!(
+            crate::from_slice::<Value>(&cbor[spans[0].clone()])
+                .unwrap()
+                .as_map()
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_query_errors() {
+        for path in [
+            "",
+            "assertions",
+            "$.",
+            "$[",
+            "$[x]",
+            "$['a",
+            "$.a b",
+            "$..",
+            "$[1",
+        ] {
+            assert!(Query::parse(path).is_err(), "{}", path);
+        }
+        assert_eq!(
+            Query::parse(r#"$['it\'s']"#).unwrap(),
+            Query {
+                steps: vec![Step {
+                    descendants: false,
+                    selector: Selector::Key("it's".to_string()),
+                }],
+            }
+        );
+
+        // Malformed input is reported once a step reaches it
+        assert!(query(&[0x82, 0x01], "$[*]").is_err());
+        assert!(query(&[0x82, 0x01], "$").is_err());
+    }
+}
@@ -0,0 +1,252 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+// Internal helpers for walking encoded CBOR without building values
+// Not part of the public API
+
+use crate::{Error, Result, constants::*};
+
+/// Cursor over an encoded CBOR buffer
+pub(crate) struct SliceReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SliceReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        SliceReader { data, pos: 0 }
+    }
+
+    /// Number of bytes consumed so far
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Number of bytes left to read
+    pub(crate) fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    pub(crate) fn peek_u8(&self) -> Result<u8> {
+        self.data.get(self.pos).copied().ok_or(Error::Eof)
+    }
+
+    pub(crate) fn read_u8(&mut self) -> Result<u8> {
+        let byte = self.peek_u8()?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    pub(crate) fn read_slice(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.remaining() {
+            return Err(Error::Eof);
+        }
+        let slice = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    /// Read an initial byte and its argument
+    ///
+    /// Returns `(major, info, argument)`. The argument is `None` for the
+    /// indefinite-length marker (additional info 31); interpreting it is left
+    /// to the caller since its meaning depends on the major type.
+    pub(crate) fn read_header(&mut self) -> Result<(u8, u8, Option<u64>)> {
+        let initial = self.read_u8()?;
+        let info = initial & 0x1f;
+        let arg_bytes = self.read_slice(argument_len(info)?)?;
+        Ok((initial >> 5, info, header_argument(info, arg_bytes)))
+    }
+}
+
+/// Convert a length read from the input to `usize`
+///
+/// Lengths are up to 64 bits on the wire; on 32-bit and wasm32 targets a
+/// plain cast would silently truncate them.
+#[inline]
+pub(crate) fn u64_to_usize(len: u64) -> Result<usize> {
+    usize::try_from(len).map_err(|_| Error::LengthOverflow(len))
+}
+
+/// Number of argument bytes following an initial byte with this additional info
+pub(crate) fn argument_len(info: u8) -> Result<usize> {
+    match info {
+        0..=23 | INDEFINITE => Ok(0),
+        24 => Ok(1),
+        25 => Ok(2),
+        26 => Ok(4),
+        27 => Ok(8),
+        _ => Err(Error::Syntax(format!(
+            "Reserved additional info {} in CBOR header",
+            info
+        ))),
+    }
+}
+
+/// Interpret the argument bytes read after an initial byte
+///
+/// `bytes` must hold [`argument_len`] bytes. Returns `None` for the
+/// indefinite-length marker.
+pub(crate) fn header_argument(info: u8, bytes: &[u8]) -> Option<u64> {
+    match info {
+        0..=23 => Some(info as u64),
+        INDEFINITE => None,
+        _ => Some(bytes.iter().fold(0, |acc, &b| (acc << 8) | b as u64)),
+    }
+}
+
+/// Reject nesting deeper than [`DEFAULT_MAX_DEPTH`]
+pub(crate) fn check_depth(depth: usize) -> Result<()> {
+    if depth >= DEFAULT_MAX_DEPTH {
+        return Err(Error::Syntax(format!(
+            "CBOR nesting depth {} exceeds maximum {}",
+            depth, DEFAULT_MAX_DEPTH
+        )));
+    }
+    Ok(())
+}
+
+/// Read the chunks of an indefinite-length string up to and including the break
+pub(crate) fn skip_chunks(reader: &mut SliceReader<'_>, major: u8) -> Result<()> {
+    while reader.peek_u8()? != BREAK {
+        match reader.read_header()? {
+            (chunk_major, _, Some(len)) if chunk_major == major => {
+                reader.read_slice(u64_to_usize(len)?)?;
+            }
+            _ => {
+                return Err(Error::Syntax(
+                    "Indefinite string chunks must be definite strings of the same type"
+                        .to_string(),
+                ));
+            }
+        }
+    }
+    reader.read_u8()?;
+    Ok(())
+}
+
+/// Find the end of the item starting at `start`
+///
+/// Headers are read and string contents skipped; nested items are counted
+/// rather than recursed into. The chunks of an indefinite-length string
+/// must be definite-length strings of the same type.
+#[cfg(any(feature = "lazy", feature = "view"))]
+pub(crate) fn item_end(bytes: &[u8], start: usize) -> Result<usize> {
+    let mut reader = SliceReader::new(&bytes[start..]);
+    // Items left in each open container; None until a break
+    let mut stack: Vec<Option<u64>> = Vec::new();
+    loop {
+        let (major, info, arg) = reader.read_header()?;
+        match (major, arg) {
+            (MAJOR_BYTES | MAJOR_TEXT, Some(len)) => {
+                reader.read_slice(u64_to_usize(len)?)?;
+            }
+            (MAJOR_BYTES | MAJOR_TEXT, None) => skip_chunks(&mut reader, major)?,
+            (MAJOR_ARRAY, Some(len)) => stack.push(Some(len)),
+            (MAJOR_MAP, Some(len)) => {
+                stack.push(Some(len.checked_mul(2).ok_or(Error::LengthOverflow(len))?))
+            }
+            (MAJOR_TAG, Some(_)) => stack.push(Some(1)),
+            (MAJOR_ARRAY | MAJOR_MAP, None) => stack.push(None),
+            _ => check_scalar(major, info, arg)?,
+        }
+
+        // Close finished containers, then account for the next item
+        loop {
+            match stack.last_mut() {
+                None => return Ok(start + reader.position()),
+                Some(Some(0)) => {
+                    stack.pop();
+                }
+                Some(Some(n)) => {
+                    *n -= 1;
+                    break;
+                }
+                Some(None) if reader.peek_u8()? == BREAK => {
+                    reader.read_u8()?;
+                    stack.pop();
+                }
+                Some(None) => break,
+            }
+        }
+    }
+}
+
+/// Check the header of a scalar item, which is copied unchanged
+pub(crate) fn check_scalar(major: u8, info: u8, arg: Option<u64>) -> Result<()> {
+    match (major, info, arg) {
+        (MAJOR_UNSIGNED | MAJOR_NEGATIVE, _, None) => {
+            Err(Error::Syntax("Integer cannot be indefinite".to_string()))
+        }
+        (MAJOR_TAG, _, None) => Err(Error::Syntax("Tag cannot be indefinite".to_string())),
+        (MAJOR_SIMPLE, INDEFINITE, _) => Err(Error::Syntax("Unexpected break".to_string())),
+        _ => Ok(()),
+    }
+}
+
+/// Write a header using the shortest encoding of `value`
+pub(crate) fn write_header(out: &mut Vec<u8>, major: u8, value: u64) {
+    if value < 24 {
+        out.push((major << 5) | value as u8);
+    } else if value < 256 {
+        out.extend_from_slice(&[(major << 5) | 24, value as u8]);
+    } else if value < 65536 {
+        out.push((major << 5) | 25);
+        out.extend_from_slice(&(value as u16).to_be_bytes());
+    } else if value < 4294967296 {
+        out.push((major << 5) | 26);
+        out.extend_from_slice(&(value as u32).to_be_bytes());
+    } else {
+        out.push((major << 5) | 27);
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+/// Additional info of the shortest float width that preserves `v`
+///
+/// NaNs are always written as half precision.
+pub(crate) fn preferred_float_info(v: f64) -> u8 {
+    if v.is_nan() || half::f16::from_f64(v).to_f64() == v {
+        FLOAT16
+    } else if (v as f32) as f64 == v {
+        FLOAT32
+    } else {
+        FLOAT64
+    }
+}
+
+/// Write a float using the shortest width that preserves its value
+///
+/// NaNs are written as the canonical quiet NaN `0xf97e00`.
+pub(crate) fn write_preferred_float(out: &mut Vec<u8>, v: f64) {
+    let info = preferred_float_info(v);
+    out.push((MAJOR_SIMPLE << 5) | info);
+    match info {
+        _ if v.is_nan() => out.extend_from_slice(&CANONICAL_NAN.to_be_bytes()),
+        FLOAT16 => out.extend_from_slice(&half::f16::from_f64(v).to_be_bytes()),
+        FLOAT32 => out.extend_from_slice(&(v as f32).to_be_bytes()),
+        _ => out.extend_from_slice(&v.to_be_bytes()),
+    }
+}
+
+/// Bits of the half-precision quiet NaN used by deterministic encoding
+pub(crate) const CANONICAL_NAN: u16 = 0x7e00;
+
+/// Decode the value of a float header argument
+pub(crate) fn float_from_arg(info: u8, arg: u64) -> f64 {
+    match info {
+        FLOAT16 => half::f16::from_bits(arg as u16).to_f64(),
+        FLOAT32 => f32::from_bits(arg as u32) as f64,
+        _ => f64::from_bits(arg),
+    }
+}
@@ -0,0 +1,1097 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! CDDL generation from Rust types
+//!
+//! Enabled with the `cddl` feature. [`to_cddl`] serializes a sample value
+//! through a tracing serializer that makes the same choices as the
+//! [`Encoder`](crate::Encoder), and writes down the shape of the output as a
+//! CDDL schema: one rule per struct and enum, with map keys as encoded,
+//! optional fields marked with `?`, tags as `#6.n(...)` and float widths as
+//! written. Because the schema comes from the serializer calls themselves,
+//! published wire-format documentation can't drift from the implementation.
+//!
+//! A sample only shows the enum variants and fields it contains. Use
+//! [`Reflection`] to combine several samples: variants and choices seen in
+//! any of them are included, and fields missing from some are optional.
+//! Fields left out with `skip_serializing_if` are always optional.
+//!
+//! The output parses with [`cddl::Schema`](crate::cddl::Schema), so it can also
+//! check data from other implementations.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::reflect::to_cddl;
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct HashedUri {
+//!     url: String,
+//!     #[serde(skip_serializing_if = "Option::is_none")]
+//!     alg: Option<String>,
+//!     #[serde(with = "serde_bytes")]
+//!     hash: Vec<u8>,
+//! }
+//!
+//! let sample = HashedUri {
+//!     url: "self#jumbf=c2pa".to_string(),
+//!     alg: None,
+//!     hash: vec![0; 32],
+//! };
+//! assert_eq!(
+//!     to_cddl(&sample).unwrap(),
+//!     "HashedUri = {\n    \"url\": tstr,\n    ? \"alg\": any,\n    \"hash\": bstr,\n}\n"
+//! );
+//! ```
+
+use std::fmt::Write as _;
+
+use serde::{Serialize, Serializer, ser};
+
+use crate::{EncoderOptions, Error, Result, VariantKeys, constants::*, keys::integer_key};
+
+/// Generate a CDDL schema for `sample` as [`to_vec`](crate::to_vec) encodes it
+pub fn to_cddl<T: Serialize + ?Sized>(sample: &T) -> Result<String> {
+    Ok(Reflection::new().trace(sample)?.to_cddl())
+}
+
+/// CDDL generation from one or more sample values
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::reflect::Reflection;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// enum Action {
+///     Created,
+///     Opened(String),
+/// }
+///
+/// let cddl = Reflection::new()
+///     .trace(&Action::Created)
+///     .unwrap()
+///     .trace(&Action::Opened("a.jpg".to_string()))
+///     .unwrap()
+///     .to_cddl();
+/// assert_eq!(cddl, "Action = \"Created\" / { \"Opened\": tstr }\n");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Reflection {
+    options: EncoderOptions,
+    root: Shape,
+    rules: Vec<(String, Rule)>,
+}
+
+impl Reflection {
+    /// Create a reflection for the default encoder settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a reflection for an [`Encoder`](crate::Encoder) configured with `options`
+    ///
+    /// `variant_keys`, `omit_none_fields` and `legacy_newtypes` change the
+    /// generated schema.
+    pub fn with_options(options: EncoderOptions) -> Self {
+        Reflection {
+            options,
+            ..Self::default()
+        }
+    }
+
+    /// Add the shape of `sample` to the schema
+    pub fn trace<T: Serialize + ?Sized>(&mut self, sample: &T) -> Result<&mut Self> {
+        let shape = sample.serialize(Tracer {
+            rules: &mut self.rules,
+            options: self.options,
+        })?;
+        self.root = merge(std::mem::take(&mut self.root), shape);
+        Ok(self)
+    }
+
+    /// Render the schema, with the rule for the traced type first
+    pub fn to_cddl(&self) -> String {
+        let mut out = String::new();
+        let root_is_first = matches!(
+            (&self.root, self.rules.first()),
+            (Shape::Named(root), Some((first, _))) if root == first
+        );
+        if !root_is_first {
+            let _ = writeln!(out, "root = {}", describe(&self.root));
+        }
+        for (name, rule) in &self.rules {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            match rule {
+                Rule::Struct(rule) if rule.fields.is_empty() => {
+                    let _ = writeln!(out, "{} = {{}}", name);
+                }
+                Rule::Struct(rule) => {
+                    let _ = writeln!(out, "{} = {{", name);
+                    for field in &rule.fields {
+                        let optional = field.seen < rule.samples || field.omittable;
+                        let _ = writeln!(
+                            out,
+                            "    {}{}: {},",
+                            if optional { "? " } else { "" },
+                            match integer_key(&field.key) {
+                                Some(key) => key.to_string(),
+                                None => quote(&field.key),
+                            },
+                            describe(&field.shape)
+                        );
+                    }
+                    out.push_str("}\n");
+                }
+                Rule::Enum(variants) => {
+                    let variants: Vec<String> = variants
+                        .iter()
+                        .map(|variant| match &variant.content {
+                            Some(content) => {
+                                format!("{{ {}: {} }}", variant.key, describe(content))
+                            }
+                            None => variant.key.clone(),
+                        })
+                        .collect();
+                    let _ = writeln!(out, "{} = {}", name, variants.join(" / "));
+                }
+            }
+        }
+        out
+    }
+}
+
+// Shapes and rules
+
+/// What a traced value encodes as
+#[derive(Debug, Clone, Default, PartialEq)]
+enum Shape {
+    /// Nothing traced yet, such as the elements of an empty sequence
+    #[default]
+    Any,
+    Bool,
+    Uint,
+    Int,
+    Float32,
+    Float64,
+    /// A float in whatever width holds it exactly (`compact_floats`)
+    Float,
+    Text,
+    Bytes,
+    /// A unit or an explicit null
+    Nil,
+    /// `Option::None`, which `omit_none_fields` leaves out of structs
+    OptionNone,
+    Undefined,
+    Simple,
+    Array(Box<Shape>),
+    Tuple(Vec<Shape>),
+    Map(Box<Shape>, Box<Shape>),
+    Tag(u64, Box<Shape>),
+    Named(String),
+    Choice(Vec<Shape>),
+}
+
+#[derive(Debug, Clone)]
+enum Rule {
+    Struct(StructRule),
+    Enum(Vec<Variant>),
+}
+
+#[derive(Debug, Clone, Default)]
+struct StructRule {
+    samples: usize,
+    fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone)]
+struct Field {
+    key: String,
+    shape: Shape,
+    /// Number of samples the field was written in
+    seen: usize,
+    /// Whether the field was ever left out by the type itself
+    omittable: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Variant {
+    /// The variant key as a CDDL literal
+    key: String,
+    /// `None` for unit variants
+    content: Option<Shape>,
+}
+
+/// Combine two shapes, as a choice if they can't be unified
+fn merge(a: Shape, b: Shape) -> Shape {
+    let mut alternatives = Vec::new();
+    add_alternative(&mut alternatives, a);
+    add_alternative(&mut alternatives, b);
+    match alternatives.len() {
+        0 => Shape::Any,
+        1 => alternatives.remove(0),
+        _ => Shape::Choice(alternatives),
+    }
+}
+
+fn add_alternative(alternatives: &mut Vec<Shape>, shape: Shape) {
+    match shape {
+        Shape::Any => {}
+        Shape::Choice(more) => {
+            for shape in more {
+                add_alternative(alternatives, shape);
+            }
+        }
+        shape => {
+            for existing in alternatives.iter_mut() {
+                if let Some(unified) = unify(existing, &shape) {
+                    *existing = unified;
+                    return;
+                }
+            }
+            alternatives.push(shape);
+        }
+    }
+}
+
+/// The single shape covering both `a` and `b`, if there is one
+fn unify(a: &Shape, b: &Shape) -> Option<Shape> {
+    Some(match (a, b) {
+        _ if a == b => a.clone(),
+        (Shape::Uint, Shape::Int) | (Shape::Int, Shape::Uint) => Shape::Int,
+        (Shape::Nil, Shape::OptionNone) | (Shape::OptionNone, Shape::Nil) => Shape::OptionNone,
+        (Shape::Array(a), Shape::Array(b)) => {
+            Shape::Array(Box::new(merge((**a).clone(), (**b).clone())))
+        }
+        (Shape::Tuple(a), Shape::Tuple(b)) if a.len() == b.len() => Shape::Tuple(
+            a.iter()
+                .zip(b)
+                .map(|(a, b)| merge(a.clone(), b.clone()))
+                .collect(),
+        ),
+        (Shape::Map(ka, va), Shape::Map(kb, vb)) => Shape::Map(
+            Box::new(merge((**ka).clone(), (**kb).clone())),
+            Box::new(merge((**va).clone(), (**vb).clone())),
+        ),
+        (Shape::Tag(a_tag, a), Shape::Tag(b_tag, b)) if a_tag == b_tag => {
+            Shape::Tag(*a_tag, Box::new(merge((**a).clone(), (**b).clone())))
+        }
+        _ => return None,
+    })
+}
+
+/// Split `Option::None` off a field's shape, for `omit_none_fields`
+fn without_none(shape: Shape) -> (Option<Shape>, bool) {
+    match shape {
+        Shape::OptionNone => (None, true),
+        Shape::Choice(alternatives) if alternatives.contains(&Shape::OptionNone) => {
+            let rest = alternatives
+                .into_iter()
+                .filter(|shape| *shape != Shape::OptionNone)
+                .fold(Shape::Any, merge);
+            (Some(rest), true)
+        }
+        shape => (Some(shape), false),
+    }
+}
+
+/// Mark a `None` as written even when `omit_none_fields` is set
+fn explicit_null(shape: Shape) -> Shape {
+    match shape {
+        Shape::OptionNone => Shape::Nil,
+        Shape::Choice(alternatives) => {
+            Shape::Choice(alternatives.into_iter().map(explicit_null).collect())
+        }
+        shape => shape,
+    }
+}
+
+fn describe(shape: &Shape) -> String {
+    match shape {
+        Shape::Any => "any".to_string(),
+        Shape::Bool => "bool".to_string(),
+        Shape::Uint => "uint".to_string(),
+        Shape::Int => "int".to_string(),
+        Shape::Float32 => "float32".to_string(),
+        Shape::Float64 => "float64".to_string(),
+        Shape::Float => "float".to_string(),
+        Shape::Text => "tstr".to_string(),
+        Shape::Bytes => "bstr".to_string(),
+        Shape::Nil | Shape::OptionNone => "nil".to_string(),
+        Shape::Undefined => "undefined".to_string(),
+        Shape::Simple => "#7".to_string(),
+        Shape::Array(element) => format!("[* {}]", describe(element)),
+        // Fixed-size arrays such as [u8; 32] serialize as tuples; pairs are
+        // clearer spelled out
+        Shape::Tuple(elements)
+            if elements.len() > 2 && elements.iter().all(|e| *e == elements[0]) =>
+        {
+            format!("[{0}*{0} {1}]", elements.len(), describe(&elements[0]))
+        }
+        Shape::Tuple(elements) => format!(
+            "[{}]",
+            elements.iter().map(describe).collect::<Vec<_>>().join(", ")
+        ),
+        Shape::Map(key, value) => format!("{{ * {} => {} }}", describe(key), describe(value)),
+        Shape::Tag(tag, content) => format!("#6.{}({})", tag, describe(content)),
+        Shape::Named(name) => name.clone(),
+        Shape::Choice(alternatives) => alternatives
+            .iter()
+            .map(describe)
+            .collect::<Vec<_>>()
+            .join(" / "),
+    }
+}
+
+/// A text string as a CDDL literal
+fn quote(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// A serde type name as a CDDL rule name
+fn rule_name(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' | '.' | '@' | '$' => c,
+            _ => '_',
+        })
+        .collect();
+    if !out.starts_with(|c: char| c.is_ascii_alphabetic() || matches!(c, '_' | '@' | '$')) {
+        out.insert(0, '_');
+    }
+    if out.ends_with(['-', '.']) {
+        out.push('_');
+    }
+    out
+}
+
+/// Find or add the rule `name`, returning its index
+fn declare(rules: &mut Vec<(String, Rule)>, name: &str, empty: Rule) -> Result<usize> {
+    match rules.iter().position(|(n, _)| n == name) {
+        Some(index)
+            if std::mem::discriminant(&rules[index].1) == std::mem::discriminant(&empty) =>
+        {
+            Ok(index)
+        }
+        Some(_) => Err(Error::Message(format!(
+            "{} is traced as both a struct and an enum",
+            name
+        ))),
+        None => {
+            rules.push((name.to_string(), empty));
+            Ok(rules.len() - 1)
+        }
+    }
+}
+
+fn add_variant(rules: &mut [(String, Rule)], index: usize, key: String, content: Option<Shape>) {
+    let Rule::Enum(variants) = &mut rules[index].1 else {
+        return;
+    };
+    match variants.iter_mut().find(|v| v.key == key) {
+        Some(variant) => {
+            variant.content = match (variant.content.take(), content) {
+                (Some(a), Some(b)) => Some(merge(a, b)),
+                (a, b) => a.or(b),
+            }
+        }
+        None => variants.push(Variant { key, content }),
+    }
+}
+
+// Tracing serializer
+
+struct Tracer<'a> {
+    rules: &'a mut Vec<(String, Rule)>,
+    options: EncoderOptions,
+}
+
+impl<'a> Tracer<'a> {
+    fn nested(&mut self) -> Tracer<'_> {
+        Tracer {
+            rules: &mut *self.rules,
+            options: self.options,
+        }
+    }
+
+    /// The variant key as the encoder writes it, as a CDDL literal
+    fn variant_key(&self, variant_index: u32, variant: &str) -> String {
+        match self.options.variant_keys {
+            VariantKeys::Name => quote(variant),
+            VariantKeys::Index => variant_index.to_string(),
+            VariantKeys::NumericName => match variant.parse::<u64>() {
+                Ok(n) if n.to_string() == variant => variant.to_string(),
+                _ => quote(variant),
+            },
+        }
+    }
+
+    fn declare_enum(&mut self, name: &str) -> Result<usize> {
+        declare(self.rules, &rule_name(name), Rule::Enum(Vec::new()))
+    }
+
+    fn trace_struct(
+        self,
+        name: String,
+        variant: Option<(usize, String)>,
+    ) -> Result<TraceStruct<'a>> {
+        let rule = declare(self.rules, &name, Rule::Struct(StructRule::default()))?;
+        Ok(TraceStruct {
+            tracer: self,
+            name,
+            rule,
+            fields: Vec::new(),
+            variant,
+        })
+    }
+}
+
+impl<'a> Serializer for Tracer<'a> {
+    type Error = Error;
+    type Ok = Shape;
+    type SerializeMap = TraceMap<'a>;
+    type SerializeSeq = TraceSeq<'a>;
+    type SerializeStruct = TraceStruct<'a>;
+    type SerializeStructVariant = TraceStruct<'a>;
+    type SerializeTuple = TraceSeq<'a>;
+    type SerializeTupleStruct = TraceSeq<'a>;
+    type SerializeTupleVariant = TraceSeq<'a>;
+
+    fn serialize_bool(self, _v: bool) -> Result<Shape> {
+        Ok(Shape::Bool)
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Shape> {
+        Ok(Shape::Int)
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Shape> {
+        Ok(Shape::Int)
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Shape> {
+        Ok(Shape::Int)
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Shape> {
+        Ok(Shape::Int)
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Shape> {
+        Ok(Shape::Uint)
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Shape> {
+        Ok(Shape::Uint)
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Shape> {
+        Ok(Shape::Uint)
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Shape> {
+        Ok(Shape::Uint)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Shape> {
+        Ok(Shape::Float32)
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Shape> {
+        if cfg!(feature = "compact_floats") {
+            Ok(Shape::Float)
+        } else {
+            Ok(Shape::Float64)
+        }
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Shape> {
+        Ok(Shape::Text)
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<Shape> {
+        Ok(Shape::Text)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Shape> {
+        Ok(Shape::Bytes)
+    }
+
+    fn serialize_none(self) -> Result<Shape> {
+        Ok(Shape::OptionNone)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Shape> {
+        // Whatever the sample holds, the field can also be None
+        Ok(merge(value.serialize(self)?, Shape::OptionNone))
+    }
+
+    fn serialize_unit(self) -> Result<Shape> {
+        Ok(Shape::Nil)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Shape> {
+        Ok(Shape::Nil)
+    }
+
+    fn serialize_unit_variant(
+        mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Shape> {
+        let rule = self.declare_enum(name)?;
+        let key = self.variant_key(variant_index, variant);
+        add_variant(self.rules, rule, key, None);
+        Ok(Shape::Named(rule_name(name)))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Shape> {
+        let tag = match name {
+            CBOR_TAG_MARKER => crate::tags::current_cbor_tag(),
+            _ => name
+                .strip_prefix("__cbor_tag_")
+                .and_then(|n| n.strip_suffix("__"))
+                .and_then(|n| n.parse().ok()),
+        };
+        if let Some(tag) = tag {
+            return Ok(Shape::Tag(tag, Box::new(value.serialize(self)?)));
+        }
+        match name {
+            CHUNKED_BYTES_MARKER => Ok(Shape::Bytes),
+            CHUNKED_TEXT_MARKER => Ok(Shape::Text),
+            UNDEFINED_MARKER => Ok(Shape::Undefined),
+            SIMPLE_MARKER => Ok(Shape::Simple),
+            NULLABLE_MARKER => Ok(explicit_null(value.serialize(self)?)),
+            _ if self.options.legacy_newtypes => Ok(Shape::Tuple(vec![value.serialize(self)?])),
+            _ => value.serialize(self),
+        }
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Shape> {
+        let rule = self.declare_enum(name)?;
+        let key = self.variant_key(variant_index, variant);
+        let content = value.serialize(self.nested())?;
+        add_variant(self.rules, rule, key, Some(content));
+        Ok(Shape::Named(rule_name(name)))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<TraceSeq<'a>> {
+        Ok(TraceSeq {
+            tracer: self,
+            elements: Vec::new(),
+            variant: None,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<TraceSeq<'a>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<TraceSeq<'a>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<TraceSeq<'a>> {
+        let rule = self.declare_enum(name)?;
+        let key = self.variant_key(variant_index, variant);
+        Ok(TraceSeq {
+            tracer: self,
+            elements: Vec::new(),
+            variant: Some((rule, key)),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<TraceMap<'a>> {
+        Ok(TraceMap {
+            tracer: self,
+            key: Shape::Any,
+            value: Shape::Any,
+        })
+    }
+
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<TraceStruct<'a>> {
+        self.trace_struct(rule_name(name), None)
+    }
+
+    fn serialize_struct_variant(
+        mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<TraceStruct<'a>> {
+        let rule = self.declare_enum(name)?;
+        let key = self.variant_key(variant_index, variant);
+        // The variant's fields get a rule of their own, named after both
+        self.trace_struct(
+            rule_name(&format!("{}-{}", name, variant)),
+            Some((rule, key)),
+        )
+    }
+}
+
+struct TraceSeq<'a> {
+    tracer: Tracer<'a>,
+    elements: Vec<Shape>,
+    /// Enum rule and variant key of a tuple variant
+    variant: Option<(usize, String)>,
+}
+
+impl TraceSeq<'_> {
+    fn element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let shape = value.serialize(self.tracer.nested())?;
+        self.elements.push(shape);
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for TraceSeq<'_> {
+    type Error = Error;
+    type Ok = Shape;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Shape> {
+        let element = self.elements.into_iter().fold(Shape::Any, merge);
+        Ok(Shape::Array(Box::new(element)))
+    }
+}
+
+impl ser::SerializeTuple for TraceSeq<'_> {
+    type Error = Error;
+    type Ok = Shape;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Shape> {
+        Ok(Shape::Tuple(self.elements))
+    }
+}
+
+impl ser::SerializeTupleStruct for TraceSeq<'_> {
+    type Error = Error;
+    type Ok = Shape;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Shape> {
+        Ok(Shape::Tuple(self.elements))
+    }
+}
+
+impl ser::SerializeTupleVariant for TraceSeq<'_> {
+    type Error = Error;
+    type Ok = Shape;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Shape> {
+        let Some((rule, key)) = self.variant else {
+            return Ok(Shape::Tuple(self.elements));
+        };
+        add_variant(
+            self.tracer.rules,
+            rule,
+            key,
+            Some(Shape::Tuple(self.elements)),
+        );
+        Ok(Shape::Named(self.tracer.rules[rule].0.clone()))
+    }
+}
+
+struct TraceMap<'a> {
+    tracer: Tracer<'a>,
+    key: Shape,
+    value: Shape,
+}
+
+impl ser::SerializeMap for TraceMap<'_> {
+    type Error = Error;
+    type Ok = Shape;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        let shape = key.serialize(self.tracer.nested())?;
+        self.key = merge(std::mem::take(&mut self.key), shape);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let shape = value.serialize(self.tracer.nested())?;
+        self.value = merge(std::mem::take(&mut self.value), shape);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Shape> {
+        Ok(Shape::Map(Box::new(self.key), Box::new(self.value)))
+    }
+}
+
+struct TraceStruct<'a> {
+    tracer: Tracer<'a>,
+    name: String,
+    rule: usize,
+    /// Key, shape if written, and whether the type may leave it out
+    fields: Vec<(&'static str, Option<Shape>, bool)>,
+    /// Enum rule and variant key of a struct variant
+    variant: Option<(usize, String)>,
+}
+
+impl ser::SerializeStruct for TraceStruct<'_> {
+    type Error = Error;
+    type Ok = Shape;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        let shape = value.serialize(self.tracer.nested())?;
+        let (shape, omittable) = if self.tracer.options.omit_none_fields {
+            without_none(shape)
+        } else {
+            (Some(shape), false)
+        };
+        self.fields.push((key, shape, omittable));
+        Ok(())
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<()> {
+        self.fields.push((key, None, true));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Shape> {
+        let Rule::Struct(rule) = &mut self.tracer.rules[self.rule].1 else {
+            return Err(Error::Message(format!("{} is not a struct", self.name)));
+        };
+        rule.samples += 1;
+        for (key, shape, omittable) in self.fields {
+            let index = match rule.fields.iter().position(|f| f.key == key) {
+                Some(index) => index,
+                None => {
+                    rule.fields.push(Field {
+                        key: key.to_string(),
+                        shape: Shape::Any,
+                        seen: 0,
+                        omittable: false,
+                    });
+                    rule.fields.len() - 1
+                }
+            };
+            let field = &mut rule.fields[index];
+            field.omittable |= omittable;
+            if let Some(shape) = shape {
+                field.seen += 1;
+                field.shape = merge(std::mem::take(&mut field.shape), shape);
+            }
+        }
+        match self.variant {
+            Some((rule, key)) => {
+                add_variant(self.tracer.rules, rule, key, Some(Shape::Named(self.name)));
+                Ok(Shape::Named(self.tracer.rules[rule].0.clone()))
+            }
+            None => Ok(Shape::Named(self.name)),
+        }
+    }
+}
+
+impl ser::SerializeStructVariant for TraceStruct<'_> {
+    type Error = Error;
+    type Ok = Shape;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<()> {
+        ser::SerializeStruct::skip_field(self, key)
+    }
+
+    fn end(self) -> Result<Shape> {
+        ser::SerializeStruct::end(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::{cddl::Schema, tags::Tagged};
+
+    #[derive(Serialize)]
+    struct Manifest {
+        claim_generator: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        ingredients: Vec<Ingredient>,
+        actions: Vec<Action>,
+        instance: Tagged<String>,
+        labels: BTreeMap<String, u32>,
+        digest: [u8; 4],
+    }
+
+    #[derive(Serialize)]
+    struct Ingredient {
+        relationship: Relationship,
+        thumbnail: Option<serde_bytes::ByteBuf>,
+        offset: i64,
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    enum Relationship {
+        ParentOf,
+        ComponentOf,
+    }
+
+    #[derive(Serialize)]
+    enum Action {
+        Created { agent: String },
+        Resized(u32, u32),
+    }
+
+    fn manifest() -> Manifest {
+        Manifest {
+            claim_generator: "test/1.0".to_string(),
+            title: None,
+            ingredients: vec![
+                Ingredient {
+                    relationship: Relationship::ParentOf,
+                    thumbnail: None,
+                    offset: -1,
+                },
+                Ingredient {
+                    relationship: Relationship::ComponentOf,
+                    thumbnail: Some(serde_bytes::ByteBuf::from(vec![1, 2])),
+                    offset: 0,
+                },
+            ],
+            actions: vec![
+                Action::Created {
+                    agent: "a".to_string(),
+                },
+                Action::Resized(10, 20),
+            ],
+            instance: Tagged::new(Some(32), "https://a.b".to_string()),
+            labels: BTreeMap::from([("c2pa".to_string(), 1)]),
+            digest: [0; 4],
+        }
+    }
+
+    #[test]
+    fn test_reflect_generates_cddl() {
+        let cddl = to_cddl(&manifest()).unwrap();
+        assert_eq!(
+            cddl,
+            r#"Manifest = {
+    "claim_generator": tstr,
+    ? "title": any,
+    "ingredients": [* Ingredient],
+    "actions": [* Action],
+    "instance": #6.32(tstr),
+    "labels": { * tstr => uint },
+    "digest": [4*4 uint],
+}
+
+Ingredient = {
+    "relationship": Relationship,
+    "thumbnail": nil / bstr,
+    "offset": int,
+}
+
+Relationship = "parentOf" / "componentOf"
+
+Action = { "Created": Action-Created } / { "Resized": [uint, uint] }
+
+Action-Created = {
+    "agent": tstr,
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_reflect_output_validates_samples() {
+        let sample = manifest();
+        let schema = Schema::parse(&to_cddl(&sample).unwrap()).unwrap();
+        assert_eq!(schema.validate(&crate::to_vec(&sample).unwrap()), Ok(()));
+
+        // Other implementations writing a different shape are caught
+        #[derive(Serialize)]
+        struct Manifest {
+            claim_generator: u32,
+        }
+        assert!(
+            schema
+                .validate(&crate::to_vec(&Manifest { claim_generator: 1 }).unwrap())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_reflect_merges_samples() {
+        #[derive(Serialize)]
+        struct Claim {
+            #[serde(skip_serializing_if = "Vec::is_empty")]
+            redactions: Vec<String>,
+            alg: Option<String>,
+            version: f64,
+        }
+
+        let cddl = Reflection::new()
+            .trace(&Claim {
+                redactions: Vec::new(),
+                alg: None,
+                version: 1.0,
+            })
+            .unwrap()
+            .trace(&Claim {
+                redactions: vec!["a".to_string()],
+                alg: Some("sha256".to_string()),
+                version: 2.0,
+            })
+            .unwrap()
+            .to_cddl();
+        let float = if cfg!(feature = "compact_floats") {
+            "float"
+        } else {
+            "float64"
+        };
+        assert_eq!(
+            cddl,
+            format!(
+                "Claim = {{\n    ? \"redactions\": [* tstr],\n    \"alg\": nil / tstr,\n    \"version\": {},\n}}\n",
+                float
+            )
+        );
+    }
+
+    #[test]
+    fn test_reflect_follows_encoder_options() {
+        #[derive(Serialize)]
+        struct Thumbnail {
+            format: Option<String>,
+            kind: Kind,
+            label: Label,
+        }
+
+        #[derive(Serialize)]
+        enum Kind {
+            #[allow(dead_code)]
+            Jpeg,
+            Png,
+        }
+
+        #[derive(Serialize)]
+        struct Label(String);
+
+        let sample = Thumbnail {
+            format: Some("image/jpeg".to_string()),
+            kind: Kind::Png,
+            label: Label("t".to_string()),
+        };
+        let options = EncoderOptions {
+            omit_none_fields: true,
+            legacy_newtypes: true,
+            variant_keys: VariantKeys::Index,
+            ..Default::default()
+        };
+        let cddl = Reflection::with_options(options)
+            .trace(&sample)
+            .unwrap()
+            .to_cddl();
+        assert_eq!(
+            cddl,
+            "Thumbnail = {\n    ? \"format\": tstr,\n    \"kind\": Kind,\n    \"label\": [tstr],\n}\n\nKind = 1\n"
+        );
+
+        let mut encoder = crate::Encoder::new(Vec::new()).with_options(options);
+        encoder.encode(&sample).unwrap();
+        let schema = Schema::parse(&cddl).unwrap();
+        assert_eq!(schema.validate(&encoder.into_inner()), Ok(()));
+    }
+
+    #[test]
+    fn test_reflect_non_struct_root() {
+        assert_eq!(to_cddl(&vec![1u8, 2]).unwrap(), "root = [* uint]\n");
+        assert_eq!(to_cddl(&Vec::<u8>::new()).unwrap(), "root = [* any]\n");
+        assert_eq!(
+            to_cddl(&(Some(-1i32), "a", ())).unwrap(),
+            "root = [int / nil, tstr, nil]\n"
+        );
+        assert_eq!(rule_name("my type<T>"), "my_type_T_");
+        assert_eq!(quote("a\"b\n"), "\"a\\\"b\\u000a\"");
+    }
+
+    #[test]
+    fn test_reflect_integer_keys() {
+        #[derive(Serialize)]
+        struct Header {
+            #[serde(rename = "__cbor_key_1__")]
+            alg: i64,
+            #[serde(rename = "__cbor_key_-1__")]
+            crv: u8,
+        }
+
+        let sample = Header { alg: -7, crv: 1 };
+        let cddl = to_cddl(&sample).unwrap();
+        assert_eq!(cddl, "Header = {\n    1: int,\n    -1: uint,\n}\n");
+        let schema = Schema::parse(&cddl).unwrap();
+        assert_eq!(schema.validate(&crate::to_vec(&sample).unwrap()), Ok(()));
+    }
+}
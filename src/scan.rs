@@ -0,0 +1,286 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Header-level scanning of encoded CBOR
+//!
+//! [`scan`] walks the structure of an encoded item and reports each header
+//! it finds, without building values or copying string contents. Nesting is
+//! tracked with an explicit stack, so deeply nested input cannot overflow the
+//! call stack.
+
+use crate::{Error, Result, constants::*, raw::SliceReader};
+
+/// A header found by [`scan`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScannedItem {
+    /// Byte offset of the item's initial byte
+    pub offset: usize,
+    /// Major type (0-7)
+    pub major: u8,
+    /// Header argument: the length of strings, arrays and maps, the value of
+    /// integers, the tag number of tags, and the simple value or float bits
+    /// of major type 7. `None` for indefinite-length items.
+    pub len: Option<u64>,
+    /// Nesting depth; the top-level item is at depth 0
+    pub depth: usize,
+}
+
+#[derive(Debug)]
+enum FrameKind {
+    Array,
+    Map,
+    Tag,
+    // Chunks of an indefinite-length string of this major type
+    Chunks(u8),
+}
+
+#[derive(Debug)]
+struct Frame {
+    kind: FrameKind,
+    // Items still expected, or None until a break for indefinite length
+    remaining: Option<u64>,
+    // Items seen so far, used to reject a break between a map key and value
+    seen: u64,
+}
+
+/// Iterator returned by [`scan`]
+pub struct Scanner<'a> {
+    reader: SliceReader<'a>,
+    stack: Vec<Frame>,
+    started: bool,
+    done: bool,
+}
+
+/// Walk the headers of a single encoded CBOR item
+///
+/// Yields one [`ScannedItem`] per data item in encoding order, including the
+/// chunks of indefinite-length strings; break markers are not reported.
+/// String contents are skipped. Malformed input, including trailing bytes
+/// after the item, produces an error after which iteration stops.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::scan;
+///
+/// // {"a": [1, 2]}
+/// let bytes = [0xa1, 0x61, b'a', 0x82, 0x01, 0x02];
+/// let items: Vec<_> = scan(&bytes).collect::<Result<_, _>>().unwrap();
+/// let summary: Vec<_> = items.iter().map(|i| (i.offset, i.major, i.depth)).collect();
+/// assert_eq!(summary, [(0, 5, 0), (1, 3, 1), (3, 4, 1), (4, 0, 2), (5, 0, 2)]);
+/// ```
+pub fn scan(bytes: &[u8]) -> Scanner<'_> {
+    Scanner {
+        reader: SliceReader::new(bytes),
+        stack: Vec::new(),
+        started: false,
+        done: false,
+    }
+}
+
+impl Scanner<'_> {
+    fn next_item(&mut self) -> Result<Option<ScannedItem>> {
+        loop {
+            while self.stack.last().is_some_and(|f| f.remaining == Some(0)) {
+                self.stack.pop();
+            }
+            if self.stack.is_empty() && self.started {
+                if self.reader.remaining() > 0 {
+                    return Err(Error::TrailingData);
+                }
+                return Ok(None);
+            }
+            if !self.started && self.reader.remaining() == 0 {
+                return Err(Error::Syntax("empty input".to_string()));
+            }
+            self.started = true;
+
+            if let Some(top) = self.stack.last()
+                && top.remaining.is_none()
+                && self.reader.peek_u8()? == BREAK
+            {
+                if matches!(top.kind, FrameKind::Map) && top.seen % 2 == 1 {
+                    return Err(Error::Syntax("Map key without a value".to_string()));
+                }
+                self.reader.read_u8()?;
+                self.stack.pop();
+                continue;
+            }
+            return self.read_item().map(Some);
+        }
+    }
+
+    fn read_item(&mut self) -> Result<ScannedItem> {
+        let offset = self.reader.position();
+        let depth = self.stack.len();
+        let (major, info, arg) = self.reader.read_header()?;
+
+        if let Some(top) = self.stack.last_mut() {
+            if let FrameKind::Chunks(string_major) = top.kind {
+                if major != string_major {
+                    return Err(Error::Syntax(
+                        "Indefinite string chunks must match the string type".to_string(),
+                    ));
+                }
+                if arg.is_none() {
+                    return Err(Error::Syntax(
+                        "Indefinite string chunks cannot be indefinite".to_string(),
+                    ));
+                }
+            }
+            top.seen += 1;
+            if let Some(remaining) = top.remaining.as_mut() {
+                *remaining -= 1;
+            }
+        }
+
+        let frame = |kind, remaining| Frame {
+            kind,
+            remaining,
+            seen: 0,
+        };
+        match (major, arg) {
+            (MAJOR_UNSIGNED | MAJOR_NEGATIVE | MAJOR_TAG, None) => {
+                return Err(Error::Syntax("Unexpected indefinite length".to_string()));
+            }
+            (MAJOR_BYTES | MAJOR_TEXT, Some(len)) => {
+                let len = usize::try_from(len).map_err(|_| Error::Eof)?;
+                self.reader.read_slice(len)?;
+            }
+            (MAJOR_BYTES | MAJOR_TEXT, None) => {
+                self.stack.push(frame(FrameKind::Chunks(major), None))
+            }
+            (MAJOR_ARRAY, len) => self.stack.push(frame(FrameKind::Array, len)),
+            (MAJOR_MAP, len) => self
+                .stack
+                .push(frame(FrameKind::Map, len.map(|n| n.saturating_mul(2)))),
+            (MAJOR_TAG, Some(_)) => self.stack.push(frame(FrameKind::Tag, Some(1))),
+            (MAJOR_SIMPLE, _) => match (info, arg) {
+                (INDEFINITE, _) => return Err(Error::Syntax("Unexpected break".to_string())),
+                (SIMPLE_VALUE, Some(val)) if val < 32 => {
+                    return Err(Error::Syntax("Invalid simple value".to_string()));
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+
+        Ok(ScannedItem {
+            offset,
+            major,
+            len: arg,
+            depth,
+        })
+    }
+}
+
+impl Iterator for Scanner<'_> {
+    type Item = Result<ScannedItem>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.next_item() {
+            Ok(Some(item)) => Some(Ok(item)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl std::iter::FusedIterator for Scanner<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Offset, major type, length and depth of each item
+    type Row = (usize, u8, Option<u64>, usize);
+
+    fn scan_all(bytes: &[u8]) -> Result<Vec<Row>> {
+        scan(bytes)
+            .map(|item| item.map(|i| (i.offset, i.major, i.len, i.depth)))
+            .collect()
+    }
+
+    #[test]
+    fn test_scan_indefinite_items() {
+        // [_ "a", (_ h'01', h'') ], tagged 24
+        let bytes = [
+            0xd8, 0x18, 0x9f, 0x61, b'a', 0x5f, 0x41, 0x01, 0x40, 0xff, 0xff,
+        ];
+        assert_eq!(
+            scan_all(&bytes).unwrap(),
+            vec![
+                (0, MAJOR_TAG, Some(24), 0),
+                (2, MAJOR_ARRAY, None, 1),
+                (3, MAJOR_TEXT, Some(1), 2),
+                (5, MAJOR_BYTES, None, 2),
+                (6, MAJOR_BYTES, Some(1), 3),
+                (8, MAJOR_BYTES, Some(0), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_simple_and_floats() {
+        let bytes = [0x83, 0xf5, 0xf8, 0x20, 0xf9, 0x3c, 0x00];
+        assert_eq!(
+            scan_all(&bytes).unwrap(),
+            vec![
+                (0, MAJOR_ARRAY, Some(3), 0),
+                (1, MAJOR_SIMPLE, Some(21), 1),
+                (2, MAJOR_SIMPLE, Some(32), 1),
+                (4, MAJOR_SIMPLE, Some(0x3c00), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_errors_stop_iteration() {
+        let cases: [&[u8]; 8] = [
+            &[],
+            &[0x01, 0x01],
+            &[0x82, 0x01],
+            &[0x81, 0xff],
+            &[0xbf, 0x01, 0xff],
+            &[0x5f, 0x61, b'a', 0xff],
+            &[0x62, b'a'],
+            &[0xf8, 0x01],
+        ];
+        for bytes in cases {
+            let results: Vec<_> = scan(bytes).collect();
+            assert!(
+                results.last().is_some_and(|r| r.is_err()),
+                "expected error for {:02x?}",
+                bytes
+            );
+            assert_eq!(results.iter().filter(|r| r.is_err()).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_scan_deep_nesting_without_recursion() {
+        let mut bytes = vec![0x81; 100_000];
+        bytes.push(0x00);
+        let items = scan(&bytes).collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(items.len(), 100_001);
+        assert_eq!(items.last().unwrap().depth, 100_000);
+    }
+}
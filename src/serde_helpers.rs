@@ -0,0 +1,490 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! `#[serde(with = "...")]` modules for common tagged fields
+//!
+//! - [`uri`]: a string as a tag 32 URI
+//! - [`datetime_string`]: a string as a tag 0 RFC 3339 date/time
+//! - [`epoch_seconds`]: an `i64` as tag 1 seconds since the epoch
+//! - [`bytes`]: bytes as a byte string instead of an array of integers
+//! - [`base64url_text`]: a string as tag 33 base64url-encoded text
+//!
+//! Text content is validated in both directions, so an invalid value fails
+//! to serialize rather than producing a malformed item. Untagged input is
+//! accepted when deserializing, so the same types can be read from formats
+//! without tags; input with a different tag is rejected.
+//!
+//! # Example
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Ingredient {
+//!     #[serde(with = "c2pa_cbor::serde_helpers::uri")]
+//!     url: String,
+//!     #[serde(with = "c2pa_cbor::serde_helpers::datetime_string")]
+//!     created: String,
+//!     #[serde(with = "c2pa_cbor::serde_helpers::bytes")]
+//!     hash: Vec<u8>,
+//! }
+//!
+//! let ingredient = Ingredient {
+//!     url: "https://example.com/a.jpg".to_string(),
+//!     created: "2024-01-15T10:30:00Z".to_string(),
+//!     hash: vec![1, 2, 3],
+//! };
+//! let cbor = c2pa_cbor::to_vec(&ingredient).unwrap();
+//! assert_eq!(&cbor[5..7], [0xd8, 0x20]); // tag 32
+//! assert_eq!(c2pa_cbor::from_slice::<Ingredient>(&cbor).unwrap(), ingredient);
+//!
+//! let bad = Ingredient { created: "yesterday".to_string(), ..ingredient };
+//! assert!(c2pa_cbor::to_vec(&bad).is_err());
+//! ```
+
+use std::fmt;
+
+use serde::{
+    Deserialize, Deserializer,
+    de::{self, Visitor},
+};
+
+use crate::tags::Tagged;
+
+/// Check that a decoded tag is either absent or the expected one
+pub(crate) fn check_tag<E: de::Error>(tag: Option<u64>, expected: u64) -> Result<(), E> {
+    match tag {
+        Some(tag) if tag != expected => Err(E::custom(format!(
+            "Expected tag {}, found tag {}",
+            expected, tag
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Read a string with tag `expected` or no tag, checking it with `validate`
+fn deserialize_text<'de, T, D>(
+    deserializer: D,
+    expected: u64,
+    validate: fn(&str) -> Result<(), String>,
+) -> Result<T, D::Error>
+where
+    T: From<String>,
+    D: Deserializer<'de>,
+{
+    let tagged = Tagged::<String>::deserialize(deserializer)?;
+    check_tag(tagged.tag, expected)?;
+    validate(&tagged.value).map_err(de::Error::custom)?;
+    Ok(tagged.value.into())
+}
+
+/// Seconds since the epoch as found in the input
+pub(crate) enum Epoch {
+    Int(i64),
+    Float(f64),
+}
+
+impl<'de> Deserialize<'de> for Epoch {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EpochVisitor;
+
+        impl Visitor<'_> for EpochVisitor {
+            type Value = Epoch;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("seconds since the epoch")
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Epoch, E> {
+                Ok(Epoch::Int(v))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Epoch, E> {
+                i64::try_from(v)
+                    .map(Epoch::Int)
+                    .map_err(|_| E::custom("Epoch time out of range"))
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Epoch, E> {
+                if !v.is_finite() {
+                    return Err(E::custom("Epoch time must be finite"));
+                }
+                Ok(Epoch::Float(v))
+            }
+        }
+
+        deserializer.deserialize_any(EpochVisitor)
+    }
+}
+
+/// Read seconds since the epoch with tag 1 or no tag
+pub(crate) fn deserialize_epoch<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Epoch, D::Error> {
+    let tagged = Tagged::<Epoch>::deserialize(deserializer)?;
+    check_tag(tagged.tag, crate::constants::TAG_EPOCH_DATETIME)?;
+    Ok(tagged.value)
+}
+
+fn validate_uri(uri: &str) -> Result<(), String> {
+    crate::validate_uri(uri).map_err(|e| e.to_string())
+}
+
+/// Check the syntax of an RFC 3339 date/time, such as `2024-01-15T10:30:00.5+01:00`
+fn validate_rfc3339(text: &str) -> Result<(), String> {
+    let invalid = || format!("Invalid RFC 3339 date/time {:?}", text);
+    let bytes = text.as_bytes();
+    let number = |range: std::ops::Range<usize>| -> Option<u32> {
+        let digits = bytes.get(range)?;
+        digits
+            .iter()
+            .all(u8::is_ascii_digit)
+            .then(|| digits.iter().fold(0, |n, d| n * 10 + u32::from(d - b'0')))
+    };
+    let separator =
+        |pos: usize, expected: &[u8]| bytes.get(pos).is_some_and(|b| expected.contains(b));
+
+    let (Some(year), Some(month), Some(day), Some(hour), Some(minute), Some(second)) = (
+        number(0..4),
+        number(5..7),
+        number(8..10),
+        number(11..13),
+        number(14..16),
+        number(17..19),
+    ) else {
+        return Err(invalid());
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if !separator(4, b"-")
+        || !separator(7, b"-")
+        || !separator(10, b"Tt")
+        || !separator(13, b":")
+        || !separator(16, b":")
+        || !(1..=12).contains(&month)
+        || !(1..=days).contains(&day)
+        || hour > 23
+        || minute > 59
+        // 60 for a leap second
+        || second > 60
+    {
+        return Err(invalid());
+    }
+
+    let mut pos = 19;
+    if separator(pos, b".") {
+        let digits = bytes[pos + 1..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if digits == 0 {
+            return Err(invalid());
+        }
+        pos += 1 + digits;
+    }
+    let offset_ok = match bytes.get(pos) {
+        Some(b'Z' | b'z') => pos + 1 == bytes.len(),
+        Some(b'+' | b'-') => {
+            pos + 6 == bytes.len()
+                && separator(pos + 3, b":")
+                && number(pos + 1..pos + 3).is_some_and(|h| h <= 23)
+                && number(pos + 4..pos + 6).is_some_and(|m| m <= 59)
+        }
+        _ => false,
+    };
+    if offset_ok { Ok(()) } else { Err(invalid()) }
+}
+
+/// Check unpadded base64url text (RFC 4648 §5), as tag 33 requires
+fn validate_base64url(text: &str) -> Result<(), String> {
+    if let Some((offset, c)) = text
+        .char_indices()
+        .find(|&(_, c)| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+    {
+        return Err(format!(
+            "Invalid base64url character {:?} at offset {}",
+            c, offset
+        ));
+    }
+    if text.len() % 4 == 1 {
+        return Err(format!("Invalid base64url length {}", text.len()));
+    }
+    Ok(())
+}
+
+/// A string as a URI with tag 32
+///
+/// The URI is checked with [`validate_uri`](crate::validate_uri). Works for
+/// any `AsRef<str>` when serializing and any `From<String>` when
+/// deserializing.
+pub mod uri {
+    use serde::{Deserializer, Serialize, Serializer, ser};
+
+    use crate::{constants::TAG_URI, tags::Tagged};
+
+    /// Serialize `uri` with tag 32
+    pub fn serialize<T, S>(uri: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<str> + ?Sized,
+        S: Serializer,
+    {
+        let uri = uri.as_ref();
+        super::validate_uri(uri).map_err(ser::Error::custom)?;
+        Tagged::new(Some(TAG_URI), uri).serialize(serializer)
+    }
+
+    /// Deserialize a URI with tag 32 or no tag
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: From<String>,
+        D: Deserializer<'de>,
+    {
+        super::deserialize_text(deserializer, TAG_URI, super::validate_uri)
+    }
+}
+
+/// A string as an RFC 3339 date/time with tag 0
+///
+/// Only the syntax and the ranges of the fields are checked; use the
+/// `datetime` modules (with the `chrono` or `time` feature) to convert to
+/// `chrono` or `time` types. Works for any `AsRef<str>` when serializing and
+/// any `From<String>` when deserializing.
+pub mod datetime_string {
+    use serde::{Deserializer, Serialize, Serializer, ser};
+
+    use crate::{constants::TAG_DATETIME_STRING, tags::Tagged};
+
+    /// Serialize `datetime` with tag 0
+    pub fn serialize<T, S>(datetime: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<str> + ?Sized,
+        S: Serializer,
+    {
+        let datetime = datetime.as_ref();
+        super::validate_rfc3339(datetime).map_err(ser::Error::custom)?;
+        Tagged::new(Some(TAG_DATETIME_STRING), datetime).serialize(serializer)
+    }
+
+    /// Deserialize an RFC 3339 date/time with tag 0 or no tag
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: From<String>,
+        D: Deserializer<'de>,
+    {
+        super::deserialize_text(deserializer, TAG_DATETIME_STRING, super::validate_rfc3339)
+    }
+}
+
+/// An `i64` as whole seconds since the Unix epoch with tag 1
+///
+/// Floats without a fractional part are accepted when deserializing.
+pub mod epoch_seconds {
+    use serde::{Deserializer, Serialize, Serializer, de};
+
+    use super::{Epoch, deserialize_epoch};
+    use crate::{constants::TAG_EPOCH_DATETIME, tags::Tagged};
+
+    /// Serialize `secs` with tag 1
+    pub fn serialize<S: Serializer>(secs: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+        Tagged::new(Some(TAG_EPOCH_DATETIME), secs).serialize(serializer)
+    }
+
+    /// Deserialize whole seconds with tag 1 or no tag
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+        match deserialize_epoch(deserializer)? {
+            Epoch::Int(secs) => Ok(secs),
+            Epoch::Float(secs) if secs.fract() == 0.0 && secs.abs() < i64::MAX as f64 => {
+                Ok(secs as i64)
+            }
+            Epoch::Float(_) => Err(de::Error::custom(
+                "Epoch time has fractional seconds or is out of range",
+            )),
+        }
+    }
+}
+
+/// Bytes as a CBOR byte string
+///
+/// Works for any `AsRef<[u8]>` when serializing and any `From<Vec<u8>>` when
+/// deserializing. Like `serde_bytes`, an array of integers is also accepted.
+pub mod bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde_bytes::ByteBuf;
+
+    /// Serialize `bytes` as a byte string
+    pub fn serialize<T, S>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]> + ?Sized,
+        S: Serializer,
+    {
+        serializer.serialize_bytes(bytes.as_ref())
+    }
+
+    /// Deserialize a byte string or an array of integers
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: From<Vec<u8>>,
+        D: Deserializer<'de>,
+    {
+        ByteBuf::deserialize(deserializer).map(|buf| buf.into_vec().into())
+    }
+}
+
+/// A string as base64url-encoded text with tag 33
+///
+/// The text must use the base64url alphabet without padding. Works for any
+/// `AsRef<str>` when serializing and any `From<String>` when deserializing.
+pub mod base64url_text {
+    use serde::{Deserializer, Serialize, Serializer, ser};
+
+    use crate::{constants::TAG_BASE64URL, tags::Tagged};
+
+    /// Serialize `text` with tag 33
+    pub fn serialize<T, S>(text: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<str> + ?Sized,
+        S: Serializer,
+    {
+        let text = text.as_ref();
+        super::validate_base64url(text).map_err(ser::Error::custom)?;
+        Tagged::new(Some(TAG_BASE64URL), text).serialize(serializer)
+    }
+
+    /// Deserialize base64url text with tag 33 or no tag
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: From<String>,
+        D: Deserializer<'de>,
+    {
+        super::deserialize_text(deserializer, TAG_BASE64URL, super::validate_base64url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Claim {
+        #[serde(with = "uri")]
+        url: String,
+        #[serde(with = "datetime_string")]
+        created: String,
+        #[serde(with = "epoch_seconds")]
+        modified: i64,
+        #[serde(with = "bytes")]
+        signature: Vec<u8>,
+        #[serde(with = "base64url_text")]
+        kid: String,
+    }
+
+    fn claim() -> Claim {
+        Claim {
+            url: "self#jumbf=/c2pa".to_string(),
+            created: "2024-01-15T10:30:00.25+01:00".to_string(),
+            modified: 1_700_000_000,
+            signature: vec![1, 2],
+            kid: "SGVsbG8".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_serde_helpers_round_trip() {
+        let cbor = crate::to_vec(&claim()).unwrap();
+        let diag = crate::to_diagnostic(&cbor).unwrap();
+        assert!(diag.contains(r#"32("self#jumbf=/c2pa")"#), "{}", diag);
+        assert!(
+            diag.contains(r#"0("2024-01-15T10:30:00.25+01:00")"#),
+            "{}",
+            diag
+        );
+        assert!(diag.contains("1(1700000000)"), "{}", diag);
+        assert!(diag.contains("h'0102'"), "{}", diag);
+        assert!(diag.contains(r#"33("SGVsbG8")"#), "{}", diag);
+        assert_eq!(crate::from_slice::<Claim>(&cbor).unwrap(), claim());
+
+        // Untagged input is accepted, e.g. from JSON
+        let json = serde_json::json!({
+            "url": "https://example.com",
+            "created": "2024-02-29T00:00:60Z",
+            "modified": 5.0,
+            "signature": [3],
+            "kid": "",
+        });
+        let claim: Claim = serde_json::from_value(json).unwrap();
+        assert_eq!(claim.modified, 5);
+        assert_eq!(claim.signature, [3]);
+    }
+
+    #[test]
+    fn test_serde_helpers_reject_invalid_content() {
+        let mut bad = claim();
+        bad.url = "a b".to_string();
+        assert!(crate::to_vec(&bad).is_err());
+
+        for created in [
+            "2024-01-15",
+            "2024-01-15 10:30:00Z",
+            "2023-02-29T10:30:00Z",
+            "2024-01-15T24:00:00Z",
+            "2024-01-15T10:30:00.Z",
+            "2024-01-15T10:30:00+0100",
+        ] {
+            let mut bad = claim();
+            bad.created = created.to_string();
+            assert!(crate::to_vec(&bad).is_err(), "{}", created);
+        }
+
+        for kid in ["SGVsbG8=", "a+b", "abcde"] {
+            let mut bad = claim();
+            bad.kid = kid.to_string();
+            assert!(crate::to_vec(&bad).is_err(), "{}", kid);
+        }
+    }
+
+    #[test]
+    fn test_serde_helpers_check_tags() {
+        #[derive(Serialize)]
+        struct Raw {
+            url: Tagged<&'static str>,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct Url {
+            #[allow(dead_code)]
+            #[serde(with = "uri")]
+            url: String,
+        }
+
+        let raw = |tag| {
+            crate::to_vec(&Raw {
+                url: Tagged::new(Some(tag), "a:b"),
+            })
+            .unwrap()
+        };
+        assert!(crate::from_slice::<Url>(&raw(32)).is_ok());
+        let err = crate::from_slice::<Url>(&raw(33)).unwrap_err();
+        assert!(err.to_string().contains("Expected tag 32, found tag 33"));
+
+        // Fractional epoch seconds do not fit an i64
+        let cbor = crate::to_vec(&Tagged::new(Some(1), 1.5)).unwrap();
+        let mut decoder = crate::Decoder::from_slice(&cbor);
+        assert!(epoch_seconds::deserialize(&mut decoder).is_err());
+    }
+}
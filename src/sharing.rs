@@ -0,0 +1,624 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Value sharing (tags 28 and 29)
+//!
+//! Implements the [value sharing](http://cbor.schmorp.de/value-sharing)
+//! extension, which lets a value that occurs many times be written once.
+//! Tag 28 marks a value as shareable; shareable values are numbered from
+//! zero in the order their tags appear, and tag 29 around a number refers
+//! back to that value. Because a value is numbered before its content is
+//! read, a value can even refer to itself, so graphs with cycles can be
+//! written as well as trees with repeated subtrees.
+//!
+//! There are two ways to produce shared values:
+//! - [`share`] rewrites an encoded item, marking each array, map or tagged
+//!   item that occurs more than once and replacing its repeats with references
+//! - [`Shared`] wraps an [`Rc`], and [`to_vec`] writes each allocation once no
+//!   matter how many `Shared` handles point to it, including handles reached
+//!   through a cycle
+//!
+//! [`expand`] replaces every reference with a copy of the value it refers
+//! to, giving an ordinary tree, which [`from_slice`] then decodes into any
+//! type and [`resolve`] into a [`Value`]. A tree cannot hold a cycle, so a
+//! reference to a value from inside that value is an error when expanding.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::sharing;
+//!
+//! // The same ingredient listed twice
+//! let ingredient = vec![("title", "photo.jpg"), ("format", "image/jpeg")];
+//! let plain = c2pa_cbor::to_vec(&[&ingredient, &ingredient]).unwrap();
+//! let shared = sharing::share(&plain).unwrap();
+//! assert!(shared.len() < plain.len());
+//! assert_eq!(
+//!     sharing::resolve(&shared).unwrap(),
+//!     c2pa_cbor::from_slice::<c2pa_cbor::Value>(&plain).unwrap()
+//! );
+//! ```
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, hash_map::Entry},
+    ops::{Deref, Range},
+    rc::Rc,
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::DeserializeOwned};
+
+use crate::{
+    Error, Result, Value,
+    constants::*,
+    raw::{SliceReader, check_depth, check_scalar, skip_chunks, u64_to_usize, write_header},
+    tags::Tagged,
+};
+
+thread_local! {
+    // Allocations written so far by the innermost `to_vec` call, keyed by address
+    static SHARED: RefCell<Option<HashMap<*const (), u64>>> = const { RefCell::new(None) };
+}
+
+/// A reference-counted value that [`to_vec`] writes only once
+///
+/// The first time [`to_vec`] meets an allocation it writes the value as
+/// shareable (tag 28); every later `Shared` pointing to the same allocation,
+/// including one reached from inside the value itself, is written as a
+/// reference (tag 29). Outside [`to_vec`] the value is written in full each
+/// time, so a cycle must not be serialized any other way.
+///
+/// Deserializing gives a new allocation for each occurrence; decode with
+/// [`from_slice`] so that references are resolved first.
+///
+/// # Example
+/// ```
+/// use std::{cell::RefCell, rc::Rc};
+///
+/// use c2pa_cbor::sharing::{self, Shared};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Node {
+///     label: &'static str,
+///     next: Option<Shared<RefCell<Node>>>,
+/// }
+///
+/// // A node whose successor is itself
+/// let node = Shared::new(RefCell::new(Node { label: "loop", next: None }));
+/// node.borrow_mut().next = Some(node.clone());
+/// let cbor = sharing::to_vec(&node).unwrap();
+/// assert_eq!(
+///     c2pa_cbor::to_diagnostic(&cbor).unwrap(),
+///     "28({\n  \"label\": \"loop\",\n  \"next\": 29(0)\n})"
+/// );
+/// assert!(sharing::resolve(&cbor).is_err());
+/// # node.borrow_mut().next = None;
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Shared<T>(pub Rc<T>);
+
+impl<T> Shared<T> {
+    /// Move `value` into a new shared allocation
+    pub fn new(value: T) -> Self {
+        Shared(Rc::new(value))
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Shared(Rc::clone(&self.0))
+    }
+}
+
+impl<T> Deref for Shared<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> From<Rc<T>> for Shared<T> {
+    fn from(rc: Rc<T>) -> Self {
+        Shared(rc)
+    }
+}
+
+impl<T: Serialize> Serialize for Shared<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let address = Rc::as_ptr(&self.0) as *const ();
+        // None when not inside to_vec, Some(None) the first time this allocation is seen
+        let seen = SHARED.with(|shared| {
+            let mut shared = shared.borrow_mut();
+            let written = shared.as_mut()?;
+            let next = written.len() as u64;
+            Some(match written.entry(address) {
+                Entry::Occupied(entry) => Some(*entry.get()),
+                Entry::Vacant(entry) => {
+                    entry.insert(next);
+                    None
+                }
+            })
+        });
+        match seen {
+            None => self.0.serialize(serializer),
+            Some(None) => Tagged::new(Some(TAG_SHAREABLE), &*self.0).serialize(serializer),
+            Some(Some(index)) => Tagged::new(Some(TAG_SHAREDREF), index).serialize(serializer),
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Shared<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Shared::new)
+    }
+}
+
+/// Restores the enclosing `to_vec` call's table when dropped
+struct SharedScope(Option<HashMap<*const (), u64>>);
+
+impl Drop for SharedScope {
+    fn drop(&mut self) {
+        SHARED.with(|shared| shared.replace(self.0.take()));
+    }
+}
+
+/// Serialize a value, writing each [`Shared`] allocation once
+///
+/// See [`Shared`] for what is written.
+pub fn to_vec<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    let _scope = SharedScope(SHARED.with(|shared| shared.replace(Some(HashMap::new()))));
+    crate::to_vec(value)
+}
+
+/// Deserialize a value from CBOR that may contain shared values
+///
+/// The item is [expanded](expand) first, so the result cannot borrow from
+/// `bytes` and references to a value from inside itself are an error.
+pub fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    crate::from_slice(&expand(bytes)?)
+}
+
+/// Decode a single CBOR item that may contain shared values into a [`Value`]
+///
+/// Equivalent to [`from_slice`] into a `Value`.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::{Value, sharing::resolve};
+///
+/// // [28([1]), 29(0)]
+/// let cbor = [0x82, 0xd8, 0x1c, 0x81, 0x01, 0xd8, 0x1d, 0x00];
+/// let one = Value::Array(vec![Value::Integer(1)]);
+/// assert_eq!(resolve(&cbor).unwrap(), Value::Array(vec![one.clone(), one]));
+/// ```
+pub fn resolve(bytes: &[u8]) -> Result<Value> {
+    from_slice(bytes)
+}
+
+/// Resolve the shared values in a single CBOR item
+///
+/// Shareable tags are removed and each reference is replaced with a copy of
+/// the value it refers to; everything else is copied unchanged. Returns an
+/// error for a reference to a value that has not been seen yet or that
+/// contains the reference. The expanded item is limited to
+/// [`DEFAULT_MAX_ALLOCATION`] bytes, since
+/// nested references can stand for exponentially many copies.
+pub fn expand(bytes: &[u8]) -> Result<Vec<u8>> {
+    if bytes.is_empty() {
+        return Err(Error::Syntax("empty input".to_string()));
+    }
+
+    let mut reader = SliceReader::new(bytes);
+    let mut out = Vec::with_capacity(bytes.len());
+    expand_item(&mut reader, bytes, &mut out, &mut Vec::new(), 0)?;
+    if reader.remaining() > 0 {
+        return Err(Error::TrailingData);
+    }
+    Ok(out)
+}
+
+/// Expand one item; `table` holds where each shareable value was written, or `None` while it is being written
+fn expand_item(
+    reader: &mut SliceReader<'_>,
+    bytes: &[u8],
+    out: &mut Vec<u8>,
+    table: &mut Vec<Option<Range<usize>>>,
+    depth: usize,
+) -> Result<()> {
+    let start = reader.position();
+    let (major, info, arg) = reader.read_header()?;
+    match (major, arg) {
+        (MAJOR_BYTES | MAJOR_TEXT, Some(len)) => {
+            reader.read_slice(u64_to_usize(len)?)?;
+        }
+        (MAJOR_BYTES | MAJOR_TEXT, None) => skip_chunks(reader, major)?,
+        (MAJOR_ARRAY | MAJOR_MAP, _) => {
+            check_depth(depth)?;
+            out.extend_from_slice(&bytes[start..reader.position()]);
+            let per_entry = if major == MAJOR_MAP { 2 } else { 1 };
+            match arg {
+                Some(len) => {
+                    for _ in 0..len.saturating_mul(per_entry) {
+                        expand_item(reader, bytes, out, table, depth + 1)?;
+                    }
+                }
+                None => {
+                    while reader.peek_u8()? != BREAK {
+                        for _ in 0..per_entry {
+                            expand_item(reader, bytes, out, table, depth + 1)?;
+                        }
+                    }
+                    out.push(reader.read_u8()?);
+                }
+            }
+            return Ok(());
+        }
+        (MAJOR_TAG, Some(TAG_SHAREABLE)) => {
+            check_depth(depth)?;
+            let index = table.len();
+            table.push(None);
+            let content_start = out.len();
+            expand_item(reader, bytes, out, table, depth + 1)?;
+            table[index] = Some(content_start..out.len());
+            return Ok(());
+        }
+        (MAJOR_TAG, Some(TAG_SHAREDREF)) => {
+            let index = match reader.read_header()? {
+                (MAJOR_UNSIGNED, _, Some(index)) => index,
+                _ => {
+                    return Err(Error::Syntax(
+                        "Shared value reference must be an unsigned integer".to_string(),
+                    ));
+                }
+            };
+            let entry = usize::try_from(index)
+                .ok()
+                .and_then(|index| table.get(index));
+            let range = match entry {
+                Some(Some(range)) => range.clone(),
+                Some(None) => {
+                    return Err(Error::Syntax(format!(
+                        "Shared value {} refers to itself",
+                        index
+                    )));
+                }
+                None => {
+                    return Err(Error::Syntax(format!(
+                        "Shared value {} is not defined",
+                        index
+                    )));
+                }
+            };
+            if out.len() + range.len() > DEFAULT_MAX_ALLOCATION {
+                return Err(Error::MemoryBudgetExceeded(DEFAULT_MAX_ALLOCATION));
+            }
+            out.extend_from_within(range);
+            return Ok(());
+        }
+        (MAJOR_TAG, Some(_)) => {
+            check_depth(depth)?;
+            out.extend_from_slice(&bytes[start..reader.position()]);
+            return expand_item(reader, bytes, out, table, depth + 1);
+        }
+        _ => check_scalar(major, info, arg)?,
+    }
+    out.extend_from_slice(&bytes[start..reader.position()]);
+    Ok(())
+}
+
+/// Mark repeated arrays, maps and tagged items as shareable and replace their repeats with references
+///
+/// Items are compared by their encoding. An item is only shared when its
+/// encoding is longer than a reference to it would be. Every other part of
+/// the encoding is copied unchanged. Returns an error if the input already
+/// uses tag 28 or 29.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::sharing::share;
+///
+/// // [[1, 2, 3], [1, 2, 3]]
+/// let input = [0x82, 0x83, 0x01, 0x02, 0x03, 0x83, 0x01, 0x02, 0x03];
+/// // [28([1, 2, 3]), 29(0)]
+/// assert_eq!(
+///     share(&input).unwrap(),
+///     [0x82, 0xd8, 0x1c, 0x83, 0x01, 0x02, 0x03, 0xd8, 0x1d, 0x00]
+/// );
+/// ```
+pub fn share(bytes: &[u8]) -> Result<Vec<u8>> {
+    if bytes.is_empty() {
+        return Err(Error::Syntax("empty input".to_string()));
+    }
+
+    let mut index = ItemIndex::default();
+    let mut reader = SliceReader::new(bytes);
+    index.add_item(&mut reader, bytes, 0)?;
+    if reader.remaining() > 0 {
+        return Err(Error::TrailingData);
+    }
+
+    let mut out = Vec::with_capacity(bytes.len());
+    index.share_item(
+        &mut SliceReader::new(bytes),
+        bytes,
+        &mut out,
+        &mut HashMap::new(),
+    )?;
+    Ok(out)
+}
+
+/// Where each item ends and how often each container encoding occurs
+#[derive(Default)]
+struct ItemIndex<'a> {
+    ends: HashMap<usize, usize>,
+    counts: HashMap<&'a [u8], usize>,
+}
+
+impl<'a> ItemIndex<'a> {
+    fn add_item(
+        &mut self,
+        reader: &mut SliceReader<'a>,
+        bytes: &'a [u8],
+        depth: usize,
+    ) -> Result<()> {
+        let start = reader.position();
+        let (major, info, arg) = reader.read_header()?;
+        match (major, arg) {
+            (MAJOR_BYTES | MAJOR_TEXT, Some(len)) => {
+                reader.read_slice(u64_to_usize(len)?)?;
+            }
+            (MAJOR_BYTES | MAJOR_TEXT, None) => skip_chunks(reader, major)?,
+            (MAJOR_ARRAY | MAJOR_MAP, _) => {
+                check_depth(depth)?;
+                let per_entry = if major == MAJOR_MAP { 2 } else { 1 };
+                match arg {
+                    Some(len) => {
+                        for _ in 0..len.saturating_mul(per_entry) {
+                            self.add_item(reader, bytes, depth + 1)?;
+                        }
+                    }
+                    None => {
+                        while reader.peek_u8()? != BREAK {
+                            for _ in 0..per_entry {
+                                self.add_item(reader, bytes, depth + 1)?;
+                            }
+                        }
+                        reader.read_u8()?;
+                    }
+                }
+            }
+            (MAJOR_TAG, Some(TAG_SHAREABLE | TAG_SHAREDREF)) => {
+                return Err(Error::Syntax(
+                    "Item already contains shared values".to_string(),
+                ));
+            }
+            (MAJOR_TAG, Some(_)) => {
+                check_depth(depth)?;
+                self.add_item(reader, bytes, depth + 1)?;
+            }
+            _ => check_scalar(major, info, arg)?,
+        }
+        self.ends.insert(start, reader.position());
+        if matches!(major, MAJOR_ARRAY | MAJOR_MAP | MAJOR_TAG) {
+            *self
+                .counts
+                .entry(&bytes[start..reader.position()])
+                .or_default() += 1;
+        }
+        Ok(())
+    }
+
+    fn share_item(
+        &self,
+        reader: &mut SliceReader<'a>,
+        bytes: &'a [u8],
+        out: &mut Vec<u8>,
+        shared: &mut HashMap<&'a [u8], u64>,
+    ) -> Result<()> {
+        let start = reader.position();
+        let encoded = &bytes[start..self.ends[&start]];
+        let Some(&count) = self.counts.get(encoded) else {
+            // Scalars and strings are copied as they are
+            reader.read_slice(encoded.len())?;
+            out.extend_from_slice(encoded);
+            return Ok(());
+        };
+
+        if let Some(&index) = shared.get(encoded) {
+            reader.read_slice(encoded.len())?;
+            write_header(out, MAJOR_TAG, TAG_SHAREDREF);
+            write_header(out, MAJOR_UNSIGNED, index);
+            return Ok(());
+        }
+        let index = shared.len() as u64;
+        if count > 1 && encoded.len() > reference_len(index) {
+            shared.insert(encoded, index);
+            write_header(out, MAJOR_TAG, TAG_SHAREABLE);
+        }
+
+        let (major, _, arg) = reader.read_header()?;
+        out.extend_from_slice(&bytes[start..reader.position()]);
+        match (major, arg) {
+            (MAJOR_TAG, _) => self.share_item(reader, bytes, out, shared)?,
+            (_, Some(len)) => {
+                let per_entry = if major == MAJOR_MAP { 2 } else { 1 };
+                for _ in 0..len.saturating_mul(per_entry) {
+                    self.share_item(reader, bytes, out, shared)?;
+                }
+            }
+            (_, None) => {
+                while reader.peek_u8()? != BREAK {
+                    self.share_item(reader, bytes, out, shared)?;
+                }
+                out.push(reader.read_u8()?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Encoded length of a reference to shared value `index`
+fn reference_len(index: u64) -> usize {
+    let mut reference = Vec::new();
+    write_header(&mut reference, MAJOR_TAG, TAG_SHAREDREF);
+    write_header(&mut reference, MAJOR_UNSIGNED, index);
+    reference.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::BTreeMap};
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Ingredient {
+        title: String,
+        format: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Manifest {
+        parent: Shared<Ingredient>,
+        ingredients: Vec<Shared<Ingredient>>,
+    }
+
+    #[test]
+    fn test_shared_round_trip() {
+        let parent = Shared::new(Ingredient {
+            title: "photo.jpg".to_string(),
+            format: "image/jpeg".to_string(),
+        });
+        let other = Shared::new(Ingredient {
+            title: "logo.png".to_string(),
+            format: "image/png".to_string(),
+        });
+        let manifest = Manifest {
+            parent: parent.clone(),
+            ingredients: vec![parent.clone(), other.clone(), other],
+        };
+
+        let cbor = to_vec(&manifest).unwrap();
+        let diag = crate::to_diagnostic(&cbor).unwrap();
+        assert_eq!(diag.matches("28(").count(), 2, "{}", diag);
+        assert_eq!(diag.matches("29(").count(), 2, "{}", diag);
+        assert_eq!(from_slice::<Manifest>(&cbor).unwrap(), manifest);
+
+        // Outside to_vec every occurrence is written in full
+        let plain = crate::to_vec(&manifest).unwrap();
+        assert!(plain.len() > cbor.len());
+        assert_eq!(crate::from_slice::<Manifest>(&plain).unwrap(), manifest);
+        assert_eq!(
+            resolve(&cbor).unwrap(),
+            crate::from_slice::<Value>(&plain).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_cycle() {
+        #[derive(Serialize)]
+        struct Node {
+            id: u8,
+            next: Option<Shared<RefCell<Node>>>,
+        }
+
+        let first = Shared::new(RefCell::new(Node { id: 1, next: None }));
+        let second = Shared::new(RefCell::new(Node {
+            id: 2,
+            next: Some(first.clone()),
+        }));
+        first.borrow_mut().next = Some(second.clone());
+
+        let cbor = to_vec(&first).unwrap();
+        assert_eq!(
+            crate::to_diagnostic(&cbor).unwrap(),
+            "28({\n  \"id\": 1,\n  \"next\": 28({\n    \"id\": 2,\n    \"next\": 29(0)\n  })\n})"
+        );
+        assert!(matches!(resolve(&cbor), Err(Error::Syntax(_))));
+
+        // Break the cycle so the nodes are freed
+        first.borrow_mut().next = None;
+    }
+
+    #[test]
+    fn test_share_subtrees() {
+        let mut item = BTreeMap::new();
+        item.insert("alg", "sha256");
+        item.insert("url", "self#jumbf=c2pa.assertions/c2pa.hash.data");
+        let value = (vec![&item; 4], [1, 2], [1, 2]);
+        let plain = crate::to_vec(&value).unwrap();
+        let shared = share(&plain).unwrap();
+        assert!(shared.len() * 2 < plain.len());
+        let diag = crate::to_diagnostic(&shared).unwrap();
+        // Short arrays are not worth a reference
+        assert!(
+            diag.contains("[\n    1,\n    2\n  ],\n  [\n    1,\n    2\n  ]"),
+            "{}",
+            diag
+        );
+        assert_eq!(diag.matches("29(0)").count(), 3, "{}", diag);
+        assert_eq!(
+            resolve(&shared).unwrap(),
+            crate::from_slice::<Value>(&plain).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_numbering_follows_wire_order() {
+        // {"z": 28([1, 2, 3]), "a": 29(0)}
+        let cbor = [
+            0xa2, 0x61, b'z', 0xd8, 0x1c, 0x83, 0x01, 0x02, 0x03, 0x61, b'a', 0xd8, 0x1d, 0x00,
+        ];
+        let value: BTreeMap<String, Vec<u8>> = from_slice(&cbor).unwrap();
+        assert_eq!(value["a"], [1, 2, 3]);
+        assert_eq!(value["z"], [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_errors() {
+        // A reference before any shareable value
+        assert!(matches!(
+            resolve(&[0xd8, 0x1d, 0x00]),
+            Err(Error::Syntax(_))
+        ));
+        // A reference that is not an unsigned integer
+        assert!(matches!(
+            resolve(&[0x82, 0xd8, 0x1c, 0x01, 0xd8, 0x1d, 0x20]),
+            Err(Error::Syntax(_))
+        ));
+        assert!(matches!(share(&[0xd8, 0x1c, 0x01]), Err(Error::Syntax(_))));
+        assert!(matches!(share(&[0x01, 0x02]), Err(Error::TrailingData)));
+        assert!(matches!(resolve(&[0x01, 0x02]), Err(Error::TrailingData)));
+    }
+
+    #[test]
+    fn test_copy_limit() {
+        // [28([]), 28([29(0), 29(0)]), 28([29(1), 29(1)]), ...], where each
+        // shared value is twice the size of the one before
+        let mut cbor = vec![0x98, 41, 0xd8, 0x1c, 0x80];
+        for level in 0..40 {
+            cbor.extend_from_slice(&[0xd8, 0x1c, 0x82]);
+            for _ in 0..2 {
+                write_header(&mut cbor, MAJOR_TAG, TAG_SHAREDREF);
+                write_header(&mut cbor, MAJOR_UNSIGNED, level);
+            }
+        }
+        assert!(matches!(
+            resolve(&cbor),
+            Err(Error::MemoryBudgetExceeded(DEFAULT_MAX_ALLOCATION))
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Encode-then-sign in one call
+//!
+//! Enabled with the `signing` feature. [`signed_payload`] performs the steps
+//! that come before every signature: encode the value, convert it to
+//! deterministic form, check its size, check that it reads back to the same
+//! bytes, and hash it. The result holds the exact bytes to embed and the
+//! digest to sign, so the two cannot drift apart.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::signing::{HashAlgorithm, SigningProfile, signed_payload};
+//! use std::collections::HashMap;
+//!
+//! let claim = HashMap::from([("b", 2), ("a", 1)]);
+//! let payload = signed_payload(&claim, &SigningProfile::c2pa()).unwrap();
+//! // Map keys are sorted regardless of HashMap order
+//! assert_eq!(payload.canonical_bytes, [0xa2, 0x61, b'a', 0x01, 0x61, b'b', 0x02]);
+//! assert_eq!(payload.digest.len(), 32);
+//!
+//! let profile = SigningProfile::new(HashAlgorithm::Sha384).with_max_size(4);
+//! assert!(signed_payload(&claim, &profile).is_err());
+//! ```
+
+use serde::Serialize;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+use crate::{
+    Error, Result,
+    canonical::{Profile, normalize},
+    to_vec, validate,
+};
+
+/// Hash algorithm used for the payload digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm {
+    /// SHA-256, 32-byte digest
+    Sha256,
+    /// SHA-384, 48-byte digest
+    Sha384,
+    /// SHA-512, 64-byte digest
+    Sha512,
+}
+
+impl HashAlgorithm {
+    /// Hash `bytes` with this algorithm
+    pub fn digest(self, bytes: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgorithm::Sha256 => Sha256::digest(bytes).to_vec(),
+            HashAlgorithm::Sha384 => Sha384::digest(bytes).to_vec(),
+            HashAlgorithm::Sha512 => Sha512::digest(bytes).to_vec(),
+        }
+    }
+}
+
+/// How [`signed_payload`] encodes and hashes a value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigningProfile {
+    hash: HashAlgorithm,
+    deterministic: Profile,
+    max_size: Option<usize>,
+}
+
+impl SigningProfile {
+    /// Core deterministic encoding (RFC 8949 §4.2.1) hashed with `hash`, with no size limit
+    pub fn new(hash: HashAlgorithm) -> Self {
+        SigningProfile {
+            hash,
+            deterministic: Profile::Core,
+            max_size: None,
+        }
+    }
+
+    /// Profile for C2PA claims: core deterministic encoding and SHA-256
+    pub fn c2pa() -> Self {
+        SigningProfile::new(HashAlgorithm::Sha256)
+    }
+
+    /// Use the dCBOR profile instead of core deterministic encoding (builder pattern)
+    pub fn with_dcbor(mut self) -> Self {
+        self.deterministic = Profile::Dcbor;
+        self
+    }
+
+    /// Reject payloads whose deterministic encoding exceeds `max_size` bytes (builder pattern)
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// The hash algorithm for the digest
+    pub fn hash(&self) -> HashAlgorithm {
+        self.hash
+    }
+}
+
+/// Bytes ready to be signed, with their digest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignablePayload {
+    /// Deterministic encoding of the value; embed exactly these bytes
+    pub canonical_bytes: Vec<u8>,
+    /// Digest of `canonical_bytes` under the profile's hash algorithm
+    pub digest: Vec<u8>,
+}
+
+impl SignablePayload {
+    /// Size of the encoded payload in bytes
+    pub fn len(&self) -> usize {
+        self.canonical_bytes.len()
+    }
+
+    /// Whether the encoded payload is empty (never true for a valid payload)
+    pub fn is_empty(&self) -> bool {
+        self.canonical_bytes.is_empty()
+    }
+}
+
+/// Encode `value` deterministically under `profile` and hash it
+///
+/// Returns an error if the value cannot be encoded or converted to the
+/// profile (for example duplicate map keys, or simple values under dCBOR),
+/// if the result exceeds the profile's size limit, or if the bytes do not
+/// read back under the default decoder limits and re-encode to themselves.
+pub fn signed_payload<T: Serialize + ?Sized>(
+    value: &T,
+    profile: &SigningProfile,
+) -> Result<SignablePayload> {
+    let encoded = to_vec(&value)?;
+    let canonical_bytes = normalize(&encoded, profile.deterministic)?;
+
+    if let Some(max_size) = profile.max_size
+        && canonical_bytes.len() > max_size
+    {
+        return Err(Error::Message(format!(
+            "Signed payload is {} bytes, over the limit of {}",
+            canonical_bytes.len(),
+            max_size
+        )));
+    }
+
+    // A verifier must be able to read the payload and arrive at the same bytes
+    validate(&canonical_bytes)?;
+    if normalize(&canonical_bytes, profile.deterministic)? != canonical_bytes {
+        return Err(Error::Message(
+            "Signed payload does not round-trip to the same bytes".to_string(),
+        ));
+    }
+
+    let digest = profile.hash.digest(&canonical_bytes);
+    Ok(SignablePayload {
+        canonical_bytes,
+        digest,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_payload_digest() {
+        // "abc" encodes as 63 61 62 63
+        let payload = signed_payload("abc", &SigningProfile::c2pa()).unwrap();
+        assert_eq!(payload.canonical_bytes, [0x63, b'a', b'b', b'c']);
+        assert_eq!(payload.len(), 4);
+        assert_eq!(
+            payload.digest,
+            HashAlgorithm::Sha256.digest(&[0x63, b'a', b'b', b'c'])
+        );
+        assert_eq!(
+            payload.digest[..4],
+            [0xa6, 0xd8, 0x9b, 0xaf],
+            "{:02x?}",
+            payload.digest
+        );
+
+        for (hash, len) in [(HashAlgorithm::Sha384, 48), (HashAlgorithm::Sha512, 64)] {
+            let payload = signed_payload("abc", &SigningProfile::new(hash)).unwrap();
+            assert_eq!(payload.digest.len(), len);
+        }
+    }
+
+    #[test]
+    fn test_signed_payload_profiles() {
+        // Core keeps 2.0 as a float, dCBOR reduces it to an integer
+        let core = signed_payload(&vec![2.0f64], &SigningProfile::c2pa()).unwrap();
+        assert_eq!(core.canonical_bytes, [0x81, 0xf9, 0x40, 0x00]);
+        let dcbor = signed_payload(&vec![2.0f64], &SigningProfile::c2pa().with_dcbor()).unwrap();
+        assert_eq!(dcbor.canonical_bytes, [0x81, 0x02]);
+        assert_ne!(core.digest, dcbor.digest);
+    }
+
+    #[test]
+    fn test_signed_payload_size_limit() {
+        let value = vec![0u8; 10];
+        let profile = SigningProfile::c2pa().with_max_size(11);
+        assert_eq!(signed_payload(&value, &profile).unwrap().len(), 11);
+        let profile = SigningProfile::c2pa().with_max_size(10);
+        assert!(signed_payload(&value, &profile).is_err());
+    }
+}
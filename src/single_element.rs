@@ -0,0 +1,96 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Keep a value wrapped in a 1-element array, whatever the encoder options
+//!
+//! Single-field types are encoded like this by default, matching serde_cbor
+//! and ciborium:
+//!
+//! | Rust shape                                       | CBOR  |
+//! |--------------------------------------------------|-------|
+//! | `#[serde(transparent)] struct A { inner: T }`    | `T`   |
+//! | `struct A(T)` (a newtype struct)                 | `T`   |
+//! | `(T,)` or a tuple struct serialized with length 1 | `[T]` |
+//!
+//! Serde derives a newtype struct for every tuple struct with one field, so
+//! `struct A(T)` can't be told apart from `struct A(T,)`. Only newtype
+//! structs change shape with
+//! [`Encoder::with_legacy_newtypes`](crate::Encoder::with_legacy_newtypes),
+//! which writes `[T]`. When a type must be written as `[T]` no matter how
+//! the encoder is configured, for example because stored data already has
+//! that shape, annotate its field with `#[serde(with = "c2pa_cbor::single_element")]`.
+//!
+//! # Example
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Version(#[serde(with = "c2pa_cbor::single_element")] u32);
+//!
+//! let cbor = c2pa_cbor::to_vec(&Version(2)).unwrap();
+//! assert_eq!(cbor, [0x81, 0x02]);
+//! assert_eq!(c2pa_cbor::from_slice::<Version>(&cbor).unwrap(), Version(2));
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serialize `value` as a 1-element array
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    (value,).serialize(serializer)
+}
+
+/// Deserialize a value from a 1-element array
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    let (value,) = <(T,)>::deserialize(deserializer)?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::{Decoder, Encoder, from_slice, to_vec};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Version(#[serde(with = "crate::single_element")] u32);
+
+    #[test]
+    fn test_single_element_ignores_encoder_options() {
+        let plain = to_vec(&Version(7)).unwrap();
+        assert_eq!(plain, [0x81, 0x07]);
+
+        // Legacy newtypes add their own wrapper around the array
+        let mut enc = Encoder::new(Vec::new()).with_legacy_newtypes();
+        enc.encode(&Version(7)).unwrap();
+        let legacy = enc.into_inner();
+        assert_eq!(legacy, [0x81, 0x81, 0x07]);
+
+        assert_eq!(from_slice::<Version>(&plain).unwrap(), Version(7));
+        let mut dec = Decoder::new(&legacy[..]).with_legacy_newtypes();
+        assert_eq!(dec.decode::<Version>().unwrap(), Version(7));
+    }
+
+    #[test]
+    fn test_single_element_rejects_other_shapes() {
+        assert!(from_slice::<Version>(&[0x07]).is_err());
+        assert!(from_slice::<Version>(&[0x80]).is_err());
+    }
+}
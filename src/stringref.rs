@@ -0,0 +1,415 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! String references (tags 25 and 256)
+//!
+//! Implements the [stringref](http://cbor.schmorp.de/stringref) extension,
+//! which shrinks items that repeat the same strings. Tag 256 opens a string
+//! namespace around its content. Inside a namespace, each definite-length
+//! byte or text string that is long enough is added to a table in the order
+//! it is encountered, and a later copy of it can be written as tag 25 around
+//! its index in the table. Large C2PA manifests repeat the same assertion
+//! labels and URIs many times over, so they shrink considerably.
+//!
+//! A string is long enough to be added when it is at least as long as a
+//! reference to it would be, given the current size of the table: 3 bytes
+//! for the first 24 entries, 4 up to 256, 5 up to 65536, 7 up to 2^32 and
+//! 11 after that. Indefinite-length strings are never added, nor are strings
+//! that were themselves written as references.
+//!
+//! Like [`canonicalize`](crate::canonicalize), [`pack`] and [`expand`] work
+//! directly on encoded bytes. [`Encoder::with_stringrefs`](crate::Encoder::with_stringrefs)
+//! packs each item as it is encoded, and [`from_slice`] expands an item before
+//! decoding it.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::stringref;
+//!
+//! let labels = vec!["c2pa.actions"; 3];
+//! let packed = stringref::to_vec(&labels).unwrap();
+//! assert_eq!(c2pa_cbor::to_diagnostic(&packed).unwrap(), r#"256([
+//!   "c2pa.actions",
+//!   25(0),
+//!   25(0)
+//! ])"#);
+//! assert!(packed.len() < c2pa_cbor::to_vec(&labels).unwrap().len());
+//!
+//! let decoded: Vec<String> = stringref::from_slice(&packed).unwrap();
+//! assert_eq!(decoded, labels);
+//! ```
+
+use std::collections::HashMap;
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    Error, Result,
+    constants::*,
+    raw::{SliceReader, check_depth, check_scalar, skip_chunks, u64_to_usize, write_header},
+};
+
+/// Shortest string added to a table that already holds `entries` strings
+fn min_len(entries: usize) -> usize {
+    match entries as u64 {
+        0..24 => 3,
+        24..256 => 4,
+        256..65536 => 5,
+        65536..4294967296 => 7,
+        _ => 11,
+    }
+}
+
+/// Serialize a value to CBOR with repeated strings written as references
+///
+/// Equivalent to [`pack`] applied to the output of [`to_vec`](crate::to_vec).
+pub fn to_vec<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    pack(&crate::to_vec(value)?)
+}
+
+/// Deserialize a value from CBOR that may contain string references
+///
+/// The item is [expanded](expand) first, so the result cannot borrow from
+/// `bytes`. Items without string references decode as with
+/// [`from_slice`](crate::from_slice).
+pub fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    crate::from_slice(&expand(bytes)?)
+}
+
+/// Wrap a single CBOR item in a string namespace, replacing repeated strings with references
+///
+/// Every other part of the encoding is copied unchanged. Returns an error if
+/// the input already uses tag 25 or 256.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::stringref::{expand, pack};
+///
+/// // ["abc", "abc"]
+/// let input = [0x82, 0x63, b'a', b'b', b'c', 0x63, b'a', b'b', b'c'];
+/// let packed = pack(&input).unwrap();
+/// // 256(["abc", 25(0)])
+/// assert_eq!(packed, [0xd9, 0x01, 0x00, 0x82, 0x63, b'a', b'b', b'c', 0xd8, 0x19, 0x00]);
+/// assert_eq!(expand(&packed).unwrap(), input);
+/// ```
+pub fn pack(bytes: &[u8]) -> Result<Vec<u8>> {
+    if bytes.is_empty() {
+        return Err(Error::Syntax("empty input".to_string()));
+    }
+
+    let mut reader = SliceReader::new(bytes);
+    let mut out = Vec::with_capacity(bytes.len() + 3);
+    write_header(&mut out, MAJOR_TAG, TAG_STRINGREF_NAMESPACE);
+    pack_item(&mut reader, bytes, &mut out, &mut HashMap::new(), 0)?;
+    if reader.remaining() > 0 {
+        return Err(Error::TrailingData);
+    }
+    Ok(out)
+}
+
+/// Resolve the string references in a single CBOR item
+///
+/// Each reference is replaced by a copy of the string it refers to and the
+/// namespace tags are removed; everything else is copied unchanged, so an
+/// item without string references comes back as it was. The expanded item is
+/// limited to [`DEFAULT_MAX_ALLOCATION`]
+/// bytes, since a few bytes of references can stand for many copies of a
+/// long string.
+pub fn expand(bytes: &[u8]) -> Result<Vec<u8>> {
+    if bytes.is_empty() {
+        return Err(Error::Syntax("empty input".to_string()));
+    }
+
+    let mut reader = SliceReader::new(bytes);
+    let mut out = Vec::with_capacity(bytes.len());
+    expand_item(&mut reader, bytes, &mut out, &mut Vec::new(), 0)?;
+    if reader.remaining() > 0 {
+        return Err(Error::TrailingData);
+    }
+    Ok(out)
+}
+
+fn pack_item<'a>(
+    reader: &mut SliceReader<'a>,
+    bytes: &'a [u8],
+    out: &mut Vec<u8>,
+    table: &mut HashMap<(u8, &'a [u8]), u64>,
+    depth: usize,
+) -> Result<()> {
+    let start = reader.position();
+    let (major, info, arg) = reader.read_header()?;
+    match (major, arg) {
+        (MAJOR_BYTES | MAJOR_TEXT, Some(len)) => {
+            let content = reader.read_slice(u64_to_usize(len)?)?;
+            if let Some(&index) = table.get(&(major, content)) {
+                write_header(out, MAJOR_TAG, TAG_STRINGREF);
+                write_header(out, MAJOR_UNSIGNED, index);
+                return Ok(());
+            }
+            if content.len() >= min_len(table.len()) {
+                table.insert((major, content), table.len() as u64);
+            }
+        }
+        (MAJOR_BYTES | MAJOR_TEXT, None) => skip_chunks(reader, major)?,
+        (MAJOR_ARRAY | MAJOR_MAP, _) => {
+            check_depth(depth)?;
+            out.extend_from_slice(&bytes[start..reader.position()]);
+            let per_entry = if major == MAJOR_MAP { 2 } else { 1 };
+            match arg {
+                Some(len) => {
+                    for _ in 0..len.saturating_mul(per_entry) {
+                        pack_item(reader, bytes, out, table, depth + 1)?;
+                    }
+                }
+                None => {
+                    while reader.peek_u8()? != BREAK {
+                        for _ in 0..per_entry {
+                            pack_item(reader, bytes, out, table, depth + 1)?;
+                        }
+                    }
+                    out.push(reader.read_u8()?);
+                }
+            }
+            return Ok(());
+        }
+        (MAJOR_TAG, Some(TAG_STRINGREF | TAG_STRINGREF_NAMESPACE)) => {
+            return Err(Error::Syntax(
+                "Item already contains string references".to_string(),
+            ));
+        }
+        (MAJOR_TAG, Some(_)) => {
+            check_depth(depth)?;
+            out.extend_from_slice(&bytes[start..reader.position()]);
+            return pack_item(reader, bytes, out, table, depth + 1);
+        }
+        _ => check_scalar(major, info, arg)?,
+    }
+    out.extend_from_slice(&bytes[start..reader.position()]);
+    Ok(())
+}
+
+fn expand_item<'a>(
+    reader: &mut SliceReader<'a>,
+    bytes: &'a [u8],
+    out: &mut Vec<u8>,
+    tables: &mut Vec<Vec<(u8, &'a [u8])>>,
+    depth: usize,
+) -> Result<()> {
+    let start = reader.position();
+    let (major, info, arg) = reader.read_header()?;
+    match (major, arg) {
+        (MAJOR_BYTES | MAJOR_TEXT, Some(len)) => {
+            let content = reader.read_slice(u64_to_usize(len)?)?;
+            if let Some(table) = tables.last_mut()
+                && content.len() >= min_len(table.len())
+            {
+                table.push((major, content));
+            }
+        }
+        (MAJOR_BYTES | MAJOR_TEXT, None) => skip_chunks(reader, major)?,
+        (MAJOR_ARRAY | MAJOR_MAP, _) => {
+            check_depth(depth)?;
+            out.extend_from_slice(&bytes[start..reader.position()]);
+            let per_entry = if major == MAJOR_MAP { 2 } else { 1 };
+            match arg {
+                Some(len) => {
+                    for _ in 0..len.saturating_mul(per_entry) {
+                        expand_item(reader, bytes, out, tables, depth + 1)?;
+                    }
+                }
+                None => {
+                    while reader.peek_u8()? != BREAK {
+                        for _ in 0..per_entry {
+                            expand_item(reader, bytes, out, tables, depth + 1)?;
+                        }
+                    }
+                    out.push(reader.read_u8()?);
+                }
+            }
+            return Ok(());
+        }
+        (MAJOR_TAG, Some(TAG_STRINGREF_NAMESPACE)) => {
+            check_depth(depth)?;
+            tables.push(Vec::new());
+            expand_item(reader, bytes, out, tables, depth + 1)?;
+            tables.pop();
+            return Ok(());
+        }
+        (MAJOR_TAG, Some(TAG_STRINGREF)) => {
+            let table = tables.last().ok_or_else(|| {
+                Error::Syntax("String reference outside a string namespace".to_string())
+            })?;
+            let index = match reader.read_header()? {
+                (MAJOR_UNSIGNED, _, Some(index)) => index,
+                _ => {
+                    return Err(Error::Syntax(
+                        "String reference must be an unsigned integer".to_string(),
+                    ));
+                }
+            };
+            let &(string_major, content) = usize::try_from(index)
+                .ok()
+                .and_then(|index| table.get(index))
+                .ok_or_else(|| {
+                    Error::Syntax(format!("String reference {} is not in the table", index))
+                })?;
+            if out.len() + content.len() > DEFAULT_MAX_ALLOCATION {
+                return Err(Error::MemoryBudgetExceeded(DEFAULT_MAX_ALLOCATION));
+            }
+            write_header(out, string_major, content.len() as u64);
+            out.extend_from_slice(content);
+            return Ok(());
+        }
+        (MAJOR_TAG, Some(_)) => {
+            check_depth(depth)?;
+            out.extend_from_slice(&bytes[start..reader.position()]);
+            return expand_item(reader, bytes, out, tables, depth + 1);
+        }
+        _ => check_scalar(major, info, arg)?,
+    }
+    out.extend_from_slice(&bytes[start..reader.position()]);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[test]
+    fn test_min_len() {
+        assert_eq!(min_len(0), 3);
+        assert_eq!(min_len(23), 3);
+        assert_eq!(min_len(24), 4);
+        assert_eq!(min_len(256), 5);
+        assert_eq!(min_len(65536), 7);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Assertion {
+            label: String,
+            url: String,
+            hash: Vec<u8>,
+        }
+
+        let assertions: Vec<Assertion> = (0..50)
+            .map(|i| Assertion {
+                label: if i % 2 == 0 {
+                    "c2pa.actions"
+                } else {
+                    "c2pa.hash.data"
+                }
+                .to_string(),
+                url: format!("self#jumbf=c2pa.assertions/{}", i % 5),
+                hash: vec![i as u8; 32],
+            })
+            .collect();
+        let plain = crate::to_vec(&assertions).unwrap();
+        let packed = to_vec(&assertions).unwrap();
+        assert!(
+            packed.len() * 10 < plain.len() * 7,
+            "{} {}",
+            packed.len(),
+            plain.len()
+        );
+        assert_eq!(expand(&packed).unwrap(), plain);
+        assert_eq!(from_slice::<Vec<Assertion>>(&packed).unwrap(), assertions);
+    }
+
+    #[test]
+    fn test_types_and_thresholds() {
+        // Text and bytes with the same content are separate entries, and
+        // "ab" is too short to be added
+        let value = crate::Value::Array(vec![
+            crate::Value::Text("abc".to_string()),
+            crate::Value::Bytes(b"abc".to_vec()),
+            crate::Value::Text("ab".to_string()),
+            crate::Value::Bytes(b"abc".to_vec()),
+            crate::Value::Text("abc".to_string()),
+            crate::Value::Text("ab".to_string()),
+        ]);
+        let packed = to_vec(&value).unwrap();
+        assert_eq!(
+            crate::to_diagnostic(&packed).unwrap(),
+            "256([\n  \"abc\",\n  h'616263',\n  \"ab\",\n  25(1),\n  25(0),\n  \"ab\"\n])"
+        );
+        assert_eq!(from_slice::<crate::Value>(&packed).unwrap(), value);
+    }
+
+    #[test]
+    fn test_indefinite_strings_not_added() {
+        // 256([(_ "abc"), "abc", "abc"])
+        let packed = [
+            0xd9, 0x01, 0x00, 0x83, 0x7f, 0x63, b'a', b'b', b'c', 0xff, 0x63, b'a', b'b', b'c',
+            0xd8, 0x19, 0x00,
+        ];
+        assert_eq!(
+            expand(&packed).unwrap(),
+            [
+                0x83, 0x7f, 0x63, b'a', b'b', b'c', 0xff, 0x63, b'a', b'b', b'c', 0x63, b'a', b'b',
+                b'c'
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_namespaces() {
+        // 256(["abc", 256(["def", 25(0)]), 25(0)])
+        let packed = [
+            0xd9, 0x01, 0x00, 0x83, 0x63, b'a', b'b', b'c', 0xd9, 0x01, 0x00, 0x82, 0x63, b'd',
+            b'e', b'f', 0xd8, 0x19, 0x00, 0xd8, 0x19, 0x00,
+        ];
+        let value: (String, Vec<String>, String) = from_slice(&packed).unwrap();
+        assert_eq!(
+            value,
+            ("abc".into(), vec!["def".into(), "def".into()], "abc".into())
+        );
+    }
+
+    #[test]
+    fn test_maps_and_tags() {
+        let mut map = BTreeMap::new();
+        map.insert("alpha", crate::tags::Tagged::new(Some(32), "alpha"));
+        map.insert("beta", crate::tags::Tagged::new(Some(32), "alpha"));
+        let packed = to_vec(&map).unwrap();
+        assert_eq!(expand(&packed).unwrap(), crate::to_vec(&map).unwrap());
+        assert_eq!(
+            crate::to_diagnostic(&packed).unwrap(),
+            "256({\n  \"alpha\": 32(25(0)),\n  \"beta\": 32(25(0))\n})"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        // A reference outside any namespace
+        assert!(matches!(expand(&[0xd8, 0x19, 0x00]), Err(Error::Syntax(_))));
+        // An index past the end of the table
+        assert!(matches!(
+            expand(&[0xd9, 0x01, 0x00, 0xd8, 0x19, 0x00]),
+            Err(Error::Syntax(_))
+        ));
+        // A reference that is not an unsigned integer
+        assert!(matches!(
+            expand(&[0xd9, 0x01, 0x00, 0xd8, 0x19, 0x61, b'a']),
+            Err(Error::Syntax(_))
+        ));
+        assert!(matches!(pack(&[0xd8, 0x19, 0x00]), Err(Error::Syntax(_))));
+        assert!(matches!(pack(&[0x01, 0x02]), Err(Error::TrailingData)));
+        assert!(matches!(expand(&[0xff]), Err(Error::Syntax(_))));
+    }
+}
@@ -13,7 +13,7 @@
 
 // Portions derived from serde_cbor (https://github.com/pyfisch/cbor)
 
-use std::{fmt, io::Write, marker::PhantomData};
+use std::{cell::Cell, fmt, io::Write, marker::PhantomData};
 
 use serde::{
     Deserialize, Deserializer, Serialize,
@@ -22,8 +22,63 @@ use serde::{
 
 use crate::{Decoder, Encoder, Result, constants::*};
 
+thread_local! {
+    static CBOR_TAG: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// The tag of the value currently being deserialized or serialized
+///
+/// Drop-in for `serde_cbor::tags::current_cbor_tag`. While the decoder reads
+/// the content of a tag, a `Deserialize` impl or visitor for that content can
+/// call this to learn the tag number. Values that are not directly tagged,
+/// including the elements of a tagged array or map, see `None`.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::tags::current_cbor_tag;
+/// use serde::de::{Deserialize, Deserializer};
+///
+/// struct Uri(String);
+///
+/// impl<'de> Deserialize<'de> for Uri {
+///     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+///         struct UriVisitor;
+///         impl<'de> serde::de::Visitor<'de> for UriVisitor {
+///             type Value = Uri;
+///             fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+///                 f.write_str("a tag 32 URI")
+///             }
+///             fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Uri, E> {
+///                 match current_cbor_tag() {
+///                     Some(32) => Ok(Uri(v.to_string())),
+///                     _ => Err(E::custom("expected tag 32")),
+///                 }
+///             }
+///         }
+///         deserializer.deserialize_any(UriVisitor)
+///     }
+/// }
+///
+/// let mut cbor = Vec::new();
+/// c2pa_cbor::encode_uri(&mut cbor, "https://example.com").unwrap();
+/// assert_eq!(c2pa_cbor::from_slice::<Uri>(&cbor).unwrap().0, "https://example.com");
+/// assert!(c2pa_cbor::from_slice::<Uri>(&c2pa_cbor::to_vec(&"x").unwrap()).is_err());
+/// ```
+pub fn current_cbor_tag() -> Option<u64> {
+    CBOR_TAG.with(Cell::get)
+}
+
+/// Replace the current tag, returning the previous one so it can be restored
+pub(crate) fn set_tag(tag: Option<u64>) -> Option<u64> {
+    CBOR_TAG.with(|t| t.replace(tag))
+}
+
 /// A tagged CBOR value
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Matches `serde_cbor::tags::Tagged`: serializing writes the tag (any tag
+/// number) before the value, and deserializing with [`crate::from_slice`]
+/// records the tag of the item, or `None` if it is untagged.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Tagged<T> {
     /// The CBOR tag number (optional for compatibility)
     pub tag: Option<u64>,
@@ -64,21 +119,9 @@ impl<T: for<'de> Deserialize<'de>> Tagged<T> {
     /// ```
     pub fn from_tagged_slice(cbor: &[u8]) -> Result<Self> {
         let mut decoder = Decoder::from_slice(cbor);
-
-        // Peek at the next byte to check if there's a tag
-        let peek = decoder.peek_u8()?;
-        let major = peek >> 5;
-
-        if major == MAJOR_TAG {
-            // Tag present - read it and then decode the value
-            let tag = decoder.read_tag()?;
-            let value: T = decoder.decode()?;
-            Ok(Tagged::new(Some(tag), value))
-        } else {
-            // No tag - just decode the value
-            let value: T = decoder.decode()?;
-            Ok(Tagged::new(None, value))
-        }
+        let tag = decoder.peek_tag()?;
+        let value: T = decoder.decode()?;
+        Ok(Tagged::new(tag, value))
     }
 }
 
@@ -136,12 +179,13 @@ impl<T: Serialize> Serialize for Tagged<T> {
                     87 => "__cbor_tag_87__", // float128 little-endian
 
                     _ => {
-                        // Unsupported tag number - use encode_tagged helper functions instead
-                        use serde::ser::Error;
-                        return Err(Error::custom(format!(
-                            "Tag {} not supported via Tagged<T>. Use encode_tagged() helper function for arbitrary tags.",
-                            tag
-                        )));
+                        // Other tag numbers reach the encoder through current_cbor_tag,
+                        // as with serde_cbor
+                        let outer_tag = set_tag(Some(tag));
+                        let result =
+                            serializer.serialize_newtype_struct(CBOR_TAG_MARKER, &self.value);
+                        set_tag(outer_tag);
+                        return result;
                     }
                 };
 
@@ -155,6 +199,52 @@ impl<T: Serialize> Serialize for Tagged<T> {
     }
 }
 
+// Forwards to a serde value deserializer, except that newtype structs are
+// visited transparently, as the decoder does, so `Tagged<T>` also works for
+// newtype `T`
+struct Transparent<D>(D);
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for Transparent<D> {
+    type Error = D::Error;
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, D::Error> {
+        self.0.deserialize_any(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, D::Error> {
+        self.0.deserialize_option(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> std::result::Result<V::Value, D::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> std::result::Result<V::Value, D::Error> {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+}
+
 // Custom deserialization that handles both tagged CBOR values and plain values (e.g., from JSON)
 impl<'de, T> Deserialize<'de> for Tagged<T>
 where
@@ -178,76 +268,96 @@ where
                 formatter.write_str("a tagged value or a plain value")
             }
 
-            // Handle the case where we get a plain value (e.g., from JSON)
-            // Just wrap it in Tagged with no tag
+            // The decoder publishes the tag of the item being visited; plain
+            // values (e.g., from JSON) have no tag
             fn visit_bool<E>(self, v: bool) -> std::result::Result<Tagged<T>, E>
             where
                 E: de::Error,
             {
-                T::deserialize(serde::de::value::BoolDeserializer::new(v))
-                    .map(|value| Tagged { tag: None, value })
+                T::deserialize(Transparent(serde::de::value::BoolDeserializer::new(v)))
+                    .map(|value| Tagged::new(current_cbor_tag(), value))
             }
 
             fn visit_i64<E>(self, v: i64) -> std::result::Result<Tagged<T>, E>
             where
                 E: de::Error,
             {
-                T::deserialize(serde::de::value::I64Deserializer::new(v))
-                    .map(|value| Tagged { tag: None, value })
+                T::deserialize(Transparent(serde::de::value::I64Deserializer::new(v)))
+                    .map(|value| Tagged::new(current_cbor_tag(), value))
             }
 
             fn visit_u64<E>(self, v: u64) -> std::result::Result<Tagged<T>, E>
             where
                 E: de::Error,
             {
-                T::deserialize(serde::de::value::U64Deserializer::new(v))
-                    .map(|value| Tagged { tag: None, value })
+                T::deserialize(Transparent(serde::de::value::U64Deserializer::new(v)))
+                    .map(|value| Tagged::new(current_cbor_tag(), value))
+            }
+
+            fn visit_i128<E>(self, v: i128) -> std::result::Result<Tagged<T>, E>
+            where
+                E: de::Error,
+            {
+                T::deserialize(Transparent(serde::de::value::I128Deserializer::new(v)))
+                    .map(|value| Tagged::new(current_cbor_tag(), value))
             }
 
             fn visit_f64<E>(self, v: f64) -> std::result::Result<Tagged<T>, E>
             where
                 E: de::Error,
             {
-                T::deserialize(serde::de::value::F64Deserializer::new(v))
-                    .map(|value| Tagged { tag: None, value })
+                T::deserialize(Transparent(serde::de::value::F64Deserializer::new(v)))
+                    .map(|value| Tagged::new(current_cbor_tag(), value))
             }
 
             fn visit_str<E>(self, v: &str) -> std::result::Result<Tagged<T>, E>
             where
                 E: de::Error,
             {
-                T::deserialize(serde::de::value::StrDeserializer::new(v))
-                    .map(|value| Tagged { tag: None, value })
+                T::deserialize(Transparent(serde::de::value::StrDeserializer::new(v)))
+                    .map(|value| Tagged::new(current_cbor_tag(), value))
             }
 
             fn visit_string<E>(self, v: String) -> std::result::Result<Tagged<T>, E>
             where
                 E: de::Error,
             {
-                T::deserialize(serde::de::value::StringDeserializer::new(v))
-                    .map(|value| Tagged { tag: None, value })
+                T::deserialize(Transparent(serde::de::value::StringDeserializer::new(v)))
+                    .map(|value| Tagged::new(current_cbor_tag(), value))
             }
 
             fn visit_bytes<E>(self, v: &[u8]) -> std::result::Result<Tagged<T>, E>
             where
                 E: de::Error,
             {
-                T::deserialize(serde::de::value::BytesDeserializer::new(v))
-                    .map(|value| Tagged { tag: None, value })
+                T::deserialize(Transparent(serde::de::value::BytesDeserializer::new(v)))
+                    .map(|value| Tagged::new(current_cbor_tag(), value))
             }
 
             fn visit_seq<A>(self, seq: A) -> std::result::Result<Tagged<T>, A::Error>
             where
                 A: de::SeqAccess<'de>,
             {
-                T::deserialize(serde::de::value::SeqAccessDeserializer::new(seq))
-                    .map(|value| Tagged { tag: None, value })
+                // Read the tag before the elements are visited
+                let tag = current_cbor_tag();
+                T::deserialize(Transparent(serde::de::value::SeqAccessDeserializer::new(
+                    seq,
+                )))
+                .map(|value| Tagged::new(tag, value))
             }
 
             fn visit_map<A>(self, map: A) -> std::result::Result<Tagged<T>, A::Error>
             where
                 A: de::MapAccess<'de>,
             {
+                // A tagged CBOR map is the content itself
+                if let Some(tag) = current_cbor_tag() {
+                    return T::deserialize(Transparent(
+                        serde::de::value::MapAccessDeserializer::new(map),
+                    ))
+                    .map(|value| Tagged::new(Some(tag), value));
+                }
+
                 // Try to deserialize as a struct with tag and value fields
                 // If that fails, deserialize as the inner type directly
                 #[derive(Deserialize)]
@@ -277,7 +387,293 @@ where
     }
 }
 
+/// A type that always carries a CBOR tag envelope
+///
+/// Implement this for domain types whose encoding is defined to be wrapped in
+/// a tag, instead of passing them around as [`Tagged<T>`].
+/// [`Encoder::encode_tagged`] writes the declared tag before the value and
+/// [`Decoder::decode_tagged`] checks that the input carries exactly that tag.
+/// For fields of such a type, use `#[serde(with = "c2pa_cbor::tags::declared")]`.
+///
+/// The tag is asked of the value, so it can depend on its content; `None`
+/// writes the value untagged and requires untagged input.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::{Decoder, Encoder, tags::TaggedSerialize};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Uri(String);
+///
+/// impl TaggedSerialize for Uri {
+///     fn cbor_tag(&self) -> Option<u64> {
+///         Some(32)
+///     }
+/// }
+///
+/// let mut encoder = Encoder::new(Vec::new());
+/// encoder.encode_tagged(&Uri("a:b".to_string())).unwrap();
+/// let cbor = encoder.into_inner();
+/// assert_eq!(cbor, [0xd8, 0x20, 0x63, b'a', b':', b'b']);
+///
+/// let uri: Uri = Decoder::from_slice(&cbor).decode_tagged().unwrap();
+/// assert_eq!(uri, Uri("a:b".to_string()));
+/// assert!(Decoder::from_slice(&cbor[2..]).decode_tagged::<Uri>().is_err());
+/// ```
+pub trait TaggedSerialize: Serialize {
+    /// The tag this value is wrapped in, or `None` to write it untagged
+    fn cbor_tag(&self) -> Option<u64>;
+}
+
+impl<T: TaggedSerialize + ?Sized> TaggedSerialize for &T {
+    fn cbor_tag(&self) -> Option<u64> {
+        (**self).cbor_tag()
+    }
+}
+
+impl<T: TaggedSerialize + ?Sized> TaggedSerialize for Box<T> {
+    fn cbor_tag(&self) -> Option<u64> {
+        (**self).cbor_tag()
+    }
+}
+
+/// Check a decoded value against the tag it was read with
+pub(crate) fn check_declared_tag<T: TaggedSerialize>(
+    value: &T,
+    found: Option<u64>,
+) -> std::result::Result<(), String> {
+    let declared = value.cbor_tag();
+    if declared == found {
+        return Ok(());
+    }
+    Err(match (declared, found) {
+        (Some(declared), Some(found)) => format!("expected tag {}, found tag {}", declared, found),
+        (Some(declared), None) => format!("expected tag {}, found an untagged value", declared),
+        (None, Some(found)) => format!("expected an untagged value, found tag {}", found),
+        (None, None) => unreachable!(),
+    })
+}
+
+/// `#[serde(with = "c2pa_cbor::tags::declared")]` for fields of a [`TaggedSerialize`] type
+///
+/// Writes the field wrapped in its declared tag and rejects input whose tag
+/// differs. Types whose content is a map must declare a tag, since an
+/// untagged map cannot be told apart from a `Tagged` tag/value pair.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::tags::TaggedSerialize;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Uri(String);
+///
+/// impl TaggedSerialize for Uri {
+///     fn cbor_tag(&self) -> Option<u64> {
+///         Some(32)
+///     }
+/// }
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Link {
+///     #[serde(with = "c2pa_cbor::tags::declared")]
+///     url: Uri,
+/// }
+///
+/// let link = Link { url: Uri("a:b".to_string()) };
+/// let cbor = c2pa_cbor::to_vec(&link).unwrap();
+/// assert_eq!(&cbor[5..7], [0xd8, 0x20]);
+/// assert_eq!(c2pa_cbor::from_slice::<Link>(&cbor).unwrap(), link);
+/// ```
+pub mod declared {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _};
+
+    use super::{Tagged, TaggedSerialize, check_declared_tag};
+
+    /// Serialize `value` wrapped in its declared tag
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: TaggedSerialize,
+        S: Serializer,
+    {
+        Tagged::new(value.cbor_tag(), value).serialize(serializer)
+    }
+
+    /// Deserialize a value, checking that it carries its declared tag
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: TaggedSerialize + Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let tagged = Tagged::<T>::deserialize(deserializer)?;
+        check_declared_tag(&tagged.value, tagged.tag).map_err(D::Error::custom)?;
+        Ok(tagged.value)
+    }
+}
+
+/// A type that is always written inside one fixed tag
+///
+/// Implemented by `#[derive(CborTagged)]` (with the `derive` feature), whose
+/// `Serialize` and `Deserialize` impls write and check this tag.
+pub trait CborTagged {
+    /// The tag the type is wrapped in
+    const CBOR_TAG: u64;
+}
+
+/// Serialize `value` wrapped in `tag`, for `#[derive(CborTagged)]`
+#[doc(hidden)]
+pub fn serialize_with_tag<T, S>(
+    tag: u64,
+    value: &T,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    T: Serialize + ?Sized,
+    S: serde::Serializer,
+{
+    Tagged::new(Some(tag), value).serialize(serializer)
+}
+
+/// Deserialize a value that must carry `tag`, for `#[derive(CborTagged)]`
+#[doc(hidden)]
+pub fn deserialize_with_tag<'de, T, D>(
+    tag: u64,
+    deserializer: D,
+) -> std::result::Result<T, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    let tagged = Tagged::<T>::deserialize(deserializer)?;
+    match tagged.tag {
+        Some(found) if found == tag => Ok(tagged.value),
+        Some(found) => Err(de::Error::custom(format!(
+            "expected tag {}, found tag {}",
+            tag, found
+        ))),
+        None => Err(de::Error::custom(format!(
+            "expected tag {}, found an untagged value",
+            tag
+        ))),
+    }
+}
+
 // Tagged value helpers
+/// Tag numbers from the IANA CBOR tags registry
+///
+/// These are the tags this crate understands, as public constants so that
+/// downstream code does not need to redefine them. [`IanaTag`](iana::IanaTag)
+/// names the same set as an enum.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::tags::iana::{self, IanaTag};
+///
+/// assert_eq!(iana::URI, 32);
+/// assert_eq!(IanaTag::from_u64(32), Some(IanaTag::Uri));
+/// assert_eq!(IanaTag::Uri.to_u64(), iana::URI);
+/// assert_eq!(IanaTag::Uri.description(), "URI (RFC 3986)");
+/// assert_eq!(IanaTag::from_u64(76), None); // reserved
+/// ```
+pub mod iana {
+    use crate::constants::*;
+
+    macro_rules! define_iana_tags {
+        ($($variant:ident, $name:ident = $value:ident, $description:literal;)*) => {
+            $(
+                #[doc = $description]
+                pub const $name: u64 = $value;
+            )*
+
+            /// A tag from the IANA CBOR tags registry known to this crate
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            #[non_exhaustive]
+            pub enum IanaTag {
+                $(
+                    #[doc = $description]
+                    $variant,
+                )*
+            }
+
+            impl IanaTag {
+                /// Every known tag, in ascending order of tag number
+                pub const ALL: &'static [IanaTag] = &[$(IanaTag::$variant),*];
+
+                /// The known tag with this number, if any
+                pub fn from_u64(tag: u64) -> Option<Self> {
+                    match tag {
+                        $($name => Some(IanaTag::$variant),)*
+                        _ => None,
+                    }
+                }
+
+                /// The tag number
+                pub fn to_u64(self) -> u64 {
+                    match self {
+                        $(IanaTag::$variant => $name,)*
+                    }
+                }
+
+                /// Short description of the tag's meaning
+                pub fn description(self) -> &'static str {
+                    match self {
+                        $(IanaTag::$variant => $description,)*
+                    }
+                }
+            }
+        };
+    }
+
+    define_iana_tags! {
+        DateTimeString, DATETIME_STRING = TAG_DATETIME_STRING, "Standard date/time string (RFC 3339)";
+        EpochDateTime, EPOCH_DATETIME = TAG_EPOCH_DATETIME, "Epoch-based date/time";
+        PositiveBignum, POSITIVE_BIGNUM = TAG_POSITIVE_BIGNUM, "Positive bignum";
+        NegativeBignum, NEGATIVE_BIGNUM = TAG_NEGATIVE_BIGNUM, "Negative bignum";
+        DecimalFraction, DECIMAL_FRACTION = TAG_DECIMAL_FRACTION, "Decimal fraction";
+        Bigfloat, BIGFLOAT = TAG_BIGFLOAT, "Bigfloat";
+        ExpectBase64Url, EXPECT_BASE64URL = TAG_EXPECT_BASE64URL, "Expected conversion to base64url";
+        ExpectBase64, EXPECT_BASE64 = TAG_EXPECT_BASE64, "Expected conversion to base64";
+        ExpectBase16, EXPECT_BASE16 = TAG_EXPECT_BASE16, "Expected conversion to base16";
+        EncodedCbor, ENCODED_CBOR = TAG_ENCODED_CBOR, "Encoded CBOR data item";
+        StringRef, STRINGREF = TAG_STRINGREF, "Reference to a string in the current string table";
+        Shareable, SHAREABLE = TAG_SHAREABLE, "Value that may be referenced later";
+        SharedRef, SHAREDREF = TAG_SHAREDREF, "Reference to a shareable value";
+        Uri, URI = TAG_URI, "URI (RFC 3986)";
+        Base64Url, BASE64URL = TAG_BASE64URL, "Base64url-encoded text";
+        Base64, BASE64 = TAG_BASE64, "Base64-encoded text";
+        Mime, MIME = TAG_MIME, "MIME message";
+        LangString, LANG_STRING = TAG_LANG_STRING, "Language-tagged string (RFC 9290)";
+        MultiDimArray, MULTI_DIM_ARRAY = TAG_MULTI_DIM_ARRAY, "Multi-dimensional array, row-major (RFC 8746)";
+        Uint8Array, UINT8_ARRAY = TAG_UINT8_ARRAY, "uint8 array";
+        Uint16BeArray, UINT16BE_ARRAY = TAG_UINT16BE_ARRAY, "uint16 big-endian array";
+        Uint32BeArray, UINT32BE_ARRAY = TAG_UINT32BE_ARRAY, "uint32 big-endian array";
+        Uint64BeArray, UINT64BE_ARRAY = TAG_UINT64BE_ARRAY, "uint64 big-endian array";
+        Uint8ClampedArray, UINT8_CLAMPED_ARRAY = TAG_UINT8_CLAMPED_ARRAY, "uint8 clamped array";
+        Uint16LeArray, UINT16LE_ARRAY = TAG_UINT16LE_ARRAY, "uint16 little-endian array";
+        Uint32LeArray, UINT32LE_ARRAY = TAG_UINT32LE_ARRAY, "uint32 little-endian array";
+        Uint64LeArray, UINT64LE_ARRAY = TAG_UINT64LE_ARRAY, "uint64 little-endian array";
+        Sint8Array, SINT8_ARRAY = TAG_SINT8_ARRAY, "sint8 array";
+        Sint16BeArray, SINT16BE_ARRAY = TAG_SINT16BE_ARRAY, "sint16 big-endian array";
+        Sint32BeArray, SINT32BE_ARRAY = TAG_SINT32BE_ARRAY, "sint32 big-endian array";
+        Sint64BeArray, SINT64BE_ARRAY = TAG_SINT64BE_ARRAY, "sint64 big-endian array";
+        Sint16LeArray, SINT16LE_ARRAY = TAG_SINT16LE_ARRAY, "sint16 little-endian array";
+        Sint32LeArray, SINT32LE_ARRAY = TAG_SINT32LE_ARRAY, "sint32 little-endian array";
+        Sint64LeArray, SINT64LE_ARRAY = TAG_SINT64LE_ARRAY, "sint64 little-endian array";
+        Float16BeArray, FLOAT16BE_ARRAY = TAG_FLOAT16BE_ARRAY, "float16 big-endian array";
+        Float32BeArray, FLOAT32BE_ARRAY = TAG_FLOAT32BE_ARRAY, "float32 big-endian array";
+        Float64BeArray, FLOAT64BE_ARRAY = TAG_FLOAT64BE_ARRAY, "float64 big-endian array";
+        Float128BeArray, FLOAT128BE_ARRAY = TAG_FLOAT128BE_ARRAY, "float128 big-endian array";
+        Float16LeArray, FLOAT16LE_ARRAY = TAG_FLOAT16LE_ARRAY, "float16 little-endian array";
+        Float32LeArray, FLOAT32LE_ARRAY = TAG_FLOAT32LE_ARRAY, "float32 little-endian array";
+        Float64LeArray, FLOAT64LE_ARRAY = TAG_FLOAT64LE_ARRAY, "float64 little-endian array";
+        Float128LeArray, FLOAT128LE_ARRAY = TAG_FLOAT128LE_ARRAY, "float128 little-endian array";
+        StringRefNamespace, STRINGREF_NAMESPACE = TAG_STRINGREF_NAMESPACE, "String reference namespace";
+        MultiDimArrayColumnMajor, MULTI_DIM_ARRAY_COLUMN_MAJOR = TAG_MULTI_DIM_ARRAY_COLUMN_MAJOR, "Multi-dimensional array, column-major (RFC 8746)";
+        SelfDescribe, SELF_DESCRIBE = TAG_SELF_DESCRIBE, "Self-described CBOR";
+    }
+}
+
 /// Encode a tagged value (tag number + content)
 pub fn encode_tagged<W: Write, T: Serialize>(writer: &mut W, tag: u64, value: &T) -> Result<()> {
     let mut encoder = Encoder::new(writer);
@@ -311,6 +707,197 @@ pub fn encode_base64<W: Write>(writer: &mut W, data: &str) -> Result<()> {
     encode_tagged(writer, TAG_BASE64, &data)
 }
 
+/// Helper to encode a MIME message (tag 36)
+pub fn encode_mime<W: Write>(writer: &mut W, message: &str) -> Result<()> {
+    encode_tagged(writer, TAG_MIME, &message)
+}
+
+/// Helper to encode a language-tagged string (tag 38)
+pub fn encode_lang_string<W: Write>(writer: &mut W, lang: &str, text: &str) -> Result<()> {
+    encode_tagged(writer, TAG_LANG_STRING, &(lang, text))
+}
+
+/// A MIME message (tag 36), including its headers
+///
+/// Serializes as tag 36 around the text of the message. Deserializing
+/// requires tag 36.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::tags::MimeMessage;
+///
+/// let message = MimeMessage("Content-Type: text/plain\r\n\r\nhello".to_string());
+/// let cbor = c2pa_cbor::to_vec(&message).unwrap();
+/// assert_eq!(&cbor[..2], [0xd8, 0x24]);
+/// assert_eq!(c2pa_cbor::from_slice::<MimeMessage>(&cbor).unwrap(), message);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MimeMessage(pub String);
+
+impl TaggedSerialize for MimeMessage {
+    fn cbor_tag(&self) -> Option<u64> {
+        Some(TAG_MIME)
+    }
+}
+
+impl Serialize for MimeMessage {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        Tagged::new(Some(TAG_MIME), &self.0).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for MimeMessage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let tagged = Tagged::<String>::deserialize(deserializer)?;
+        if tagged.tag != Some(TAG_MIME) {
+            return Err(de::Error::custom("Expected tag 36 for a MIME message"));
+        }
+        Ok(MimeMessage(tagged.value))
+    }
+}
+
+/// A language-tagged string (tag 38, RFC 9290 Appendix A)
+///
+/// Serializes as `38([lang, text])`, where `lang` is a BCP 47 language tag
+/// such as `"en"` or `"fr-CA"`. Deserializing requires tag 38, a two-element
+/// array and a non-empty language tag.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::tags::LangString;
+///
+/// let caption = LangString::new("fr", "Coucher de soleil");
+/// let cbor = c2pa_cbor::to_vec(&caption).unwrap();
+/// assert_eq!(&cbor[..5], [0xd8, 0x26, 0x82, 0x62, b'f']);
+/// assert_eq!(c2pa_cbor::from_slice::<LangString>(&cbor).unwrap(), caption);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LangString {
+    /// BCP 47 language tag
+    pub lang: String,
+    /// The text in that language
+    pub text: String,
+}
+
+impl LangString {
+    /// Create a language-tagged string
+    pub fn new(lang: impl Into<String>, text: impl Into<String>) -> Self {
+        LangString {
+            lang: lang.into(),
+            text: text.into(),
+        }
+    }
+}
+
+impl TaggedSerialize for LangString {
+    fn cbor_tag(&self) -> Option<u64> {
+        Some(TAG_LANG_STRING)
+    }
+}
+
+impl Serialize for LangString {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        Tagged::new(Some(TAG_LANG_STRING), (&self.lang, &self.text)).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for LangString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let tagged = Tagged::<Vec<String>>::deserialize(deserializer)?;
+        if tagged.tag != Some(TAG_LANG_STRING) {
+            return Err(de::Error::custom(
+                "Expected tag 38 for a language-tagged string",
+            ));
+        }
+        let [lang, text]: [String; 2] = tagged.value.try_into().map_err(|_| {
+            de::Error::custom("Language-tagged string must be an array of language and text")
+        })?;
+        if lang.is_empty() {
+            return Err(de::Error::custom("Language tag cannot be empty"));
+        }
+        Ok(LangString { lang, text })
+    }
+}
+
+// Macro to generate the expected-conversion wrappers (tags 21-23)
+macro_rules! define_expected_conversion {
+    ($($(#[$doc:meta])* $name:ident, $tag:ident, $label:literal);* $(;)?) => {
+        $(
+            $(#[$doc])*
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+            pub struct $name<T>(pub T);
+
+            impl<T: AsRef<[u8]>> TaggedSerialize for $name<T> {
+                fn cbor_tag(&self) -> Option<u64> {
+                    Some($tag)
+                }
+            }
+
+            impl<T: AsRef<[u8]>> Serialize for $name<T> {
+                fn serialize<S: serde::Serializer>(
+                    &self,
+                    serializer: S,
+                ) -> std::result::Result<S::Ok, S::Error> {
+                    let bytes = serde_bytes::Bytes::new(self.0.as_ref());
+                    Tagged::new(Some($tag), bytes).serialize(serializer)
+                }
+            }
+
+            impl<'de, T: From<Vec<u8>>> Deserialize<'de> for $name<T> {
+                fn deserialize<D: Deserializer<'de>>(
+                    deserializer: D,
+                ) -> std::result::Result<Self, D::Error> {
+                    let tagged = Tagged::<serde_bytes::ByteBuf>::deserialize(deserializer)?;
+                    match tagged.tag {
+                        None | Some($tag) => Ok($name(T::from(tagged.value.into_vec()))),
+                        Some(tag) => Err(de::Error::custom(format!(
+                            "Expected tag {} ({}), found tag {}",
+                            $tag, $label, tag
+                        ))),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+define_expected_conversion! {
+    /// Bytes with a hint to show them as base64url when converted to text (tag 21)
+    ///
+    /// Serializes as tag 21 around a byte string. `to_json`
+    /// renders the bytes as unpadded base64url. Deserializing accepts the
+    /// byte string with tag 21 or untagged, and rejects other tags.
+    ExpectBase64Url, TAG_EXPECT_BASE64URL, "base64url";
+    /// Bytes with a hint to show them as base64 when converted to text (tag 22)
+    ///
+    /// Serializes as tag 22 around a byte string. `to_json`
+    /// renders the bytes as padded base64. Deserializing accepts the byte
+    /// string with tag 22 or untagged, and rejects other tags.
+    ExpectBase64, TAG_EXPECT_BASE64, "base64";
+    /// Bytes with a hint to show them as hex when converted to text (tag 23)
+    ///
+    /// Serializes as tag 23 around a byte string. `to_json`
+    /// renders the bytes as lowercase hex. Deserializing accepts the byte
+    /// string with tag 23 or untagged, and rejects other tags.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::tags::ExpectBase16;
+    ///
+    /// let hash = ExpectBase16(vec![0xde, 0xad]);
+    /// let cbor = c2pa_cbor::to_vec(&hash).unwrap();
+    /// assert_eq!(cbor, [0xd7, 0x42, 0xde, 0xad]);
+    /// assert_eq!(c2pa_cbor::from_slice::<ExpectBase16<Vec<u8>>>(&cbor).unwrap(), hash);
+    /// ```
+    ExpectBase16, TAG_EXPECT_BASE16, "base16";
+}
+
 // RFC 8746 - Typed array helpers
 
 /// Helper to encode a uint8 array (tag 64)
@@ -318,6 +905,11 @@ pub fn encode_uint8_array<W: Write>(writer: &mut W, data: &[u8]) -> Result<()> {
     encode_tagged(writer, TAG_UINT8_ARRAY, &data)
 }
 
+/// Helper to encode a uint8 clamped array (tag 68)
+pub fn encode_uint8_clamped_array<W: Write>(writer: &mut W, data: &[u8]) -> Result<()> {
+    encode_tagged(writer, TAG_UINT8_CLAMPED_ARRAY, &data)
+}
+
 // Macro to generate typed array encoding functions
 macro_rules! define_typed_array_encoder {
     ($(#[$doc:meta] $name:ident, $tag:ident, $ty:ty, $to_bytes:ident);* $(;)?) => {
@@ -358,6 +950,20 @@ define_typed_array_encoder! {
     encode_uint32le_array, TAG_UINT32LE_ARRAY, u32, to_le_bytes;
     /// Helper to encode a uint64 little-endian array (tag 71)
     encode_uint64le_array, TAG_UINT64LE_ARRAY, u64, to_le_bytes;
+    /// Helper to encode a sint8 array (tag 72)
+    encode_sint8_array, TAG_SINT8_ARRAY, i8, to_be_bytes;
+    /// Helper to encode a sint16 big-endian array (tag 73)
+    encode_sint16be_array, TAG_SINT16BE_ARRAY, i16, to_be_bytes;
+    /// Helper to encode a sint32 big-endian array (tag 74)
+    encode_sint32be_array, TAG_SINT32BE_ARRAY, i32, to_be_bytes;
+    /// Helper to encode a sint64 big-endian array (tag 75)
+    encode_sint64be_array, TAG_SINT64BE_ARRAY, i64, to_be_bytes;
+    /// Helper to encode a sint16 little-endian array (tag 77)
+    encode_sint16le_array, TAG_SINT16LE_ARRAY, i16, to_le_bytes;
+    /// Helper to encode a sint32 little-endian array (tag 78)
+    encode_sint32le_array, TAG_SINT32LE_ARRAY, i32, to_le_bytes;
+    /// Helper to encode a sint64 little-endian array (tag 79)
+    encode_sint64le_array, TAG_SINT64LE_ARRAY, i64, to_le_bytes;
     /// Helper to encode a float32 big-endian array (tag 81)
     encode_float32be_array, TAG_FLOAT32BE_ARRAY, f32, to_be_bytes;
     /// Helper to encode a float64 big-endian array (tag 82)
@@ -366,6 +972,175 @@ define_typed_array_encoder! {
     encode_float32le_array, TAG_FLOAT32LE_ARRAY, f32, to_le_bytes;
     /// Helper to encode a float64 little-endian array (tag 86)
     encode_float64le_array, TAG_FLOAT64LE_ARRAY, f64, to_le_bytes;
+    /// Helper to encode `half::f16` values as a float16 big-endian array (tag 80)
+    encode_f16be_array, TAG_FLOAT16BE_ARRAY, half::f16, to_be_bytes;
+    /// Helper to encode `half::f16` values as a float16 little-endian array (tag 84)
+    encode_f16le_array, TAG_FLOAT16LE_ARRAY, half::f16, to_le_bytes;
+}
+
+/// A float128 typed array (tag 83 or 87), kept as raw bytes
+///
+/// Rust has no stable 128-bit float, so the elements are not converted;
+/// the bytes are carried through unchanged along with their byte order, and
+/// encoding writes the same tag back. Decoding requires tag 83 or 87 and a
+/// length that is a multiple of 16.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::tags::Float128Bytes;
+///
+/// let mut cbor = vec![0xd8, 0x57, 0x50];
+/// cbor.extend([0u8; 16]);
+/// let array: Float128Bytes = c2pa_cbor::from_slice(&cbor).unwrap();
+/// assert!(array.little_endian);
+/// assert_eq!(array.len(), 1);
+/// assert_eq!(c2pa_cbor::to_vec(&array).unwrap(), cbor);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Float128Bytes {
+    /// Whether the elements are little-endian (tag 87) or big-endian (tag 83)
+    pub little_endian: bool,
+    /// The array content, 16 bytes per element
+    pub bytes: Vec<u8>,
+}
+
+impl Float128Bytes {
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        self.bytes.len() / 16
+    }
+
+    /// Whether the array has no elements
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// The raw bytes of each element, in the array's byte order
+    pub fn elements(&self) -> impl Iterator<Item = [u8; 16]> + '_ {
+        self.bytes
+            .chunks_exact(16)
+            .map(|chunk| chunk.try_into().expect("chunk of 16 bytes"))
+    }
+
+    fn tag(&self) -> u64 {
+        if self.little_endian {
+            TAG_FLOAT128LE_ARRAY
+        } else {
+            TAG_FLOAT128BE_ARRAY
+        }
+    }
+}
+
+impl Serialize for Float128Bytes {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        Tagged::new(Some(self.tag()), serde_bytes::Bytes::new(&self.bytes)).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Float128Bytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let tagged = Tagged::<serde_bytes::ByteBuf>::deserialize(deserializer)?;
+        let little_endian = match tagged.tag {
+            Some(TAG_FLOAT128BE_ARRAY) => false,
+            Some(TAG_FLOAT128LE_ARRAY) => true,
+            _ => {
+                return Err(de::Error::custom(
+                    "Expected tag 83 or 87 for a float128 array",
+                ));
+            }
+        };
+        let bytes = tagged.value.into_vec();
+        if bytes.len() % 16 != 0 {
+            return Err(de::Error::custom(
+                "Float128 array length must be a multiple of 16",
+            ));
+        }
+        Ok(Float128Bytes {
+            little_endian,
+            bytes,
+        })
+    }
+}
+
+/// A uint8 clamped array (tag 68), the CBOR form of a JavaScript `Uint8ClampedArray`
+///
+/// The bytes are the same as a plain uint8 array; the tag records that
+/// values were clamped rather than wrapped when the array was filled, so it
+/// is kept distinct from tag 64 on both encode and decode.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::tags::ClampedBytes;
+///
+/// let pixels = ClampedBytes::from_clamped([-20.0, 127.5, 128.5, 300.0]);
+/// assert_eq!(pixels.0, [0, 128, 128, 255]);
+///
+/// let cbor = c2pa_cbor::to_vec(&pixels).unwrap();
+/// assert_eq!(&cbor[..2], [0xd8, 0x44]);
+/// assert_eq!(c2pa_cbor::from_slice::<ClampedBytes>(&cbor).unwrap(), pixels);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ClampedBytes(pub Vec<u8>);
+
+impl ClampedBytes {
+    /// Convert numbers the way a `Uint8ClampedArray` stores them
+    ///
+    /// Values are clamped to 0..=255 and rounded half to even; NaN becomes 0.
+    pub fn from_clamped(values: impl IntoIterator<Item = f64>) -> Self {
+        ClampedBytes(
+            values
+                .into_iter()
+                .map(|v| {
+                    if v.is_nan() {
+                        0
+                    } else {
+                        v.clamp(0.0, 255.0).round_ties_even() as u8
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+impl From<Vec<u8>> for ClampedBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        ClampedBytes(bytes)
+    }
+}
+
+impl Serialize for ClampedBytes {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        Tagged::new(
+            Some(TAG_UINT8_CLAMPED_ARRAY),
+            serde_bytes::Bytes::new(&self.0),
+        )
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ClampedBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        // ByteBuf also accepts the array of byte values written by encode_uint8_clamped_array
+        let tagged = Tagged::<serde_bytes::ByteBuf>::deserialize(deserializer)?;
+        if tagged.tag != Some(TAG_UINT8_CLAMPED_ARRAY) {
+            return Err(de::Error::custom(
+                "Expected tag 68 for a uint8 clamped array",
+            ));
+        }
+        Ok(ClampedBytes(tagged.value.into_vec()))
+    }
+}
+
+impl TaggedSerialize for ClampedBytes {
+    fn cbor_tag(&self) -> Option<u64> {
+        Some(TAG_UINT8_CLAMPED_ARRAY)
+    }
 }
 
 #[cfg(test)]
@@ -413,6 +1188,147 @@ mod tests {
         assert_eq!(tagged.value, 42);
     }
 
+    #[test]
+    fn test_tagged_from_slice_records_tag() {
+        // Tags outside the static marker table round-trip too
+        let tagged = Tagged::new(Some(1234), vec![1u8, 2]);
+        let cbor = crate::to_vec(&tagged).unwrap();
+        assert_eq!(&cbor[..3], [0xd9, 0x04, 0xd2]);
+        assert_eq!(crate::from_slice::<Tagged<Vec<u8>>>(&cbor).unwrap(), tagged);
+
+        let untagged: Tagged<String> =
+            crate::from_slice(&crate::to_vec(&"plain").unwrap()).unwrap();
+        assert_eq!(untagged, Tagged::new(None, "plain".to_string()));
+
+        // A tagged map is the content of the tag, not a tag/value pair
+        let mut map = std::collections::BTreeMap::new();
+        map.insert("a".to_string(), 1u8);
+        let cbor = crate::to_vec(&Tagged::new(Some(500), map.clone())).unwrap();
+        let decoded: Tagged<std::collections::BTreeMap<String, u8>> =
+            crate::from_slice(&cbor).unwrap();
+        assert_eq!(decoded, Tagged::new(Some(500), map));
+    }
+
+    #[test]
+    fn test_current_cbor_tag_scoping() {
+        // 24([32("x"), "y"]): only the outer array and the first element are tagged
+        let cbor = [0xd8, 0x18, 0x82, 0xd8, 0x20, 0x61, b'x', 0x61, b'y'];
+        let decoded: Tagged<Vec<Tagged<String>>> = crate::from_slice(&cbor).unwrap();
+        assert_eq!(decoded.tag, Some(24));
+        assert_eq!(
+            decoded.value,
+            vec![
+                Tagged::new(Some(32), "x".to_string()),
+                Tagged::new(None, "y".to_string())
+            ]
+        );
+
+        // Map keys and values are not covered by the tag of the map
+        let cbor = [0xd9, 0x01, 0x00, 0xa1, 0x61, b'k', 0x01];
+        let decoded: Tagged<std::collections::BTreeMap<Tagged<String>, Tagged<u8>>> =
+            crate::from_slice(&cbor).unwrap();
+        assert_eq!(decoded.tag, Some(256));
+        let (key, value) = decoded.value.into_iter().next().unwrap();
+        assert_eq!((key.tag, value.tag), (None, None));
+
+        assert_eq!(current_cbor_tag(), None);
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Time {
+        Text(String),
+        Epoch(i64),
+    }
+
+    impl TaggedSerialize for Time {
+        fn cbor_tag(&self) -> Option<u64> {
+            match self {
+                Time::Text(_) => Some(TAG_DATETIME_STRING),
+                Time::Epoch(_) => Some(TAG_EPOCH_DATETIME),
+            }
+        }
+    }
+
+    #[test]
+    fn test_tagged_serialize_encoder_and_decoder() {
+        let mut encoder = Encoder::new(Vec::new());
+        encoder.encode_tagged(&Time::Epoch(10)).unwrap();
+        encoder.encode_tagged(&Time::Text("x".to_string())).unwrap();
+        let cbor = encoder.into_inner();
+        assert_eq!(cbor, [0xc1, 0x0a, 0xc0, 0x61, b'x']);
+
+        let mut decoder = Decoder::from_slice(&cbor);
+        assert_eq!(decoder.decode_tagged::<Time>().unwrap(), Time::Epoch(10));
+        assert_eq!(
+            decoder.decode_tagged::<Time>().unwrap(),
+            Time::Text("x".to_string())
+        );
+        decoder.end().unwrap();
+
+        // Epoch number under tag 0, and an untagged number
+        let err = Decoder::from_slice(&[0xc0, 0x0a])
+            .decode_tagged::<Time>()
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("expected tag 1, found tag 0"),
+            "{}",
+            err
+        );
+        assert!(
+            Decoder::from_slice(&[0x0a])
+                .decode_tagged::<Time>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_tagged_serialize_declared_field() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Event {
+            #[serde(with = "declared")]
+            at: Time,
+            #[serde(with = "declared")]
+            until: Box<Time>,
+        }
+
+        let event = Event {
+            at: Time::Epoch(1),
+            until: Box::new(Time::Text("t".to_string())),
+        };
+        let cbor = crate::to_vec(&event).unwrap();
+        assert_eq!(&cbor[4..6], [0xc1, 0x01]);
+        assert_eq!(crate::from_slice::<Event>(&cbor).unwrap(), event);
+
+        // {"at": 0(1), "until": 0("t")}
+        let mut wrong = cbor.clone();
+        wrong[4] = 0xc0;
+        assert!(crate::from_slice::<Event>(&wrong).is_err());
+    }
+
+    #[test]
+    fn test_float128_bytes_round_trip() {
+        // 1.0 as a big-endian binary128
+        let mut one = [0u8; 16];
+        one[..2].copy_from_slice(&[0x3f, 0xff]);
+        let array = Float128Bytes {
+            little_endian: false,
+            bytes: [one, [0; 16]].concat(),
+        };
+        let cbor = crate::to_vec(&array).unwrap();
+        assert_eq!(&cbor[..4], [0xd8, 0x53, 0x58, 0x20]);
+        let decoded: Float128Bytes = crate::from_slice(&cbor).unwrap();
+        assert_eq!(decoded, array);
+        assert_eq!(decoded.elements().collect::<Vec<_>>(), [one, [0; 16]]);
+
+        // Wrong tag, missing tag and a partial element are rejected
+        let mut cbor = vec![0xd8, 0x52, 0x50];
+        cbor.extend([0u8; 16]);
+        assert!(crate::from_slice::<Float128Bytes>(&cbor).is_err());
+        assert!(crate::from_slice::<Float128Bytes>(&cbor[2..]).is_err());
+        assert!(crate::from_slice::<Float128Bytes>(&[0xd8, 0x53, 0x41, 0x00]).is_err());
+    }
+
     // ========== Helper Function Tests ==========
 
     #[test]
@@ -483,6 +1399,103 @@ mod tests {
         assert_eq!(tag, TAG_BASE64);
     }
 
+    #[test]
+    fn test_encode_mime_and_lang_string() {
+        let mut buf = Vec::new();
+        encode_mime(&mut buf, "MIME-Version: 1.0\r\n\r\nbody").unwrap();
+        assert_eq!(&buf[..2], [0xd8, 0x24]);
+        let message: MimeMessage = crate::from_slice(&buf).unwrap();
+        assert!(message.0.ends_with("body"));
+
+        let mut buf = Vec::new();
+        encode_lang_string(&mut buf, "en", "Sunset").unwrap();
+        assert_eq!(
+            buf,
+            [
+                0xd8, 0x26, 0x82, 0x62, b'e', b'n', 0x66, b'S', b'u', b'n', b's', b'e', b't'
+            ]
+        );
+        let caption: LangString = crate::from_slice(&buf).unwrap();
+        assert_eq!(caption, LangString::new("en", "Sunset"));
+    }
+
+    #[test]
+    fn test_lang_string_captions_round_trip() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Metadata {
+            captions: Vec<LangString>,
+            raw: MimeMessage,
+        }
+
+        let metadata = Metadata {
+            captions: vec![
+                LangString::new("en", "Sunset"),
+                LangString::new("fr-CA", "Coucher de soleil"),
+            ],
+            raw: MimeMessage("Subject: x\r\n\r\n".to_string()),
+        };
+        let cbor = crate::to_vec(&metadata).unwrap();
+        assert_eq!(crate::from_slice::<Metadata>(&cbor).unwrap(), metadata);
+    }
+
+    #[test]
+    fn test_lang_string_rejects_malformed() {
+        // Untagged, wrong tag, wrong arity and an empty language tag
+        let cases: [&[u8]; 5] = [
+            &[0x82, 0x62, b'e', b'n', 0x61, b'x'],
+            &[0xd8, 0x20, 0x82, 0x62, b'e', b'n', 0x61, b'x'],
+            &[0xd8, 0x26, 0x81, 0x62, b'e', b'n'],
+            &[0xd8, 0x26, 0x83, 0x62, b'e', b'n', 0x61, b'x', 0x61, b'y'],
+            &[0xd8, 0x26, 0x82, 0x60, 0x61, b'x'],
+        ];
+        for cbor in cases {
+            assert!(
+                crate::from_slice::<LangString>(cbor).is_err(),
+                "{:02x?}",
+                cbor
+            );
+        }
+        assert!(crate::from_slice::<MimeMessage>(&[0x61, b'x']).is_err());
+    }
+
+    #[test]
+    fn test_expected_conversion_wrappers() {
+        let cbor = crate::to_vec(&ExpectBase64Url([0xfbu8, 0xff])).unwrap();
+        assert_eq!(cbor, [0xd5, 0x42, 0xfb, 0xff]);
+        let cbor = crate::to_vec(&ExpectBase64(b"hi".as_slice())).unwrap();
+        assert_eq!(cbor, [0xd6, 0x42, b'h', b'i']);
+
+        // Tag 23 or untagged bytes decode; other tags do not
+        let decoded: ExpectBase16<Vec<u8>> = crate::from_slice(&[0x41, 0x01]).unwrap();
+        assert_eq!(decoded, ExpectBase16(vec![1]));
+        assert!(crate::from_slice::<ExpectBase16<Vec<u8>>>(&[0xd6, 0x41, 0x01]).is_err());
+        assert!(crate::from_slice::<ExpectBase64<Vec<u8>>>(&[0xd6, 0x41, 0x01]).is_ok());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_expected_conversion_to_json() {
+        #[derive(Serialize)]
+        struct Hashes {
+            url: ExpectBase64Url<Vec<u8>>,
+            std: ExpectBase64<Vec<u8>>,
+            hex: ExpectBase16<Vec<u8>>,
+            plain: serde_bytes::ByteBuf,
+        }
+
+        let hashes = Hashes {
+            url: ExpectBase64Url(vec![0xfb, 0xff]),
+            std: ExpectBase64(vec![0xfb, 0xff]),
+            hex: ExpectBase16(vec![0xfb, 0xff]),
+            plain: serde_bytes::ByteBuf::from(vec![0xfb, 0xff]),
+        };
+        let value = crate::to_value(&hashes).unwrap();
+        assert_eq!(
+            crate::to_json(&value),
+            serde_json::json!({"url": "-_8", "std": "+/8=", "hex": "fbff", "plain": "-_8"})
+        );
+    }
+
     #[test]
     fn test_encode_uint8_array() {
         let data: Vec<u8> = vec![1, 2, 3, 4, 5];
@@ -618,6 +1631,137 @@ mod tests {
         assert_eq!(tag, TAG_FLOAT64LE_ARRAY);
     }
 
+    #[test]
+    fn test_encode_sint_arrays() {
+        let mut buf = Vec::new();
+        encode_sint8_array(&mut buf, &[-1, 127]).unwrap();
+        assert_eq!(&buf[..2], [0xd8, 0x48]);
+        assert_eq!(crate::decode_typed_array::<i8>(&buf).unwrap(), [-1, 127]);
+
+        let mut buf = Vec::new();
+        encode_sint16be_array(&mut buf, &[-2, 0x0102]).unwrap();
+        let bytes: Vec<u8> = crate::Decoder::from_slice(&buf[2..]).decode().unwrap();
+        assert_eq!(bytes, [0xff, 0xfe, 0x01, 0x02]);
+        assert_eq!(
+            crate::decode_typed_array::<i16>(&buf).unwrap(),
+            [-2, 0x0102]
+        );
+
+        let mut buf = Vec::new();
+        encode_sint16le_array(&mut buf, &[-2]).unwrap();
+        let bytes: Vec<u8> = crate::Decoder::from_slice(&buf[2..]).decode().unwrap();
+        assert_eq!(bytes, [0xfe, 0xff]);
+
+        type Encode = fn(&mut Vec<u8>) -> Result<()>;
+        let cases: [(Encode, u64); 4] = [
+            (
+                |w| encode_sint32be_array(w, &[i32::MIN]),
+                TAG_SINT32BE_ARRAY,
+            ),
+            (
+                |w| encode_sint32le_array(w, &[i32::MIN]),
+                TAG_SINT32LE_ARRAY,
+            ),
+            (
+                |w| encode_sint64be_array(w, &[i64::MIN]),
+                TAG_SINT64BE_ARRAY,
+            ),
+            (
+                |w| encode_sint64le_array(w, &[i64::MIN]),
+                TAG_SINT64LE_ARRAY,
+            ),
+        ];
+        for (encode, expected_tag) in cases {
+            let mut buf = Vec::new();
+            encode(&mut buf).unwrap();
+            let mut decoder = crate::Decoder::from_slice(&buf);
+            assert_eq!(decoder.read_tag().unwrap(), expected_tag);
+        }
+        let mut buf = Vec::new();
+        encode_sint64le_array(&mut buf, &[i64::MIN, -1]).unwrap();
+        assert_eq!(
+            crate::decode_typed_array::<i64>(&buf).unwrap(),
+            [i64::MIN, -1]
+        );
+        let mut buf = Vec::new();
+        encode_sint32be_array(&mut buf, &[i32::MIN, 5]).unwrap();
+        assert_eq!(
+            crate::decode_typed_array::<i32>(&buf).unwrap(),
+            [i32::MIN, 5]
+        );
+    }
+
+    #[test]
+    fn test_iana_tags() {
+        use iana::IanaTag;
+
+        assert!(
+            IanaTag::ALL
+                .windows(2)
+                .all(|w| w[0].to_u64() < w[1].to_u64())
+        );
+        for &tag in IanaTag::ALL {
+            assert_eq!(IanaTag::from_u64(tag.to_u64()), Some(tag));
+            assert!(!tag.description().is_empty());
+        }
+        assert_eq!(IanaTag::ALL.len(), 45);
+        assert_eq!(
+            IanaTag::from_u64(iana::SELF_DESCRIBE),
+            Some(IanaTag::SelfDescribe)
+        );
+        assert_eq!(iana::FLOAT128LE_ARRAY, 87);
+        assert_eq!(IanaTag::from_u64(6), None);
+    }
+
+    #[test]
+    fn test_uint8_clamped_array() {
+        let mut cbor = Vec::new();
+        encode_uint8_clamped_array(&mut cbor, &[0, 255]).unwrap();
+        assert_eq!(&cbor[..2], [0xd8, 0x44]);
+        assert_eq!(
+            crate::from_slice::<ClampedBytes>(&cbor).unwrap(),
+            ClampedBytes(vec![0, 255])
+        );
+
+        // Tag 64 is a different type, and the tag survives a round trip
+        let mut plain = Vec::new();
+        encode_uint8_array(&mut plain, &[0, 255]).unwrap();
+        assert!(crate::from_slice::<ClampedBytes>(&plain).is_err());
+        let bytes = crate::to_vec(&ClampedBytes(vec![1, 2])).unwrap();
+        assert_eq!(bytes, [0xd8, 0x44, 0x42, 0x01, 0x02]);
+        let tagged: Tagged<serde_bytes::ByteBuf> = crate::from_slice(&bytes).unwrap();
+        assert_eq!(tagged.tag, Some(68));
+
+        assert_eq!(
+            ClampedBytes::from_clamped([f64::NAN, 0.5, 1.5, 254.6, f64::INFINITY]).0,
+            [0, 0, 2, 255, 255]
+        );
+    }
+
+    #[test]
+    fn test_encode_f16_arrays() {
+        use half::f16;
+
+        let values = [f16::from_f32(1.0), f16::from_f32(-0.5), f16::INFINITY];
+        let mut be = Vec::new();
+        encode_f16be_array(&mut be, &values).unwrap();
+        let mut raw = Vec::new();
+        encode_float16be_array(&mut raw, &[0x3c00, 0xb800, 0x7c00]).unwrap();
+        assert_eq!(be, raw);
+
+        let mut le = Vec::new();
+        encode_f16le_array(&mut le, &values).unwrap();
+        assert_eq!(&le[..2], [0xd8, 0x54]);
+
+        for cbor in [&be, &le] {
+            assert_eq!(crate::decode_typed_array::<f16>(cbor).unwrap(), values);
+            assert_eq!(
+                crate::typed_array::decode_f16_array_as_f32(cbor).unwrap(),
+                [1.0, -0.5, f32::INFINITY]
+            );
+        }
+    }
+
     #[test]
     fn test_encode_tagged_roundtrip() {
         // Test the generic encode_tagged function
@@ -0,0 +1,126 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Streaming conversion between JSON and CBOR
+//!
+//! Both directions go through `serde_transcode`, so no intermediate value is
+//! built. `&mut Decoder<R>` and `&mut Encoder<W>` can also be passed to
+//! `serde_transcode::transcode` directly to pair them with other formats.
+//!
+//! CBOR that has no JSON counterpart is written the way `serde_json` writes
+//! the corresponding serde data: tags are dropped, byte strings become arrays
+//! of numbers, `undefined` becomes `null`, and non-finite floats become
+//! `null`. Use [`to_json`](crate::to_json) for the RFC 8949 §6.1 mapping.
+
+use std::io::{BufReader, Read, Write};
+
+use crate::{DEFAULT_MAX_ALLOCATION, Decoder, Encoder, Error, Result};
+
+fn json_error(e: serde_json::Error) -> Error {
+    if e.is_io() {
+        Error::Io(e.into())
+    } else {
+        Error::Message(e.to_string())
+    }
+}
+
+/// Convert a single JSON document read from `reader` to CBOR written to `writer`
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::transcode_json_to_cbor;
+///
+/// let mut cbor = Vec::new();
+/// transcode_json_to_cbor(&br#"{"a": [1, null]}"#[..], &mut cbor).unwrap();
+/// assert_eq!(cbor, [0xa1, 0x61, b'a', 0x82, 0x01, 0xf6]);
+/// ```
+pub fn transcode_json_to_cbor<R: Read, W: Write>(reader: R, writer: W) -> Result<()> {
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+    let mut encoder = Encoder::new(writer);
+    serde_transcode::transcode(&mut deserializer, &mut encoder)?;
+    deserializer.end().map_err(json_error)
+}
+
+/// Convert a single CBOR item read from `reader` to JSON written to `writer`
+///
+/// The default allocation limit applies, and trailing input is an error.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::transcode_cbor_to_json;
+///
+/// let mut json = Vec::new();
+/// transcode_cbor_to_json(&[0xa1, 0x61, b'a', 0x82, 0x01, 0xf6][..], &mut json).unwrap();
+/// assert_eq!(json, br#"{"a":[1,null]}"#);
+/// ```
+pub fn transcode_cbor_to_json<R: Read, W: Write>(reader: R, writer: W) -> Result<()> {
+    let mut decoder =
+        Decoder::new(BufReader::new(reader)).with_max_allocation(DEFAULT_MAX_ALLOCATION);
+    let mut serializer = serde_json::Serializer::new(writer);
+    serde_transcode::transcode(&mut decoder, &mut serializer).map_err(json_error)?;
+    decoder.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json_to_cbor(json: &str) -> Result<Vec<u8>> {
+        let mut cbor = Vec::new();
+        transcode_json_to_cbor(json.as_bytes(), &mut cbor)?;
+        Ok(cbor)
+    }
+
+    fn cbor_to_json(cbor: &[u8]) -> Result<String> {
+        let mut json = Vec::new();
+        transcode_cbor_to_json(cbor, &mut json)?;
+        Ok(String::from_utf8(json).unwrap())
+    }
+
+    #[test]
+    fn test_transcode_round_trip() {
+        let json =
+            r#"{"name":"c2pa","size":-42,"ratio":0.5,"tags":["a","b"],"extra":null,"ok":true}"#;
+        let cbor = json_to_cbor(json).unwrap();
+        assert_eq!(cbor_to_json(&cbor).unwrap(), json);
+
+        let value: serde_json::Value = crate::from_slice(&cbor).unwrap();
+        assert_eq!(
+            value,
+            serde_json::from_str::<serde_json::Value>(json).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_transcode_cbor_only_items() {
+        // 32("https://a.b") keeps its content, h'0102' becomes an array
+        let cbor = [
+            0x82, 0xd8, 0x20, 0x6b, b'h', b't', b't', b'p', b's', b':', b'/', b'/', b'a', b'.',
+            b'b', 0x42, 0x01, 0x02,
+        ];
+        assert_eq!(cbor_to_json(&cbor).unwrap(), r#"["https://a.b",[1,2]]"#);
+        // Integer keys are written as strings
+        assert_eq!(cbor_to_json(&[0xa1, 0x01, 0xf7]).unwrap(), r#"{"1":null}"#);
+    }
+
+    #[test]
+    fn test_transcode_rejects_trailing_input() {
+        assert!(json_to_cbor("[1] [2]").is_err());
+        assert!(matches!(
+            cbor_to_json(&[0x01, 0x02]),
+            Err(Error::TrailingData)
+        ));
+        assert!(json_to_cbor("{\"a\":").is_err());
+        assert!(cbor_to_json(&[0x82, 0x01]).is_err());
+    }
+}
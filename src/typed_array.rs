@@ -0,0 +1,939 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! RFC 8746 typed arrays
+//!
+//! A typed array is a byte string holding packed numbers, tagged with the
+//! element type and byte order. The `encode_*_array` helpers in
+//! [`tags`](crate::tags) write them; [`decode_typed_array`] reads one back
+//! into a `Vec` of native numbers, checking that the tag matches the
+//! element type and that the length is a whole number of elements. Either
+//! byte order is accepted.
+//!
+//! [`TypedArray<T>`] does both through serde, so it can be used as a field
+//! of a derived struct.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::{decode_typed_array, encode_uint16le_array};
+//!
+//! let mut cbor = Vec::new();
+//! encode_uint16le_array(&mut cbor, &[1, 0x0203]).unwrap();
+//! assert_eq!(decode_typed_array::<u16>(&cbor).unwrap(), [1, 0x0203]);
+//!
+//! // The tag says uint16, so reading it as u32 fails
+//! assert!(decode_typed_array::<u32>(&cbor).is_err());
+//! ```
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, Visitor, value::SeqAccessDeserializer},
+};
+use serde_bytes::Bytes;
+
+use crate::{
+    Error, Result,
+    constants::*,
+    from_slice,
+    tags::{Tagged, current_cbor_tag},
+};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A number that can be an element of an RFC 8746 typed array
+///
+/// Implemented for `u8`, `u16`, `u32`, `u64`, `i8`, `i16`, `i32`, `i64`,
+/// `half::f16`, `f32` and `f64`.
+pub trait TypedArrayElement: Copy + private::Sealed {
+    /// Size of one element in bytes
+    const SIZE: usize;
+    /// Tag for a big-endian array of this type
+    const BIG_ENDIAN_TAG: u64;
+    /// Tag for a little-endian array of this type
+    const LITTLE_ENDIAN_TAG: u64;
+
+    /// Read one element from `SIZE` big-endian bytes
+    fn from_be_slice(bytes: &[u8]) -> Self;
+    /// Read one element from `SIZE` little-endian bytes
+    fn from_le_slice(bytes: &[u8]) -> Self;
+    /// Append the big-endian bytes of this element
+    fn write_be(self, out: &mut Vec<u8>);
+    /// Append the little-endian bytes of this element
+    fn write_le(self, out: &mut Vec<u8>);
+
+    /// Whether `tag` holds this type, and if so whether it is little-endian
+    fn byte_order(tag: u64) -> Option<bool> {
+        if tag == Self::BIG_ENDIAN_TAG {
+            Some(false)
+        } else if tag == Self::LITTLE_ENDIAN_TAG {
+            Some(true)
+        } else {
+            None
+        }
+    }
+}
+
+macro_rules! impl_typed_array_element {
+    ($($ty:ty, $be:ident, $le:ident);* $(;)?) => {
+        $(
+            impl private::Sealed for $ty {}
+
+            impl TypedArrayElement for $ty {
+                const SIZE: usize = std::mem::size_of::<$ty>();
+                const BIG_ENDIAN_TAG: u64 = $be;
+                const LITTLE_ENDIAN_TAG: u64 = $le;
+
+                fn from_be_slice(bytes: &[u8]) -> Self {
+                    <$ty>::from_be_bytes(bytes.try_into().expect("element size"))
+                }
+
+                fn from_le_slice(bytes: &[u8]) -> Self {
+                    <$ty>::from_le_bytes(bytes.try_into().expect("element size"))
+                }
+
+                fn write_be(self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_be_bytes());
+                }
+
+                fn write_le(self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_typed_array_element! {
+    u16, TAG_UINT16BE_ARRAY, TAG_UINT16LE_ARRAY;
+    u32, TAG_UINT32BE_ARRAY, TAG_UINT32LE_ARRAY;
+    u64, TAG_UINT64BE_ARRAY, TAG_UINT64LE_ARRAY;
+    i8, TAG_SINT8_ARRAY, TAG_SINT8_ARRAY;
+    i16, TAG_SINT16BE_ARRAY, TAG_SINT16LE_ARRAY;
+    i32, TAG_SINT32BE_ARRAY, TAG_SINT32LE_ARRAY;
+    i64, TAG_SINT64BE_ARRAY, TAG_SINT64LE_ARRAY;
+    f32, TAG_FLOAT32BE_ARRAY, TAG_FLOAT32LE_ARRAY;
+    f64, TAG_FLOAT64BE_ARRAY, TAG_FLOAT64LE_ARRAY;
+    half::f16, TAG_FLOAT16BE_ARRAY, TAG_FLOAT16LE_ARRAY;
+}
+
+impl private::Sealed for u8 {}
+
+impl TypedArrayElement for u8 {
+    const SIZE: usize = 1;
+    const BIG_ENDIAN_TAG: u64 = TAG_UINT8_ARRAY;
+    const LITTLE_ENDIAN_TAG: u64 = TAG_UINT8_ARRAY;
+
+    fn from_be_slice(bytes: &[u8]) -> Self {
+        bytes[0]
+    }
+
+    fn from_le_slice(bytes: &[u8]) -> Self {
+        bytes[0]
+    }
+
+    fn write_be(self, out: &mut Vec<u8>) {
+        out.push(self);
+    }
+
+    fn write_le(self, out: &mut Vec<u8>) {
+        out.push(self);
+    }
+
+    // Clamped arrays hold the same bytes; clamping only matters when writing
+    fn byte_order(tag: u64) -> Option<bool> {
+        matches!(tag, TAG_UINT8_ARRAY | TAG_UINT8_CLAMPED_ARRAY).then_some(false)
+    }
+}
+
+/// The content of a typed array: a byte string, or an array of byte values
+pub(crate) struct PackedBytes(pub(crate) Vec<u8>);
+
+impl<'de> Deserialize<'de> for PackedBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct PackedVisitor;
+
+        impl<'de> Visitor<'de> for PackedVisitor {
+            type Value = PackedBytes;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a byte string")
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> std::result::Result<PackedBytes, A::Error> {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+                while let Some(b) = seq.next_element::<u8>()? {
+                    bytes.push(b);
+                }
+                Ok(PackedBytes(bytes))
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> std::result::Result<PackedBytes, E> {
+                Ok(PackedBytes(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E: de::Error>(
+                self,
+                v: Vec<u8>,
+            ) -> std::result::Result<PackedBytes, E> {
+                Ok(PackedBytes(v))
+            }
+        }
+
+        deserializer.deserialize_bytes(PackedVisitor)
+    }
+}
+
+/// Unpack the content of a typed array
+pub(crate) fn elements_from_bytes<T: TypedArrayElement>(
+    bytes: &[u8],
+    little_endian: bool,
+) -> Result<Vec<T>> {
+    if !bytes.len().is_multiple_of(T::SIZE) {
+        return Err(Error::Syntax(format!(
+            "Typed array length {} is not a multiple of the element size {}",
+            bytes.len(),
+            T::SIZE
+        )));
+    }
+    let read = if little_endian {
+        T::from_le_slice
+    } else {
+        T::from_be_slice
+    };
+    Ok(bytes.chunks_exact(T::SIZE).map(read).collect())
+}
+
+/// Decode a tagged typed array into native numbers
+///
+/// `cbor` must hold exactly one byte string tagged with either the
+/// big-endian or little-endian typed array tag for `T` (tag 64 or 68 for
+/// `u8`, 72 for `i8`). An array of byte values is read like a byte string,
+/// since that is what the `encode_*_array` helpers write.
+pub fn decode_typed_array<T: TypedArrayElement>(cbor: &[u8]) -> Result<Vec<T>> {
+    let tagged: Tagged<PackedBytes> = from_slice(cbor)?;
+    let tag = tagged
+        .tag
+        .ok_or_else(|| Error::Syntax("Expected a tagged typed array".to_string()))?;
+    let little_endian = T::byte_order(tag).ok_or_else(|| {
+        Error::Syntax(format!(
+            "Tag {} is not a typed array of {}",
+            tag,
+            std::any::type_name::<T>()
+        ))
+    })?;
+    elements_from_bytes(&tagged.value.0, little_endian)
+}
+
+/// Decode a float16 typed array (tag 80 or 84), widening the elements to `f32`
+///
+/// Widening is exact, so this is a convenience for code that has no use for
+/// `half::f16` itself.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::{encode_float16le_array, typed_array::decode_f16_array_as_f32};
+///
+/// // 1.0 and 65504 (the largest finite f16) as raw bits
+/// let mut cbor = Vec::new();
+/// encode_float16le_array(&mut cbor, &[0x3c00, 0x7bff]).unwrap();
+/// assert_eq!(decode_f16_array_as_f32(&cbor).unwrap(), [1.0, 65504.0]);
+/// ```
+pub fn decode_f16_array_as_f32(cbor: &[u8]) -> Result<Vec<f32>> {
+    Ok(decode_typed_array::<half::f16>(cbor)?
+        .into_iter()
+        .map(f32::from)
+        .collect())
+}
+
+/// Elements of a typed array read by [`Decoder::as_typed_slice`](crate::Decoder)
+///
+/// Dereferences to `&[T]` either way.
+#[cfg(feature = "zero_copy")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedArrayRef<'a, T> {
+    /// Borrowed straight from the input
+    Borrowed(&'a [T]),
+    /// Converted because of byte order, alignment or encoding
+    Owned(Vec<T>),
+}
+
+#[cfg(feature = "zero_copy")]
+impl<T: Clone> TypedArrayRef<'_, T> {
+    /// Whether the elements were borrowed without copying
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self, TypedArrayRef::Borrowed(_))
+    }
+
+    /// Return the elements as a `Vec`, copying if borrowed
+    pub fn into_owned(self) -> Vec<T> {
+        match self {
+            TypedArrayRef::Borrowed(elements) => elements.to_vec(),
+            TypedArrayRef::Owned(elements) => elements,
+        }
+    }
+}
+
+#[cfg(feature = "zero_copy")]
+impl<T> std::ops::Deref for TypedArrayRef<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match self {
+            TypedArrayRef::Borrowed(elements) => elements,
+            TypedArrayRef::Owned(elements) => elements,
+        }
+    }
+}
+
+/// A typed array that serializes as an RFC 8746 tag and packed byte string
+///
+/// Serializing writes the tag for `T` in the array's byte order followed by
+/// the packed elements. Deserializing accepts a typed array with either
+/// byte order for `T`, or a plain untagged array of numbers (as written by
+/// `Vec<T>`, or found in JSON); the byte order is then the native one.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::TypedArray;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, Debug, PartialEq)]
+/// struct Samples {
+///     levels: TypedArray<f32>,
+/// }
+///
+/// let samples = Samples {
+///     levels: TypedArray::big_endian(vec![1.0, 0.5]),
+/// };
+/// let cbor = c2pa_cbor::to_vec(&samples).unwrap();
+/// // Tag 81 (float32 big-endian) around 8 bytes
+/// assert_eq!(&cbor[8..11], [0xd8, 0x51, 0x48]);
+/// assert_eq!(c2pa_cbor::from_slice::<Samples>(&cbor).unwrap(), samples);
+///
+/// // A plain array of numbers is accepted too
+/// let plain = c2pa_cbor::to_vec(&vec![1.0f32, 0.5]).unwrap();
+/// let levels: TypedArray<f32> = c2pa_cbor::from_slice(&plain).unwrap();
+/// assert_eq!(levels.values, [1.0, 0.5]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TypedArray<T> {
+    /// The elements
+    pub values: Vec<T>,
+    /// Whether to write the elements little-endian rather than big-endian
+    pub little_endian: bool,
+}
+
+impl<T: TypedArrayElement> TypedArray<T> {
+    /// A typed array in the native byte order of this target
+    pub fn new(values: Vec<T>) -> Self {
+        Self::with_order(values, cfg!(target_endian = "little"))
+    }
+
+    /// A big-endian typed array
+    pub fn big_endian(values: Vec<T>) -> Self {
+        Self::with_order(values, false)
+    }
+
+    /// A little-endian typed array
+    pub fn little_endian(values: Vec<T>) -> Self {
+        Self::with_order(values, true)
+    }
+
+    /// Single-byte types have one tag, which is read back as big-endian
+    fn with_order(values: Vec<T>, little_endian: bool) -> Self {
+        TypedArray {
+            values,
+            little_endian: little_endian && T::LITTLE_ENDIAN_TAG != T::BIG_ENDIAN_TAG,
+        }
+    }
+
+    /// The tag written for this array
+    pub fn tag(&self) -> u64 {
+        if self.little_endian {
+            T::LITTLE_ENDIAN_TAG
+        } else {
+            T::BIG_ENDIAN_TAG
+        }
+    }
+
+    /// Return the elements
+    pub fn into_inner(self) -> Vec<T> {
+        self.values
+    }
+}
+
+impl<T: TypedArrayElement> From<Vec<T>> for TypedArray<T> {
+    fn from(values: Vec<T>) -> Self {
+        TypedArray::new(values)
+    }
+}
+
+impl<T: TypedArrayElement> Serialize for TypedArray<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serialize_packed(&self.values, self.tag(), self.little_endian, serializer)
+    }
+}
+
+/// Write `values` as a typed array with `tag`
+fn serialize_packed<T: TypedArrayElement, S: Serializer>(
+    values: &[T],
+    tag: u64,
+    little_endian: bool,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    let mut packed = Vec::with_capacity(values.len() * T::SIZE);
+    for &value in values {
+        if little_endian {
+            value.write_le(&mut packed);
+        } else {
+            value.write_be(&mut packed);
+        }
+    }
+    Tagged::new(Some(tag), Bytes::new(&packed)).serialize(serializer)
+}
+
+/// `#[serde(with = "c2pa_cbor::typed_array::big_endian")]` for `Vec<T>` fields
+///
+/// Writes the field as a big-endian [`TypedArray`] and reads it back the way
+/// `TypedArray` does, accepting either byte order or a plain array.
+///
+/// # Example
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, Debug, PartialEq)]
+/// struct Samples {
+///     #[serde(with = "c2pa_cbor::typed_array::big_endian")]
+///     levels: Vec<f32>,
+/// }
+///
+/// let samples = Samples { levels: vec![1.0, 0.5] };
+/// let cbor = c2pa_cbor::to_vec(&samples).unwrap();
+/// // Tag 81 (float32 big-endian) around 8 bytes
+/// assert_eq!(&cbor[8..11], [0xd8, 0x51, 0x48]);
+/// assert_eq!(c2pa_cbor::from_slice::<Samples>(&cbor).unwrap(), samples);
+/// ```
+pub mod big_endian {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::{TypedArray, TypedArrayElement};
+
+    /// Serialize `values` as a big-endian typed array
+    pub fn serialize<T, S>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: TypedArrayElement,
+        S: Serializer,
+    {
+        super::serialize_packed(values, T::BIG_ENDIAN_TAG, false, serializer)
+    }
+
+    /// Deserialize a typed array of either byte order, or a plain array
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        T: TypedArrayElement + Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        TypedArray::deserialize(deserializer).map(TypedArray::into_inner)
+    }
+}
+
+/// `#[serde(with = "c2pa_cbor::typed_array::little_endian")]` for `Vec<T>` fields
+///
+/// Like [`big_endian`], but writes the elements little-endian. Single-byte
+/// elements have one tag, which is the big-endian one.
+pub mod little_endian {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::{TypedArray, TypedArrayElement};
+
+    /// Serialize `values` as a little-endian typed array
+    pub fn serialize<T, S>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: TypedArrayElement,
+        S: Serializer,
+    {
+        super::serialize_packed(values, T::LITTLE_ENDIAN_TAG, true, serializer)
+    }
+
+    /// Deserialize a typed array of either byte order, or a plain array
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        T: TypedArrayElement + Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        TypedArray::deserialize(deserializer).map(TypedArray::into_inner)
+    }
+}
+
+impl<'de, T: TypedArrayElement + Deserialize<'de>> Deserialize<'de> for TypedArray<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct ArrayVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: TypedArrayElement + Deserialize<'de>> Visitor<'de> for ArrayVisitor<T> {
+            type Value = TypedArray<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a typed array or an array of numbers")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> std::result::Result<Self::Value, E> {
+                unpack(current_cbor_tag(), v)
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(
+                self,
+                seq: A,
+            ) -> std::result::Result<Self::Value, A::Error> {
+                // Read the tag before the elements are visited
+                match current_cbor_tag() {
+                    Some(tag) => {
+                        let packed = PackedBytes::deserialize(SeqAccessDeserializer::new(seq))?;
+                        unpack(Some(tag), &packed.0)
+                    }
+                    None => {
+                        let values = Vec::<T>::deserialize(SeqAccessDeserializer::new(seq))?;
+                        Ok(TypedArray::new(values))
+                    }
+                }
+            }
+        }
+
+        fn unpack<T: TypedArrayElement, E: de::Error>(
+            tag: Option<u64>,
+            bytes: &[u8],
+        ) -> std::result::Result<TypedArray<T>, E> {
+            let tag = tag.ok_or_else(|| E::custom("Expected a tagged typed array"))?;
+            let little_endian = T::byte_order(tag).ok_or_else(|| {
+                E::custom(format!(
+                    "Tag {} is not a typed array of {}",
+                    tag,
+                    std::any::type_name::<T>()
+                ))
+            })?;
+            let values = elements_from_bytes(bytes, little_endian).map_err(E::custom)?;
+            Ok(TypedArray {
+                values,
+                little_endian,
+            })
+        }
+
+        deserializer.deserialize_any(ArrayVisitor(PhantomData))
+    }
+}
+
+/// A multi-dimensional array (RFC 8746 §3.1)
+///
+/// Serialized as tag 40 (row-major) or tag 1040 (column-major) around an
+/// array of the dimensions and a [`TypedArray`] holding the elements.
+/// Deserializing checks that the product of the dimensions matches the
+/// number of elements.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::MultiDimArray;
+///
+/// // A 2x3 grid, row by row
+/// let grid = MultiDimArray::new(vec![2, 3], vec![1u16, 2, 3, 4, 5, 6]).unwrap();
+/// assert_eq!(grid.get(&[1, 0]), Some(&4));
+///
+/// let cbor = c2pa_cbor::to_vec(&grid).unwrap();
+/// assert_eq!(&cbor[..2], [0xd8, 0x28]);
+/// assert_eq!(c2pa_cbor::from_slice::<MultiDimArray<u16>>(&cbor).unwrap(), grid);
+///
+/// assert!(MultiDimArray::new(vec![2, 2], vec![1u16, 2, 3]).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultiDimArray<T> {
+    dimensions: Vec<u64>,
+    data: TypedArray<T>,
+    column_major: bool,
+}
+
+impl<T: TypedArrayElement> MultiDimArray<T> {
+    /// A row-major array with elements in native byte order
+    ///
+    /// Returns an error if the product of `dimensions` is not the number of
+    /// elements.
+    pub fn new(dimensions: Vec<u64>, values: Vec<T>) -> Result<Self> {
+        Self::from_parts(dimensions, TypedArray::new(values), false)
+    }
+
+    /// A column-major array with elements in native byte order
+    pub fn column_major(dimensions: Vec<u64>, values: Vec<T>) -> Result<Self> {
+        Self::from_parts(dimensions, TypedArray::new(values), true)
+    }
+
+    /// Build from dimensions and a typed array, checking the shape
+    pub fn from_parts(
+        dimensions: Vec<u64>,
+        data: TypedArray<T>,
+        column_major: bool,
+    ) -> Result<Self> {
+        check_shape(&dimensions, data.values.len())?;
+        Ok(MultiDimArray {
+            dimensions,
+            data,
+            column_major,
+        })
+    }
+
+    /// The size of each dimension, outermost first
+    pub fn dimensions(&self) -> &[u64] {
+        &self.dimensions
+    }
+
+    /// The elements in storage order
+    pub fn values(&self) -> &[T] {
+        &self.data.values
+    }
+
+    /// Whether the elements are stored column-major (tag 1040)
+    pub fn is_column_major(&self) -> bool {
+        self.column_major
+    }
+
+    /// The tag written for this array
+    pub fn tag(&self) -> u64 {
+        if self.column_major {
+            TAG_MULTI_DIM_ARRAY_COLUMN_MAJOR
+        } else {
+            TAG_MULTI_DIM_ARRAY
+        }
+    }
+
+    /// The element at `index`, one coordinate per dimension
+    ///
+    /// Returns `None` if the number of coordinates does not match the
+    /// dimensions or any coordinate is out of range.
+    pub fn get(&self, index: &[usize]) -> Option<&T> {
+        if index.len() != self.dimensions.len() {
+            return None;
+        }
+        let mut offset = 0usize;
+        let mut stride = 1usize;
+        let mut visit = |(&i, &dim): (&usize, &u64)| -> Option<()> {
+            if i as u64 >= dim {
+                return None;
+            }
+            offset += i * stride;
+            stride = stride.checked_mul(usize::try_from(dim).ok()?)?;
+            Some(())
+        };
+        if self.column_major {
+            index
+                .iter()
+                .zip(&self.dimensions)
+                .try_for_each(&mut visit)?;
+        } else {
+            index
+                .iter()
+                .zip(&self.dimensions)
+                .rev()
+                .try_for_each(&mut visit)?;
+        }
+        self.data.values.get(offset)
+    }
+
+    /// Return the dimensions and the typed array
+    pub fn into_parts(self) -> (Vec<u64>, TypedArray<T>) {
+        (self.dimensions, self.data)
+    }
+}
+
+fn check_shape(dimensions: &[u64], len: usize) -> Result<()> {
+    if dimensions.is_empty() {
+        return Err(Error::Syntax(
+            "Multi-dimensional array needs at least one dimension".to_string(),
+        ));
+    }
+    let expected = dimensions
+        .iter()
+        .try_fold(1u64, |product, &dim| product.checked_mul(dim));
+    if expected != Some(len as u64) {
+        return Err(Error::Syntax(format!(
+            "Multi-dimensional array of shape {:?} cannot hold {} elements",
+            dimensions, len
+        )));
+    }
+    Ok(())
+}
+
+impl<T: TypedArrayElement> Serialize for MultiDimArray<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        Tagged::new(Some(self.tag()), (&self.dimensions, &self.data)).serialize(serializer)
+    }
+}
+
+impl<'de, T: TypedArrayElement + Deserialize<'de>> Deserialize<'de> for MultiDimArray<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let tagged = Tagged::<(Vec<u64>, TypedArray<T>)>::deserialize(deserializer)?;
+        let column_major = match tagged.tag {
+            Some(TAG_MULTI_DIM_ARRAY) => false,
+            Some(TAG_MULTI_DIM_ARRAY_COLUMN_MAJOR) => true,
+            _ => {
+                return Err(de::Error::custom(
+                    "Expected tag 40 or 1040 for a multi-dimensional array",
+                ));
+            }
+        };
+        let (dimensions, data) = tagged.value;
+        MultiDimArray::from_parts(dimensions, data, column_major).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tags::*;
+
+    #[test]
+    fn test_decode_typed_array_round_trip() {
+        let mut cbor = Vec::new();
+        encode_uint32be_array(&mut cbor, &[1, u32::MAX]).unwrap();
+        assert_eq!(decode_typed_array::<u32>(&cbor).unwrap(), [1, u32::MAX]);
+
+        let mut cbor = Vec::new();
+        encode_uint64le_array(&mut cbor, &[7, 1 << 40]).unwrap();
+        assert_eq!(decode_typed_array::<u64>(&cbor).unwrap(), [7, 1 << 40]);
+
+        let mut cbor = Vec::new();
+        encode_float32le_array(&mut cbor, &[1.5, -0.25]).unwrap();
+        assert_eq!(decode_typed_array::<f32>(&cbor).unwrap(), [1.5, -0.25]);
+
+        let mut cbor = Vec::new();
+        encode_float64be_array(&mut cbor, &[f64::MIN_POSITIVE]).unwrap();
+        assert_eq!(
+            decode_typed_array::<f64>(&cbor).unwrap(),
+            [f64::MIN_POSITIVE]
+        );
+
+        let mut cbor = Vec::new();
+        encode_uint8_array(&mut cbor, &[1, 2, 3]).unwrap();
+        assert_eq!(decode_typed_array::<u8>(&cbor).unwrap(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_decode_typed_array_signed() {
+        // 73(h'fffe'), 77(h'feff') and 72(h'80')
+        assert_eq!(
+            decode_typed_array::<i16>(&[0xd8, 0x49, 0x42, 0xff, 0xfe]).unwrap(),
+            [-2]
+        );
+        assert_eq!(
+            decode_typed_array::<i16>(&[0xd8, 0x4d, 0x42, 0xfe, 0xff]).unwrap(),
+            [-2]
+        );
+        assert_eq!(
+            decode_typed_array::<i8>(&[0xd8, 0x48, 0x41, 0x80]).unwrap(),
+            [-128]
+        );
+        // Clamped uint8
+        assert_eq!(
+            decode_typed_array::<u8>(&[0xd8, 0x44, 0x41, 0xff]).unwrap(),
+            [255]
+        );
+    }
+
+    #[test]
+    fn test_decode_typed_array_rejects_mismatches() {
+        // Untagged, wrong element type, partial element and a non-byte-string
+        assert!(decode_typed_array::<u16>(&[0x42, 0x00, 0x01]).is_err());
+        assert!(decode_typed_array::<i16>(&[0xd8, 0x41, 0x42, 0x00, 0x01]).is_err());
+        assert!(decode_typed_array::<u16>(&[0xd8, 0x41, 0x43, 0x00, 0x01, 0x02]).is_err());
+        assert!(decode_typed_array::<u16>(&[0xd8, 0x41, 0x82, 0x19, 0x01, 0x00, 0x01]).is_err());
+        assert!(decode_typed_array::<u16>(&[0xd8, 0x41, 0x62, b'a', b'b']).is_err());
+        assert_eq!(
+            decode_typed_array::<u32>(&[0xd8, 0x42, 0x40]).unwrap(),
+            Vec::<u32>::new()
+        );
+    }
+
+    #[test]
+    fn test_typed_array_serde() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Frame {
+            ids: TypedArray<u16>,
+            offsets: TypedArray<i64>,
+            raw: TypedArray<u8>,
+        }
+
+        let frame = Frame {
+            ids: TypedArray::little_endian(vec![1, 0x0203]),
+            offsets: TypedArray::big_endian(vec![-1]),
+            raw: TypedArray::little_endian(vec![9]),
+        };
+        let cbor = crate::to_vec(&frame).unwrap();
+        let decoded: Frame = crate::from_slice(&cbor).unwrap();
+        assert_eq!(decoded, frame);
+
+        // 69(h'01000302'), readable with decode_typed_array too
+        let ids = crate::to_vec(&frame.ids).unwrap();
+        assert_eq!(ids, [0xd8, 0x45, 0x44, 0x01, 0x00, 0x03, 0x02]);
+        assert_eq!(decode_typed_array::<u16>(&ids).unwrap(), [1, 0x0203]);
+
+        // Output of the encode helpers, an array of byte values
+        let mut helper = Vec::new();
+        encode_uint8_array(&mut helper, &[1, 2]).unwrap();
+        let raw: TypedArray<u8> = crate::from_slice(&helper).unwrap();
+        assert_eq!(raw.values, [1, 2]);
+    }
+
+    #[test]
+    fn test_typed_array_plain_and_mismatched() {
+        let plain = crate::to_vec(&vec![1.5f64, -2.0]).unwrap();
+        let array: TypedArray<f64> = crate::from_slice(&plain).unwrap();
+        assert_eq!(array, TypedArray::new(vec![1.5, -2.0]));
+
+        let json: TypedArray<u32> = serde_json::from_str("[1, 2]").unwrap();
+        assert_eq!(json.into_inner(), [1, 2]);
+
+        // uint16 tag for an i16 array, an untagged byte string, and a partial element
+        assert!(crate::from_slice::<TypedArray<i16>>(&[0xd8, 0x41, 0x42, 0x00, 0x01]).is_err());
+        assert!(crate::from_slice::<TypedArray<u16>>(&[0x42, 0x00, 0x01]).is_err());
+        assert!(crate::from_slice::<TypedArray<u16>>(&[0xd8, 0x41, 0x41, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_typed_array_field_modules() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Levels {
+            #[serde(with = "little_endian")]
+            left: Vec<i16>,
+            #[serde(with = "big_endian")]
+            right: Vec<u8>,
+        }
+
+        let levels = Levels {
+            left: vec![1, -2],
+            right: vec![3],
+        };
+        let cbor = crate::to_vec(&levels).unwrap();
+        // "left": 77(h'0100feff'), "right": 64(h'03')
+        assert_eq!(&cbor[6..13], [0xd8, 0x4d, 0x44, 0x01, 0x00, 0xfe, 0xff]);
+        assert_eq!(&cbor[19..23], [0xd8, 0x40, 0x41, 0x03]);
+        assert_eq!(crate::from_slice::<Levels>(&cbor).unwrap(), levels);
+    }
+
+    #[test]
+    fn test_multi_dim_array_round_trip() {
+        // 40([[2, 2], 65(h'0001000200030004')])
+        let grid = MultiDimArray::from_parts(
+            vec![2, 2],
+            TypedArray::big_endian(vec![1u16, 2, 3, 4]),
+            false,
+        )
+        .unwrap();
+        let cbor = crate::to_vec(&grid).unwrap();
+        assert_eq!(
+            cbor,
+            [
+                0xd8, 0x28, 0x82, 0x82, 0x02, 0x02, 0xd8, 0x41, 0x48, 0, 1, 0, 2, 0, 3, 0, 4
+            ]
+        );
+        assert_eq!(
+            crate::from_slice::<MultiDimArray<u16>>(&cbor).unwrap(),
+            grid
+        );
+        assert_eq!(grid.get(&[0, 1]), Some(&2));
+        assert_eq!(grid.get(&[2, 0]), None);
+        assert_eq!(grid.get(&[0]), None);
+
+        // Column-major stores the first coordinate fastest
+        let columns =
+            MultiDimArray::column_major(vec![2, 3], vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+        assert_eq!(columns.get(&[1, 0]), Some(&2.0));
+        assert_eq!(columns.get(&[0, 2]), Some(&5.0));
+        let cbor = crate::to_vec(&columns).unwrap();
+        assert_eq!(&cbor[..3], [0xd9, 0x04, 0x10]);
+        let decoded: MultiDimArray<f32> = crate::from_slice(&cbor).unwrap();
+        assert!(decoded.is_column_major());
+        assert_eq!(decoded, columns);
+    }
+
+    #[test]
+    fn test_multi_dim_array_rejects_bad_shapes() {
+        assert!(MultiDimArray::new(vec![], vec![1u8]).is_err());
+        assert!(MultiDimArray::new(vec![u64::MAX, 2], vec![1u8]).is_err());
+        assert!(MultiDimArray::<u8>::new(vec![3, 0], vec![]).is_ok());
+
+        // 40([[2, 2], 64(h'010203')]): three elements for a 2x2 shape
+        let short = [
+            0xd8, 0x28, 0x82, 0x82, 0x02, 0x02, 0xd8, 0x40, 0x43, 1, 2, 3,
+        ];
+        assert!(crate::from_slice::<MultiDimArray<u8>>(&short).is_err());
+        // A valid one-dimensional array under tag 41, and untagged
+        let wrong_tag = [0xd8, 0x29, 0x82, 0x81, 0x02, 0xd8, 0x40, 0x42, 1, 2];
+        assert!(crate::from_slice::<MultiDimArray<u8>>(&wrong_tag).is_err());
+        assert!(crate::from_slice::<MultiDimArray<u8>>(&wrong_tag[2..]).is_err());
+    }
+
+    #[cfg(feature = "zero_copy")]
+    #[test]
+    fn test_as_typed_slice_borrows_when_possible() {
+        use crate::Decoder;
+
+        // The tag, byte string header and 2-byte length take 5 bytes, so
+        // starting at offset 3 of a u32 buffer aligns the content
+        let values: Vec<u32> = (0..64).collect();
+        let native = crate::to_vec(&TypedArray::new(values.clone())).unwrap();
+        assert_eq!(native[2], 0x59);
+        let mut aligned = vec![0u32; native.len() / 4 + 2];
+        let buffer: &mut [u8] = bytemuck::cast_slice_mut(&mut aligned);
+        buffer[3..native.len() + 3].copy_from_slice(&native);
+        {
+            let mut decoder = Decoder::from_slice(&buffer[3..native.len() + 3]);
+            let elements = decoder.as_typed_slice::<u32>().unwrap();
+            assert!(elements.is_borrowed());
+            assert_eq!(&elements[..], values);
+            decoder.end().unwrap();
+        }
+
+        // Misaligned input and the other byte order are copied
+        buffer[4..native.len() + 4].copy_from_slice(&native);
+        let elements = Decoder::from_slice(&buffer[4..native.len() + 4])
+            .as_typed_slice::<u32>()
+            .unwrap();
+        assert!(!elements.is_borrowed());
+        assert_eq!(&elements[..], values);
+
+        let swapped = if cfg!(target_endian = "little") {
+            TypedArray::big_endian(values.clone())
+        } else {
+            TypedArray::little_endian(values.clone())
+        };
+        let cbor = crate::to_vec(&swapped).unwrap();
+        let elements = Decoder::from_slice(&cbor).as_typed_slice::<u32>().unwrap();
+        assert!(!elements.is_borrowed());
+        assert_eq!(elements.into_owned(), values);
+
+        // Arrays of byte values from the encode helpers, and mismatched tags
+        let mut helper = Vec::new();
+        encode_uint16be_array(&mut helper, &[1, 2]).unwrap();
+        let elements = Decoder::from_slice(&helper)
+            .as_typed_slice::<u16>()
+            .unwrap();
+        assert_eq!(&elements[..], [1, 2]);
+        assert!(
+            Decoder::from_slice(&helper)
+                .as_typed_slice::<i16>()
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,497 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! URI validation for tag 32 values
+//!
+//! [`validate_uri`] checks the character-level syntax of a URI reference
+//! (RFC 3986), and [`UriPolicy`] additionally restricts which schemes are
+//! accepted. A policy can be installed on a [`Decoder`](crate::Decoder) with
+//! [`with_uri_policy`](crate::Decoder::with_uri_policy) so every tag 32 value
+//! is checked while decoding.
+//!
+//! [`JumbfUri`] is a validated `self#jumbf=...` manifest reference.
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Error, Result, Tagged, constants::TAG_URI};
+
+/// Pseudo-scheme used by C2PA for references into the manifest store
+pub const SELF_JUMBF: &str = "self#jumbf";
+
+const SELF_JUMBF_PREFIX: &str = "self#jumbf=";
+const MANIFEST_STORE: &str = "c2pa";
+const ASSERTION_STORE: &str = "c2pa.assertions";
+
+/// Check that `uri` only contains characters allowed in a URI reference
+///
+/// Every byte must be an RFC 3986 unreserved or reserved character, or part
+/// of a `%XX` percent-encoding.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::validate_uri;
+///
+/// assert!(validate_uri("https://example.com/a%20b").is_ok());
+/// assert!(validate_uri("https://example.com/a b").is_err());
+/// ```
+pub fn validate_uri(uri: &str) -> Result<()> {
+    if uri.is_empty() {
+        return Err(Error::Syntax("URI is empty".to_string()));
+    }
+    let bytes = uri.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let is_hex = |pos: usize| bytes.get(pos).is_some_and(|b| b.is_ascii_hexdigit());
+                if !is_hex(i + 1) || !is_hex(i + 2) {
+                    return Err(Error::Syntax(format!(
+                        "Invalid percent-encoding at offset {} in URI",
+                        i
+                    )));
+                }
+                i += 3;
+            }
+            b if is_uri_char(b) => i += 1,
+            _ => {
+                let c = uri[i..].chars().next().unwrap_or_default();
+                return Err(Error::Syntax(format!(
+                    "Invalid character {:?} at offset {} in URI",
+                    c, i
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_uri_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"-._~:/?#[]@!$&'()*+,;=".contains(&b)
+}
+
+/// Scheme of an absolute URI, or `None` for a relative reference
+fn scheme_of(uri: &str) -> Option<&str> {
+    let scheme = &uri[..uri.find(':')?];
+    let mut chars = scheme.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then_some(scheme)
+}
+
+/// Allow-list of URI schemes accepted for tag 32 values
+///
+/// Scheme names are compared case-insensitively. The C2PA manifest reference
+/// form `self#jumbf=...` is not a URI scheme, but can be allowed with the
+/// [`SELF_JUMBF`] pseudo-scheme.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::UriPolicy;
+///
+/// let policy = UriPolicy::c2pa();
+/// assert!(policy.check("https://example.com/manifest").is_ok());
+/// assert!(policy.check("self#jumbf=/c2pa/urn:c2pa:1234/c2pa.assertions").is_ok());
+/// assert!(policy.check("ftp://example.com").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UriPolicy {
+    schemes: Vec<String>,
+}
+
+impl UriPolicy {
+    /// Create a policy allowing the given schemes
+    pub fn new<I, S>(schemes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        UriPolicy {
+            schemes: schemes.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Policy allowing what C2PA hashed-URI references may use: `https` and `self#jumbf`
+    pub fn c2pa() -> Self {
+        UriPolicy::new(["https", SELF_JUMBF])
+    }
+
+    /// Allow an additional scheme (builder pattern)
+    pub fn allow(mut self, scheme: impl Into<String>) -> Self {
+        self.schemes.push(scheme.into());
+        self
+    }
+
+    /// Returns true if `scheme` is on the allow-list
+    pub fn allows(&self, scheme: &str) -> bool {
+        self.schemes.iter().any(|s| s.eq_ignore_ascii_case(scheme))
+    }
+
+    /// Check the syntax of `uri` and that its scheme is allowed
+    ///
+    /// `http` and `https` URIs must also have a non-empty host, and
+    /// `self#jumbf=` references a non-empty path.
+    pub fn check(&self, uri: &str) -> Result<()> {
+        validate_uri(uri)?;
+
+        if let Some(path) = uri.strip_prefix(SELF_JUMBF_PREFIX) {
+            if !self.allows(SELF_JUMBF) {
+                return Err(Error::Syntax(
+                    "self#jumbf references are not allowed".to_string(),
+                ));
+            }
+            if path.is_empty() {
+                return Err(Error::Syntax(
+                    "self#jumbf reference has an empty path".to_string(),
+                ));
+            }
+            return Ok(());
+        }
+
+        let scheme =
+            scheme_of(uri).ok_or_else(|| Error::Syntax(format!("URI {:?} has no scheme", uri)))?;
+        if !self.allows(scheme) {
+            return Err(Error::Syntax(format!(
+                "URI scheme {:?} is not allowed",
+                scheme
+            )));
+        }
+
+        if scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https") {
+            let rest = &uri[scheme.len() + 1..];
+            let authority = rest
+                .strip_prefix("//")
+                .ok_or_else(|| Error::Syntax(format!("URI {:?} is missing an authority", uri)))?;
+            let host_end = authority.find(['/', '?', '#']).unwrap_or(authority.len());
+            let host = &authority[..host_end];
+            // Strip userinfo and port
+            let host = host.rsplit_once('@').map_or(host, |(_, h)| h);
+            let host = match host.rfind(':') {
+                Some(i) if !host.ends_with(']') => &host[..i],
+                _ => host,
+            };
+            if host.is_empty() {
+                return Err(Error::Syntax(format!("URI {:?} has an empty host", uri)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A validated C2PA `self#jumbf=` reference
+///
+/// References are normalized on construction: repeated `/` separators are
+/// collapsed and a trailing `/` is removed. The path is either absolute
+/// (`self#jumbf=/c2pa/<manifest>/...`) or relative to the current manifest
+/// (`self#jumbf=c2pa.assertions/...`).
+///
+/// Serializes as a text string under tag 32 (URI) and deserializes from a
+/// tagged or untagged text string.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::JumbfUri;
+///
+/// let uri: JumbfUri = "self#jumbf=/c2pa/urn:c2pa:1234//c2pa.assertions/c2pa.hash.data/"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(
+///     uri.as_str(),
+///     "self#jumbf=/c2pa/urn:c2pa:1234/c2pa.assertions/c2pa.hash.data"
+/// );
+/// assert_eq!(uri.manifest_label(), Some("urn:c2pa:1234"));
+/// assert_eq!(uri.assertion_label(), Some("c2pa.hash.data"));
+///
+/// let bytes = c2pa_cbor::to_vec(&uri).unwrap();
+/// assert_eq!(&bytes[..2], &[0xd8, 0x20]); // tag 32
+/// assert_eq!(c2pa_cbor::from_slice::<JumbfUri>(&bytes).unwrap(), uri);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct JumbfUri(String);
+
+impl JumbfUri {
+    /// Validate and normalize a `self#jumbf=` reference
+    pub fn new(uri: &str) -> Result<Self> {
+        let path = uri.strip_prefix(SELF_JUMBF_PREFIX).ok_or_else(|| {
+            Error::Syntax(format!(
+                "JUMBF URI {:?} must start with \"self#jumbf=\"",
+                uri
+            ))
+        })?;
+
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        if segments.is_empty() {
+            return Err(Error::Syntax(format!(
+                "JUMBF URI {:?} has an empty path",
+                uri
+            )));
+        }
+        let mut normalized = String::with_capacity(uri.len());
+        normalized.push_str(SELF_JUMBF_PREFIX);
+        if path.starts_with('/') {
+            normalized.push('/');
+        }
+        normalized.push_str(&segments.join("/"));
+        validate_uri(&normalized)?;
+        Ok(JumbfUri(normalized))
+    }
+
+    /// Reference to an assertion, absolute if `manifest_label` is given
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::JumbfUri;
+    ///
+    /// let uri = JumbfUri::assertion(None, "c2pa.actions").unwrap();
+    /// assert_eq!(uri.as_str(), "self#jumbf=c2pa.assertions/c2pa.actions");
+    /// ```
+    pub fn assertion(manifest_label: Option<&str>, assertion_label: &str) -> Result<Self> {
+        match manifest_label {
+            Some(manifest) => JumbfUri::new(&format!(
+                "{SELF_JUMBF_PREFIX}/{MANIFEST_STORE}/{manifest}/{ASSERTION_STORE}/{assertion_label}"
+            )),
+            None => JumbfUri::new(&format!(
+                "{SELF_JUMBF_PREFIX}{ASSERTION_STORE}/{assertion_label}"
+            )),
+        }
+    }
+
+    /// The full reference, including the `self#jumbf=` prefix
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The JUMBF path following `self#jumbf=`
+    pub fn path(&self) -> &str {
+        &self.0[SELF_JUMBF_PREFIX.len()..]
+    }
+
+    /// Returns true if the path starts at the root of the manifest store
+    pub fn is_absolute(&self) -> bool {
+        self.path().starts_with('/')
+    }
+
+    /// The labels of the JUMBF boxes along the path
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.path().split('/').filter(|s| !s.is_empty())
+    }
+
+    /// Label of the referenced manifest, for absolute references into the C2PA store
+    pub fn manifest_label(&self) -> Option<&str> {
+        if !self.is_absolute() {
+            return None;
+        }
+        let mut segments = self.segments();
+        match segments.next() {
+            Some(MANIFEST_STORE) => segments.next(),
+            _ => None,
+        }
+    }
+
+    /// Label of the referenced assertion, if the path goes through the assertion store
+    pub fn assertion_label(&self) -> Option<&str> {
+        let mut segments = self.segments();
+        segments.find(|s| *s == ASSERTION_STORE)?;
+        segments.next()
+    }
+
+    /// Resolve a relative reference against the manifest it appears in
+    ///
+    /// Absolute references are returned unchanged.
+    pub fn to_absolute(&self, manifest_label: &str) -> Result<Self> {
+        if self.is_absolute() {
+            return Ok(self.clone());
+        }
+        JumbfUri::new(&format!(
+            "{SELF_JUMBF_PREFIX}/{MANIFEST_STORE}/{manifest_label}/{}",
+            self.path()
+        ))
+    }
+
+    /// Consume the reference and return the normalized string
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for JumbfUri {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for JumbfUri {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        JumbfUri::new(s)
+    }
+}
+
+impl AsRef<str> for JumbfUri {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Serialize for JumbfUri {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        Tagged::new(Some(TAG_URI), self.as_str()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for JumbfUri {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        JumbfUri::new(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Decoder, Value, encode_uri};
+
+    #[test]
+    fn test_validate_uri_syntax() {
+        assert!(validate_uri("https://example.com/path?q=1#frag").is_ok());
+        assert!(
+            validate_uri("self#jumbf=/c2pa/urn:c2pa:abc/c2pa.assertions/c2pa.hash.data").is_ok()
+        );
+        assert!(validate_uri("urn:uuid:F3E2A1B0-0000-0000-0000-000000000000").is_ok());
+
+        match validate_uri("https://exa mple.com") {
+            Err(Error::Syntax(msg)) => assert_eq!(msg, "Invalid character ' ' at offset 11 in URI"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match validate_uri("https://example.com/%2") {
+            Err(Error::Syntax(msg)) => {
+                assert_eq!(msg, "Invalid percent-encoding at offset 20 in URI")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(validate_uri("https://example.com/é").is_err());
+        assert!(validate_uri("").is_err());
+    }
+
+    #[test]
+    fn test_uri_policy_schemes() {
+        let policy = UriPolicy::c2pa();
+        assert!(policy.check("HTTPS://example.com").is_ok());
+        assert!(policy.check("https://user@example.com:8443/x").is_ok());
+        assert!(policy.check("self#jumbf=/c2pa").is_ok());
+
+        match policy.check("http://example.com") {
+            Err(Error::Syntax(msg)) => assert_eq!(msg, "URI scheme \"http\" is not allowed"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(policy.check("https:example.com").is_err());
+        assert!(policy.check("https://:443/x").is_err());
+        assert!(policy.check("self#jumbf=").is_err());
+        assert!(policy.check("relative/path").is_err());
+
+        let policy = UriPolicy::new(["https"]).allow("data");
+        assert!(policy.check("data:text/plain,hi").is_ok());
+        assert!(policy.check("self#jumbf=/c2pa").is_err());
+    }
+
+    #[test]
+    fn test_decoder_uri_policy() {
+        let mut good = Vec::new();
+        encode_uri(&mut good, "https://example.com").unwrap();
+        let mut bad = Vec::new();
+        encode_uri(&mut bad, "ftp://example.com").unwrap();
+
+        let decode = |bytes: &[u8]| -> Result<Value> {
+            Decoder::new(bytes)
+                .with_uri_policy(UriPolicy::c2pa())
+                .decode()
+        };
+        assert!(decode(&good).is_ok());
+        assert!(decode(&bad).is_err());
+
+        // Also enforced through Tagged<T> and inside containers
+        let mut decoder = Decoder::new(&bad[..]).with_uri_policy(UriPolicy::c2pa());
+        assert!(decoder.decode::<Tagged<String>>().is_err());
+        let mut nested = vec![0x81];
+        nested.extend_from_slice(&bad);
+        assert!(decode(&nested).is_err());
+
+        // Tag 32 must enclose a text string
+        assert!(decode(&[0xd8, 0x20, 0x01]).is_err());
+
+        // Other tags and plain strings are not checked
+        let plain = crate::to_vec(&"ftp://example.com").unwrap();
+        assert!(decode(&plain).is_ok());
+        let mut other_tag = Vec::new();
+        crate::encode_tagged(&mut other_tag, 33, &"ftp://example.com").unwrap();
+        assert!(decode(&other_tag).is_ok());
+
+        // Without a policy anything goes
+        let value: Value = crate::from_slice(&bad).unwrap();
+        assert_eq!(value, Value::Text("ftp://example.com".to_string()));
+    }
+
+    #[test]
+    fn test_jumbf_uri_normalization() {
+        let uri = JumbfUri::new("self#jumbf=//c2pa/m1///c2pa.signature/").unwrap();
+        assert_eq!(uri.as_str(), "self#jumbf=/c2pa/m1/c2pa.signature");
+        assert_eq!(uri.manifest_label(), Some("m1"));
+        assert_eq!(uri.assertion_label(), None);
+        assert_eq!(
+            uri.segments().collect::<Vec<_>>(),
+            vec!["c2pa", "m1", "c2pa.signature"]
+        );
+
+        assert!(JumbfUri::new("https://example.com").is_err());
+        assert!(JumbfUri::new("self#jumbf=").is_err());
+        assert!(JumbfUri::new("self#jumbf=///").is_err());
+        assert!(JumbfUri::new("self#jumbf=/c2pa/bad label").is_err());
+    }
+
+    #[test]
+    fn test_jumbf_uri_relative() {
+        let uri = JumbfUri::assertion(None, "c2pa.thumbnail.claim.jpeg").unwrap();
+        assert!(!uri.is_absolute());
+        assert_eq!(uri.manifest_label(), None);
+        assert_eq!(uri.assertion_label(), Some("c2pa.thumbnail.claim.jpeg"));
+
+        let absolute = uri.to_absolute("urn:c2pa:abcd").unwrap();
+        assert_eq!(
+            absolute,
+            JumbfUri::assertion(Some("urn:c2pa:abcd"), "c2pa.thumbnail.claim.jpeg").unwrap()
+        );
+        assert_eq!(absolute.to_absolute("other").unwrap(), absolute);
+    }
+
+    #[test]
+    fn test_jumbf_uri_serde() {
+        let uri = JumbfUri::assertion(Some("m"), "c2pa.actions").unwrap();
+        let bytes = crate::to_vec(&uri).unwrap();
+        let mut expected = Vec::new();
+        encode_uri(&mut expected, uri.as_str()).unwrap();
+        assert_eq!(bytes, expected);
+        assert_eq!(crate::from_slice::<JumbfUri>(&bytes).unwrap(), uri);
+
+        // Untagged strings are accepted, but must still be valid references
+        let plain = crate::to_vec(&"self#jumbf=c2pa.assertions/x").unwrap();
+        assert!(crate::from_slice::<JumbfUri>(&plain).is_ok());
+        let plain = crate::to_vec(&"https://example.com").unwrap();
+        assert!(crate::from_slice::<JumbfUri>(&plain).is_err());
+
+        // Accepted by the C2PA URI policy
+        let mut decoder = Decoder::new(&bytes[..]).with_uri_policy(UriPolicy::c2pa());
+        assert!(decoder.decode::<JumbfUri>().is_ok());
+    }
+}
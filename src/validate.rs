@@ -0,0 +1,331 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Well-formedness checking (RFC 8949 §5.3.1 / Appendix C)
+//!
+//! [`validate`] and [`validate_reader`] walk an encoded item without
+//! building values or allocating strings and collections, which makes them
+//! a cheap accept/reject pass for untrusted input before a full decode.
+//!
+//! The checks cover:
+//! - header structure (reserved additional info, truncated input)
+//! - indefinite-length items only for strings, arrays and maps, and chunks
+//!   of indefinite strings being definite strings of the same type
+//! - break markers only where they close an indefinite-length item
+//! - every tag being followed by exactly one item
+//! - UTF-8 validity of text strings (each chunk on its own)
+//! - two-byte simple values not encoding values below 32
+//! - nesting depth up to [`DEFAULT_MAX_DEPTH`]
+
+use std::io::{ErrorKind, Read};
+
+use crate::{
+    Error, Result,
+    constants::*,
+    raw::{SliceReader, argument_len, check_depth, header_argument, u64_to_usize},
+};
+
+/// Check that `bytes` holds exactly one well-formed CBOR item
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::validate;
+///
+/// assert!(validate(&[0x82, 0x01, 0x61, b'a']).is_ok());
+/// // Break marker outside an indefinite-length item
+/// assert!(validate(&[0x81, 0xff]).is_err());
+/// ```
+pub fn validate(bytes: &[u8]) -> Result<()> {
+    if bytes.is_empty() {
+        return Err(Error::Syntax("empty input".to_string()));
+    }
+    let mut reader = SliceReader::new(bytes);
+    validate_item(&mut reader, 0)?;
+    if reader.remaining() > 0 {
+        return Err(Error::TrailingData);
+    }
+    Ok(())
+}
+
+/// Check that `reader` yields exactly one well-formed CBOR item
+///
+/// String contents are streamed through a small fixed-size buffer, so memory
+/// use does not depend on the declared lengths in the input. The reader is
+/// consumed up to its end to detect trailing data.
+pub fn validate_reader<R: Read>(reader: R) -> Result<()> {
+    let mut source = IoSource {
+        reader,
+        peeked: None,
+    };
+    validate_item(&mut source, 0)?;
+    if !source.at_end()? {
+        return Err(Error::TrailingData);
+    }
+    Ok(())
+}
+
+// Byte source the validator can walk
+trait Source {
+    fn peek_u8(&mut self) -> Result<u8>;
+    fn read_u8(&mut self) -> Result<u8>;
+    fn read_header(&mut self) -> Result<(u8, u8, Option<u64>)>;
+    /// Skip `len` bytes of string content, checking UTF-8 if `text` is set
+    fn skip_string(&mut self, len: u64, text: bool) -> Result<()>;
+}
+
+impl Source for SliceReader<'_> {
+    fn peek_u8(&mut self) -> Result<u8> {
+        SliceReader::peek_u8(self)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        SliceReader::read_u8(self)
+    }
+
+    fn read_header(&mut self) -> Result<(u8, u8, Option<u64>)> {
+        SliceReader::read_header(self)
+    }
+
+    fn skip_string(&mut self, len: u64, text: bool) -> Result<()> {
+        let len = u64_to_usize(len)?;
+        let content = self.read_slice(len)?;
+        if text {
+            std::str::from_utf8(content).map_err(|_| Error::InvalidUtf8)?;
+        }
+        Ok(())
+    }
+}
+
+struct IoSource<R: Read> {
+    reader: R,
+    peeked: Option<u8>,
+}
+
+impl<R: Read> IoSource<R> {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.reader.read_exact(buf).map_err(|e| match e.kind() {
+            ErrorKind::UnexpectedEof => Error::Eof,
+            _ => Error::Io(e),
+        })
+    }
+
+    fn at_end(&mut self) -> Result<bool> {
+        match self.peek_u8() {
+            Ok(_) => Ok(false),
+            Err(Error::Eof) => Ok(true),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<R: Read> Source for IoSource<R> {
+    fn peek_u8(&mut self) -> Result<u8> {
+        if let Some(byte) = self.peeked {
+            return Ok(byte);
+        }
+        let byte = self.read_u8()?;
+        self.peeked = Some(byte);
+        Ok(byte)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        if let Some(byte) = self.peeked.take() {
+            return Ok(byte);
+        }
+        let mut buf = [0u8; 1];
+        self.read_exact(&mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn read_header(&mut self) -> Result<(u8, u8, Option<u64>)> {
+        let initial = self.read_u8()?;
+        let info = initial & 0x1f;
+        let mut buf = [0u8; 8];
+        let arg_bytes = &mut buf[..argument_len(info)?];
+        self.read_exact(arg_bytes)?;
+        Ok((initial >> 5, info, header_argument(info, arg_bytes)))
+    }
+
+    fn skip_string(&mut self, len: u64, text: bool) -> Result<()> {
+        let mut buf = [0u8; 4096];
+        // Bytes of a UTF-8 sequence split across reads, kept at the front of `buf`
+        let mut carry = 0;
+        let mut remaining = len;
+        while remaining > 0 {
+            let n = (buf.len() - carry).min(usize::try_from(remaining).unwrap_or(usize::MAX));
+            self.read_exact(&mut buf[carry..carry + n])?;
+            remaining -= n as u64;
+            let filled = carry + n;
+            carry = 0;
+            if text && let Err(e) = std::str::from_utf8(&buf[..filled]) {
+                if e.error_len().is_some() {
+                    return Err(Error::InvalidUtf8);
+                }
+                let valid = e.valid_up_to();
+                buf.copy_within(valid..filled, 0);
+                carry = filled - valid;
+            }
+        }
+        if carry > 0 {
+            return Err(Error::InvalidUtf8);
+        }
+        Ok(())
+    }
+}
+
+fn validate_item<S: Source>(src: &mut S, depth: usize) -> Result<()> {
+    let (major, info, arg) = src.read_header()?;
+    match (major, arg) {
+        (MAJOR_UNSIGNED | MAJOR_NEGATIVE, Some(_)) => Ok(()),
+        (MAJOR_BYTES | MAJOR_TEXT, Some(len)) => src.skip_string(len, major == MAJOR_TEXT),
+        (MAJOR_BYTES | MAJOR_TEXT, None) => {
+            while src.peek_u8()? != BREAK {
+                let (chunk_major, _, chunk_len) = src.read_header()?;
+                if chunk_major != major {
+                    return Err(Error::Syntax(
+                        "Indefinite string chunks must match the string type".to_string(),
+                    ));
+                }
+                let chunk_len = chunk_len.ok_or_else(|| {
+                    Error::Syntax("Indefinite string chunks cannot be indefinite".to_string())
+                })?;
+                src.skip_string(chunk_len, major == MAJOR_TEXT)?;
+            }
+            src.read_u8()?;
+            Ok(())
+        }
+        (MAJOR_ARRAY | MAJOR_MAP, Some(len)) => {
+            check_depth(depth)?;
+            let items_per_entry = if major == MAJOR_MAP { 2 } else { 1 };
+            for _ in 0..len {
+                for _ in 0..items_per_entry {
+                    validate_item(src, depth + 1)?;
+                }
+            }
+            Ok(())
+        }
+        (MAJOR_ARRAY | MAJOR_MAP, None) => {
+            check_depth(depth)?;
+            while src.peek_u8()? != BREAK {
+                validate_item(src, depth + 1)?;
+                if major == MAJOR_MAP {
+                    // A break here would leave a key without a value
+                    validate_item(src, depth + 1)?;
+                }
+            }
+            src.read_u8()?;
+            Ok(())
+        }
+        (MAJOR_TAG, Some(_)) => {
+            check_depth(depth)?;
+            validate_item(src, depth + 1)
+        }
+        (MAJOR_SIMPLE, _) => match (info, arg) {
+            (0..=23 | FLOAT16 | FLOAT32 | FLOAT64, _) => Ok(()),
+            (SIMPLE_VALUE, Some(val)) if val >= 32 => Ok(()),
+            (INDEFINITE, _) => Err(Error::Syntax("Unexpected break".to_string())),
+            _ => Err(Error::Syntax("Invalid simple value".to_string())),
+        },
+        _ => Err(Error::Syntax("Unexpected indefinite length".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_both(bytes: &[u8]) -> Result<()> {
+        let from_slice = validate(bytes);
+        let from_reader = validate_reader(bytes);
+        assert_eq!(
+            from_slice.is_ok(),
+            from_reader.is_ok(),
+            "slice and reader disagree on {:02x?}",
+            bytes
+        );
+        from_slice
+    }
+
+    #[test]
+    fn test_validate_accepts_well_formed() {
+        let value = crate::Value::Array(vec![
+            crate::Value::Text("héllo".to_string()),
+            crate::Value::Bytes(vec![0; 5000]),
+            crate::Value::Tag(32, Box::new(crate::Value::Text("https://x".to_string()))),
+            crate::Value::Float(1.5),
+        ]);
+        check_both(&crate::to_vec(&value).unwrap()).unwrap();
+
+        // Indefinite-length string, array and map
+        check_both(&[0x7f, 0x61, b'a', 0x60, 0xff]).unwrap();
+        check_both(&[0x9f, 0x01, 0x9f, 0xff, 0xff]).unwrap();
+        check_both(&[0xbf, 0x01, 0x02, 0xff]).unwrap();
+        // Two-byte simple value 32 and undefined
+        check_both(&[0x82, 0xf8, 0x20, 0xf7]).unwrap();
+    }
+
+    #[test]
+    fn test_validate_rejects_malformed() {
+        // Truncated string and truncated header
+        assert!(matches!(check_both(&[0x62, b'a']), Err(Error::Eof)));
+        assert!(matches!(check_both(&[0x19, 0x01]), Err(Error::Eof)));
+        // Reserved additional info
+        assert!(check_both(&[0x1c]).is_err());
+        // Indefinite integer and tag
+        assert!(check_both(&[0x1f]).is_err());
+        assert!(check_both(&[0xdf, 0x01]).is_err());
+        // Misplaced breaks
+        assert!(check_both(&[0xff]).is_err());
+        assert!(check_both(&[0x81, 0xff]).is_err());
+        assert!(check_both(&[0xbf, 0x01, 0xff]).is_err());
+        // Tag without content
+        assert!(matches!(check_both(&[0xc1]), Err(Error::Eof)));
+        // Chunk of the wrong type, nested indefinite chunk
+        assert!(check_both(&[0x5f, 0x61, b'a', 0xff]).is_err());
+        assert!(check_both(&[0x5f, 0x5f, 0xff, 0xff]).is_err());
+        // Invalid UTF-8, including a sequence split across chunks
+        assert!(matches!(check_both(&[0x61, 0xff]), Err(Error::InvalidUtf8)));
+        assert!(check_both(&[0x7f, 0x61, 0xc3, 0x61, 0xa9, 0xff]).is_err());
+        // Two-byte encoding of a simple value below 32
+        assert!(check_both(&[0xf8, 0x10]).is_err());
+        // Trailing data
+        assert!(matches!(
+            check_both(&[0x01, 0x01]),
+            Err(Error::TrailingData)
+        ));
+    }
+
+    #[test]
+    fn test_validate_reader_utf8_across_buffer_boundary() {
+        // Place a multi-byte character across the 4096-byte read buffer
+        let mut text = "a".repeat(4095);
+        text.push('é');
+        text.push_str(&"b".repeat(100));
+        check_both(&crate::to_vec(&text).unwrap()).unwrap();
+
+        let mut bytes = crate::to_vec(&text).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] = 0x80;
+        assert!(matches!(check_both(&bytes), Err(Error::InvalidUtf8)));
+    }
+
+    #[test]
+    fn test_validate_depth_limit() {
+        let mut deep = vec![0x81; DEFAULT_MAX_DEPTH + 1];
+        deep.push(0x00);
+        assert!(check_both(&deep).is_err());
+        let mut ok = vec![0x81; DEFAULT_MAX_DEPTH - 1];
+        ok.push(0x00);
+        check_both(&ok).unwrap();
+    }
+}
@@ -13,13 +13,69 @@
 
 // Portions derived from serde_cbor (https://github.com/pyfisch/cbor)
 
-use std::{collections::BTreeMap, fmt};
+use std::{cell::Cell, collections::BTreeMap, fmt, ops::ControlFlow};
 
 use serde::{
     Deserialize, Deserializer, Serialize, Serializer,
     de::{self, Visitor},
 };
 
+use crate::{
+    constants::{
+        CBOR_TAG_MARKER, CHUNKED_BYTES_MARKER, CHUNKED_TEXT_MARKER, SIMPLE_MARKER, UNDEFINED,
+        UNDEFINED_MARKER,
+    },
+    decoder::PathSegment,
+    keys::FieldKey,
+    tags::Tagged,
+};
+
+thread_local! {
+    // Set while the decoder reports a simple value, so `Value` can tell
+    // undefined from null and simple(n) from the integer n
+    static SIMPLE: Cell<Option<u8>> = const { Cell::new(None) };
+}
+
+thread_local! {
+    // Set while the decoder visits a map, when maps should keep their order
+    static ORDERED_MAPS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Visit a map, reading it into [`Value::OrderedMap`] rather than
+/// [`Value::Map`] if `ordered` is set
+pub(crate) fn visit_map_ordered<T>(ordered: bool, visit: impl FnOnce() -> T) -> T {
+    let outer = ORDERED_MAPS.with(|o| o.replace(ordered));
+    let result = visit();
+    ORDERED_MAPS.with(|o| o.set(outer));
+    result
+}
+
+fn visit_as_simple<T>(simple: u8, visit: impl FnOnce() -> T) -> T {
+    let outer = SIMPLE.with(|s| s.replace(Some(simple)));
+    let result = visit();
+    SIMPLE.with(|s| s.set(outer));
+    result
+}
+
+/// Visit the undefined simple value as unit
+///
+/// Visitors other than the one for [`Value`] can't tell it from null.
+pub(crate) fn visit_undefined<'de, V: Visitor<'de>, E: de::Error>(
+    visitor: V,
+) -> Result<V::Value, E> {
+    visit_as_simple(UNDEFINED, || visitor.visit_unit())
+}
+
+/// Visit an unassigned simple value as its number
+///
+/// Visitors other than the one for [`Value`] see a `u8`.
+pub(crate) fn visit_simple<'de, V: Visitor<'de>, E: de::Error>(
+    visitor: V,
+    simple: u8,
+) -> Result<V::Value, E> {
+    visit_as_simple(simple, || visitor.visit_u8(simple))
+}
+
 /// Dynamic CBOR value type for working with untyped CBOR data
 ///
 /// This type can represent any CBOR value without knowing its type at compile time.
@@ -49,6 +105,8 @@ use serde::{
 pub enum Value {
     /// Null value
     Null,
+    /// Undefined value (simple value 23), which serde formats other than CBOR see as null
+    Undefined,
     /// Boolean value
     Bool(bool),
     /// Integer value (signed 64-bit)
@@ -63,8 +121,27 @@ pub enum Value {
     Array(Vec<Value>),
     /// Map of values
     Map(BTreeMap<Value, Value>),
+    /// Map with its entries in the order they were sent
+    ///
+    /// Decoded in place of [`Value::Map`] when the decoder is set up with
+    /// [`with_ordered_maps`](crate::Decoder::with_ordered_maps), so a map can
+    /// be seen exactly as it was transmitted: entries keep their wire order
+    /// and repeated keys are all kept. Encoded with its entries in order.
+    /// Equal only to an ordered map with the same entries in the same order.
+    OrderedMap(Vec<(Value, Value)>),
     /// Tagged value (tag number, boxed content)
+    ///
+    /// Serialized as the tag followed by its content. Decoding into a `Value`
+    /// drops tags and gives back only the content (see
+    /// [`into_untagged`](Value::into_untagged)), as do serializers for formats
+    /// without tags, such as JSON; read tags with [`Tagged`].
     Tag(u64, Box<Value>),
+    /// Unassigned simple value (0–19 or 32–255)
+    ///
+    /// 20–23 are `false`, `true`, `null` and `undefined`, which decode as
+    /// those values, and 24–31 are reserved and can't be encoded. Serde
+    /// formats other than CBOR see the number.
+    Simple(u8),
 }
 
 impl Serialize for Value {
@@ -74,6 +151,7 @@ impl Serialize for Value {
     {
         match self {
             Value::Null => serializer.serialize_none(),
+            Value::Undefined => serializer.serialize_newtype_struct(UNDEFINED_MARKER, &()),
             Value::Bool(b) => serializer.serialize_bool(*b),
             Value::Integer(i) => serializer.serialize_i64(*i),
             Value::Float(f) => serializer.serialize_f64(*f),
@@ -81,11 +159,11 @@ impl Serialize for Value {
             Value::Text(s) => serializer.serialize_str(s),
             Value::Array(a) => a.serialize(serializer),
             Value::Map(m) => m.serialize(serializer),
-            Value::Tag(_tag, _value) => {
-                // For now, serialize the inner value
-                // Full tag support would require custom CBOR encoding
-                _value.serialize(serializer)
+            Value::OrderedMap(entries) => {
+                serializer.collect_map(entries.iter().map(|(k, v)| (k, v)))
             }
+            Value::Tag(tag, value) => Tagged::new(Some(*tag), &**value).serialize(serializer),
+            Value::Simple(n) => serializer.serialize_newtype_struct(SIMPLE_MARKER, n),
         }
     }
 }
@@ -125,6 +203,9 @@ impl<'de> Deserialize<'de> for Value {
             }
 
             fn visit_u8<E>(self, value: u8) -> Result<Value, E> {
+                if SIMPLE.with(Cell::get) == Some(value) {
+                    return Ok(Value::Simple(value));
+                }
                 Ok(Value::Integer(value as i64))
             }
 
@@ -140,11 +221,18 @@ impl<'de> Deserialize<'de> for Value {
             where
                 E: de::Error,
             {
-                if value <= i64::MAX as u64 {
-                    Ok(Value::Integer(value as i64))
-                } else {
-                    Err(E::custom(format!("u64 value {} too large for i64", value)))
-                }
+                i64::try_from(value)
+                    .map(Value::Integer)
+                    .map_err(|_| E::custom(crate::Error::IntegerOverflow(value as i128)))
+            }
+
+            fn visit_i128<E>(self, value: i128) -> Result<Value, E>
+            where
+                E: de::Error,
+            {
+                i64::try_from(value)
+                    .map(Value::Integer)
+                    .map_err(|_| E::custom(crate::Error::IntegerOverflow(value)))
             }
 
             fn visit_f32<E>(self, value: f32) -> Result<Value, E> {
@@ -189,6 +277,9 @@ impl<'de> Deserialize<'de> for Value {
             }
 
             fn visit_unit<E>(self) -> Result<Value, E> {
+                if SIMPLE.with(Cell::get) == Some(UNDEFINED) {
+                    return Ok(Value::Undefined);
+                }
                 Ok(Value::Null)
             }
 
@@ -196,7 +287,8 @@ impl<'de> Deserialize<'de> for Value {
             where
                 V: de::SeqAccess<'de>,
             {
-                let mut vec = Vec::new();
+                // Cap the up-front allocation so a hostile length header can't force it
+                let mut vec = Vec::with_capacity(visitor.size_hint().unwrap_or(0).min(4096));
                 while let Some(elem) = visitor.next_element()? {
                     vec.push(elem);
                 }
@@ -207,6 +299,14 @@ impl<'de> Deserialize<'de> for Value {
             where
                 V: de::MapAccess<'de>,
             {
+                if ORDERED_MAPS.with(Cell::get) {
+                    let mut entries =
+                        Vec::with_capacity(visitor.size_hint().unwrap_or(0).min(4096));
+                    while let Some(entry) = visitor.next_entry()? {
+                        entries.push(entry);
+                    }
+                    return Ok(Value::OrderedMap(entries));
+                }
                 let mut map = BTreeMap::new();
                 while let Some((key, value)) = visitor.next_entry()? {
                     map.insert(key, value);
@@ -219,12 +319,37 @@ impl<'de> Deserialize<'de> for Value {
     }
 }
 
+/// A key found more than once in a map, reported by [`Value::duplicate_keys`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateKey<'a> {
+    /// Location of the map
+    pub path: Vec<PathSegment>,
+    /// The repeated key
+    pub key: &'a Value,
+    /// The values sent with the key, in order
+    pub values: Vec<&'a Value>,
+}
+
+// Location step for the value of the map entry with this key
+fn key_segment(key: &Value) -> PathSegment {
+    match key {
+        Value::Text(key) => PathSegment::Key(key.clone()),
+        Value::Integer(key) => PathSegment::IntKey(i128::from(*key)),
+        _ => PathSegment::OtherKey,
+    }
+}
+
 impl Value {
     /// Returns true if the value is null
     pub fn is_null(&self) -> bool {
         matches!(self, Value::Null)
     }
 
+    /// Returns true if the value is undefined
+    pub fn is_undefined(&self) -> bool {
+        matches!(self, Value::Undefined)
+    }
+
     /// Returns true if the value is a boolean
     pub fn is_bool(&self) -> bool {
         matches!(self, Value::Bool(_))
@@ -265,6 +390,277 @@ impl Value {
         matches!(self, Value::Tag(_, _))
     }
 
+    /// Remove tags at every level, keeping their content
+    ///
+    /// Decoding into a `Value` drops tags the same way, so this is what an
+    /// encoded `Value` decodes back to.
+    pub fn into_untagged(self) -> Value {
+        match self {
+            Value::Tag(_, content) => content.into_untagged(),
+            Value::Array(items) => {
+                Value::Array(items.into_iter().map(Value::into_untagged).collect())
+            }
+            Value::Map(map) => Value::Map(
+                map.into_iter()
+                    .map(|(k, v)| (k.into_untagged(), v.into_untagged()))
+                    .collect(),
+            ),
+            Value::OrderedMap(entries) => Value::OrderedMap(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.into_untagged(), v.into_untagged()))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+
+    /// Replace ordered maps with [`Value::Map`] at every level
+    ///
+    /// Entries are sorted by key, and of repeated keys the last is kept, as
+    /// when decoding without [`with_ordered_maps`](crate::Decoder::with_ordered_maps).
+    pub fn into_sorted(self) -> Value {
+        match self {
+            Value::Tag(tag, content) => Value::Tag(tag, Box::new(content.into_sorted())),
+            Value::Array(items) => {
+                Value::Array(items.into_iter().map(Value::into_sorted).collect())
+            }
+            Value::Map(map) => Value::Map(
+                map.into_iter()
+                    .map(|(k, v)| (k.into_sorted(), v.into_sorted()))
+                    .collect(),
+            ),
+            Value::OrderedMap(entries) => Value::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.into_sorted(), v.into_sorted()))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+
+    /// Find the keys sent more than once in an ordered map, at every level
+    ///
+    /// Decoding with [`with_ordered_maps`](crate::Decoder::with_ordered_maps)
+    /// keeps every entry of a map, where [`Value::Map`] would keep only the
+    /// last of repeated keys. Each repeated key is reported once, along with
+    /// the location of its map and the values it was sent with, in order.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::{Decoder, PathSegment, Value};
+    ///
+    /// // [{"alg": "ps256", "alg": "none"}]
+    /// let cbor = [
+    ///     0x81, 0xa2, 0x63, b'a', b'l', b'g', 0x65, b'p', b's', b'2', b'5', b'6', 0x63, b'a',
+    ///     b'l', b'g', 0x64, b'n', b'o', b'n', b'e',
+    /// ];
+    /// let value = Decoder::new(&cbor[..]).with_ordered_maps().decode_value().unwrap();
+    /// let duplicates = value.duplicate_keys();
+    /// assert_eq!(duplicates.len(), 1);
+    /// assert_eq!(duplicates[0].path, [PathSegment::Index(0)]);
+    /// assert_eq!(duplicates[0].key, &Value::Text("alg".into()));
+    /// assert_eq!(duplicates[0].values.len(), 2);
+    /// ```
+    pub fn duplicate_keys(&self) -> Vec<DuplicateKey<'_>> {
+        let mut found = Vec::new();
+        self.find_duplicate_keys(&mut Vec::new(), &mut found);
+        found
+    }
+
+    fn find_duplicate_keys<'a>(
+        &'a self,
+        path: &mut Vec<PathSegment>,
+        found: &mut Vec<DuplicateKey<'a>>,
+    ) {
+        match self {
+            Value::Tag(_, content) => content.find_duplicate_keys(path, found),
+            Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    path.push(PathSegment::Index(index));
+                    item.find_duplicate_keys(path, found);
+                    path.pop();
+                }
+            }
+            Value::Map(map) => {
+                for (key, item) in map {
+                    path.push(key_segment(key));
+                    item.find_duplicate_keys(path, found);
+                    path.pop();
+                }
+            }
+            Value::OrderedMap(entries) => {
+                let mut repeated: BTreeMap<&Value, Vec<&Value>> = BTreeMap::new();
+                for (key, item) in entries {
+                    repeated.entry(key).or_default().push(item);
+                }
+                for (key, values) in repeated {
+                    if values.len() > 1 {
+                        found.push(DuplicateKey {
+                            path: path.clone(),
+                            key,
+                            values,
+                        });
+                    }
+                }
+                for (key, item) in entries {
+                    path.push(key_segment(key));
+                    item.find_duplicate_keys(path, found);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Visit this value and everything inside it, allowing changes in place
+    ///
+    /// `visit` sees each value before its contents, along with its location
+    /// from this value: array elements by index, map values by key, and the
+    /// content of a tag at the same location as the tag. Map keys are not
+    /// visited, since changing one would move its entry. Contents are visited
+    /// as they are after `visit` returns, so a replaced value is walked in its
+    /// new form. Returning [`ControlFlow::Break`] ends the walk, and `walk`
+    /// returns it.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::{PathSegment, Value};
+    /// use std::ops::ControlFlow;
+    ///
+    /// let mut value: Value = c2pa_cbor::from_slice(&[
+    ///     0xa2, 0x61, 0x61, 0x42, 0x01, 0x02, 0x61, 0x62, 0x81, 0x41, 0x03,
+    /// ])
+    /// .unwrap(); // {"a": h'0102', "b": [h'03']}
+    ///
+    /// // Redact every byte string except the one at "a"
+    /// let _ = value.walk(&mut |item, path| {
+    ///     if item.is_bytes() && path != [PathSegment::Key("a".into())] {
+    ///         *item = Value::Bytes(Vec::new());
+    ///     }
+    ///     ControlFlow::Continue(())
+    /// });
+    /// assert_eq!(
+    ///     c2pa_cbor::to_vec(&value).unwrap(),
+    ///     [0xa2, 0x61, 0x61, 0x42, 0x01, 0x02, 0x61, 0x62, 0x81, 0x40]
+    /// );
+    /// ```
+    pub fn walk<F>(&mut self, visit: &mut F) -> ControlFlow<()>
+    where
+        F: FnMut(&mut Value, &[PathSegment]) -> ControlFlow<()>,
+    {
+        self.walk_at(&mut Vec::new(), visit)
+    }
+
+    fn walk_at<F>(&mut self, path: &mut Vec<PathSegment>, visit: &mut F) -> ControlFlow<()>
+    where
+        F: FnMut(&mut Value, &[PathSegment]) -> ControlFlow<()>,
+    {
+        visit(self, path)?;
+        match self {
+            Value::Array(items) => {
+                for (index, item) in items.iter_mut().enumerate() {
+                    path.push(PathSegment::Index(index));
+                    let flow = item.walk_at(path, visit);
+                    path.pop();
+                    flow?;
+                }
+            }
+            Value::Map(map) => {
+                for (key, item) in map.iter_mut() {
+                    item.walk_entry(key, path, visit)?;
+                }
+            }
+            Value::OrderedMap(entries) => {
+                for (key, item) in entries.iter_mut() {
+                    item.walk_entry(key, path, visit)?;
+                }
+            }
+            Value::Tag(_, content) => content.walk_at(path, visit)?,
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Merge `other` into this value, as a JSON Merge Patch (RFC 7396)
+    ///
+    /// If `other` is a map, each of its entries is merged into the matching
+    /// entry of this value, which becomes an empty map first if it is not
+    /// one: a null removes the entry, and anything else is merged into it
+    /// recursively, starting from null if it is missing. Any other `other`
+    /// replaces this value, arrays and tagged values included. Keys of any
+    /// type are matched, not only text.
+    ///
+    /// Either side may be an [`OrderedMap`](Value::OrderedMap). Merging into
+    /// one keeps its order: entries are changed in place, new entries are
+    /// appended, and a null removes every entry with that key.
+    ///
+    /// Since null means removal, a merge cannot set an entry to null; use
+    /// [`diff`](crate::diff) and [`Patch`](crate::Patch) for that.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::Value;
+    ///
+    /// let text = |s: &str| Value::Text(s.to_string());
+    /// let mut template = Value::Map(
+    ///     [(text("format"), text("image/jpeg")), (text("title"), text("untitled"))].into(),
+    /// );
+    /// template.merge(Value::Map(
+    ///     [(text("title"), text("a.jpg")), (text("format"), Value::Null)].into(),
+    /// ));
+    /// assert_eq!(template, Value::Map([(text("title"), text("a.jpg"))].into()));
+    /// ```
+    pub fn merge(&mut self, other: Value) {
+        let entries = match other {
+            Value::Map(entries) => entries.into_iter().collect(),
+            Value::OrderedMap(entries) => entries,
+            other => {
+                *self = other;
+                return;
+            }
+        };
+        if let Value::OrderedMap(target) = self {
+            for (key, value) in entries {
+                if value.is_null() {
+                    target.retain(|(k, _)| *k != key);
+                } else {
+                    ordered_entry(target, key).merge(value);
+                }
+            }
+            return;
+        }
+        if !self.is_map() {
+            *self = Value::Map(BTreeMap::new());
+        }
+        let Value::Map(map) = self else {
+            unreachable!()
+        };
+        for (key, value) in entries {
+            if value.is_null() {
+                map.remove(&key);
+            } else {
+                map.entry(key).or_insert(Value::Null).merge(value);
+            }
+        }
+    }
+
+    fn walk_entry<F>(
+        &mut self,
+        key: &Value,
+        path: &mut Vec<PathSegment>,
+        visit: &mut F,
+    ) -> ControlFlow<()>
+    where
+        F: FnMut(&mut Value, &[PathSegment]) -> ControlFlow<()>,
+    {
+        path.push(key_segment(key));
+        let flow = self.walk_at(path, visit);
+        path.pop();
+        flow
+    }
+
     /// Returns the value as a boolean, if it is one
     pub fn as_bool(&self) -> Option<bool> {
         match self {
@@ -321,6 +717,14 @@ impl Value {
         }
     }
 
+    /// Returns the entries of an ordered map, if this is one
+    pub fn as_ordered_map(&self) -> Option<&[(Value, Value)]> {
+        match self {
+            Value::OrderedMap(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
     /// Returns the tag number and inner value, if this is a tagged value
     pub fn as_tag(&self) -> Option<(u64, &Value)> {
         match self {
@@ -328,6 +732,104 @@ impl Value {
             _ => None,
         }
     }
+
+    /// Encode this value in the deterministic profile (RFC 8949 §4.2.1)
+    ///
+    /// This is the normal form used by [`structural_hash`](Value::structural_hash),
+    /// and is byte-for-byte what [`canonicalize`](crate::canonicalize) produces for
+    /// any encoding of the same value, tags included:
+    /// - integers, lengths and tag numbers use their shortest encoding
+    /// - floats use the shortest width that preserves their value, and every
+    ///   NaN is written as `0xf97e00`
+    /// - map entries are ordered by the bytewise lexicographic order of their
+    ///   encoded keys
+    ///
+    /// The last rule holds for keys of any type, and is independent of the
+    /// [`Ord`] order that `Value::Map` uses internally. With mixed keys it
+    /// sorts by major type first: unsigned integers, negative integers, byte
+    /// strings, text strings, arrays, maps, tags, then simple values and
+    /// floats; within a type, shorter encodings sort first. This ordering is a
+    /// stable guarantee, so hashes of the normal form can be stored.
+    ///
+    /// # Example
+    /// ```
+    /// use c2pa_cbor::Value;
+    ///
+    /// // {-1: 0, 10: 0, h'': 0}
+    /// let map = Value::Map(
+    ///     [Value::Integer(-1), Value::Integer(10), Value::Bytes(vec![])]
+    ///         .into_iter()
+    ///         .map(|k| (k, Value::Integer(0)))
+    ///         .collect(),
+    /// );
+    /// assert_eq!(
+    ///     map.to_canonical_vec(),
+    ///     [0xa3, 0x0a, 0x00, 0x20, 0x00, 0x40, 0x00]
+    /// );
+    /// ```
+    pub fn to_canonical_vec(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write_canonical(&mut out);
+        out
+    }
+
+    fn write_canonical(&self, out: &mut Vec<u8>) {
+        use crate::{
+            constants::*,
+            raw::{write_header, write_preferred_float},
+        };
+
+        match self {
+            Value::Null => out.push(0xf6),
+            Value::Undefined => out.push(0xf7),
+            Value::Bool(b) => out.push(if *b { 0xf5 } else { 0xf4 }),
+            Value::Integer(i) if *i < 0 => write_header(out, MAJOR_NEGATIVE, (-1 - *i) as u64),
+            Value::Integer(i) => write_header(out, MAJOR_UNSIGNED, *i as u64),
+            Value::Float(f) => write_preferred_float(out, *f),
+            Value::Bytes(b) => {
+                write_header(out, MAJOR_BYTES, b.len() as u64);
+                out.extend_from_slice(b);
+            }
+            Value::Text(s) => {
+                write_header(out, MAJOR_TEXT, s.len() as u64);
+                out.extend_from_slice(s.as_bytes());
+            }
+            Value::Array(items) => {
+                write_header(out, MAJOR_ARRAY, items.len() as u64);
+                for item in items {
+                    item.write_canonical(out);
+                }
+            }
+            Value::Map(map) => write_map(out, map.iter()),
+            Value::OrderedMap(entries) => write_map(out, entries.iter().map(|(k, v)| (k, v))),
+            Value::Tag(tag, content) => {
+                write_header(out, MAJOR_TAG, *tag);
+                content.write_canonical(out);
+            }
+            Value::Simple(n) => write_header(out, MAJOR_SIMPLE, *n as u64),
+        }
+
+        fn write_map<'a>(out: &mut Vec<u8>, map: impl Iterator<Item = (&'a Value, &'a Value)>) {
+            let mut entries: Vec<(Vec<u8>, &Value)> =
+                map.map(|(k, v)| (k.to_canonical_vec(), v)).collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            write_header(out, MAJOR_MAP, entries.len() as u64);
+            for (key, value) in entries {
+                out.extend_from_slice(&key);
+                value.write_canonical(out);
+            }
+        }
+    }
+
+    /// Feed the normal form of this value into a hasher
+    ///
+    /// Two values hash alike exactly when their
+    /// [`to_canonical_vec`](Value::to_canonical_vec) encodings are equal, so
+    /// the hash agrees with hashing canonical CBOR bytes directly, whatever
+    /// the types of the map keys.
+    pub fn structural_hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write(&self.to_canonical_vec());
+    }
 }
 
 // Implement Eq, PartialOrd, and Ord for Value to allow it to be used as a map key
@@ -345,6 +847,11 @@ impl Ord for Value {
             (Null, _) => Ordering::Less,
             (_, Null) => Ordering::Greater,
 
+            // Undefined sorts right after null, as 0xf7 follows 0xf6
+            (Undefined, Undefined) => Ordering::Equal,
+            (Undefined, _) => Ordering::Less,
+            (_, Undefined) => Ordering::Greater,
+
             // Bool comparison
             (Bool(a), Bool(b)) => a.cmp(b),
             (Bool(_), _) => Ordering::Less,
@@ -395,11 +902,21 @@ impl Ord for Value {
             (Map(_), _) => Ordering::Less,
             (_, Map(_)) => Ordering::Greater,
 
+            // Ordered maps compare entry by entry, in their order
+            (OrderedMap(a), OrderedMap(b)) => a.cmp(b),
+            (OrderedMap(_), _) => Ordering::Less,
+            (_, OrderedMap(_)) => Ordering::Greater,
+
             // Tag comparison
             (Tag(tag_a, val_a), Tag(tag_b, val_b)) => match tag_a.cmp(tag_b) {
                 Ordering::Equal => val_a.cmp(val_b),
                 other => other,
             },
+            (Tag(..), _) => Ordering::Less,
+            (_, Tag(..)) => Ordering::Greater,
+
+            // Simple value comparison
+            (Simple(a), Simple(b)) => a.cmp(b),
         }
     }
 }
@@ -470,14 +987,15 @@ impl Serializer for ValueSerializer {
     }
 
     fn serialize_u64(self, v: u64) -> Result<Value, crate::Error> {
-        if v <= i64::MAX as u64 {
-            Ok(Value::Integer(v as i64))
-        } else {
-            Err(crate::Error::Message(format!(
-                "u64 value {} too large for i64",
-                v
-            )))
-        }
+        i64::try_from(v)
+            .map(Value::Integer)
+            .map_err(|_| crate::Error::IntegerOverflow(v as i128))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, crate::Error> {
+        i64::try_from(v)
+            .map(Value::Integer)
+            .map_err(|_| crate::Error::IntegerOverflow(v))
     }
 
     fn serialize_f32(self, v: f32) -> Result<Value, crate::Error> {
@@ -527,10 +1045,46 @@ impl Serializer for ValueSerializer {
 
     fn serialize_newtype_struct<T: ?Sized + Serialize>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Value, crate::Error> {
-        value.serialize(self)
+        if name == UNDEFINED_MARKER {
+            return Ok(Value::Undefined);
+        }
+        if name == SIMPLE_MARKER
+            && let Value::Integer(n @ 0..=255) = value.serialize(ValueSerializer)?
+        {
+            return Ok(Value::Simple(n as u8));
+        }
+
+        // Tags from Tagged<T> are kept, so to_json can honor them
+        let tag = match name {
+            CBOR_TAG_MARKER => crate::tags::current_cbor_tag(),
+            _ => name
+                .strip_prefix("__cbor_tag_")
+                .and_then(|n| n.strip_suffix("__"))
+                .and_then(|n| n.parse().ok()),
+        };
+        if let Some(tag) = tag {
+            return Ok(Value::Tag(tag, Box::new(value.serialize(self)?)));
+        }
+
+        // Chunked strings arrive as an array of chunks; a Value has no chunk
+        // boundaries, so join them back into a single string
+        match (name, value.serialize(self)?) {
+            (CHUNKED_TEXT_MARKER, Value::Array(chunks)) => Ok(Value::Text(
+                chunks.iter().filter_map(Value::as_str).collect(),
+            )),
+            (CHUNKED_BYTES_MARKER, Value::Array(chunks)) => Ok(Value::Bytes(
+                chunks
+                    .iter()
+                    .filter_map(Value::as_bytes)
+                    .flatten()
+                    .copied()
+                    .collect(),
+            )),
+            (_, inner) => Ok(inner),
+        }
     }
 
     fn serialize_newtype_variant<T: ?Sized + Serialize>(
@@ -707,7 +1261,7 @@ impl serde::ser::SerializeStruct for SerializeMap {
         key: &'static str,
         value: &T,
     ) -> Result<(), crate::Error> {
-        serde::ser::SerializeMap::serialize_entry(self, key, value)
+        serde::ser::SerializeMap::serialize_entry(self, &FieldKey(key), value)
     }
 
     fn end(self) -> Result<Value, crate::Error> {
@@ -730,7 +1284,7 @@ impl serde::ser::SerializeStructVariant for SerializeStructVariant {
         value: &T,
     ) -> Result<(), crate::Error> {
         self.map.insert(
-            Value::Text(key.to_string()),
+            FieldKey(key).serialize(ValueSerializer)?,
             value.serialize(ValueSerializer)?,
         );
         Ok(())
@@ -756,6 +1310,215 @@ where
     crate::from_slice(&bytes)
 }
 
+/// How byte strings are written when converting to JSON (RFC 8949 §6.1)
+#[cfg(feature = "json")]
+#[derive(Clone, Copy)]
+enum BytesEncoding {
+    Base64Url,
+    Base64,
+    Base16,
+}
+
+/// Convert a `Value` to JSON following RFC 8949 §6.1
+///
+/// - integers and finite floats become numbers; NaN and infinities become `null`
+/// - byte strings become unpadded base64url strings, unless an enclosing tag
+///   21, 22 or 23 asks for base64url, padded base64 or base16 instead
+/// - bignums (tags 2 and 3) become numbers if they fit in `i64`/`u64`, and
+///   decimal strings otherwise
+/// - other tags are dropped and their content converted
+/// - map keys that are not text are stringified: integers as decimal, byte
+///   strings with the byte string encoding, anything else as its JSON text
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::{Value, value::to_json};
+///
+/// let value = Value::Array(vec![Value::Bytes(vec![0xfb, 0xff]), Value::Float(f64::NAN)]);
+/// assert_eq!(to_json(&value), serde_json::json!(["-_8", null]));
+/// ```
+#[cfg(feature = "json")]
+pub fn to_json(value: &Value) -> serde_json::Value {
+    value_to_json(value, BytesEncoding::Base64Url)
+}
+
+#[cfg(feature = "json")]
+fn value_to_json(value: &Value, encoding: BytesEncoding) -> serde_json::Value {
+    use serde_json::Value as Json;
+
+    match value {
+        // RFC 8949 §6.1 substitutes null for other simple values
+        Value::Null | Value::Undefined | Value::Simple(_) => Json::Null,
+        Value::Bool(b) => Json::Bool(*b),
+        Value::Integer(i) => Json::from(*i),
+        Value::Float(f) => serde_json::Number::from_f64(*f).map_or(Json::Null, Json::Number),
+        Value::Bytes(b) => Json::String(encode_bytes(b, encoding)),
+        Value::Text(s) => Json::String(s.clone()),
+        Value::Array(a) => Json::Array(a.iter().map(|v| value_to_json(v, encoding)).collect()),
+        Value::Map(m) => Json::Object(
+            m.iter()
+                .map(|(k, v)| (json_key(k, encoding), value_to_json(v, encoding)))
+                .collect(),
+        ),
+        Value::OrderedMap(entries) => Json::Object(
+            entries
+                .iter()
+                .map(|(k, v)| (json_key(k, encoding), value_to_json(v, encoding)))
+                .collect(),
+        ),
+        Value::Tag(tag @ (2 | 3), content) if content.is_bytes() => {
+            bignum_to_json(*tag == 3, content.as_bytes().unwrap_or_default())
+        }
+        Value::Tag(21, content) => value_to_json(content, BytesEncoding::Base64Url),
+        Value::Tag(22, content) => value_to_json(content, BytesEncoding::Base64),
+        Value::Tag(23, content) => value_to_json(content, BytesEncoding::Base16),
+        Value::Tag(_, content) => value_to_json(content, encoding),
+    }
+}
+
+#[cfg(feature = "json")]
+fn json_key(key: &Value, encoding: BytesEncoding) -> String {
+    match key {
+        Value::Text(s) => s.clone(),
+        Value::Integer(i) => i.to_string(),
+        Value::Bytes(b) => encode_bytes(b, encoding),
+        other => value_to_json(other, encoding).to_string(),
+    }
+}
+
+#[cfg(feature = "json")]
+fn encode_bytes(data: &[u8], encoding: BytesEncoding) -> String {
+    const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    const BASE64URL: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+    let (alphabet, pad) = match encoding {
+        BytesEncoding::Base16 => {
+            return data.iter().map(|b| format!("{:02x}", b)).collect();
+        }
+        BytesEncoding::Base64 => (BASE64, true),
+        BytesEncoding::Base64Url => (BASE64URL, false),
+    };
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, &b)| acc | ((b as u32) << (16 - 8 * i)));
+        let chars = chunk.len() + 1;
+        for i in 0..4 {
+            if i < chars {
+                out.push(alphabet[(n >> (18 - 6 * i)) as usize & 0x3f] as char);
+            } else if pad {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Convert a bignum magnitude to a JSON number if it fits, or a decimal string
+#[cfg(feature = "json")]
+fn bignum_to_json(negative: bool, magnitude: &[u8]) -> serde_json::Value {
+    let digits: Vec<u8> = magnitude.iter().copied().skip_while(|&b| b == 0).collect();
+    if digits.len() <= 8 {
+        let n = digits.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+        if !negative {
+            return serde_json::Value::from(n);
+        }
+        if let Ok(n) = i64::try_from(n) {
+            return serde_json::Value::from(-1 - n);
+        }
+    }
+
+    // Negative bignums encode -1 - n, so the magnitude printed is n + 1
+    let mut digits = digits;
+    if negative {
+        let mut carry = true;
+        for byte in digits.iter_mut().rev() {
+            (*byte, carry) = byte.overflowing_add(carry as u8);
+            if !carry {
+                break;
+            }
+        }
+        if carry {
+            digits.insert(0, 1);
+        }
+    }
+
+    // Repeated division by 10 over the big-endian magnitude
+    let mut decimal = Vec::new();
+    while !digits.is_empty() {
+        let mut remainder = 0u32;
+        for byte in digits.iter_mut() {
+            let acc = (remainder << 8) | *byte as u32;
+            *byte = (acc / 10) as u8;
+            remainder = acc % 10;
+        }
+        decimal.push(b'0' + remainder as u8);
+        let leading_zeros = digits.iter().take_while(|&&b| b == 0).count();
+        digits.drain(..leading_zeros);
+    }
+    if negative {
+        decimal.push(b'-');
+    }
+    decimal.reverse();
+    serde_json::Value::String(String::from_utf8(decimal).unwrap_or_default())
+}
+
+/// Convert JSON to a `Value` following RFC 8949 §6.2
+///
+/// Numbers become integers when they are integral and fit in `i64`,
+/// positive bignums (tag 2) when they only fit in `u64`, and floats
+/// otherwise. Strings always become text, since JSON carries no marker for
+/// byte strings.
+///
+/// # Example
+/// ```
+/// use c2pa_cbor::{Value, value::from_json};
+///
+/// let value = from_json(&serde_json::json!({"a": [1, 2.5]}));
+/// assert_eq!(value.as_map().unwrap().len(), 1);
+/// ```
+#[cfg(feature = "json")]
+pub fn from_json(json: &serde_json::Value) -> Value {
+    use serde_json::Value as Json;
+
+    match json {
+        Json::Null => Value::Null,
+        Json::Bool(b) => Value::Bool(*b),
+        Json::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Value::Integer(i)
+            } else if let Some(u) = n.as_u64() {
+                Value::Tag(2, Box::new(Value::Bytes(u.to_be_bytes().to_vec())))
+            } else {
+                Value::Float(n.as_f64().unwrap_or(f64::NAN))
+            }
+        }
+        Json::String(s) => Value::Text(s.clone()),
+        Json::Array(a) => Value::Array(a.iter().map(from_json).collect()),
+        Json::Object(o) => Value::Map(
+            o.iter()
+                .map(|(k, v)| (Value::Text(k.clone()), from_json(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// The value of the first entry of an ordered map with `key`, appending a
+/// null entry if there is none
+pub(crate) fn ordered_entry(entries: &mut Vec<(Value, Value)>, key: Value) -> &mut Value {
+    let index = match entries.iter().position(|(k, _)| *k == key) {
+        Some(index) => index,
+        None => {
+            entries.push((key, Value::Null));
+            entries.len() - 1
+        }
+    };
+    &mut entries[index].1
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1223,4 +1986,409 @@ mod tests {
         let decoded: ComplexEnum = from_value(value).unwrap();
         assert_eq!(decoded, data);
     }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_to_json_rfc8949_rules() {
+        use serde_json::json;
+
+        let mut map = BTreeMap::new();
+        map.insert(Value::Integer(1), Value::Text("one".to_string()));
+        map.insert(Value::Bytes(vec![0xff]), Value::Null);
+        map.insert(Value::Bool(true), Value::Float(f64::INFINITY));
+        let value = Value::Array(vec![
+            Value::Map(map),
+            Value::Bytes(b"hello".to_vec()),
+            Value::Tag(22, Box::new(Value::Bytes(b"hello".to_vec()))),
+            Value::Tag(
+                23,
+                Box::new(Value::Array(vec![Value::Bytes(vec![0xde, 0xad])])),
+            ),
+            Value::Tag(
+                21,
+                Box::new(Value::Tag(23, Box::new(Value::Bytes(vec![1])))),
+            ),
+            Value::Tag(32, Box::new(Value::Text("https://x".to_string()))),
+        ]);
+        assert_eq!(
+            to_json(&value),
+            json!([
+                {"1": "one", "_w": null, "true": null},
+                "aGVsbG8",
+                "aGVsbG8=",
+                ["dead"],
+                "01",
+                "https://x"
+            ])
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_to_json_bignums() {
+        use serde_json::json;
+
+        let big =
+            |tag, bytes: &[u8]| to_json(&Value::Tag(tag, Box::new(Value::Bytes(bytes.to_vec()))));
+        assert_eq!(big(2, &[0x01, 0x00]), json!(256));
+        assert_eq!(big(2, &[0xff; 8]), json!(u64::MAX));
+        assert_eq!(big(3, &[0x00]), json!(-1));
+        assert_eq!(
+            big(3, &[0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]),
+            json!(i64::MIN)
+        );
+        // 2^64 and -2^64 - 1
+        assert_eq!(
+            big(2, &[1, 0, 0, 0, 0, 0, 0, 0, 0]),
+            json!("18446744073709551616")
+        );
+        assert_eq!(big(3, &[0xff; 8]), json!("-18446744073709551616"));
+        assert_eq!(
+            big(3, &[1, 0, 0, 0, 0, 0, 0, 0, 0]),
+            json!("-18446744073709551617")
+        );
+    }
+
+    #[test]
+    fn test_canonical_vec_matches_canonicalize() {
+        let mut inner = BTreeMap::new();
+        inner.insert(Value::Text("b".to_string()), Value::Float(f64::NAN));
+        inner.insert(Value::Text("a".to_string()), Value::Float(1.5));
+        let mut map = BTreeMap::new();
+        map.insert(Value::Integer(-24), Value::Null);
+        map.insert(Value::Integer(1000), Value::Bool(true));
+        map.insert(Value::Bytes(vec![1, 2]), Value::Map(inner));
+        map.insert(
+            Value::Array(vec![Value::Integer(0)]),
+            Value::Tag(32, Box::new(Value::Text("x".to_string()))),
+        );
+        map.insert(Value::Text("zz".to_string()), Value::Float(0.1));
+        map.insert(Value::Bool(false), Value::Integer(i64::MIN));
+        let value = Value::Map(map);
+
+        let canonical = value.to_canonical_vec();
+        let mut encoded = Vec::new();
+        crate::Encoder::new(&mut encoded)
+            .write_value_streaming(&value)
+            .unwrap();
+        assert_eq!(crate::canonicalize(&encoded).unwrap(), canonical);
+        assert!(crate::is_canonical(&canonical).unwrap());
+
+        // Keys by major type: 1000, -24, h'0102', "zz", [0], false
+        let keys: Vec<u8> = crate::scan(&canonical)
+            .map(|item| item.unwrap())
+            .filter(|item| item.depth == 1)
+            .step_by(2)
+            .map(|item| item.major)
+            .collect();
+        assert_eq!(keys, [0, 1, 2, 3, 4, 7]);
+    }
+
+    #[test]
+    fn test_structural_hash_uses_normal_form() {
+        use std::hash::{DefaultHasher, Hasher};
+
+        fn hash(value: &Value) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.structural_hash(&mut hasher);
+            hasher.finish()
+        }
+        fn hash_bytes(bytes: &[u8]) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            hasher.write(bytes);
+            hasher.finish()
+        }
+
+        let mut map = BTreeMap::new();
+        map.insert(Value::Integer(-1), Value::Text("neg".to_string()));
+        map.insert(Value::Integer(7), Value::Text("pos".to_string()));
+        let value = Value::Map(map);
+
+        // A non-canonical encoding of the same map hashes the same once normalized
+        let loose = [
+            0xbf, 0x20, 0x63, b'n', b'e', b'g', 0x18, 0x07, 0x63, b'p', b'o', b's', 0xff,
+        ];
+        assert_eq!(
+            hash(&value),
+            hash_bytes(&crate::canonicalize(&loose).unwrap())
+        );
+
+        // Tags and float/integer distinctions are part of the normal form
+        assert_ne!(hash(&Value::Integer(1)), hash(&Value::Float(1.0)));
+        assert_ne!(
+            hash(&Value::Text("x".to_string())),
+            hash(&Value::Tag(32, Box::new(Value::Text("x".to_string()))))
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_from_json_round_trip() {
+        use serde_json::json;
+
+        let json = json!({"name": "photo.jpg", "sizes": [1, -2, 2.5], "big": u64::MAX, "ok": true, "none": null});
+        let value = from_json(&json);
+        let map = value.as_map().unwrap();
+        assert_eq!(
+            map[&Value::Text("big".to_string())],
+            Value::Tag(2, Box::new(Value::Bytes(vec![0xff; 8])))
+        );
+        assert_eq!(
+            map[&Value::Text("sizes".to_string())],
+            Value::Array(vec![
+                Value::Integer(1),
+                Value::Integer(-2),
+                Value::Float(2.5)
+            ])
+        );
+        assert_eq!(to_json(&value), json);
+    }
+
+    #[test]
+    fn test_walk_paths_and_mutation() {
+        let text = |s: &str| Value::Text(s.to_string());
+        let mut value = Value::Map(BTreeMap::from([
+            (text("uri"), Value::Tag(32, Box::new(text("http://a")))),
+            (
+                Value::Integer(-1),
+                Value::Array(vec![Value::Bytes(vec![1]), Value::Null]),
+            ),
+        ]));
+
+        let mut seen = Vec::new();
+        let flow = value.walk(&mut |item, path| {
+            seen.push(path.to_vec());
+            match item {
+                Value::Tag(32, _) => *item = text("https://a"),
+                Value::Bytes(bytes) => bytes.clear(),
+                _ => {}
+            }
+            ControlFlow::Continue(())
+        });
+        assert_eq!(flow, ControlFlow::Continue(()));
+        assert_eq!(
+            seen,
+            [
+                vec![],
+                vec![PathSegment::IntKey(-1)],
+                vec![PathSegment::IntKey(-1), PathSegment::Index(0)],
+                vec![PathSegment::IntKey(-1), PathSegment::Index(1)],
+                vec![PathSegment::Key("uri".into())],
+            ]
+        );
+        assert_eq!(
+            value,
+            Value::Map(BTreeMap::from([
+                (text("uri"), text("https://a")),
+                (
+                    Value::Integer(-1),
+                    Value::Array(vec![Value::Bytes(vec![]), Value::Null]),
+                ),
+            ]))
+        );
+
+        // Breaking stops the walk
+        let mut count = 0;
+        let flow = value.walk(&mut |item, _| {
+            count += 1;
+            if item.is_array() {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert_eq!(flow, ControlFlow::Break(()));
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_merge() {
+        let text = |s: &str| Value::Text(s.to_string());
+        let map = |entries: Vec<(Value, Value)>| Value::Map(entries.into_iter().collect());
+
+        // The examples of RFC 7396 §3
+        let mut target = map(vec![
+            (text("title"), text("Goodbye!")),
+            (
+                text("author"),
+                map(vec![
+                    (text("givenName"), text("John")),
+                    (text("familyName"), text("Doe")),
+                ]),
+            ),
+            (
+                text("tags"),
+                Value::Array(vec![text("example"), text("sample")]),
+            ),
+            (text("content"), text("This will be unchanged")),
+        ]);
+        target.merge(map(vec![
+            (text("title"), text("Hello!")),
+            (text("phoneNumber"), text("+01-123-456-7890")),
+            (text("author"), map(vec![(text("familyName"), Value::Null)])),
+            (text("tags"), Value::Array(vec![text("example")])),
+        ]));
+        assert_eq!(
+            target,
+            map(vec![
+                (text("title"), text("Hello!")),
+                (text("author"), map(vec![(text("givenName"), text("John"))]),),
+                (text("tags"), Value::Array(vec![text("example")])),
+                (text("content"), text("This will be unchanged")),
+                (text("phoneNumber"), text("+01-123-456-7890")),
+            ])
+        );
+
+        // Nulls inside new entries are dropped, and integer keys merge too
+        let mut target = Value::Array(vec![]);
+        target.merge(map(vec![(
+            Value::Integer(1),
+            map(vec![
+                (text("a"), Value::Null),
+                (text("b"), Value::Bool(true)),
+            ]),
+        )]));
+        assert_eq!(
+            target,
+            map(vec![(
+                Value::Integer(1),
+                map(vec![(text("b"), Value::Bool(true))])
+            )])
+        );
+
+        target.merge(Value::Integer(2));
+        assert_eq!(target, Value::Integer(2));
+
+        // An ordered target keeps its order, and an ordered map merges like a map
+        let mut target = Value::OrderedMap(vec![
+            (text("b"), Value::Integer(2)),
+            (text("a"), Value::Integer(1)),
+            (text("b"), Value::Integer(3)),
+        ]);
+        target.merge(map(vec![(text("c"), Value::Integer(3))]));
+        target.merge(Value::OrderedMap(vec![
+            (text("a"), map(vec![(text("x"), Value::Bool(true))])),
+            (text("b"), Value::Null),
+        ]));
+        assert_eq!(
+            target,
+            Value::OrderedMap(vec![
+                (text("a"), map(vec![(text("x"), Value::Bool(true))])),
+                (text("c"), Value::Integer(3)),
+            ])
+        );
+
+        let mut target = Value::Null;
+        target.merge(Value::OrderedMap(vec![
+            (text("a"), Value::Integer(1)),
+            (text("b"), Value::Null),
+        ]));
+        assert_eq!(target, map(vec![(text("a"), Value::Integer(1))]));
+    }
+
+    #[test]
+    fn test_serialize_tag() {
+        let value = Value::Tag(1000, Box::new(Value::Integer(1)));
+        let cbor = crate::to_vec(&value).unwrap();
+        assert_eq!(cbor, [0xd9, 0x03, 0xe8, 0x01]);
+        assert_eq!(
+            crate::from_slice::<Value>(&cbor).unwrap(),
+            Value::Integer(1)
+        );
+        assert_eq!(
+            crate::from_slice::<crate::tags::Tagged<Value>>(&cbor).unwrap(),
+            crate::tags::Tagged::new(Some(1000), Value::Integer(1))
+        );
+
+        let nested = Value::Tag(
+            1,
+            Box::new(Value::Tag(32, Box::new(Value::Text("a".into())))),
+        );
+        assert_eq!(
+            crate::to_vec(&nested).unwrap(),
+            [0xc1, 0xd8, 0x20, 0x61, b'a']
+        );
+    }
+
+    #[test]
+    fn test_ordered_maps() {
+        use crate::{Decoder, Encoder};
+
+        // {"b": {2: 0, 1: 0}, "a": 1, "a": 2}
+        let cbor = [
+            0xa3, 0x61, b'b', 0xa2, 0x02, 0x00, 0x01, 0x00, 0x61, b'a', 0x01, 0x61, b'a', 0x02,
+        ];
+        let text = |s: &str| Value::Text(s.to_string());
+        let expected = Value::OrderedMap(vec![
+            (
+                text("b"),
+                Value::OrderedMap(vec![
+                    (Value::Integer(2), Value::Integer(0)),
+                    (Value::Integer(1), Value::Integer(0)),
+                ]),
+            ),
+            (text("a"), Value::Integer(1)),
+            (text("a"), Value::Integer(2)),
+        ]);
+
+        let value: Value = Decoder::new(&cbor[..])
+            .with_ordered_maps()
+            .decode()
+            .unwrap();
+        assert_eq!(value, expected);
+        let value = Decoder::new(&cbor[..])
+            .with_ordered_maps()
+            .decode_value()
+            .unwrap();
+        assert_eq!(value, expected);
+
+        // Written back exactly as read, in either encoder path
+        assert_eq!(to_vec(&value).unwrap(), cbor);
+        let mut encoder = Encoder::new(Vec::new());
+        encoder.write_value_streaming(&value).unwrap();
+        assert_eq!(encoder.into_inner(), cbor);
+
+        // Without the option, and once sorted, maps are ordinary maps
+        let sorted: Value = from_slice(&cbor).unwrap();
+        assert!(sorted.is_map());
+        assert_eq!(value.clone().into_sorted(), sorted);
+        assert_eq!(Decoder::new(&cbor[..]).decode_value().unwrap(), sorted);
+        assert_ne!(value, sorted);
+        assert_eq!(value.to_canonical_vec().len(), cbor.len());
+    }
+
+    #[test]
+    fn test_duplicate_keys() {
+        use crate::Decoder;
+
+        // {1: {"a": 1, "a": 2, "a": 3, "b": 0}, 2: [{0: 0, 0: 1}]}
+        let cbor = [
+            0xa2, 0x01, 0xa4, 0x61, b'a', 0x01, 0x61, b'a', 0x02, 0x61, b'a', 0x03, 0x61, b'b',
+            0x00, 0x02, 0x81, 0xa2, 0x00, 0x00, 0x00, 0x01,
+        ];
+        let value: Value = Decoder::new(&cbor[..])
+            .with_ordered_maps()
+            .decode()
+            .unwrap();
+        let duplicates = value.duplicate_keys();
+        assert_eq!(
+            duplicates,
+            [
+                DuplicateKey {
+                    path: vec![PathSegment::IntKey(1)],
+                    key: &Value::Text("a".to_string()),
+                    values: vec![&Value::Integer(1), &Value::Integer(2), &Value::Integer(3)],
+                },
+                DuplicateKey {
+                    path: vec![PathSegment::IntKey(2), PathSegment::Index(0)],
+                    key: &Value::Integer(0),
+                    values: vec![&Value::Integer(0), &Value::Integer(1)],
+                },
+            ]
+        );
+
+        // Sorted maps have already dropped all but the last
+        let sorted: Value = from_slice(&cbor).unwrap();
+        assert!(sorted.duplicate_keys().is_empty());
+    }
 }
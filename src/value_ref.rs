@@ -0,0 +1,369 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! A document model that borrows strings from the input
+//!
+//! [`from_slice_ref`] reads any CBOR item into a [`ValueRef`] whose byte and
+//! text strings point into the input buffer, so inspecting a large manifest
+//! made mostly of byte strings does not copy them. Only indefinite-length
+//! strings, whose chunks have to be joined, are owned. Unlike decoding into
+//! [`Value`], tags are kept and maps keep every entry in wire order.
+//! [`ValueRef::into_owned`] converts to a [`Value`] when one is needed.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::{ValueRef, from_slice_ref};
+//! use std::borrow::Cow;
+//!
+//! // {"data": h'00010203'}
+//! let cbor = [0xa1, 0x64, b'd', b'a', b't', b'a', 0x44, 0x00, 0x01, 0x02, 0x03];
+//! let value = from_slice_ref(&cbor).unwrap();
+//! let (key, data) = &value.as_map().unwrap()[0];
+//! assert_eq!(key.as_str(), Some("data"));
+//! assert!(matches!(data, ValueRef::Bytes(Cow::Borrowed(_))));
+//! assert_eq!(data.as_bytes(), Some(&cbor[7..]));
+//! ```
+
+use std::borrow::Cow;
+
+use crate::{
+    Error, Result, Value,
+    constants::*,
+    raw::{SliceReader, check_depth, float_from_arg, u64_to_usize},
+};
+
+/// A CBOR item with its strings borrowed from the input, made by
+/// [`from_slice_ref`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueRef<'a> {
+    /// Null value
+    Null,
+    /// Undefined value (simple value 23)
+    Undefined,
+    /// Boolean value
+    Bool(bool),
+    /// Integer value (signed 64-bit)
+    Integer(i64),
+    /// Floating point value
+    Float(f64),
+    /// Byte string, owned only if it had indefinite length
+    Bytes(Cow<'a, [u8]>),
+    /// Text string, owned only if it had indefinite length
+    Text(Cow<'a, str>),
+    /// Array of values
+    Array(Vec<ValueRef<'a>>),
+    /// Map entries in wire order, repeated keys included
+    Map(Vec<(ValueRef<'a>, ValueRef<'a>)>),
+    /// Tagged value (tag number, boxed content)
+    Tag(u64, Box<ValueRef<'a>>),
+    /// Unassigned simple value (0–19 or 32–255)
+    Simple(u8),
+}
+
+impl<'a> ValueRef<'a> {
+    /// Returns the value as an integer, if it is one
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            ValueRef::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a byte slice, if it is a byte string
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            ValueRef::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a string slice, if it is a text string
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ValueRef::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as an array, if it is one
+    pub fn as_array(&self) -> Option<&[ValueRef<'a>]> {
+        match self {
+            ValueRef::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries of a map, if this is one
+    pub fn as_map(&self) -> Option<&[(ValueRef<'a>, ValueRef<'a>)]> {
+        match self {
+            ValueRef::Map(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Returns the tag number and inner value, if this is a tagged value
+    pub fn as_tag(&self) -> Option<(u64, &ValueRef<'a>)> {
+        match self {
+            ValueRef::Tag(tag, value) => Some((*tag, value)),
+            _ => None,
+        }
+    }
+
+    /// Copy into an owned [`Value`]
+    ///
+    /// Tags become [`Value::Tag`], and maps become [`Value::Map`], keeping
+    /// the last of repeated keys.
+    pub fn into_owned(self) -> Value {
+        match self {
+            ValueRef::Null => Value::Null,
+            ValueRef::Undefined => Value::Undefined,
+            ValueRef::Bool(b) => Value::Bool(b),
+            ValueRef::Integer(i) => Value::Integer(i),
+            ValueRef::Float(f) => Value::Float(f),
+            ValueRef::Bytes(b) => Value::Bytes(b.into_owned()),
+            ValueRef::Text(s) => Value::Text(s.into_owned()),
+            ValueRef::Array(a) => Value::Array(a.into_iter().map(ValueRef::into_owned).collect()),
+            ValueRef::Map(m) => Value::Map(
+                m.into_iter()
+                    .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                    .collect(),
+            ),
+            ValueRef::Tag(tag, content) => Value::Tag(tag, Box::new(content.into_owned())),
+            ValueRef::Simple(n) => Value::Simple(n),
+        }
+    }
+}
+
+impl From<ValueRef<'_>> for Value {
+    fn from(value: ValueRef<'_>) -> Self {
+        value.into_owned()
+    }
+}
+
+/// Read a single CBOR item from a slice, borrowing its strings
+///
+/// The whole slice must be one item. Nesting is limited to
+/// [`DEFAULT_MAX_DEPTH`] levels, and integers
+/// outside the `i64` range are an [`Error::IntegerOverflow`], as when
+/// decoding into [`Value`].
+pub fn from_slice_ref(bytes: &[u8]) -> Result<ValueRef<'_>> {
+    if bytes.is_empty() {
+        return Err(Error::Syntax("empty input".to_string()));
+    }
+    let mut reader = SliceReader::new(bytes);
+    let value = read_item(&mut reader, 0)?;
+    if reader.remaining() > 0 {
+        return Err(Error::Syntax(format!(
+            "unexpected trailing data: {} bytes remaining",
+            reader.remaining()
+        )));
+    }
+    Ok(value)
+}
+
+fn read_item<'a>(reader: &mut SliceReader<'a>, depth: usize) -> Result<ValueRef<'a>> {
+    let (major, info, arg) = reader.read_header()?;
+    Ok(match major {
+        MAJOR_UNSIGNED | MAJOR_NEGATIVE => {
+            let val =
+                arg.ok_or_else(|| Error::Syntax("Integer cannot be indefinite".to_string()))?;
+            let val = if major == MAJOR_UNSIGNED {
+                val as i128
+            } else {
+                -1 - val as i128
+            };
+            ValueRef::Integer(i64::try_from(val).map_err(|_| Error::IntegerOverflow(val))?)
+        }
+        MAJOR_BYTES => ValueRef::Bytes(read_string(reader, major, arg)?),
+        MAJOR_TEXT => ValueRef::Text(match read_string(reader, major, arg)? {
+            Cow::Borrowed(bytes) => {
+                Cow::Borrowed(std::str::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)?)
+            }
+            // Each chunk was checked, so this only fails on a bug
+            Cow::Owned(bytes) => {
+                Cow::Owned(String::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)?)
+            }
+        }),
+        MAJOR_ARRAY => {
+            check_depth(depth)?;
+            let mut items = Vec::new();
+            match arg {
+                Some(len) => {
+                    // Every item takes at least a byte, which bounds the allocation
+                    items.reserve(u64_to_usize(len)?.min(reader.remaining()));
+                    for _ in 0..len {
+                        items.push(read_item(reader, depth + 1)?);
+                    }
+                }
+                None => {
+                    while reader.peek_u8()? != BREAK {
+                        items.push(read_item(reader, depth + 1)?);
+                    }
+                    reader.read_u8()?;
+                }
+            }
+            ValueRef::Array(items)
+        }
+        MAJOR_MAP => {
+            check_depth(depth)?;
+            let mut entries = Vec::new();
+            match arg {
+                Some(len) => {
+                    entries.reserve(u64_to_usize(len)?.min(reader.remaining() / 2));
+                    for _ in 0..len {
+                        let key = read_item(reader, depth + 1)?;
+                        entries.push((key, read_item(reader, depth + 1)?));
+                    }
+                }
+                None => {
+                    while reader.peek_u8()? != BREAK {
+                        let key = read_item(reader, depth + 1)?;
+                        entries.push((key, read_item(reader, depth + 1)?));
+                    }
+                    reader.read_u8()?;
+                }
+            }
+            ValueRef::Map(entries)
+        }
+        MAJOR_TAG => {
+            check_depth(depth)?;
+            let tag = arg.ok_or_else(|| Error::Syntax("Tag cannot be indefinite".to_string()))?;
+            ValueRef::Tag(tag, Box::new(read_item(reader, depth + 1)?))
+        }
+        _ => match (info, arg) {
+            (FALSE, _) => ValueRef::Bool(false),
+            (TRUE, _) => ValueRef::Bool(true),
+            (NULL, _) => ValueRef::Null,
+            (UNDEFINED, _) => ValueRef::Undefined,
+            (0..FALSE, _) => ValueRef::Simple(info),
+            (SIMPLE_VALUE, Some(val)) => ValueRef::Simple(val as u8),
+            (FLOAT16 | FLOAT32 | FLOAT64, Some(bits)) => {
+                ValueRef::Float(float_from_arg(info, bits))
+            }
+            (INDEFINITE, _) => return Err(Error::Syntax("Unexpected break".to_string())),
+            _ => return Err(Error::Syntax("Invalid simple value".to_string())),
+        },
+    })
+}
+
+/// Read the content of a byte or text string, borrowing it unless its
+/// indefinite-length chunks have to be joined
+pub(crate) fn read_string<'a>(
+    reader: &mut SliceReader<'a>,
+    major: u8,
+    arg: Option<u64>,
+) -> Result<Cow<'a, [u8]>> {
+    if let Some(len) = arg {
+        return Ok(Cow::Borrowed(reader.read_slice(u64_to_usize(len)?)?));
+    }
+    let mut content = Vec::new();
+    while reader.peek_u8()? != BREAK {
+        let (chunk_major, _, chunk_len) = reader.read_header()?;
+        if chunk_major != major {
+            return Err(Error::Syntax(
+                "Indefinite string chunks must match the string type".to_string(),
+            ));
+        }
+        let chunk_len = chunk_len.ok_or_else(|| {
+            Error::Syntax("Indefinite string chunks cannot be indefinite".to_string())
+        })?;
+        let chunk = reader.read_slice(u64_to_usize(chunk_len)?)?;
+        if major == MAJOR_TEXT {
+            std::str::from_utf8(chunk).map_err(|_| Error::InvalidUtf8)?;
+        }
+        content.extend_from_slice(chunk);
+    }
+    reader.read_u8()?;
+    Ok(Cow::Owned(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{from_slice, to_vec};
+
+    #[test]
+    fn test_strings_are_borrowed() {
+        // [h'0102', "ab", (_ h'03', h'04'), (_ "c", "d")]
+        let cbor = [
+            0x84, 0x42, 0x01, 0x02, 0x62, b'a', b'b', 0x5f, 0x41, 0x03, 0x41, 0x04, 0xff, 0x7f,
+            0x61, b'c', 0x61, b'd', 0xff,
+        ];
+        let value = from_slice_ref(&cbor).unwrap();
+        let items = value.as_array().unwrap();
+        assert!(
+            matches!(&items[0], ValueRef::Bytes(Cow::Borrowed(b)) if b.as_ptr() == cbor[2..].as_ptr())
+        );
+        assert!(matches!(&items[1], ValueRef::Text(Cow::Borrowed("ab"))));
+        assert_eq!(items[2], ValueRef::Bytes(Cow::Owned(vec![3, 4])));
+        assert_eq!(items[3], ValueRef::Text(Cow::Owned("cd".to_string())));
+    }
+
+    #[test]
+    fn test_into_owned() {
+        let value = Value::Map(
+            [
+                (Value::Integer(-5), Value::Bytes(vec![0; 100])),
+                (
+                    Value::Text("list".to_string()),
+                    Value::Array(vec![
+                        Value::Float(1.5),
+                        Value::Null,
+                        Value::Undefined,
+                        Value::Bool(true),
+                        Value::Simple(99),
+                        Value::Integer(i64::MIN),
+                    ]),
+                ),
+            ]
+            .into(),
+        );
+        let cbor = to_vec(&value).unwrap();
+        assert_eq!(from_slice_ref(&cbor).unwrap().into_owned(), value);
+        assert_eq!(from_slice::<Value>(&cbor).unwrap(), value);
+
+        // Tags and repeated keys are kept until the conversion
+        let cbor = [0xd8, 0x20, 0xa2, 0x01, 0x02, 0x01, 0x03]; // 32({1: 2, 1: 3})
+        let value = from_slice_ref(&cbor).unwrap();
+        let (tag, map) = value.as_tag().unwrap();
+        assert_eq!(tag, 32);
+        assert_eq!(map.as_map().unwrap().len(), 2);
+        assert_eq!(
+            Value::from(value),
+            Value::Tag(
+                32,
+                Box::new(Value::Map([(Value::Integer(1), Value::Integer(3))].into()))
+            )
+        );
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        assert!(from_slice_ref(&[]).is_err());
+        assert!(from_slice_ref(&[0x01, 0x02]).is_err());
+        assert!(from_slice_ref(&[0x62, 0xff, 0xfe]).is_err());
+        assert!(from_slice_ref(&[0x5f, 0x61, b'a', 0xff]).is_err());
+        assert!(from_slice_ref(&[0xff]).is_err());
+        assert!(matches!(
+            from_slice_ref(&[0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]),
+            Err(Error::IntegerOverflow(_))
+        ));
+
+        // A huge length claim is bounded by the input
+        assert!(from_slice_ref(&[0x9b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]).is_err());
+
+        let mut deep = vec![0x81; DEFAULT_MAX_DEPTH + 1];
+        deep.push(0x00);
+        assert!(from_slice_ref(&deep).is_err());
+    }
+}
@@ -0,0 +1,434 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Read-only access to canonical CBOR without deserializing
+//!
+//! Enabled with the `view` feature. [`CborView::new`] checks once that a
+//! buffer holds a single item in the deterministic profile (see
+//! [`check_canonical`](crate::check_canonical)); after that, typed getters
+//! read fields straight from the buffer. Strings are borrowed, and nothing is
+//! allocated except for the encoded form of a map key being looked up.
+//!
+//! Map lookups compare encoded keys and stop as soon as they pass the
+//! position the key would have in canonical order, so they only walk the
+//! entries before the requested one.
+//!
+//! # Example
+//! ```
+//! use c2pa_cbor::view::CborView;
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Hash<'a> {
+//!     alg: &'a str,
+//!     #[serde(with = "serde_bytes")]
+//!     hash: &'a [u8],
+//! }
+//!
+//! let cbor = c2pa_cbor::to_vec(&Hash { alg: "sha256", hash: &[0xab, 0xcd] }).unwrap();
+//! let cbor = c2pa_cbor::canonicalize(&cbor).unwrap();
+//!
+//! let view = CborView::new(&cbor).unwrap();
+//! assert_eq!(view.map().unwrap().get_text("alg").unwrap(), "sha256");
+//! assert_eq!(view.map().unwrap().get_bytes("hash").unwrap(), [0xab, 0xcd]);
+//! ```
+
+use crate::{
+    Error, Result,
+    constants::*,
+    raw::{SliceReader, float_from_arg, item_end, u64_to_usize, write_header},
+};
+
+/// A single encoded item inside a validated canonical buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CborView<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> CborView<'a> {
+    /// View a buffer holding a single item in the deterministic profile
+    ///
+    /// Returns an error if the buffer is malformed, has trailing data, or is
+    /// not canonically encoded.
+    pub fn new(bytes: &'a [u8]) -> Result<Self> {
+        if let Some(violation) = crate::check_canonical(bytes)? {
+            return Err(Error::Syntax(format!("Not canonical CBOR: {}", violation)));
+        }
+        Ok(CborView { bytes })
+    }
+
+    /// The encoded bytes of this item
+    pub fn as_raw(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Major type (0-7) of this item
+    pub fn major(&self) -> u8 {
+        self.bytes[0] >> 5
+    }
+
+    fn header(&self) -> Result<(u8, u8, Option<u64>, SliceReader<'a>)> {
+        let mut reader = SliceReader::new(self.bytes);
+        let (major, info, arg) = reader.read_header()?;
+        Ok((major, info, arg, reader))
+    }
+
+    fn expect(&self, expected: u8, what: &str) -> Result<(u64, SliceReader<'a>)> {
+        match self.header()? {
+            (major, _, Some(arg), reader) if major == expected => Ok((arg, reader)),
+            _ => Err(Error::Syntax(format!("Expected {}", what))),
+        }
+    }
+
+    /// Tag number of this item, if it is tagged
+    pub fn tag(&self) -> Option<u64> {
+        match self.header() {
+            Ok((MAJOR_TAG, _, arg, _)) => arg,
+            _ => None,
+        }
+    }
+
+    /// The item inside any tags
+    pub fn untagged(&self) -> Result<CborView<'a>> {
+        let mut view = *self;
+        while view.major() == MAJOR_TAG {
+            let (_, _, _, reader) = view.header()?;
+            view = CborView {
+                bytes: &view.bytes[reader.position()..],
+            };
+        }
+        Ok(view)
+    }
+
+    /// Read an integer that fits in `i64`
+    pub fn as_i64(&self) -> Result<i64> {
+        match self.header()? {
+            (MAJOR_UNSIGNED, _, Some(n), _) => i64::try_from(n).ok(),
+            (MAJOR_NEGATIVE, _, Some(n), _) => i64::try_from(n).ok().map(|n| -1 - n),
+            _ => return Err(Error::Syntax("Expected integer".to_string())),
+        }
+        .ok_or_else(|| Error::Syntax("Integer out of range".to_string()))
+    }
+
+    /// Read an unsigned integer
+    pub fn as_u64(&self) -> Result<u64> {
+        self.expect(MAJOR_UNSIGNED, "unsigned integer")
+            .map(|(n, _)| n)
+    }
+
+    /// Read a float
+    pub fn as_f64(&self) -> Result<f64> {
+        match self.header()? {
+            (MAJOR_SIMPLE, info @ (FLOAT16 | FLOAT32 | FLOAT64), Some(bits), _) => {
+                Ok(float_from_arg(info, bits))
+            }
+            _ => Err(Error::Syntax("Expected float".to_string())),
+        }
+    }
+
+    /// Read a boolean
+    pub fn as_bool(&self) -> Result<bool> {
+        match self.bytes[0] {
+            0xf4 => Ok(false),
+            0xf5 => Ok(true),
+            _ => Err(Error::Syntax("Expected boolean".to_string())),
+        }
+    }
+
+    /// Whether this item is `null`
+    pub fn is_null(&self) -> bool {
+        self.bytes[0] == 0xf6
+    }
+
+    /// Borrow the content of a byte string
+    pub fn as_bytes(&self) -> Result<&'a [u8]> {
+        let (len, mut reader) = self.expect(MAJOR_BYTES, "byte string")?;
+        reader.read_slice(u64_to_usize(len)?)
+    }
+
+    /// Borrow the content of a text string
+    pub fn as_text(&self) -> Result<&'a str> {
+        let (len, mut reader) = self.expect(MAJOR_TEXT, "text string")?;
+        let bytes = reader.read_slice(u64_to_usize(len)?)?;
+        std::str::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)
+    }
+
+    /// View this item as an array
+    pub fn array(&self) -> Result<ArrayView<'a>> {
+        let (len, reader) = self.expect(MAJOR_ARRAY, "array")?;
+        Ok(ArrayView {
+            bytes: self.bytes,
+            start: reader.position(),
+            len,
+        })
+    }
+
+    /// View this item as a map
+    pub fn map(&self) -> Result<MapView<'a>> {
+        let (len, reader) = self.expect(MAJOR_MAP, "map")?;
+        Ok(MapView {
+            bytes: self.bytes,
+            start: reader.position(),
+            len,
+        })
+    }
+}
+
+/// Iterator over consecutive items, yielding `count` of them
+struct Items<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    count: u64,
+}
+
+impl<'a> Iterator for Items<'a> {
+    type Item = Result<CborView<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.count == 0 {
+            return None;
+        }
+        self.count -= 1;
+        match item_end(self.bytes, self.pos) {
+            Ok(end) => {
+                let view = CborView {
+                    bytes: &self.bytes[self.pos..end],
+                };
+                self.pos = end;
+                Some(Ok(view))
+            }
+            Err(e) => {
+                self.count = 0;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// An array inside a validated canonical buffer
+#[derive(Debug, Clone, Copy)]
+pub struct ArrayView<'a> {
+    bytes: &'a [u8],
+    start: usize,
+    len: u64,
+}
+
+impl<'a> ArrayView<'a> {
+    /// Number of elements
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether the array has no elements
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The elements in order
+    pub fn iter(&self) -> impl Iterator<Item = Result<CborView<'a>>> + use<'a> {
+        Items {
+            bytes: self.bytes,
+            pos: self.start,
+            count: self.len,
+        }
+    }
+
+    /// The element at `index`, skipping the ones before it
+    pub fn get(&self, index: u64) -> Result<Option<CborView<'a>>> {
+        if index >= self.len {
+            return Ok(None);
+        }
+        self.iter().nth(u64_to_usize(index)?).transpose()
+    }
+}
+
+/// A map inside a validated canonical buffer
+#[derive(Debug, Clone, Copy)]
+pub struct MapView<'a> {
+    bytes: &'a [u8],
+    start: usize,
+    len: u64,
+}
+
+impl<'a> MapView<'a> {
+    /// Number of entries
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether the map has no entries
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The entries in encoded (canonical) order
+    pub fn entries(&self) -> impl Iterator<Item = Result<(CborView<'a>, CborView<'a>)>> + use<'a> {
+        let mut items = Items {
+            bytes: self.bytes,
+            pos: self.start,
+            count: self.len.saturating_mul(2),
+        };
+        std::iter::from_fn(move || {
+            let key = items.next()?;
+            let value = items.next()?;
+            Some(key.and_then(|key| value.map(|value| (key, value))))
+        })
+    }
+
+    /// The value for a key given by its encoding
+    fn get_encoded(&self, encoded_key: &[u8]) -> Result<Option<CborView<'a>>> {
+        for entry in self.entries() {
+            let (key, value) = entry?;
+            match key.as_raw().cmp(encoded_key) {
+                std::cmp::Ordering::Less => {}
+                std::cmp::Ordering::Equal => return Ok(Some(value)),
+                // Keys are sorted, so the key is not in the map
+                std::cmp::Ordering::Greater => break,
+            }
+        }
+        Ok(None)
+    }
+
+    /// The value for a text key
+    pub fn get(&self, key: &str) -> Result<Option<CborView<'a>>> {
+        let mut encoded = Vec::with_capacity(key.len() + 9);
+        write_header(&mut encoded, MAJOR_TEXT, key.len() as u64);
+        encoded.extend_from_slice(key.as_bytes());
+        self.get_encoded(&encoded)
+    }
+
+    /// The value for an integer key, as used by COSE headers
+    pub fn get_int(&self, key: i64) -> Result<Option<CborView<'a>>> {
+        let mut encoded = Vec::with_capacity(9);
+        if key < 0 {
+            write_header(&mut encoded, MAJOR_NEGATIVE, (-1 - key) as u64);
+        } else {
+            write_header(&mut encoded, MAJOR_UNSIGNED, key as u64);
+        }
+        self.get_encoded(&encoded)
+    }
+
+    /// The value for a text key, which must be present
+    pub fn require(&self, key: &str) -> Result<CborView<'a>> {
+        self.get(key)?
+            .ok_or_else(|| Error::Syntax(format!("Missing map key {:?}", key)))
+    }
+
+    /// The text string for a key
+    pub fn get_text(&self, key: &str) -> Result<&'a str> {
+        self.require(key)?.as_text()
+    }
+
+    /// The byte string for a key
+    pub fn get_bytes(&self, key: &str) -> Result<&'a [u8]> {
+        self.require(key)?.as_bytes()
+    }
+
+    /// The integer for a key
+    pub fn get_i64(&self, key: &str) -> Result<i64> {
+        self.require(key)?.as_i64()
+    }
+
+    /// The boolean for a key
+    pub fn get_bool(&self, key: &str) -> Result<bool> {
+        self.require(key)?.as_bool()
+    }
+
+    /// The array for a key
+    pub fn get_array(&self, key: &str) -> Result<ArrayView<'a>> {
+        self.require(key)?.array()
+    }
+
+    /// The map for a key
+    pub fn get_map(&self, key: &str) -> Result<MapView<'a>> {
+        self.require(key)?.map()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // {-1: 6, 1: "ES256", "alg": 32("sha256"), "list": [1, h'00', {"a": true}], "z": null}
+    fn sample() -> Vec<u8> {
+        let mut cbor = vec![0xa5, 0x01, 0x65];
+        cbor.extend_from_slice(b"ES256");
+        cbor.extend_from_slice(&[0x20, 0x06, 0x61, b'z', 0xf6, 0x63]);
+        cbor.extend_from_slice(b"alg");
+        cbor.extend_from_slice(&[0xd8, 0x20, 0x66]);
+        cbor.extend_from_slice(b"sha256");
+        cbor.extend_from_slice(&[0x64]);
+        cbor.extend_from_slice(b"list");
+        cbor.extend_from_slice(&[0x83, 0x01, 0x41, 0x00, 0xa1, 0x61, b'a', 0xf5]);
+        cbor
+    }
+
+    #[test]
+    fn test_view_getters() {
+        let cbor = sample();
+        let map = CborView::new(&cbor).unwrap().map().unwrap();
+        assert_eq!(map.len(), 5);
+
+        assert_eq!(map.get_int(1).unwrap().unwrap().as_text().unwrap(), "ES256");
+        assert_eq!(map.get_int(-1).unwrap().unwrap().as_i64().unwrap(), 6);
+        assert!(map.get_int(2).unwrap().is_none());
+        assert!(map.require("z").unwrap().is_null());
+
+        let alg = map.require("alg").unwrap();
+        assert_eq!(alg.tag(), Some(32));
+        assert_eq!(alg.untagged().unwrap().as_text().unwrap(), "sha256");
+        assert!(map.get_text("alg").is_err());
+
+        let list = map.get_array("list").unwrap();
+        assert_eq!(list.get(0).unwrap().unwrap().as_u64().unwrap(), 1);
+        assert_eq!(list.get(1).unwrap().unwrap().as_bytes().unwrap(), [0]);
+        let inner = list.get(2).unwrap().unwrap().map().unwrap();
+        assert!(inner.get_bool("a").unwrap());
+        assert!(list.get(3).unwrap().is_none());
+
+        // Missing keys and type mismatches are errors, not panics
+        assert!(map.get_text("missing").is_err());
+        assert!(map.get_map("list").is_err());
+        assert!(map.get("a").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_view_entries_and_raw() {
+        let cbor = sample();
+        let map = CborView::new(&cbor).unwrap().map().unwrap();
+        let keys: Vec<_> = map
+            .entries()
+            .map(|e| e.unwrap().0.as_raw().to_vec())
+            .collect();
+        assert_eq!(keys[0], [0x01]);
+        assert_eq!(keys[2], [0x61, b'z']);
+
+        // Raw items can be hashed or decoded on their own
+        let list = map.require("list").unwrap();
+        let decoded: (
+            u8,
+            serde_bytes::ByteBuf,
+            std::collections::BTreeMap<String, bool>,
+        ) = crate::from_slice(list.as_raw()).unwrap();
+        assert_eq!(decoded.0, 1);
+    }
+
+    #[test]
+    fn test_view_rejects_non_canonical() {
+        // Unsorted keys, indefinite length and trailing data
+        assert!(CborView::new(&[0xa2, 0x61, b'b', 0x01, 0x61, b'a', 0x02]).is_err());
+        assert!(CborView::new(&[0x9f, 0x01, 0xff]).is_err());
+        assert!(CborView::new(&[0x01, 0x02]).is_err());
+        assert!(CborView::new(&[]).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+// #[derive(CborTagged)] round trips and tag checks
+
+#![cfg(feature = "derive")]
+
+use c2pa_cbor::{CborTagged, Value};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, CborTagged)]
+#[cbor(tag = 1001)]
+struct Claim {
+    #[serde(rename = "claim_generator")]
+    generator: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    title: Option<String>,
+}
+
+#[derive(Debug, PartialEq, CborTagged)]
+#[cbor(tag = 32)]
+struct Uri(String);
+
+#[derive(Debug, PartialEq, CborTagged)]
+#[cbor(tag = 40000)]
+#[serde(rename_all = "lowercase")]
+enum Kind {
+    Created,
+    Edited { by: String },
+}
+
+#[derive(Debug, PartialEq, CborTagged)]
+#[cbor(tag = 1002)]
+struct Thumbnail {
+    #[cbor(key = 1)]
+    format: String,
+    #[cbor(key = -1, typed_array = "f32le")]
+    levels: Vec<f32>,
+    #[cbor(typed_array = "u16be")]
+    histogram: Vec<u16>,
+    label: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Manifest {
+    claim: Claim,
+    links: Vec<Uri>,
+}
+
+#[test]
+fn test_struct_is_wrapped_in_its_tag() {
+    let claim = Claim {
+        generator: "test/1.0".to_string(),
+        title: None,
+    };
+    let cbor = c2pa_cbor::to_vec(&claim).unwrap();
+    // tag(1001) {"claim_generator": "test/1.0"}
+    assert_eq!(cbor[..4], [0xd9, 0x03, 0xe9, 0xa1]);
+    assert_eq!(c2pa_cbor::from_slice::<Claim>(&cbor).unwrap(), claim);
+    assert_eq!(Claim::CBOR_TAG, 1001);
+}
+
+#[test]
+fn test_newtype_and_enum_round_trip() {
+    let uri = Uri("a:b".to_string());
+    let cbor = c2pa_cbor::to_vec(&uri).unwrap();
+    assert_eq!(cbor, [0xd8, 0x20, 0x63, b'a', b':', b'b']);
+    assert_eq!(c2pa_cbor::from_slice::<Uri>(&cbor).unwrap(), uri);
+
+    for kind in [
+        Kind::Created,
+        Kind::Edited {
+            by: "me".to_string(),
+        },
+    ] {
+        let cbor = c2pa_cbor::to_vec(&kind).unwrap();
+        assert_eq!(cbor[..3], [0xd9, 0x9c, 0x40]);
+        assert_eq!(c2pa_cbor::from_slice::<Kind>(&cbor).unwrap(), kind);
+    }
+    let cbor = c2pa_cbor::to_vec(&Kind::Created).unwrap();
+    assert_eq!(&cbor[3..], [0x67, b'c', b'r', b'e', b'a', b't', b'e', b'd']);
+}
+
+#[test]
+fn test_nested_in_derived_types() {
+    let manifest = Manifest {
+        claim: Claim {
+            generator: "test/1.0".to_string(),
+            title: Some("photo".to_string()),
+        },
+        links: vec![Uri("a:b".to_string()), Uri("c:d".to_string())],
+    };
+    let cbor = c2pa_cbor::to_vec(&manifest).unwrap();
+    assert_eq!(c2pa_cbor::from_slice::<Manifest>(&cbor).unwrap(), manifest);
+}
+
+#[test]
+fn test_integer_keys_and_typed_arrays() {
+    let thumbnail = Thumbnail {
+        format: "png".to_string(),
+        levels: vec![1.0],
+        histogram: vec![1, 2],
+        label: None,
+    };
+    let cbor = c2pa_cbor::to_vec(&thumbnail).unwrap();
+    // 1002({1: "png", -1: 85(h'0000803f'), "histogram": 65(h'00010002'), "label": null})
+    assert_eq!(cbor[..5], [0xd9, 0x03, 0xea, 0xa4, 0x01]);
+    assert_eq!(
+        &cbor[9..17],
+        [0x20, 0xd8, 0x55, 0x44, 0x00, 0x00, 0x80, 0x3f]
+    );
+    assert_eq!(&cbor[27..34], [0xd8, 0x41, 0x44, 0x00, 0x01, 0x00, 0x02]);
+    assert_eq!(
+        c2pa_cbor::from_slice::<Thumbnail>(&cbor).unwrap(),
+        thumbnail
+    );
+
+    let value = c2pa_cbor::to_value(&thumbnail).unwrap();
+    let cbor = c2pa_cbor::to_vec(&value).unwrap();
+    assert_eq!(
+        c2pa_cbor::from_slice::<Thumbnail>(&cbor).unwrap(),
+        thumbnail
+    );
+}
+
+#[test]
+fn test_wrong_or_missing_tag_is_rejected() {
+    let untagged = c2pa_cbor::to_vec(&Value::Text("a:b".to_string())).unwrap();
+    let err = c2pa_cbor::from_slice::<Uri>(&untagged).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("expected tag 32, found an untagged value")
+    );
+
+    let mut other = vec![0xd8, 0x21];
+    other.extend_from_slice(&untagged);
+    let err = c2pa_cbor::from_slice::<Uri>(&other).unwrap_err();
+    assert!(err.to_string().contains("expected tag 32, found tag 33"));
+}
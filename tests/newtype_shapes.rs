@@ -0,0 +1,152 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+// Regression suite for single-field shapes
+//
+// Each shape is encoded with c2pa_cbor, serde_cbor and ciborium and compared
+// with a fixed fixture, then every library decodes the fixture back. The
+// legacy fixtures are what earlier releases wrote for newtype structs and
+// must keep decoding with the compatibility mode.
+
+#![cfg(feature = "differential")]
+
+use std::{collections::BTreeMap, fmt::Debug};
+
+use c2pa_cbor::{Decoder, Encoder};
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer, de::DeserializeOwned,
+    ser::SerializeTupleStruct,
+};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+struct Transparent {
+    inner: u32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Newtype(u32);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct NewtypeText(String);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct NewtypeMap(BTreeMap<String, u32>);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct NewtypeOfTuple((u32,));
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Pinned(#[serde(with = "c2pa_cbor::single_element")] u32);
+
+// A real tuple struct of arity 1; serde derives a newtype for `struct A(T,)`
+#[derive(Debug, PartialEq)]
+struct TupleStruct(u32);
+
+impl Serialize for TupleStruct {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_tuple_struct("TupleStruct", 1)?;
+        state.serialize_field(&self.0)?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for TupleStruct {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (value,) = <(u32,)>::deserialize(deserializer)?;
+        Ok(TupleStruct(value))
+    }
+}
+
+/// Check that all three libraries write `fixture` for `value` and read it back
+fn check_shape<T>(value: &T, fixture: &[u8])
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let ours = c2pa_cbor::to_vec(value).unwrap();
+    assert_eq!(ours, fixture, "c2pa_cbor output for {value:?}");
+    let serde_cbor_out = serde_cbor::to_vec(value).unwrap();
+    assert_eq!(serde_cbor_out, fixture, "serde_cbor output for {value:?}");
+    let mut ciborium_out = Vec::new();
+    ciborium::ser::into_writer(value, &mut ciborium_out).unwrap();
+    assert_eq!(ciborium_out, fixture, "ciborium output for {value:?}");
+
+    assert_eq!(&c2pa_cbor::from_slice::<T>(fixture).unwrap(), value);
+    assert_eq!(&serde_cbor::from_slice::<T>(fixture).unwrap(), value);
+    assert_eq!(&ciborium::de::from_reader::<T, _>(fixture).unwrap(), value);
+}
+
+/// Check the legacy encoder output and that the compatibility mode reads it
+fn check_legacy<T>(value: &T, fixture: &[u8])
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let mut encoder = Encoder::new(Vec::new()).with_legacy_newtypes();
+    encoder.encode(value).unwrap();
+    assert_eq!(encoder.into_inner(), fixture, "legacy output for {value:?}");
+    let mut decoder = Decoder::new(fixture).with_legacy_newtypes();
+    assert_eq!(&decoder.decode::<T>().unwrap(), value);
+}
+
+#[test]
+fn test_transparent_struct() {
+    check_shape(&Transparent { inner: 42 }, &[0x18, 0x2a]);
+    // serde(transparent) never goes through serialize_newtype_struct
+    check_legacy(&Transparent { inner: 42 }, &[0x18, 0x2a]);
+}
+
+#[test]
+fn test_plain_newtypes() {
+    check_shape(&Newtype(42), &[0x18, 0x2a]);
+    check_shape(&NewtypeText("hi".into()), &[0x62, b'h', b'i']);
+    check_shape(
+        &NewtypeMap(BTreeMap::from([("a".into(), 1)])),
+        &[0xa1, 0x61, b'a', 0x01],
+    );
+
+    check_legacy(&Newtype(42), &[0x81, 0x18, 0x2a]);
+    check_legacy(&NewtypeText("hi".into()), &[0x81, 0x62, b'h', b'i']);
+    check_legacy(
+        &NewtypeMap(BTreeMap::from([("a".into(), 1)])),
+        &[0x81, 0xa1, 0x61, b'a', 0x01],
+    );
+}
+
+#[test]
+fn test_one_element_tuples() {
+    check_shape(&TupleStruct(42), &[0x81, 0x18, 0x2a]);
+    check_shape(&(42u32,), &[0x81, 0x18, 0x2a]);
+    check_shape(&Pinned(42), &[0x81, 0x18, 0x2a]);
+    // A newtype over a tuple is as wide as the tuple
+    check_shape(&NewtypeOfTuple((42,)), &[0x81, 0x18, 0x2a]);
+
+    // Tuples are not newtypes, so the legacy option leaves them alone
+    check_legacy(&TupleStruct(42), &[0x81, 0x18, 0x2a]);
+    check_legacy(&(42u32,), &[0x81, 0x18, 0x2a]);
+    check_legacy(&Pinned(42), &[0x81, 0x81, 0x18, 0x2a]);
+    check_legacy(&NewtypeOfTuple((42,)), &[0x81, 0x81, 0x18, 0x2a]);
+}
+
+#[test]
+fn test_shapes_are_distinguished_by_default() {
+    // Without the compatibility mode a newtype never consumes an array
+    assert!(c2pa_cbor::from_slice::<Newtype>(&[0x81, 0x18, 0x2a]).is_err());
+    assert!(c2pa_cbor::from_slice::<TupleStruct>(&[0x18, 0x2a]).is_err());
+    assert!(c2pa_cbor::from_slice::<Pinned>(&[0x18, 0x2a]).is_err());
+
+    // The compatibility mode reads a wrapped newtype and still reads the standard form
+    for fixture in [&[0x18, 0x2a][..], &[0x81, 0x18, 0x2a]] {
+        let mut decoder = Decoder::new(fixture).with_legacy_newtypes();
+        assert_eq!(decoder.decode::<Newtype>().unwrap(), Newtype(42));
+    }
+}
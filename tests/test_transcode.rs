@@ -0,0 +1,108 @@
+// Copyright 2026 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+// Tests for using the decoder and encoder as serde_transcode source and sink
+
+use c2pa_cbor::{Decoder, ser::Serializer};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Label(String);
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Record {
+    label: Label,
+    missing: Option<u32>,
+    present: Option<u32>,
+    #[serde(with = "serde_bytes")]
+    hash: Vec<u8>,
+    ratio: f64,
+    items: Vec<Option<Label>>,
+}
+
+fn record() -> Record {
+    Record {
+        label: Label("c2pa.hash.data".to_string()),
+        missing: None,
+        present: Some(7),
+        hash: vec![0xde, 0xad, 0xbe, 0xef],
+        // Needs a double, so it is written the same with compact_floats
+        ratio: 0.1,
+        items: vec![None, Some(Label("x".to_string()))],
+    }
+}
+
+#[test]
+fn test_transcode_cbor_to_cbor_by_reference() {
+    let bytes = c2pa_cbor::to_vec(&record()).unwrap();
+
+    let mut from = Decoder::new(&bytes[..]);
+    let mut to = Serializer::new(Vec::new());
+    serde_transcode::transcode(&mut from, &mut to).unwrap();
+    from.end().unwrap();
+
+    // Definite lengths are carried through, so the output is identical
+    let output = to.into_inner();
+    assert_eq!(output, bytes);
+    assert_eq!(c2pa_cbor::from_slice::<Record>(&output).unwrap(), record());
+}
+
+#[test]
+fn test_transcode_cbor_to_cbor_by_value() {
+    let bytes = c2pa_cbor::to_vec(&record()).unwrap();
+
+    let mut to = Serializer::new(Vec::new());
+    serde_transcode::transcode(Decoder::new(&bytes[..]), &mut to).unwrap();
+    assert_eq!(to.into_inner(), bytes);
+}
+
+#[test]
+fn test_transcode_cbor_to_json_streaming() {
+    let bytes = c2pa_cbor::to_vec(&record()).unwrap();
+
+    // Read through a plain reader that only yields a few bytes at a time
+    struct Trickle<'a>(&'a [u8]);
+    impl std::io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = buf.len().min(self.0.len()).min(3);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    let mut from = Decoder::new(Trickle(&bytes));
+    let mut json = Vec::new();
+    serde_transcode::transcode(&mut from, &mut serde_json::Serializer::new(&mut json)).unwrap();
+    from.end().unwrap();
+
+    assert_eq!(
+        std::str::from_utf8(&json).unwrap(),
+        r#"{"label":"c2pa.hash.data","missing":null,"present":7,"hash":[222,173,190,239],"ratio":0.1,"items":[null,"x"]}"#
+    );
+}
+
+#[test]
+fn test_transcode_json_to_cbor_options_and_newtypes() {
+    let json = r#"{"label":"c2pa.hash.data","missing":null,"present":7,"hash":[222,173,190,239],"ratio":0.1,"items":[null,"x"]}"#;
+
+    let mut from = serde_json::Deserializer::from_str(json);
+    let mut to = Serializer::new(Vec::new());
+    serde_transcode::transcode(&mut from, &mut to).unwrap();
+    from.end().unwrap();
+
+    // JSON has no byte strings, so the hash arrives as an array of integers,
+    // which serde_bytes still accepts
+    let decoded: Record = c2pa_cbor::from_slice(&to.into_inner()).unwrap();
+    assert_eq!(decoded, record());
+}